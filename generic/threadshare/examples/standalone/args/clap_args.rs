@@ -1,6 +1,25 @@
 use super::super::CAT;
 use clap::Parser;
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum Src {
+    /// Standalone test source
+    Standalone,
+    /// Live ts-audiotestsrc
+    AudioTestSrc,
+}
+
+impl Src {
+    pub fn element_name(self) -> &'static str {
+        use super::super::src;
+        use Src::*;
+        match self {
+            Standalone => src::ELEMENT_NAME,
+            AudioTestSrc => "ts-audiotestsrc",
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
 pub enum Sink {
     /// Item handling in PadHandler with async Mutex
@@ -49,10 +68,22 @@ pub struct Args {
     #[clap(short, long, default_value_t = 5000)]
     pub num_buffers: i32,
 
+    /// The Src variant to use.
+    #[clap(long, value_enum, default_value_t = Src::Standalone)]
+    pub src: Src,
+
     /// The Sink variant to use.
     #[clap(long, value_enum, default_value_t = Sink::SyncMutex)]
     pub sink: Sink,
 
+    /// Delay in ms added by a ts-identity between the src and the sink (0 = no ts-identity).
+    #[clap(long, default_value_t = 0)]
+    pub identity_latency: u32,
+
+    /// Maximum random delay in ms added to the ts-identity latency.
+    #[clap(long, default_value_t = 0)]
+    pub identity_jitter: u32,
+
     /// Disables statistics logging.
     #[clap(short, long)]
     pub disable_stats_log: bool,
@@ -1,5 +1,14 @@
 use super::super::CAT;
 
+#[derive(Copy, Clone, Debug)]
+pub struct StandaloneSrc;
+
+impl StandaloneSrc {
+    pub fn element_name(self) -> &'static str {
+        super::super::src::ELEMENT_NAME
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct SyncMutexSink;
 
@@ -16,7 +25,10 @@ pub struct Args {
     pub wait: u32,
     pub push_period: u32,
     pub num_buffers: i32,
+    pub src: StandaloneSrc,
     pub sink: SyncMutexSink,
+    pub identity_latency: u32,
+    pub identity_jitter: u32,
     pub disable_stats_log: bool,
 }
 
@@ -28,7 +40,10 @@ impl Default for Args {
             wait: 20,
             push_period: 20,
             num_buffers: 5000,
+            src: StandaloneSrc,
             sink: SyncMutexSink,
+            identity_latency: 0,
+            identity_jitter: 0,
             disable_stats_log: false,
         }
     }
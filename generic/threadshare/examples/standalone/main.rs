@@ -48,6 +48,7 @@ fn main() {
 
     gst::init().unwrap();
     self::plugin_register_static().unwrap();
+    gstthreadshare::plugin_register_static().unwrap();
 
     #[cfg(debug_assertions)]
     gst::warning!(CAT, "RUNNING DEBUG BUILD");
@@ -59,15 +60,22 @@ fn main() {
     for i in 0..args.streams {
         let ctx_name = format!("standalone {}", i % args.groups);
 
-        let src = gst::ElementFactory::make(src::ELEMENT_NAME)
+        let src = gst::ElementFactory::make(args.src.element_name())
             .name(format!("src-{i}").as_str())
             .property("context", &ctx_name)
             .property("context-wait", args.wait)
-            .property("push-period", args.push_period)
             .property("num-buffers", args.num_buffers)
             .build()
             .unwrap();
 
+        if src.find_property("push-period").is_some() {
+            src.set_property("push-period", args.push_period);
+        } else {
+            // ts-audiotestsrc
+            src.set_property("is-live", true);
+            src.set_property("buffer-duration", args.push_period);
+        }
+
         let sink = gst::ElementFactory::make(args.sink.element_name())
             .name(format!("sink-{i}").as_str())
             .property("context", &ctx_name)
@@ -76,7 +84,10 @@ fn main() {
             .unwrap();
 
         if i == 0 {
-            src.set_property("main-elem", true);
+            // ts-audiotestsrc only declares this property with the `tuning` feature
+            if src.find_property("main-elem").is_some() {
+                src.set_property("main-elem", true);
+            }
             sink.set_property("main-elem", true);
 
             if !args.disable_stats_log {
@@ -97,9 +108,27 @@ fn main() {
             }
         }
 
-        let elements = &[&src, &sink];
-        pipeline.add_many(elements).unwrap();
-        gst::Element::link_many(elements).unwrap();
+        let mut elements = vec![src];
+        if args.identity_latency > 0 || args.identity_jitter > 0 {
+            // Emulate network conditions
+            let identity = gst::ElementFactory::make("ts-identity")
+                .name(format!("identity-{i}").as_str())
+                .property(
+                    "latency",
+                    u64::from(args.identity_latency) * gst::ClockTime::MSECOND.nseconds(),
+                )
+                .property(
+                    "jitter",
+                    u64::from(args.identity_jitter) * gst::ClockTime::MSECOND.nseconds(),
+                )
+                .build()
+                .unwrap();
+            elements.push(identity);
+        }
+        elements.push(sink);
+
+        pipeline.add_many(&elements).unwrap();
+        gst::Element::link_many(&elements).unwrap();
     }
 
     let l = glib::MainLoop::new(None, false);
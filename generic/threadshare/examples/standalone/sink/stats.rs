@@ -1,8 +1,7 @@
 use gst::prelude::*;
 use std::time::{Duration, Instant};
 
-#[cfg(feature = "tuning")]
-use gstthreadshare::runtime::Context;
+use gstthreadshare::runtime::{Context, ContextStats};
 
 use super::CAT;
 
@@ -35,6 +34,7 @@ pub struct Stats {
     interval_late_warn: Duration,
     interval_late_count: f32,
     interval_late_count_delta: f32,
+    context_stats_init: ContextStats,
     #[cfg(feature = "tuning")]
     parked_duration_init: Duration,
 }
@@ -86,6 +86,7 @@ impl Stats {
             gst::info!(CAT, "Ramp up complete. Stats logs in {:2?}", LOG_PERIOD);
             self.log_start_instant = Some(Instant::now());
             self.last_delta_instant = self.log_start_instant;
+            self.context_stats_init = Context::current().unwrap().stats();
 
             #[cfg(feature = "tuning")]
             {
@@ -221,6 +222,19 @@ impl Stats {
 
         gst::info!(CAT, "Global stats:");
 
+        let context_stats = Context::current()
+            .unwrap()
+            .stats()
+            .delta(&self.context_stats_init);
+        gst::info!(
+            CAT,
+            "o context: {:.1} wakeups/s, polling {:4.2?}, executing {:4.2?}, {:.1} task polls per period",
+            context_stats.wakeups_per_sec(),
+            context_stats.polling_duration,
+            context_stats.executing_duration,
+            context_stats.task_polls_per_period(),
+        );
+
         #[cfg(feature = "tuning")]
         {
             let duration = _log_start.elapsed();
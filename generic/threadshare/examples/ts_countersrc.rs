@@ -0,0 +1,347 @@
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+//! A minimal thread-sharing source built on the `runtime::src_base` helpers.
+//!
+//! `ts-countersrc` pushes `num-buffers` buffers holding a counter, one every
+//! `interval` ms, then pushes EOS. Only the items production is implemented here:
+//! the stream prelude, flush handling & state changes are handled by the helpers.
+
+use gst::glib;
+use gst::prelude::*;
+
+mod countersrc {
+    use futures::future::BoxFuture;
+    use futures::prelude::*;
+
+    use gst::glib;
+    use gst::prelude::*;
+    use gst::subclass::prelude::*;
+
+    use std::sync::LazyLock;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use gstthreadshare::runtime::prelude::*;
+    use gstthreadshare::runtime::{
+        timer, Context, PadSrc, PadSrcRef, SrcItem, SrcTaskAdapter, Task,
+    };
+
+    const DEFAULT_CONTEXT: &str = "";
+    const DEFAULT_INTERVAL: u32 = 20;
+    const DEFAULT_NUM_BUFFERS: u32 = 50;
+
+    #[derive(Debug, Clone)]
+    struct Settings {
+        context: String,
+        interval: u32,
+        num_buffers: u32,
+    }
+
+    impl Default for Settings {
+        fn default() -> Self {
+            Settings {
+                context: DEFAULT_CONTEXT.into(),
+                interval: DEFAULT_INTERVAL,
+                num_buffers: DEFAULT_NUM_BUFFERS,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct CounterSrcPadHandler;
+    impl PadSrcHandler for CounterSrcPadHandler {
+        type ElementImpl = CounterSrc;
+
+        fn src_event(self, _pad: &gst::Pad, imp: &CounterSrc, event: gst::Event) -> bool {
+            match event.view() {
+                gst::EventView::FlushStart(..) => {
+                    imp.task.flush_start().await_maybe_on_context().is_ok()
+                }
+                gst::EventView::FlushStop(..) => {
+                    imp.task.flush_stop().await_maybe_on_context().is_ok()
+                }
+                _ => false,
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct CounterSrcTask {
+        element: super::CounterSrc,
+        timer: Option<timer::Interval>,
+        interval: Duration,
+        num_buffers: u64,
+        count: u64,
+    }
+
+    impl SrcTaskImpl for CounterSrcTask {
+        fn element(&self) -> &gst::Element {
+            self.element.upcast_ref()
+        }
+
+        fn src_pad(&self) -> PadSrcRef<'_> {
+            self.element.imp().src_pad.as_ref()
+        }
+
+        fn caps(&mut self) -> Option<gst::Caps> {
+            Some(gst::Caps::builder("application/x-counter").build())
+        }
+
+        fn start(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+            self.timer = Some(timer::interval(self.interval).unwrap());
+            future::ok(()).boxed()
+        }
+
+        fn stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+            self.timer = None;
+            self.count = 0;
+            future::ok(()).boxed()
+        }
+
+        fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
+            async move {
+                if self.count == self.num_buffers {
+                    return Err(gst::FlowError::Eos);
+                }
+
+                self.timer.as_mut().unwrap().next().await;
+
+                let mut buffer = gst::Buffer::from_slice(self.count.to_be_bytes());
+                {
+                    let buffer = buffer.get_mut().unwrap();
+                    buffer.set_pts(self.element.current_running_time());
+                    buffer.set_offset(self.count);
+                }
+                self.count += 1;
+
+                Ok(SrcItem::Buffer(buffer))
+            }
+            .boxed()
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct CounterSrc {
+        src_pad: PadSrc,
+        task: Task,
+        settings: Mutex<Settings>,
+    }
+
+    impl TsSrcImpl for CounterSrc {
+        fn task(&self) -> &Task {
+            &self.task
+        }
+
+        fn prepare(&self) -> Result<(), gst::ErrorMessage> {
+            let settings = self.settings.lock().unwrap().clone();
+            let context = Context::acquire(&settings.context, Duration::ZERO).map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to acquire Context: {}", err]
+                )
+            })?;
+
+            let task_impl = CounterSrcTask {
+                element: self.obj().clone(),
+                timer: None,
+                interval: Duration::from_millis(settings.interval.into()),
+                num_buffers: settings.num_buffers.into(),
+                count: 0,
+            };
+
+            self.task
+                .prepare(SrcTaskAdapter::new(task_impl), context)
+                .block_on()?;
+
+            Ok(())
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CounterSrc {
+        const NAME: &'static str = "GstTsCounterSrc";
+        type Type = super::CounterSrc;
+        type ParentType = gst::Element;
+
+        fn with_class(klass: &Self::Class) -> Self {
+            Self {
+                src_pad: PadSrc::new(
+                    gst::Pad::from_template(&klass.pad_template("src").unwrap()),
+                    CounterSrcPadHandler,
+                ),
+                task: Task::default(),
+                settings: Default::default(),
+            }
+        }
+    }
+
+    impl ObjectImpl for CounterSrc {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+                vec![
+                    glib::ParamSpecString::builder("context")
+                        .nick("Context")
+                        .blurb("Context name to share threads with")
+                        .default_value(Some(DEFAULT_CONTEXT))
+                        .build(),
+                    glib::ParamSpecUInt::builder("interval")
+                        .nick("Interval")
+                        .blurb("Interval between two buffers in ms")
+                        .minimum(1)
+                        .default_value(DEFAULT_INTERVAL)
+                        .build(),
+                    glib::ParamSpecUInt::builder("num-buffers")
+                        .nick("Num Buffers")
+                        .blurb("Number of buffers to output before sending EOS")
+                        .default_value(DEFAULT_NUM_BUFFERS)
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let mut settings = self.settings.lock().unwrap();
+            match pspec.name() {
+                "context" => {
+                    settings.context = value
+                        .get::<Option<String>>()
+                        .unwrap()
+                        .unwrap_or_else(|| DEFAULT_CONTEXT.into());
+                }
+                "interval" => settings.interval = value.get().unwrap(),
+                "num-buffers" => settings.num_buffers = value.get().unwrap(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let settings = self.settings.lock().unwrap();
+            match pspec.name() {
+                "context" => settings.context.to_value(),
+                "interval" => settings.interval.to_value(),
+                "num-buffers" => settings.num_buffers.to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.add_pad(self.src_pad.gst_pad()).unwrap();
+            obj.set_element_flags(gst::ElementFlags::SOURCE);
+        }
+    }
+
+    impl GstObjectImpl for CounterSrc {}
+
+    impl ElementImpl for CounterSrc {
+        fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+            static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> =
+                LazyLock::new(|| {
+                    gst::subclass::ElementMetadata::new(
+                        "Thread-sharing counter source",
+                        "Source/Test",
+                        "Pushes buffers holding a counter",
+                        "François Laignel <fengalin@free.fr>",
+                    )
+                });
+
+            Some(&*ELEMENT_METADATA)
+        }
+
+        fn pad_templates() -> &'static [gst::PadTemplate] {
+            static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+                vec![gst::PadTemplate::new(
+                    "src",
+                    gst::PadDirection::Src,
+                    gst::PadPresence::Always,
+                    &gst::Caps::new_any(),
+                )
+                .unwrap()]
+            });
+
+            PAD_TEMPLATES.as_ref()
+        }
+
+        fn change_state(
+            &self,
+            transition: gst::StateChange,
+        ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+            self.src_change_state(transition)
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct CounterSrc(ObjectSubclass<countersrc::CounterSrc>) @extends gst::Element, gst::Object;
+}
+
+fn main() {
+    gst::init().unwrap();
+
+    gst::Element::register(
+        None,
+        "ts-countersrc",
+        gst::Rank::NONE,
+        CounterSrc::static_type(),
+    )
+    .unwrap();
+
+    let pipeline = gst::Pipeline::default();
+    for i in 0..4 {
+        let src = gst::ElementFactory::make("ts-countersrc")
+            .name(format!("src-{i}").as_str())
+            .property("context", "countersrc")
+            .build()
+            .unwrap();
+        let sink = gst::ElementFactory::make("fakesink")
+            .name(format!("sink-{i}").as_str())
+            .property("sync", false)
+            .build()
+            .unwrap();
+
+        pipeline.add_many([&src, &sink]).unwrap();
+        src.link(&sink).unwrap();
+    }
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+
+    let bus = pipeline.bus().unwrap();
+    for msg in bus.iter_timed(gst::ClockTime::NONE) {
+        match msg.view() {
+            gst::MessageView::Eos(..) => {
+                println!("Got EOS from all sources");
+                break;
+            }
+            gst::MessageView::Error(err) => {
+                eprintln!(
+                    "Error from {:?}: {}",
+                    err.src().map(|s| s.path_string()),
+                    err.error()
+                );
+                break;
+            }
+            _ => (),
+        }
+    }
+
+    pipeline.set_state(gst::State::Null).unwrap();
+}
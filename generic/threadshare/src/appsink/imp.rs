@@ -0,0 +1,479 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use futures::future::BoxFuture;
+use futures::prelude::*;
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+
+use std::sync::LazyLock;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::runtime::prelude::*;
+use crate::runtime::PadSink;
+
+const DEFAULT_MAX_BUFFERS: u32 = 10;
+const DEFAULT_DROP: bool = false;
+
+#[derive(Debug, Clone)]
+struct Settings {
+    max_buffers: u32,
+    drop: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            max_buffers: DEFAULT_MAX_BUFFERS,
+            drop: DEFAULT_DROP,
+        }
+    }
+}
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-appsink",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing app sink"),
+    )
+});
+
+#[derive(Debug)]
+enum SinkItem {
+    Sample(gst::Sample),
+    Eos,
+}
+
+#[derive(Debug)]
+enum SampleData {
+    Buffer(gst::Buffer),
+    BufferList(gst::BufferList),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    caps: Option<gst::Caps>,
+    segment: Option<gst::Segment>,
+}
+
+#[derive(Clone, Debug)]
+struct AppSinkPadHandler;
+
+impl PadSinkHandler for AppSinkPadHandler {
+    type ElementImpl = AppSink;
+
+    fn sink_chain(
+        self,
+        pad: gst::Pad,
+        elem: super::AppSink,
+        buffer: gst::Buffer,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            gst::log!(CAT, obj = pad, "Handling {:?}", buffer);
+            let imp = elem.imp();
+            let sample = imp.new_sample(SampleData::Buffer(buffer));
+            imp.enqueue_item(SinkItem::Sample(sample)).await?;
+            elem.emit_by_name::<()>("new-sample", &[]);
+
+            Ok(gst::FlowSuccess::Ok)
+        }
+        .boxed()
+    }
+
+    fn sink_chain_list(
+        self,
+        pad: gst::Pad,
+        elem: super::AppSink,
+        list: gst::BufferList,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            gst::log!(CAT, obj = pad, "Handling {:?}", list);
+            let imp = elem.imp();
+            let sample = imp.new_sample(SampleData::BufferList(list));
+            imp.enqueue_item(SinkItem::Sample(sample)).await?;
+            elem.emit_by_name::<()>("new-sample", &[]);
+
+            Ok(gst::FlowSuccess::Ok)
+        }
+        .boxed()
+    }
+
+    fn sink_event(self, pad: &gst::Pad, imp: &AppSink, event: gst::Event) -> bool {
+        gst::debug!(CAT, obj = pad, "Handling non-serialized {:?}", event);
+
+        if let gst::EventView::FlushStart(..) = event.view() {
+            imp.reset_channel();
+        }
+
+        true
+    }
+
+    fn sink_event_serialized(
+        self,
+        pad: gst::Pad,
+        elem: super::AppSink,
+        event: gst::Event,
+    ) -> BoxFuture<'static, bool> {
+        async move {
+            gst::log!(CAT, obj = pad, "Handling serialized {:?}", event);
+
+            let imp = elem.imp();
+
+            use gst::EventView;
+            match event.view() {
+                EventView::Caps(ev) => {
+                    imp.state.lock().unwrap().caps = Some(ev.caps_owned());
+                }
+                EventView::Segment(ev) => {
+                    imp.state.lock().unwrap().segment = Some(ev.segment().clone());
+                }
+                EventView::FlushStop(..) => {
+                    imp.state.lock().unwrap().segment = None;
+                }
+                EventView::Eos(..) => {
+                    if imp.enqueue_item(SinkItem::Eos).await.is_err() {
+                        gst::debug!(CAT, obj = pad, "Flushing, dropping EOS");
+                        return false;
+                    }
+
+                    elem.emit_by_name::<()>("eos", &[]);
+                    let _ = elem.post_message(gst::message::Eos::builder().src(&elem).build());
+                }
+                _ => (),
+            }
+
+            true
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug)]
+pub struct AppSink {
+    sink_pad: PadSink,
+    channel: Mutex<Option<(flume::Sender<SinkItem>, flume::Receiver<SinkItem>)>>,
+    is_eos: AtomicBool,
+    state: Mutex<State>,
+    settings: Mutex<Settings>,
+}
+
+impl AppSink {
+    fn new_sample(&self, data: SampleData) -> gst::Sample {
+        let state = self.state.lock().unwrap();
+
+        let mut builder = gst::Sample::builder();
+        builder = match data {
+            SampleData::Buffer(ref buffer) => builder.buffer(buffer),
+            SampleData::BufferList(ref list) => builder.buffer_list(list),
+        };
+        if let Some(ref caps) = state.caps {
+            builder = builder.caps(caps);
+        }
+        if let Some(ref segment) = state.segment {
+            builder = builder.segment(segment);
+        }
+
+        builder.build()
+    }
+
+    async fn enqueue_item(&self, item: SinkItem) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let (sender, receiver) = match *self.channel.lock().unwrap() {
+            Some((ref sender, ref receiver)) => (sender.clone(), receiver.clone()),
+            None => return Err(gst::FlowError::Flushing),
+        };
+
+        let drop_old = self.settings.lock().unwrap().drop;
+        if !drop_old || matches!(item, SinkItem::Eos) {
+            // Don't keep a receiver around while waiting for room in the channel,
+            // so that a flush disconnects us.
+            drop(receiver);
+
+            return sender
+                .send_async(item)
+                .await
+                .map(|_| gst::FlowSuccess::Ok)
+                .map_err(|_| {
+                    gst::debug!(CAT, imp = self, "Flushing");
+                    gst::FlowError::Flushing
+                });
+        }
+
+        let mut item = item;
+        loop {
+            match sender.try_send(item) {
+                Ok(()) => return Ok(gst::FlowSuccess::Ok),
+                Err(flume::TrySendError::Full(ret)) => {
+                    if let Ok(old) = receiver.try_recv() {
+                        gst::debug!(CAT, imp = self, "Channel full, dropping {:?}", old);
+                    }
+                    item = ret;
+                }
+                Err(flume::TrySendError::Disconnected(_)) => {
+                    gst::debug!(CAT, imp = self, "Flushing");
+                    return Err(gst::FlowError::Flushing);
+                }
+            }
+        }
+    }
+
+    fn pull_sample(&self, timeout: Option<Duration>) -> Option<gst::Sample> {
+        if self.is_eos.load(Ordering::SeqCst) {
+            gst::debug!(CAT, imp = self, "EOS, no more samples");
+            return None;
+        }
+
+        let receiver = self.channel.lock().unwrap().as_ref()?.1.clone();
+        let item = match timeout {
+            Some(timeout) => receiver.recv_timeout(timeout).ok()?,
+            None => receiver.recv().ok()?,
+        };
+
+        match item {
+            SinkItem::Sample(sample) => Some(sample),
+            SinkItem::Eos => {
+                gst::debug!(CAT, imp = self, "Reached EOS");
+                self.is_eos.store(true, Ordering::SeqCst);
+                None
+            }
+        }
+    }
+
+    /// Replaces the channel, discarding pending samples and waking up
+    /// pending pulls as well as the streaming thread.
+    fn reset_channel(&self) {
+        let max_buffers = self.settings.lock().unwrap().max_buffers;
+        *self.channel.lock().unwrap() = Some(flume::bounded(max_buffers as usize));
+        self.is_eos.store(false, Ordering::SeqCst);
+    }
+
+    fn prepare(&self) {
+        gst::debug!(CAT, imp = self, "Preparing");
+        self.reset_channel();
+        gst::debug!(CAT, imp = self, "Prepared");
+    }
+
+    fn unprepare(&self) {
+        gst::debug!(CAT, imp = self, "Unpreparing");
+        *self.channel.lock().unwrap() = None;
+        gst::debug!(CAT, imp = self, "Unprepared");
+    }
+
+    fn stop(&self) {
+        gst::debug!(CAT, imp = self, "Stopping");
+        self.reset_channel();
+        *self.state.lock().unwrap() = State::default();
+        gst::debug!(CAT, imp = self, "Stopped");
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for AppSink {
+    const NAME: &'static str = "GstTsAppSink";
+    type Type = super::AppSink;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        Self {
+            sink_pad: PadSink::new(
+                gst::Pad::from_template(&klass.pad_template("sink").unwrap()),
+                AppSinkPadHandler,
+            ),
+            channel: Mutex::new(None),
+            is_eos: AtomicBool::new(false),
+            state: Mutex::new(State::default()),
+            settings: Mutex::new(Settings::default()),
+        }
+    }
+}
+
+impl ObjectImpl for AppSink {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecUInt::builder("max-buffers")
+                    .nick("Max Buffers")
+                    .blurb("Maximum number of samples to queue")
+                    .minimum(1)
+                    .default_value(DEFAULT_MAX_BUFFERS)
+                    .build(),
+                glib::ParamSpecBoolean::builder("drop")
+                    .nick("Drop")
+                    .blurb("Drop old samples instead of blocking when the queue is full")
+                    .default_value(DEFAULT_DROP)
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: LazyLock<Vec<glib::subclass::Signal>> = LazyLock::new(|| {
+            vec![
+                /**
+                 * ts-appsink::pull-sample:
+                 * @self: A ts-appsink
+                 *
+                 * Blocks until a sample is available, EOS is reached or the element is flushed.
+                 *
+                 * Returns: a #GstSample or %NULL on EOS or when flushing
+                 */
+                glib::subclass::Signal::builder("pull-sample")
+                    .return_type::<gst::Sample>()
+                    .action()
+                    .class_handler(|_, args| {
+                        let elem = args[0].get::<super::AppSink>().expect("signal arg");
+
+                        Some(elem.imp().pull_sample(None).to_value())
+                    })
+                    .build(),
+                /**
+                 * ts-appsink::try-pull-sample:
+                 * @self: A ts-appsink
+                 * @timeout: the maximum time to wait in nanoseconds
+                 *
+                 * Returns: a #GstSample or %NULL on timeout, EOS or when flushing
+                 */
+                glib::subclass::Signal::builder("try-pull-sample")
+                    .param_types([u64::static_type()])
+                    .return_type::<gst::Sample>()
+                    .action()
+                    .class_handler(|_, args| {
+                        let elem = args[0].get::<super::AppSink>().expect("signal arg");
+                        let timeout = args[1].get::<u64>().expect("signal arg");
+
+                        Some(
+                            elem.imp()
+                                .pull_sample(Some(Duration::from_nanos(timeout)))
+                                .to_value(),
+                        )
+                    })
+                    .build(),
+                /**
+                 * ts-appsink::new-sample:
+                 * @self: A ts-appsink
+                 *
+                 * Emitted from the streaming thread when a new sample is available.
+                 */
+                glib::subclass::Signal::builder("new-sample").build(),
+                /**
+                 * ts-appsink::eos:
+                 * @self: A ts-appsink
+                 *
+                 * Emitted from the streaming thread when EOS is received.
+                 */
+                glib::subclass::Signal::builder("eos").build(),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "max-buffers" => {
+                settings.max_buffers = value.get().expect("type checked upstream");
+            }
+            "drop" => {
+                settings.drop = value.get().expect("type checked upstream");
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "max-buffers" => settings.max_buffers.to_value(),
+            "drop" => settings.drop.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let obj = self.obj();
+        obj.add_pad(self.sink_pad.gst_pad()).unwrap();
+        obj.set_element_flags(gst::ElementFlags::SINK);
+    }
+}
+
+impl GstObjectImpl for AppSink {}
+
+impl ElementImpl for AppSink {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Thread-sharing app sink",
+                "Sink/Generic",
+                "Thread-sharing app sink",
+                "agent <agent@local>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+            let caps = gst::Caps::new_any();
+
+            let sink_pad_template = gst::PadTemplate::new(
+                "sink",
+                gst::PadDirection::Sink,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![sink_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        gst::trace!(CAT, imp = self, "Changing state {:?}", transition);
+
+        match transition {
+            gst::StateChange::NullToReady => {
+                self.prepare();
+            }
+            gst::StateChange::PausedToReady => {
+                self.stop();
+            }
+            gst::StateChange::ReadyToNull => {
+                self.unprepare();
+            }
+            _ => (),
+        }
+
+        self.parent_change_state(transition)
+    }
+}
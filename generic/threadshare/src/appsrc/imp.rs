@@ -18,7 +18,7 @@
 //
 // SPDX-License-Identifier: LGPL-2.1-or-later
 
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 use futures::future::BoxFuture;
 use futures::prelude::*;
 
@@ -28,17 +28,20 @@ use gst::subclass::prelude::*;
 
 use std::sync::LazyLock;
 
-use std::sync::Mutex;
+use std::sync::{Condvar, Mutex};
 use std::time::Duration;
 
+use crate::runtime::executor::debug_assert_not_context_thread;
 use crate::runtime::prelude::*;
-use crate::runtime::{Context, PadSrc, Task, TaskState};
+use crate::runtime::{Context, PadSrc, PadSrcRef, SrcItem, SrcTaskAdapter, Task, TaskState};
 
 const DEFAULT_CONTEXT: &str = "";
 const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
 const DEFAULT_CAPS: Option<gst::Caps> = None;
 const DEFAULT_MAX_BUFFERS: u32 = 10;
 const DEFAULT_DO_TIMESTAMP: bool = false;
+const DEFAULT_WAIT_FOR_CLOCK: bool = false;
+const DEFAULT_BLOCK: bool = false;
 
 #[derive(Debug, Clone)]
 struct Settings {
@@ -47,6 +50,8 @@ struct Settings {
     caps: Option<gst::Caps>,
     max_buffers: u32,
     do_timestamp: bool,
+    wait_for_clock: bool,
+    block: bool,
 }
 
 impl Default for Settings {
@@ -57,6 +62,8 @@ impl Default for Settings {
             caps: DEFAULT_CAPS,
             max_buffers: DEFAULT_MAX_BUFFERS,
             do_timestamp: DEFAULT_DO_TIMESTAMP,
+            wait_for_clock: DEFAULT_WAIT_FOR_CLOCK,
+            block: DEFAULT_BLOCK,
         }
     }
 }
@@ -90,7 +97,7 @@ impl PadSrcHandler for AppSrcPadHandler {
             EventView::FlushStop(..) => imp.task.flush_stop().await_maybe_on_context().is_ok(),
             EventView::Reconfigure(..) => true,
             EventView::Latency(..) => true,
-            _ => false,
+            _ => imp.obj().emit_by_name::<bool>("handle-event", &[&event]),
         };
 
         if ret {
@@ -111,6 +118,11 @@ impl PadSrcHandler for AppSrcPadHandler {
                 q.set(true, gst::ClockTime::ZERO, gst::ClockTime::NONE);
                 true
             }
+            QueryViewMut::Position(q) if q.format() == gst::Format::Time => {
+                let position = *imp.position.lock().unwrap();
+                q.set(position);
+                true
+            }
             QueryViewMut::Scheduling(q) => {
                 q.set(gst::SchedulingFlags::SEQUENTIAL, 1, -1, 0);
                 q.add_scheduling_modes(&[gst::PadMode::Push]);
@@ -147,8 +159,7 @@ impl PadSrcHandler for AppSrcPadHandler {
 struct AppSrcTask {
     element: super::AppSrc,
     receiver: mpsc::Receiver<StreamItem>,
-    need_initial_events: bool,
-    need_segment: bool,
+    pending_buffer: Option<gst::Buffer>,
 }
 
 impl AppSrcTask {
@@ -156,8 +167,7 @@ impl AppSrcTask {
         AppSrcTask {
             element,
             receiver,
-            need_initial_events: true,
-            need_segment: true,
+            pending_buffer: None,
         }
     }
 }
@@ -166,103 +176,101 @@ impl AppSrcTask {
     fn flush(&mut self) {
         // Purge the channel
         while let Ok(Some(_item)) = self.receiver.try_next() {}
+        self.pending_buffer = None;
+        *self.element.imp().position.lock().unwrap() = None;
+        self.element.imp().notify_room();
     }
 
-    async fn push_item(&mut self, item: StreamItem) -> Result<gst::FlowSuccess, gst::FlowError> {
-        gst::log!(CAT, obj = self.element, "Handling {:?}", item);
-        let appsrc = self.element.imp();
-
-        if self.need_initial_events {
-            gst::debug!(CAT, obj = self.element, "Pushing initial events");
-
-            let stream_id = format!("{:08x}{:08x}", rand::random::<u32>(), rand::random::<u32>());
-            let stream_start_evt = gst::event::StreamStart::builder(&stream_id)
-                .group_id(gst::GroupId::next())
-                .build();
-            appsrc.src_pad.push_event(stream_start_evt).await;
-
-            let caps = appsrc.settings.lock().unwrap().caps.clone();
-            if let Some(caps) = caps {
-                appsrc
-                    .src_pad
-                    .push_event(gst::event::Caps::new(&caps))
-                    .await;
-                *appsrc.configured_caps.lock().unwrap() = Some(caps.clone());
-            }
-
-            self.need_initial_events = false;
+    fn update_position(&self, buffer: &gst::Buffer) {
+        if let Some(ts) = buffer.pts().or_else(|| buffer.dts()) {
+            let position = ts.opt_add(buffer.duration()).unwrap_or(ts);
+            *self.element.imp().position.lock().unwrap() = Some(position);
         }
+    }
+}
 
-        if self.need_segment {
-            let segment_evt =
-                gst::event::Segment::new(&gst::FormattedSegment::<gst::format::Time>::new());
-            appsrc.src_pad.push_event(segment_evt).await;
+impl SrcTaskImpl for AppSrcTask {
+    fn element(&self) -> &gst::Element {
+        self.element.upcast_ref()
+    }
 
-            self.need_segment = false;
-        }
+    fn src_pad(&self) -> PadSrcRef<'_> {
+        self.element.imp().src_pad.as_ref()
+    }
 
-        match item {
-            StreamItem::Buffer(buffer) => {
-                gst::log!(CAT, obj = self.element, "Forwarding {:?}", buffer);
-                appsrc.src_pad.push(buffer).await
-            }
-            StreamItem::Event(event) => {
-                match event.view() {
-                    gst::EventView::Eos(_) => {
-                        // Let the caller push the event
-                        Err(gst::FlowError::Eos)
-                    }
-                    _ => {
-                        gst::log!(CAT, obj = self.element, "Forwarding {:?}", event);
-                        appsrc.src_pad.push_event(event).await;
-                        Ok(gst::FlowSuccess::Ok)
-                    }
-                }
-            }
-        }
+    fn caps(&mut self) -> Option<gst::Caps> {
+        self.element.imp().settings.lock().unwrap().caps.clone()
     }
-}
 
-impl TaskImpl for AppSrcTask {
-    type Item = StreamItem;
+    fn caps_pushed(&mut self, caps: &gst::Caps) {
+        let appsrc = self.element.imp();
+        *appsrc.configured_caps.lock().unwrap() = Some(caps.clone());
+        appsrc.negotiate_pool(caps);
+    }
 
-    fn try_next(&mut self) -> BoxFuture<'_, Result<StreamItem, gst::FlowError>> {
-        async move {
-            self.receiver
-                .next()
-                .await
-                .ok_or_else(|| panic!("Internal channel sender dropped while Task is Started"))
+    fn reconfigure(&mut self) {
+        let appsrc = self.element.imp();
+        let caps = appsrc.configured_caps.lock().unwrap().clone();
+        if let Some(caps) = caps {
+            gst::debug!(CAT, obj = self.element, "Renegotiating buffer pool");
+            appsrc.negotiate_pool(&caps);
         }
-        .boxed()
     }
 
-    fn handle_item(&mut self, item: StreamItem) -> BoxFuture<'_, Result<(), gst::FlowError>> {
+    fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
         async move {
-            let res = self.push_item(item).await;
-            match res {
-                Ok(_) => {
-                    gst::log!(CAT, obj = self.element, "Successfully pushed item");
-                }
-                Err(gst::FlowError::Eos) => {
-                    gst::debug!(CAT, obj = self.element, "EOS");
-                    let appsrc = self.element.imp();
-                    appsrc.src_pad.push_event(gst::event::Eos::new()).await;
-                }
-                Err(gst::FlowError::Flushing) => {
-                    gst::debug!(CAT, obj = self.element, "Flushing");
-                }
-                Err(err) => {
-                    gst::error!(CAT, obj = self.element, "Got error {}", err);
-                    gst::element_error!(
-                        &self.element,
-                        gst::StreamError::Failed,
-                        ("Internal data stream error"),
-                        ["streaming stopped, reason {}", err]
-                    );
+            if self.pending_buffer.is_none() {
+                let item = self
+                    .receiver
+                    .next()
+                    .await
+                    .expect("Internal channel sender dropped while Task is Started");
+                self.element.imp().notify_room();
+
+                gst::log!(CAT, obj = self.element, "Handling {:?}", item);
+                match item {
+                    StreamItem::Buffer(buffer) if self.element.imp().timestamps_in_task() => {
+                        self.pending_buffer = Some(buffer);
+                    }
+                    StreamItem::Buffer(buffer) => {
+                        self.update_position(&buffer);
+                        return Ok(SrcItem::Buffer(buffer));
+                    }
+                    StreamItem::Event(event) => {
+                        return match event.view() {
+                            // Let the adapter push the event
+                            gst::EventView::Eos(_) => Err(gst::FlowError::Eos),
+                            _ => Ok(SrcItem::Event(event)),
+                        };
+                    }
                 }
             }
 
-            res.map(drop)
+            // Keep the buffer in `pending_buffer` while waiting
+            // so that it is discarded on flush but not on pause.
+            let clock = loop {
+                let receiver = {
+                    let mut clock_sender = self.element.imp().clock_sender.lock().unwrap();
+                    // Checking under the lock so that a clock set meanwhile can't be missed
+                    if let Some(clock) = self.element.clock() {
+                        break clock;
+                    }
+
+                    let (sender, receiver) = oneshot::channel();
+                    *clock_sender = Some(sender);
+
+                    receiver
+                };
+
+                gst::log!(CAT, obj = self.element, "Waiting for a clock");
+                let _ = receiver.await;
+            };
+
+            let mut buffer = self.pending_buffer.take().unwrap();
+            self.element.imp().timestamp(&mut buffer, &clock);
+            self.update_position(&buffer);
+
+            Ok(SrcItem::Buffer(buffer))
         }
         .boxed()
     }
@@ -272,8 +280,7 @@ impl TaskImpl for AppSrcTask {
             gst::log!(CAT, obj = self.element, "Stopping task");
 
             self.flush();
-            self.need_initial_events = true;
-            self.need_segment = true;
+            self.element.imp().release_pool();
 
             gst::log!(CAT, obj = self.element, "Task stopped");
             Ok(())
@@ -286,7 +293,6 @@ impl TaskImpl for AppSrcTask {
             gst::log!(CAT, obj = self.element, "Starting task flush");
 
             self.flush();
-            self.need_segment = true;
 
             gst::log!(CAT, obj = self.element, "Task flush started");
             Ok(())
@@ -300,44 +306,145 @@ pub struct AppSrc {
     src_pad: PadSrc,
     task: Task,
     sender: Mutex<Option<mpsc::Sender<StreamItem>>>,
+    /// Notified when room is made in the queue, e.g. for a blocking `push-buffer`.
+    sender_cvar: Condvar,
+    /// Dropped when a clock is set to wake up the task waiting for one with `wait-for-clock`.
+    clock_sender: Mutex<Option<oneshot::Sender<()>>>,
     configured_caps: Mutex<Option<gst::Caps>>,
+    negotiated_pool: Mutex<Option<gst::BufferPool>>,
+    position: Mutex<Option<gst::ClockTime>>,
     settings: Mutex<Settings>,
 }
 
 impl AppSrc {
+    fn negotiate_pool(&self, caps: &gst::Caps) {
+        self.release_pool();
+
+        let mut query = gst::query::Allocation::new(Some(caps), true);
+        if !self.src_pad.gst_pad().peer_query(&mut query) {
+            gst::debug!(CAT, imp = self, "Allocation query not handled downstream");
+            return;
+        }
+
+        let Some((Some(pool), size, min, max)) = query.allocation_pools().into_iter().next() else {
+            gst::debug!(CAT, imp = self, "No buffer pool proposed downstream");
+            return;
+        };
+
+        let mut config = pool.config();
+        config.set_params(Some(caps), size, min, max);
+        if let Err(err) = pool.set_config(config) {
+            gst::warning!(CAT, imp = self, "Failed to configure {:?}: {}", pool, err);
+            return;
+        }
+
+        if let Err(err) = pool.set_active(true) {
+            gst::warning!(CAT, imp = self, "Failed to activate {:?}: {}", pool, err);
+            return;
+        }
+
+        gst::debug!(CAT, imp = self, "Negotiated {:?}", pool);
+        *self.negotiated_pool.lock().unwrap() = Some(pool);
+
+        self.obj().notify("negotiated-pool");
+    }
+
+    fn release_pool(&self) {
+        let pool = self.negotiated_pool.lock().unwrap().take();
+        if let Some(pool) = pool {
+            gst::debug!(CAT, imp = self, "Releasing {:?}", pool);
+            let _ = pool.set_active(false);
+            self.obj().notify("negotiated-pool");
+        }
+    }
+
+    fn timestamps_in_task(&self) -> bool {
+        let settings = self.settings.lock().unwrap();
+        settings.do_timestamp && settings.wait_for_clock
+    }
+
+    fn timestamp(&self, buffer: &mut gst::Buffer, clock: &gst::Clock) {
+        let base_time = self.obj().base_time();
+        let now = clock.time();
+
+        let buffer = buffer.make_mut();
+        buffer.set_dts(now.opt_checked_sub(base_time).ok().flatten());
+        buffer.set_pts(None);
+    }
+
+    /// Wakes up `push-buffer` if it is waiting for room in the queue.
+    fn notify_room(&self) {
+        // Lock the sender so that the notification can't be missed between
+        // the failed attempt to queue the buffer and the wait.
+        let _sender = self.sender.lock().unwrap();
+        self.sender_cvar.notify_all();
+    }
+
     fn push_buffer(&self, mut buffer: gst::Buffer) -> bool {
-        let state = self.task.lock_state();
+        let (do_timestamp, wait_for_clock, block) = {
+            let settings = self.settings.lock().unwrap();
+            (
+                settings.do_timestamp,
+                settings.wait_for_clock,
+                settings.block,
+            )
+        };
+
+        if block {
+            debug_assert_not_context_thread("AppSrc push-buffer");
+
+            if let Some(context) = Context::current() {
+                gst::error!(
+                    CAT,
+                    imp = self,
+                    "Refusing to block push-buffer on a thread of Context {}: this would deadlock. \
+                     Push from another thread or disable the block property",
+                    context.name(),
+                );
+                return false;
+            }
+        }
+
+        let mut state = self.task.lock_state();
         if *state != TaskState::Started && *state != TaskState::Paused {
             gst::debug!(CAT, imp = self, "Rejecting buffer due to element state");
             return false;
         }
 
-        let do_timestamp = self.settings.lock().unwrap().do_timestamp;
-        if do_timestamp {
-            let elem = self.obj();
-            if let Some(clock) = elem.clock() {
-                let base_time = elem.base_time();
-                let now = clock.time();
-
-                let buffer = buffer.make_mut();
-                buffer.set_dts(now.opt_checked_sub(base_time).ok().flatten());
-                buffer.set_pts(None);
+        // With wait-for-clock, the buffer is timestamped by the task when dequeued
+        if do_timestamp && !wait_for_clock {
+            if let Some(clock) = self.obj().clock() {
+                self.timestamp(&mut buffer, &clock);
             } else {
                 gst::error!(CAT, imp = self, "Don't have a clock yet");
                 return false;
             }
         }
 
-        let mut sender = self.sender.lock().unwrap();
-        match sender
-            .as_mut()
-            .unwrap()
-            .try_send(StreamItem::Buffer(buffer))
-        {
-            Ok(_) => true,
-            Err(err) => {
-                gst::error!(CAT, imp = self, "Failed to queue buffer: {}", err);
-                false
+        let mut item = StreamItem::Buffer(buffer);
+        loop {
+            let mut sender = self.sender.lock().unwrap();
+            match sender.as_mut().unwrap().try_send(item) {
+                Ok(_) => return true,
+                Err(err) if block && err.is_full() => {
+                    item = err.into_inner();
+                }
+                Err(err) => {
+                    gst::error!(CAT, imp = self, "Failed to queue buffer: {}", err);
+                    return false;
+                }
+            }
+
+            // Don't prevent state transitions while waiting for room in the queue.
+            // The task notifies when it dequeues an item or when it is flushed.
+            drop(state);
+            gst::log!(CAT, imp = self, "Queue full, waiting");
+            drop(self.sender_cvar.wait(sender).unwrap());
+
+            state = self.task.lock_state();
+            if *state != TaskState::Started && *state != TaskState::Paused {
+                gst::debug!(CAT, imp = self, "Rejecting buffer due to element state");
+                return false;
             }
         }
     }
@@ -357,10 +464,14 @@ impl AppSrc {
             }
         }
     }
+}
 
-    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Preparing");
+impl TsSrcImpl for AppSrc {
+    fn task(&self) -> &Task {
+        &self.task
+    }
 
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
         let settings = self.settings.lock().unwrap();
         let context =
             Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
@@ -382,43 +493,22 @@ impl AppSrc {
         let (sender, receiver) = mpsc::channel(max_buffers);
         *self.sender.lock().unwrap() = Some(sender);
 
+        self.task.set_name(self.obj().name().as_str());
+
         self.task
-            .prepare(AppSrcTask::new(self.obj().clone(), receiver), context)
+            .prepare(
+                SrcTaskAdapter::new(AppSrcTask::new(self.obj().clone(), receiver)),
+                context,
+            )
             .block_on()?;
 
-        gst::debug!(CAT, imp = self, "Prepared");
-
         Ok(())
     }
 
     fn unprepare(&self) {
-        gst::debug!(CAT, imp = self, "Unpreparing");
-
         *self.sender.lock().unwrap() = None;
         self.task.unprepare().block_on().unwrap();
-
-        gst::debug!(CAT, imp = self, "Unprepared");
-    }
-
-    fn stop(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Stopping");
-        self.task.stop().block_on()?;
-        gst::debug!(CAT, imp = self, "Stopped");
-        Ok(())
-    }
-
-    fn start(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Starting");
-        self.task.start().block_on()?;
-        gst::debug!(CAT, imp = self, "Started");
-        Ok(())
-    }
-
-    fn pause(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Pausing");
-        self.task.pause().block_on()?;
-        gst::debug!(CAT, imp = self, "Paused");
-        Ok(())
+        self.release_pool();
     }
 }
 
@@ -436,7 +526,11 @@ impl ObjectSubclass for AppSrc {
             ),
             task: Task::default(),
             sender: Default::default(),
+            sender_cvar: Condvar::new(),
+            clock_sender: Default::default(),
             configured_caps: Default::default(),
+            negotiated_pool: Default::default(),
+            position: Default::default(),
             settings: Default::default(),
         }
     }
@@ -472,6 +566,21 @@ impl ObjectImpl for AppSrc {
                     .blurb("Timestamp buffers with the current running time on arrival")
                     .default_value(DEFAULT_DO_TIMESTAMP)
                     .build(),
+                glib::ParamSpecBoolean::builder("wait-for-clock")
+                    .nick("Wait For Clock")
+                    .blurb("With do-timestamp, queue buffers pushed before a clock is available and timestamp them when they are dequeued")
+                    .default_value(DEFAULT_WAIT_FOR_CLOCK)
+                    .build(),
+                glib::ParamSpecBoolean::builder("block")
+                    .nick("Block")
+                    .blurb("Block push-buffer when max-buffers are queued. Must not be used from a Context thread")
+                    .default_value(DEFAULT_BLOCK)
+                    .build(),
+                glib::ParamSpecObject::builder::<gst::BufferPool>("negotiated-pool")
+                    .nick("Negotiated Pool")
+                    .blurb("Buffer pool negotiated with downstream, if any")
+                    .read_only()
+                    .build(),
             ]
         });
 
@@ -507,6 +616,21 @@ impl ObjectImpl for AppSrc {
                         Some(elem.imp().end_of_stream().to_value())
                     })
                     .build(),
+                /**
+                 * ts-appsrc::handle-event:
+                 * @self: A ts-appsrc
+                 * @event: the upstream #GstEvent
+                 *
+                 * Emitted for upstream events which are not handled internally,
+                 * e.g. QoS, navigation or custom upstream events.
+                 * Flush & reconfigure events are never forwarded.
+                 *
+                 * Returns: %TRUE if the application handled the event, %FALSE otherwise
+                 */
+                glib::subclass::Signal::builder("handle-event")
+                    .param_types([gst::Event::static_type()])
+                    .return_type::<bool>()
+                    .build(),
             ]
         });
 
@@ -536,11 +660,21 @@ impl ObjectImpl for AppSrc {
             "do-timestamp" => {
                 settings.do_timestamp = value.get().expect("type checked upstream");
             }
+            "wait-for-clock" => {
+                settings.wait_for_clock = value.get().expect("type checked upstream");
+            }
+            "block" => {
+                settings.block = value.get().expect("type checked upstream");
+            }
             _ => unimplemented!(),
         }
     }
 
     fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        if pspec.name() == "negotiated-pool" {
+            return self.negotiated_pool.lock().unwrap().to_value();
+        }
+
         let settings = self.settings.lock().unwrap();
         match pspec.name() {
             "context" => settings.context.to_value(),
@@ -548,6 +682,8 @@ impl ObjectImpl for AppSrc {
             "caps" => settings.caps.to_value(),
             "max-buffers" => settings.max_buffers.to_value(),
             "do-timestamp" => settings.do_timestamp.to_value(),
+            "wait-for-clock" => settings.wait_for_clock.to_value(),
+            "block" => settings.block.to_value(),
             _ => unimplemented!(),
         }
     }
@@ -598,42 +734,16 @@ impl ElementImpl for AppSrc {
         &self,
         transition: gst::StateChange,
     ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
-        gst::trace!(CAT, imp = self, "Changing state {:?}", transition);
-
-        match transition {
-            gst::StateChange::NullToReady => {
-                self.prepare().map_err(|err| {
-                    self.post_error_message(err);
-                    gst::StateChangeError
-                })?;
-            }
-            gst::StateChange::PlayingToPaused => {
-                self.pause().map_err(|_| gst::StateChangeError)?;
-            }
-            gst::StateChange::ReadyToNull => {
-                self.unprepare();
-            }
-            _ => (),
-        }
+        self.src_change_state(transition)
+    }
 
-        let mut success = self.parent_change_state(transition)?;
+    fn set_clock(&self, clock: Option<&gst::Clock>) -> bool {
+        let res = self.parent_set_clock(clock);
 
-        match transition {
-            gst::StateChange::ReadyToPaused => {
-                success = gst::StateChangeSuccess::NoPreroll;
-            }
-            gst::StateChange::PausedToPlaying => {
-                self.start().map_err(|_| gst::StateChangeError)?;
-            }
-            gst::StateChange::PlayingToPaused => {
-                success = gst::StateChangeSuccess::NoPreroll;
-            }
-            gst::StateChange::PausedToReady => {
-                self.stop().map_err(|_| gst::StateChangeError)?;
-            }
-            _ => (),
+        if clock.is_some() {
+            self.clock_sender.lock().unwrap().take();
         }
 
-        Ok(success)
+        res
     }
 }
@@ -15,14 +15,15 @@ use gst::subclass::prelude::*;
 
 use std::sync::LazyLock;
 
-use std::mem::size_of;
 use std::sync::Mutex;
 use std::time::Duration;
 #[cfg(feature = "tuning")]
 use std::time::Instant;
 
 use crate::runtime::prelude::*;
-use crate::runtime::{self, task, timer, PadSrc, Task};
+use crate::runtime::{self, timer, PadSrc, PadSrcRef, SrcItem, SrcTaskAdapter, Task};
+
+use super::Wave;
 
 static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
     gst::DebugCategory::new(
@@ -38,6 +39,8 @@ const DEFAULT_BUFFER_DURATION: gst::ClockTime = gst::ClockTime::from_mseconds(10
 const DEFAULT_DO_TIMESTAMP: bool = false;
 const DEFAULT_IS_LIVE: bool = false;
 const DEFAULT_NUM_BUFFERS: i32 = -1;
+const DEFAULT_SAMPLES_PER_BUFFER: u32 = 0;
+const DEFAULT_WAVE: Wave = Wave::Sine;
 
 const DEFAULT_CHANNELS: usize = 1;
 const DEFAULT_FREQ: f32 = 440.0;
@@ -51,7 +54,7 @@ const LOG_BUFFER_INTERVAL: u32 = 2000;
 
 static DEFAULT_CAPS: LazyLock<gst::Caps> = LazyLock::new(|| {
     gst_audio::AudioCapsBuilder::new_interleaved()
-        .format(gst_audio::AUDIO_FORMAT_S16)
+        .format_list([gst_audio::AUDIO_FORMAT_S16, gst_audio::AUDIO_FORMAT_F32])
         .rate_range(8_000..i32::MAX)
         .channels_range(1..i32::MAX)
         .build()
@@ -65,6 +68,8 @@ struct Settings {
     is_live: bool,
     buffer_duration: gst::ClockTime,
     num_buffers: Option<u32>,
+    samples_per_buffer: Option<u32>,
+    wave: Wave,
     #[cfg(feature = "tuning")]
     is_main_elem: bool,
 }
@@ -78,6 +83,8 @@ impl Default for Settings {
             is_live: DEFAULT_IS_LIVE,
             buffer_duration: DEFAULT_BUFFER_DURATION,
             num_buffers: None,
+            samples_per_buffer: None,
+            wave: DEFAULT_WAVE,
             #[cfg(feature = "tuning")]
             is_main_elem: false,
         }
@@ -95,7 +102,15 @@ impl PadSrcHandler for AudioTestSrcPadHandler {
         if let gst::QueryViewMut::Latency(q) = query.view_mut() {
             let settings = imp.settings.lock().unwrap();
             let min_latency = if settings.is_live {
-                settings.buffer_duration
+                settings
+                    .samples_per_buffer
+                    .and_then(|samples_per_buffer| {
+                        let rate = gst_audio::AudioInfo::from_caps(&pad.current_caps()?)
+                            .ok()?
+                            .rate();
+                        gst::ClockTime::SECOND.mul_div_floor(samples_per_buffer as u64, rate as u64)
+                    })
+                    .unwrap_or(settings.buffer_duration)
             } else {
                 gst::ClockTime::ZERO
             };
@@ -132,14 +147,21 @@ impl Negotiation {
 struct AudioTestSrcTask {
     elem: super::AudioTestSrc,
     buffer_pool: gst::BufferPool,
+    format: gst_audio::AudioFormat,
     rate: u32,
     channels: usize,
     do_timestamp: bool,
     is_live: bool,
     buffer_duration: gst::ClockTime,
-    need_initial_events: bool,
+    samples_per_buffer: Option<u32>,
+    buffer_samples: u64,
+    wave: Wave,
+    need_reconfigure: bool,
+    caps_pushed: bool,
     step: f32,
     accumulator: f32,
+    base_time: Option<gst::ClockTime>,
+    sample_offset: u64,
     last_buffer_end: Option<gst::ClockTime>,
     caps: gst::Caps,
     buffer_count: u32,
@@ -157,14 +179,21 @@ impl AudioTestSrcTask {
         AudioTestSrcTask {
             elem,
             buffer_pool: gst::BufferPool::new(),
+            format: gst_audio::AUDIO_FORMAT_S16,
             rate: DEFAULT_RATE,
             channels: DEFAULT_CHANNELS,
             do_timestamp: DEFAULT_DO_TIMESTAMP,
             is_live: DEFAULT_IS_LIVE,
             buffer_duration: DEFAULT_BUFFER_DURATION,
-            need_initial_events: true,
+            samples_per_buffer: None,
+            buffer_samples: 0,
+            wave: DEFAULT_WAVE,
+            need_reconfigure: false,
+            caps_pushed: false,
             step: 0.0,
             accumulator: 0.0,
+            base_time: None,
+            sample_offset: 0,
             last_buffer_end: None,
             caps: gst::Caps::new_empty(),
             buffer_count: 0,
@@ -178,11 +207,13 @@ impl AudioTestSrcTask {
         }
     }
 
-    async fn negotiate(&mut self) -> Result<Negotiation, gst::ErrorMessage> {
+    fn negotiate(&mut self) -> Result<Negotiation, gst::ErrorMessage> {
         let imp = self.elem.imp();
         let pad = imp.src_pad.gst_pad();
 
-        if !pad.check_reconfigure() {
+        // The pad flag might have been consumed by the adapter while streaming, see `reconfigure`
+        let need_reconfigure = std::mem::take(&mut self.need_reconfigure);
+        if !pad.check_reconfigure() && !need_reconfigure {
             return Ok(Negotiation::Unchanged);
         }
 
@@ -209,6 +240,9 @@ impl AudioTestSrcTask {
                 err
             })?;
 
+            s.fixate_field_str("format", gst_audio::AUDIO_FORMAT_S16.to_str());
+            self.format = gst_audio::AudioFormat::from_string(s.get::<&str>("format").unwrap());
+
             s.fixate_field_nearest_int("rate", DEFAULT_RATE as i32);
             self.rate = s.get::<i32>("rate").unwrap() as u32;
             self.step = 2.0 * std::f32::consts::PI * DEFAULT_FREQ / (self.rate as f32);
@@ -229,16 +263,54 @@ impl AudioTestSrcTask {
         caps.fixate();
         gst::debug!(CAT, imp = imp, "fixated to {caps:?}");
 
-        imp.src_pad.push_event(gst::event::Caps::new(&caps)).await;
-
         self.caps = caps;
 
         Ok(Negotiation::Changed)
     }
+
+    fn samples_to_time(&self, samples: u64) -> gst::ClockTime {
+        gst::ClockTime::SECOND
+            .mul_div_floor(samples, self.rate as u64)
+            .unwrap()
+    }
+
+    fn fill_sine<const N: usize>(&mut self, data: &mut [u8], to_bytes: impl Fn(f32) -> [u8; N]) {
+        data.chunks_exact_mut(self.channels * N).for_each(|frame| {
+            let sample = to_bytes(self.accumulator.sin() * DEFAULT_VOLUME);
+
+            frame
+                .chunks_exact_mut(N)
+                .for_each(|channel| channel.copy_from_slice(&sample));
+
+            self.accumulator += self.step;
+            if self.accumulator >= 2.0 * std::f32::consts::PI {
+                self.accumulator = -2.0 * std::f32::consts::PI;
+            }
+        });
+    }
 }
 
-impl TaskImpl for AudioTestSrcTask {
-    type Item = gst::Buffer;
+impl SrcTaskImpl for AudioTestSrcTask {
+    fn element(&self) -> &gst::Element {
+        self.elem.upcast_ref()
+    }
+
+    fn src_pad(&self) -> PadSrcRef<'_> {
+        self.elem.imp().src_pad.as_ref()
+    }
+
+    fn caps(&mut self) -> Option<gst::Caps> {
+        Some(self.caps.clone())
+    }
+
+    fn caps_pushed(&mut self, _caps: &gst::Caps) {
+        self.caps_pushed = true;
+    }
+
+    fn reconfigure(&mut self) {
+        // Renegotiation takes place when the Task is (re)started
+        self.need_reconfigure = true;
+    }
 
     fn prepare(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
         gst::log!(CAT, obj = self.elem, "Preparing Task");
@@ -248,6 +320,8 @@ impl TaskImpl for AudioTestSrcTask {
         self.do_timestamp = settings.do_timestamp;
         self.is_live = settings.is_live;
         self.buffer_duration = settings.buffer_duration;
+        self.samples_per_buffer = settings.samples_per_buffer;
+        self.wave = settings.wave;
         self.num_buffers = settings.num_buffers;
 
         #[cfg(feature = "tuning")]
@@ -262,42 +336,35 @@ impl TaskImpl for AudioTestSrcTask {
         async move {
             gst::log!(CAT, obj = self.elem, "Starting Task");
 
-            if self.need_initial_events {
-                gst::debug!(CAT, obj = self.elem, "Pushing initial events");
-
-                let stream_id =
-                    format!("{:08x}{:08x}", rand::random::<u32>(), rand::random::<u32>());
-                let stream_start_evt = gst::event::StreamStart::builder(&stream_id)
-                    .group_id(gst::GroupId::next())
-                    .build();
-                self.elem.imp().src_pad.push_event(stream_start_evt).await;
-            }
+            if self.negotiate()?.has_changed() {
+                self.buffer_samples = match self.samples_per_buffer {
+                    Some(samples_per_buffer) => samples_per_buffer as u64,
+                    None => (self.rate as u64) * self.buffer_duration.mseconds() / 1_000,
+                };
+                self.buffer_duration = self.samples_to_time(self.buffer_samples);
 
-            if self.negotiate().await?.has_changed() {
-                let bytes_per_buffer = (self.rate as u64)
-                    * self.buffer_duration.mseconds()
-                    * self.channels as u64
-                    * size_of::<i16>() as u64
-                    / 1_000;
+                let bytes_per_buffer =
+                    self.buffer_samples * self.channels as u64 * (self.format.width() / 8) as u64;
 
                 let mut pool_config = self.buffer_pool.config();
                 pool_config
                     .as_mut()
                     .set_params(Some(&self.caps), bytes_per_buffer as u32, 2, 6);
                 self.buffer_pool.set_config(pool_config).unwrap();
+
+                if self.caps_pushed {
+                    // Otherwise, the caps are pushed along with the initial events
+                    self.elem
+                        .imp()
+                        .src_pad
+                        .push_event(gst::event::Caps::new(&self.caps))
+                        .await;
+                }
             }
 
             assert!(!self.caps.is_empty());
             self.buffer_pool.set_active(true).unwrap();
 
-            if self.need_initial_events {
-                let segment_evt =
-                    gst::event::Segment::new(&gst::FormattedSegment::<gst::format::Time>::new());
-                self.elem.imp().src_pad.push_event(segment_evt).await;
-
-                self.need_initial_events = false;
-            }
-
             self.buffer_count = 0;
 
             #[cfg(feature = "tuning")]
@@ -320,14 +387,20 @@ impl TaskImpl for AudioTestSrcTask {
     fn stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
         gst::log!(CAT, obj = self.elem, "Stopping Task");
 
-        self.need_initial_events = true;
+        self.caps_pushed = false;
         self.accumulator = 0.0;
+        self.base_time = None;
+        self.sample_offset = 0;
         self.last_buffer_end = None;
 
         future::ok(()).boxed()
     }
 
-    fn try_next(&mut self) -> BoxFuture<'_, Result<gst::Buffer, gst::FlowError>> {
+    fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
+        if self.num_buffers.opt_eq(self.buffer_count) == Some(true) {
+            return future::err(gst::FlowError::Eos).boxed();
+        }
+
         let mut buffer = match self.buffer_pool.acquire_buffer(None) {
             Ok(buffer) => buffer,
             Err(err) => {
@@ -339,41 +412,73 @@ impl TaskImpl for AudioTestSrcTask {
         let buffer_mut = buffer.get_mut().unwrap();
 
         let start = if self.is_live | self.do_timestamp {
-            self.last_buffer_end
-                .or_else(|| self.elem.current_running_time())
+            if self.base_time.is_none() {
+                self.base_time = self.elem.current_running_time();
+            }
+            // Computed from the sample count so that durations rounding doesn't accumulate
+            self.base_time
+                .opt_add(self.samples_to_time(self.sample_offset))
         } else {
             None
         };
 
         {
-            use std::io::Write;
-
             let mut mapped = buffer_mut.map_writable().unwrap();
             let slice = mapped.as_mut_slice();
-            slice
-                .chunks_mut(self.channels * size_of::<i16>())
-                .for_each(|frame| {
-                    let sample = ((self.accumulator.sin() * DEFAULT_VOLUME * (i16::MAX as f32))
-                        as i16)
-                        .to_ne_bytes();
-
-                    frame.chunks_mut(size_of::<i16>()).for_each(|mut channel| {
-                        let _ = channel.write(&sample).unwrap();
+            match self.wave {
+                Wave::Silence => slice.fill(0),
+                Wave::Sine if self.format == gst_audio::AUDIO_FORMAT_F32 => {
+                    self.fill_sine(slice, f32::to_ne_bytes);
+                }
+                Wave::Sine => {
+                    self.fill_sine(slice, |sample| {
+                        ((sample * (i16::MAX as f32)) as i16).to_ne_bytes()
                     });
+                }
+            }
+        }
 
-                    self.accumulator += self.step;
-                    if self.accumulator >= 2.0 * std::f32::consts::PI {
-                        self.accumulator = -2.0 * std::f32::consts::PI;
-                    }
-                });
+        if start.is_some() {
+            buffer_mut.set_pts(start);
+            buffer_mut.set_duration(self.buffer_duration);
         }
 
         if self.do_timestamp {
             buffer_mut.set_dts(start);
-            buffer_mut.set_duration(self.buffer_duration);
         }
 
-        self.last_buffer_end = start.opt_add(self.buffer_duration);
+        buffer_mut.set_offset(self.sample_offset);
+        self.sample_offset += self.buffer_samples;
+        buffer_mut.set_offset_end(self.sample_offset);
+
+        self.last_buffer_end = self
+            .base_time
+            .opt_add(self.samples_to_time(self.sample_offset));
+
+        self.buffer_count += 1;
+
+        #[cfg(feature = "tuning")]
+        if self.is_main_elem {
+            if let Some(parked_duration_init) = self.parked_duration_init {
+                if self.buffer_count % LOG_BUFFER_INTERVAL == 0 {
+                    let parked_duration = runtime::Context::current().unwrap().parked_duration()
+                        - parked_duration_init;
+
+                    gst::info!(
+                        CAT,
+                        "Parked: {:5.2?}%",
+                        parked_duration.as_nanos() as f32 * 100.0
+                            / self.log_start.elapsed().as_nanos() as f32,
+                    );
+                }
+            } else if self.buffer_count == RAMPUP_BUFFER_COUNT {
+                self.parked_duration_init =
+                    Some(runtime::Context::current().unwrap().parked_duration());
+                self.log_start = Instant::now();
+
+                gst::info!(CAT, "Ramp up complete");
+            }
+        }
 
         async move {
             if self.is_live {
@@ -390,84 +495,7 @@ impl TaskImpl for AudioTestSrcTask {
                 runtime::executor::yield_now().await;
             }
 
-            Ok(buffer)
-        }
-        .boxed()
-    }
-
-    fn handle_item(&mut self, buffer: gst::Buffer) -> BoxFuture<'_, Result<(), gst::FlowError>> {
-        async move {
-            let imp = self.elem.imp();
-
-            gst::debug!(CAT, imp = imp, "Pushing {buffer:?}");
-            imp.src_pad.push(buffer).await?;
-            gst::log!(CAT, imp = imp, "Successfully pushed buffer");
-
-            self.buffer_count += 1;
-
-            #[cfg(feature = "tuning")]
-            if self.is_main_elem {
-                if let Some(parked_duration_init) = self.parked_duration_init {
-                    if self.buffer_count % LOG_BUFFER_INTERVAL == 0 {
-                        let parked_duration =
-                            runtime::Context::current().unwrap().parked_duration()
-                                - parked_duration_init;
-
-                        gst::info!(
-                            CAT,
-                            "Parked: {:5.2?}%",
-                            parked_duration.as_nanos() as f32 * 100.0
-                                / self.log_start.elapsed().as_nanos() as f32,
-                        );
-                    }
-                } else if self.buffer_count == RAMPUP_BUFFER_COUNT {
-                    self.parked_duration_init =
-                        Some(runtime::Context::current().unwrap().parked_duration());
-                    self.log_start = Instant::now();
-
-                    gst::info!(CAT, "Ramp up complete");
-                }
-            }
-
-            if self.num_buffers.opt_eq(self.buffer_count) == Some(true) {
-                return Err(gst::FlowError::Eos);
-            }
-
-            Ok(())
-        }
-        .boxed()
-    }
-
-    fn handle_loop_error(&mut self, err: gst::FlowError) -> BoxFuture<'_, task::Trigger> {
-        async move {
-            match err {
-                gst::FlowError::Flushing => {
-                    gst::debug!(CAT, obj = self.elem, "Flushing");
-
-                    task::Trigger::FlushStart
-                }
-                gst::FlowError::Eos => {
-                    gst::debug!(CAT, obj = self.elem, "EOS");
-                    self.elem
-                        .imp()
-                        .src_pad
-                        .push_event(gst::event::Eos::new())
-                        .await;
-
-                    task::Trigger::Stop
-                }
-                err => {
-                    gst::error!(CAT, obj = self.elem, "Got error {err}");
-                    gst::element_error!(
-                        &self.elem,
-                        gst::StreamError::Failed,
-                        ("Internal data stream error"),
-                        ["streaming stopped, reason {}", err]
-                    );
-
-                    task::Trigger::Error
-                }
-            }
+            Ok(SrcItem::Buffer(buffer))
         }
         .boxed()
     }
@@ -480,10 +508,12 @@ pub struct AudioTestSrc {
     settings: Mutex<Settings>,
 }
 
-impl AudioTestSrc {
-    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Preparing");
+impl TsSrcImpl for AudioTestSrc {
+    fn task(&self) -> &Task {
+        &self.task
+    }
 
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
         let settings = self.settings.lock().unwrap();
         let context =
             runtime::Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
@@ -494,42 +524,15 @@ impl AudioTestSrc {
             })?;
         drop(settings);
 
+        self.task.set_name(self.obj().name().as_str());
+
         self.task
-            .prepare(AudioTestSrcTask::new(self.obj().clone()), context)
+            .prepare(
+                SrcTaskAdapter::new(AudioTestSrcTask::new(self.obj().clone())),
+                context,
+            )
             .block_on()?;
 
-        gst::debug!(CAT, imp = self, "Prepared");
-
-        Ok(())
-    }
-
-    fn unprepare(&self) {
-        gst::debug!(CAT, imp = self, "Unpreparing");
-        self.task.unprepare().block_on().unwrap();
-        gst::debug!(CAT, imp = self, "Unprepared");
-    }
-
-    fn stop(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Stopping");
-        self.task.stop().block_on()?;
-        gst::debug!(CAT, imp = self, "Stopped");
-
-        Ok(())
-    }
-
-    fn start(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Starting");
-        self.task.start().block_on()?;
-        gst::debug!(CAT, imp = self, "Started");
-
-        Ok(())
-    }
-
-    fn pause(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Pausing");
-        self.task.pause().block_on()?;
-        gst::debug!(CAT, imp = self, "Paused");
-
         Ok(())
     }
 }
@@ -586,6 +589,15 @@ impl ObjectImpl for AudioTestSrc {
                     .minimum(-1i32)
                     .default_value(DEFAULT_NUM_BUFFERS)
                     .build(),
+                glib::ParamSpecUInt::builder("samples-per-buffer")
+                    .nick("Samples per Buffer")
+                    .blurb("Number of samples in each buffer (0 = use buffer-duration)")
+                    .default_value(DEFAULT_SAMPLES_PER_BUFFER)
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("wave", DEFAULT_WAVE)
+                    .nick("Waveform")
+                    .blurb("Oscillator waveform")
+                    .build(),
                 #[cfg(feature = "tuning")]
                 glib::ParamSpecBoolean::builder("main-elem")
                     .nick("Main Element")
@@ -623,6 +635,13 @@ impl ObjectImpl for AudioTestSrc {
                 let value = value.get::<i32>().unwrap();
                 settings.num_buffers = if value > 0 { Some(value as u32) } else { None };
             }
+            "samples-per-buffer" => {
+                let value = value.get::<u32>().unwrap();
+                settings.samples_per_buffer = if value > 0 { Some(value) } else { None };
+            }
+            "wave" => {
+                settings.wave = value.get::<Wave>().unwrap();
+            }
             #[cfg(feature = "tuning")]
             "main-elem" => {
                 settings.is_main_elem = value.get::<bool>().unwrap();
@@ -644,6 +663,11 @@ impl ObjectImpl for AudioTestSrc {
                 .and_then(|val| val.try_into().ok())
                 .unwrap_or(-1i32)
                 .to_value(),
+            "samples-per-buffer" => settings
+                .samples_per_buffer
+                .unwrap_or(DEFAULT_SAMPLES_PER_BUFFER)
+                .to_value(),
+            "wave" => settings.wave.to_value(),
             #[cfg(feature = "tuning")]
             "main-elem" => settings.is_main_elem.to_value(),
             _ => unimplemented!(),
@@ -695,43 +719,6 @@ impl ElementImpl for AudioTestSrc {
         &self,
         transition: gst::StateChange,
     ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
-        gst::trace!(CAT, imp = self, "Changing state {transition:?}");
-
-        match transition {
-            gst::StateChange::NullToReady => {
-                self.prepare().map_err(|err| {
-                    self.post_error_message(err);
-                    gst::StateChangeError
-                })?;
-            }
-            gst::StateChange::PlayingToPaused => {
-                self.pause().map_err(|_| gst::StateChangeError)?;
-            }
-            gst::StateChange::ReadyToNull => {
-                self.unprepare();
-            }
-            _ => (),
-        }
-
-        let mut success = self.parent_change_state(transition)?;
-
-        match transition {
-            gst::StateChange::ReadyToPaused => {
-                self.pause().map_err(|_| gst::StateChangeError)?;
-                success = gst::StateChangeSuccess::NoPreroll;
-            }
-            gst::StateChange::PausedToPlaying => {
-                self.start().map_err(|_| gst::StateChangeError)?;
-            }
-            gst::StateChange::PlayingToPaused => {
-                success = gst::StateChangeSuccess::NoPreroll;
-            }
-            gst::StateChange::PausedToReady => {
-                self.stop().map_err(|_| gst::StateChangeError)?;
-            }
-            _ => (),
-        }
-
-        Ok(success)
+        self.src_change_state(transition)
     }
 }
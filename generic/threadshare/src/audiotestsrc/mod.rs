@@ -3,11 +3,24 @@ use gst::prelude::*;
 
 mod imp;
 
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstTsAudioTestSrcWave")]
+pub enum Wave {
+    #[default]
+    #[enum_value(name = "Sine", nick = "sine")]
+    Sine,
+    #[enum_value(name = "Silence", nick = "silence")]
+    Silence,
+}
+
 glib::wrapper! {
     pub struct AudioTestSrc(ObjectSubclass<imp::AudioTestSrc>) @extends gst::Element, gst::Object;
 }
 
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    Wave::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+
     gst::Element::register(
         Some(plugin),
         "ts-audiotestsrc",
@@ -17,6 +17,7 @@
 //
 // SPDX-License-Identifier: LGPL-2.1-or-later
 
+use futures::channel::oneshot;
 use futures::future::{self, abortable, AbortHandle};
 
 use gst::prelude::*;
@@ -40,27 +41,38 @@ pub enum DataQueueItem {
     Buffer(gst::Buffer),
     BufferList(gst::BufferList),
     Event(gst::Event),
+    /// Position of a serialized query in the stream: the sender is notified
+    /// once all the items queued before it were pushed downstream.
+    Barrier(oneshot::Sender<()>),
 }
 
 impl DataQueueItem {
-    fn size(&self) -> (u32, u32) {
+    pub fn size(&self) -> (u32, u32) {
         match *self {
             DataQueueItem::Buffer(ref buffer) => (1, buffer.size() as u32),
             DataQueueItem::BufferList(ref list) => (
                 list.len() as u32,
                 list.iter().map(|b| b.size() as u32).sum::<u32>(),
             ),
-            DataQueueItem::Event(_) => (0, 0),
+            DataQueueItem::Event(_) | DataQueueItem::Barrier(_) => (0, 0),
         }
     }
+}
 
-    fn timestamp(&self) -> Option<gst::ClockTime> {
-        match *self {
-            DataQueueItem::Buffer(ref buffer) => buffer.dts_or_pts(),
-            DataQueueItem::BufferList(ref list) => list.iter().find_map(|b| b.dts_or_pts()),
-            DataQueueItem::Event(_) => None,
-        }
-    }
+#[derive(Debug)]
+struct QueuedItem {
+    item: DataQueueItem,
+    // Accounted in the time level, so that the exact same amount is released on removal
+    duration: gst::ClockTime,
+}
+
+/// The current fill level of a [`DataQueue`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DataQueueLevel {
+    pub buffers: u32,
+    pub bytes: u32,
+    /// Accumulated duration of the queued buffers.
+    pub time: gst::ClockTime,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -78,13 +90,20 @@ struct DataQueueInner {
     src_pad: gst::Pad,
 
     state: DataQueueState,
-    queue: VecDeque<DataQueueItem>,
+    queue: VecDeque<QueuedItem>,
 
     cur_size_buffers: u32,
     cur_size_bytes: u32,
+    cur_size_time: gst::ClockTime,
+    // Used to estimate the duration of buffers without one
+    last_pts: Option<gst::ClockTime>,
     max_size_buffers: Option<u32>,
     max_size_bytes: Option<u32>,
     max_size_time: Option<gst::ClockTime>,
+    min_threshold_buffers: Option<u32>,
+    min_threshold_time: Option<gst::ClockTime>,
+    // Output is held until the min thresholds are reached
+    holding: bool,
 
     pending_handle: Option<AbortHandle>,
 }
@@ -95,6 +114,93 @@ impl DataQueueInner {
             pending_handle.abort();
         }
     }
+
+    fn buffer_duration(&mut self, buffer: &gst::BufferRef) -> gst::ClockTime {
+        let pts = buffer.pts();
+        let duration = buffer
+            .duration()
+            .or_else(|| pts.opt_checked_sub(self.last_pts).ok().flatten())
+            .unwrap_or(gst::ClockTime::ZERO);
+
+        if pts.is_some() {
+            self.last_pts = pts;
+        }
+
+        duration
+    }
+
+    fn item_duration(&mut self, item: &DataQueueItem) -> gst::ClockTime {
+        match *item {
+            DataQueueItem::Buffer(ref buffer) => self.buffer_duration(buffer),
+            DataQueueItem::BufferList(ref list) => {
+                list.iter().map(|buffer| self.buffer_duration(buffer)).sum()
+            }
+            DataQueueItem::Event(ref event) => {
+                if event.type_() == gst::EventType::Segment {
+                    // Timestamps from different segments can't be compared
+                    self.last_pts = None;
+                }
+
+                gst::ClockTime::ZERO
+            }
+            DataQueueItem::Barrier(_) => gst::ClockTime::ZERO,
+        }
+    }
+
+    fn has_min_threshold(&self) -> bool {
+        self.min_threshold_buffers.is_some() || self.min_threshold_time.is_some()
+    }
+
+    fn is_full(&self) -> bool {
+        self.max_size_buffers
+            .is_some_and(|max| max <= self.cur_size_buffers)
+            || self
+                .max_size_bytes
+                .is_some_and(|max| max <= self.cur_size_bytes)
+            || self
+                .max_size_time
+                .is_some_and(|max| max <= self.cur_size_time)
+    }
+
+    fn check_threshold(&mut self) -> bool {
+        if !self.holding {
+            return true;
+        }
+
+        let reached = self
+            .min_threshold_buffers
+            .map_or(true, |min| self.cur_size_buffers >= min)
+            && self
+                .min_threshold_time
+                .map_or(true, |min| self.cur_size_time >= min);
+
+        // EOS and serialized queries must not wait for more data,
+        // neither can a full queue
+        self.holding = !(reached
+            || self.is_full()
+            || self.queue.iter().any(|queued| match queued.item {
+                DataQueueItem::Event(ref event) => event.type_() == gst::EventType::Eos,
+                DataQueueItem::Barrier(_) => true,
+                _ => false,
+            }));
+
+        if !self.holding {
+            gst::debug!(
+                DATA_QUEUE_CAT,
+                obj = self.element,
+                "Min threshold reached, releasing output"
+            );
+        }
+
+        !self.holding
+    }
+
+    fn remove_accounting(&mut self, queued: &QueuedItem) {
+        let (count, bytes) = queued.item.size();
+        self.cur_size_buffers -= count;
+        self.cur_size_bytes -= bytes;
+        self.cur_size_time -= queued.duration;
+    }
 }
 
 impl DataQueue {
@@ -112,17 +218,77 @@ impl DataQueue {
             queue: VecDeque::new(),
             cur_size_buffers: 0,
             cur_size_bytes: 0,
+            cur_size_time: gst::ClockTime::ZERO,
+            last_pts: None,
             max_size_buffers,
             max_size_bytes,
             max_size_time: max_size_time.into(),
+            min_threshold_buffers: None,
+            min_threshold_time: None,
+            holding: false,
             pending_handle: None,
         })))
     }
 
+    /// Updates the size limits.
+    ///
+    /// The new limits apply to the next items to be queued: shrinking doesn't
+    /// drop already queued items.
+    pub fn set_max_size(
+        &self,
+        max_size_buffers: Option<u32>,
+        max_size_bytes: Option<u32>,
+        max_size_time: impl Into<Option<gst::ClockTime>>,
+    ) {
+        let mut inner = self.0.lock().unwrap();
+        inner.max_size_buffers = max_size_buffers;
+        inner.max_size_bytes = max_size_bytes;
+        inner.max_size_time = max_size_time.into();
+        // A full queue no longer holds output for the min thresholds
+        inner.wake();
+    }
+
+    /// Updates the time limit only, see [`DataQueue::set_max_size`].
+    pub fn set_max_size_time(&self, max_size_time: impl Into<Option<gst::ClockTime>>) {
+        let mut inner = self.0.lock().unwrap();
+        inner.max_size_time = max_size_time.into();
+        inner.wake();
+    }
+
+    /// Sets the levels to reach before output starts and resumes after an underrun.
+    pub fn set_min_threshold(
+        &self,
+        min_threshold_buffers: Option<u32>,
+        min_threshold_time: impl Into<Option<gst::ClockTime>>,
+    ) {
+        let mut inner = self.0.lock().unwrap();
+        inner.min_threshold_buffers = min_threshold_buffers;
+        inner.min_threshold_time = min_threshold_time.into();
+        inner.holding = inner.has_min_threshold();
+        inner.wake();
+    }
+
     pub fn state(&self) -> DataQueueState {
         self.0.lock().unwrap().state
     }
 
+    pub fn level(&self) -> DataQueueLevel {
+        let inner = self.0.lock().unwrap();
+        DataQueueLevel {
+            buffers: inner.cur_size_buffers,
+            bytes: inner.cur_size_bytes,
+            time: inner.cur_size_time,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().queue.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.0.lock().unwrap().is_full()
+    }
+
     pub fn start(&self) {
         let mut inner = self.0.lock().unwrap();
         if inner.state == DataQueueState::Started {
@@ -159,8 +325,8 @@ impl DataQueue {
         gst::debug!(DATA_QUEUE_CAT, obj = inner.element, "Clearing data queue");
 
         let src_pad = inner.src_pad.clone();
-        for item in inner.queue.drain(..) {
-            if let DataQueueItem::Event(event) = item {
+        for queued in inner.queue.drain(..) {
+            if let DataQueueItem::Event(event) = queued.item {
                 if event.is_sticky()
                     && event.type_() != gst::EventType::Segment
                     && event.type_() != gst::EventType::Eos
@@ -170,6 +336,12 @@ impl DataQueue {
             }
         }
 
+        inner.cur_size_buffers = 0;
+        inner.cur_size_bytes = 0;
+        inner.cur_size_time = gst::ClockTime::ZERO;
+        inner.last_pts = None;
+        inner.holding = inner.has_min_threshold();
+
         gst::debug!(DATA_QUEUE_CAT, obj = inner.element, "Data queue cleared");
     }
 
@@ -195,8 +367,6 @@ impl DataQueue {
         );
 
         let (count, bytes) = item.size();
-        let queue_ts = inner.queue.iter().filter_map(|i| i.timestamp()).next();
-        let ts = item.timestamp();
 
         if let Some(max) = inner.max_size_buffers {
             if max <= inner.cur_size_buffers {
@@ -224,35 +394,56 @@ impl DataQueue {
             }
         }
 
-        // FIXME: Use running time
-        if let (Some(max), Some(queue_ts), Some(ts)) = (inner.max_size_time, queue_ts, ts) {
-            let level = if queue_ts > ts {
-                queue_ts - ts
-            } else {
-                ts - queue_ts
-            };
-
-            if max <= level {
+        if let Some(max) = inner.max_size_time {
+            if max <= inner.cur_size_time {
                 gst::debug!(
                     DATA_QUEUE_CAT,
                     obj = inner.element,
                     "Queue is full (time): {} <= {}",
                     max,
-                    level
+                    inner.cur_size_time
                 );
                 return Err(item);
             }
         }
 
-        inner.queue.push_back(item);
+        let duration = inner.item_duration(&item);
+        inner.queue.push_back(QueuedItem { item, duration });
         inner.cur_size_buffers += count;
         inner.cur_size_bytes += bytes;
+        inner.cur_size_time += duration;
 
         inner.wake();
 
         Ok(())
     }
 
+    /// Drops the oldest buffer or buffer list, leaving the queued events in place.
+    ///
+    /// Returns the number of dropped buffers or `None` if no buffers were queued.
+    pub fn drop_oldest_buffer(&self) -> Option<u32> {
+        let mut inner = self.0.lock().unwrap();
+
+        let idx = inner.queue.iter().position(|queued| {
+            !matches!(
+                queued.item,
+                DataQueueItem::Event(_) | DataQueueItem::Barrier(_)
+            )
+        })?;
+        let queued = inner.queue.remove(idx).unwrap();
+
+        gst::debug!(
+            DATA_QUEUE_CAT,
+            obj = inner.element,
+            "Dropping item {:?}",
+            queued.item
+        );
+
+        inner.remove_accounting(&queued);
+
+        Some(queued.item.size().0)
+    }
+
     // TODO: implement as a Stream now that we use a StdMutex
     #[allow(clippy::should_implement_trait)]
     pub async fn next(&mut self) -> Option<DataQueueItem> {
@@ -260,23 +451,32 @@ impl DataQueue {
             let pending_fut = {
                 let mut inner = self.0.lock().unwrap();
                 match inner.state {
+                    DataQueueState::Started if !inner.check_threshold() => {
+                        gst::log!(
+                            DATA_QUEUE_CAT,
+                            obj = inner.element,
+                            "Holding output until min threshold is reached"
+                        );
+                    }
                     DataQueueState::Started => match inner.queue.pop_front() {
                         None => {
                             gst::debug!(DATA_QUEUE_CAT, obj = inner.element, "Data queue is empty");
                         }
-                        Some(item) => {
+                        Some(queued) => {
                             gst::debug!(
                                 DATA_QUEUE_CAT,
                                 obj = inner.element,
                                 "Popped item {:?}",
-                                item
+                                queued.item
                             );
 
-                            let (count, bytes) = item.size();
-                            inner.cur_size_buffers -= count;
-                            inner.cur_size_bytes -= bytes;
+                            inner.remove_accounting(&queued);
+                            if inner.queue.is_empty() {
+                                // Underrun: hold output until the min thresholds are reached again
+                                inner.holding = inner.has_min_threshold();
+                            }
 
-                            return Some(item);
+                            return Some(queued.item);
                         }
                     },
                     DataQueueState::Stopped => {
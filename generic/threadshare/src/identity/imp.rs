@@ -0,0 +1,591 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use futures::channel::mpsc;
+use futures::future::{abortable, AbortHandle, BoxFuture};
+use futures::prelude::*;
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+
+use std::sync::LazyLock;
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::runtime::prelude::*;
+use crate::runtime::{timer, Context, PadSink, PadSrc};
+
+const DEFAULT_LATENCY: u64 = 0;
+const DEFAULT_JITTER: u64 = 0;
+const DEFAULT_DROP_PROBABILITY: f32 = 0.0;
+const DEFAULT_ERROR_AFTER: i32 = -1;
+
+#[derive(Debug, Clone)]
+struct Settings {
+    latency: u64,
+    jitter: u64,
+    drop_probability: f32,
+    error_after: i32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            latency: DEFAULT_LATENCY,
+            jitter: DEFAULT_JITTER,
+            drop_probability: DEFAULT_DROP_PROBABILITY,
+            error_after: DEFAULT_ERROR_AFTER,
+        }
+    }
+}
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-identity",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing identity"),
+    )
+});
+
+#[derive(Clone, Debug)]
+struct IdentityPadSinkHandler;
+
+impl PadSinkHandler for IdentityPadSinkHandler {
+    type ElementImpl = Identity;
+
+    fn sink_chain(
+        self,
+        pad: gst::Pad,
+        elem: super::Identity,
+        buffer: gst::Buffer,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            gst::log!(CAT, obj = pad, "Handling {:?}", buffer);
+            let imp = elem.imp();
+
+            let Some(delay) = imp.next_delay()? else {
+                gst::debug!(CAT, obj = pad, "Dropping {:?}", buffer);
+                elem.emit_by_name::<()>("drop", &[&buffer]);
+                return Ok(gst::FlowSuccess::Ok);
+            };
+
+            let deadline = Instant::now() + delay;
+            imp.deliver(&elem, DelayedItem::Buffer(buffer, deadline))
+                .await
+        }
+        .boxed()
+    }
+
+    fn sink_chain_list(
+        self,
+        pad: gst::Pad,
+        elem: super::Identity,
+        list: gst::BufferList,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            gst::log!(CAT, obj = pad, "Handling {:?}", list);
+            let imp = elem.imp();
+
+            // Lists are delayed, dropped & counted as a whole
+            let Some(delay) = imp.next_delay()? else {
+                gst::debug!(CAT, obj = pad, "Dropping {:?}", list);
+                for buffer in list.iter_owned() {
+                    elem.emit_by_name::<()>("drop", &[&buffer]);
+                }
+                return Ok(gst::FlowSuccess::Ok);
+            };
+
+            let deadline = Instant::now() + delay;
+            imp.deliver(&elem, DelayedItem::BufferList(list, deadline))
+                .await
+        }
+        .boxed()
+    }
+
+    fn sink_event(self, pad: &gst::Pad, imp: &Identity, event: gst::Event) -> bool {
+        gst::debug!(CAT, obj = pad, "Handling non-serialized {:?}", event);
+
+        if let gst::EventView::FlushStart(..) = event.view() {
+            imp.abort_delivery();
+        }
+
+        imp.src_pad.gst_pad().push_event(event)
+    }
+
+    fn sink_event_serialized(
+        self,
+        pad: gst::Pad,
+        elem: super::Identity,
+        event: gst::Event,
+    ) -> BoxFuture<'static, bool> {
+        async move {
+            let imp = elem.imp();
+
+            let is_flush_stop = matches!(event.view(), gst::EventView::FlushStop(..));
+            if is_flush_stop {
+                imp.delivery.lock().unwrap().flow = Ok(gst::FlowSuccess::Ok);
+            } else if let Some(delivery) = imp.delivery.lock().unwrap().delivery.as_ref() {
+                // Keep the event behind the buffers waiting for their deadline
+                gst::log!(CAT, obj = pad, "Queuing serialized {:?}", event);
+                return delivery
+                    .sender
+                    .unbounded_send(DelayedItem::Event(event))
+                    .is_ok();
+            }
+
+            gst::log!(CAT, obj = pad, "Forwarding serialized {:?}", event);
+            imp.src_pad.push_event(event).await
+        }
+        .boxed()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct IdentityPadSrcHandler;
+
+impl PadSrcHandler for IdentityPadSrcHandler {
+    type ElementImpl = Identity;
+
+    fn src_event(self, pad: &gst::Pad, imp: &Identity, event: gst::Event) -> bool {
+        gst::log!(CAT, obj = pad, "Handling {:?}", event);
+
+        if let gst::EventView::FlushStart(..) = event.view() {
+            imp.abort_delivery();
+        }
+
+        imp.sink_pad.gst_pad().push_event(event)
+    }
+
+    fn src_query(self, pad: &gst::Pad, imp: &Identity, query: &mut gst::QueryRef) -> bool {
+        gst::log!(CAT, obj = pad, "Handling {:?}", query);
+
+        if let gst::QueryViewMut::Latency(q) = query.view_mut() {
+            let mut peer_query = gst::query::Latency::new();
+            if !imp.sink_pad.gst_pad().peer_query(&mut peer_query) {
+                return false;
+            }
+
+            let settings = imp.settings.lock().unwrap().clone();
+            let latency = gst::ClockTime::from_nseconds(settings.latency);
+            let jitter = gst::ClockTime::from_nseconds(settings.jitter);
+
+            let (live, min, max) = peer_query.result();
+            q.set(live, min + latency, max.opt_add(latency + jitter));
+            gst::log!(CAT, obj = pad, "Returning {:?}", q.query_mut());
+
+            return true;
+        }
+
+        gst::log!(CAT, obj = pad, "Forwarding {:?}", query);
+        imp.sink_pad.gst_pad().peer_query(query)
+    }
+}
+
+/// An item waiting for its delivery.
+#[derive(Debug)]
+enum DelayedItem {
+    Buffer(gst::Buffer, Instant),
+    BufferList(gst::BufferList, Instant),
+    // Serialized events are delivered right after the preceding buffers
+    Event(gst::Event),
+}
+
+#[derive(Debug)]
+struct Delivery {
+    sender: mpsc::UnboundedSender<DelayedItem>,
+    abort_handle: AbortHandle,
+}
+
+#[derive(Debug)]
+struct DeliveryState {
+    /// The delivery loop running on the upstream `Context`, if any.
+    delivery: Option<Delivery>,
+    /// Result of the last push from the delivery loop.
+    flow: Result<gst::FlowSuccess, gst::FlowError>,
+}
+
+impl Default for DeliveryState {
+    fn default() -> Self {
+        DeliveryState {
+            delivery: None,
+            flow: Ok(gst::FlowSuccess::Ok),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Identity {
+    sink_pad: PadSink,
+    src_pad: PadSrc,
+    num_buffers: Mutex<u64>,
+    delivery: Mutex<DeliveryState>,
+    // Aborted on flush start & when stopping
+    delay_handle: Mutex<Option<AbortHandle>>,
+    settings: Mutex<Settings>,
+}
+
+impl Identity {
+    /// Returns the delay to apply to the next buffer,
+    /// `None` if it must be dropped.
+    fn next_delay(&self) -> Result<Option<Duration>, gst::FlowError> {
+        let settings = self.settings.lock().unwrap().clone();
+
+        let mut num_buffers = self.num_buffers.lock().unwrap();
+        *num_buffers += 1;
+        if settings.error_after > 0 && *num_buffers >= settings.error_after as u64 {
+            gst::element_imp_error!(
+                self,
+                gst::CoreError::Failed,
+                ["Failed after iterations as requested"]
+            );
+            return Err(gst::FlowError::Error);
+        }
+        drop(num_buffers);
+
+        if settings.drop_probability > 0.0 && rand::random::<f32>() < settings.drop_probability {
+            return Ok(None);
+        }
+
+        let jitter = Duration::from_nanos(settings.jitter).mul_f64(rand::random::<f64>());
+
+        Ok(Some(Duration::from_nanos(settings.latency) + jitter))
+    }
+
+    /// Pushes `item` downstream once its deadline is reached.
+    ///
+    /// When running on a `Context`, the items are handed over to a delivery loop
+    /// on that `Context` so that the upstream element is not held while waiting
+    /// and the delays of successive buffers overlap. Otherwise, the deadline is
+    /// awaited before pushing.
+    async fn deliver(
+        &self,
+        elem: &super::Identity,
+        item: DelayedItem,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let Some(ts_ctx) = Context::current() else {
+            if let Some(deadline) = item.deadline() {
+                self.delay(deadline.saturating_duration_since(Instant::now()))
+                    .await?;
+            }
+
+            return Self::push_item(elem, item).await;
+        };
+
+        let mut state = self.delivery.lock().unwrap();
+        let flow = state.flow?;
+        let delivery = state
+            .delivery
+            .get_or_insert_with(|| Self::spawn_delivery(elem, &ts_ctx));
+        delivery
+            .sender
+            .unbounded_send(item)
+            .map_err(|_| gst::FlowError::Flushing)?;
+
+        Ok(flow)
+    }
+
+    fn spawn_delivery(elem: &super::Identity, ts_ctx: &Context) -> Delivery {
+        gst::debug!(CAT, obj = elem, "Starting delivery loop");
+
+        let (sender, mut receiver) = mpsc::unbounded::<DelayedItem>();
+        let elem = elem.clone();
+        let (delivery_fut, abort_handle) = abortable(async move {
+            while let Some(item) = receiver.next().await {
+                if let Some(deadline) = item.deadline() {
+                    timer::at(deadline).await;
+                }
+
+                let res = Self::push_item(&elem, item).await;
+
+                let mut state = elem.imp().delivery.lock().unwrap();
+                state.flow = res;
+                if let Err(err) = res {
+                    gst::debug!(CAT, obj = elem, "Stopping delivery loop: {err:?}");
+                    state.delivery = None;
+                    break;
+                }
+            }
+        });
+
+        let _ = ts_ctx.spawn(delivery_fut);
+
+        Delivery {
+            sender,
+            abort_handle,
+        }
+    }
+
+    async fn push_item(
+        elem: &super::Identity,
+        item: DelayedItem,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let imp = elem.imp();
+
+        match item {
+            DelayedItem::Buffer(buffer, _) => {
+                elem.emit_by_name::<()>("handoff", &[&buffer]);
+                imp.src_pad.push(buffer).await
+            }
+            DelayedItem::BufferList(list, _) => imp.src_pad.push_list(list).await,
+            DelayedItem::Event(event) => {
+                if !imp.src_pad.push_event(event).await {
+                    gst::debug!(CAT, obj = elem, "Failed to push serialized event");
+                }
+                Ok(gst::FlowSuccess::Ok)
+            }
+        }
+    }
+
+    /// Waits for `delay` using the `Context` timers.
+    ///
+    /// Returns `Err(Flushing)` if the wait was aborted.
+    async fn delay(&self, delay: Duration) -> Result<(), gst::FlowError> {
+        if delay.is_zero() {
+            return Ok(());
+        }
+
+        gst::trace!(CAT, imp = self, "Delaying for {delay:?}");
+
+        let (delay_fut, abort_handle) = abortable(timer::delay_for(delay));
+        *self.delay_handle.lock().unwrap() = Some(abort_handle);
+        let res = delay_fut.await;
+        self.delay_handle.lock().unwrap().take();
+
+        res.map_err(|_| gst::FlowError::Flushing)
+    }
+
+    /// Aborts the pending deliveries, dropping the items waiting for their deadline.
+    fn abort_delivery(&self) {
+        if let Some(delay_handle) = self.delay_handle.lock().unwrap().take() {
+            delay_handle.abort();
+        }
+
+        let mut state = self.delivery.lock().unwrap();
+        if let Some(delivery) = state.delivery.take() {
+            delivery.abort_handle.abort();
+        }
+        state.flow = Err(gst::FlowError::Flushing);
+    }
+}
+
+impl DelayedItem {
+    fn deadline(&self) -> Option<Instant> {
+        match self {
+            DelayedItem::Buffer(_, deadline) | DelayedItem::BufferList(_, deadline) => {
+                Some(*deadline)
+            }
+            DelayedItem::Event(_) => None,
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for Identity {
+    const NAME: &'static str = "GstTsIdentity";
+    type Type = super::Identity;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        Self {
+            sink_pad: PadSink::new(
+                gst::Pad::from_template(&klass.pad_template("sink").unwrap()),
+                IdentityPadSinkHandler,
+            ),
+            src_pad: PadSrc::new(
+                gst::Pad::from_template(&klass.pad_template("src").unwrap()),
+                IdentityPadSrcHandler,
+            ),
+            num_buffers: Mutex::new(0),
+            delivery: Mutex::new(DeliveryState::default()),
+            delay_handle: Mutex::new(None),
+            settings: Mutex::new(Settings::default()),
+        }
+    }
+}
+
+impl ObjectImpl for Identity {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecUInt64::builder("latency")
+                    .nick("Latency")
+                    .blurb("Fixed delay in ns applied to the delivery of each buffer")
+                    .default_value(DEFAULT_LATENCY)
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecUInt64::builder("jitter")
+                    .nick("Jitter")
+                    .blurb("Maximum random delay in ns added to the latency")
+                    .default_value(DEFAULT_JITTER)
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecFloat::builder("drop-probability")
+                    .nick("Drop Probability")
+                    .blurb("The probability a buffer is dropped")
+                    .minimum(0.0)
+                    .maximum(1.0)
+                    .default_value(DEFAULT_DROP_PROBABILITY)
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecInt::builder("error-after")
+                    .nick("Error After")
+                    .blurb("Error after N buffers (-1 = disabled)")
+                    .minimum(-1)
+                    .default_value(DEFAULT_ERROR_AFTER)
+                    .mutable_playing()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: LazyLock<Vec<glib::subclass::Signal>> = LazyLock::new(|| {
+            vec![
+                /**
+                 * ts-identity::handoff:
+                 * @self: A ts-identity
+                 * @buffer: the #GstBuffer about to be pushed
+                 *
+                 * Emitted from the streaming thread once the buffer has been delayed.
+                 */
+                glib::subclass::Signal::builder("handoff")
+                    .param_types([gst::Buffer::static_type()])
+                    .build(),
+                /**
+                 * ts-identity::drop:
+                 * @self: A ts-identity
+                 * @buffer: the dropped #GstBuffer
+                 *
+                 * Emitted from the streaming thread when a buffer is dropped
+                 * according to #ts-identity:drop-probability.
+                 */
+                glib::subclass::Signal::builder("drop")
+                    .param_types([gst::Buffer::static_type()])
+                    .build(),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "latency" => {
+                settings.latency = value.get().expect("type checked upstream");
+            }
+            "jitter" => {
+                settings.jitter = value.get().expect("type checked upstream");
+            }
+            "drop-probability" => {
+                settings.drop_probability = value.get().expect("type checked upstream");
+            }
+            "error-after" => {
+                settings.error_after = value.get().expect("type checked upstream");
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "latency" => settings.latency.to_value(),
+            "jitter" => settings.jitter.to_value(),
+            "drop-probability" => settings.drop_probability.to_value(),
+            "error-after" => settings.error_after.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let obj = self.obj();
+        obj.add_pad(self.sink_pad.gst_pad()).unwrap();
+        obj.add_pad(self.src_pad.gst_pad()).unwrap();
+    }
+}
+
+impl GstObjectImpl for Identity {}
+
+impl ElementImpl for Identity {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Thread-sharing identity",
+                "Generic",
+                "Pass data through, optionally delaying or dropping it",
+                "agent <agent@local>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+            let caps = gst::Caps::new_any();
+
+            let sink_pad_template = gst::PadTemplate::new(
+                "sink",
+                gst::PadDirection::Sink,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            let src_pad_template = gst::PadTemplate::new(
+                "src",
+                gst::PadDirection::Src,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![sink_pad_template, src_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        gst::trace!(CAT, imp = self, "Changing state {:?}", transition);
+
+        match transition {
+            gst::StateChange::ReadyToPaused => {
+                *self.num_buffers.lock().unwrap() = 0;
+                self.delivery.lock().unwrap().flow = Ok(gst::FlowSuccess::Ok);
+            }
+            gst::StateChange::PausedToReady => {
+                self.abort_delivery();
+            }
+            _ => (),
+        }
+
+        self.parent_change_state(transition)
+    }
+}
@@ -17,6 +17,7 @@
 //
 // SPDX-License-Identifier: LGPL-2.1-or-later
 
+use futures::channel::oneshot;
 use futures::future::BoxFuture;
 use futures::future::{abortable, AbortHandle};
 use futures::prelude::*;
@@ -27,20 +28,31 @@ use gst::subclass::prelude::*;
 
 use std::sync::LazyLock;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use crate::runtime::prelude::*;
 use crate::runtime::{self, PadSink, PadSrc};
 
+use super::SyncMode;
+
 const DEFAULT_CONTEXT: &str = "";
 const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+const DEFAULT_SYNC_STREAMS: bool = true;
+const DEFAULT_SYNC_MODE: SyncMode = SyncMode::ActiveSegment;
+const DEFAULT_CACHE_BUFFERS: bool = false;
+const DEFAULT_CACHE_BUFFERS_MAX_DURATION: gst::ClockTime = gst::ClockTime::from_seconds(5);
+const DEFAULT_ALWAYS_OK: bool = true;
 
 #[derive(Debug, Clone)]
 struct Settings {
     context: String,
     context_wait: Duration,
+    sync_streams: bool,
+    sync_mode: SyncMode,
+    cache_buffers: bool,
+    cache_buffers_max_duration: gst::ClockTime,
 }
 
 impl Default for Settings {
@@ -48,6 +60,10 @@ impl Default for Settings {
         Settings {
             context: DEFAULT_CONTEXT.into(),
             context_wait: DEFAULT_CONTEXT_WAIT,
+            sync_streams: DEFAULT_SYNC_STREAMS,
+            sync_mode: DEFAULT_SYNC_MODE,
+            cache_buffers: DEFAULT_CACHE_BUFFERS,
+            cache_buffers_max_duration: DEFAULT_CACHE_BUFFERS_MAX_DURATION,
         }
     }
 }
@@ -57,6 +73,9 @@ struct InputSelectorPadSinkHandlerInner {
     segment: Option<gst::Segment>,
     send_sticky: bool,
     abort_handle: Option<AbortHandle>,
+    // Buffers received while inactive since the last keyframe
+    cache: VecDeque<gst::Buffer>,
+    cache_start: Option<gst::ClockTime>,
 }
 
 impl Default for InputSelectorPadSinkHandlerInner {
@@ -65,6 +84,8 @@ impl Default for InputSelectorPadSinkHandlerInner {
             segment: None,
             send_sticky: true,
             abort_handle: None,
+            cache: VecDeque::new(),
+            cache_start: None,
         }
     }
 }
@@ -72,6 +93,45 @@ impl Default for InputSelectorPadSinkHandlerInner {
 #[derive(Clone, Debug, Default)]
 struct InputSelectorPadSinkHandler(Arc<Mutex<InputSelectorPadSinkHandlerInner>>);
 
+impl InputSelectorPadSinkHandlerInner {
+    /* None if the segment isn't in time format */
+    fn running_time(&self, buffer: &gst::Buffer) -> Option<Option<gst::ClockTime>> {
+        self.segment
+            .as_ref()
+            .and_then(|segment| segment.downcast_ref::<gst::format::Time>())
+            .map(|segment| segment.to_running_time(buffer.pts()))
+    }
+
+    fn cache_buffer(
+        &mut self,
+        buffer: &gst::Buffer,
+        rtime: Option<gst::ClockTime>,
+        max_duration: gst::ClockTime,
+    ) {
+        if !buffer.flags().contains(gst::BufferFlags::DELTA_UNIT) {
+            self.clear_cache();
+            self.cache_start = rtime;
+        } else if self.cache.is_empty() {
+            // Wait for a keyframe, the cache would be useless otherwise
+            return;
+        } else if rtime
+            .opt_saturating_sub(self.cache_start)
+            .is_some_and(|duration| duration > max_duration)
+        {
+            // Can't drop only the oldest buffers as the keyframe is needed
+            self.clear_cache();
+            return;
+        }
+
+        self.cache.push_back(buffer.clone());
+    }
+
+    fn clear_cache(&mut self) {
+        self.cache.clear();
+        self.cache_start = None;
+    }
+}
+
 impl InputSelectorPadSinkHandler {
     /* Wait until specified time */
     async fn sync(&self, elem: &super::InputSelector, running_time: Option<gst::ClockTime>) {
@@ -85,6 +145,47 @@ impl InputSelectorPadSinkHandler {
         }
     }
 
+    /* Wait until the active pad reaches the specified running time */
+    async fn sync_active_segment(
+        &self,
+        elem: &super::InputSelector,
+        pad: &gst::Pad,
+        running_time: Option<gst::ClockTime>,
+    ) {
+        let Some(running_time) = running_time else {
+            return runtime::executor::yield_now().await;
+        };
+
+        loop {
+            let receiver = {
+                let mut state = elem.imp().state.lock().unwrap();
+                if state.active_eos
+                    || state
+                        .active_sinkpad
+                        .as_ref()
+                        .map_or(true, |active| active == pad)
+                    || state
+                        .last_running_time
+                        .map_or(true, |last| last >= running_time)
+                {
+                    break;
+                }
+
+                let (sender, receiver) = oneshot::channel();
+                state.active_waiters.push(sender);
+                receiver
+            };
+
+            gst::log!(
+                CAT,
+                obj = pad,
+                "Waiting for active pad to reach {running_time}"
+            );
+            // Woken up when the active pad forwards a buffer or changes
+            let _ = receiver.await;
+        }
+    }
+
     async fn handle_item(
         &self,
         pad: &gst::Pad,
@@ -92,25 +193,104 @@ impl InputSelectorPadSinkHandler {
         mut buffer: gst::Buffer,
     ) -> Result<gst::FlowSuccess, gst::FlowError> {
         let inputselector = elem.imp();
+        let (sync_streams, sync_mode, cache_buffers, cache_buffers_max_duration) = {
+            let settings = inputselector.settings.lock().unwrap();
+            (
+                settings.sync_streams,
+                settings.sync_mode,
+                settings.cache_buffers,
+                settings.cache_buffers_max_duration,
+            )
+        };
 
-        let (stickies, is_active, sync_future, switched_pad) = {
+        let (
+            stickies,
+            is_active,
+            sync_future,
+            mut switched_pad,
+            segment_start,
+            switched,
+            cached,
+            pad_switched,
+            running_time_offset,
+        ) = {
             let mut state = inputselector.state.lock().unwrap();
             let mut inner = self.0.lock().unwrap();
             let mut stickies = vec![];
             let mut sync_future = None;
-            let switched_pad = state.switched_pad;
-
-            if let Some(segment) = &inner.segment {
-                if let Some(segment) = segment.downcast_ref::<gst::format::Time>() {
-                    let rtime = segment.to_running_time(buffer.pts());
-                    let (sync_fut, abort_handle) = abortable(self.sync(elem, rtime));
-                    inner.abort_handle = Some(abort_handle);
-                    sync_future = Some(sync_fut.map_err(|_| gst::FlowError::Flushing));
-                }
+            let mut segment_start = None;
+            let mut cached = VecDeque::new();
+            let mut pad_switched = None;
+
+            let rtime = inner.running_time(&buffer);
+            let has_time_segment = rtime.is_some();
+            let rtime = rtime.flatten();
+
+            // Apply the switch requested with the `switch` signal
+            // once the active pad reaches the stop running time
+            let switched = state.active_sinkpad.as_ref() == Some(pad)
+                && state
+                    .pending_switch
+                    .as_ref()
+                    .is_some_and(|switch| rtime.opt_ge(switch.stop_time).unwrap_or(false));
+            if switched {
+                let switch = state.pending_switch.take().unwrap();
+                gst::debug!(
+                    CAT,
+                    obj = pad,
+                    "Switching to {} at {}",
+                    switch.pad.name(),
+                    switch.stop_time
+                );
+                let old_pad = state.active_sinkpad.replace(switch.pad);
+                state.switched_from.get_or_insert(old_pad);
+                state.switched_pad = true;
+                state.switch_start_time = switch.start_time;
+                state.active_eos = false;
+                state.active_waiters.clear();
             }
 
+            let switched_pad = state.switched_pad;
+
             let is_active = {
-                if state.active_sinkpad.as_ref() == Some(pad) {
+                if state.active_sinkpad.as_ref() != Some(pad) {
+                    false
+                } else if state
+                    .switch_start_time
+                    .is_some_and(|start_time| rtime.opt_lt(start_time).unwrap_or(false))
+                {
+                    gst::log!(CAT, obj = pad, "Dropping buffer before switch start time");
+                    false
+                } else {
+                    if state.switched_pad {
+                        // Push the cached GOP first so that the switch is glitch-free
+                        cached = std::mem::take(&mut inner.cache);
+                        inner.cache_start = None;
+
+                        let first_rtime = cached
+                            .front()
+                            .map_or(rtime, |buffer| inner.running_time(buffer).flatten());
+
+                        // Shift the new pad after what was already pushed so that
+                        // the running time downstream doesn't go backwards
+                        if let Some(first_rtime) = first_rtime {
+                            state.running_time_offset = state
+                                .highest_running_time
+                                .map_or(gst::ClockTime::ZERO, |highest| {
+                                    highest.saturating_sub(first_rtime)
+                                });
+                            gst::debug!(
+                                CAT,
+                                obj = pad,
+                                "Running time offset {}",
+                                state.running_time_offset
+                            );
+                        }
+
+                        if let Some(old_pad) = state.switched_from.take() {
+                            pad_switched = Some((old_pad, first_rtime));
+                        }
+                    }
                     if inner.send_sticky || state.switched_pad {
                         pad.sticky_events_foreach(|event| {
                             use std::ops::ControlFlow;
@@ -121,23 +301,135 @@ impl InputSelectorPadSinkHandler {
                         inner.send_sticky = false;
                         state.switched_pad = false;
                     }
+                    segment_start = state.switch_start_time.take();
+                    if let Some(rtime) = rtime {
+                        let end = rtime
+                            + buffer.duration().unwrap_or(gst::ClockTime::ZERO)
+                            + state.running_time_offset;
+                        state.highest_running_time = Some(
+                            state
+                                .highest_running_time
+                                .map_or(end, |highest| highest.max(end)),
+                        );
+                        state.last_running_time = Some(rtime);
+                        for waiter in state.active_waiters.drain(..) {
+                            let _ = waiter.send(());
+                        }
+                    }
                     true
-                } else {
-                    false
                 }
             };
 
-            (stickies, is_active, sync_future, switched_pad)
+            if !is_active && cache_buffers {
+                inner.cache_buffer(&buffer, rtime, cache_buffers_max_duration);
+            }
+
+            if has_time_segment {
+                // Inactive pads are either synced against the clock or the
+                // running time of the active pad, then their data is discarded
+                let sync_fut = if is_active {
+                    let rtime = rtime.map(|rtime| rtime + state.running_time_offset);
+                    Some(self.sync(elem, rtime).boxed())
+                } else if sync_streams && sync_mode == SyncMode::Clock {
+                    Some(self.sync(elem, rtime).boxed())
+                } else if sync_streams {
+                    Some(self.sync_active_segment(elem, pad, rtime).boxed())
+                } else {
+                    None
+                };
+
+                if let Some(sync_fut) = sync_fut {
+                    let (sync_fut, abort_handle) = abortable(sync_fut);
+                    inner.abort_handle = Some(abort_handle);
+                    sync_future = Some(sync_fut.map_err(|_| gst::FlowError::Flushing));
+                }
+            }
+
+            (
+                stickies,
+                is_active,
+                sync_future,
+                switched_pad,
+                segment_start,
+                switched,
+                cached,
+                pad_switched,
+                state.running_time_offset,
+            )
         };
 
+        if switched {
+            elem.notify("active-pad");
+        }
+
         if let Some(sync_fut) = sync_future {
             sync_fut.await?;
         }
 
-        for event in stickies {
+        for mut event in stickies {
+            if let gst::EventView::Segment(e) = event.view() {
+                if let Ok(mut segment) = e.segment().clone().downcast::<gst::format::Time>() {
+                    let mut changed = false;
+
+                    // Make the segment start at the switch so that the running time
+                    // downstream doesn't go backwards
+                    if let Some(start) = segment_start
+                        .and_then(|start_time| segment.position_from_running_time(start_time))
+                    {
+                        if segment.start().opt_lt(start).unwrap_or(false) {
+                            segment.set_start(start);
+                            changed = true;
+                        }
+                    }
+
+                    if running_time_offset > gst::ClockTime::ZERO {
+                        let base = segment.base().unwrap_or(gst::ClockTime::ZERO);
+                        segment.set_base(base + running_time_offset);
+                        changed = true;
+                    }
+
+                    if changed {
+                        event = gst::event::Segment::builder(&segment)
+                            .seqnum(event.seqnum())
+                            .build();
+                    }
+                }
+            }
+
+            // Downstream would reset its state on a stream-start
+            // for the stream it is already handling
+            if let gst::EventView::StreamStart(e) = event.view() {
+                let current = inputselector
+                    .src_pad
+                    .gst_pad()
+                    .sticky_event::<gst::event::StreamStart>(0);
+                if current.is_some_and(|current| current.stream_id() == e.stream_id()) {
+                    gst::log!(CAT, obj = pad, "Skipping stream-start for the same stream");
+                    continue;
+                }
+            }
+
             inputselector.src_pad.push_event(event).await;
         }
 
+        if let Some((old_pad, running_time)) = pad_switched {
+            inputselector.pad_switched(old_pad, pad, running_time);
+        }
+
+        if is_active && !cached.is_empty() {
+            gst::debug!(CAT, obj = pad, "Forwarding {} cached buffers", cached.len());
+
+            for mut cached_buffer in cached {
+                if switched_pad {
+                    cached_buffer
+                        .make_mut()
+                        .set_flags(gst::BufferFlags::DISCONT);
+                    switched_pad = false;
+                }
+                inputselector.src_pad.push(cached_buffer).await?;
+            }
+        }
+
         if is_active {
             gst::log!(CAT, obj = pad, "Forwarding {:?}", buffer);
 
@@ -147,8 +439,14 @@ impl InputSelectorPadSinkHandler {
             }
 
             inputselector.src_pad.push(buffer).await
-        } else {
+        } else if pad
+            .downcast_ref::<super::InputSelectorSinkPad>()
+            .map_or(DEFAULT_ALWAYS_OK, |pad| pad.imp().always_ok())
+        {
             Ok(gst::FlowSuccess::Ok)
+        } else {
+            gst::log!(CAT, obj = pad, "Discarding {:?} from inactive pad", buffer);
+            Err(gst::FlowError::NotLinked)
         }
     }
 }
@@ -185,11 +483,20 @@ impl PadSinkHandler for InputSelectorPadSinkHandler {
 
     fn sink_event_serialized(
         self,
-        _pad: gst::Pad,
-        _elem: super::InputSelector,
+        pad: gst::Pad,
+        elem: super::InputSelector,
         event: gst::Event,
     ) -> BoxFuture<'static, bool> {
         async move {
+            if let gst::EventView::Eos(..) = event.view() {
+                // Don't keep inactive pads waiting for an active pad which is done
+                let mut state = elem.imp().state.lock().unwrap();
+                if state.active_sinkpad.as_ref() == Some(&pad) {
+                    state.active_eos = true;
+                    state.active_waiters.clear();
+                }
+            }
+
             let mut inner = self.0.lock().unwrap();
 
             // Remember the segment for later use
@@ -224,6 +531,7 @@ impl PadSinkHandler for InputSelectorPadSinkHandler {
             if let Some(abort_handle) = inner.abort_handle.take() {
                 abort_handle.abort();
             }
+            inner.clear_cache();
         }
         true
     }
@@ -299,10 +607,30 @@ impl PadSrcHandler for InputSelectorPadSrcHandler {
     }
 }
 
+#[derive(Debug)]
+struct PendingSwitch {
+    pad: gst::Pad,
+    stop_time: gst::ClockTime,
+    start_time: Option<gst::ClockTime>,
+}
+
 #[derive(Debug)]
 struct State {
     active_sinkpad: Option<gst::Pad>,
     switched_pad: bool,
+    pending_switch: Option<PendingSwitch>,
+    // Buffers of the new active pad before this are dropped
+    switch_start_time: Option<gst::ClockTime>,
+    last_running_time: Option<gst::ClockTime>,
+    // Highest running time pushed downstream, including the offset
+    highest_running_time: Option<gst::ClockTime>,
+    // Added to the running time of the active pad
+    running_time_offset: gst::ClockTime,
+    // Previous active pad until the first buffer of the new one is forwarded
+    switched_from: Option<Option<gst::Pad>>,
+    active_eos: bool,
+    // Inactive pads waiting for the active pad to progress
+    active_waiters: Vec<oneshot::Sender<()>>,
 }
 
 impl Default for State {
@@ -310,6 +638,14 @@ impl Default for State {
         State {
             active_sinkpad: None,
             switched_pad: true,
+            pending_switch: None,
+            switch_start_time: None,
+            last_running_time: None,
+            highest_running_time: None,
+            running_time_offset: gst::ClockTime::ZERO,
+            switched_from: None,
+            active_eos: false,
+            active_waiters: Vec::new(),
         }
     }
 }
@@ -337,6 +673,98 @@ static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
 });
 
 impl InputSelector {
+    fn switch(
+        &self,
+        pad: gst::Pad,
+        stop_time: Option<gst::ClockTime>,
+        start_time: Option<gst::ClockTime>,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        if !self.pads.lock().unwrap().sink_pads.contains_key(&pad) {
+            gst::warning!(
+                CAT,
+                imp = self,
+                "Can't switch to unknown pad {}",
+                pad.name()
+            );
+            return;
+        }
+
+        gst::debug!(
+            CAT,
+            imp = self,
+            "Switching to {} with stop time {} and start time {}",
+            pad.name(),
+            stop_time.display(),
+            start_time.display()
+        );
+
+        match stop_time {
+            Some(stop_time) if state.active_sinkpad.is_some() => {
+                state.pending_switch = Some(PendingSwitch {
+                    pad,
+                    stop_time,
+                    start_time: start_time.or(Some(stop_time)),
+                });
+            }
+            _ => {
+                state.pending_switch = None;
+                state.switch_start_time = start_time.or(state.last_running_time);
+                let old_pad = state.active_sinkpad.replace(pad.clone());
+                state.switched_pad = true;
+                if old_pad.as_ref() != Some(&pad) {
+                    state.switched_from.get_or_insert(old_pad.clone());
+                }
+                state.active_eos = false;
+                state.active_waiters.clear();
+                drop(state);
+
+                if let Some(old_pad) = old_pad {
+                    if old_pad != pad {
+                        let _ = old_pad.push_event(gst::event::Reconfigure::new());
+                    }
+                }
+                let _ = pad.push_event(gst::event::Reconfigure::new());
+
+                self.obj().notify("active-pad");
+            }
+        }
+    }
+
+    fn pad_switched(
+        &self,
+        old_pad: Option<gst::Pad>,
+        new_pad: &gst::Pad,
+        running_time: Option<gst::ClockTime>,
+    ) {
+        gst::debug!(
+            CAT,
+            imp = self,
+            "Switched from {:?} to {} at {}",
+            old_pad.as_ref().map(|pad| pad.name()),
+            new_pad.name(),
+            running_time.display()
+        );
+
+        let s = gst::Structure::builder("ts-input-selector-pad-switched")
+            .field("old-pad", &old_pad)
+            .field("new-pad", new_pad)
+            .field("running-time", running_time)
+            .build();
+        let _ = self
+            .obj()
+            .post_message(gst::message::Element::builder(s).src(&*self.obj()).build());
+
+        self.obj().emit_by_name::<()>(
+            "pad-switched",
+            &[
+                &old_pad,
+                new_pad,
+                &running_time.map_or(u64::MAX, gst::ClockTime::nseconds),
+            ],
+        );
+    }
+
     fn unprepare(&self) {
         let mut state = self.state.lock().unwrap();
         gst::debug!(CAT, imp = self, "Unpreparing");
@@ -387,12 +815,84 @@ impl ObjectImpl for InputSelector {
                     .readwrite()
                     .mutable_playing()
                     .build(),
+                glib::ParamSpecBoolean::builder("sync-streams")
+                    .nick("Sync Streams")
+                    .blurb("Synchronize inactive streams to the running time of the active stream or to the current clock")
+                    .default_value(DEFAULT_SYNC_STREAMS)
+                    .readwrite()
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecBoolean::builder("cache-buffers")
+                    .nick("Cache Buffers")
+                    .blurb("Cache buffers since the last keyframe on inactive pads and forward them on activation")
+                    .default_value(DEFAULT_CACHE_BUFFERS)
+                    .readwrite()
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecUInt64::builder("cache-buffers-max-duration")
+                    .nick("Cache Buffers Max Duration")
+                    .blurb("Maximum duration of the buffers cached on an inactive pad (in ns)")
+                    .maximum(u64::MAX - 1)
+                    .default_value(DEFAULT_CACHE_BUFFERS_MAX_DURATION.nseconds())
+                    .readwrite()
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("sync-mode", DEFAULT_SYNC_MODE)
+                    .nick("Sync Mode")
+                    .blurb("Behavior in sync-streams mode")
+                    .readwrite()
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt64::builder("running-time-offset")
+                    .nick("Running Time Offset")
+                    .blurb("Offset applied to the running time of the active pad to keep the output running time monotonic (in ns)")
+                    .read_only()
+                    .build(),
             ]
         });
 
         PROPERTIES.as_ref()
     }
 
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: LazyLock<Vec<glib::subclass::Signal>> = LazyLock::new(|| {
+            vec![
+                glib::subclass::Signal::builder("switch")
+                    .param_types([
+                        gst::Pad::static_type(),
+                        u64::static_type(),
+                        u64::static_type(),
+                    ])
+                    .action()
+                    .class_handler(|_, args| {
+                        let element = args[0].get::<super::InputSelector>().expect("signal arg");
+                        let pad = args[1].get::<gst::Pad>().expect("signal arg");
+                        let stop_time =
+                            args[2].get::<Option<gst::ClockTime>>().expect("signal arg");
+                        let start_time =
+                            args[3].get::<Option<gst::ClockTime>>().expect("signal arg");
+                        element.imp().switch(pad, stop_time, start_time);
+                        None
+                    })
+                    .build(),
+                /*
+                 * Emitted when the output actually switched to a new pad, with
+                 * the previous pad, the new pad and the running time of the
+                 * first buffer forwarded from the new pad.
+                 */
+                glib::subclass::Signal::builder("pad-switched")
+                    .param_types([
+                        gst::Pad::static_type(),
+                        gst::Pad::static_type(),
+                        u64::static_type(),
+                    ])
+                    .build(),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
     fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
         match pspec.name() {
             "context" => {
@@ -408,6 +908,23 @@ impl ObjectImpl for InputSelector {
                     value.get::<u32>().expect("type checked upstream").into(),
                 );
             }
+            "sync-streams" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.sync_streams = value.get().expect("type checked upstream");
+            }
+            "sync-mode" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.sync_mode = value.get().expect("type checked upstream");
+            }
+            "cache-buffers" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.cache_buffers = value.get().expect("type checked upstream");
+            }
+            "cache-buffers-max-duration" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.cache_buffers_max_duration =
+                    gst::ClockTime::from_nseconds(value.get().expect("type checked upstream"));
+            }
             "active-pad" => {
                 let pad = value
                     .get::<Option<gst::Pad>>()
@@ -415,11 +932,18 @@ impl ObjectImpl for InputSelector {
                 let mut state = self.state.lock().unwrap();
                 let pads = self.pads.lock().unwrap();
                 let mut old_pad = None;
+                state.pending_switch = None;
+                state.switch_start_time = None;
+                state.active_eos = false;
+                state.active_waiters.clear();
                 if let Some(ref pad) = pad {
                     if pads.sink_pads.contains_key(pad) {
                         old_pad.clone_from(&state.active_sinkpad);
                         state.active_sinkpad = Some(pad.clone());
                         state.switched_pad = true;
+                        if old_pad.as_ref() != Some(pad) {
+                            state.switched_from.get_or_insert(old_pad.clone());
+                        }
                     }
                 } else {
                     state.active_sinkpad = None;
@@ -452,11 +976,31 @@ impl ObjectImpl for InputSelector {
                 let settings = self.settings.lock().unwrap();
                 (settings.context_wait.as_millis() as u32).to_value()
             }
+            "sync-streams" => {
+                let settings = self.settings.lock().unwrap();
+                settings.sync_streams.to_value()
+            }
+            "sync-mode" => {
+                let settings = self.settings.lock().unwrap();
+                settings.sync_mode.to_value()
+            }
+            "cache-buffers" => {
+                let settings = self.settings.lock().unwrap();
+                settings.cache_buffers.to_value()
+            }
+            "cache-buffers-max-duration" => {
+                let settings = self.settings.lock().unwrap();
+                settings.cache_buffers_max_duration.nseconds().to_value()
+            }
             "active-pad" => {
                 let state = self.state.lock().unwrap();
                 let active_pad = state.active_sinkpad.clone();
                 active_pad.to_value()
             }
+            "running-time-offset" => {
+                let state = self.state.lock().unwrap();
+                state.running_time_offset.nseconds().to_value()
+            }
             _ => unimplemented!(),
         }
     }
@@ -489,11 +1033,12 @@ impl ElementImpl for InputSelector {
     fn pad_templates() -> &'static [gst::PadTemplate] {
         static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
             let caps = gst::Caps::new_any();
-            let sink_pad_template = gst::PadTemplate::new(
+            let sink_pad_template = gst::PadTemplate::with_gtype(
                 "sink_%u",
                 gst::PadDirection::Sink,
                 gst::PadPresence::Request,
                 &caps,
+                super::InputSelectorSinkPad::static_type(),
             )
             .unwrap();
 
@@ -544,13 +1089,13 @@ impl ElementImpl for InputSelector {
     ) -> Option<gst::Pad> {
         let mut state = self.state.lock().unwrap();
         let mut pads = self.pads.lock().unwrap();
-        let sink_pad = gst::Pad::builder_from_template(templ)
+        let sink_pad = gst::PadBuilder::<super::InputSelectorSinkPad>::from_template(templ)
             .name(format!("sink_{}", pads.pad_serial).as_str())
             .build();
         pads.pad_serial += 1;
         sink_pad.set_active(true).unwrap();
         self.obj().add_pad(&sink_pad).unwrap();
-        let sink_pad = PadSink::new(sink_pad, InputSelectorPadSinkHandler::default());
+        let sink_pad = PadSink::new(sink_pad.upcast(), InputSelectorPadSinkHandler::default());
         let ret = sink_pad.gst_pad().clone();
 
         if state.active_sinkpad.is_none() {
@@ -576,6 +1121,29 @@ impl ElementImpl for InputSelector {
         self.obj().remove_pad(pad).unwrap();
         drop(pads);
 
+        let mut state = self.state.lock().unwrap();
+        if state
+            .pending_switch
+            .as_ref()
+            .is_some_and(|switch| &switch.pad == pad)
+        {
+            gst::debug!(CAT, imp = self, "Cancelling switch to {}", pad.name());
+            state.pending_switch = None;
+        }
+        if state.active_sinkpad.as_ref() == Some(pad) {
+            gst::debug!(CAT, imp = self, "Active pad {} released", pad.name());
+            state.active_sinkpad = None;
+            state.pending_switch = None;
+            state.switch_start_time = None;
+            state.active_eos = false;
+            // Don't keep inactive pads waiting for the released pad
+            state.active_waiters.clear();
+        }
+        if state.switched_from.as_ref() == Some(&Some(pad.clone())) {
+            state.switched_from = Some(None);
+        }
+        drop(state);
+
         let _ = self
             .obj()
             .post_message(gst::message::Latency::builder().src(&*self.obj()).build());
@@ -585,3 +1153,80 @@ impl ElementImpl for InputSelector {
         Some(gst::SystemClock::obtain())
     }
 }
+
+/// Request sink pad of the input selector.
+///
+/// Buffers received while the pad is inactive are discarded. If `always-ok`
+/// is disabled, the pad returns `NOT_LINKED` for them so that upstream can
+/// stop producing. EOS received on an inactive pad is not forwarded
+/// downstream, whatever the value of `always-ok`.
+#[derive(Debug, Default)]
+pub struct InputSelectorSinkPad {
+    settings: Mutex<SinkPadSettings>,
+}
+
+#[derive(Debug, Clone)]
+struct SinkPadSettings {
+    always_ok: bool,
+}
+
+impl Default for SinkPadSettings {
+    fn default() -> Self {
+        SinkPadSettings {
+            always_ok: DEFAULT_ALWAYS_OK,
+        }
+    }
+}
+
+impl InputSelectorSinkPad {
+    fn always_ok(&self) -> bool {
+        self.settings.lock().unwrap().always_ok
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for InputSelectorSinkPad {
+    const NAME: &'static str = "GstTsInputSelectorSinkPad";
+    type Type = super::InputSelectorSinkPad;
+    type ParentType = gst::Pad;
+}
+
+impl ObjectImpl for InputSelectorSinkPad {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![glib::ParamSpecBoolean::builder("always-ok")
+                .nick("Always OK")
+                .blurb("Make an inactive pad return OK instead of NOT_LINKED")
+                .default_value(DEFAULT_ALWAYS_OK)
+                .readwrite()
+                .mutable_playing()
+                .build()]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        match pspec.name() {
+            "always-ok" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.always_ok = value.get().expect("type checked upstream");
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "always-ok" => {
+                let settings = self.settings.lock().unwrap();
+                settings.always_ok.to_value()
+            }
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl GstObjectImpl for InputSelectorSinkPad {}
+
+impl PadImpl for InputSelectorSinkPad {}
@@ -22,11 +22,32 @@ use gst::prelude::*;
 
 mod imp;
 
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstTsInputSelectorSyncMode")]
+pub enum SyncMode {
+    #[default]
+    #[enum_value(
+        name = "Sync using the current active segment",
+        nick = "active-segment"
+    )]
+    ActiveSegment,
+    #[enum_value(name = "Sync using the clock", nick = "clock")]
+    Clock,
+}
+
 glib::wrapper! {
     pub struct InputSelector(ObjectSubclass<imp::InputSelector>) @extends gst::Element, gst::Object;
 }
 
+glib::wrapper! {
+    pub struct InputSelectorSinkPad(ObjectSubclass<imp::InputSelectorSinkPad>) @extends gst::Pad, gst::Object;
+}
+
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    SyncMode::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+    InputSelectorSinkPad::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+
     gst::Element::register(
         Some(plugin),
         "ts-input-selector",
@@ -0,0 +1,874 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use futures::future::BoxFuture;
+use futures::prelude::*;
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+
+use std::sync::LazyLock;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use crate::runtime::prelude::*;
+use crate::runtime::{Context, PadSink, PadSrc, PadSrcRef, SrcItem, SrcTaskAdapter, Task};
+
+use super::NoConsumerPolicy;
+
+const DEFAULT_CHANNEL: &str = "";
+const DEFAULT_CONTEXT: &str = "";
+const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+const DEFAULT_NO_CONSUMER_POLICY: NoConsumerPolicy = NoConsumerPolicy::Drop;
+const DEFAULT_MAX_BUFFERS: u32 = 200;
+
+static INTER_CHANNELS: LazyLock<Mutex<HashMap<String, Weak<InterChannel>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone)]
+struct SettingsSink {
+    channel: String,
+    no_consumer_policy: NoConsumerPolicy,
+    max_buffers: u32,
+}
+
+impl Default for SettingsSink {
+    fn default() -> Self {
+        SettingsSink {
+            channel: DEFAULT_CHANNEL.into(),
+            no_consumer_policy: DEFAULT_NO_CONSUMER_POLICY,
+            max_buffers: DEFAULT_MAX_BUFFERS,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SettingsSrc {
+    channel: String,
+    context: String,
+    context_wait: Duration,
+}
+
+impl Default for SettingsSrc {
+    fn default() -> Self {
+        SettingsSrc {
+            channel: DEFAULT_CHANNEL.into(),
+            context: DEFAULT_CONTEXT.into(),
+            context_wait: DEFAULT_CONTEXT_WAIT,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum InterItem {
+    Buffer(gst::Buffer),
+    BufferList(gst::BufferList),
+    Event(gst::Event),
+}
+
+impl InterItem {
+    fn is_data(&self) -> bool {
+        !matches!(self, InterItem::Event(..))
+    }
+}
+
+#[derive(Debug)]
+struct ChannelState {
+    have_sink: bool,
+    have_src: bool,
+    no_consumer_policy: NoConsumerPolicy,
+    max_buffers: u32,
+    queue: VecDeque<InterItem>,
+    n_buffers: u32,
+    caps: Option<gst::Caps>,
+    segment: Option<gst::Segment>,
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        ChannelState {
+            have_sink: false,
+            have_src: false,
+            no_consumer_policy: DEFAULT_NO_CONSUMER_POLICY,
+            max_buffers: DEFAULT_MAX_BUFFERS,
+            queue: VecDeque::new(),
+            n_buffers: 0,
+            caps: None,
+            segment: None,
+        }
+    }
+}
+
+impl ChannelState {
+    fn clear(&mut self) {
+        self.queue.clear();
+        self.n_buffers = 0;
+    }
+}
+
+/// Data shared between the `ts-intersink` and `ts-intersrc` using the same channel name.
+///
+/// Either side can create the channel, which lives as long as one of them holds it.
+#[derive(Debug)]
+struct InterChannel {
+    name: String,
+    state: Mutex<ChannelState>,
+    notify_sender: flume::Sender<()>,
+    notify_receiver: flume::Receiver<()>,
+}
+
+impl InterChannel {
+    fn get(name: &str) -> Arc<Self> {
+        let mut channels = INTER_CHANNELS.lock().unwrap();
+
+        if let Some(channel) = channels.get(name).and_then(Weak::upgrade) {
+            return channel;
+        }
+
+        let (notify_sender, notify_receiver) = flume::bounded(1);
+        let channel = Arc::new(InterChannel {
+            name: name.into(),
+            state: Mutex::new(ChannelState::default()),
+            notify_sender,
+            notify_receiver,
+        });
+        channels.insert(name.into(), Arc::downgrade(&channel));
+
+        channel
+    }
+
+    fn attach_sink(&self, settings: &SettingsSink) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.have_sink {
+            return false;
+        }
+
+        state.have_sink = true;
+        state.no_consumer_policy = settings.no_consumer_policy;
+        state.max_buffers = settings.max_buffers;
+
+        true
+    }
+
+    fn detach_sink(&self) {
+        self.state.lock().unwrap().have_sink = false;
+    }
+
+    fn attach_src(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.have_src {
+            return false;
+        }
+
+        state.have_src = true;
+
+        true
+    }
+
+    fn detach_src(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.have_src = false;
+        if state.no_consumer_policy == NoConsumerPolicy::Drop {
+            state.clear();
+        }
+    }
+
+    fn caps(&self) -> Option<gst::Caps> {
+        self.state.lock().unwrap().caps.clone()
+    }
+
+    fn segment(&self) -> Option<gst::Segment> {
+        self.state.lock().unwrap().segment.clone()
+    }
+
+    fn flush(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.clear();
+        state.segment = None;
+    }
+
+    fn enqueue(&self, imp: &InterSink, item: InterItem) {
+        let mut state = self.state.lock().unwrap();
+
+        match item {
+            InterItem::Event(ref event) => match event.view() {
+                gst::EventView::Caps(ev) => state.caps = Some(ev.caps_owned()),
+                gst::EventView::Segment(ev) => state.segment = Some(ev.segment().clone()),
+                _ => (),
+            },
+            _ => {
+                if !state.have_src && state.no_consumer_policy == NoConsumerPolicy::Drop {
+                    gst::trace!(SINK_CAT, imp = imp, "No consumer, dropping {:?}", item);
+                    return;
+                }
+
+                if state.n_buffers >= state.max_buffers {
+                    // Drop the oldest data, but keep the serialized events in order
+                    if let Some(pos) = state.queue.iter().position(InterItem::is_data) {
+                        let old = state.queue.remove(pos).unwrap();
+                        gst::debug!(SINK_CAT, imp = imp, "Channel full, dropping {:?}", old);
+                        state.n_buffers -= 1;
+                    }
+                }
+
+                state.n_buffers += 1;
+            }
+        }
+
+        if !state.have_src && state.no_consumer_policy == NoConsumerPolicy::Drop {
+            // Caps & segment are kept aside and sent to the next consumer
+            return;
+        }
+
+        state.queue.push_back(item);
+        drop(state);
+
+        let _ = self.notify_sender.try_send(());
+    }
+
+    fn pop(&self) -> Option<InterItem> {
+        let mut state = self.state.lock().unwrap();
+
+        let item = state.queue.pop_front()?;
+        if item.is_data() {
+            state.n_buffers -= 1;
+        }
+
+        Some(item)
+    }
+}
+
+impl Drop for InterChannel {
+    fn drop(&mut self) {
+        let mut channels = INTER_CHANNELS.lock().unwrap();
+        // A new channel with the same name might have been created in the meantime
+        if channels
+            .get(&self.name)
+            .is_some_and(|channel| channel.strong_count() == 0)
+        {
+            channels.remove(&self.name);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct InterSinkPadHandler;
+
+impl PadSinkHandler for InterSinkPadHandler {
+    type ElementImpl = InterSink;
+
+    fn sink_chain(
+        self,
+        pad: gst::Pad,
+        elem: super::InterSink,
+        buffer: gst::Buffer,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            gst::log!(SINK_CAT, obj = pad, "Handling {:?}", buffer);
+            elem.imp().enqueue_item(InterItem::Buffer(buffer))
+        }
+        .boxed()
+    }
+
+    fn sink_chain_list(
+        self,
+        pad: gst::Pad,
+        elem: super::InterSink,
+        list: gst::BufferList,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            gst::log!(SINK_CAT, obj = pad, "Handling {:?}", list);
+            elem.imp().enqueue_item(InterItem::BufferList(list))
+        }
+        .boxed()
+    }
+
+    fn sink_event(self, pad: &gst::Pad, _imp: &InterSink, event: gst::Event) -> bool {
+        // Non-serialized events, including flushes, stay in the producer pipeline
+        gst::debug!(SINK_CAT, obj = pad, "Handling non-serialized {:?}", event);
+
+        true
+    }
+
+    fn sink_event_serialized(
+        self,
+        pad: gst::Pad,
+        elem: super::InterSink,
+        event: gst::Event,
+    ) -> BoxFuture<'static, bool> {
+        async move {
+            gst::log!(SINK_CAT, obj = pad, "Handling serialized {:?}", event);
+
+            let imp = elem.imp();
+
+            use gst::EventView;
+            match event.view() {
+                EventView::StreamStart(..) => {
+                    // ts-intersrc sends its own stream-start when it attaches
+                    return true;
+                }
+                EventView::FlushStop(..) => {
+                    if let Some(channel) = imp.channel.lock().unwrap().as_ref() {
+                        channel.flush();
+                    }
+                    return true;
+                }
+                EventView::Eos(..) => {
+                    // The consumer must keep running, waiting for the next producer
+                    let _ = elem.post_message(gst::message::Eos::builder().src(&elem).build());
+                    return true;
+                }
+                _ => (),
+            }
+
+            imp.enqueue_item(InterItem::Event(event)).is_ok()
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug)]
+pub struct InterSink {
+    sink_pad: PadSink,
+    channel: Mutex<Option<Arc<InterChannel>>>,
+    settings: Mutex<SettingsSink>,
+}
+
+static SINK_CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-intersink",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing inter sink"),
+    )
+});
+
+impl InterSink {
+    fn enqueue_item(&self, item: InterItem) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let Some(channel) = self.channel.lock().unwrap().clone() else {
+            gst::debug!(SINK_CAT, imp = self, "Not prepared, dropping {:?}", item);
+            return Err(gst::FlowError::Flushing);
+        };
+
+        channel.enqueue(self, item);
+
+        Ok(gst::FlowSuccess::Ok)
+    }
+
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
+        gst::debug!(SINK_CAT, imp = self, "Preparing");
+
+        let settings = self.settings.lock().unwrap().clone();
+
+        let channel = InterChannel::get(&settings.channel);
+        if !channel.attach_sink(&settings) {
+            return Err(gst::error_msg!(
+                gst::ResourceError::OpenWrite,
+                ["Channel '{}' already has a ts-intersink", settings.channel]
+            ));
+        }
+
+        *self.channel.lock().unwrap() = Some(channel);
+
+        gst::debug!(SINK_CAT, imp = self, "Prepared");
+
+        Ok(())
+    }
+
+    fn unprepare(&self) {
+        gst::debug!(SINK_CAT, imp = self, "Unpreparing");
+
+        if let Some(channel) = self.channel.lock().unwrap().take() {
+            channel.detach_sink();
+        }
+
+        gst::debug!(SINK_CAT, imp = self, "Unprepared");
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for InterSink {
+    const NAME: &'static str = "GstTsInterSink";
+    type Type = super::InterSink;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        Self {
+            sink_pad: PadSink::new(
+                gst::Pad::from_template(&klass.pad_template("sink").unwrap()),
+                InterSinkPadHandler,
+            ),
+            channel: Mutex::new(None),
+            settings: Mutex::new(SettingsSink::default()),
+        }
+    }
+}
+
+impl ObjectImpl for InterSink {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("channel")
+                    .nick("Channel")
+                    .blurb("Name of the channel to share with a ts-intersrc")
+                    .default_value(Some(DEFAULT_CHANNEL))
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default(
+                    "no-consumer-policy",
+                    DEFAULT_NO_CONSUMER_POLICY,
+                )
+                .nick("No Consumer Policy")
+                .blurb("What to do with the data while no ts-intersrc is attached")
+                .mutable_ready()
+                .build(),
+                glib::ParamSpecUInt::builder("max-buffers")
+                    .nick("Max Buffers")
+                    .blurb("Maximum number of buffers to queue, older ones are dropped")
+                    .minimum(1)
+                    .default_value(DEFAULT_MAX_BUFFERS)
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "channel" => {
+                settings.channel = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| DEFAULT_CHANNEL.into());
+            }
+            "no-consumer-policy" => {
+                settings.no_consumer_policy = value.get().expect("type checked upstream");
+            }
+            "max-buffers" => {
+                settings.max_buffers = value.get().expect("type checked upstream");
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "channel" => settings.channel.to_value(),
+            "no-consumer-policy" => settings.no_consumer_policy.to_value(),
+            "max-buffers" => settings.max_buffers.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let obj = self.obj();
+        obj.add_pad(self.sink_pad.gst_pad()).unwrap();
+        obj.set_element_flags(gst::ElementFlags::SINK);
+    }
+}
+
+impl GstObjectImpl for InterSink {}
+
+impl ElementImpl for InterSink {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Thread-sharing inter sink",
+                "Sink/Generic",
+                "Thread-sharing inter-pipeline sink",
+                "agent <agent@local>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+            let caps = gst::Caps::new_any();
+
+            let sink_pad_template = gst::PadTemplate::new(
+                "sink",
+                gst::PadDirection::Sink,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![sink_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        gst::trace!(SINK_CAT, imp = self, "Changing state {:?}", transition);
+
+        match transition {
+            gst::StateChange::NullToReady => {
+                self.prepare().map_err(|err| {
+                    self.post_error_message(err);
+                    gst::StateChangeError
+                })?;
+            }
+            gst::StateChange::ReadyToNull => {
+                self.unprepare();
+            }
+            _ => (),
+        }
+
+        self.parent_change_state(transition)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct InterSrcPadHandler;
+
+impl PadSrcHandler for InterSrcPadHandler {
+    type ElementImpl = InterSrc;
+
+    fn src_event(self, pad: &gst::Pad, imp: &InterSrc, event: gst::Event) -> bool {
+        gst::log!(SRC_CAT, obj = pad, "Handling {:?}", event);
+
+        use gst::EventView;
+        let ret = match event.view() {
+            EventView::FlushStart(..) => imp.task.flush_start().await_maybe_on_context().is_ok(),
+            EventView::FlushStop(..) => imp.task.flush_stop().await_maybe_on_context().is_ok(),
+            // Upstream events can't reach the producer pipeline
+            _ => false,
+        };
+
+        if ret {
+            gst::log!(SRC_CAT, obj = pad, "Handled {:?}", event);
+        } else {
+            gst::log!(SRC_CAT, obj = pad, "Didn't handle {:?}", event);
+        }
+
+        ret
+    }
+
+    fn src_query(self, pad: &gst::Pad, imp: &InterSrc, query: &mut gst::QueryRef) -> bool {
+        gst::log!(SRC_CAT, obj = pad, "Handling {:?}", query);
+
+        use gst::QueryViewMut;
+        let ret = match query.view_mut() {
+            QueryViewMut::Latency(q) => {
+                q.set(true, gst::ClockTime::ZERO, gst::ClockTime::NONE);
+                true
+            }
+            QueryViewMut::Scheduling(q) => {
+                q.set(gst::SchedulingFlags::SEQUENTIAL, 1, -1, 0);
+                q.add_scheduling_modes(&[gst::PadMode::Push]);
+                true
+            }
+            QueryViewMut::Caps(q) => {
+                let caps = pad.current_caps().or_else(|| {
+                    imp.channel
+                        .lock()
+                        .unwrap()
+                        .as_ref()
+                        .and_then(|channel| channel.caps())
+                });
+                let caps = if let Some(ref caps) = caps {
+                    q.filter()
+                        .map(|f| f.intersect_with_mode(caps, gst::CapsIntersectMode::First))
+                        .unwrap_or_else(|| caps.clone())
+                } else {
+                    q.filter()
+                        .map(|f| f.to_owned())
+                        .unwrap_or_else(gst::Caps::new_any)
+                };
+
+                q.set_result(&caps);
+
+                true
+            }
+            _ => false,
+        };
+
+        if ret {
+            gst::log!(SRC_CAT, obj = pad, "Handled {:?}", query);
+        } else {
+            gst::log!(SRC_CAT, obj = pad, "Didn't handle {:?}", query);
+        }
+
+        ret
+    }
+}
+
+#[derive(Debug)]
+struct InterSrcTask {
+    element: super::InterSrc,
+    channel: Arc<InterChannel>,
+}
+
+impl InterSrcTask {
+    fn new(element: super::InterSrc, channel: Arc<InterChannel>) -> Self {
+        InterSrcTask { element, channel }
+    }
+}
+
+impl SrcTaskImpl for InterSrcTask {
+    fn element(&self) -> &gst::Element {
+        self.element.upcast_ref()
+    }
+
+    fn src_pad(&self) -> PadSrcRef<'_> {
+        self.element.imp().src_pad.as_ref()
+    }
+
+    fn caps(&mut self) -> Option<gst::Caps> {
+        // Caps from the producer might have been received before we attached
+        self.channel.caps()
+    }
+
+    fn segment(&mut self) -> gst::Segment {
+        self.channel
+            .segment()
+            .unwrap_or_else(|| gst::FormattedSegment::<gst::format::Time>::new().upcast())
+    }
+
+    fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
+        async move {
+            loop {
+                if let Some(item) = self.channel.pop() {
+                    gst::log!(SRC_CAT, obj = self.element, "Forwarding {:?}", item);
+                    return Ok(match item {
+                        InterItem::Buffer(buffer) => SrcItem::Buffer(buffer),
+                        InterItem::BufferList(list) => SrcItem::BufferList(list),
+                        InterItem::Event(event) => SrcItem::Event(event),
+                    });
+                }
+
+                // The channel holds the sender, so this can't fail
+                let _ = self.channel.notify_receiver.recv_async().await;
+            }
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug)]
+pub struct InterSrc {
+    src_pad: PadSrc,
+    task: Task,
+    channel: Mutex<Option<Arc<InterChannel>>>,
+    settings: Mutex<SettingsSrc>,
+}
+
+static SRC_CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-intersrc",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing inter source"),
+    )
+});
+
+impl TsSrcImpl for InterSrc {
+    fn task(&self) -> &Task {
+        &self.task
+    }
+
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
+        let settings = self.settings.lock().unwrap().clone();
+
+        let ts_ctx = Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::OpenRead,
+                ["Failed to acquire Context: {}", err]
+            )
+        })?;
+
+        let channel = InterChannel::get(&settings.channel);
+        if !channel.attach_src() {
+            return Err(gst::error_msg!(
+                gst::ResourceError::OpenRead,
+                ["Channel '{}' already has a ts-intersrc", settings.channel]
+            ));
+        }
+
+        *self.channel.lock().unwrap() = Some(channel.clone());
+
+        self.task.set_name(self.obj().name().as_str());
+
+        self.task
+            .prepare(
+                SrcTaskAdapter::new(InterSrcTask::new(self.obj().clone(), channel)),
+                ts_ctx,
+            )
+            .block_on()
+            .inspect_err(|_| {
+                if let Some(channel) = self.channel.lock().unwrap().take() {
+                    channel.detach_src();
+                }
+            })?;
+
+        Ok(())
+    }
+
+    fn unprepare(&self) {
+        self.task.unprepare().block_on().unwrap();
+
+        if let Some(channel) = self.channel.lock().unwrap().take() {
+            channel.detach_src();
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for InterSrc {
+    const NAME: &'static str = "GstTsInterSrc";
+    type Type = super::InterSrc;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        Self {
+            src_pad: PadSrc::new(
+                gst::Pad::from_template(&klass.pad_template("src").unwrap()),
+                InterSrcPadHandler,
+            ),
+            task: Task::default(),
+            channel: Mutex::new(None),
+            settings: Mutex::new(SettingsSrc::default()),
+        }
+    }
+}
+
+impl ObjectImpl for InterSrc {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("context")
+                    .nick("Context")
+                    .blurb("Context name to share threads with")
+                    .default_value(Some(DEFAULT_CONTEXT))
+                    .build(),
+                glib::ParamSpecUInt::builder("context-wait")
+                    .nick("Context Wait")
+                    .blurb("Throttle poll loop to run at most once every this many ms")
+                    .maximum(1000)
+                    .default_value(DEFAULT_CONTEXT_WAIT.as_millis() as u32)
+                    .build(),
+                glib::ParamSpecString::builder("channel")
+                    .nick("Channel")
+                    .blurb("Name of the channel to share with a ts-intersink")
+                    .default_value(Some(DEFAULT_CHANNEL))
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "context" => {
+                settings.context = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| "".into());
+            }
+            "context-wait" => {
+                settings.context_wait = Duration::from_millis(
+                    value.get::<u32>().expect("type checked upstream").into(),
+                );
+            }
+            "channel" => {
+                settings.channel = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| DEFAULT_CHANNEL.into());
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "context" => settings.context.to_value(),
+            "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
+            "channel" => settings.channel.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let obj = self.obj();
+        obj.add_pad(self.src_pad.gst_pad()).unwrap();
+        obj.set_element_flags(gst::ElementFlags::SOURCE);
+    }
+}
+
+impl GstObjectImpl for InterSrc {}
+
+impl ElementImpl for InterSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Thread-sharing inter source",
+                "Source/Generic",
+                "Thread-sharing inter-pipeline source",
+                "agent <agent@local>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+            let caps = gst::Caps::new_any();
+
+            let src_pad_template = gst::PadTemplate::new(
+                "src",
+                gst::PadDirection::Src,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![src_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        self.src_change_state(transition)
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use gst::glib;
+use gst::prelude::*;
+
+mod imp;
+
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstTsInterSinkNoConsumerPolicy")]
+pub enum NoConsumerPolicy {
+    #[default]
+    #[enum_value(
+        name = "Drop: discard the data while no ts-intersrc is attached",
+        nick = "drop"
+    )]
+    Drop,
+    #[enum_value(
+        name = "Keep: queue the data until a ts-intersrc attaches",
+        nick = "keep"
+    )]
+    Keep,
+}
+
+glib::wrapper! {
+    pub struct InterSink(ObjectSubclass<imp::InterSink>) @extends gst::Element, gst::Object;
+}
+
+glib::wrapper! {
+    pub struct InterSrc(ObjectSubclass<imp::InterSrc>) @extends gst::Element, gst::Object;
+}
+
+pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    NoConsumerPolicy::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+
+    gst::Element::register(
+        Some(plugin),
+        "ts-intersink",
+        gst::Rank::NONE,
+        InterSink::static_type(),
+    )?;
+    gst::Element::register(
+        Some(plugin),
+        "ts-intersrc",
+        gst::Rank::NONE,
+        InterSrc::static_type(),
+    )
+}
@@ -67,7 +67,6 @@ extern "C" {
     pub fn ts_rtp_jitter_buffer_get_delay(jbuf: *mut RTPJitterBuffer) -> GstClockTime;
     pub fn ts_rtp_jitter_buffer_set_delay(jbuf: *mut RTPJitterBuffer, delay: GstClockTime);
     pub fn ts_rtp_jitter_buffer_set_clock_rate(jbuf: *mut RTPJitterBuffer, clock_rate: c_uint);
-    #[allow(dead_code)]
     pub fn ts_rtp_jitter_buffer_get_clock_rate(jbuf: *mut RTPJitterBuffer) -> c_uint;
     pub fn ts_rtp_jitter_buffer_reset_skew(jbuf: *mut RTPJitterBuffer);
 
@@ -97,6 +96,19 @@ extern "C" {
         percent: *mut c_int,
     ) -> *mut RTPJitterBufferItem;
     pub fn ts_rtp_jitter_buffer_peek(jbuf: *mut RTPJitterBuffer) -> *mut RTPJitterBufferItem;
+    pub fn ts_rtp_jitter_buffer_get_ts_diff(jbuf: *mut RTPJitterBuffer) -> c_uint;
+    pub fn ts_rtp_jitter_buffer_is_buffering(jbuf: *mut RTPJitterBuffer) -> gboolean;
+    pub fn ts_rtp_jitter_buffer_can_fast_start(
+        jbuf: *mut RTPJitterBuffer,
+        num_packet: c_int,
+    ) -> gboolean;
+    pub fn ts_rtp_jitter_buffer_get_sync(
+        jbuf: *mut RTPJitterBuffer,
+        rtptime: *mut u64,
+        timestamp: *mut u64,
+        clock_rate: *mut c_uint,
+        last_rtptime: *mut u64,
+    );
 
     pub fn ts_gst_rtp_packet_rate_ctx_reset(ctx: *mut RTPPacketRateCtx, clock_rate: c_int);
     pub fn ts_gst_rtp_packet_rate_ctx_update(
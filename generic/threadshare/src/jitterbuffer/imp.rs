@@ -29,21 +29,41 @@ use gst_rtp::RTPBuffer;
 use std::sync::LazyLock;
 
 use std::cmp::Ordering;
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::mem;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::runtime::prelude::*;
 use crate::runtime::{self, Context, PadSink, PadSrc, Task};
 
-use super::jitterbuffer::{RTPJitterBuffer, RTPJitterBufferItem, RTPPacketRateCtx};
+use super::jitterbuffer::{
+    RTPJitterBuffer, RTPJitterBufferItem, RTPJitterBufferMode, RTPPacketRateCtx,
+};
+use super::JitterBufferMode;
 
 const DEFAULT_LATENCY: gst::ClockTime = gst::ClockTime::from_mseconds(200);
-const DEFAULT_DO_LOST: bool = false;
+const DEFAULT_DO_LOST: bool = true;
 const DEFAULT_MAX_DROPOUT_TIME: u32 = 60000;
 const DEFAULT_MAX_MISORDER_TIME: u32 = 2000;
+const DEFAULT_DO_RETRANSMISSION: bool = false;
+const DEFAULT_RTX_DELAY: i32 = -1;
+const DEFAULT_RTX_RETRY_TIMEOUT: i32 = -1;
+const DEFAULT_RTX_RETRY_PERIOD: i32 = -1;
+const DEFAULT_AUTO_RTX_DELAY: gst::ClockTime = gst::ClockTime::from_mseconds(20);
+const DEFAULT_AUTO_RTX_TIMEOUT: gst::ClockTime = gst::ClockTime::from_mseconds(40);
+const DEFAULT_DROP_ON_LATENCY: bool = false;
+const DEFAULT_MAX_RTCP_RTP_TIME_DIFF: i32 = 1000;
+const DEFAULT_FASTSTART_MIN_PACKETS: u32 = 0;
+const DEFAULT_MODE: JitterBufferMode = JitterBufferMode::Slave;
+const DEFAULT_ADD_REFERENCE_TIMESTAMP_META: bool = false;
+const DEFAULT_REFERENCE_TIMESTAMP_CAPS: &str = "timestamp/x-ntp";
+const DEFAULT_TS_OFFSET: i64 = 0;
+const DROP_WARNING_INTERVAL: Duration = Duration::from_secs(1);
+const LOSS_MESSAGE_INTERVAL: Duration = Duration::from_secs(1);
 const DEFAULT_CONTEXT: &str = "";
 const DEFAULT_CONTEXT_WAIT: gst::ClockTime = gst::ClockTime::ZERO;
 
@@ -53,6 +73,17 @@ struct Settings {
     do_lost: bool,
     max_dropout_time: u32,
     max_misorder_time: u32,
+    do_retransmission: bool,
+    rtx_delay: i32,
+    rtx_retry_timeout: i32,
+    rtx_retry_period: i32,
+    drop_on_latency: bool,
+    max_rtcp_rtp_time_diff: i32,
+    faststart_min_packets: u32,
+    mode: JitterBufferMode,
+    add_reference_timestamp_meta: bool,
+    reference_timestamp_caps: gst::Caps,
+    ts_offset: i64,
     context: String,
     context_wait: gst::ClockTime,
 }
@@ -64,12 +95,55 @@ impl Default for Settings {
             do_lost: DEFAULT_DO_LOST,
             max_dropout_time: DEFAULT_MAX_DROPOUT_TIME,
             max_misorder_time: DEFAULT_MAX_MISORDER_TIME,
+            do_retransmission: DEFAULT_DO_RETRANSMISSION,
+            rtx_delay: DEFAULT_RTX_DELAY,
+            rtx_retry_timeout: DEFAULT_RTX_RETRY_TIMEOUT,
+            rtx_retry_period: DEFAULT_RTX_RETRY_PERIOD,
+            drop_on_latency: DEFAULT_DROP_ON_LATENCY,
+            max_rtcp_rtp_time_diff: DEFAULT_MAX_RTCP_RTP_TIME_DIFF,
+            faststart_min_packets: DEFAULT_FASTSTART_MIN_PACKETS,
+            mode: DEFAULT_MODE,
+            add_reference_timestamp_meta: DEFAULT_ADD_REFERENCE_TIMESTAMP_META,
+            reference_timestamp_caps: gst::Caps::new_empty_simple(DEFAULT_REFERENCE_TIMESTAMP_CAPS),
+            ts_offset: DEFAULT_TS_OFFSET,
             context: DEFAULT_CONTEXT.into(),
             context_wait: DEFAULT_CONTEXT_WAIT,
         }
     }
 }
 
+impl Settings {
+    // -1 means automatic for all the rtx settings, as in rtpjitterbuffer
+
+    fn rtx_delay(&self, packet_spacing: gst::ClockTime) -> gst::ClockTime {
+        if self.rtx_delay >= 0 {
+            gst::ClockTime::from_mseconds(self.rtx_delay as u64)
+        } else if !packet_spacing.is_zero() {
+            packet_spacing / 2
+        } else {
+            DEFAULT_AUTO_RTX_DELAY
+        }
+    }
+
+    fn rtx_retry_timeout(&self, avg_rtt: gst::ClockTime) -> gst::ClockTime {
+        if self.rtx_retry_timeout >= 0 {
+            gst::ClockTime::from_mseconds(self.rtx_retry_timeout as u64)
+        } else if !avg_rtt.is_zero() {
+            2 * avg_rtt
+        } else {
+            DEFAULT_AUTO_RTX_TIMEOUT
+        }
+    }
+
+    fn rtx_retry_period(&self, avg_rtt: gst::ClockTime) -> gst::ClockTime {
+        if self.rtx_retry_period >= 0 {
+            gst::ClockTime::from_mseconds(self.rtx_retry_period as u64)
+        } else {
+            self.latency.saturating_sub(self.rtx_retry_timeout(avg_rtt))
+        }
+    }
+}
+
 #[derive(Eq)]
 struct GapPacket {
     buffer: gst::Buffer,
@@ -117,6 +191,24 @@ struct SinkHandlerInner {
 
     last_in_seqnum: Option<u16>,
     last_rtptime: Option<u32>,
+
+    // Arrival time and rtptime of the last in order packet, for the jitter
+    jitter_dts: Option<gst::ClockTime>,
+    jitter_rtptime: Option<u32>,
+
+    // Highest seqnum received so far, used to detect gaps for retransmission
+    highest_in_seqnum: Option<u16>,
+    highest_in_pts: Option<gst::ClockTime>,
+
+    // Packets dropped because of drop-on-latency since the last warning
+    num_dropped_since_warning: u64,
+    last_drop_warning: Option<Instant>,
+    pending_drop_warning: Option<u64>,
+
+    // Resynchronization to be notified once the buffers are handled
+    pending_resync: Option<gst::Structure>,
+    // Last buffering percent in buffer mode, to be posted
+    pending_buffering: Option<i32>,
 }
 
 impl Default for SinkHandlerInner {
@@ -129,6 +221,15 @@ impl Default for SinkHandlerInner {
             last_pt: None,
             last_in_seqnum: None,
             last_rtptime: None,
+            jitter_dts: None,
+            jitter_rtptime: None,
+            highest_in_seqnum: None,
+            highest_in_pts: None,
+            num_dropped_since_warning: 0,
+            last_drop_warning: None,
+            pending_drop_warning: None,
+            pending_resync: None,
+            pending_buffering: None,
         }
     }
 }
@@ -160,9 +261,20 @@ impl SinkHandler {
         state.earliest_pts = None;
         state.earliest_seqnum = None;
 
+        state.rtx_timers.clear();
+        state.lost_timers.clear();
+
+        state.faststart_remaining = 0;
+
         inner.ips_rtptime = None;
         inner.ips_pts = None;
 
+        inner.jitter_dts = None;
+        inner.jitter_rtptime = None;
+
+        inner.highest_in_seqnum = None;
+        inner.highest_in_pts = None;
+
         mem::take(&mut inner.gap_packets)
     }
 
@@ -204,14 +316,39 @@ impl SinkHandler {
             gst::debug!(CAT, imp = jb, "Caps 'clock-rate' <= 0");
             return Err(gst::FlowError::Error);
         }
-        state.clock_rate = Some(clock_rate as u32);
 
-        inner.packet_rate_ctx.reset(clock_rate);
-        state.jbuf.set_clock_rate(clock_rate as u32);
+        state.clock_base = s.get::<u32>("clock-base").ok();
+
+        state.pt_map.insert(pt, clock_rate as u32);
+        self.set_clock_rate(inner, state, jb, clock_rate as u32);
 
         Ok(gst::FlowSuccess::Ok)
     }
 
+    fn set_clock_rate(
+        &self,
+        inner: &mut SinkHandlerInner,
+        state: &mut State,
+        jb: &JitterBuffer,
+        clock_rate: u32,
+    ) {
+        if state.jbuf.clock_rate() != clock_rate {
+            gst::debug!(CAT, imp = jb, "Clock rate changed to {}", clock_rate);
+
+            // rtptime based measurements don't make sense with the new clock rate
+            inner.ips_rtptime = None;
+            inner.ips_pts = None;
+            inner.jitter_dts = None;
+            inner.jitter_rtptime = None;
+        }
+
+        state.clock_rate = Some(clock_rate);
+
+        inner.packet_rate_ctx.reset(clock_rate as i32);
+        // Resets the skew, and hence recomputes the timestamps, if the clock rate changed
+        state.jbuf.set_clock_rate(clock_rate);
+    }
+
     fn calculate_packet_spacing(
         &self,
         inner: &mut SinkHandlerInner,
@@ -246,6 +383,61 @@ impl SinkHandler {
         }
     }
 
+    /// Checks whether the time elapsed since the previous packet is too large
+    /// to be a packet spacing, e.g. because the sender paused the stream while
+    /// keeping its seqnums going.
+    fn is_timestamp_jump(
+        &self,
+        inner: &SinkHandlerInner,
+        state: &State,
+        latency: gst::ClockTime,
+        pts: Option<gst::ClockTime>,
+    ) -> bool {
+        if state.packet_spacing.is_zero() {
+            return false;
+        }
+
+        pts.opt_checked_sub(inner.ips_pts)
+            .ok()
+            .flatten()
+            .is_some_and(|diff| diff > latency.max(4 * state.packet_spacing))
+    }
+
+    fn calculate_jitter(
+        &self,
+        inner: &mut SinkHandlerInner,
+        jb: &JitterBuffer,
+        dts: Option<gst::ClockTime>,
+        rtptime: u32,
+        clock_rate: u32,
+    ) {
+        let Some(dts) = dts else {
+            return;
+        };
+
+        let dts_diff = inner.jitter_dts.map_or(gst::ClockTime::ZERO, |last_dts| {
+            dts.saturating_sub(last_dts)
+        });
+        let rtp_diff = inner
+            .jitter_rtptime
+            .map_or(0, |last_rtptime| rtptime.wrapping_sub(last_rtptime));
+
+        inner.jitter_dts = Some(dts);
+        inner.jitter_rtptime = Some(rtptime);
+
+        let rtp_diff = (rtp_diff as u64)
+            .mul_div_floor(*gst::ClockTime::SECOND, clock_rate as u64)
+            .map_or(gst::ClockTime::ZERO, gst::ClockTime::from_nseconds);
+
+        let diff = if dts_diff > rtp_diff {
+            dts_diff - rtp_diff
+        } else {
+            rtp_diff - dts_diff
+        };
+
+        jb.stats.update_jitter(diff);
+    }
+
     fn handle_big_gap_buffer(
         &self,
         inner: &mut SinkHandlerInner,
@@ -304,6 +496,178 @@ impl SinkHandler {
         reset
     }
 
+    fn schedule_gap_timers(
+        &self,
+        inner: &SinkHandlerInner,
+        state: &mut State,
+        jb: &JitterBuffer,
+        highest_in_seqnum: u16,
+        gap: u16,
+        pts: Option<gst::ClockTime>,
+        max_rtx_requests: u32,
+    ) {
+        let (Some(highest_in_pts), Some(pts)) = (inner.highest_in_pts, pts) else {
+            return;
+        };
+
+        let (latency, do_retransmission, rtx_delay) = {
+            let settings = jb.settings.lock().unwrap();
+            (
+                settings.latency,
+                settings.do_retransmission,
+                settings.rtx_delay(state.packet_spacing),
+            )
+        };
+
+        let now = jb.obj().current_running_time();
+        let spacing = pts.saturating_sub(highest_in_pts) / gap as u64;
+
+        for i in 1..gap {
+            let seqnum = highest_in_seqnum.wrapping_add(i);
+            let expected_pts = highest_in_pts + spacing * i as u64;
+            let deadline = expected_pts + latency;
+
+            // Packets which are already too late are declared lost in one go
+            let too_late = now.is_some_and(|now| deadline <= now);
+            match state.lost_timers.last_mut() {
+                Some(timer)
+                    if too_late
+                        && timer.deadline <= deadline
+                        && timer.last_seqnum().wrapping_add(1) == seqnum =>
+                {
+                    timer.num_packets += 1;
+                }
+                _ => {
+                    gst::log!(
+                        CAT,
+                        imp = jb,
+                        "Scheduling lost timer for #{} at {}",
+                        seqnum,
+                        deadline,
+                    );
+
+                    state.lost_timers.push(LostTimer {
+                        seqnum,
+                        num_packets: 1,
+                        timestamp: expected_pts,
+                        spacing,
+                        deadline,
+                    });
+                }
+            }
+
+            // No point in requesting a packet which would be too late anyway
+            if !do_retransmission || too_late {
+                continue;
+            }
+
+            // Only request the most recent packets of large gaps,
+            // the older ones couldn't be received within the latency
+            let num_newer = (gap - i) as u32;
+            if num_newer > max_rtx_requests {
+                if num_newer == max_rtx_requests + 1 {
+                    gst::debug!(
+                        CAT,
+                        imp = jb,
+                        "Gap too large, not requesting retransmission up to #{}",
+                        seqnum,
+                    );
+                }
+                continue;
+            }
+
+            if state.rtx_timers.contains_key(&seqnum) {
+                continue;
+            }
+
+            gst::debug!(
+                CAT,
+                imp = jb,
+                "Scheduling retransmission request for #{} expected at {}",
+                seqnum,
+                expected_pts,
+            );
+
+            state.rtx_timers.insert(
+                seqnum,
+                RtxTimer {
+                    seqnum,
+                    expected_pts,
+                    delay: rtx_delay,
+                    timeout: expected_pts + rtx_delay,
+                    num_requests: 0,
+                    first_request: None,
+                    last_request: None,
+                },
+            );
+        }
+    }
+
+    fn drop_on_latency(
+        &self,
+        inner: &mut SinkHandlerInner,
+        state: &mut State,
+        jb: &JitterBuffer,
+        latency: gst::ClockTime,
+    ) {
+        let Some(clock_rate) = state.clock_rate else {
+            return;
+        };
+
+        let latency_ts = latency.mseconds() * clock_rate as u64 / 1000;
+        if latency_ts == 0 {
+            return;
+        }
+
+        let mut num_dropped = 0;
+        while state.jbuf.ts_diff() as u64 >= latency_ts {
+            let (jb_item, _) = state.jbuf.pop();
+            let Some(jb_item) = jb_item else {
+                break;
+            };
+
+            gst::debug!(
+                CAT,
+                imp = jb,
+                "Queue full, dropping old packet {:?}",
+                jb_item.seqnum()
+            );
+
+            // Don't consider the dropped packets as lost
+            if let Some(seq) = jb_item.seqnum() {
+                state.last_popped_seqnum = Some(seq);
+                state.clear_timers_until(seq);
+            }
+            if jb_item.pts().is_some() {
+                state.last_popped_pts = jb_item.pts();
+            }
+
+            num_dropped += 1;
+        }
+
+        if num_dropped == 0 {
+            return;
+        }
+
+        state.discont = true;
+        jb.stats
+            .num_dropped
+            .fetch_add(num_dropped, AtomicOrdering::Relaxed);
+
+        let (earliest_pts, earliest_seqnum) = state.jbuf.find_earliest();
+        state.earliest_pts = earliest_pts;
+        state.earliest_seqnum = earliest_seqnum;
+
+        inner.num_dropped_since_warning += num_dropped;
+        if inner
+            .last_drop_warning
+            .map_or(true, |last| last.elapsed() >= DROP_WARNING_INTERVAL)
+        {
+            inner.last_drop_warning = Some(Instant::now());
+            inner.pending_drop_warning = Some(mem::take(&mut inner.num_dropped_since_warning));
+        }
+    }
+
     fn store(
         &self,
         inner: &mut SinkHandlerInner,
@@ -313,18 +677,25 @@ impl SinkHandler {
     ) -> Result<gst::FlowSuccess, gst::FlowError> {
         let mut state = jb.state.lock().unwrap();
 
-        let (max_misorder_time, max_dropout_time) = {
+        let (max_misorder_time, max_dropout_time, drop_on_latency, latency, faststart_min_packets) = {
             let settings = jb.settings.lock().unwrap();
-            (settings.max_misorder_time, settings.max_dropout_time)
+            (
+                settings.max_misorder_time,
+                settings.max_dropout_time,
+                settings.drop_on_latency,
+                settings.latency,
+                settings.faststart_min_packets,
+            )
         };
 
-        let (seq, rtptime, pt) = {
+        let (seq, rtptime, pt, ssrc) = {
             let rtp_buffer =
                 RTPBuffer::from_buffer_readable(&buffer).map_err(|_| gst::FlowError::Error)?;
             (
                 rtp_buffer.seq(),
                 rtp_buffer.timestamp(),
                 rtp_buffer.payload_type(),
+                rtp_buffer.ssrc(),
             )
         };
 
@@ -343,6 +714,8 @@ impl SinkHandler {
 
         let element = jb.obj();
 
+        state.last_ssrc = Some(ssrc);
+
         if dts.is_none() {
             dts = pts;
         } else if pts.is_none() {
@@ -369,7 +742,10 @@ impl SinkHandler {
 
             gst::debug!(CAT, obj = pad, "New payload type: {}", pt);
 
-            if let Some(caps) = pad.current_caps() {
+            if let Some(&clock_rate) = state.pt_map.get(&pt) {
+                gst::debug!(CAT, obj = pad, "Using cached clock rate {}", clock_rate);
+                self.set_clock_rate(inner, &mut state, jb, clock_rate);
+            } else if let Some(caps) = pad.current_caps() {
                 /* Ignore errors at this point, as we want to emit request-pt-map */
                 let _ = self.parse_caps(inner, &mut state, jb, &caps, pt);
             }
@@ -396,6 +772,8 @@ impl SinkHandler {
 
         let max_dropout = inner.packet_rate_ctx.max_dropout(max_dropout_time as i32);
         let max_misorder = inner.packet_rate_ctx.max_misorder(max_misorder_time as i32);
+        // Packets expected within the latency, older missing packets can't be recovered
+        let max_rtx_requests = inner.packet_rate_ctx.max_dropout(latency.mseconds() as i32);
 
         pts = state
             .jbuf
@@ -414,11 +792,52 @@ impl SinkHandler {
         if let Some(last_in_seqnum) = inner.last_in_seqnum {
             let gap = gst_rtp::compare_seqnum(last_in_seqnum, seq);
             if gap == 1 {
+                if self.is_timestamp_jump(inner, &state, latency, pts) {
+                    gst::info!(
+                        CAT,
+                        imp = jb,
+                        "Timestamp jump from #{} to #{}, restarting estimations",
+                        last_in_seqnum,
+                        seq
+                    );
+                    inner.ips_pts = None;
+                    inner.ips_rtptime = None;
+                    inner.jitter_dts = None;
+                    inner.jitter_rtptime = None;
+                }
+
                 self.calculate_packet_spacing(inner, &mut state, rtptime, pts);
+                if let Some(clock_rate) = state.clock_rate {
+                    self.calculate_jitter(inner, jb, dts, rtptime, clock_rate);
+                }
             } else {
                 if (gap != -1 && gap < -(max_misorder as i32)) || (gap >= max_dropout as i32) {
                     let reset = self.handle_big_gap_buffer(inner, jb, buffer, pt);
                     if reset {
+                        let reason = if gap >= max_dropout as i32 {
+                            "dropout"
+                        } else {
+                            "misorder"
+                        };
+                        let first_seqnum = inner.gap_packets.first().map_or(seq, |p| p.seq);
+
+                        gst::info!(
+                            CAT,
+                            imp = jb,
+                            "Resyncing on {} from #{} to #{}",
+                            reason,
+                            last_in_seqnum,
+                            first_seqnum
+                        );
+
+                        inner.pending_resync = Some(
+                            gst::Structure::builder("ts-jitterbuffer-resync")
+                                .field("reason", reason)
+                                .field("last-seqnum", last_in_seqnum as u32)
+                                .field("seqnum", first_seqnum as u32)
+                                .build(),
+                        );
+
                         // Handle reset in `enqueue_item` to avoid recursion
                         return Err(gst::FlowError::CustomError);
                     } else {
@@ -436,7 +855,7 @@ impl SinkHandler {
             let gap = gst_rtp::compare_seqnum(last_popped_seqnum, seq);
 
             if gap <= 0 {
-                state.stats.num_late += 1;
+                jb.stats.num_late.fetch_add(1, AtomicOrdering::Relaxed);
                 gst::debug!(CAT, imp = jb, "Dropping late {}", seq);
                 return Ok(gst::FlowSuccess::Ok);
             }
@@ -444,19 +863,71 @@ impl SinkHandler {
 
         inner.last_in_seqnum = Some(seq);
 
+        state.remove_lost_timer(seq);
+
+        if let Some(timer) = state.rtx_timers.remove(&seq) {
+            if let Some(last_request) = timer.last_request {
+                let rtt = element
+                    .current_running_time()
+                    .opt_checked_sub(last_request)
+                    .ok()
+                    .flatten();
+                jb.stats.update_rtx_success(timer.num_requests, rtt);
+
+                gst::debug!(
+                    CAT,
+                    imp = jb,
+                    "Recovered #{} after {} retransmission request(s)",
+                    seq,
+                    timer.num_requests
+                );
+            }
+        }
+
+        if let Some(highest_in_seqnum) = inner.highest_in_seqnum {
+            let gap = gst_rtp::compare_seqnum(highest_in_seqnum, seq);
+            if gap > 0 {
+                if gap > 1 {
+                    self.schedule_gap_timers(
+                        inner,
+                        &mut state,
+                        jb,
+                        highest_in_seqnum,
+                        gap as u16,
+                        pts,
+                        max_rtx_requests,
+                    );
+                }
+
+                inner.highest_in_seqnum = Some(seq);
+                inner.highest_in_pts = pts;
+            }
+        } else {
+            inner.highest_in_seqnum = Some(seq);
+            inner.highest_in_pts = pts;
+        }
+
         let jb_item = if estimated_dts {
             RTPJitterBufferItem::new(buffer, gst::ClockTime::NONE, pts, Some(seq), rtptime)
         } else {
             RTPJitterBufferItem::new(buffer, dts, pts, Some(seq), rtptime)
         };
 
-        let (success, _, _) = state.jbuf.insert(jb_item);
+        let (success, _, percent) = state.jbuf.insert(jb_item);
 
         if !success {
             /* duplicate */
+            jb.stats
+                .num_duplicates
+                .fetch_add(1, AtomicOrdering::Relaxed);
+            gst::debug!(CAT, imp = jb, "Dropping duplicate {}", seq);
             return Ok(gst::FlowSuccess::Ok);
         }
 
+        if percent != -1 {
+            inner.pending_buffering = Some(percent);
+        }
+
         if Some(rtptime) == inner.last_rtptime {
             state.equidistant -= 2;
         } else {
@@ -472,7 +943,7 @@ impl SinkHandler {
             (Some(earliest_pts), Some(pts)) if pts < earliest_pts => true,
             (Some(earliest_pts), Some(pts)) if pts == earliest_pts => state
                 .earliest_seqnum
-                .is_some_and(|earliest_seqnum| seq > earliest_seqnum),
+                .is_some_and(|earliest_seqnum| gst_rtp::compare_seqnum(earliest_seqnum, seq) > 0),
             _ => false,
         };
 
@@ -481,6 +952,26 @@ impl SinkHandler {
             state.earliest_seqnum = Some(seq);
         }
 
+        // Nothing pushed yet: don't wait for the latency
+        // if enough consecutive packets are already there
+        if faststart_min_packets > 0
+            && state.last_popped_seqnum.is_none()
+            && state.faststart_remaining == 0
+            && state.jbuf.can_fast_start(faststart_min_packets)
+        {
+            gst::debug!(
+                CAT,
+                obj = pad,
+                "Fast starting with {} consecutive packets",
+                faststart_min_packets
+            );
+            state.faststart_remaining = faststart_min_packets;
+        }
+
+        if drop_on_latency {
+            self.drop_on_latency(inner, &mut state, jb, latency);
+        }
+
         gst::log!(CAT, obj = pad, "Stored buffer");
 
         Ok(gst::FlowSuccess::Ok)
@@ -499,11 +990,14 @@ impl SinkHandler {
             buffers.push_back(buf);
         }
 
+        let mut resyncs = Vec::new();
+
         // This is to avoid recursion with `store`, `reset` and `enqueue_item`
         while let Some(buf) = buffers.pop_front() {
             if let Err(err) = self.store(&mut inner, &pad, jb, buf) {
                 match err {
                     gst::FlowError::CustomError => {
+                        resyncs.extend(inner.pending_resync.take());
                         for gap_packet in self.reset(&mut inner, jb) {
                             buffers.push_back(gap_packet.buffer);
                         }
@@ -513,17 +1007,44 @@ impl SinkHandler {
             }
         }
 
+        let element = jb.obj();
+        for resync in resyncs {
+            let _ = element.post_message(
+                gst::message::Element::builder(resync)
+                    .src(&*element)
+                    .build(),
+            );
+        }
+
+        if let Some(percent) = inner.pending_buffering.take() {
+            let _ = element.post_message(
+                gst::message::Buffering::builder(percent)
+                    .src(&*element)
+                    .build(),
+            );
+        }
+
+        if let Some(num_dropped) = inner.pending_drop_warning.take() {
+            gst::element_imp_warning!(
+                jb,
+                gst::ResourceError::NoSpaceLeft,
+                ("Latency exceeded"),
+                [
+                    "Dropped {} packets to keep within the configured latency",
+                    num_dropped
+                ]
+            );
+        }
+
         let mut state = jb.state.lock().unwrap();
 
-        let (latency, context_wait) = {
-            let settings = jb.settings.lock().unwrap();
-            (settings.latency, settings.context_wait)
-        };
+        let latency = jb.settings.lock().unwrap().latency;
 
         // Reschedule if needed
-        let (_, next_wakeup) =
-            jb.src_pad_handler
-                .next_wakeup(&jb.obj(), &state, latency, context_wait);
+        let (now, next_wakeup) = jb.src_pad_handler.next_wakeup(&jb.obj(), &state, latency);
+        let next_wakeup = jb
+            .src_pad_handler
+            .with_timers_wakeup(&state, now, next_wakeup);
         if let Some((next_wakeup, _)) = next_wakeup {
             if let Some((previous_next_wakeup, ref abort_handle)) = state.wait_handle {
                 if previous_next_wakeup.is_none()
@@ -541,7 +1062,17 @@ impl SinkHandler {
                 }
             }
         }
-        state.last_res
+
+        // A sender report might have been waiting for the first packets
+        let res = state.last_res;
+        let sync = jb.handle_sync(&mut state);
+        drop(state);
+
+        if let Some(sync) = sync {
+            element.emit_by_name::<()>("handle-sync", &[&sync]);
+        }
+
+        res
     }
 }
 
@@ -636,12 +1167,135 @@ impl PadSinkHandler for SinkHandler {
     }
 }
 
+#[derive(Clone, Default)]
+struct RtcpSinkHandler;
+
+impl PadSinkHandler for RtcpSinkHandler {
+    type ElementImpl = JitterBuffer;
+
+    fn sink_chain(
+        self,
+        pad: gst::Pad,
+        elem: super::JitterBuffer,
+        buffer: gst::Buffer,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            gst::log!(CAT, obj = pad, "Handling RTCP {:?}", buffer);
+            elem.imp().handle_rtcp(buffer)
+        }
+        .boxed()
+    }
+
+    fn sink_event(self, pad: &gst::Pad, _jb: &JitterBuffer, event: gst::Event) -> bool {
+        gst::log!(CAT, obj = pad, "Dropping RTCP {:?}", event);
+        true
+    }
+
+    fn sink_event_serialized(
+        self,
+        pad: gst::Pad,
+        _elem: super::JitterBuffer,
+        event: gst::Event,
+    ) -> BoxFuture<'static, bool> {
+        async move {
+            gst::log!(CAT, obj = pad, "Dropping serialized RTCP {:?}", event);
+            true
+        }
+        .boxed()
+    }
+}
+
+/// Returns the SSRC, NTP time and RTP time of the sender report starting `buffer`.
+fn parse_sender_report(buffer: &gst::BufferRef) -> Option<(u32, u64, u32)> {
+    const RTCP_SR_MIN_SIZE: usize = 28;
+    const RTCP_TYPE_SR: u8 = 200;
+
+    let map = buffer.map_readable().ok()?;
+    let data = map.as_slice();
+
+    if data.len() < RTCP_SR_MIN_SIZE || data[0] >> 6 != 2 || data[1] != RTCP_TYPE_SR {
+        return None;
+    }
+
+    let length = (u16::from_be_bytes([data[2], data[3]]) as usize + 1) * 4;
+    if length < RTCP_SR_MIN_SIZE || length > data.len() {
+        return None;
+    }
+
+    let ssrc = u32::from_be_bytes(data[4..8].try_into().unwrap());
+    let ntptime = u64::from_be_bytes(data[8..16].try_into().unwrap());
+    let rtptime = u32::from_be_bytes(data[16..20].try_into().unwrap());
+
+    Some((ssrc, ntptime, rtptime))
+}
+
+/// Converts a 32.32 fixed point NTP time to nanoseconds.
+fn ntp_to_clock_time(ntptime: u64) -> gst::ClockTime {
+    let secs = ntptime >> 32;
+    let frac = ntptime & 0xffff_ffff;
+
+    gst::ClockTime::from_nseconds(
+        secs * gst::ClockTime::SECOND.nseconds()
+            + ((frac * gst::ClockTime::SECOND.nseconds()) >> 32),
+    )
+}
+
+/// Extends `rtptime` to 64 bits around the last extended RTP time,
+/// the same way `gst_rtp_buffer_ext_timestamp` does.
+fn ext_rtptime(last_ext_rtptime: Option<u64>, rtptime: u32) -> u64 {
+    match last_ext_rtptime {
+        None => u64::from(rtptime) + (1 << 32),
+        Some(last) => {
+            let diff = rtptime.wrapping_sub(last as u32) as i32;
+            last.wrapping_add_signed(diff as i64)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SenderReport {
+    ext_rtptime: u64,
+    ntptime: gst::ClockTime,
+    buffer: gst::Buffer,
+}
+
 #[derive(Clone, Default)]
 struct SrcHandler;
 
 impl SrcHandler {
     fn clear(&self) {}
 
+    /// Computes the reference timestamp for a packet from the last sender report,
+    /// or from the running time and `ts_offset` if no sender report is available.
+    fn reference_timestamp(
+        &self,
+        state: &State,
+        rtptime: u32,
+        pts: Option<gst::ClockTime>,
+        ts_offset: i64,
+    ) -> Option<gst::ClockTime> {
+        if let (Some((sr_ext_rtptime, sr_ntptime)), Some(clock_rate)) =
+            (state.ntp_reference, state.clock_rate)
+        {
+            let diff = rtptime.wrapping_sub(sr_ext_rtptime as u32) as i32 as i64;
+            let offset = diff * gst::ClockTime::SECOND.nseconds() as i64 / clock_rate as i64;
+
+            return sr_ntptime
+                .nseconds()
+                .checked_add_signed(offset)
+                .map(gst::ClockTime::from_nseconds);
+        }
+
+        if ts_offset != 0 {
+            return pts?
+                .nseconds()
+                .checked_add_signed(ts_offset)
+                .map(gst::ClockTime::from_nseconds);
+        }
+
+        None
+    }
+
     fn generate_lost_events(
         &self,
         state: &mut State,
@@ -650,8 +1304,8 @@ impl SrcHandler {
         pts: impl Into<Option<gst::ClockTime>>,
         discont: &mut bool,
     ) -> Vec<gst::Event> {
+        let jb = element.imp();
         let (latency, do_lost) = {
-            let jb = element.imp();
             let settings = jb.settings.lock().unwrap();
             (settings.latency, settings.do_lost)
         };
@@ -697,10 +1351,14 @@ impl SrcHandler {
                     events.push(gst::event::CustomDownstream::new(s));
                 }
 
+                state.record_loss(lost_seqnum, n_packets, element.current_running_time());
+
                 lost_seqnum = lost_seqnum.wrapping_add(n_packets as u16);
                 last_popped_pts += n_packets * spacing;
                 state.last_popped_pts = Some(last_popped_pts);
-                state.stats.num_lost += n_packets;
+                jb.stats
+                    .num_lost
+                    .fetch_add(n_packets, AtomicOrdering::Relaxed);
             }
 
             while lost_seqnum != seqnum {
@@ -724,7 +1382,8 @@ impl SrcHandler {
                     events.push(gst::event::CustomDownstream::new(s));
                 }
 
-                state.stats.num_lost += 1;
+                jb.stats.num_lost.fetch_add(1, AtomicOrdering::Relaxed);
+                state.record_loss(lost_seqnum, 1, element.current_running_time());
 
                 lost_seqnum = lost_seqnum.wrapping_add(1);
             }
@@ -739,11 +1398,21 @@ impl SrcHandler {
     ) -> Result<gst::FlowSuccess, gst::FlowError> {
         let jb = element.imp();
 
-        let (lost_events, buffer, seq) = {
+        let reference_timestamp_meta = {
+            let settings = jb.settings.lock().unwrap();
+            settings.add_reference_timestamp_meta.then(|| {
+                (
+                    settings.reference_timestamp_caps.clone(),
+                    settings.ts_offset,
+                )
+            })
+        };
+
+        let (lost_events, buffer, seq, percent, loss_report) = {
             let mut state = jb.state.lock().unwrap();
 
             let mut discont = false;
-            let (jb_item, _) = state.jbuf.pop();
+            let (jb_item, percent) = state.jbuf.pop();
 
             let jb_item = match jb_item {
                 None => {
@@ -759,6 +1428,7 @@ impl SrcHandler {
             let dts = jb_item.dts();
             let pts = jb_item.pts();
             let seq = jb_item.seqnum();
+            let rtptime = jb_item.rtptime();
             let mut buffer = jb_item.into_buffer();
 
             let lost_events = {
@@ -786,6 +1456,19 @@ impl SrcHandler {
                     buffer.set_flags(gst::BufferFlags::DISCONT);
                 }
 
+                if let Some((ref caps, ts_offset)) = reference_timestamp_meta {
+                    if let Some(timestamp) =
+                        self.reference_timestamp(&state, rtptime, buffer.pts(), ts_offset)
+                    {
+                        gst::ReferenceTimestampMeta::add(
+                            buffer,
+                            caps,
+                            timestamp,
+                            gst::ClockTime::NONE,
+                        );
+                    }
+                }
+
                 lost_events
             };
 
@@ -794,17 +1477,41 @@ impl SrcHandler {
                 state.position = state.last_popped_pts;
             }
             state.last_popped_seqnum = seq;
+            if let Some(seq) = seq {
+                state.clear_timers_until(seq);
+            }
+            state.faststart_remaining = state.faststart_remaining.saturating_sub(1);
 
-            state.stats.num_pushed += 1;
+            jb.stats.num_pushed.fetch_add(1, AtomicOrdering::Relaxed);
 
-            (lost_events, buffer, seq)
+            // A packet is pushed, ending any run of lost packets
+            state.end_loss_burst();
+            let loss_report = state.take_loss_report();
+
+            (lost_events, buffer, seq, percent, loss_report)
         };
 
-        for event in lost_events {
-            gst::debug!(
-                CAT,
-                obj = jb.src_pad.gst_pad(),
-                "Pushing lost event {:?}",
+        if let Some(loss_report) = loss_report {
+            let _ = element.post_message(
+                gst::message::Element::builder(loss_report)
+                    .src(element)
+                    .build(),
+            );
+        }
+
+        if percent != -1 {
+            let _ = element.post_message(
+                gst::message::Buffering::builder(percent)
+                    .src(element)
+                    .build(),
+            );
+        }
+
+        for event in lost_events {
+            gst::debug!(
+                CAT,
+                obj = jb.src_pad.gst_pad(),
+                "Pushing lost event {:?}",
                 event
             );
             let _ = jb.src_pad.push_event(event).await;
@@ -821,12 +1528,199 @@ impl SrcHandler {
         jb.src_pad.push(buffer).await
     }
 
+    fn handle_lost_timers(
+        &self,
+        element: &super::JitterBuffer,
+        state: &mut State,
+    ) -> Vec<gst::Event> {
+        let mut events = vec![];
+
+        let Some(now) = element.current_running_time() else {
+            return events;
+        };
+
+        let jb = element.imp();
+        let do_lost = jb.settings.lock().unwrap().do_lost;
+
+        while let Some(timer) = state.lost_timers.first() {
+            if timer.deadline > now {
+                break;
+            }
+
+            // Packets preceding the lost ones must be pushed first
+            if let (_, Some(head_seq)) = state.jbuf.peek() {
+                if gst_rtp::compare_seqnum(head_seq, timer.seqnum) > 0 {
+                    break;
+                }
+            }
+
+            let mut timer = state.lost_timers.remove(0);
+
+            if let Some(last_popped_seqnum) = state.last_popped_seqnum {
+                let already_popped = gst_rtp::compare_seqnum(timer.seqnum, last_popped_seqnum) + 1;
+                if already_popped >= timer.num_packets as i32 {
+                    continue;
+                }
+                if already_popped > 0 {
+                    timer.skip(already_popped as u16);
+                }
+            }
+
+            let last_seqnum = timer.last_seqnum();
+            let retry = state
+                .rtx_timers
+                .get(&last_seqnum)
+                .map_or(0, |rtx_timer| rtx_timer.num_requests);
+
+            gst::debug!(
+                CAT,
+                obj = element,
+                "Declaring #{}..=#{} lost",
+                timer.seqnum,
+                last_seqnum
+            );
+
+            state.last_popped_seqnum = Some(last_seqnum);
+            state.last_popped_pts =
+                Some(timer.timestamp + timer.spacing * (timer.num_packets - 1) as u64);
+            state.discont = true;
+            state.clear_timers_until(last_seqnum);
+
+            jb.stats
+                .num_lost
+                .fetch_add(timer.num_packets as u64, AtomicOrdering::Relaxed);
+            state.record_loss(timer.seqnum, timer.num_packets as u64, Some(now));
+
+            if do_lost {
+                let s = gst::Structure::builder("GstRTPPacketLost")
+                    .field("seqnum", timer.seqnum as u32)
+                    .field("timestamp", timer.timestamp)
+                    .field(
+                        "duration",
+                        (timer.spacing * timer.num_packets as u64).nseconds(),
+                    )
+                    .field("retry", retry)
+                    .build();
+
+                events.push(gst::event::CustomDownstream::new(s));
+            }
+        }
+
+        events
+    }
+
+    fn handle_rtx_timers(
+        &self,
+        element: &super::JitterBuffer,
+        state: &mut State,
+    ) -> Vec<gst::Event> {
+        let mut events = vec![];
+
+        let Some(now) = element.current_running_time() else {
+            return events;
+        };
+
+        let jb = element.imp();
+        let avg_rtt = jb.stats.avg_rtx_rtt();
+
+        let (latency, retry_timeout, retry_period) = {
+            let settings = jb.settings.lock().unwrap();
+            (
+                settings.latency,
+                settings.rtx_retry_timeout(avg_rtt),
+                settings.rtx_retry_period(avg_rtt),
+            )
+        };
+
+        let packet_spacing = state.packet_spacing;
+        let mut num_requests = 0;
+
+        state.rtx_timers.retain(|_, timer| {
+            if timer.timeout > now {
+                return true;
+            }
+
+            if timer
+                .first_request
+                .is_some_and(|first_request| now.saturating_sub(first_request) > retry_period)
+            {
+                gst::debug!(
+                    CAT,
+                    obj = element,
+                    "Giving up retransmission of #{} after {} request(s)",
+                    timer.seqnum,
+                    timer.num_requests
+                );
+                return false;
+            }
+
+            let s = gst::Structure::builder("GstRTPRetransmissionRequest")
+                .field("seqnum", timer.seqnum as u32)
+                .field("running-time", timer.expected_pts.nseconds())
+                .field("delay", timer.delay.mseconds() as u32)
+                .field("retry", timer.num_requests)
+                .field("frequency", retry_timeout.mseconds() as u32)
+                .field("period", retry_period.mseconds() as u32)
+                .field("deadline", latency.mseconds() as u32)
+                .field("packet-spacing", packet_spacing.nseconds())
+                .field("avg-rtt", avg_rtt.nseconds())
+                .build();
+
+            gst::debug!(CAT, obj = element, "Requesting retransmission {:?}", s);
+
+            events.push((timer.expected_pts, gst::event::CustomUpstream::new(s)));
+
+            timer.num_requests += 1;
+            timer.first_request.get_or_insert(now);
+            timer.last_request = Some(now);
+            timer.timeout = now + retry_timeout;
+            num_requests += 1;
+
+            true
+        });
+
+        jb.stats
+            .num_rtx_requests
+            .fetch_add(num_requests, AtomicOrdering::Relaxed);
+
+        // Request the packets in the order they were expected
+        events.sort_by_key(|(expected_pts, _)| *expected_pts);
+        events.into_iter().map(|(_, event)| event).collect()
+    }
+
+    fn with_timers_wakeup(
+        &self,
+        state: &State,
+        now: Option<gst::ClockTime>,
+        next_wakeup: Option<(Option<gst::ClockTime>, Duration)>,
+    ) -> Option<(Option<gst::ClockTime>, Duration)> {
+        let Some(timeout) = state
+            .rtx_timers
+            .values()
+            .map(|timer| timer.timeout)
+            .chain(state.lost_timers.first().map(|timer| timer.deadline))
+            .min()
+        else {
+            return next_wakeup;
+        };
+
+        match next_wakeup {
+            Some((Some(wakeup), _)) if wakeup <= timeout => next_wakeup,
+            _ => {
+                let delay = timeout
+                    .opt_saturating_sub(now)
+                    .unwrap_or(gst::ClockTime::ZERO);
+
+                Some((Some(timeout), delay.into()))
+            }
+        }
+    }
+
     fn next_wakeup(
         &self,
         element: &super::JitterBuffer,
         state: &State,
         latency: gst::ClockTime,
-        context_wait: gst::ClockTime,
     ) -> (
         Option<gst::ClockTime>,
         Option<(Option<gst::ClockTime>, Duration)>,
@@ -853,11 +1747,44 @@ impl SrcHandler {
             return (now, None);
         }
 
-        let next_wakeup = state.earliest_pts.map(|earliest_pts| {
-            (earliest_pts + latency)
-                .saturating_sub(state.packet_spacing)
-                .saturating_sub(context_wait / 2)
-        });
+        if state.faststart_remaining > 0 {
+            gst::debug!(
+                CAT,
+                obj = element,
+                "Fast starting, {} packets left",
+                state.faststart_remaining
+            );
+            return (now, Some((now, Duration::ZERO)));
+        }
+
+        // Only the packets following the last pushed one
+        // can be pushed without waiting on the clock
+        let (_, head_seq) = state.jbuf.peek();
+        let is_next = |first_ok: bool| {
+            head_seq.is_some_and(|seq| {
+                state
+                    .last_popped_seqnum
+                    .map_or(first_ok, |last| seq == last.wrapping_add(1))
+            })
+        };
+
+        match state.jbuf.mode() {
+            RTPJitterBufferMode::Buffer if state.jbuf.is_buffering() => {
+                gst::debug!(CAT, obj = element, "Buffering, not waking up");
+                return (now, None);
+            }
+            RTPJitterBufferMode::Buffer if is_next(true) => {
+                return (now, Some((now, Duration::ZERO)));
+            }
+            RTPJitterBufferMode::None if is_next(false) => {
+                return (now, Some((now, Duration::ZERO)));
+            }
+            _ => (),
+        }
+
+        let next_wakeup = state
+            .earliest_pts
+            .map(|earliest_pts| (earliest_pts + latency).saturating_sub(state.packet_spacing));
 
         let delay = next_wakeup
             .opt_saturating_sub(now)
@@ -952,11 +1879,160 @@ impl PadSrcHandler for SrcHandler {
     }
 }
 
+/// Statistics, compatible with rtpjitterbuffer's.
+///
+/// Kept out of `State` so that they can be read from any thread
+/// without contending with the streaming threads.
 #[derive(Debug, Default)]
 struct Stats {
-    num_pushed: u64,
-    num_lost: u64,
-    num_late: u64,
+    num_pushed: AtomicU64,
+    num_lost: AtomicU64,
+    num_late: AtomicU64,
+    num_duplicates: AtomicU64,
+    num_dropped: AtomicU64,
+    num_rtx_requests: AtomicU64,
+    num_rtx_success: AtomicU64,
+    /// Average jitter in ns
+    avg_jitter: AtomicU64,
+    /// Average retransmission round trip time in ns
+    avg_rtx_rtt: AtomicU64,
+    /// Average number of requests per recovered packet, as `f64` bits
+    avg_rtx_per_packet: AtomicU64,
+}
+
+impl Stats {
+    fn update_jitter(&self, diff: gst::ClockTime) {
+        if diff.is_zero() {
+            return;
+        }
+
+        let avg_jitter = self.avg_jitter.load(AtomicOrdering::Relaxed);
+        self.avg_jitter.store(
+            (diff.nseconds() + 15 * avg_jitter) >> 4,
+            AtomicOrdering::Relaxed,
+        );
+    }
+
+    fn avg_rtx_rtt(&self) -> gst::ClockTime {
+        gst::ClockTime::from_nseconds(self.avg_rtx_rtt.load(AtomicOrdering::Relaxed))
+    }
+
+    fn update_rtx_success(&self, num_requests: u32, rtt: Option<gst::ClockTime>) {
+        self.num_rtx_success.fetch_add(1, AtomicOrdering::Relaxed);
+
+        let avg_rtx_per_packet =
+            f64::from_bits(self.avg_rtx_per_packet.load(AtomicOrdering::Relaxed));
+        let avg_rtx_per_packet = if avg_rtx_per_packet == 0.0 {
+            num_requests as f64
+        } else {
+            (num_requests as f64 + 7.0 * avg_rtx_per_packet) / 8.0
+        };
+        self.avg_rtx_per_packet
+            .store(avg_rtx_per_packet.to_bits(), AtomicOrdering::Relaxed);
+
+        if let Some(rtt) = rtt {
+            let avg_rtt = self.avg_rtx_rtt.load(AtomicOrdering::Relaxed);
+            let avg_rtt = if avg_rtt == 0 {
+                rtt.nseconds()
+            } else {
+                (rtt.nseconds() + 7 * avg_rtt) / 8
+            };
+            self.avg_rtx_rtt.store(avg_rtt, AtomicOrdering::Relaxed);
+        }
+    }
+
+    fn reset(&self) {
+        self.num_pushed.store(0, AtomicOrdering::Relaxed);
+        self.num_lost.store(0, AtomicOrdering::Relaxed);
+        self.num_late.store(0, AtomicOrdering::Relaxed);
+        self.num_duplicates.store(0, AtomicOrdering::Relaxed);
+        self.num_dropped.store(0, AtomicOrdering::Relaxed);
+        self.num_rtx_requests.store(0, AtomicOrdering::Relaxed);
+        self.num_rtx_success.store(0, AtomicOrdering::Relaxed);
+        self.avg_jitter.store(0, AtomicOrdering::Relaxed);
+        self.avg_rtx_rtt.store(0, AtomicOrdering::Relaxed);
+        self.avg_rtx_per_packet.store(0, AtomicOrdering::Relaxed);
+    }
+
+    fn to_structure(&self) -> gst::Structure {
+        gst::Structure::builder("application/x-rtp-jitterbuffer-stats")
+            .field("num-pushed", self.num_pushed.load(AtomicOrdering::Relaxed))
+            .field("num-lost", self.num_lost.load(AtomicOrdering::Relaxed))
+            .field("num-late", self.num_late.load(AtomicOrdering::Relaxed))
+            .field(
+                "num-duplicates",
+                self.num_duplicates.load(AtomicOrdering::Relaxed),
+            )
+            .field(
+                "num-dropped",
+                self.num_dropped.load(AtomicOrdering::Relaxed),
+            )
+            .field("avg-jitter", self.avg_jitter.load(AtomicOrdering::Relaxed))
+            .field(
+                "rtx-count",
+                self.num_rtx_requests.load(AtomicOrdering::Relaxed),
+            )
+            .field(
+                "rtx-success-count",
+                self.num_rtx_success.load(AtomicOrdering::Relaxed),
+            )
+            .field(
+                "rtx-per-packet",
+                f64::from_bits(self.avg_rtx_per_packet.load(AtomicOrdering::Relaxed)),
+            )
+            .field("rtx-rtt", self.avg_rtx_rtt.load(AtomicOrdering::Relaxed))
+            .build()
+    }
+}
+
+/// A range of missing packets, declared lost when `deadline` is reached.
+#[derive(Clone, Copy, Debug)]
+struct LostTimer {
+    seqnum: u16,
+    num_packets: u16,
+    timestamp: gst::ClockTime,
+    spacing: gst::ClockTime,
+    deadline: gst::ClockTime,
+}
+
+impl LostTimer {
+    fn last_seqnum(&self) -> u16 {
+        self.seqnum.wrapping_add(self.num_packets - 1)
+    }
+
+    fn contains(&self, seqnum: u16) -> bool {
+        let idx = gst_rtp::compare_seqnum(self.seqnum, seqnum);
+        idx >= 0 && idx < self.num_packets as i32
+    }
+
+    fn skip(&mut self, num_packets: u16) {
+        let offset = self.spacing * num_packets as u64;
+        self.seqnum = self.seqnum.wrapping_add(num_packets);
+        self.num_packets -= num_packets;
+        self.timestamp += offset;
+        self.deadline += offset;
+    }
+}
+
+/// Contiguous packets declared lost, possibly aggregating several bursts
+/// while the loss messages are rate limited.
+#[derive(Debug)]
+struct LossBurst {
+    first_seqnum: u16,
+    count: u64,
+    running_time: Option<gst::ClockTime>,
+    num_bursts: u32,
+}
+
+#[derive(Debug)]
+struct RtxTimer {
+    seqnum: u16,
+    expected_pts: gst::ClockTime,
+    delay: gst::ClockTime,
+    timeout: gst::ClockTime,
+    num_requests: u32,
+    first_request: Option<gst::ClockTime>,
+    last_request: Option<gst::ClockTime>,
 }
 
 // Shared state between element, sink and source pad
@@ -968,6 +2044,8 @@ struct State {
 
     segment: gst::FormattedSegment<gst::ClockTime>,
     clock_rate: Option<u32>,
+    // Clock rates per payload type
+    pt_map: HashMap<u8, u32>,
 
     packet_spacing: gst::ClockTime,
     equidistant: i32,
@@ -978,7 +2056,26 @@ struct State {
     last_popped_seqnum: Option<u16>,
     last_popped_pts: Option<gst::ClockTime>,
 
-    stats: Stats,
+    // By seqnum
+    rtx_timers: HashMap<u16, RtxTimer>,
+    // Ordered by seqnum
+    lost_timers: Vec<LostTimer>,
+
+    // Current run of lost packets
+    loss_burst: Option<LossBurst>,
+    // Ended runs of lost packets not notified yet
+    pending_loss: Option<LossBurst>,
+    last_loss_report: Option<Instant>,
+
+    // Packets to push right away when fast starting
+    faststart_remaining: u32,
+
+    clock_base: Option<u32>,
+    last_ssrc: Option<u32>,
+    // Last sender report, with its extended RTP time, not handled yet
+    last_sr: Option<SenderReport>,
+    // Extended RTP time and NTP time of the last valid sender report
+    ntp_reference: Option<(u64, gst::ClockTime)>,
 
     earliest_pts: Option<gst::ClockTime>,
     earliest_seqnum: Option<u16>,
@@ -996,6 +2093,7 @@ impl Default for State {
 
             segment: gst::FormattedSegment::<gst::ClockTime>::new(),
             clock_rate: None,
+            pt_map: HashMap::new(),
 
             packet_spacing: gst::ClockTime::ZERO,
             equidistant: 0,
@@ -1006,7 +2104,19 @@ impl Default for State {
             last_popped_seqnum: None,
             last_popped_pts: None,
 
-            stats: Stats::default(),
+            rtx_timers: HashMap::new(),
+            lost_timers: Vec::new(),
+
+            loss_burst: None,
+            pending_loss: None,
+            last_loss_report: None,
+
+            faststart_remaining: 0,
+
+            clock_base: None,
+            last_ssrc: None,
+            last_sr: None,
+            ntp_reference: None,
 
             earliest_pts: None,
             earliest_seqnum: None,
@@ -1016,6 +2126,99 @@ impl Default for State {
     }
 }
 
+impl State {
+    /// Adds `count` packets from `seqnum` to the current run of lost packets,
+    /// or starts a new one if they don't follow it.
+    fn record_loss(&mut self, seqnum: u16, count: u64, running_time: Option<gst::ClockTime>) {
+        if let Some(ref mut burst) = self.loss_burst {
+            if burst.first_seqnum.wrapping_add(burst.count as u16) == seqnum {
+                burst.count += count;
+                return;
+            }
+        }
+
+        self.end_loss_burst();
+        self.loss_burst = Some(LossBurst {
+            first_seqnum: seqnum,
+            count,
+            running_time,
+            num_bursts: 1,
+        });
+    }
+
+    fn end_loss_burst(&mut self) {
+        let Some(burst) = self.loss_burst.take() else {
+            return;
+        };
+
+        match self.pending_loss {
+            Some(ref mut pending) => {
+                pending.count += burst.count;
+                pending.num_bursts += 1;
+            }
+            None => self.pending_loss = Some(burst),
+        }
+    }
+
+    /// Returns the `ts-jitterbuffer-loss` structure for the pending lost packets,
+    /// unless one was returned less than `LOSS_MESSAGE_INTERVAL` ago.
+    fn take_loss_report(&mut self) -> Option<gst::Structure> {
+        self.pending_loss.as_ref()?;
+
+        let now = Instant::now();
+        if self
+            .last_loss_report
+            .is_some_and(|last| now.duration_since(last) < LOSS_MESSAGE_INTERVAL)
+        {
+            return None;
+        }
+        self.last_loss_report = Some(now);
+
+        let loss = self.pending_loss.take().unwrap();
+        Some(
+            gst::Structure::builder("ts-jitterbuffer-loss")
+                .field("first-seqnum", loss.first_seqnum as u32)
+                .field("count", loss.count)
+                .field("running-time", loss.running_time)
+                .field("num-bursts", loss.num_bursts)
+                .build(),
+        )
+    }
+
+    /// Forgets about the timers of the packets up to and including `seqnum`.
+    fn clear_timers_until(&mut self, seqnum: u16) {
+        self.rtx_timers
+            .retain(|&timer_seqnum, _| gst_rtp::compare_seqnum(seqnum, timer_seqnum) > 0);
+        self.lost_timers
+            .retain(|timer| gst_rtp::compare_seqnum(seqnum, timer.last_seqnum()) > 0);
+    }
+
+    /// Removes `seqnum` from the lost timers, splitting ranges if needed.
+    fn remove_lost_timer(&mut self, seqnum: u16) {
+        let Some(pos) = self
+            .lost_timers
+            .iter()
+            .position(|timer| timer.contains(seqnum))
+        else {
+            return;
+        };
+
+        let mut timer = self.lost_timers.remove(pos);
+        let idx = gst_rtp::compare_seqnum(timer.seqnum, seqnum) as u16;
+
+        if idx + 1 < timer.num_packets {
+            let mut after = timer;
+            after.skip(idx + 1);
+            self.lost_timers.insert(pos, after);
+        }
+
+        if idx > 0 {
+            timer.num_packets = idx;
+            self.lost_timers.insert(pos, timer);
+        }
+    }
+}
+
 struct JitterBufferTask {
     element: super::JitterBuffer,
     src_pad_handler: SrcHandler,
@@ -1048,10 +2251,16 @@ impl TaskImpl for JitterBufferTask {
 
             let jb = self.element.imp();
 
-            let latency = jb.settings.lock().unwrap().latency;
+            let (latency, mode) = {
+                let settings = jb.settings.lock().unwrap();
+                (settings.latency, settings.mode)
+            };
             let state = State::default();
 
+            jb.stats.reset();
+
             state.jbuf.set_delay(latency);
+            state.jbuf.set_mode(mode.into());
             *jb.state.lock().unwrap() = state;
 
             gst::log!(CAT, obj = self.element, "Task started");
@@ -1065,28 +2274,25 @@ impl TaskImpl for JitterBufferTask {
     // in https://gitlab.freedesktop.org/gstreamer/gst-plugins-rs/-/merge_requests/756.
     // It should be possible to remove the loop below as try_next /  handle_item
     // are executed in a loop by the Task state machine.
-    // It should also be possible to store latency and context_wait as
-    // fields of JitterBufferTask so as to avoid locking the settings.
+    // It should also be possible to store latency as a field of
+    // JitterBufferTask so as to avoid locking the settings.
     // If latency can change during processing, a command based mechanism
     // could be implemented. See the command implementation for ts-udpsink as
     // an example.
     fn try_next(&mut self) -> BoxFuture<'_, Result<(), gst::FlowError>> {
         async move {
             let jb = self.element.imp();
-            let (latency, context_wait) = {
-                let settings = jb.settings.lock().unwrap();
-                (settings.latency, settings.context_wait)
-            };
+            let latency = jb.settings.lock().unwrap().latency;
 
             loop {
                 let delay_fut = {
                     let mut state = jb.state.lock().unwrap();
-                    let (_, next_wakeup) = self.src_pad_handler.next_wakeup(
-                        &self.element,
-                        &state,
-                        latency,
-                        context_wait,
-                    );
+                    let (now, next_wakeup) =
+                        self.src_pad_handler
+                            .next_wakeup(&self.element, &state, latency);
+                    let next_wakeup =
+                        self.src_pad_handler
+                            .with_timers_wakeup(&state, now, next_wakeup);
 
                     let (delay_fut, abort_handle) = match next_wakeup {
                         Some((_, delay)) if delay.is_zero() => (None, None),
@@ -1094,7 +2300,9 @@ impl TaskImpl for JitterBufferTask {
                             let (delay_fut, abort_handle) = abortable(async move {
                                 match next_wakeup {
                                     Some((_, delay)) => {
-                                        runtime::timer::delay_for_at_least(delay).await;
+                                        // Deadlines must not be quantized by the
+                                        // Context throttling
+                                        runtime::timer::delay_for_precise(delay).await;
                                     }
                                     None => {
                                         future::pending::<()>().await;
@@ -1121,16 +2329,43 @@ impl TaskImpl for JitterBufferTask {
                     }
                 }
 
+                let (rtx_events, lost_events) = {
+                    let mut state = jb.state.lock().unwrap();
+                    (
+                        self.src_pad_handler
+                            .handle_rtx_timers(&self.element, &mut state),
+                        self.src_pad_handler
+                            .handle_lost_timers(&self.element, &mut state),
+                    )
+                };
+
+                for event in rtx_events {
+                    gst::debug!(
+                        CAT,
+                        obj = jb.sink_pad.gst_pad(),
+                        "Pushing retransmission request {:?}",
+                        event
+                    );
+                    let _ = jb.sink_pad.gst_pad().push_event(event);
+                }
+
+                for event in lost_events {
+                    gst::debug!(
+                        CAT,
+                        obj = jb.src_pad.gst_pad(),
+                        "Pushing lost event {:?}",
+                        event
+                    );
+                    let _ = jb.src_pad.push_event(event).await;
+                }
+
                 let (head_pts, head_seq) = {
                     let state = jb.state.lock().unwrap();
                     //
                     // Check earliest PTS as we have just taken the lock
-                    let (now, next_wakeup) = self.src_pad_handler.next_wakeup(
-                        &self.element,
-                        &state,
-                        latency,
-                        context_wait,
-                    );
+                    let (now, next_wakeup) =
+                        self.src_pad_handler
+                            .next_wakeup(&self.element, &state, latency);
 
                     gst::debug!(
                         CAT,
@@ -1170,12 +2405,9 @@ impl TaskImpl for JitterBufferTask {
                     if res.is_ok() {
                         // Return and reschedule if the next packet would be in the future
                         // Check earliest PTS as we have just taken the lock
-                        let (now, next_wakeup) = self.src_pad_handler.next_wakeup(
-                            &self.element,
-                            &state,
-                            latency,
-                            context_wait,
-                        );
+                        let (now, next_wakeup) =
+                            self.src_pad_handler
+                                .next_wakeup(&self.element, &state, latency);
                         if let Some((Some(next_wakeup), _)) = next_wakeup {
                             if now.is_some_and(|now| next_wakeup > now) {
                                 // Reschedule and wait a bit longer in the next iteration
@@ -1235,12 +2467,14 @@ impl TaskImpl for JitterBufferTask {
 
 pub struct JitterBuffer {
     sink_pad: PadSink,
+    rtcp_sink_pad: StdMutex<Option<PadSink>>,
     src_pad: PadSrc,
     sink_pad_handler: SinkHandler,
     src_pad_handler: SrcHandler,
     task: Task,
     state: StdMutex<State>,
     settings: StdMutex<Settings>,
+    stats: Stats,
 }
 
 static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
@@ -1257,9 +2491,120 @@ impl JitterBuffer {
 
         let mut state = self.state.lock().unwrap();
         state.clock_rate = None;
+        state.clock_base = None;
+        state.pt_map.clear();
         state.jbuf.reset_skew();
     }
 
+    fn handle_rtcp(&self, buffer: gst::Buffer) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let Some((ssrc, ntptime, rtptime)) = parse_sender_report(&buffer) else {
+            gst::debug!(CAT, imp = self, "Ignoring RTCP packet, not a sender report");
+            return Ok(gst::FlowSuccess::Ok);
+        };
+
+        let mut state = self.state.lock().unwrap();
+
+        if state.last_ssrc.is_some_and(|last_ssrc| last_ssrc != ssrc) {
+            gst::debug!(
+                CAT,
+                imp = self,
+                "Ignoring sender report for SSRC {:08x}, expected {:08x}",
+                ssrc,
+                state.last_ssrc.unwrap(),
+            );
+            return Ok(gst::FlowSuccess::Ok);
+        }
+
+        // Use the same extension as the jitterbuffer for the RTP packets
+        let (.., last_rtptime) = state.jbuf.sync();
+        state.last_sr = Some(SenderReport {
+            ext_rtptime: ext_rtptime(last_rtptime, rtptime),
+            ntptime: ntp_to_clock_time(ntptime),
+            buffer,
+        });
+
+        let sync = self.handle_sync(&mut state);
+        drop(state);
+
+        if let Some(sync) = sync {
+            self.obj().emit_by_name::<()>("handle-sync", &[&sync]);
+        }
+
+        Ok(gst::FlowSuccess::Ok)
+    }
+
+    /// Checks the pending sender report against the RTP packets received so far
+    /// and returns the structure to be passed to `handle-sync` if it's usable.
+    ///
+    /// The sender report is kept until the first RTP packets are received.
+    fn handle_sync(&self, state: &mut State) -> Option<gst::Structure> {
+        state.last_sr.as_ref()?;
+
+        let (base_rtptime, base_time, clock_rate, last_rtptime) = state.jbuf.sync();
+        let (Some(base_rtptime), Some(base_time)) = (base_rtptime, base_time) else {
+            gst::debug!(CAT, imp = self, "Keeping sender report, no RTP values yet");
+            return None;
+        };
+        if clock_rate == 0 || clock_rate == u32::MAX {
+            gst::debug!(CAT, imp = self, "Keeping sender report, no clock rate yet");
+            return None;
+        }
+
+        let SenderReport {
+            ext_rtptime: mut sr_ext_rtptime,
+            ntptime: sr_ntptime,
+            buffer: sr_buffer,
+        } = state.last_sr.take().unwrap();
+
+        gst::debug!(
+            CAT,
+            imp = self,
+            "ext SR {}, base {}, clock-rate {}, clock-base {:?}, last-rtptime {:?}",
+            sr_ext_rtptime,
+            base_rtptime,
+            clock_rate,
+            state.clock_base,
+            last_rtptime,
+        );
+
+        // Can't accept anything that happened before the last resync
+        if base_rtptime > sr_ext_rtptime {
+            gst::debug!(
+                CAT,
+                imp = self,
+                "Dropping sender report, older than base time"
+            );
+            return None;
+        }
+
+        // The SR RTP time must be close to what was last observed
+        if let Some(last_rtptime) = last_rtptime.filter(|last| sr_ext_rtptime > *last) {
+            let max_diff = self.settings.lock().unwrap().max_rtcp_rtp_time_diff;
+            let diff = sr_ext_rtptime - last_rtptime;
+            if max_diff >= 0 && diff > max_diff as u64 * clock_rate as u64 / 1000 {
+                // Still notify so rtpbin can use other sync methods,
+                // but invalidate the RTCP data
+                gst::debug!(CAT, imp = self, "Sender report too far ahead ({})", diff);
+                sr_ext_rtptime = u64::MAX;
+            }
+        }
+
+        if sr_ext_rtptime != u64::MAX {
+            state.ntp_reference = Some((sr_ext_rtptime, sr_ntptime));
+        }
+
+        Some(
+            gst::Structure::builder("application/x-rtp-sync")
+                .field("base-rtptime", base_rtptime)
+                .field("base-time", base_time.nseconds())
+                .field("clock-rate", clock_rate)
+                .field("clock-base", state.clock_base.map_or(u64::MAX, u64::from))
+                .field("sr-ext-rtptime", sr_ext_rtptime)
+                .field("sr-buffer", sr_buffer)
+                .build(),
+        )
+    }
+
     fn prepare(&self) -> Result<(), gst::ErrorMessage> {
         gst::debug!(CAT, imp = self, "Preparing");
 
@@ -1268,6 +2613,8 @@ impl JitterBuffer {
             Context::acquire(&settings.context, settings.context_wait.into()).unwrap()
         };
 
+        self.task.set_name(self.obj().name().as_str());
+
         self.task
             .prepare(
                 JitterBufferTask::new(&self.obj(), &self.src_pad_handler, &self.sink_pad_handler),
@@ -1316,6 +2663,7 @@ impl ObjectSubclass for JitterBuffer {
                 gst::Pad::from_template(&klass.pad_template("sink").unwrap()),
                 sink_pad_handler.clone(),
             ),
+            rtcp_sink_pad: StdMutex::new(None),
             src_pad: PadSrc::new(
                 gst::Pad::from_template(&klass.pad_template("src").unwrap()),
                 src_pad_handler.clone(),
@@ -1325,6 +2673,7 @@ impl ObjectSubclass for JitterBuffer {
             task: Task::default(),
             state: StdMutex::new(State::default()),
             settings: StdMutex::new(Settings::default()),
+            stats: Stats::default(),
         }
     }
 }
@@ -1364,6 +2713,64 @@ impl ObjectImpl for JitterBuffer {
                     .blurb("The maximum time (milliseconds) of misordered packets tolerated.")
                     .default_value(DEFAULT_MAX_MISORDER_TIME)
                     .build(),
+                glib::ParamSpecBoolean::builder("do-retransmission")
+                    .nick("Do Retransmission")
+                    .blurb("Send retransmission events upstream when a packet is late/lost")
+                    .default_value(DEFAULT_DO_RETRANSMISSION)
+                    .build(),
+                glib::ParamSpecInt::builder("rtx-delay")
+                    .nick("RTX Delay")
+                    .blurb("Extra time in ms to wait before sending retransmission event (-1 automatic)")
+                    .minimum(-1)
+                    .default_value(DEFAULT_RTX_DELAY)
+                    .build(),
+                glib::ParamSpecInt::builder("rtx-retry-timeout")
+                    .nick("RTX Retry Timeout")
+                    .blurb("Retry sending a transmission event after this timeout in ms (-1 automatic)")
+                    .minimum(-1)
+                    .default_value(DEFAULT_RTX_RETRY_TIMEOUT)
+                    .build(),
+                glib::ParamSpecInt::builder("rtx-retry-period")
+                    .nick("RTX Retry Period")
+                    .blurb("Try to get a retransmission for this many ms (-1 automatic)")
+                    .minimum(-1)
+                    .default_value(DEFAULT_RTX_RETRY_PERIOD)
+                    .build(),
+                glib::ParamSpecBoolean::builder("drop-on-latency")
+                    .nick("Drop buffers when maximum latency is reached")
+                    .blurb("Tells the jitterbuffer to never exceed the given latency in size")
+                    .default_value(DEFAULT_DROP_ON_LATENCY)
+                    .build(),
+                glib::ParamSpecInt::builder("max-rtcp-rtp-time-diff")
+                    .nick("Max RTCP RTP Time Diff")
+                    .blurb("Maximum amount of time in ms that the RTP time in RTCP SRs is allowed to be ahead (-1 disabled)")
+                    .minimum(-1)
+                    .default_value(DEFAULT_MAX_RTCP_RTP_TIME_DIFF)
+                    .build(),
+                glib::ParamSpecUInt::builder("faststart-min-packets")
+                    .nick("Faststart minimum packets")
+                    .blurb("The number of consecutive packets needed to start (set to 0 to disable)")
+                    .default_value(DEFAULT_FASTSTART_MIN_PACKETS)
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("mode", DEFAULT_MODE)
+                    .nick("Mode")
+                    .blurb("Control the buffering algorithm in use")
+                    .build(),
+                glib::ParamSpecBoolean::builder("add-reference-timestamp-meta")
+                    .nick("Add Reference Timestamp Meta")
+                    .blurb("Add reference timestamp metas to the output buffers, anchored to the sender reports or to ts-offset")
+                    .default_value(DEFAULT_ADD_REFERENCE_TIMESTAMP_META)
+                    .build(),
+                glib::ParamSpecString::builder("reference-timestamp-caps")
+                    .nick("Reference Timestamp Caps")
+                    .blurb("Caps identifying the reference clock of the reference timestamp metas")
+                    .default_value(Some(DEFAULT_REFERENCE_TIMESTAMP_CAPS))
+                    .build(),
+                glib::ParamSpecInt64::builder("ts-offset")
+                    .nick("Timestamp Offset")
+                    .blurb("Offset in ns from the running time to the reference clock, used when no sender report is available (0 = unset)")
+                    .default_value(DEFAULT_TS_OFFSET)
+                    .build(),
                 glib::ParamSpecBoxed::builder::<gst::Structure>("stats")
                     .nick("Statistics")
                     .blurb("Various statistics")
@@ -1391,6 +2798,9 @@ impl ObjectImpl for JitterBuffer {
                     .param_types([u32::static_type()])
                     .return_type::<gst::Caps>()
                     .build(),
+                glib::subclass::Signal::builder("handle-sync")
+                    .param_types([gst::Structure::static_type()])
+                    .build(),
             ]
         });
 
@@ -1427,6 +2837,61 @@ impl ObjectImpl for JitterBuffer {
                 let mut settings = self.settings.lock().unwrap();
                 settings.max_misorder_time = value.get().expect("type checked upstream");
             }
+            "do-retransmission" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.do_retransmission = value.get().expect("type checked upstream");
+            }
+            "rtx-delay" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.rtx_delay = value.get().expect("type checked upstream");
+            }
+            "rtx-retry-timeout" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.rtx_retry_timeout = value.get().expect("type checked upstream");
+            }
+            "rtx-retry-period" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.rtx_retry_period = value.get().expect("type checked upstream");
+            }
+            "drop-on-latency" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.drop_on_latency = value.get().expect("type checked upstream");
+            }
+            "max-rtcp-rtp-time-diff" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.max_rtcp_rtp_time_diff = value.get().expect("type checked upstream");
+            }
+            "faststart-min-packets" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.faststart_min_packets = value.get().expect("type checked upstream");
+            }
+            "mode" => {
+                let mode = value
+                    .get::<JitterBufferMode>()
+                    .expect("type checked upstream");
+                self.settings.lock().unwrap().mode = mode;
+                self.state.lock().unwrap().jbuf.set_mode(mode.into());
+            }
+            "add-reference-timestamp-meta" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.add_reference_timestamp_meta = value.get().expect("type checked upstream");
+            }
+            "reference-timestamp-caps" => {
+                let caps = value
+                    .get::<Option<&str>>()
+                    .expect("type checked upstream")
+                    .unwrap_or(DEFAULT_REFERENCE_TIMESTAMP_CAPS);
+                match gst::Caps::from_str(caps) {
+                    Ok(caps) => self.settings.lock().unwrap().reference_timestamp_caps = caps,
+                    Err(err) => {
+                        gst::error!(CAT, imp = self, "Invalid reference timestamp caps: {}", err)
+                    }
+                }
+            }
+            "ts-offset" => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.ts_offset = value.get().expect("type checked upstream");
+            }
             "context" => {
                 let mut settings = self.settings.lock().unwrap();
                 settings.context = value
@@ -1462,15 +2927,51 @@ impl ObjectImpl for JitterBuffer {
                 let settings = self.settings.lock().unwrap();
                 settings.max_misorder_time.to_value()
             }
-            "stats" => {
-                let state = self.state.lock().unwrap();
-                let s = gst::Structure::builder("application/x-rtp-jitterbuffer-stats")
-                    .field("num-pushed", state.stats.num_pushed)
-                    .field("num-lost", state.stats.num_lost)
-                    .field("num-late", state.stats.num_late)
-                    .build();
-                s.to_value()
+            "do-retransmission" => {
+                let settings = self.settings.lock().unwrap();
+                settings.do_retransmission.to_value()
+            }
+            "rtx-delay" => {
+                let settings = self.settings.lock().unwrap();
+                settings.rtx_delay.to_value()
+            }
+            "rtx-retry-timeout" => {
+                let settings = self.settings.lock().unwrap();
+                settings.rtx_retry_timeout.to_value()
+            }
+            "rtx-retry-period" => {
+                let settings = self.settings.lock().unwrap();
+                settings.rtx_retry_period.to_value()
+            }
+            "drop-on-latency" => {
+                let settings = self.settings.lock().unwrap();
+                settings.drop_on_latency.to_value()
+            }
+            "max-rtcp-rtp-time-diff" => {
+                let settings = self.settings.lock().unwrap();
+                settings.max_rtcp_rtp_time_diff.to_value()
             }
+            "faststart-min-packets" => {
+                let settings = self.settings.lock().unwrap();
+                settings.faststart_min_packets.to_value()
+            }
+            "mode" => {
+                let settings = self.settings.lock().unwrap();
+                settings.mode.to_value()
+            }
+            "add-reference-timestamp-meta" => {
+                let settings = self.settings.lock().unwrap();
+                settings.add_reference_timestamp_meta.to_value()
+            }
+            "reference-timestamp-caps" => {
+                let settings = self.settings.lock().unwrap();
+                settings.reference_timestamp_caps.to_string().to_value()
+            }
+            "ts-offset" => {
+                let settings = self.settings.lock().unwrap();
+                settings.ts_offset.to_value()
+            }
+            "stats" => self.stats.to_structure().to_value(),
             "context" => {
                 let settings = self.settings.lock().unwrap();
                 settings.context.to_value()
@@ -1529,12 +3030,55 @@ impl ElementImpl for JitterBuffer {
             )
             .unwrap();
 
-            vec![sink_pad_template, src_pad_template]
+            let sink_rtcp_pad_template = gst::PadTemplate::new(
+                "sink_rtcp",
+                gst::PadDirection::Sink,
+                gst::PadPresence::Request,
+                &gst::Caps::new_empty_simple("application/x-rtcp"),
+            )
+            .unwrap();
+
+            vec![sink_pad_template, src_pad_template, sink_rtcp_pad_template]
         });
 
         PAD_TEMPLATES.as_ref()
     }
 
+    fn request_new_pad(
+        &self,
+        templ: &gst::PadTemplate,
+        _name: Option<&str>,
+        _caps: Option<&gst::Caps>,
+    ) -> Option<gst::Pad> {
+        let mut rtcp_sink_pad = self.rtcp_sink_pad.lock().unwrap();
+        if rtcp_sink_pad.is_some() {
+            gst::warning!(CAT, imp = self, "sink_rtcp pad already requested");
+            return None;
+        }
+
+        let pad = gst::Pad::builder_from_template(templ)
+            .name("sink_rtcp")
+            .build();
+        pad.set_active(true).unwrap();
+        self.obj().add_pad(&pad).unwrap();
+        *rtcp_sink_pad = Some(PadSink::new(pad.clone(), RtcpSinkHandler));
+
+        Some(pad)
+    }
+
+    fn release_pad(&self, pad: &gst::Pad) {
+        let mut rtcp_sink_pad = self.rtcp_sink_pad.lock().unwrap();
+        if rtcp_sink_pad
+            .as_ref()
+            .is_some_and(|rtcp_sink_pad| rtcp_sink_pad.gst_pad() == pad)
+        {
+            *rtcp_sink_pad = None;
+            self.obj().remove_pad(pad).unwrap();
+        }
+
+        self.state.lock().unwrap().last_sr = None;
+    }
+
     fn change_state(
         &self,
         transition: gst::StateChange,
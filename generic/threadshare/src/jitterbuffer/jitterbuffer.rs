@@ -65,6 +65,17 @@ impl FromGlib<ffi::RTPJitterBufferMode> for RTPJitterBufferMode {
     }
 }
 
+impl From<super::JitterBufferMode> for RTPJitterBufferMode {
+    fn from(mode: super::JitterBufferMode) -> Self {
+        match mode {
+            super::JitterBufferMode::None => RTPJitterBufferMode::None,
+            super::JitterBufferMode::Slave => RTPJitterBufferMode::Slave,
+            super::JitterBufferMode::Buffer => RTPJitterBufferMode::Buffer,
+            super::JitterBufferMode::Synced => RTPJitterBufferMode::Synced,
+        }
+    }
+}
+
 pub struct RTPJitterBufferItem(Option<ptr::NonNull<ffi::RTPJitterBufferItem>>);
 
 unsafe impl Send for RTPJitterBufferItem {}
@@ -146,7 +157,6 @@ impl RTPJitterBufferItem {
         }
     }
 
-    #[allow(dead_code)]
     pub fn rtptime(&self) -> u32 {
         unsafe {
             let item = self.0.as_ref().expect("Invalid wrapper");
@@ -222,12 +232,10 @@ impl RTPJitterBuffer {
         unsafe { from_glib_full(ffi::ts_rtp_jitter_buffer_new()) }
     }
 
-    #[allow(dead_code)]
     pub fn mode(&self) -> RTPJitterBufferMode {
         unsafe { from_glib(ffi::ts_rtp_jitter_buffer_get_mode(self.to_glib_none().0)) }
     }
 
-    #[allow(dead_code)]
     pub fn set_mode(&self, mode: RTPJitterBufferMode) {
         unsafe { ffi::ts_rtp_jitter_buffer_set_mode(self.to_glib_none().0, mode.into_glib()) }
     }
@@ -248,7 +256,6 @@ impl RTPJitterBuffer {
         unsafe { ffi::ts_rtp_jitter_buffer_set_clock_rate(self.to_glib_none().0, clock_rate) }
     }
 
-    #[allow(dead_code)]
     pub fn clock_rate(&self) -> u32 {
         unsafe { ffi::ts_rtp_jitter_buffer_get_clock_rate(self.to_glib_none().0) }
     }
@@ -278,7 +285,8 @@ impl RTPJitterBuffer {
     pub fn insert(&self, mut item: RTPJitterBufferItem) -> (bool, bool, i32) {
         unsafe {
             let mut head = mem::MaybeUninit::uninit();
-            let mut percent = mem::MaybeUninit::uninit();
+            // Only set when the buffering level changed
+            let mut percent = mem::MaybeUninit::new(-1);
             let ptr = item.0.take().expect("Invalid wrapper");
             let ret: bool = from_glib(ffi::ts_rtp_jitter_buffer_insert(
                 self.to_glib_none().0,
@@ -318,7 +326,8 @@ impl RTPJitterBuffer {
 
     pub fn pop(&self) -> (Option<RTPJitterBufferItem>, i32) {
         unsafe {
-            let mut percent = mem::MaybeUninit::uninit();
+            // Only set when the buffering level changed
+            let mut percent = mem::MaybeUninit::new(-1);
             let item = ffi::ts_rtp_jitter_buffer_pop(self.to_glib_none().0, percent.as_mut_ptr());
 
             (
@@ -349,6 +358,56 @@ impl RTPJitterBuffer {
         }
     }
 
+    pub fn ts_diff(&self) -> u32 {
+        unsafe { ffi::ts_rtp_jitter_buffer_get_ts_diff(self.to_glib_none().0) }
+    }
+
+    pub fn is_buffering(&self) -> bool {
+        unsafe {
+            from_glib(ffi::ts_rtp_jitter_buffer_is_buffering(
+                self.to_glib_none().0,
+            ))
+        }
+    }
+
+    pub fn can_fast_start(&self, num_packets: u32) -> bool {
+        unsafe {
+            from_glib(ffi::ts_rtp_jitter_buffer_can_fast_start(
+                self.to_glib_none().0,
+                num_packets as i32,
+            ))
+        }
+    }
+
+    /// Returns the base extended RTP time, the base time including the
+    /// current skew, the clock rate and the last extended RTP time.
+    pub fn sync(&self) -> (Option<u64>, Option<gst::ClockTime>, u32, Option<u64>) {
+        unsafe {
+            let mut rtptime = mem::MaybeUninit::uninit();
+            let mut timestamp = mem::MaybeUninit::uninit();
+            let mut clock_rate = mem::MaybeUninit::uninit();
+            let mut last_rtptime = mem::MaybeUninit::uninit();
+
+            ffi::ts_rtp_jitter_buffer_get_sync(
+                self.to_glib_none().0,
+                rtptime.as_mut_ptr(),
+                timestamp.as_mut_ptr(),
+                clock_rate.as_mut_ptr(),
+                last_rtptime.as_mut_ptr(),
+            );
+
+            let rtptime = rtptime.assume_init();
+            let last_rtptime = last_rtptime.assume_init();
+
+            (
+                (rtptime != u64::MAX).then_some(rtptime),
+                from_glib(timestamp.assume_init()),
+                clock_rate.assume_init(),
+                (last_rtptime != u64::MAX).then_some(last_rtptime),
+            )
+        }
+    }
+
     pub fn flush(&self) {
         unsafe extern "C" fn free_item(item: glib::ffi::gpointer, _: glib::ffi::gpointer) {
             let _ =
@@ -25,11 +25,28 @@ mod imp;
 #[allow(clippy::module_inception)]
 pub mod jitterbuffer;
 
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstTsJitterBufferMode")]
+pub enum JitterBufferMode {
+    #[enum_value(name = "Only use RTP timestamps", nick = "none")]
+    None = 0,
+    #[default]
+    #[enum_value(name = "Slave receiver to sender clock", nick = "slave")]
+    Slave = 1,
+    #[enum_value(name = "Do low/high watermark buffering", nick = "buffer")]
+    Buffer = 2,
+    #[enum_value(name = "Synchronized sender and receiver clocks", nick = "synced")]
+    Synced = 4,
+}
+
 glib::wrapper! {
     pub struct JitterBuffer(ObjectSubclass<imp::JitterBuffer>) @extends gst::Element, gst::Object;
 }
 
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    JitterBufferMode::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+
     gst::Element::register(
         Some(plugin),
         "ts-jitterbuffer",
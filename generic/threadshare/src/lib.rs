@@ -16,32 +16,52 @@
 #[macro_use]
 pub mod runtime;
 
+mod appsink;
 mod appsrc;
 mod audiotestsrc;
 pub mod dataqueue;
+mod identity;
 mod inputselector;
+mod inter;
 mod jitterbuffer;
 mod proxy;
 mod queue;
 pub mod socket;
+mod tcpclientsink;
 mod tcpclientsrc;
+mod tcpserversrc;
+mod ticksrc;
 mod udpsink;
 mod udpsrc;
+#[cfg(unix)]
+mod unixdgramsink;
+#[cfg(unix)]
+mod unixdgramsrc;
 
 pub mod net;
 
 use gst::glib;
 
 fn plugin_init(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    appsink::register(plugin)?;
     appsrc::register(plugin)?;
     audiotestsrc::register(plugin)?;
+    identity::register(plugin)?;
+    inter::register(plugin)?;
     inputselector::register(plugin)?;
     jitterbuffer::register(plugin)?;
     proxy::register(plugin)?;
     queue::register(plugin)?;
+    tcpclientsink::register(plugin)?;
     tcpclientsrc::register(plugin)?;
+    tcpserversrc::register(plugin)?;
+    ticksrc::register(plugin)?;
     udpsink::register(plugin)?;
     udpsrc::register(plugin)?;
+    #[cfg(unix)]
+    unixdgramsink::register(plugin)?;
+    #[cfg(unix)]
+    unixdgramsrc::register(plugin)?;
 
     Ok(())
 }
@@ -8,8 +8,177 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
+use futures::channel::oneshot;
+
 use getifaddrs::Interface;
 
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::sync::{mpsc, LazyLock};
+use std::thread;
+use std::time::Duration;
+
+/// Parses an address optionally enclosed in brackets and with an IPv6 scope.
+///
+/// Accepted forms include `"224.1.1.1"`, `"::1"`, `"[ff02::1]"` and `"[ff02::1%eth0]"`.
+/// The scope can be either an interface name or an interface index.
+///
+/// Returns the address and the scope id (0 if none).
+pub fn parse_address(address: &str) -> Result<(IpAddr, u32), String> {
+    let address = address
+        .strip_prefix('[')
+        .and_then(|address| address.strip_suffix(']'))
+        .unwrap_or(address);
+
+    let (address, scope) = match address.split_once('%') {
+        Some((address, scope)) => (address, Some(scope)),
+        None => (address, None),
+    };
+
+    // v4-mapped IPv6 addresses are handled as IPv4 addresses
+    let addr = address
+        .parse::<IpAddr>()
+        .map_err(|err| format!("{err}"))?
+        .to_canonical();
+
+    let Some(scope) = scope else {
+        return Ok((addr, 0));
+    };
+
+    if addr.is_ipv4() {
+        return Err(format!("Unexpected scope '{scope}' for IPv4 address"));
+    }
+
+    if let Ok(scope_id) = scope.parse::<u32>() {
+        return Ok((addr, scope_id));
+    }
+
+    getifaddrs::getifaddrs()
+        .map_err(|err| format!("Failed to get interfaces: {err}"))?
+        .find(|iface| iface.name == scope)
+        .and_then(|iface| iface.index)
+        .map(|scope_id| (addr, scope_id))
+        .ok_or_else(|| format!("Unknown interface '{scope}'"))
+}
+
+/// Whether the socket buffer size reported by the OS is lower than the `requested` one.
+///
+/// Linux doubles the requested value to account for bookkeeping overhead
+/// and reports the doubled value back.
+pub fn buffer_size_clamped(requested: usize, effective: usize) -> bool {
+    #[cfg(target_os = "linux")]
+    let requested = requested.saturating_mul(2);
+
+    effective < requested
+}
+
+// Upper bound for the exponential backoff between reconnection attempts,
+// unless the initial delay itself is larger.
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Consecutive reconnection attempts of a client with exponential backoff.
+#[derive(Debug, Default)]
+pub struct ReconnectBackoff {
+    attempts: u32,
+}
+
+impl ReconnectBackoff {
+    /// Counts a new attempt and returns the delay to wait before it.
+    ///
+    /// The delay starts at `initial_delay` and doubles on each attempt.
+    /// Returns `None` if `max_attempts` (0 = unlimited) were already made.
+    pub fn next_delay(&mut self, initial_delay: Duration, max_attempts: u32) -> Option<Duration> {
+        if max_attempts > 0 && self.attempts >= max_attempts {
+            return None;
+        }
+
+        self.attempts += 1;
+        let backoff = 1u32 << (self.attempts - 1).min(16);
+
+        Some(
+            initial_delay
+                .saturating_mul(backoff)
+                .min(MAX_RECONNECT_BACKOFF.max(initial_delay)),
+        )
+    }
+
+    /// Number of consecutive attempts since the last reset.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Resets the attempts, e.g. once connected.
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+}
+
+type ResolveRequest = (String, u16, oneshot::Sender<io::Result<Vec<SocketAddr>>>);
+
+/// Resolves `host` without blocking the calling `Context`.
+///
+/// Name resolution is blocking, so it is performed on a thread shared
+/// by all the elements, one host at a time.
+pub async fn resolve(host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+    static RESOLVER: LazyLock<io::Result<mpsc::Sender<ResolveRequest>>> = LazyLock::new(|| {
+        let (sender, receiver) = mpsc::channel::<ResolveRequest>();
+        thread::Builder::new()
+            .name("ts-resolver".into())
+            .spawn(move || {
+                for (host, port, res_sender) in receiver {
+                    let res = (host.as_str(), port)
+                        .to_socket_addrs()
+                        .map(Iterator::collect);
+                    let _ = res_sender.send(res);
+                }
+            })?;
+
+        Ok(sender)
+    });
+
+    let sender = RESOLVER.as_ref().map_err(|err| {
+        io::Error::new(err.kind(), format!("Failed to start the resolver: {err}"))
+    })?;
+
+    let (res_sender, res_receiver) = oneshot::channel();
+    sender
+        .send((host.to_owned(), port, res_sender))
+        .map_err(|_| io::Error::other("Resolver not running"))?;
+
+    res_receiver
+        .await
+        .map_err(|_| io::Error::other("Resolver not running"))?
+}
+
+/// Builds the address of a unix socket.
+///
+/// If `is_abstract` is set, `path` is a name in the Linux abstract namespace
+/// and no file is involved.
+#[cfg(unix)]
+pub fn unix_socket_addr(
+    path: &str,
+    is_abstract: bool,
+) -> std::io::Result<std::os::unix::net::SocketAddr> {
+    use std::os::unix::net::SocketAddr;
+
+    if is_abstract {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            SocketAddr::from_abstract_name(path)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "the abstract namespace is only available on Linux",
+            ))
+        }
+    } else {
+        SocketAddr::from_pathname(path)
+    }
+}
+
 #[cfg(unix)]
 pub mod imp {
     use super::*;
@@ -24,9 +193,7 @@ pub mod imp {
     #[cfg(target_os = "macos")]
     use libc::ip_mreq;
 
-    #[cfg(any(target_os = "solaris", target_os = "illumos", target_os = "macos"))]
-    use std::net::IpAddr;
-    use std::net::Ipv4Addr;
+    use std::net::{Ipv4Addr, Ipv6Addr};
 
     /// Join multicast address for a given interface.
     pub fn join_multicast_v4(
@@ -268,6 +435,116 @@ pub mod imp {
             Ok(())
         }
     }
+
+    /// Joins the IPv6 multicast `group` for `source` on the interface with the provided index.
+    pub fn join_ssm_v6(
+        socket: &UdpSocket,
+        source: &Ipv6Addr,
+        group: &Ipv6Addr,
+        iface_index: u32,
+    ) -> Result<(), io::Error> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            ssm_operation_v6(
+                socket,
+                source,
+                group,
+                iface_index,
+                libc::MCAST_JOIN_SOURCE_GROUP,
+            )
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            let _ = (socket, source, group, iface_index);
+            Err(ssm_v6_unsupported())
+        }
+    }
+
+    /// Leaves the IPv6 multicast `group` for `source` on the interface with the provided index.
+    pub fn leave_ssm_v6(
+        socket: &UdpSocket,
+        source: &Ipv6Addr,
+        group: &Ipv6Addr,
+        iface_index: u32,
+    ) -> Result<(), io::Error> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            ssm_operation_v6(
+                socket,
+                source,
+                group,
+                iface_index,
+                libc::MCAST_LEAVE_SOURCE_GROUP,
+            )
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            let _ = (socket, source, group, iface_index);
+            Err(ssm_v6_unsupported())
+        }
+    }
+
+    /// `struct group_source_req` from `<netinet/in.h>`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[repr(C)]
+    struct GroupSourceReq {
+        gsr_interface: u32,
+        gsr_group: libc::sockaddr_storage,
+        gsr_source: libc::sockaddr_storage,
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn sockaddr_storage_v6(addr: &Ipv6Addr) -> libc::sockaddr_storage {
+        // SAFETY: sockaddr_storage is a plain C struct for which all zeroes is a valid value.
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+
+        // SAFETY: sockaddr_storage is large enough & suitably aligned for any sockaddr type.
+        let sin6 = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in6) };
+        sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+        sin6.sin6_addr.s6_addr = addr.octets();
+
+        storage
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn ssm_operation_v6(
+        socket: &UdpSocket,
+        source: &Ipv6Addr,
+        group: &Ipv6Addr,
+        iface_index: u32,
+        group_op: i32,
+    ) -> Result<(), io::Error> {
+        let req = GroupSourceReq {
+            gsr_interface: iface_index,
+            gsr_group: sockaddr_storage_v6(group),
+            gsr_source: sockaddr_storage_v6(source),
+        };
+
+        // SAFETY: Requires a valid group_source_req struct to be passed together with its size
+        // for checking validity. On errors a negative integer is returned.
+        unsafe {
+            if setsockopt(
+                socket.as_raw_fd(),
+                libc::IPPROTO_IPV6,
+                group_op,
+                &req as *const _ as *const _,
+                mem::size_of_val(&req) as _,
+            ) < 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn ssm_v6_unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "IPv6 source-specific multicast is not supported on this platform",
+        )
+    }
 }
 
 #[cfg(windows)]
@@ -276,7 +553,7 @@ pub mod imp {
 
     use std::{
         io, mem,
-        net::{Ipv4Addr, UdpSocket},
+        net::{Ipv4Addr, Ipv6Addr, UdpSocket},
         os::windows::io::AsRawSocket,
     };
 
@@ -368,4 +645,31 @@ pub mod imp {
 
         Ok(())
     }
+
+    /// Joins the IPv6 multicast `group` for `source` on the interface with the provided index.
+    pub fn join_ssm_v6(
+        _socket: &UdpSocket,
+        _source: &Ipv6Addr,
+        _group: &Ipv6Addr,
+        _iface_index: u32,
+    ) -> Result<(), io::Error> {
+        Err(ssm_v6_unsupported())
+    }
+
+    /// Leaves the IPv6 multicast `group` for `source` on the interface with the provided index.
+    pub fn leave_ssm_v6(
+        _socket: &UdpSocket,
+        _source: &Ipv6Addr,
+        _group: &Ipv6Addr,
+        _iface_index: u32,
+    ) -> Result<(), io::Error> {
+        Err(ssm_v6_unsupported())
+    }
+
+    fn ssm_v6_unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "IPv6 source-specific multicast is not supported on this platform",
+        )
+    }
 }
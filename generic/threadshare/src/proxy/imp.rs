@@ -28,21 +28,20 @@ use gst::subclass::prelude::*;
 use std::sync::LazyLock;
 
 use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 use std::sync::{Arc, Weak};
 use std::sync::{Mutex, MutexGuard};
 use std::time::Duration;
 
 use crate::runtime::prelude::*;
-use crate::runtime::{Context, PadSink, PadSinkWeak, PadSrc, PadSrcWeak, Task};
+use crate::runtime::{timer, Context, PadSink, PadSrc, PadSrcWeak, Task};
 
-use crate::dataqueue::{DataQueue, DataQueueItem};
+use crate::dataqueue::{DataQueue, DataQueueItem, DataQueueLevel};
+
+use super::UnconnectedMode;
 
 static PROXY_CONTEXTS: LazyLock<Mutex<HashMap<String, Weak<Mutex<ProxyContextInner>>>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
-static PROXY_SRC_PADS: LazyLock<Mutex<HashMap<String, PadSrcWeak>>> =
-    LazyLock::new(|| Mutex::new(HashMap::new()));
-static PROXY_SINK_PADS: LazyLock<Mutex<HashMap<String, PadSinkWeak>>> =
-    LazyLock::new(|| Mutex::new(HashMap::new()));
 
 const DEFAULT_PROXY_CONTEXT: &str = "";
 
@@ -51,20 +50,37 @@ const DEFAULT_MAX_SIZE_BYTES: u32 = 1024 * 1024;
 const DEFAULT_MAX_SIZE_TIME: gst::ClockTime = gst::ClockTime::SECOND;
 const DEFAULT_CONTEXT: &str = "";
 const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+const DEFAULT_UNCONNECTED_MODE: UnconnectedMode = UnconnectedMode::Block;
+const DEFAULT_UNCONNECTED_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_SINK_MAX_SIZE_TIME: gst::ClockTime = gst::ClockTime::ZERO;
+const DEFAULT_FORWARD_EOS: bool = true;
 
 #[derive(Debug, Clone)]
 struct SettingsSink {
     proxy_context: String,
+    unconnected_mode: UnconnectedMode,
+    unconnected_timeout: Duration,
+    max_size_time: gst::ClockTime,
 }
 
 impl Default for SettingsSink {
     fn default() -> Self {
         SettingsSink {
             proxy_context: DEFAULT_PROXY_CONTEXT.into(),
+            unconnected_mode: DEFAULT_UNCONNECTED_MODE,
+            unconnected_timeout: DEFAULT_UNCONNECTED_TIMEOUT,
+            max_size_time: DEFAULT_SINK_MAX_SIZE_TIME,
         }
     }
 }
 
+impl SettingsSink {
+    /* `None` lets each proxysrc apply its own max-size-time */
+    fn max_size_time(&self) -> Option<gst::ClockTime> {
+        Some(self.max_size_time).filter(|max_size_time| !max_size_time.is_zero())
+    }
+}
+
 #[derive(Debug, Clone)]
 struct SettingsSrc {
     max_size_buffers: u32,
@@ -73,6 +89,7 @@ struct SettingsSrc {
     context: String,
     context_wait: Duration,
     proxy_context: String,
+    forward_eos: bool,
 }
 
 impl Default for SettingsSrc {
@@ -84,11 +101,80 @@ impl Default for SettingsSrc {
             context: DEFAULT_CONTEXT.into(),
             context_wait: DEFAULT_CONTEXT_WAIT,
             proxy_context: DEFAULT_PROXY_CONTEXT.into(),
+            forward_eos: DEFAULT_FORWARD_EOS,
         }
     }
 }
 
-// TODO: Refactor into a Sender and Receiver instead of the have_ booleans
+impl SettingsSrc {
+    fn max_size_time(&self) -> Option<gst::ClockTime> {
+        Some(self.max_size_time).filter(|max_size_time| !max_size_time.is_zero())
+    }
+}
+
+/// Transfer statistics, updated on the streaming path.
+#[derive(Debug, Default)]
+struct Stats {
+    buffers: AtomicU64,
+    bytes: AtomicU64,
+    events: AtomicU64,
+    dropped: AtomicU64,
+    /// Monotonic time of the last transfer in µs, 0 if none
+    last_transfer: AtomicI64,
+}
+
+impl Stats {
+    fn account_transferred(&self, item: &DataQueueItem) {
+        match *item {
+            DataQueueItem::Buffer(_) | DataQueueItem::BufferList(_) => {
+                let (count, bytes) = item.size();
+                self.buffers.fetch_add(count.into(), Ordering::Relaxed);
+                self.bytes.fetch_add(bytes.into(), Ordering::Relaxed);
+            }
+            DataQueueItem::Event(_) => {
+                self.events.fetch_add(1, Ordering::Relaxed);
+            }
+            DataQueueItem::Barrier(_) => return,
+        }
+
+        self.last_transfer
+            .store(glib::monotonic_time(), Ordering::Relaxed);
+    }
+
+    fn account_dropped(&self, item: &DataQueueItem) {
+        let count = match *item {
+            DataQueueItem::Buffer(_) | DataQueueItem::BufferList(_) => item.size().0.into(),
+            // Sticky events are kept for the next proxysrc
+            DataQueueItem::Event(ref event) if event.is_sticky() => 0,
+            DataQueueItem::Event(_) => 1,
+            DataQueueItem::Barrier(_) => 0,
+        };
+        self.dropped.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        self.buffers.store(0, Ordering::Relaxed);
+        self.bytes.store(0, Ordering::Relaxed);
+        self.events.store(0, Ordering::Relaxed);
+        self.dropped.store(0, Ordering::Relaxed);
+        self.last_transfer.store(0, Ordering::Relaxed);
+    }
+
+    fn to_structure(&self, name: &str) -> gst::Structure {
+        let last_transfer = self.last_transfer.load(Ordering::Relaxed);
+
+        gst::Structure::builder(name)
+            .field("buffers-transferred", self.buffers.load(Ordering::Relaxed))
+            .field("bytes-transferred", self.bytes.load(Ordering::Relaxed))
+            .field("events-transferred", self.events.load(Ordering::Relaxed))
+            .field("dropped", self.dropped.load(Ordering::Relaxed))
+            .field_if_some(
+                "last-transfer-time",
+                (last_transfer > 0).then(|| (last_transfer as u64).useconds().nseconds()),
+            )
+            .build()
+    }
+}
 
 #[derive(Debug, Default)]
 struct PendingQueue {
@@ -103,14 +189,268 @@ impl PendingQueue {
     }
 }
 
+/// The queue of a proxysrc attached to a proxy context.
+#[derive(Debug)]
+struct ProxySrcQueue {
+    src_pad: PadSrcWeak,
+    dataqueue: DataQueue,
+    pending_queue: Option<PendingQueue>,
+    last_res: Result<gst::FlowSuccess, gst::FlowError>,
+    full: bool,
+    empty: bool,
+    /// The max-size-time of the proxysrc, applied unless the proxysink defines one
+    max_size_time: Option<gst::ClockTime>,
+}
+
+impl ProxySrcQueue {
+    fn new(
+        src_pad: PadSrcWeak,
+        dataqueue: DataQueue,
+        max_size_time: Option<gst::ClockTime>,
+    ) -> Self {
+        ProxySrcQueue {
+            src_pad,
+            dataqueue,
+            pending_queue: None,
+            last_res: Err(gst::FlowError::Flushing),
+            full: false,
+            empty: true,
+            max_size_time,
+        }
+    }
+
+    fn apply_max_size_time(&mut self, sink_max_size_time: Option<gst::ClockTime>) {
+        self.dataqueue
+            .set_max_size_time(sink_max_size_time.or(self.max_size_time));
+        // The limit might have grown
+        self.notify_more_queue_space();
+    }
+
+    /* Refreshes the full & empty flags from the dataqueue.
+     * Returns the names of the flags which changed */
+    fn update_fill_state(&mut self) -> Vec<&'static str> {
+        let full = self.dataqueue.is_full();
+        let empty = self.dataqueue.is_empty();
+
+        let mut changed = Vec::new();
+        if full != self.full {
+            self.full = full;
+            changed.push("full");
+        }
+        if empty != self.empty {
+            self.empty = empty;
+            changed.push("empty");
+        }
+
+        changed
+    }
+
+    /* Sticky events are kept in the pending queue until the next item is queued */
+    fn replay_sticky_events(&mut self, sticky_events: &[gst::Event]) {
+        if sticky_events.is_empty() {
+            return;
+        }
+
+        let pending_queue = self.pending_queue.get_or_insert_with(PendingQueue::default);
+        pending_queue.items.extend(
+            sticky_events
+                .iter()
+                .map(|event| DataQueueItem::Event(event.clone())),
+        );
+    }
+
+    /* Queues the item, keeping it in the pending queue if the dataqueue is full.
+     * Returns `true` if the caller must wait for the pending queue to be emptied */
+    fn queue_item(&mut self, item: DataQueueItem) -> bool {
+        let item = match self.pending_queue {
+            None => self.dataqueue.push(item),
+            Some(ref mut pending_queue) if !pending_queue.scheduled => {
+                let mut failed_item = None;
+                while let Some(item) = pending_queue.items.pop_front() {
+                    if let Err(item) = self.dataqueue.push(item) {
+                        failed_item = Some(item);
+                        break;
+                    }
+                }
+
+                if let Some(failed_item) = failed_item {
+                    pending_queue.items.push_front(failed_item);
+
+                    Err(item)
+                } else {
+                    self.dataqueue.push(item)
+                }
+            }
+            Some(_) => Err(item),
+        };
+
+        let Err(item) = item else {
+            return false;
+        };
+
+        let pending_queue = self.pending_queue.get_or_insert_with(PendingQueue::default);
+
+        // Non-EOS events can wait for the next buffer to schedule the pending queue
+        if !matches!(
+            item,
+            DataQueueItem::Event(ref ev) if ev.type_() != gst::EventType::Eos,
+        ) {
+            pending_queue.scheduled = true;
+        }
+
+        pending_queue.items.push_back(item);
+
+        pending_queue.scheduled
+    }
+
+    /* Moves as many pending items as possible to the dataqueue.
+     * Returns a receiver to wait on if the dataqueue is still full */
+    fn try_empty_pending_queue(&mut self) -> Option<oneshot::Receiver<()>> {
+        let pending_queue = self.pending_queue.as_mut()?;
+
+        while let Some(item) = pending_queue.items.pop_front() {
+            if let Err(item) = self.dataqueue.push(item) {
+                pending_queue.items.push_front(item);
+                let (sender, receiver) = oneshot::channel();
+                pending_queue.more_queue_space_sender = Some(sender);
+
+                return Some(receiver);
+            }
+        }
+
+        self.pending_queue = None;
+
+        None
+    }
+
+    fn notify_more_queue_space(&mut self) {
+        if let Some(pending_queue) = self.pending_queue.as_mut() {
+            pending_queue.notify_more_queue_space();
+        }
+    }
+}
+
 #[derive(Debug)]
 struct ProxyContextInner {
     name: String,
-    dataqueue: Option<DataQueue>,
+    /// Queues of the attached proxysrcs, by id
+    src_queues: HashMap<u64, ProxySrcQueue>,
+    next_src_id: u64,
+    /// Notified when a proxysrc attaches to the context
+    src_waiter: Option<oneshot::Sender<()>>,
+    /// Sticky events to replay to newly attached proxysrcs
+    sticky_events: Vec<gst::Event>,
     last_res: Result<gst::FlowSuccess, gst::FlowError>,
-    pending_queue: Option<PendingQueue>,
-    have_sink: bool,
-    have_src: bool,
+    sink: Option<glib::WeakRef<super::ProxySink>>,
+    /// Latency upstream of the proxysink: (live, min, max)
+    upstream_latency: Option<(bool, gst::ClockTime, Option<gst::ClockTime>)>,
+    /// The max-size-time of the proxysink, which overrides the proxysrcs'
+    max_size_time: Option<gst::ClockTime>,
+}
+
+impl ProxyContextInner {
+    fn store_sticky_event(&mut self, event: &gst::Event) {
+        let event_type = event.type_();
+        match event_type {
+            gst::EventType::FlushStop => self
+                .sticky_events
+                .retain(|ev| !matches!(ev.type_(), gst::EventType::Segment | gst::EventType::Eos)),
+            _ if event.is_sticky() => {
+                if event_type == gst::EventType::StreamStart {
+                    self.sticky_events
+                        .retain(|ev| ev.type_() != gst::EventType::Eos);
+                }
+
+                match self
+                    .sticky_events
+                    .iter_mut()
+                    .find(|ev| ev.type_() == event_type)
+                {
+                    Some(ev) => *ev = event.clone(),
+                    None => self.sticky_events.push(event.clone()),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn set_max_size_time(&mut self, max_size_time: Option<gst::ClockTime>) {
+        self.max_size_time = max_size_time;
+        for src_queue in self.src_queues.values_mut() {
+            src_queue.apply_max_size_time(max_size_time);
+        }
+    }
+
+    /* The level of the fullest proxysrc queue: this is the one holding back the proxysink */
+    fn level(&self) -> DataQueueLevel {
+        self.src_queues
+            .values()
+            .map(|src_queue| src_queue.dataqueue.level())
+            .max_by_key(|level| (level.buffers, level.bytes, level.time))
+            .unwrap_or_default()
+    }
+
+    /* The proxysink is full as soon as one proxysrc queue is full */
+    fn is_full(&self) -> bool {
+        self.src_queues.values().any(|src_queue| src_queue.full)
+    }
+
+    /* The proxysink is empty when all the proxysrc queues are empty */
+    fn is_empty(&self) -> bool {
+        self.src_queues.values().all(|src_queue| src_queue.empty)
+    }
+
+    /* Refreshes the fill state of the proxysrc queues.
+     * Returns the elements and properties to notify */
+    fn update_fill_state(&mut self) -> Vec<(gst::Element, &'static str)> {
+        let (was_full, was_empty) = (self.is_full(), self.is_empty());
+
+        let mut changes = Vec::new();
+        for src_queue in self.src_queues.values_mut() {
+            let changed = src_queue.update_fill_state();
+            if changed.is_empty() {
+                continue;
+            }
+
+            if let Some(element) = src_queue
+                .src_pad
+                .upgrade()
+                .and_then(|src_pad| src_pad.gst_pad().parent_element())
+            {
+                changes.extend(changed.into_iter().map(|name| (element.clone(), name)));
+            }
+        }
+
+        if let Some(sink) = self.sink.as_ref().and_then(|sink| sink.upgrade()) {
+            if was_full != self.is_full() {
+                changes.push((sink.clone().upcast(), "full"));
+            }
+            if was_empty != self.is_empty() {
+                changes.push((sink.upcast(), "empty"));
+            }
+        }
+
+        changes
+    }
+
+    /* The flow result for the proxysink: successful as long as one
+     * of the proxysrcs accepts data */
+    fn sink_res(&self) -> Result<gst::FlowSuccess, gst::FlowError> {
+        self.last_res?;
+
+        let mut res = Ok(gst::FlowSuccess::Ok);
+        for (idx, src_queue) in self.src_queues.values().enumerate() {
+            if src_queue.last_res.is_ok() {
+                return src_queue.last_res;
+            }
+
+            if idx == 0 {
+                res = src_queue.last_res;
+            }
+        }
+
+        res
+    }
 }
 
 impl Drop for ProxyContextInner {
@@ -123,7 +463,8 @@ impl Drop for ProxyContextInner {
 #[derive(Debug)]
 struct ProxyContext {
     shared: Arc<Mutex<ProxyContextInner>>,
-    as_sink: bool,
+    /// `None` for the proxysink
+    src_id: Option<u64>,
     name: String,
 }
 
@@ -133,77 +474,145 @@ impl ProxyContext {
         self.shared.lock().unwrap()
     }
 
-    fn get(name: &str, as_sink: bool) -> Option<Self> {
-        let mut proxy_ctxs = PROXY_CONTEXTS.lock().unwrap();
+    fn get_shared(
+        proxy_ctxs: &mut HashMap<String, Weak<Mutex<ProxyContextInner>>>,
+        name: &str,
+    ) -> Arc<Mutex<ProxyContextInner>> {
+        if let Some(shared) = proxy_ctxs.get(name).and_then(Weak::upgrade) {
+            return shared;
+        }
 
-        let mut proxy_ctx = None;
-        if let Some(shared_weak) = proxy_ctxs.get(name) {
-            if let Some(shared) = shared_weak.upgrade() {
-                {
-                    let shared = shared.lock().unwrap();
-                    if (shared.have_sink && as_sink) || (shared.have_src && !as_sink) {
-                        return None;
-                    }
-                }
+        let shared = Arc::new(Mutex::new(ProxyContextInner {
+            name: name.into(),
+            src_queues: HashMap::new(),
+            next_src_id: 0,
+            src_waiter: None,
+            sticky_events: Vec::new(),
+            last_res: Err(gst::FlowError::Flushing),
+            sink: None,
+            upstream_latency: None,
+            max_size_time: None,
+        }));
+
+        proxy_ctxs.insert(name.into(), Arc::downgrade(&shared));
+
+        shared
+    }
 
-                proxy_ctx = Some({
-                    let proxy_ctx = ProxyContext {
-                        shared,
-                        as_sink,
-                        name: name.into(),
-                    };
-                    {
-                        let mut shared = proxy_ctx.lock_shared();
-                        if as_sink {
-                            shared.have_sink = true;
-                        } else {
-                            shared.have_src = true;
-                        }
-                    }
+    fn get_sink(name: &str, sink: &super::ProxySink) -> Option<Self> {
+        let mut proxy_ctxs = PROXY_CONTEXTS.lock().unwrap();
+        let shared = Self::get_shared(&mut proxy_ctxs, name);
 
-                    proxy_ctx
-                });
+        {
+            let mut shared = shared.lock().unwrap();
+            if shared.sink.is_some() {
+                return None;
             }
+            shared.sink = Some(sink.downgrade());
         }
 
-        if proxy_ctx.is_none() {
-            let shared = Arc::new(Mutex::new(ProxyContextInner {
-                name: name.into(),
-                dataqueue: None,
-                last_res: Err(gst::FlowError::Flushing),
-                pending_queue: None,
-                have_sink: as_sink,
-                have_src: !as_sink,
-            }));
-
-            proxy_ctxs.insert(name.into(), Arc::downgrade(&shared));
-
-            proxy_ctx = Some(ProxyContext {
-                shared,
-                as_sink,
-                name: name.into(),
-            });
+        Some(ProxyContext {
+            shared,
+            src_id: None,
+            name: name.into(),
+        })
+    }
+
+    fn get_src(name: &str, mut src_queue: ProxySrcQueue) -> Self {
+        let mut proxy_ctxs = PROXY_CONTEXTS.lock().unwrap();
+        let shared = Self::get_shared(&mut proxy_ctxs, name);
+
+        let src_id = {
+            let mut shared = shared.lock().unwrap();
+            let src_id = shared.next_src_id;
+            shared.next_src_id += 1;
+
+            src_queue.replay_sticky_events(&shared.sticky_events);
+            src_queue.apply_max_size_time(shared.max_size_time);
+            shared.src_queues.insert(src_id, src_queue);
+
+            // Wake up the proxysink if it was waiting for a proxysrc
+            shared.src_waiter.take();
+
+            src_id
+        };
+
+        ProxyContext {
+            shared,
+            src_id: Some(src_id),
+            name: name.into(),
         }
+    }
 
-        proxy_ctx
+    /* Notifies the proxysink, if any, that `proxysrc` was attached or detached */
+    fn notify_sink(
+        shared: &Mutex<ProxyContextInner>,
+        name: &str,
+        proxysrc: &super::ProxySrc,
+        attached: bool,
+    ) {
+        let (sink, n_srcs) = {
+            let shared_ctx = shared.lock().unwrap();
+            (
+                shared_ctx.sink.as_ref().and_then(|sink| sink.upgrade()),
+                shared_ctx.src_queues.len(),
+            )
+        };
+
+        if let Some(sink) = sink {
+            sink.imp()
+                .post_proxysrc_message(name, proxysrc, attached, n_srcs);
+        }
+    }
+
+    fn with_src_queue<R>(&self, func: impl FnOnce(&mut ProxySrcQueue) -> R) -> R {
+        let mut shared_ctx = self.lock_shared();
+        let src_id = self.src_id.expect("not a proxysrc");
+        func(shared_ctx.src_queues.get_mut(&src_id).unwrap())
     }
 }
 
 impl Drop for ProxyContext {
     fn drop(&mut self) {
         let mut shared_ctx = self.lock_shared();
-        if self.as_sink {
-            assert!(shared_ctx.have_sink);
-            shared_ctx.have_sink = false;
-            let _ = shared_ctx.pending_queue.take();
-        } else {
-            assert!(shared_ctx.have_src);
-            shared_ctx.have_src = false;
-            let _ = shared_ctx.dataqueue.take();
+        match self.src_id {
+            None => {
+                assert!(shared_ctx.sink.is_some());
+                shared_ctx.sink = None;
+                shared_ctx.src_waiter.take();
+                shared_ctx.sticky_events.clear();
+                shared_ctx.upstream_latency = None;
+                shared_ctx.set_max_size_time(None);
+                for src_queue in shared_ctx.src_queues.values_mut() {
+                    let _ = src_queue.pending_queue.take();
+                }
+            }
+            Some(src_id) => {
+                // Dropping the pending queue wakes up the proxysink if it was waiting for it
+                let src_queue = shared_ctx.src_queues.remove(&src_id);
+                assert!(src_queue.is_some());
+            }
         }
     }
 }
 
+/* Must be called without holding the proxy context lock */
+fn notify_fill_state(changes: Vec<(gst::Element, &'static str)>) {
+    for (element, property_name) in changes {
+        element.notify(property_name);
+    }
+}
+
+/* Shares an item with one of the proxysrcs: buffers and events are refcounted */
+fn share_item(item: &DataQueueItem) -> DataQueueItem {
+    match *item {
+        DataQueueItem::Buffer(ref buffer) => DataQueueItem::Buffer(buffer.clone()),
+        DataQueueItem::BufferList(ref list) => DataQueueItem::BufferList(list.clone()),
+        DataQueueItem::Event(ref event) => DataQueueItem::Event(event.clone()),
+        DataQueueItem::Barrier(_) => unreachable!("proxysink doesn't queue barriers"),
+    }
+}
+
 #[derive(Clone, Debug)]
 struct ProxySinkPadHandler;
 
@@ -241,33 +650,36 @@ impl PadSinkHandler for ProxySinkPadHandler {
     fn sink_event(self, pad: &gst::Pad, imp: &ProxySink, event: gst::Event) -> bool {
         gst::debug!(SINK_CAT, obj = pad, "Handling non-serialized {:?}", event);
 
-        let src_pad = {
+        let src_pads = {
             let proxy_ctx = imp.proxy_ctx.lock().unwrap();
+            let shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
 
-            PROXY_SRC_PADS
-                .lock()
-                .unwrap()
-                .get(&proxy_ctx.as_ref().unwrap().name)
-                .and_then(|src_pad| src_pad.upgrade())
+            shared_ctx
+                .src_queues
+                .values()
+                .filter_map(|src_queue| src_queue.src_pad.upgrade())
                 .map(|src_pad| src_pad.gst_pad().clone())
+                .collect::<Vec<_>>()
         };
 
         if let gst::EventView::FlushStart(..) = event.view() {
             imp.stop();
         }
 
-        if let Some(src_pad) = src_pad {
-            gst::log!(SINK_CAT, obj = pad, "Forwarding non-serialized {:?}", event);
-            src_pad.push_event(event)
-        } else {
+        if src_pads.is_empty() {
             gst::error!(
                 SINK_CAT,
                 obj = pad,
                 "No src pad to forward non-serialized {:?} to",
                 event
             );
-            true
+            return true;
         }
+
+        gst::log!(SINK_CAT, obj = pad, "Forwarding non-serialized {:?}", event);
+        src_pads.iter().fold(true, |res, src_pad| {
+            src_pad.push_event(event.clone()) && res
+        })
     }
 
     fn sink_event_serialized(
@@ -302,6 +714,7 @@ pub struct ProxySink {
     sink_pad: PadSink,
     proxy_ctx: Mutex<Option<ProxyContext>>,
     settings: Mutex<SettingsSink>,
+    stats: Stats,
 }
 
 static SINK_CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
@@ -313,51 +726,25 @@ static SINK_CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
 });
 
 impl ProxySink {
-    async fn schedule_pending_queue(&self) {
+    async fn wait_pending_queues(&self) {
         loop {
             let more_queue_space_receiver = {
                 let proxy_ctx = self.proxy_ctx.lock().unwrap();
                 let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
 
-                gst::log!(SINK_CAT, imp = self, "Trying to empty pending queue");
-
-                let ProxyContextInner {
-                    pending_queue: ref mut pq,
-                    ref dataqueue,
-                    ..
-                } = *shared_ctx;
-
-                if let Some(ref mut pending_queue) = *pq {
-                    if let Some(ref dataqueue) = dataqueue {
-                        let mut failed_item = None;
-                        while let Some(item) = pending_queue.items.pop_front() {
-                            if let Err(item) = dataqueue.push(item) {
-                                failed_item = Some(item);
-                                break;
-                            }
-                        }
-
-                        if let Some(failed_item) = failed_item {
-                            pending_queue.items.push_front(failed_item);
-                            let (sender, receiver) = oneshot::channel();
-                            pending_queue.more_queue_space_sender = Some(sender);
-
-                            receiver
-                        } else {
-                            gst::log!(SINK_CAT, imp = self, "Pending queue is empty now");
-                            *pq = None;
-                            return;
-                        }
-                    } else {
-                        let (sender, receiver) = oneshot::channel();
-                        pending_queue.more_queue_space_sender = Some(sender);
-
-                        receiver
+                gst::log!(SINK_CAT, imp = self, "Trying to empty pending queues");
+
+                let receiver = shared_ctx
+                    .src_queues
+                    .values_mut()
+                    .find_map(ProxySrcQueue::try_empty_pending_queue);
+
+                match receiver {
+                    Some(receiver) => receiver,
+                    None => {
+                        gst::log!(SINK_CAT, imp = self, "Pending queues are empty now");
+                        return;
                     }
-                } else {
-                    gst::log!(SINK_CAT, imp = self, "Flushing, dropping pending queue");
-                    *pq = None;
-                    return;
                 }
             };
 
@@ -367,127 +754,141 @@ impl ProxySink {
     }
 
     async fn enqueue_item(&self, item: DataQueueItem) -> Result<gst::FlowSuccess, gst::FlowError> {
-        let wait_fut = {
-            let proxy_ctx = self.proxy_ctx.lock().unwrap();
-            let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-
-            /* We've taken the lock again, make sure not to recreate
-             * a pending queue if tearing down */
-            shared_ctx.last_res?;
-
-            let item = {
-                let ProxyContextInner {
-                    ref mut pending_queue,
-                    ref dataqueue,
-                    ..
-                } = *shared_ctx;
-
-                match (pending_queue, dataqueue) {
-                    (None, Some(ref dataqueue)) => dataqueue.push(item),
-                    (Some(ref mut pending_queue), Some(ref dataqueue)) => {
-                        if !pending_queue.scheduled {
-                            let mut failed_item = None;
-                            while let Some(item) = pending_queue.items.pop_front() {
-                                if let Err(item) = dataqueue.push(item) {
-                                    failed_item = Some(item);
-                                    break;
-                                }
-                            }
-
-                            if let Some(failed_item) = failed_item {
-                                pending_queue.items.push_front(failed_item);
-
-                                Err(item)
-                            } else {
-                                dataqueue.push(item)
-                            }
+        let (unconnected_mode, unconnected_timeout) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.unconnected_mode, settings.unconnected_timeout)
+        };
+
+        let mut item = Some(item);
+
+        let (must_wait, fill_state_changes) = loop {
+            let src_waiter = {
+                let proxy_ctx = self.proxy_ctx.lock().unwrap();
+                let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
+
+                /* We've taken the lock again, make sure not to recreate
+                 * a pending queue if tearing down */
+                shared_ctx.last_res?;
+
+                if !shared_ctx.src_queues.is_empty() {
+                    // Serialized events go through the same queues as the buffers,
+                    // so they reach the proxysrcs in the exact same order
+                    if let Some(DataQueueItem::Event(ref event)) = item {
+                        shared_ctx.store_sticky_event(event);
+                    }
+
+                    self.stats.account_transferred(item.as_ref().unwrap());
+
+                    // Each proxysrc gets its own reference to the item
+                    let n_srcs = shared_ctx.src_queues.len();
+                    let mut must_wait = false;
+                    for (idx, src_queue) in shared_ctx.src_queues.values_mut().enumerate() {
+                        let item = if idx + 1 == n_srcs {
+                            item.take().unwrap()
                         } else {
-                            Err(item)
-                        }
+                            share_item(item.as_ref().unwrap())
+                        };
+
+                        must_wait |= src_queue.queue_item(item);
                     }
-                    _ => Err(item),
+
+                    break (must_wait, shared_ctx.update_fill_state());
                 }
-            };
 
-            if let Err(item) = item {
-                if shared_ctx
-                    .pending_queue
-                    .as_ref()
-                    .map(|pending_queue| !pending_queue.scheduled)
-                    .unwrap_or(true)
-                {
-                    if shared_ctx.pending_queue.is_none() {
-                        shared_ctx.pending_queue = Some(PendingQueue::default());
+                if unconnected_mode == UnconnectedMode::Drop {
+                    if let Some(DataQueueItem::Event(ref event)) = item {
+                        shared_ctx.store_sticky_event(event);
                     }
 
-                    let pending_queue = shared_ctx.pending_queue.as_mut().unwrap();
+                    gst::log!(SINK_CAT, imp = self, "No proxysrc, dropping {:?}", item);
+                    self.stats.account_dropped(item.as_ref().unwrap());
+                    return Ok(gst::FlowSuccess::Ok);
+                }
 
-                    let schedule_now = !matches!(
-                        item,
-                        DataQueueItem::Event(ref ev) if ev.type_() != gst::EventType::Eos,
-                    );
+                gst::log!(SINK_CAT, imp = self, "No proxysrc yet, waiting for one");
+                let (sender, receiver) = oneshot::channel();
+                shared_ctx.src_waiter = Some(sender);
 
-                    pending_queue.items.push_back(item);
+                receiver
+            };
 
-                    gst::log!(
-                        SINK_CAT,
-                        imp = self,
-                        "Proxy is full - Pushing first item on pending queue"
+            if unconnected_mode == UnconnectedMode::Error {
+                let timeout = std::pin::pin!(timer::delay_for(unconnected_timeout));
+                if let future::Either::Right(_) = future::select(src_waiter, timeout).await {
+                    gst::element_imp_error!(
+                        self,
+                        gst::ResourceError::NotFound,
+                        ["No proxysrc attached after {:?}", unconnected_timeout]
                     );
-
-                    if schedule_now {
-                        gst::log!(SINK_CAT, imp = self, "Scheduling pending queue now");
-                        pending_queue.scheduled = true;
-
-                        let wait_fut = self.schedule_pending_queue();
-                        Some(wait_fut)
-                    } else {
-                        gst::log!(SINK_CAT, imp = self, "Scheduling pending queue later");
-
-                        None
-                    }
-                } else {
-                    shared_ctx
-                        .pending_queue
-                        .as_mut()
-                        .unwrap()
-                        .items
-                        .push_back(item);
-
-                    None
+                    return Err(gst::FlowError::Error);
                 }
             } else {
-                None
+                let _ = src_waiter.await;
             }
         };
 
-        if let Some(wait_fut) = wait_fut {
-            gst::log!(SINK_CAT, imp = self, "Blocking until queue has space again");
-            wait_fut.await;
+        notify_fill_state(fill_state_changes);
+
+        if must_wait {
+            gst::log!(
+                SINK_CAT,
+                imp = self,
+                "Blocking until queues have space again"
+            );
+            self.wait_pending_queues().await;
+            self.update_fill_state();
         }
 
         let proxy_ctx = self.proxy_ctx.lock().unwrap();
         let shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-        shared_ctx.last_res
+        shared_ctx.sink_res()
+    }
+
+    fn update_fill_state(&self) {
+        let fill_state_changes = match *self.proxy_ctx.lock().unwrap() {
+            Some(ref proxy_ctx) => proxy_ctx.lock_shared().update_fill_state(),
+            None => return,
+        };
+
+        notify_fill_state(fill_state_changes);
+    }
+
+    fn current_level(&self) -> DataQueueLevel {
+        self.proxy_ctx
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|proxy_ctx| proxy_ctx.lock_shared().level())
+            .unwrap_or_default()
+    }
+
+    fn fill_state(&self) -> (bool, bool) {
+        self.proxy_ctx
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or((false, true), |proxy_ctx| {
+                let shared_ctx = proxy_ctx.lock_shared();
+                (shared_ctx.is_full(), shared_ctx.is_empty())
+            })
     }
 
     fn prepare(&self) -> Result<(), gst::ErrorMessage> {
         gst::debug!(SINK_CAT, imp = self, "Preparing");
 
-        let proxy_context = self.settings.lock().unwrap().proxy_context.to_string();
+        let settings = self.settings.lock().unwrap().clone();
 
-        let proxy_ctx = ProxyContext::get(&proxy_context, true).ok_or_else(|| {
-            gst::error_msg!(
-                gst::ResourceError::OpenRead,
-                ["Failed to create or get ProxyContext"]
-            )
-        })?;
+        let proxy_ctx =
+            ProxyContext::get_sink(&settings.proxy_context, &self.obj()).ok_or_else(|| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to create or get ProxyContext"]
+                )
+            })?;
 
-        {
-            let mut proxy_sink_pads = PROXY_SINK_PADS.lock().unwrap();
-            assert!(!proxy_sink_pads.contains_key(&proxy_context));
-            proxy_sink_pads.insert(proxy_context, self.sink_pad.downgrade());
-        }
+        proxy_ctx
+            .lock_shared()
+            .set_max_size_time(settings.max_size_time());
 
         *self.proxy_ctx.lock().unwrap() = Some(proxy_ctx);
 
@@ -496,24 +897,108 @@ impl ProxySink {
         Ok(())
     }
 
+    /* Queries the latency upstream of the proxysink and caches it for the proxysrcs.
+     * If it changed, the proxysrcs post a Latency message so that their
+     * pipelines query it again */
+    fn query_upstream_latency(&self) -> Option<(bool, gst::ClockTime, Option<gst::ClockTime>)> {
+        let mut query = gst::query::Latency::new();
+        if !self.sink_pad.gst_pad().peer_query(&mut query) {
+            gst::debug!(SINK_CAT, imp = self, "Upstream latency query failed");
+            return None;
+        }
+
+        let latency = query.result();
+        gst::debug!(SINK_CAT, imp = self, "Upstream latency {:?}", latency);
+
+        let proxysrcs = {
+            let proxy_ctx = self.proxy_ctx.lock().unwrap();
+            let Some(ref proxy_ctx) = *proxy_ctx else {
+                return Some(latency);
+            };
+
+            let mut shared_ctx = proxy_ctx.lock_shared();
+            match shared_ctx.upstream_latency.replace(latency) {
+                Some(prev_latency) if prev_latency != latency => shared_ctx
+                    .src_queues
+                    .values()
+                    .filter_map(|src_queue| {
+                        src_queue
+                            .src_pad
+                            .upgrade()
+                            .and_then(|src_pad| src_pad.gst_pad().parent_element())
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+
+        for proxysrc in proxysrcs {
+            let _ = proxysrc.post_message(gst::message::Latency::builder().src(&proxysrc).build());
+        }
+
+        Some(latency)
+    }
+
+    fn post_proxysrc_message(
+        &self,
+        proxy_context: &str,
+        proxysrc: &super::ProxySrc,
+        attached: bool,
+        n_proxysrcs: usize,
+    ) {
+        gst::info!(
+            SINK_CAT,
+            imp = self,
+            "{} {} ({} proxysrcs attached)",
+            if attached { "Attached" } else { "Detached" },
+            proxysrc.name(),
+            n_proxysrcs,
+        );
+
+        let obj = self.obj();
+        let _ = obj.post_message(
+            gst::message::Element::builder(
+                gst::Structure::builder(if attached {
+                    "GstTsProxySrcAttached"
+                } else {
+                    "GstTsProxySrcDetached"
+                })
+                .field("proxy-context", proxy_context)
+                .field("proxysrc", proxysrc)
+                .field("n-proxysrcs", n_proxysrcs as u32)
+                .build(),
+            )
+            .src(&*obj)
+            .build(),
+        );
+    }
+
     fn unprepare(&self) {
         gst::debug!(SINK_CAT, imp = self, "Unpreparing");
         *self.proxy_ctx.lock().unwrap() = None;
+        self.stats.reset();
         gst::debug!(SINK_CAT, imp = self, "Unprepared");
     }
 
+    /* Moves the prepared proxysink to the proxy context from the settings */
+    fn rebind_proxy_context(&self) {
+        if self.proxy_ctx.lock().unwrap().take().is_none() {
+            return;
+        }
+
+        gst::debug!(SINK_CAT, imp = self, "Rebinding proxy context");
+
+        if let Err(err) = self.prepare() {
+            self.post_error_message(err);
+        }
+    }
+
     fn start(&self) {
         let proxy_ctx = self.proxy_ctx.lock().unwrap();
         let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
 
         gst::debug!(SINK_CAT, imp = self, "Starting");
 
-        {
-            let settings = self.settings.lock().unwrap();
-            let mut proxy_sink_pads = PROXY_SINK_PADS.lock().unwrap();
-            proxy_sink_pads.remove(&settings.proxy_context);
-        }
-
         shared_ctx.last_res = Ok(gst::FlowSuccess::Ok);
 
         gst::debug!(SINK_CAT, imp = self, "Started");
@@ -525,7 +1010,10 @@ impl ProxySink {
 
         gst::debug!(SINK_CAT, imp = self, "Stopping");
 
-        let _ = shared_ctx.pending_queue.take();
+        for src_queue in shared_ctx.src_queues.values_mut() {
+            let _ = src_queue.pending_queue.take();
+        }
+        shared_ctx.src_waiter.take();
         shared_ctx.last_res = Err(gst::FlowError::Flushing);
 
         gst::debug!(SINK_CAT, imp = self, "Stopped");
@@ -546,6 +1034,7 @@ impl ObjectSubclass for ProxySink {
             ),
             proxy_ctx: Mutex::new(None),
             settings: Mutex::new(SettingsSink::default()),
+            stats: Stats::default(),
         }
     }
 }
@@ -553,11 +1042,72 @@ impl ObjectSubclass for ProxySink {
 impl ObjectImpl for ProxySink {
     fn properties() -> &'static [glib::ParamSpec] {
         static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
-            vec![glib::ParamSpecString::builder("proxy-context")
-                .nick("Proxy Context")
-                .blurb("Context name of the proxy to share with")
-                .default_value(Some(DEFAULT_PROXY_CONTEXT))
-                .build()]
+            vec![
+                glib::ParamSpecString::builder("proxy-context")
+                    .nick("Proxy Context")
+                    .blurb("Context name of the proxy to share with")
+                    .default_value(Some(DEFAULT_PROXY_CONTEXT))
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default(
+                    "unconnected-mode",
+                    DEFAULT_UNCONNECTED_MODE,
+                )
+                .nick("Unconnected Mode")
+                .blurb("What to do with incoming data while no proxysrc is attached")
+                .mutable_playing()
+                .build(),
+                glib::ParamSpecUInt::builder("unconnected-timeout")
+                    .nick("Unconnected Timeout")
+                    .blurb("Time in ms to wait for a proxysrc before erroring out in error mode")
+                    .default_value(DEFAULT_UNCONNECTED_TIMEOUT.as_millis() as u32)
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecUInt64::builder("max-size-time")
+                    .nick("Max Size Time")
+                    .blurb("Maximum number of nanoseconds to queue for each proxysrc (0=use the proxysrc's max-size-time)")
+                    .maximum(u64::MAX - 1)
+                    .default_value(DEFAULT_SINK_MAX_SIZE_TIME.nseconds())
+                    .mutable_playing()
+                    .build(),
+                /**
+                 * ts-proxysink:stats:
+                 *
+                 * Transfer statistics since the element left the NULL state:
+                 * number of buffers, bytes and events handed to the proxysrcs,
+                 * number of items dropped while no proxysrc was attached, and the
+                 * monotonic time of the last transfer in ns if any.
+                 */
+                glib::ParamSpecBoxed::builder::<gst::Structure>("stats")
+                    .nick("Statistics")
+                    .blurb("Statistics about the transfers through the proxy")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecUInt::builder("current-level-buffers")
+                    .nick("Current Level Buffers")
+                    .blurb("Current number of buffers in the fullest proxysrc queue")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecUInt::builder("current-level-bytes")
+                    .nick("Current Level Bytes")
+                    .blurb("Current amount of data in the fullest proxysrc queue (bytes)")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecUInt64::builder("current-level-time")
+                    .nick("Current Level Time")
+                    .blurb("Current amount of data in the fullest proxysrc queue (in ns)")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecBoolean::builder("full")
+                    .nick("Full")
+                    .blurb("Whether the queue of one of the proxysrcs is full")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecBoolean::builder("empty")
+                    .nick("Empty")
+                    .blurb("Whether the queues of all the proxysrcs are empty")
+                    .read_only()
+                    .build(),
+            ]
         });
 
         PROPERTIES.as_ref()
@@ -567,19 +1117,77 @@ impl ObjectImpl for ProxySink {
         let mut settings = self.settings.lock().unwrap();
         match pspec.name() {
             "proxy-context" => {
-                settings.proxy_context = value
+                let proxy_context = value
                     .get::<Option<String>>()
                     .expect("type checked upstream")
                     .unwrap_or_else(|| DEFAULT_PROXY_CONTEXT.into());
+                if proxy_context == settings.proxy_context {
+                    return;
+                }
+
+                let state = self.obj().current_state();
+                if state > gst::State::Ready {
+                    gst::warning!(
+                        SINK_CAT,
+                        imp = self,
+                        "Can't change proxy-context from {} to {} in {:?}",
+                        settings.proxy_context,
+                        proxy_context,
+                        state,
+                    );
+                    return;
+                }
+
+                settings.proxy_context = proxy_context;
+                drop(settings);
+
+                if state == gst::State::Ready {
+                    self.rebind_proxy_context();
+                }
+            }
+            "unconnected-mode" => {
+                settings.unconnected_mode = value.get().expect("type checked upstream");
+            }
+            "unconnected-timeout" => {
+                settings.unconnected_timeout = Duration::from_millis(
+                    value.get::<u32>().expect("type checked upstream").into(),
+                );
+            }
+            "max-size-time" => {
+                settings.max_size_time = value.get::<u64>().unwrap().nseconds();
+                let max_size_time = settings.max_size_time();
+                drop(settings);
+
+                if let Some(ref proxy_ctx) = *self.proxy_ctx.lock().unwrap() {
+                    proxy_ctx.lock_shared().set_max_size_time(max_size_time);
+                }
             }
             _ => unimplemented!(),
         }
     }
 
     fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "current-level-buffers" => return self.current_level().buffers.to_value(),
+            "current-level-bytes" => return self.current_level().bytes.to_value(),
+            "current-level-time" => return self.current_level().time.nseconds().to_value(),
+            "full" => return self.fill_state().0.to_value(),
+            "empty" => return self.fill_state().1.to_value(),
+            "stats" => {
+                return self
+                    .stats
+                    .to_structure("application/x-ts-proxysink-stats")
+                    .to_value()
+            }
+            _ => (),
+        }
+
         let settings = self.settings.lock().unwrap();
         match pspec.name() {
             "proxy-context" => settings.proxy_context.to_value(),
+            "unconnected-mode" => settings.unconnected_mode.to_value(),
+            "unconnected-timeout" => (settings.unconnected_timeout.as_millis() as u32).to_value(),
+            "max-size-time" => settings.max_size_time.nseconds().to_value(),
             _ => unimplemented!(),
         }
     }
@@ -657,6 +1265,23 @@ impl ElementImpl for ProxySink {
 
         Ok(success)
     }
+
+    fn query(&self, query: &mut gst::QueryRef) -> bool {
+        gst::log!(SINK_CAT, imp = self, "Handling {:?}", query);
+
+        // The pipeline's latency query also refreshes the latency of the proxysrcs
+        if let gst::QueryViewMut::Latency(q) = query.view_mut() {
+            return match self.query_upstream_latency() {
+                Some((live, min, max)) => {
+                    q.set(live, min, max);
+                    true
+                }
+                None => false,
+            };
+        }
+
+        self.parent_query(query)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -668,17 +1293,6 @@ impl PadSrcHandler for ProxySrcPadHandler {
     fn src_event(self, pad: &gst::Pad, imp: &ProxySrc, event: gst::Event) -> bool {
         gst::log!(SRC_CAT, obj = pad, "Handling {:?}", event);
 
-        let sink_pad = {
-            let proxy_ctx = imp.proxy_ctx.lock().unwrap();
-
-            PROXY_SINK_PADS
-                .lock()
-                .unwrap()
-                .get(&proxy_ctx.as_ref().unwrap().name)
-                .and_then(|sink_pad| sink_pad.upgrade())
-                .map(|sink_pad| sink_pad.gst_pad().clone())
-        };
-
         use gst::EventView;
         match event.view() {
             EventView::FlushStart(..) => {
@@ -708,22 +1322,40 @@ impl PadSrcHandler for ProxySrcPadHandler {
             _ => (),
         }
 
-        if let Some(sink_pad) = sink_pad {
+        // Flushing only applies to this proxysrc, the other consumers are not affected
+        if matches!(
+            event.view(),
+            EventView::FlushStart(..) | EventView::FlushStop(..)
+        ) {
+            gst::log!(SRC_CAT, obj = pad, "Handled {:?}", event);
+            return true;
+        }
+
+        let proxysink = {
+            let proxy_ctx = imp.proxy_ctx.lock().unwrap();
+            let shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
+            shared_ctx.sink.as_ref().and_then(|sink| sink.upgrade())
+        };
+
+        // Pushing on the proxysink's sink pad sends the event to its upstream peer
+        if let Some(proxysink) = proxysink {
             gst::log!(SRC_CAT, obj = pad, "Forwarding {:?}", event);
-            sink_pad.push_event(event)
+            proxysink.imp().sink_pad.gst_pad().push_event(event)
         } else {
             gst::error!(SRC_CAT, obj = pad, "No sink pad to forward {:?} to", event);
             false
         }
     }
 
-    fn src_query(self, pad: &gst::Pad, _proxysrc: &ProxySrc, query: &mut gst::QueryRef) -> bool {
+    fn src_query(self, pad: &gst::Pad, proxysrc: &ProxySrc, query: &mut gst::QueryRef) -> bool {
         gst::log!(SRC_CAT, obj = pad, "Handling {:?}", query);
 
         use gst::QueryViewMut;
         let ret = match query.view_mut() {
             QueryViewMut::Latency(q) => {
-                q.set(true, gst::ClockTime::ZERO, gst::ClockTime::NONE);
+                // The proxysrc is always live, whatever the upstream of the proxysink
+                let (min, max) = proxysrc.latency();
+                q.set(true, min, max);
                 true
             }
             QueryViewMut::Scheduling(q) => {
@@ -773,13 +1405,9 @@ impl ProxySrcTask {
     async fn push_item(&self, item: DataQueueItem) -> Result<(), gst::FlowError> {
         let proxysrc = self.element.imp();
 
-        {
-            let proxy_ctx = proxysrc.proxy_ctx.lock().unwrap();
-            let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-            if let Some(pending_queue) = shared_ctx.pending_queue.as_mut() {
-                pending_queue.notify_more_queue_space();
-            }
-        }
+        proxysrc.with_src_queue(ProxySrcQueue::notify_more_queue_space);
+        proxysrc.update_fill_state();
+        proxysrc.stats.account_transferred(&item);
 
         match item {
             DataQueueItem::Buffer(buffer) => {
@@ -791,10 +1419,23 @@ impl ProxySrcTask {
                 proxysrc.src_pad.push_list(list).await.map(drop)
             }
             DataQueueItem::Event(event) => {
+                if event.type_() == gst::EventType::Eos
+                    && !proxysrc.settings.lock().unwrap().forward_eos
+                {
+                    // Keep waiting for a new stream from the producer
+                    gst::debug!(SRC_CAT, obj = self.element, "Swallowing {:?}", event);
+                    proxysrc.post_eos_message();
+                    return Ok(());
+                }
+
                 gst::log!(SRC_CAT, obj = self.element, "Forwarding {:?}", event);
                 proxysrc.src_pad.push_event(event).await;
                 Ok(())
             }
+            DataQueueItem::Barrier(sender) => {
+                let _ = sender.send(());
+                Ok(())
+            }
         }
     }
 }
@@ -806,15 +1447,10 @@ impl TaskImpl for ProxySrcTask {
         async move {
             gst::log!(SRC_CAT, obj = self.element, "Starting task");
 
-            let proxysrc = self.element.imp();
-            let proxy_ctx = proxysrc.proxy_ctx.lock().unwrap();
-            let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-
-            shared_ctx.last_res = Ok(gst::FlowSuccess::Ok);
-
-            if let Some(pending_queue) = shared_ctx.pending_queue.as_mut() {
-                pending_queue.notify_more_queue_space();
-            }
+            self.element.imp().with_src_queue(|src_queue| {
+                src_queue.last_res = Ok(gst::FlowSuccess::Ok);
+                src_queue.notify_more_queue_space();
+            });
 
             self.dataqueue.start();
 
@@ -841,21 +1477,19 @@ impl TaskImpl for ProxySrcTask {
             match res {
                 Ok(()) => {
                     gst::log!(SRC_CAT, obj = self.element, "Successfully pushed item");
-                    let proxy_ctx = proxysrc.proxy_ctx.lock().unwrap();
-                    let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-                    shared_ctx.last_res = Ok(gst::FlowSuccess::Ok);
+                    proxysrc
+                        .with_src_queue(|src_queue| src_queue.last_res = Ok(gst::FlowSuccess::Ok));
                 }
                 Err(gst::FlowError::Flushing) => {
                     gst::debug!(SRC_CAT, obj = self.element, "Flushing");
-                    let proxy_ctx = proxysrc.proxy_ctx.lock().unwrap();
-                    let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-                    shared_ctx.last_res = Err(gst::FlowError::Flushing);
+                    proxysrc.with_src_queue(|src_queue| {
+                        src_queue.last_res = Err(gst::FlowError::Flushing)
+                    });
                 }
                 Err(gst::FlowError::Eos) => {
                     gst::debug!(SRC_CAT, obj = self.element, "EOS");
-                    let proxy_ctx = proxysrc.proxy_ctx.lock().unwrap();
-                    let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-                    shared_ctx.last_res = Err(gst::FlowError::Eos);
+                    proxysrc
+                        .with_src_queue(|src_queue| src_queue.last_res = Err(gst::FlowError::Eos));
                 }
                 Err(err) => {
                     gst::error!(SRC_CAT, obj = self.element, "Got error {}", err);
@@ -865,9 +1499,7 @@ impl TaskImpl for ProxySrcTask {
                         ("Internal data stream error"),
                         ["streaming stopped, reason {}", err]
                     );
-                    let proxy_ctx = proxysrc.proxy_ctx.lock().unwrap();
-                    let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-                    shared_ctx.last_res = Err(err);
+                    proxysrc.with_src_queue(|src_queue| src_queue.last_res = Err(err));
                 }
             }
 
@@ -880,18 +1512,17 @@ impl TaskImpl for ProxySrcTask {
         async move {
             gst::log!(SRC_CAT, obj = self.element, "Stopping task");
 
-            let proxysrc = self.element.imp();
-            let proxy_ctx = proxysrc.proxy_ctx.lock().unwrap();
-            let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-
             self.dataqueue.clear();
             self.dataqueue.stop();
+            self.element.imp().update_fill_state();
 
-            shared_ctx.last_res = Err(gst::FlowError::Flushing);
+            self.element.imp().with_src_queue(|src_queue| {
+                src_queue.last_res = Err(gst::FlowError::Flushing);
 
-            if let Some(mut pending_queue) = shared_ctx.pending_queue.take() {
-                pending_queue.notify_more_queue_space();
-            }
+                if let Some(mut pending_queue) = src_queue.pending_queue.take() {
+                    pending_queue.notify_more_queue_space();
+                }
+            });
 
             gst::log!(SRC_CAT, obj = self.element, "Task stopped");
             Ok(())
@@ -903,13 +1534,11 @@ impl TaskImpl for ProxySrcTask {
         async move {
             gst::log!(SRC_CAT, obj = self.element, "Starting task flush");
 
-            let proxysrc = self.element.imp();
-            let proxy_ctx = proxysrc.proxy_ctx.lock().unwrap();
-            let mut shared_ctx = proxy_ctx.as_ref().unwrap().lock_shared();
-
             self.dataqueue.clear();
 
-            shared_ctx.last_res = Err(gst::FlowError::Flushing);
+            let proxysrc = self.element.imp();
+            proxysrc.with_src_queue(|src_queue| src_queue.last_res = Err(gst::FlowError::Flushing));
+            proxysrc.update_fill_state();
 
             gst::log!(SRC_CAT, obj = self.element, "Task flush started");
             Ok(())
@@ -925,6 +1554,7 @@ pub struct ProxySrc {
     proxy_ctx: Mutex<Option<ProxyContext>>,
     dataqueue: Mutex<Option<DataQueue>>,
     settings: Mutex<SettingsSrc>,
+    stats: Stats,
 }
 
 static SRC_CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
@@ -936,18 +1566,47 @@ static SRC_CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
 });
 
 impl ProxySrc {
+    fn with_src_queue<R>(&self, func: impl FnOnce(&mut ProxySrcQueue) -> R) -> R {
+        self.proxy_ctx
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .with_src_queue(func)
+    }
+
+    fn update_fill_state(&self) {
+        let fill_state_changes = match *self.proxy_ctx.lock().unwrap() {
+            Some(ref proxy_ctx) => proxy_ctx.lock_shared().update_fill_state(),
+            None => return,
+        };
+
+        notify_fill_state(fill_state_changes);
+    }
+
+    fn current_level(&self) -> DataQueueLevel {
+        self.dataqueue
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(DataQueue::level)
+            .unwrap_or_default()
+    }
+
+    fn fill_state(&self) -> (bool, bool) {
+        match *self.proxy_ctx.lock().unwrap() {
+            Some(ref proxy_ctx) => {
+                proxy_ctx.with_src_queue(|src_queue| (src_queue.full, src_queue.empty))
+            }
+            None => (false, true),
+        }
+    }
+
     fn prepare(&self) -> Result<(), gst::ErrorMessage> {
         gst::debug!(SRC_CAT, imp = self, "Preparing");
 
         let settings = self.settings.lock().unwrap().clone();
 
-        let proxy_ctx = ProxyContext::get(&settings.proxy_context, false).ok_or_else(|| {
-            gst::error_msg!(
-                gst::ResourceError::OpenRead,
-                ["Failed to create get shared_state"]
-            )
-        })?;
-
         let ts_ctx = Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
             gst::error_msg!(
                 gst::ResourceError::OpenRead,
@@ -968,25 +1627,14 @@ impl ProxySrc {
             } else {
                 Some(settings.max_size_bytes)
             },
-            if settings.max_size_time.is_zero() {
-                None
-            } else {
-                Some(settings.max_size_time)
-            },
+            settings.max_size_time(),
         );
 
-        {
-            let mut shared_ctx = proxy_ctx.lock_shared();
-            shared_ctx.dataqueue = Some(dataqueue.clone());
-
-            let mut proxy_src_pads = PROXY_SRC_PADS.lock().unwrap();
-            assert!(!proxy_src_pads.contains_key(&settings.proxy_context));
-            proxy_src_pads.insert(settings.proxy_context, self.src_pad.downgrade());
-        }
-
-        *self.proxy_ctx.lock().unwrap() = Some(proxy_ctx);
+        self.attach_proxy_context(&settings.proxy_context, &dataqueue);
         *self.dataqueue.lock().unwrap() = Some(dataqueue.clone());
 
+        self.task.set_name(self.obj().name().as_str());
+
         self.task
             .prepare(ProxySrcTask::new(self.obj().clone(), dataqueue), ts_ctx)
             .block_on()?;
@@ -999,20 +1647,109 @@ impl ProxySrc {
     fn unprepare(&self) {
         gst::debug!(SRC_CAT, imp = self, "Unpreparing");
 
-        {
-            let settings = self.settings.lock().unwrap();
-            let mut proxy_src_pads = PROXY_SRC_PADS.lock().unwrap();
-            proxy_src_pads.remove(&settings.proxy_context);
-        }
-
         self.task.unprepare().block_on().unwrap();
 
         *self.dataqueue.lock().unwrap() = None;
-        *self.proxy_ctx.lock().unwrap() = None;
+        self.detach_proxy_context();
+        self.stats.reset();
 
         gst::debug!(SRC_CAT, imp = self, "Unprepared");
     }
 
+    /* The latency upstream of the proxysink plus our queue's */
+    fn latency(&self) -> (gst::ClockTime, Option<gst::ClockTime>) {
+        let (upstream_latency, proxysink) = match *self.proxy_ctx.lock().unwrap() {
+            Some(ref proxy_ctx) => {
+                let shared_ctx = proxy_ctx.lock_shared();
+                (
+                    shared_ctx.upstream_latency,
+                    shared_ctx.sink.as_ref().and_then(|sink| sink.upgrade()),
+                )
+            }
+            None => (None, None),
+        };
+
+        let Some((_live, min, max)) = upstream_latency
+            .or_else(|| proxysink.and_then(|proxysink| proxysink.imp().query_upstream_latency()))
+        else {
+            return (gst::ClockTime::ZERO, gst::ClockTime::NONE);
+        };
+
+        let max = self
+            .max_size_time()
+            .and_then(|max_size_time| max.map(|max| max + max_size_time));
+
+        (min, max)
+    }
+
+    fn post_eos_message(&self) {
+        let proxy_context = self.settings.lock().unwrap().proxy_context.clone();
+
+        let obj = self.obj();
+        let _ = obj.post_message(
+            gst::message::Element::builder(
+                gst::Structure::builder("GstTsProxySrcEos")
+                    .field("proxy-context", proxy_context)
+                    .build(),
+            )
+            .src(&*obj)
+            .build(),
+        );
+    }
+
+    /* The max-size-time which applies to our queue: the proxysink's if it defines one */
+    fn max_size_time(&self) -> Option<gst::ClockTime> {
+        let sink_max_size_time = self
+            .proxy_ctx
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|proxy_ctx| proxy_ctx.lock_shared().max_size_time);
+
+        sink_max_size_time.or_else(|| self.settings.lock().unwrap().max_size_time())
+    }
+
+    fn attach_proxy_context(&self, proxy_context: &str, dataqueue: &DataQueue) {
+        let proxy_ctx = ProxyContext::get_src(
+            proxy_context,
+            ProxySrcQueue::new(
+                self.src_pad.downgrade(),
+                dataqueue.clone(),
+                self.settings.lock().unwrap().max_size_time(),
+            ),
+        );
+        ProxyContext::notify_sink(&proxy_ctx.shared, &proxy_ctx.name, &self.obj(), true);
+
+        *self.proxy_ctx.lock().unwrap() = Some(proxy_ctx);
+    }
+
+    fn detach_proxy_context(&self) {
+        let proxy_ctx = self.proxy_ctx.lock().unwrap().take();
+        if let Some(proxy_ctx) = proxy_ctx {
+            let shared = proxy_ctx.shared.clone();
+            let name = proxy_ctx.name.clone();
+            // Dropping the context removes our queue before notifying the sink
+            drop(proxy_ctx);
+            ProxyContext::notify_sink(&shared, &name, &self.obj(), false);
+        }
+    }
+
+    /* Moves the prepared proxysrc to the proxy context from the settings.
+     * The sticky events of the new proxy context are replayed on the next item */
+    fn rebind_proxy_context(&self) {
+        let Some(dataqueue) = self.dataqueue.lock().unwrap().clone() else {
+            return;
+        };
+
+        gst::debug!(SRC_CAT, imp = self, "Rebinding proxy context");
+
+        self.detach_proxy_context();
+        dataqueue.clear();
+
+        let proxy_context = self.settings.lock().unwrap().proxy_context.clone();
+        self.attach_proxy_context(&proxy_context, &dataqueue);
+    }
+
     fn stop(&self) -> Result<(), gst::ErrorMessage> {
         gst::debug!(SRC_CAT, imp = self, "Stopping");
         self.task.stop().await_maybe_on_context()?;
@@ -1051,6 +1788,7 @@ impl ObjectSubclass for ProxySrc {
             proxy_ctx: Mutex::new(None),
             dataqueue: Mutex::new(None),
             settings: Mutex::new(SettingsSrc::default()),
+            stats: Stats::default(),
         }
     }
 }
@@ -1087,10 +1825,53 @@ impl ObjectImpl for ProxySrc {
                     .build(),
                 glib::ParamSpecUInt64::builder("max-size-time")
                     .nick("Max Size Time")
-                    .blurb("Maximum number of nanoseconds to queue (0=unlimited)")
+                    .blurb("Maximum number of nanoseconds to queue (0=unlimited), unless the proxysink defines one")
                     .maximum(u64::MAX - 1)
                     .default_value(DEFAULT_MAX_SIZE_TIME.nseconds())
                     .build(),
+                glib::ParamSpecBoolean::builder("forward-eos")
+                    .nick("Forward EOS")
+                    .blurb("Forward EOS from the producer, or post an element message and wait for a new stream")
+                    .default_value(DEFAULT_FORWARD_EOS)
+                    .mutable_playing()
+                    .build(),
+                /**
+                 * ts-proxysrc:stats:
+                 *
+                 * Transfer statistics since the element left the NULL state:
+                 * number of buffers, bytes and events pushed downstream, number of
+                 * dropped items, and the monotonic time of the last transfer in ns if any.
+                 */
+                glib::ParamSpecBoxed::builder::<gst::Structure>("stats")
+                    .nick("Statistics")
+                    .blurb("Statistics about the transfers through the proxy")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecUInt::builder("current-level-buffers")
+                    .nick("Current Level Buffers")
+                    .blurb("Current number of buffers in the proxy queue")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecUInt::builder("current-level-bytes")
+                    .nick("Current Level Bytes")
+                    .blurb("Current amount of data in the proxy queue (bytes)")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecUInt64::builder("current-level-time")
+                    .nick("Current Level Time")
+                    .blurb("Current amount of data in the proxy queue (in ns)")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecBoolean::builder("full")
+                    .nick("Full")
+                    .blurb("Whether the proxy queue is full")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecBoolean::builder("empty")
+                    .nick("Empty")
+                    .blurb("Whether the proxy queue is empty")
+                    .read_only()
+                    .build(),
             ]
         });
 
@@ -1109,6 +1890,9 @@ impl ObjectImpl for ProxySrc {
             "max-size-time" => {
                 settings.max_size_time = value.get::<u64>().unwrap().nseconds();
             }
+            "forward-eos" => {
+                settings.forward_eos = value.get().expect("type checked upstream");
+            }
             "context" => {
                 settings.context = value
                     .get::<Option<String>>()
@@ -1121,21 +1905,66 @@ impl ObjectImpl for ProxySrc {
                 );
             }
             "proxy-context" => {
-                settings.proxy_context = value
+                let proxy_context = value
                     .get::<Option<String>>()
                     .expect("type checked upstream")
                     .unwrap_or_else(|| DEFAULT_PROXY_CONTEXT.into());
+                if proxy_context == settings.proxy_context {
+                    return;
+                }
+
+                let state = self.obj().current_state();
+                if state > gst::State::Ready {
+                    gst::warning!(
+                        SRC_CAT,
+                        imp = self,
+                        "Can't change proxy-context from {} to {} in {:?}",
+                        settings.proxy_context,
+                        proxy_context,
+                        state,
+                    );
+                    return;
+                }
+
+                settings.proxy_context = proxy_context;
+                drop(settings);
+
+                if state == gst::State::Ready {
+                    self.rebind_proxy_context();
+                }
             }
             _ => unimplemented!(),
         }
     }
 
     fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "current-level-buffers" => return self.current_level().buffers.to_value(),
+            "current-level-bytes" => return self.current_level().bytes.to_value(),
+            "current-level-time" => return self.current_level().time.nseconds().to_value(),
+            "full" => return self.fill_state().0.to_value(),
+            "empty" => return self.fill_state().1.to_value(),
+            "stats" => {
+                return self
+                    .stats
+                    .to_structure("application/x-ts-proxysrc-stats")
+                    .to_value()
+            }
+            // Reflects the proxysink's max-size-time when it applies
+            "max-size-time" => {
+                return self
+                    .max_size_time()
+                    .map_or(0, gst::ClockTime::nseconds)
+                    .to_value()
+            }
+            _ => (),
+        }
+
         let settings = self.settings.lock().unwrap();
         match pspec.name() {
             "max-size-buffers" => settings.max_size_buffers.to_value(),
             "max-size-bytes" => settings.max_size_bytes.to_value(),
-            "max-size-time" => settings.max_size_time.nseconds().to_value(),
+            "forward-eos" => settings.forward_eos.to_value(),
             "context" => settings.context.to_value(),
             "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
             "proxy-context" => settings.proxy_context.to_value(),
@@ -22,6 +22,25 @@ use gst::prelude::*;
 
 mod imp;
 
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstTsProxySinkUnconnectedMode")]
+pub enum UnconnectedMode {
+    #[default]
+    #[enum_value(name = "Block until a proxysrc is attached", nick = "block")]
+    Block,
+    #[enum_value(
+        name = "Drop buffers, keeping sticky events for the next proxysrc",
+        nick = "drop"
+    )]
+    Drop,
+    #[enum_value(
+        name = "Error out if no proxysrc is attached after unconnected-timeout",
+        nick = "error"
+    )]
+    Error,
+}
+
 glib::wrapper! {
     pub struct ProxySink(ObjectSubclass<imp::ProxySink>) @extends gst::Element, gst::Object;
 }
@@ -31,6 +50,8 @@ glib::wrapper! {
 }
 
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    UnconnectedMode::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+
     gst::Element::register(
         Some(plugin),
         "ts-proxysink",
@@ -28,19 +28,28 @@ use gst::subclass::prelude::*;
 use std::sync::LazyLock;
 
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::runtime::prelude::*;
 use crate::runtime::{Context, PadSink, PadSrc, Task};
 
-use crate::dataqueue::{DataQueue, DataQueueItem};
+use crate::dataqueue::{DataQueue, DataQueueItem, DataQueueLevel, DataQueueState};
+
+use super::Leaky;
 
 const DEFAULT_MAX_SIZE_BUFFERS: u32 = 200;
 const DEFAULT_MAX_SIZE_BYTES: u32 = 1024 * 1024;
 const DEFAULT_MAX_SIZE_TIME: gst::ClockTime = gst::ClockTime::SECOND;
 const DEFAULT_CONTEXT: &str = "";
 const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+const DEFAULT_LEAKY: Leaky = Leaky::No;
+const DEFAULT_LATENCY: gst::ClockTime = gst::ClockTime::ZERO;
+const DEFAULT_MIN_THRESHOLD_BUFFERS: u32 = 0;
+const DEFAULT_MIN_THRESHOLD_TIME: gst::ClockTime = gst::ClockTime::ZERO;
+
+const DROPPED_LOG_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Clone)]
 struct Settings {
@@ -49,6 +58,40 @@ struct Settings {
     max_size_time: gst::ClockTime,
     context: String,
     context_wait: Duration,
+    leaky: Leaky,
+    latency: gst::ClockTime,
+    min_threshold_buffers: u32,
+    min_threshold_time: gst::ClockTime,
+}
+
+impl Settings {
+    /// Latency introduced by the queue when it is full.
+    fn queue_latency(&self) -> gst::ClockTime {
+        if self.max_size_time.is_zero() {
+            self.latency
+        } else {
+            self.max_size_time
+        }
+    }
+
+    fn max_size_buffers(&self) -> Option<u32> {
+        Some(self.max_size_buffers).filter(|&buffers| buffers > 0)
+    }
+
+    fn max_size_bytes(&self) -> Option<u32> {
+        Some(self.max_size_bytes).filter(|&bytes| bytes > 0)
+    }
+
+    fn max_size_time(&self) -> Option<gst::ClockTime> {
+        Some(self.max_size_time).filter(|time| !time.is_zero())
+    }
+
+    fn apply_min_threshold(&self, dataqueue: &DataQueue) {
+        dataqueue.set_min_threshold(
+            Some(self.min_threshold_buffers).filter(|&buffers| buffers > 0),
+            Some(self.min_threshold_time).filter(|time| !time.is_zero()),
+        );
+    }
 }
 
 impl Default for Settings {
@@ -59,10 +102,62 @@ impl Default for Settings {
             max_size_time: DEFAULT_MAX_SIZE_TIME,
             context: DEFAULT_CONTEXT.into(),
             context_wait: DEFAULT_CONTEXT_WAIT,
+            leaky: DEFAULT_LEAKY,
+            latency: DEFAULT_LATENCY,
+            min_threshold_buffers: DEFAULT_MIN_THRESHOLD_BUFFERS,
+            min_threshold_time: DEFAULT_MIN_THRESHOLD_TIME,
         }
     }
 }
 
+#[derive(Debug, Default)]
+struct Stats {
+    dropped: u64,
+    dropped_since_log: u64,
+    last_dropped_log: Option<Instant>,
+    full_since: Option<Instant>,
+    full_time: Duration,
+    full_count: u64,
+    empty_time: Duration,
+    empty_count: u64,
+    max_level: DataQueueLevel,
+}
+
+impl Stats {
+    fn start_full(&mut self) {
+        self.full_since = Some(Instant::now());
+        self.full_count += 1;
+    }
+
+    fn end_full(&mut self) {
+        if let Some(full_since) = self.full_since.take() {
+            self.full_time += full_since.elapsed();
+        }
+    }
+
+    fn update_max_level(&mut self, level: DataQueueLevel) {
+        self.max_level.buffers = self.max_level.buffers.max(level.buffers);
+        self.max_level.bytes = self.max_level.bytes.max(level.bytes);
+        self.max_level.time = self.max_level.time.max(level.time);
+    }
+
+    fn to_structure(&self) -> gst::Structure {
+        // Include the ongoing full period, if any
+        let full_time = self.full_time + self.full_since.map_or(Duration::ZERO, |t| t.elapsed());
+
+        gst::Structure::builder("application/x-ts-queue-stats")
+            .field("dropped", self.dropped)
+            .field("full-time", full_time.as_nanos() as u64)
+            .field("full-count", self.full_count)
+            .field("empty-time", self.empty_time.as_nanos() as u64)
+            .field("empty-count", self.empty_count)
+            .field("max-level-buffers", self.max_level.buffers)
+            .field("max-level-bytes", self.max_level.bytes)
+            .field("max-level-time", self.max_level.time.nseconds())
+            .build()
+    }
+}
+
 #[derive(Debug)]
 struct PendingQueue {
     more_queue_space_sender: Option<oneshot::Sender<()>>,
@@ -164,9 +259,7 @@ impl PadSinkHandler for QueuePadSinkHandler {
         gst::log!(CAT, obj = pad, "Handling {:?}", query);
 
         if query.is_serialized() {
-            // FIXME: How can we do this?
-            gst::log!(CAT, obj = pad, "Dropping serialized {:?}", query);
-            false
+            imp.forward_serialized_query(query)
         } else {
             gst::log!(CAT, obj = pad, "Forwarding {:?}", query);
             imp.src_pad.gst_pad().peer_query(query)
@@ -212,6 +305,21 @@ impl PadSrcHandler for QueuePadSrcHandler {
     fn src_query(self, pad: &gst::Pad, imp: &Queue, query: &mut gst::QueryRef) -> bool {
         gst::log!(CAT, obj = pad, "Handling {:?}", query);
 
+        if let gst::QueryViewMut::Latency(q) = query.view_mut() {
+            let mut peer_query = gst::query::Latency::new();
+            if !imp.sink_pad.gst_pad().peer_query(&mut peer_query) {
+                return false;
+            }
+
+            let latency = imp.settings.lock().unwrap().queue_latency();
+
+            let (live, min, max) = peer_query.result();
+            q.set(live, min + latency, max.opt_add(latency));
+            gst::log!(CAT, obj = pad, "Returning {:?}", q.query_mut());
+
+            return true;
+        }
+
         if let gst::QueryViewMut::Scheduling(q) = query.view_mut() {
             let mut new_query = gst::query::Scheduling::new();
             let res = imp.sink_pad.gst_pad().peer_query(&mut new_query);
@@ -244,34 +352,94 @@ impl PadSrcHandler for QueuePadSrcHandler {
 struct QueueTask {
     element: super::Queue,
     dataqueue: DataQueue,
+    underrun: bool,
+    /// Last forwarded stream-start, caps & segment events
+    sticky_events: [Option<gst::Event>; 3],
+    /// Sticky events to re-send after a flush, in serialization order
+    pending_sticky_events: Vec<gst::Event>,
 }
 
 impl QueueTask {
     fn new(element: super::Queue, dataqueue: DataQueue) -> Self {
-        QueueTask { element, dataqueue }
+        QueueTask {
+            element,
+            dataqueue,
+            underrun: false,
+            sticky_events: [None, None, None],
+            pending_sticky_events: Vec::new(),
+        }
     }
 
-    async fn push_item(&self, item: DataQueueItem) -> Result<(), gst::FlowError> {
+    fn sticky_event_idx(event_type: gst::EventType) -> Option<usize> {
+        match event_type {
+            gst::EventType::StreamStart => Some(0),
+            gst::EventType::Caps => Some(1),
+            gst::EventType::Segment => Some(2),
+            _ => None,
+        }
+    }
+
+    /* Pushes the sticky events pending since the last flush which must precede
+     * an item of type `until` (`None` for buffers). Pending events of the same
+     * type as `until` are superseded and discarded */
+    async fn push_pending_sticky_events(&mut self, until: Option<gst::EventType>) {
+        if self.pending_sticky_events.is_empty() {
+            return;
+        }
+
         let queue = self.element.imp();
+        let mut kept = Vec::new();
+        for event in std::mem::take(&mut self.pending_sticky_events) {
+            let event_type = event.type_();
+            if until.map_or(true, |until| event_type < until) {
+                gst::log!(CAT, obj = self.element, "Re-sending {:?}", event);
+                queue.src_pad.push_event(event).await;
+            } else if Some(event_type) != until {
+                kept.push(event);
+            }
+        }
+        self.pending_sticky_events = kept;
+    }
+
+    async fn push_item(&mut self, item: DataQueueItem) -> Result<(), gst::FlowError> {
+        let element = self.element.clone();
+        let queue = element.imp();
 
         if let Some(pending_queue) = queue.pending_queue.lock().unwrap().as_mut() {
             pending_queue.notify_more_queue_space();
         }
+        queue.end_overrun();
 
         match item {
             DataQueueItem::Buffer(buffer) => {
+                self.push_pending_sticky_events(None).await;
                 gst::log!(CAT, obj = self.element, "Forwarding {:?}", buffer);
                 queue.src_pad.push(buffer).await.map(drop)
             }
             DataQueueItem::BufferList(list) => {
+                self.push_pending_sticky_events(None).await;
                 gst::log!(CAT, obj = self.element, "Forwarding {:?}", list);
                 queue.src_pad.push_list(list).await.map(drop)
             }
             DataQueueItem::Event(event) => {
+                let event_type = event.type_();
+                if let Some(idx) = Self::sticky_event_idx(event_type) {
+                    self.push_pending_sticky_events(Some(event_type)).await;
+                    self.sticky_events[idx] = Some(event.clone());
+                } else if event_type == gst::EventType::FlushStop {
+                    self.pending_sticky_events =
+                        self.sticky_events.iter().flatten().cloned().collect();
+                }
+
                 gst::log!(CAT, obj = self.element, "Forwarding {:?}", event);
                 queue.src_pad.push_event(event).await;
                 Ok(())
             }
+            DataQueueItem::Barrier(sender) => {
+                gst::log!(CAT, obj = self.element, "Reached serialized query");
+                let _ = sender.send(());
+                Ok(())
+            }
         }
     }
 }
@@ -298,10 +466,40 @@ impl TaskImpl for QueueTask {
 
     fn try_next(&mut self) -> BoxFuture<'_, Result<DataQueueItem, gst::FlowError>> {
         async move {
-            self.dataqueue
+            // Sampled before dequeuing so the level includes the next item
+            self.element
+                .imp()
+                .stats
+                .lock()
+                .unwrap()
+                .update_max_level(self.dataqueue.level());
+
+            let empty_since = if self.dataqueue.is_empty() {
+                if !self.underrun {
+                    gst::debug!(CAT, obj = self.element, "Queue is empty");
+                    self.underrun = true;
+                    self.element.emit_by_name::<()>("underrun", &[]);
+                }
+
+                Some(Instant::now())
+            } else {
+                self.underrun = false;
+                None
+            };
+
+            let item = self
+                .dataqueue
                 .next()
                 .await
-                .ok_or_else(|| panic!("DataQueue stopped while Task is Started"))
+                .ok_or_else(|| panic!("DataQueue stopped while Task is Started"));
+
+            if let Some(empty_since) = empty_since {
+                let mut stats = self.element.imp().stats.lock().unwrap();
+                stats.empty_time += empty_since.elapsed();
+                stats.empty_count += 1;
+            }
+
+            item
         }
         .boxed()
     }
@@ -354,6 +552,10 @@ impl TaskImpl for QueueTask {
             if let Some(mut pending_queue) = queue.pending_queue.lock().unwrap().take() {
                 pending_queue.notify_more_queue_space();
             }
+            queue.end_overrun();
+            self.underrun = false;
+            self.sticky_events = [None, None, None];
+            self.pending_sticky_events.clear();
 
             *last_res = Err(gst::FlowError::Flushing);
 
@@ -375,6 +577,7 @@ impl TaskImpl for QueueTask {
             if let Some(mut pending_queue) = queue.pending_queue.lock().unwrap().take() {
                 pending_queue.notify_more_queue_space();
             }
+            queue.end_overrun();
 
             *last_res = Err(gst::FlowError::Flushing);
 
@@ -393,6 +596,8 @@ pub struct Queue {
     dataqueue: Mutex<Option<DataQueue>>,
     pending_queue: Mutex<Option<PendingQueue>>,
     last_res: Mutex<Result<gst::FlowSuccess, gst::FlowError>>,
+    overrun: AtomicBool,
+    stats: Mutex<Stats>,
     settings: Mutex<Settings>,
 }
 
@@ -441,6 +646,98 @@ impl Queue {
         }
     }
 
+    /* Called when the DataQueue is full: drops the oldest queued buffers
+     * until there is enough space for the current item. Errors out if
+     * there are no more buffers to drop */
+    fn queue_leaking_downstream(
+        &self,
+        dataqueue: &DataQueue,
+        pending_queue: &mut Option<PendingQueue>,
+        mut item: DataQueueItem,
+    ) -> Result<(), DataQueueItem> {
+        loop {
+            if pending_queue.as_ref().is_some_and(|pq| pq.scheduled) {
+                return Err(item);
+            }
+
+            let Some(count) = dataqueue.drop_oldest_buffer() else {
+                return Err(item);
+            };
+            self.account_dropped(count);
+
+            item = match self.queue_until_full(dataqueue, pending_queue, item) {
+                Ok(()) => return Ok(()),
+                Err(item) => item,
+            };
+        }
+    }
+
+    fn current_level(&self) -> DataQueueLevel {
+        self.dataqueue
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(DataQueue::level)
+            .unwrap_or_default()
+    }
+
+    /* Applies new size limits to the running queue: the limits are checked
+     * on each enqueue, so growing them wakes up a blocked upstream while
+     * shrinking them only applies as queued items are drained */
+    fn update_size_limits(&self, settings: &Settings) {
+        let Some(dataqueue) = self.dataqueue.lock().unwrap().clone() else {
+            return;
+        };
+
+        dataqueue.set_max_size(
+            settings.max_size_buffers(),
+            settings.max_size_bytes(),
+            settings.max_size_time(),
+        );
+
+        if let Some(pending_queue) = self.pending_queue.lock().unwrap().as_mut() {
+            pending_queue.notify_more_queue_space();
+        }
+
+        let obj = self.obj();
+        obj.notify("current-level-buffers");
+        obj.notify("current-level-bytes");
+        obj.notify("current-level-time");
+    }
+
+    fn post_latency_message(&self) {
+        let _ = self
+            .obj()
+            .post_message(gst::message::Latency::builder().src(&*self.obj()).build());
+    }
+
+    fn end_overrun(&self) {
+        if self.overrun.swap(false, Ordering::SeqCst) {
+            self.stats.lock().unwrap().end_full();
+        }
+    }
+
+    fn account_dropped(&self, count: u32) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.dropped += count as u64;
+        stats.dropped_since_log += count as u64;
+
+        let now = Instant::now();
+        if stats.last_dropped_log.map_or(true, |last| {
+            now.duration_since(last) >= DROPPED_LOG_INTERVAL
+        }) {
+            gst::info!(
+                CAT,
+                imp = self,
+                "Queue is full, dropped {} buffers ({} in total)",
+                stats.dropped_since_log,
+                stats.dropped,
+            );
+            stats.dropped_since_log = 0;
+            stats.last_dropped_log = Some(now);
+        }
+    }
+
     /* Schedules emptying of the pending queue. If there is an upstream
      * TaskContext, the new task is spawned, it is otherwise
      * returned, for the caller to block on */
@@ -486,6 +783,9 @@ impl Queue {
     }
 
     async fn enqueue_item(&self, item: DataQueueItem) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let leaky = self.settings.lock().unwrap().leaky;
+        let mut signal_overrun = false;
+
         let wait_fut = {
             let dataqueue = self.dataqueue.lock().unwrap();
             let dataqueue = dataqueue.as_ref().ok_or_else(|| {
@@ -495,7 +795,39 @@ impl Queue {
 
             let mut pending_queue = self.pending_queue.lock().unwrap();
 
-            if let Err(item) = self.queue_until_full(dataqueue, &mut pending_queue, item) {
+            let res = self
+                .queue_until_full(dataqueue, &mut pending_queue, item)
+                .or_else(|item| {
+                    if dataqueue.state() == DataQueueState::Started
+                        && !self.overrun.swap(true, Ordering::SeqCst)
+                    {
+                        gst::debug!(CAT, imp = self, "Queue is full");
+                        self.stats.lock().unwrap().start_full();
+                        signal_overrun = true;
+                    }
+
+                    match leaky {
+                        // Serialized events and queries are never dropped
+                        _ if matches!(
+                            item,
+                            DataQueueItem::Event(_) | DataQueueItem::Barrier(_)
+                        ) =>
+                        {
+                            Err(item)
+                        }
+                        Leaky::No => Err(item),
+                        Leaky::Upstream => {
+                            gst::log!(CAT, imp = self, "Queue is full, dropping {:?}", item);
+                            self.account_dropped(item.size().0);
+                            Ok(())
+                        }
+                        Leaky::Downstream => {
+                            self.queue_leaking_downstream(dataqueue, &mut pending_queue, item)
+                        }
+                    }
+                });
+
+            if let Err(item) = res {
                 if pending_queue
                     .as_ref()
                     .map(|pq| !pq.scheduled)
@@ -541,6 +873,10 @@ impl Queue {
             }
         };
 
+        if signal_overrun {
+            self.obj().emit_by_name::<()>("overrun", &[]);
+        }
+
         if let Some(wait_fut) = wait_fut {
             gst::log!(CAT, imp = self, "Blocking until queue has space again");
             wait_fut.await;
@@ -549,6 +885,43 @@ impl Queue {
         *self.last_res.lock().unwrap()
     }
 
+    /* Called from the upstream streaming thread: blocks until all the items
+     * queued before the query were pushed downstream, then forwards it */
+    fn forward_serialized_query(&self, query: &mut gst::QueryRef) -> bool {
+        if let gst::QueryView::Allocation(..) = query.view() {
+            let dataqueue = self.dataqueue.lock().unwrap();
+            let is_empty = dataqueue.as_ref().is_some_and(DataQueue::is_empty)
+                && self.pending_queue.lock().unwrap().is_none();
+            drop(dataqueue);
+
+            if is_empty {
+                gst::log!(CAT, imp = self, "Queue is empty, forwarding {:?}", query);
+                return self.src_pad.gst_pad().peer_query(query);
+            }
+        }
+
+        gst::log!(
+            CAT,
+            imp = self,
+            "Waiting for queue to drain before {:?}",
+            query
+        );
+
+        let (sender, receiver) = oneshot::channel();
+        if futures::executor::block_on(self.enqueue_item(DataQueueItem::Barrier(sender))).is_err() {
+            gst::debug!(CAT, imp = self, "Not forwarding {:?}", query);
+            return false;
+        }
+
+        if futures::executor::block_on(receiver).is_err() {
+            gst::debug!(CAT, imp = self, "Flushing, not forwarding {:?}", query);
+            return false;
+        }
+
+        gst::log!(CAT, imp = self, "Forwarding {:?}", query);
+        self.src_pad.gst_pad().peer_query(query)
+    }
+
     fn prepare(&self) -> Result<(), gst::ErrorMessage> {
         gst::debug!(CAT, imp = self, "Preparing");
 
@@ -557,22 +930,11 @@ impl Queue {
         let dataqueue = DataQueue::new(
             &self.obj().clone().upcast(),
             self.src_pad.gst_pad(),
-            if settings.max_size_buffers == 0 {
-                None
-            } else {
-                Some(settings.max_size_buffers)
-            },
-            if settings.max_size_bytes == 0 {
-                None
-            } else {
-                Some(settings.max_size_bytes)
-            },
-            if settings.max_size_time.is_zero() {
-                None
-            } else {
-                Some(settings.max_size_time)
-            },
+            settings.max_size_buffers(),
+            settings.max_size_bytes(),
+            settings.max_size_time(),
         );
+        settings.apply_min_threshold(&dataqueue);
 
         *self.dataqueue.lock().unwrap() = Some(dataqueue.clone());
 
@@ -584,6 +946,8 @@ impl Queue {
                 )
             })?;
 
+        self.task.set_name(self.obj().name().as_str());
+
         self.task
             .prepare(QueueTask::new(self.obj().clone(), dataqueue), context)
             .block_on()?;
@@ -602,6 +966,7 @@ impl Queue {
         *self.pending_queue.lock().unwrap() = None;
 
         *self.last_res.lock().unwrap() = Ok(gst::FlowSuccess::Ok);
+        *self.stats.lock().unwrap() = Stats::default();
 
         gst::debug!(CAT, imp = self, "Unprepared");
     }
@@ -641,6 +1006,8 @@ impl ObjectSubclass for Queue {
             dataqueue: Mutex::new(None),
             pending_queue: Mutex::new(None),
             last_res: Mutex::new(Ok(gst::FlowSuccess::Ok)),
+            overrun: AtomicBool::new(false),
+            stats: Mutex::new(Stats::default()),
             settings: Mutex::new(Settings::default()),
         }
     }
@@ -665,17 +1032,76 @@ impl ObjectImpl for Queue {
                     .nick("Max Size Buffers")
                     .blurb("Maximum number of buffers to queue (0=unlimited)")
                     .default_value(DEFAULT_MAX_SIZE_BUFFERS)
+                    .mutable_playing()
                     .build(),
                 glib::ParamSpecUInt::builder("max-size-bytes")
                     .nick("Max Size Bytes")
                     .blurb("Maximum number of bytes to queue (0=unlimited)")
                     .default_value(DEFAULT_MAX_SIZE_BYTES)
+                    .mutable_playing()
                     .build(),
                 glib::ParamSpecUInt64::builder("max-size-time")
                     .nick("Max Size Time")
                     .blurb("Maximum number of nanoseconds to queue (0=unlimited)")
                     .maximum(u64::MAX - 1)
                     .default_value(DEFAULT_MAX_SIZE_TIME.nseconds())
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecUInt64::builder("latency")
+                    .nick("Latency")
+                    .blurb("Latency in ns to report when max-size-time is 0 (unlimited)")
+                    .maximum(u64::MAX - 1)
+                    .default_value(DEFAULT_LATENCY.nseconds())
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecUInt::builder("min-threshold-buffers")
+                    .nick("Min Threshold Buffers")
+                    .blurb("Min. number of buffers in the queue before output starts or resumes after an underrun (0=disable)")
+                    .default_value(DEFAULT_MIN_THRESHOLD_BUFFERS)
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecUInt64::builder("min-threshold-time")
+                    .nick("Min Threshold Time")
+                    .blurb("Min. amount of data in the queue in ns before output starts or resumes after an underrun (0=disable)")
+                    .maximum(u64::MAX - 1)
+                    .default_value(DEFAULT_MIN_THRESHOLD_TIME.nseconds())
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("leaky", DEFAULT_LEAKY)
+                    .nick("Leaky")
+                    .blurb("Where the queue leaks, if at all")
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecUInt::builder("current-level-buffers")
+                    .nick("Current Level Buffers")
+                    .blurb("Current number of buffers in the queue")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecUInt::builder("current-level-bytes")
+                    .nick("Current Level Bytes")
+                    .blurb("Current amount of data in the queue (bytes)")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecUInt64::builder("current-level-time")
+                    .nick("Current Level Time")
+                    .blurb("Current amount of data in the queue (in ns)")
+                    .read_only()
+                    .build(),
+                /**
+                 * ts-queue:stats:
+                 *
+                 * Cumulative statistics since the element left the NULL state:
+                 * number of dropped buffers, time spent full (blocking upstream)
+                 * or empty (starving downstream) with the matching transition
+                 * counts, and the maximum observed level.
+                 *
+                 * The statistics are always collected: sampling happens once per
+                 * dequeued item and only involves a few arithmetic operations.
+                 */
+                glib::ParamSpecBoxed::builder::<gst::Structure>("stats")
+                    .nick("Statistics")
+                    .blurb("Statistics about the queue")
+                    .read_only()
                     .build(),
             ]
         });
@@ -683,17 +1109,81 @@ impl ObjectImpl for Queue {
         PROPERTIES.as_ref()
     }
 
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: LazyLock<Vec<glib::subclass::Signal>> = LazyLock::new(|| {
+            vec![
+                /**
+                 * ts-queue::overrun:
+                 * @self: A ts-queue
+                 *
+                 * Emitted when the queue becomes full, i.e. when an item
+                 * can't be queued because one of the max-size limits is reached.
+                 * Emitted again only after an item was dequeued.
+                 */
+                glib::subclass::Signal::builder("overrun").run_first().build(),
+                /**
+                 * ts-queue::underrun:
+                 * @self: A ts-queue
+                 *
+                 * Emitted from the streaming thread when the queue becomes empty.
+                 */
+                glib::subclass::Signal::builder("underrun").run_first().build(),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
     fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
         let mut settings = self.settings.lock().unwrap();
         match pspec.name() {
             "max-size-buffers" => {
                 settings.max_size_buffers = value.get().expect("type checked upstream");
+                let new_settings = settings.clone();
+                drop(settings);
+                self.update_size_limits(&new_settings);
             }
             "max-size-bytes" => {
                 settings.max_size_bytes = value.get().expect("type checked upstream");
+                let new_settings = settings.clone();
+                drop(settings);
+                self.update_size_limits(&new_settings);
             }
             "max-size-time" => {
-                settings.max_size_time = value.get::<u64>().unwrap().nseconds();
+                let max_size_time = value.get::<u64>().unwrap().nseconds();
+                let prev_latency = settings.queue_latency();
+                settings.max_size_time = max_size_time;
+                let latency_changed = settings.queue_latency() != prev_latency;
+                let new_settings = settings.clone();
+                drop(settings);
+                self.update_size_limits(&new_settings);
+
+                if latency_changed {
+                    self.post_latency_message();
+                }
+            }
+            "min-threshold-buffers" => {
+                settings.min_threshold_buffers = value.get().expect("type checked upstream");
+                if let Some(dataqueue) = self.dataqueue.lock().unwrap().as_ref() {
+                    settings.apply_min_threshold(dataqueue);
+                }
+            }
+            "min-threshold-time" => {
+                settings.min_threshold_time = value.get::<u64>().unwrap().nseconds();
+                if let Some(dataqueue) = self.dataqueue.lock().unwrap().as_ref() {
+                    settings.apply_min_threshold(dataqueue);
+                }
+            }
+            "latency" => {
+                let latency = value.get::<u64>().unwrap().nseconds();
+                let prev_latency = settings.queue_latency();
+                settings.latency = latency;
+                let latency_changed = settings.queue_latency() != prev_latency;
+                drop(settings);
+
+                if latency_changed {
+                    self.post_latency_message();
+                }
             }
             "context" => {
                 settings.context = value
@@ -706,18 +1196,33 @@ impl ObjectImpl for Queue {
                     value.get::<u32>().expect("type checked upstream").into(),
                 );
             }
+            "leaky" => {
+                settings.leaky = value.get().expect("type checked upstream");
+            }
             _ => unimplemented!(),
         }
     }
 
     fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "current-level-buffers" => return self.current_level().buffers.to_value(),
+            "current-level-bytes" => return self.current_level().bytes.to_value(),
+            "current-level-time" => return self.current_level().time.nseconds().to_value(),
+            "stats" => return self.stats.lock().unwrap().to_structure().to_value(),
+            _ => (),
+        }
+
         let settings = self.settings.lock().unwrap();
         match pspec.name() {
             "max-size-buffers" => settings.max_size_buffers.to_value(),
             "max-size-bytes" => settings.max_size_bytes.to_value(),
             "max-size-time" => settings.max_size_time.nseconds().to_value(),
+            "latency" => settings.latency.nseconds().to_value(),
+            "min-threshold-buffers" => settings.min_threshold_buffers.to_value(),
+            "min-threshold-time" => settings.min_threshold_time.nseconds().to_value(),
             "context" => settings.context.to_value(),
             "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
+            "leaky" => settings.leaky.to_value(),
             _ => unimplemented!(),
         }
     }
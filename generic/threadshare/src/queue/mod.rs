@@ -22,11 +22,26 @@ use gst::prelude::*;
 
 mod imp;
 
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstTsQueueLeaky")]
+pub enum Leaky {
+    #[default]
+    #[enum_value(name = "Not Leaky", nick = "no")]
+    No,
+    #[enum_value(name = "Leaky on upstream (new buffers)", nick = "upstream")]
+    Upstream,
+    #[enum_value(name = "Leaky on downstream (old buffers)", nick = "downstream")]
+    Downstream,
+}
+
 glib::wrapper! {
     pub struct Queue(ObjectSubclass<imp::Queue>) @extends gst::Element, gst::Object;
 }
 
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    Leaky::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+
     gst::Element::register(
         Some(plugin),
         "ts-queue",
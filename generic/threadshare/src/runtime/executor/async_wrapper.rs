@@ -63,6 +63,11 @@ use super::{Reactor, Readable, ReadableOwned, Registration, Source, Writable, Wr
 /// [timerfd]: https://github.com/smol-rs/async-io/blob/master/examples/linux-timerfd.rs
 /// [inotify]: https://github.com/smol-rs/async-io/blob/master/examples/linux-inotify.rs
 ///
+/// Use [`Context::register_io`] to register an I/O handle with a specific `Context`,
+/// e.g. from outside of it.
+///
+/// [`Context::register_io`]: super::Context::register_io
+///
 /// ### Concurrent I/O
 ///
 /// Note that [`&Async<T>`][`Async`] implements [`AsyncRead`] and [`AsyncWrite`] if `&T`
@@ -141,7 +146,7 @@ impl<T: AsFd + Send + 'static> Async<T> {
         // this type.
         let registration = unsafe { Registration::new(fd) };
 
-        let source = Reactor::with_mut(|reactor| reactor.insert_io(registration))?;
+        let source = Reactor::with(|reactor| reactor.insert_io(registration))?;
         Ok(Async {
             source,
             io: Some(io),
@@ -214,7 +219,7 @@ impl<T: AsSocket + Send + 'static> Async<T> {
         // this type.
         let registration = unsafe { Registration::new(borrowed) };
 
-        let source = Reactor::with_mut(|reactor| reactor.insert_io(registration))?;
+        let source = Reactor::with(|reactor| reactor.insert_io(registration))?;
         Ok(Async {
             source,
             io: Some(io),
@@ -271,7 +276,7 @@ impl<T: Send + 'static> Async<T> {
     /// Unwraps the inner I/O handle.
     pub fn into_inner(mut self) -> io::Result<T> {
         let io = self.io.take().unwrap();
-        Reactor::with_mut(|reactor| reactor.remove_io(&self.source))?;
+        Reactor::with(|reactor| reactor.remove_io(&self.source))?;
         Ok(io)
     }
 
@@ -436,7 +441,7 @@ impl<T: Send + 'static> Drop for Async<T> {
             if let Some(sched) = self.sched.upgrade() {
                 let source = Arc::clone(&self.source);
                 sched.spawn_and_unpark(async move {
-                    Reactor::with_mut(|reactor| {
+                    Reactor::with(|reactor| {
                         if let Err(err) = reactor.remove_io(&source) {
                             gst::error!(
                                 RUNTIME_CAT,
@@ -742,7 +747,35 @@ impl Async<TcpStream> {
         // Begin async connect.
         let addr = addr.into();
         let domain = Domain::for_address(addr);
-        let socket = connect(addr.into(), domain, Some(Protocol::TCP))?;
+        let socket = connect(addr.into(), domain, Some(Protocol::TCP), None)?;
+        let stream = Async::new(TcpStream::from(socket))?;
+
+        // The stream becomes writable when connected.
+        stream.writable().await?;
+
+        // Check if there was an error while connecting.
+        match stream.get_ref().take_error()? {
+            None => Ok(stream),
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Creates a TCP connection to the specified address from the specified local address.
+    ///
+    /// Binding with port number 0 will request an available port from the OS.
+    pub async fn connect_from<A: Into<SocketAddr>>(
+        local_addr: SocketAddr,
+        addr: A,
+    ) -> io::Result<Async<TcpStream>> {
+        // Begin async connect.
+        let addr = addr.into();
+        let domain = Domain::for_address(addr);
+        let socket = connect(
+            addr.into(),
+            domain,
+            Some(Protocol::TCP),
+            Some(local_addr.into()),
+        )?;
         let stream = Async::new(TcpStream::from(socket))?;
 
         // The stream becomes writable when connected.
@@ -901,7 +934,7 @@ impl Async<UnixStream> {
     /// Creates a UDS stream connected to the specified path.
     pub async fn connect<P: AsRef<Path>>(path: P) -> io::Result<Async<UnixStream>> {
         // Begin async connect.
-        let socket = connect(SockAddr::unix(path)?, Domain::UNIX, None)?;
+        let socket = connect(SockAddr::unix(path)?, Domain::UNIX, None, None)?;
         let stream = Async::new(UnixStream::from(socket))?;
 
         // The stream becomes writable when connected.
@@ -1008,7 +1041,12 @@ async fn optimistic(fut: impl Future<Output = io::Result<()>>) -> io::Result<()>
     .await
 }
 
-fn connect(addr: SockAddr, domain: Domain, protocol: Option<Protocol>) -> io::Result<Socket> {
+fn connect(
+    addr: SockAddr,
+    domain: Domain,
+    protocol: Option<Protocol>,
+    local_addr: Option<SockAddr>,
+) -> io::Result<Socket> {
     let sock_type = Type::STREAM;
     #[cfg(any(
         target_os = "android",
@@ -1036,6 +1074,11 @@ fn connect(addr: SockAddr, domain: Domain, protocol: Option<Protocol>) -> io::Re
     )))]
     // If the current platform doesn't support nonblocking at creation, enable it after creation
     socket.set_nonblocking(true)?;
+    if let Some(local_addr) = local_addr {
+        // Allow rebinding to the same local port while a previous connection is in TIME_WAIT
+        socket.set_reuse_address(true)?;
+        socket.bind(&local_addr)?;
+    }
     match socket.connect(&addr) {
         Ok(_) => {}
         #[cfg(unix)]
@@ -0,0 +1,348 @@
+// Take a look at the license at the top of the repository in the LICENSE file.
+
+use std::sync::LazyLock;
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Mutex;
+
+use crate::runtime::RUNTIME_CAT;
+
+/// Environment variable used to override the CPU affinity of `Context` threads.
+///
+/// Format: `context-name:cpus[;context-name:cpus...]` where `cpus` is a comma
+/// separated list of CPU indices or ranges, e.g. `net-ctx:2,3;audio:4-6`.
+pub const AFFINITY_ENV_VAR: &str = "GST_THREADSHARE_CONTEXT_AFFINITY";
+
+/// Environment variable used to override the scheduling policy of `Context` threads.
+///
+/// Format: `context-name:policy:value[;context-name:policy:value...]` where
+/// `policy` is one of `nice`, `fifo` or `rr`, e.g. `audio:fifo:50;bulk:nice:10`.
+pub const SCHEDULING_ENV_VAR: &str = "GST_THREADSHARE_CONTEXT_SCHEDULING";
+
+static CONFIGS: LazyLock<Mutex<HashMap<String, ContextConfig>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static ENV_AFFINITIES: LazyLock<HashMap<String, Vec<usize>>> =
+    LazyLock::new(|| parse_env_var(AFFINITY_ENV_VAR, parse_cpus));
+
+static ENV_SCHEDULING: LazyLock<HashMap<String, SchedulingPolicy>> =
+    LazyLock::new(|| parse_env_var(SCHEDULING_ENV_VAR, SchedulingPolicy::parse));
+
+/// Scheduling policy for the threads of a `Context`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchedulingPolicy {
+    /// Default time-sharing policy with the provided niceness (-20 to 19).
+    Nice(i32),
+    /// `SCHED_FIFO` real-time policy with the provided priority (1 to 99).
+    Fifo(i32),
+    /// `SCHED_RR` real-time policy with the provided priority (1 to 99).
+    RoundRobin(i32),
+}
+
+impl SchedulingPolicy {
+    fn parse(value: &str) -> Option<Self> {
+        let (policy, value) = value.split_once(':')?;
+        let value = value.trim().parse().ok()?;
+
+        match policy.trim() {
+            "nice" => Some(SchedulingPolicy::Nice(value)),
+            "fifo" => Some(SchedulingPolicy::Fifo(value)),
+            "rr" => Some(SchedulingPolicy::RoundRobin(value)),
+            _ => None,
+        }
+    }
+}
+
+/// Configuration applied to the threads of a [`Context`] when they are spawned.
+///
+/// The configuration must be registered using [`Context::configure`] before
+/// the first element acquires the [`Context`].
+///
+/// [`Context`]: super::Context
+/// [`Context::configure`]: super::Context::configure
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContextConfig {
+    cpu_affinity: Option<Vec<usize>>,
+    scheduling: Option<SchedulingPolicy>,
+}
+
+impl ContextConfig {
+    /// Pins the threads of the `Context` to the provided CPUs.
+    ///
+    /// Only supported on Linux & Android. Failures to apply the affinity
+    /// are logged and the `Context` threads keep running unpinned.
+    pub fn cpu_affinity(mut self, cpus: impl IntoIterator<Item = usize>) -> Self {
+        self.cpu_affinity = Some(cpus.into_iter().collect());
+        self
+    }
+
+    pub fn cpus(&self) -> Option<&[usize]> {
+        self.cpu_affinity.as_deref()
+    }
+
+    /// Sets the scheduling policy of the threads of the `Context`.
+    ///
+    /// Only supported on Linux & Android. Real-time policies and negative
+    /// niceness usually require the `CAP_SYS_NICE` capability: on failure,
+    /// a warning is logged and the threads keep the default scheduling.
+    pub fn scheduling(mut self, policy: SchedulingPolicy) -> Self {
+        self.scheduling = Some(policy);
+        self
+    }
+
+    pub fn scheduling_policy(&self) -> Option<SchedulingPolicy> {
+        self.scheduling
+    }
+
+    /// Registers the configuration for the `Context` with the provided name.
+    pub(super) fn register(context_name: &str, config: ContextConfig) {
+        CONFIGS
+            .lock()
+            .unwrap()
+            .insert(context_name.to_string(), config);
+    }
+
+    /// Returns the configuration for the `Context` with the provided name.
+    ///
+    /// Values from the environment take precedence over the registered ones.
+    pub(super) fn for_context(context_name: &str) -> ContextConfig {
+        let mut config = CONFIGS
+            .lock()
+            .unwrap()
+            .get(context_name)
+            .cloned()
+            .unwrap_or_default();
+
+        if let Some(cpus) = ENV_AFFINITIES.get(context_name) {
+            config.cpu_affinity = Some(cpus.clone());
+        }
+
+        if let Some(policy) = ENV_SCHEDULING.get(context_name) {
+            config.scheduling = Some(*policy);
+        }
+
+        config
+    }
+
+    /// Applies the configuration to current thread.
+    pub(super) fn apply(&self, context_name: &str) {
+        if let Some(cpus) = self.cpu_affinity.as_ref() {
+            match set_cpu_affinity(cpus) {
+                Ok(()) => gst::debug!(
+                    RUNTIME_CAT,
+                    "Pinned thread for Context {} to CPUs {:?}",
+                    context_name,
+                    cpus,
+                ),
+                Err(err) => gst::warning!(
+                    RUNTIME_CAT,
+                    "Failed to pin thread for Context {} to CPUs {:?}: {}",
+                    context_name,
+                    cpus,
+                    err,
+                ),
+            }
+        }
+
+        if let Some(policy) = self.scheduling {
+            match set_scheduling(policy) {
+                Ok(()) => gst::debug!(
+                    RUNTIME_CAT,
+                    "Applied {:?} to thread for Context {}",
+                    policy,
+                    context_name,
+                ),
+                Err(err) if err.kind() == io::ErrorKind::PermissionDenied => gst::warning!(
+                    RUNTIME_CAT,
+                    "Not allowed to apply {:?} to thread for Context {}, \
+                     CAP_SYS_NICE might be missing. Keeping default scheduling",
+                    policy,
+                    context_name,
+                ),
+                Err(err) => gst::warning!(
+                    RUNTIME_CAT,
+                    "Failed to apply {:?} to thread for Context {}: {}",
+                    policy,
+                    context_name,
+                    err,
+                ),
+            }
+        }
+    }
+}
+
+fn parse_env_var<T>(env_var: &str, parse: impl Fn(&str) -> Option<T>) -> HashMap<String, T> {
+    std::env::var(env_var)
+        .map(|value| parse_entries(env_var, &value, parse))
+        .unwrap_or_default()
+}
+
+fn parse_entries<T>(
+    env_var: &str,
+    value: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> HashMap<String, T> {
+    let mut entries = HashMap::new();
+
+    for entry in value.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        match entry
+            .split_once(':')
+            .and_then(|(name, value)| Some((name.trim(), parse(value)?)))
+        {
+            Some((name, value)) => {
+                entries.insert(name.to_string(), value);
+            }
+            None => gst::warning!(
+                RUNTIME_CAT,
+                "Ignoring invalid {} entry '{}'",
+                env_var,
+                entry
+            ),
+        }
+    }
+
+    entries
+}
+
+fn parse_cpus(value: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+
+    for item in value.split(',').map(str::trim) {
+        match item.split_once('-') {
+            Some((first, last)) => {
+                let first = first.trim().parse::<usize>().ok()?;
+                let last = last.trim().parse::<usize>().ok()?;
+                if first > last {
+                    return None;
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(item.parse().ok()?),
+        }
+    }
+
+    Some(cpus)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_cpu_affinity(cpus: &[usize]) -> io::Result<()> {
+    // SAFETY: `cpu_set_t` is a plain bit mask for which all zeros is a valid value
+    // and `CPU_SET` is only called with indices within its bounds.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &cpu in cpus {
+            if cpu >= libc::CPU_SETSIZE as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("CPU {cpu} out of range"),
+                ));
+            }
+            libc::CPU_SET(cpu, &mut set);
+        }
+
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_cpu_affinity(_cpus: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "CPU affinity is not supported on this platform",
+    ))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_scheduling(policy: SchedulingPolicy) -> io::Result<()> {
+    let (policy, priority) = match policy {
+        SchedulingPolicy::Nice(nice) => {
+            // On Linux, the niceness is a per-thread attribute when using the thread id
+            // SAFETY: these syscalls have no memory safety requirements.
+            let res = unsafe {
+                let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+                libc::setpriority(libc::PRIO_PROCESS, tid, nice)
+            };
+            if res != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            return Ok(());
+        }
+        SchedulingPolicy::Fifo(priority) => (libc::SCHED_FIFO, priority),
+        SchedulingPolicy::RoundRobin(priority) => (libc::SCHED_RR, priority),
+    };
+
+    // SAFETY: all zeros is a valid `sched_param` and we only set the priority.
+    let res = unsafe {
+        let mut param: libc::sched_param = std::mem::zeroed();
+        param.sched_priority = priority;
+        libc::pthread_setschedparam(libc::pthread_self(), policy, &param)
+    };
+    if res != 0 {
+        return Err(io::Error::from_raw_os_error(res));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_scheduling(_policy: SchedulingPolicy) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Scheduling policies are not supported on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_env_affinities() {
+        gst::init().unwrap();
+
+        let affinities = parse_entries(
+            AFFINITY_ENV_VAR,
+            "net-ctx:2,3; audio : 4-6;broken;bad:1-a;",
+            parse_cpus,
+        );
+        assert_eq!(affinities.len(), 2);
+        assert_eq!(affinities["net-ctx"], [2, 3]);
+        assert_eq!(affinities["audio"], [4, 5, 6]);
+    }
+
+    #[test]
+    fn parse_env_scheduling() {
+        gst::init().unwrap();
+
+        let policies = parse_entries(
+            SCHEDULING_ENV_VAR,
+            "audio:fifo:50;bulk:nice:10;video:rr:20;bad:idle:1;worse:nice",
+            SchedulingPolicy::parse,
+        );
+        assert_eq!(policies.len(), 3);
+        assert_eq!(policies["audio"], SchedulingPolicy::Fifo(50));
+        assert_eq!(policies["bulk"], SchedulingPolicy::Nice(10));
+        assert_eq!(policies["video"], SchedulingPolicy::RoundRobin(20));
+    }
+
+    #[test]
+    fn registered_config() {
+        gst::init().unwrap();
+
+        assert_eq!(
+            ContextConfig::for_context("registered_config"),
+            ContextConfig::default()
+        );
+
+        let config = ContextConfig::default().cpu_affinity([0]);
+        ContextConfig::register("registered_config", config.clone());
+        assert_eq!(ContextConfig::for_context("registered_config"), config);
+        assert_eq!(config.cpus(), Some([0].as_slice()));
+    }
+}
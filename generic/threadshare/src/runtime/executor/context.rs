@@ -3,6 +3,8 @@
 //
 // Take a look at the license at the top of the repository in the LICENSE file.
 
+use futures::channel::oneshot;
+use futures::future::AbortHandle;
 use futures::prelude::*;
 
 use std::sync::LazyLock;
@@ -10,12 +12,22 @@ use std::sync::LazyLock;
 use std::collections::HashMap;
 use std::io;
 use std::pin::Pin;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once};
 use std::task::{self, Poll};
+use std::thread::ThreadId;
 use std::time::Duration;
 
-use super::{Handle, HandleWeak, JoinHandle, Scheduler, SubTaskOutput, TaskId};
-use crate::runtime::RUNTIME_CAT;
+#[cfg(unix)]
+use std::os::unix::io::AsFd;
+#[cfg(windows)]
+use std::os::windows::io::AsSocket;
+
+use super::{
+    Async, ContextConfig, ContextStats, Handle, HandleWeak, JoinHandle, Scheduler,
+    SubTaskJoinHandle, SubTaskOutput, TaskId, TaskInfo,
+};
+use crate::runtime::{CONTEXT_DUMP_CAT, RUNTIME_CAT};
 
 // We are bound to using `sync` for the `runtime` `Mutex`es. Attempts to use `async` `Mutex`es
 // lead to the following issues:
@@ -30,6 +42,19 @@ use crate::runtime::RUNTIME_CAT;
 static CONTEXTS: LazyLock<Mutex<HashMap<Arc<str>, ContextWeak>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
+/// Maximum duration to wait for pending sub tasks when the last user releases a `Context`.
+const RELEASE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Environment variable used to dump the `Context`s when the process receives `SIGUSR1`.
+///
+/// The handler is installed on Unix when the variable is set to `1` and the
+/// `ts-context-dump` debug category is enabled. A handler previously installed
+/// by the application is still called.
+pub const DUMP_SIGNAL_ENV_VAR: &str = "GST_THREADSHARE_CONTEXT_DUMP_SIGNAL";
+
+// Set from the `SIGUSR1` handler, checked by the `Context` threads.
+static DUMP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
 /// Blocks on `future` in one way or another if possible.
 ///
 /// IO & time related `Future`s must be handled within their own [`Context`].
@@ -40,10 +65,14 @@ static CONTEXTS: LazyLock<Mutex<HashMap<Arc<str>, ContextWeak>>> =
 ///
 /// Otherwise the current thread is blocking and the passed in future is executed.
 ///
+/// In both cases, the returned [`SubTaskJoinHandle`] resolves to the output of the future.
+/// When the future was deferred as a sub task, the output is available once the current task
+/// has drained its sub tasks, e.g. after the pad function returns.
+///
 /// Note that you must not pass any futures here that wait for the currently active task in one way
 /// or another as this would deadlock!
 #[track_caller]
-pub fn block_on_or_add_sub_task<Fut>(future: Fut) -> Option<Fut::Output>
+pub fn block_on_or_add_sub_task<Fut>(future: Fut) -> SubTaskJoinHandle<Fut::Output>
 where
     Fut: Future + Send + 'static,
     Fut::Output: Send + 'static,
@@ -55,15 +84,19 @@ where
             cur_task_id,
             cur_context.name()
         );
-        let _ = cur_context.add_sub_task(cur_task_id, async move {
-            future.await;
-            Ok(())
-        });
-        return None;
+        let (sender, receiver) = oneshot::channel();
+        let abort_handle = cur_context
+            .add_sub_task(cur_task_id, async move {
+                // The receiver might have been dropped, which is fine.
+                let _ = sender.send(future.await);
+                Ok(())
+            })
+            .ok();
+        return SubTaskJoinHandle::sub_task(cur_task_id, receiver, abort_handle);
     }
 
     // Not running in a Context thread so we can block
-    Some(block_on(future))
+    SubTaskJoinHandle::ready(block_on(future))
 }
 
 /// Blocks on `future`.
@@ -93,6 +126,35 @@ where
     Scheduler::block_on(future)
 }
 
+/// Asserts that current thread is not a [`Context`] thread before blocking.
+///
+/// Blocking a `Context` thread stalls all the tasks sharing the `Context`
+/// and deadlocks if the awaited result depends on one of them. This is
+/// easy to miss in application callbacks, such as action signals, which
+/// might be invoked from a `Context` thread.
+///
+/// This is a no-op unless the `context-assertions` feature is enabled.
+///
+/// # Panics
+///
+/// With the `context-assertions` feature, panics if called within a [`Context`] thread.
+#[track_caller]
+#[inline]
+pub fn debug_assert_not_context_thread(entry_point: &str) {
+    #[cfg(feature = "context-assertions")]
+    if let Some(context) = Context::current() {
+        let msg = format!(
+            "{entry_point} would block Context {} from one of its threads",
+            context.name()
+        );
+        gst::error!(RUNTIME_CAT, "{}", msg);
+        panic!("{}", msg);
+    }
+
+    #[cfg(not(feature = "context-assertions"))]
+    let _ = entry_point;
+}
+
 /// Yields execution back to the runtime.
 #[inline]
 pub fn yield_now() -> YieldNow {
@@ -117,19 +179,125 @@ impl Future for YieldNow {
     }
 }
 
+/// Dumps all the `Context`s if it was requested by a signal.
+pub(super) fn dump_if_requested() {
+    if DUMP_REQUESTED.load(Ordering::Relaxed) && DUMP_REQUESTED.swap(false, Ordering::Relaxed) {
+        Context::dump_all();
+    }
+}
+
+/// Installs a `SIGUSR1` handler which requests a dump of all the `Context`s.
+///
+/// The handler is only installed if [`DUMP_SIGNAL_ENV_VAR`] is `1` and the dump
+/// category is enabled, so as to not interfere with applications using `SIGUSR1`
+/// for their own purposes. The handler which was previously installed is chained.
+fn install_dump_signal_handler() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        if std::env::var(DUMP_SIGNAL_ENV_VAR).as_deref() != Ok("1")
+            || CONTEXT_DUMP_CAT.threshold() < gst::DebugLevel::Info
+        {
+            return;
+        }
+
+        #[cfg(unix)]
+        {
+            use std::sync::atomic::AtomicUsize;
+
+            // The handler previously installed for `SIGUSR1` and its flags.
+            static PREV_HANDLER: AtomicUsize = AtomicUsize::new(libc::SIG_DFL);
+            static PREV_SIGINFO: AtomicBool = AtomicBool::new(false);
+
+            extern "C" fn on_dump_signal(
+                signum: libc::c_int,
+                info: *mut libc::siginfo_t,
+                ucontext: *mut libc::c_void,
+            ) {
+                DUMP_REQUESTED.store(true, Ordering::Relaxed);
+
+                let prev = PREV_HANDLER.load(Ordering::Relaxed);
+                if prev == libc::SIG_DFL || prev == libc::SIG_IGN {
+                    // Don't terminate the process as the default action would.
+                    return;
+                }
+
+                // SAFETY: `prev` was returned by `sigaction` for `SIGUSR1` and
+                // is called with the signature matching its `SA_SIGINFO` flag.
+                unsafe {
+                    if PREV_SIGINFO.load(Ordering::Relaxed) {
+                        let prev = std::mem::transmute::<
+                            usize,
+                            extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void),
+                        >(prev);
+                        prev(signum, info, ucontext);
+                    } else {
+                        let prev = std::mem::transmute::<usize, extern "C" fn(libc::c_int)>(prev);
+                        prev(signum);
+                    }
+                }
+            }
+
+            // SAFETY: the handler only stores to atomics, which is async-signal-safe,
+            // before calling the previous handler.
+            let res = unsafe {
+                let mut prev_action: libc::sigaction = std::mem::zeroed();
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = on_dump_signal
+                    as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void)
+                    as usize;
+                action.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO;
+                libc::sigemptyset(&mut action.sa_mask);
+
+                let res = libc::sigaction(libc::SIGUSR1, &action, &mut prev_action);
+                if res == 0 {
+                    PREV_SIGINFO.store(
+                        prev_action.sa_flags & libc::SA_SIGINFO != 0,
+                        Ordering::Relaxed,
+                    );
+                    PREV_HANDLER.store(prev_action.sa_sigaction, Ordering::Relaxed);
+                }
+
+                res
+            };
+
+            if res == 0 {
+                gst::info!(CONTEXT_DUMP_CAT, "Send SIGUSR1 to dump the Contexts");
+            } else {
+                gst::warning!(
+                    CONTEXT_DUMP_CAT,
+                    "Failed to install SIGUSR1 handler: {}",
+                    io::Error::last_os_error(),
+                );
+            }
+        }
+    });
+}
+
+/// Information about a [`Context`], see [`Context::enumerate`].
+#[derive(Clone, Debug)]
+pub struct ContextInfo {
+    pub name: Arc<str>,
+    pub wait: Duration,
+    pub thread_ids: Vec<ThreadId>,
+    pub n_tasks: usize,
+    pub n_sub_tasks: usize,
+}
+
 #[derive(Clone, Debug)]
 pub struct ContextWeak(HandleWeak);
 
 impl ContextWeak {
     pub fn upgrade(&self) -> Option<Context> {
-        self.0.upgrade().map(Context)
+        self.0.upgrade().map(Context::from)
     }
 }
 
 /// A `threadshare` `runtime` `Context`.
 ///
 /// The `Context` provides low-level asynchronous processing features to
-/// multiplex task execution on a single thread.
+/// multiplex task execution on a single thread, or on a small pool of threads
+/// (see [`Context::acquire_multi`]).
 ///
 /// `Element` implementations should use [`PadSrc`] and [`PadSink`] which
 ///  provide high-level features.
@@ -137,7 +305,7 @@ impl ContextWeak {
 /// [`PadSrc`]: ../pad/struct.PadSrc.html
 /// [`PadSink`]: ../pad/struct.PadSink.html
 #[derive(Clone, Debug)]
-pub struct Context(Handle);
+pub struct Context(Handle, Option<Arc<WaitRequest>>);
 
 impl PartialEq for Context {
     fn eq(&self, other: &Self) -> bool {
@@ -147,31 +315,318 @@ impl PartialEq for Context {
 
 impl Eq for Context {}
 
+/// The wait duration requested by a user of a [`Context`].
+///
+/// The request is withdrawn when the last clone of the `Context`
+/// returned by [`Context::acquire`] is dropped. When the last user
+/// releases the `Context`, it is shut down gracefully.
+#[derive(Debug)]
+struct WaitRequest {
+    handle: Handle,
+    wait: Duration,
+}
+
+impl Drop for WaitRequest {
+    fn drop(&mut self) {
+        let mut contexts = CONTEXTS.lock().unwrap();
+
+        let max_throttling = self.handle.remove_wait_request(self.wait);
+        if self.handle.has_wait_requests() {
+            gst::debug!(
+                RUNTIME_CAT,
+                "Released Context '{}' wait {:?}, now throttling {:?}",
+                self.handle.context_name(),
+                self.wait,
+                max_throttling,
+            );
+            return;
+        }
+
+        if Context::is_context_thread() {
+            // Can't block here, the `Context` will be shut down
+            // without draining when the last handle is dropped.
+            gst::debug!(
+                RUNTIME_CAT,
+                "Last user released Context '{}' from a Context thread",
+                self.handle.context_name(),
+            );
+            return;
+        }
+
+        let context = Context::from(self.handle.clone());
+        context.unregister(&mut contexts);
+        drop(contexts);
+
+        gst::debug!(
+            RUNTIME_CAT,
+            "Last user released Context '{}', shutting down",
+            context.name(),
+        );
+        context.0.shutdown(RELEASE_SHUTDOWN_TIMEOUT);
+    }
+}
+
 impl Context {
+    /// Acquires the `Context` with the provided name, starting it if needed.
+    ///
+    /// `wait` is the maximum throttling duration requested by the caller.
+    /// When several users share a `Context` with different `wait`s, the
+    /// `Context` throttles using the smallest one among its current users
+    /// and re-adjusts as they release the `Context`, i.e. when the last
+    /// clone of the returned `Context` is dropped.
     pub fn acquire(context_name: &str, wait: Duration) -> Result<Self, io::Error> {
+        Self::acquire_multi(context_name, wait, 1)
+    }
+
+    /// Acquires a `Context` running on `n_threads` threads.
+    ///
+    /// The threads share the same reactor and pick tasks from the same queue,
+    /// so a task can be executed by any of the threads each time it is woken up.
+    /// A given task is never polled concurrently though, which means that the
+    /// iterations of a `runtime::Task` never overlap.
+    ///
+    /// Compared to a single-threaded `Context`, this allows spreading the load
+    /// of many elements over several cores without sharding them manually
+    /// across `Context` names. The tradeoffs are:
+    ///
+    /// * Tasks of the same `Context` can now run in parallel. Elements must
+    ///   not rely on the `Context` to serialize accesses to shared state.
+    /// * The reactor is protected by a `Mutex` which the threads contend for
+    ///   when registering timers and I/O sources.
+    /// * Thread-local values are not preserved between two `await` points.
+    ///
+    /// If a `Context` with the same name is already running, it is joined
+    /// regardless of its number of threads.
+    pub fn acquire_multi(
+        context_name: &str,
+        wait: Duration,
+        n_threads: usize,
+    ) -> Result<Self, io::Error> {
         assert_ne!(context_name, Scheduler::DUMMY_NAME);
 
         let mut contexts = CONTEXTS.lock().unwrap();
 
         if let Some(context_weak) = contexts.get(context_name) {
             if let Some(context) = context_weak.upgrade() {
-                gst::debug!(RUNTIME_CAT, "Joining Context '{}'", context.name());
-                return Ok(context);
+                if n_threads > 1 && n_threads != context.n_threads() {
+                    gst::warning!(
+                        RUNTIME_CAT,
+                        "Joining Context '{}' with {} threads instead of requested {}",
+                        context.name(),
+                        context.n_threads(),
+                        n_threads,
+                    );
+                } else {
+                    gst::debug!(RUNTIME_CAT, "Joining Context '{}'", context.name());
+                }
+
+                let prev_wait = context.wait_duration();
+                let max_throttling = context.0.add_wait_request(wait);
+                if wait != prev_wait {
+                    gst::warning!(
+                        RUNTIME_CAT,
+                        "Joining Context '{}' with wait {:?} while it was throttling {:?}, now throttling {:?}",
+                        context.name(),
+                        wait,
+                        prev_wait,
+                        max_throttling,
+                    );
+                }
+
+                return Ok(context.with_wait_request(wait));
             }
         }
 
-        let context = Context(Scheduler::start(context_name, wait));
+        install_dump_signal_handler();
+
+        let config = ContextConfig::for_context(context_name);
+        let handle = Scheduler::start_multi(context_name, wait, n_threads, config);
+        handle.add_wait_request(wait);
+        let context = Context::from(handle).with_wait_request(wait);
         contexts.insert(context_name.into(), context.downgrade());
 
         gst::debug!(
             RUNTIME_CAT,
-            "New Context '{}' throttling {:?}",
+            "New Context '{}' throttling {:?} with {} threads",
             context.name(),
             wait,
+            context.n_threads(),
         );
         Ok(context)
     }
 
+    /// Registers the configuration for the `Context` with the provided name.
+    ///
+    /// The configuration is applied when the `Context` threads are spawned,
+    /// so this must be called before the `Context` is acquired for the first
+    /// time, typically before the pipeline is started.
+    ///
+    /// Some configuration items can be overridden using environment variables
+    /// (see [`AFFINITY_ENV_VAR`](super::AFFINITY_ENV_VAR) and
+    /// [`SCHEDULING_ENV_VAR`](super::SCHEDULING_ENV_VAR)).
+    pub fn configure(context_name: &str, config: ContextConfig) {
+        assert_ne!(context_name, Scheduler::DUMMY_NAME);
+
+        let contexts = CONTEXTS.lock().unwrap();
+        if contexts
+            .get(context_name)
+            .and_then(ContextWeak::upgrade)
+            .is_some()
+        {
+            gst::warning!(
+                RUNTIME_CAT,
+                "Context '{}' is already running, configuration will apply on next start",
+                context_name,
+            );
+        }
+
+        ContextConfig::register(context_name, config);
+    }
+
+    /// Returns information about all the running `Context`s.
+    pub fn enumerate() -> Vec<ContextInfo> {
+        Self::running().iter().map(Context::info).collect()
+    }
+
+    fn running() -> Vec<Context> {
+        let mut contexts = CONTEXTS
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(ContextWeak::upgrade)
+            .collect::<Vec<_>>();
+        contexts.sort_by(|a, b| a.name().cmp(b.name()));
+
+        contexts
+    }
+
+    /// Returns information about this `Context`.
+    pub fn info(&self) -> ContextInfo {
+        let tasks = self.tasks();
+
+        ContextInfo {
+            name: self.0.context_name().into(),
+            wait: self.wait_duration(),
+            thread_ids: self.0.thread_ids(),
+            n_tasks: tasks.len(),
+            n_sub_tasks: tasks.iter().map(|task| task.n_sub_tasks).sum(),
+        }
+    }
+
+    /// Returns information about the tasks currently registered on this `Context`.
+    pub fn tasks(&self) -> Vec<TaskInfo> {
+        self.0.task_infos()
+    }
+
+    /// Sets the name of the task with the provided `TaskId`.
+    ///
+    /// The name is reported by [`Self::tasks`] & [`Self::dump`].
+    pub fn set_task_name(&self, task_id: TaskId, name: impl Into<Arc<str>>) {
+        self.0.set_task_name(task_id, name.into());
+    }
+
+    /// Logs this `Context` and its tasks to the `ts-context-dump` debug category.
+    pub fn dump(&self) {
+        if CONTEXT_DUMP_CAT.threshold() < gst::DebugLevel::Info {
+            return;
+        }
+
+        let info = self.info();
+        gst::info!(
+            CONTEXT_DUMP_CAT,
+            "Context '{}': wait {:?}, threads {:?}, {} tasks, {} sub tasks",
+            info.name,
+            info.wait,
+            info.thread_ids,
+            info.n_tasks,
+            info.n_sub_tasks,
+        );
+
+        for task in self.tasks() {
+            gst::info!(
+                CONTEXT_DUMP_CAT,
+                "  {:?} '{}': {} sub tasks",
+                task.id,
+                task.name.as_deref().unwrap_or("unnamed"),
+                task.n_sub_tasks,
+            );
+        }
+    }
+
+    /// Logs all the running `Context`s to the `ts-context-dump` debug category.
+    ///
+    /// This is also triggered when a `runtime::Task` switches to the `Error`
+    /// state, provided the category is enabled (e.g. `GST_DEBUG=ts-context-dump:4`),
+    /// and on Unix when the process receives `SIGUSR1` if [`DUMP_SIGNAL_ENV_VAR`]
+    /// is set to `1`.
+    pub fn dump_all() {
+        if CONTEXT_DUMP_CAT.threshold() < gst::DebugLevel::Info {
+            return;
+        }
+
+        let contexts = Self::running();
+        gst::info!(CONTEXT_DUMP_CAT, "{} running Contexts", contexts.len());
+        for context in contexts {
+            context.dump();
+        }
+    }
+
+    /// Binds the already registered `wait` request to the returned `Context`.
+    fn with_wait_request(self, wait: Duration) -> Self {
+        let wait_request = WaitRequest {
+            handle: self.0.clone(),
+            wait,
+        };
+
+        Context(self.0, Some(Arc::new(wait_request)))
+    }
+
+    /// Removes this `Context` from the registry so that it can't be acquired anymore.
+    fn unregister(&self, contexts: &mut HashMap<Arc<str>, ContextWeak>) {
+        let is_registered = contexts
+            .get(self.name())
+            .and_then(ContextWeak::upgrade)
+            .is_some_and(|registered| &registered == self);
+        if is_registered {
+            contexts.remove(self.name());
+        }
+    }
+
+    /// Shuts this `Context` down gracefully.
+    ///
+    /// The `Context` can't be acquired anymore and new tasks are cancelled.
+    /// The sub tasks which are still pending are executed, waiting for them
+    /// at most `timeout`, then the `Context` threads and reactor are stopped.
+    ///
+    /// This is automatically called when the last user releases the `Context`,
+    /// i.e. when the last clone of a `Context` returned by [`Self::acquire`]
+    /// is dropped, unless this happens on a `Context` thread.
+    ///
+    /// Returns `true` if all the pending sub tasks completed. If the `Context`
+    /// is already shutting down, waits for that shutdown to complete and
+    /// returns its outcome.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called within a [`Context`] thread.
+    #[track_caller]
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        if let Some(context) = Context::current() {
+            let msg = format!(
+                "Attempt to shut down Context {} within Context {}",
+                self.name(),
+                context.name()
+            );
+            gst::error!(RUNTIME_CAT, "{}", msg);
+            panic!("{}", msg);
+        }
+
+        self.unregister(&mut CONTEXTS.lock().unwrap());
+
+        gst::debug!(RUNTIME_CAT, "Shutting down Context '{}'", self.name());
+        self.0.shutdown(timeout)
+    }
+
     pub fn downgrade(&self) -> ContextWeak {
         ContextWeak(self.0.downgrade())
     }
@@ -183,10 +638,25 @@ impl Context {
     // FIXME this could be renamed as max_throttling
     // but then, all elements should also change their
     // wait variables and properties to max_throttling.
+    /// Current max throttling duration, i.e. the smallest `wait`
+    /// requested by the current users of the `Context`.
     pub fn wait_duration(&self) -> Duration {
         self.0.max_throttling()
     }
 
+    /// Number of threads executing the tasks of this `Context`.
+    pub fn n_threads(&self) -> usize {
+        self.0.n_threads()
+    }
+
+    /// Returns the utilization statistics of this `Context`.
+    ///
+    /// Periodic logs of these statistics can be enabled using
+    /// [`STATS_ENV_VAR`](super::STATS_ENV_VAR).
+    pub fn stats(&self) -> ContextStats {
+        self.0.stats()
+    }
+
     /// Total duration the scheduler spent parked.
     ///
     /// This is only useful for performance evaluation.
@@ -196,20 +666,23 @@ impl Context {
     }
 
     /// Returns `true` if a `Context` is running on current thread.
+    ///
+    /// Application callbacks, e.g. action signals handlers, can use this
+    /// to make sure they don't block a `Context` thread.
     pub fn is_context_thread() -> bool {
         Scheduler::is_scheduler_thread()
     }
 
     /// Returns the `Context` running on current thread, if any.
     pub fn current() -> Option<Context> {
-        Scheduler::current().map(Context)
+        Scheduler::current().map(Context::from)
     }
 
     /// Returns the `TaskId` running on current thread, if any.
     pub fn current_task() -> Option<(Context, TaskId)> {
         Scheduler::current().map(|scheduler| {
             // Context users always operate on a Task
-            (Context(scheduler), TaskId::current().unwrap())
+            (Context::from(scheduler), TaskId::current().unwrap())
         })
     }
 
@@ -241,6 +714,7 @@ impl Context {
                     self.name(),
                     cur.name()
                 );
+                debug_assert_not_context_thread("Context::enter");
             }
         } else {
             gst::debug!(RUNTIME_CAT, "Entering Context {}", self.name());
@@ -249,6 +723,52 @@ impl Context {
         self.0.enter(f)
     }
 
+    /// Registers the I/O handle `io` with the reactor of this [`Context`].
+    ///
+    /// This allows awaiting the readiness of file descriptors which are not created
+    /// by the `runtime`, e.g. serial ports or netlink sockets, from the tasks of
+    /// this `Context` using [`Async::readable`], [`Async::read_with`], etc.
+    ///
+    /// The handle is put in non-blocking mode and it is deregistered from the reactor
+    /// when the returned [`Async`] is dropped. The file descriptor must remain open
+    /// while it is registered: use a type owning it, such as [`OwnedFd`], or an
+    /// `Arc<File>` if it must also be used elsewhere.
+    ///
+    /// Note that regular files are always ready and must not be registered.
+    ///
+    /// [`OwnedFd`]: std::os::fd::OwnedFd
+    #[cfg(unix)]
+    pub fn register_io<T>(&self, io: T) -> io::Result<Async<T>>
+    where
+        T: AsFd + Send + 'static,
+    {
+        if Context::current().as_ref() == Some(self) {
+            Async::new(io)
+        } else {
+            self.enter(|| Async::new(io))
+        }
+    }
+
+    /// Registers the I/O handle `io` with the reactor of this [`Context`].
+    ///
+    /// This allows awaiting the readiness of sockets which are not created
+    /// by the `runtime` from the tasks of this `Context` using
+    /// [`Async::readable`], [`Async::read_with`], etc.
+    ///
+    /// The handle is put in non-blocking mode and it is deregistered from the reactor
+    /// when the returned [`Async`] is dropped.
+    #[cfg(windows)]
+    pub fn register_io<T>(&self, io: T) -> io::Result<Async<T>>
+    where
+        T: AsSocket + Send + 'static,
+    {
+        if Context::current().as_ref() == Some(self) {
+            Async::new(io)
+        } else {
+            self.enter(|| Async::new(io))
+        }
+    }
+
     pub fn spawn<Fut>(&self, future: Fut) -> JoinHandle<Fut::Output>
     where
         Fut: Future + Send + 'static,
@@ -276,13 +796,39 @@ impl Context {
         self.0.unpark();
     }
 
-    pub fn add_sub_task<T>(&self, task_id: TaskId, sub_task: T) -> Result<(), T>
+    /// Adds a sub task to the task with the provided `task_id`.
+    ///
+    /// The sub task is executed when the task drains its sub tasks.
+    /// Use the returned [`AbortHandle`] to abort it, in which case it
+    /// is considered as done with no error.
+    ///
+    /// Returns the sub task back if the task no longer exists.
+    pub fn add_sub_task<T>(&self, task_id: TaskId, sub_task: T) -> Result<AbortHandle, T>
     where
         T: Future<Output = SubTaskOutput> + Send + 'static,
     {
         self.0.add_sub_task(task_id, sub_task)
     }
 
+    /// Aborts the pending sub tasks of the task with the provided `task_id`.
+    ///
+    /// This includes the sub task being executed if any.
+    pub fn abort_sub_tasks(&self, task_id: TaskId) {
+        self.0.abort_sub_tasks(task_id);
+    }
+
+    /// Returns the number of sub tasks added to the task with the provided `task_id`
+    /// since it was last drained.
+    pub fn n_added_sub_tasks(&self, task_id: TaskId) -> usize {
+        self.0.n_added_sub_tasks(task_id)
+    }
+
+    /// Returns the abort handles of the sub tasks added to the task with the provided
+    /// `task_id` since it was last drained, skipping the first `skip` ones.
+    pub fn added_sub_task_abort_handles(&self, task_id: TaskId, skip: usize) -> Vec<AbortHandle> {
+        self.0.added_sub_task_abort_handles(task_id, skip)
+    }
+
     pub async fn drain_sub_tasks() -> SubTaskOutput {
         let (ctx, task_id) = match Context::current_task() {
             Some(task) => task,
@@ -295,7 +841,7 @@ impl Context {
 
 impl From<Handle> for Context {
     fn from(handle: Handle) -> Self {
-        Context(handle)
+        Context(handle, None)
     }
 }
 
@@ -473,6 +1019,372 @@ mod tests {
         }
     }
 
+    #[test]
+    fn block_on_or_add_sub_task_inline() {
+        gst::init().unwrap();
+
+        // Not in a Context thread: the future is executed immediately
+        let handle = super::block_on_or_add_sub_task(async { 42 });
+        assert!(!handle.is_sub_task());
+        assert_eq!(futures::executor::block_on(handle).unwrap(), 42);
+    }
+
+    #[test]
+    fn block_on_or_add_sub_task_deferred() {
+        gst::init().unwrap();
+
+        let context =
+            Context::acquire("block_on_or_add_sub_task_deferred", SLEEP_DURATION).unwrap();
+
+        let pending_handle = futures::executor::block_on(context.spawn(async {
+            let handle = super::block_on_or_add_sub_task(async { 42 });
+            assert!(handle.is_sub_task());
+
+            // Output is available once the sub tasks are drained
+            Context::drain_sub_tasks().await.unwrap();
+            assert_eq!(handle.await.unwrap(), 42);
+
+            // This one is never drained
+            super::block_on_or_add_sub_task(async { 43 })
+        }))
+        .unwrap();
+
+        // The sub task was dropped along with its task
+        pending_handle.now_or_never().unwrap().unwrap_err();
+    }
+
+    #[test]
+    fn abort_sub_tasks() {
+        gst::init().unwrap();
+
+        let context = Context::acquire("abort_sub_tasks", SLEEP_DURATION).unwrap();
+
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let join_handle = context.spawn(async move {
+            let (ctx, task_id) = Context::current_task().unwrap();
+            let res = ctx.add_sub_task(task_id, async move {
+                started_tx.send(()).unwrap();
+                // Sleep forever
+                futures::future::pending::<()>().await;
+                Ok(())
+            });
+            assert!(res.is_ok());
+
+            // Won't be executed since it's behind the sleeping sub task
+            let pending_handle = super::block_on_or_add_sub_task(async { 42 });
+
+            Context::drain_sub_tasks().await.unwrap();
+
+            pending_handle
+        });
+        let task_id = join_handle.task_id();
+
+        started_rx.recv().unwrap();
+        let start = Instant::now();
+        context.abort_sub_tasks(task_id);
+
+        let pending_handle = futures::executor::block_on(join_handle).unwrap();
+        assert!(start.elapsed() < 5 * DELAY, "not aborted promptly");
+        pending_handle.now_or_never().unwrap().unwrap_err();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn register_io() {
+        use std::io::Write;
+        use std::os::fd::OwnedFd;
+        use std::os::unix::net::UnixStream;
+
+        gst::init().unwrap();
+
+        let context = Context::acquire("register_io", SLEEP_DURATION).unwrap();
+
+        // Registered as an opaque fd from outside the Context
+        let (mut writer, reader) = UnixStream::pair().unwrap();
+        let reader = context.register_io(OwnedFd::from(reader)).unwrap();
+
+        let join_handle = context.spawn(async move {
+            let mut buf = [0; 4];
+            let len = reader
+                .read_with(|fd| rustix::io::read(fd, &mut buf).map_err(std::io::Error::from))
+                .await
+                .unwrap();
+
+            (len, buf)
+        });
+
+        writer.write_all(b"ping").unwrap();
+        let (len, buf) = futures::executor::block_on(join_handle).unwrap();
+        assert_eq!(&buf[..len], b"ping");
+    }
+
+    #[test]
+    fn multi_threaded() {
+        gst::init().unwrap();
+
+        let context = Context::acquire_multi("multi_threaded", SLEEP_DURATION, 4).unwrap();
+        assert_eq!(context.n_threads(), 4);
+
+        // Joining the same Context gives the same threads
+        let other = Context::acquire("multi_threaded", SLEEP_DURATION).unwrap();
+        assert_eq!(other, context);
+        assert_eq!(other.n_threads(), 4);
+
+        // Blocking tasks are executed in parallel
+        let start = Instant::now();
+        let join_handles = (0..4)
+            .map(|_| {
+                context.spawn_and_unpark(async {
+                    std::thread::sleep(DELAY);
+                    assert_eq!(Context::current().unwrap().name(), "multi_threaded");
+                })
+            })
+            .collect::<Vec<_>>();
+        futures::executor::block_on(future::try_join_all(join_handles)).unwrap();
+        assert!(start.elapsed() < DELAY * 3);
+
+        // Timers and I/O sources can be used from any thread
+        let bytes_sent = futures::executor::block_on(context.spawn(async {
+            let saddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 5004);
+            let socket = Async::<UdpSocket>::bind(saddr).unwrap();
+            crate::runtime::timer::delay_for(DELAY).await;
+            let saddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 4004);
+            socket.send_to(&[0; 10], saddr).await.unwrap()
+        }))
+        .unwrap();
+        assert_eq!(bytes_sent, 10);
+    }
+
+    #[test]
+    fn wait_override() {
+        gst::init().unwrap();
+
+        let ctx_20 = Context::acquire("wait_override", Duration::from_millis(20)).unwrap();
+        assert_eq!(ctx_20.wait_duration(), Duration::from_millis(20));
+
+        // Smallest wait among users is adopted
+        let ctx_5 = Context::acquire("wait_override", Duration::from_millis(5)).unwrap();
+        assert_eq!(ctx_5, ctx_20);
+        assert_eq!(ctx_20.wait_duration(), Duration::from_millis(5));
+
+        let ctx_10 = Context::acquire("wait_override", Duration::from_millis(10)).unwrap();
+        assert_eq!(ctx_10.wait_duration(), Duration::from_millis(5));
+
+        // Request is kept as long as a clone of the acquired Context is alive
+        let ctx_5_clone = ctx_5.clone();
+        drop(ctx_5);
+        assert_eq!(ctx_20.wait_duration(), Duration::from_millis(5));
+
+        drop(ctx_5_clone);
+        assert_eq!(ctx_20.wait_duration(), Duration::from_millis(10));
+
+        // Timers are still fired with the updated throttling
+        let elapsed = futures::executor::block_on(ctx_20.spawn(async {
+            let now = Instant::now();
+            crate::runtime::timer::delay_for(DELAY).await;
+            now.elapsed()
+        }))
+        .unwrap();
+        assert!(elapsed + Duration::from_millis(5) >= DELAY);
+
+        drop(ctx_10);
+        assert_eq!(ctx_20.wait_duration(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn shutdown() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        gst::init().unwrap();
+
+        // Adds a slow sub task to a task which won't drain it.
+        fn add_slow_sub_task(
+            context: &Context,
+        ) -> (Arc<AtomicBool>, futures::channel::oneshot::Sender<()>) {
+            let completed = Arc::new(AtomicBool::new(false));
+            let (added_tx, added_rx) = std::sync::mpsc::channel();
+            let (release_tx, release_rx) = futures::channel::oneshot::channel::<()>();
+
+            let completed_clone = completed.clone();
+            let _ = context.spawn(async move {
+                let (ctx, task_id) = Context::current_task().unwrap();
+                let res = ctx.add_sub_task(task_id, async move {
+                    crate::runtime::timer::delay_for(DELAY).await;
+                    completed_clone.store(true, Ordering::SeqCst);
+                    Ok(())
+                });
+                assert!(res.is_ok());
+                added_tx.send(()).unwrap();
+
+                let _ = release_rx.await;
+            });
+            added_rx.recv().unwrap();
+
+            (completed, release_tx)
+        }
+
+        // Explicit shutdown
+        let context = Context::acquire("shutdown", SLEEP_DURATION).unwrap();
+        let (completed, _release_tx) = add_slow_sub_task(&context);
+
+        assert!(context.shutdown(Duration::from_secs(5)));
+        assert!(completed.load(Ordering::SeqCst));
+
+        // New tasks are rejected
+        assert!(futures::executor::block_on(context.spawn(async {})).is_err());
+
+        // A new Context is started when acquiring the same name
+        let new_context = Context::acquire("shutdown", SLEEP_DURATION).unwrap();
+        assert_ne!(new_context, context);
+
+        // Shutdown when the last user releases the Context
+        let (completed, _release_tx) = add_slow_sub_task(&new_context);
+        let other_user = Context::acquire("shutdown", SLEEP_DURATION).unwrap();
+
+        drop(new_context);
+        assert!(!completed.load(Ordering::SeqCst));
+
+        drop(other_user);
+        assert!(completed.load(Ordering::SeqCst));
+
+        // Concurrent shutdowns wait for the one in progress
+        let context = Context::acquire("shutdown-concurrent", SLEEP_DURATION).unwrap();
+        let (completed, _release_tx) = add_slow_sub_task(&context);
+
+        let other_shutdown = std::thread::spawn({
+            let context = context.clone();
+            move || context.shutdown(Duration::from_secs(5))
+        });
+        assert!(context.shutdown(Duration::from_secs(5)));
+        assert!(completed.load(Ordering::SeqCst));
+        assert!(other_shutdown.join().unwrap());
+    }
+
+    #[test]
+    fn stats() {
+        gst::init().unwrap();
+
+        let context = Context::acquire("context_stats", SLEEP_DURATION).unwrap();
+        let initial = context.stats();
+
+        futures::executor::block_on(context.spawn(async {
+            for _ in 0..5 {
+                crate::runtime::timer::delay_for(SLEEP_DURATION).await;
+            }
+        }))
+        .unwrap();
+
+        let delta = context.stats().delta(&initial);
+        assert!(delta.elapsed >= SLEEP_DURATION * 5);
+        assert!(delta.wakeups > 0);
+        assert!(delta.reactor_runs > 0);
+        assert!(delta.task_polls >= 5);
+        assert!(delta.wakeups_per_sec() > 0.0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn configure_affinity() {
+        use crate::runtime::ContextConfig;
+
+        fn current_cpus() -> Vec<usize> {
+            // SAFETY: all zeros is a valid `cpu_set_t` & `CPU_ISSET` is called within bounds.
+            unsafe {
+                let mut set: libc::cpu_set_t = std::mem::zeroed();
+                assert_eq!(
+                    libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set),
+                    0
+                );
+                (0..libc::CPU_SETSIZE as usize)
+                    .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+                    .collect()
+            }
+        }
+
+        gst::init().unwrap();
+
+        let cpu = current_cpus()[0];
+        Context::configure(
+            "configure_affinity",
+            ContextConfig::default().cpu_affinity([cpu]),
+        );
+
+        let context = Context::acquire("configure_affinity", SLEEP_DURATION).unwrap();
+        let cpus = futures::executor::block_on(context.spawn(async { current_cpus() })).unwrap();
+        assert_eq!(cpus, [cpu]);
+    }
+
+    #[test]
+    fn enumerate() {
+        gst::init().unwrap();
+
+        let context = Context::acquire("enumerate", SLEEP_DURATION).unwrap();
+
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = futures::channel::oneshot::channel::<()>();
+        let join_handle = context.spawn(async move {
+            let (ctx, task_id) = Context::current_task().unwrap();
+            assert!(ctx.add_sub_task(task_id, async { Ok(()) }).is_ok());
+            started_tx.send(()).unwrap();
+            let _ = release_rx.await;
+        });
+        context.set_task_name(join_handle.task_id(), "enumerate-task");
+        started_rx.recv().unwrap();
+
+        let info = Context::enumerate()
+            .into_iter()
+            .find(|info| &*info.name == "enumerate")
+            .unwrap();
+        assert_eq!(info.wait, SLEEP_DURATION);
+        assert_eq!(info.thread_ids.len(), 1);
+        assert_eq!(info.n_tasks, 1);
+        assert_eq!(info.n_sub_tasks, 1);
+
+        let tasks = context.tasks();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, join_handle.task_id());
+        assert_eq!(tasks[0].name.as_deref(), Some("enumerate-task"));
+
+        context.dump();
+
+        release_tx.send(()).unwrap();
+        futures::executor::block_on(join_handle).unwrap();
+        assert!(context.tasks().is_empty());
+
+        drop(context);
+        assert!(!Context::enumerate()
+            .iter()
+            .any(|info| &*info.name == "enumerate"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn configure_scheduling() {
+        use crate::runtime::{ContextConfig, SchedulingPolicy};
+
+        fn current_nice() -> i32 {
+            // SAFETY: these syscalls have no memory safety requirements.
+            unsafe {
+                let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+                libc::getpriority(libc::PRIO_PROCESS, tid)
+            }
+        }
+
+        gst::init().unwrap();
+
+        // Raising the niceness doesn't require any privileges
+        let nice = (current_nice() + 1).min(19);
+        Context::configure(
+            "configure_scheduling",
+            ContextConfig::default().scheduling(SchedulingPolicy::Nice(nice)),
+        );
+
+        let context = Context::acquire("configure_scheduling", SLEEP_DURATION).unwrap();
+        let ctx_nice =
+            futures::executor::block_on(context.spawn(async { current_nice() })).unwrap();
+        assert_eq!(ctx_nice, nice);
+    }
+
     #[test]
     fn block_on_from_sync() {
         gst::init().unwrap();
@@ -3,6 +3,8 @@
 //
 // Take a look at the license at the top of the repository in the LICENSE file.
 
+use futures::channel::oneshot;
+use futures::future::AbortHandle;
 use futures::prelude::*;
 
 use std::fmt;
@@ -43,6 +45,15 @@ impl<T> JoinHandle<T> {
         }
     }
 
+    /// Returns a `JoinHandle` for a task which was cancelled before it could run.
+    pub(super) fn cancelled(task_id: TaskId, scheduler: &Handle) -> Self {
+        JoinHandle {
+            task: None,
+            task_id,
+            scheduler: scheduler.clone(),
+        }
+    }
+
     pub fn is_current(&self) -> bool {
         if let Some((cur_scheduler, task_id)) = Scheduler::current().zip(TaskId::current()) {
             cur_scheduler == self.scheduler && task_id == self.task_id
@@ -100,3 +111,101 @@ impl<T> fmt::Debug for JoinHandle<T> {
             .finish()
     }
 }
+
+/// A handle to a future passed to [`block_on_or_add_sub_task`].
+///
+/// The handle resolves to the output of the future whether it was executed
+/// immediately or deferred as a sub task. In the latter case, the output is
+/// available once the sub task has been executed, i.e. after the current task
+/// has drained its sub tasks.
+///
+/// Dropping the handle doesn't cancel the sub task, use [`Self::abort`] for this.
+///
+/// [`block_on_or_add_sub_task`]: super::block_on_or_add_sub_task
+pub struct SubTaskJoinHandle<T>(SubTaskJoinHandleInner<T>);
+
+enum SubTaskJoinHandleInner<T> {
+    Ready(Option<T>),
+    SubTask {
+        task_id: TaskId,
+        receiver: oneshot::Receiver<T>,
+        abort_handle: Option<AbortHandle>,
+    },
+}
+
+// The output is never pinned.
+impl<T> Unpin for SubTaskJoinHandle<T> {}
+
+impl<T> SubTaskJoinHandle<T> {
+    pub(super) fn ready(output: T) -> Self {
+        SubTaskJoinHandle(SubTaskJoinHandleInner::Ready(Some(output)))
+    }
+
+    pub(super) fn sub_task(
+        task_id: TaskId,
+        receiver: oneshot::Receiver<T>,
+        abort_handle: Option<AbortHandle>,
+    ) -> Self {
+        SubTaskJoinHandle(SubTaskJoinHandleInner::SubTask {
+            task_id,
+            receiver,
+            abort_handle,
+        })
+    }
+
+    /// Aborts the sub task.
+    ///
+    /// The handle then resolves to a [`JoinError`] unless the sub task
+    /// was already completed. This has no effect if the future was
+    /// executed immediately.
+    pub fn abort(&self) {
+        if let SubTaskJoinHandleInner::SubTask {
+            abort_handle: Some(abort_handle),
+            ..
+        } = &self.0
+        {
+            abort_handle.abort();
+        }
+    }
+
+    /// Returns `true` if the future was deferred as a sub task.
+    pub fn is_sub_task(&self) -> bool {
+        matches!(self.0, SubTaskJoinHandleInner::SubTask { .. })
+    }
+}
+
+impl<T> Future for SubTaskJoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        match &mut self.0 {
+            SubTaskJoinHandleInner::Ready(output) => Poll::Ready(Ok(output
+                .take()
+                .expect("SubTaskJoinHandle polled after completion"))),
+            SubTaskJoinHandleInner::SubTask {
+                task_id, receiver, ..
+            } => {
+                // The sender is dropped without sending if the sub task
+                // couldn't be added or was discarded before completion.
+                let task_id = *task_id;
+                receiver
+                    .poll_unpin(cx)
+                    .map(|res| res.map_err(|_| JoinError(task_id)))
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for SubTaskJoinHandle<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            SubTaskJoinHandleInner::Ready(_) => {
+                fmt.debug_tuple("SubTaskJoinHandle::Ready").finish()
+            }
+            SubTaskJoinHandleInner::SubTask { task_id, .. } => fmt
+                .debug_struct("SubTaskJoinHandle::SubTask")
+                .field("task_id", task_id)
+                .finish(),
+        }
+    }
+}
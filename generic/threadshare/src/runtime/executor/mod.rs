@@ -6,9 +6,10 @@
 //! The `Executor` for the `threadshare` GStreamer plugins framework.
 //!
 //! The [`threadshare`]'s `Executor` consists in a set of [`Context`]s. Each [`Context`] is
-//! identified by a `name` and runs a loop in a dedicated `thread`. Users can use the [`Context`]
-//! to spawn `Future`s. `Future`s are asynchronous processings which allow waiting for resources
-//! in a non-blocking way. Examples of non-blocking operations are:
+//! identified by a `name` and runs a loop in a dedicated `thread` (or in a pool of `thread`s, see
+//! [`Context::acquire_multi`]). Users can use the [`Context`] to spawn `Future`s. `Future`s are
+//! asynchronous processings which allow waiting for resources in a non-blocking way. Examples of
+//! non-blocking operations are:
 //!
 //! * Waiting for an incoming packet on a Socket.
 //! * Waiting for an asynchronous `Mutex` `lock` to succeed.
@@ -23,11 +24,17 @@
 pub mod async_wrapper;
 pub use async_wrapper::Async;
 
+mod config;
+pub use config::{ContextConfig, SchedulingPolicy, AFFINITY_ENV_VAR, SCHEDULING_ENV_VAR};
+
 mod context;
-pub use context::{block_on, block_on_or_add_sub_task, yield_now, Context};
+pub use context::{
+    block_on, block_on_or_add_sub_task, debug_assert_not_context_thread, yield_now, Context,
+    ContextInfo, ContextWeak, DUMP_SIGNAL_ENV_VAR,
+};
 
 mod join;
-pub use join::JoinHandle;
+pub use join::{JoinError, JoinHandle, SubTaskJoinHandle};
 
 pub mod reactor;
 use reactor::{Reactor, Readable, ReadableOwned, Registration, Source, Writable, WritableOwned};
@@ -37,8 +44,11 @@ use reactor::{Reactor, Readable, ReadableOwned, Registration, Source, Writable,
 mod scheduler;
 use scheduler::{Handle, HandleWeak, Scheduler};
 
+mod stats;
+pub use stats::{ContextStats, STATS_ENV_VAR};
+
 mod task;
-pub use task::{SubTaskOutput, TaskId};
+pub use task::{SubTaskOutput, TaskId, TaskInfo};
 
 pub mod timer;
 
@@ -21,7 +21,7 @@ use std::marker::PhantomData;
 use std::mem;
 use std::panic;
 use std::pin::Pin;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll, Waker};
 use std::time::{Duration, Instant};
@@ -57,9 +57,22 @@ const READ: usize = 0;
 const WRITE: usize = 1;
 
 thread_local! {
-    static CURRENT_REACTOR: RefCell<Option<Reactor>> = const { RefCell::new(None) };
+    static CURRENT_REACTOR: RefCell<Option<Arc<Reactor>>> = const { RefCell::new(None) };
+
+    /// State of the `Reactor` of a single-threaded `Context`.
+    static LOCAL_STATE: RefCell<Option<ReactorState>> = const { RefCell::new(None) };
 }
 
+/// The reactor of a [`Context`](crate::runtime::Context).
+///
+/// The reactor is shared by all the threads of a multi-threaded `Context`.
+/// Only the portions which need exclusive access are kept in a [`ReactorState`],
+/// which is guarded by a `Mutex` for a shared reactor. The `ReactorState` of the
+/// reactor of a single-threaded `Context` lives in a thread local `RefCell`
+/// instead, so the default case doesn't pay for the locking.
+///
+/// Lock order: the `state` of the reactor can be locked before the `state`
+/// of a [`Source`], never the other way around.
 #[derive(Debug)]
 pub(super) struct Reactor {
     /// Portable bindings to epoll/kqueue/event ports/wepoll.
@@ -75,18 +88,29 @@ pub(super) struct Reactor {
     /// fresh "round" of `ReactorLock::react()`.
     ticker: AtomicUsize,
 
+    /// Half max throttling duration in nanoseconds, needed to fire timers.
+    half_max_throttling: AtomicU64,
+
+    /// A queue of timer operations (insert and remove).
+    ///
+    /// When inserting or removing a timer, we don't process it immediately - we just push it into
+    /// this queue. Timers actually get processed when the queue fills up or the reactor is polled.
+    timer_ops: ConcurrentQueue<TimerOp>,
+
+    /// State requiring exclusive access if the reactor is shared between threads.
+    ///
+    /// `None` if the state is in the `LOCAL_STATE` of the reactor's thread.
+    shared_state: Option<Mutex<ReactorState>>,
+}
+
+#[derive(Debug)]
+struct ReactorState {
     /// Time when timers have been checked in current time slice.
     timers_check_instant: Instant,
 
     /// Time limit when timers are being fired in current time slice.
     time_slice_end: Instant,
 
-    /// Half max throttling duration, needed to fire timers.
-    half_max_throttling: Duration,
-
-    /// List of wakers to wake when reacting.
-    wakers: Vec<Waker>,
-
     /// Registered sources.
     sources: Slab<Arc<Source>>,
 
@@ -111,68 +135,134 @@ pub(super) struct Reactor {
     /// timer.
     after_timers: BTreeMap<(Instant, AfterTimerId), Waker>,
 
-    /// A queue of timer operations (insert and remove).
+    /// An ordered map of registered precise timers.
     ///
-    /// When inserting or removing a timer, we don't process it immediately - we just push it into
-    /// this queue. Timers actually get processed when the queue fills up or the reactor is polled.
-    timer_ops: ConcurrentQueue<TimerOp>,
+    /// These timers are not subject to throttling: they are fired as soon as
+    /// their expected time is reached, see [`Reactor::process_precise_timers`].
+    precise_timers: BTreeMap<(Instant, PreciseTimerId), Waker>,
 }
 
-impl Reactor {
-    fn new(max_throttling: Duration) -> Self {
-        Reactor {
-            poller: Poller::new().expect("cannot initialize I/O event notification"),
-            ticker: AtomicUsize::new(0),
+impl ReactorState {
+    fn new() -> Self {
+        ReactorState {
             timers_check_instant: Instant::now(),
             time_slice_end: Instant::now(),
-            half_max_throttling: max_throttling / 2,
-            wakers: Vec::new(),
             sources: Slab::new(),
             events: Events::new(),
             timers: BTreeMap::new(),
             after_timers: BTreeMap::new(),
+            precise_timers: BTreeMap::new(),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.sources.clear();
+        self.events.clear();
+        self.timers.clear();
+        self.after_timers.clear();
+        self.precise_timers.clear();
+    }
+}
+
+impl Reactor {
+    fn new(max_throttling: Duration, shared: bool) -> Self {
+        Reactor {
+            poller: Poller::new().expect("cannot initialize I/O event notification"),
+            ticker: AtomicUsize::new(0),
+            half_max_throttling: AtomicU64::new((max_throttling / 2).as_nanos() as u64),
             timer_ops: ConcurrentQueue::bounded(1000),
+            shared_state: shared.then(|| Mutex::new(ReactorState::new())),
         }
     }
 
     /// Initializes the reactor for current thread.
-    pub fn init(max_throttling: Duration) {
+    ///
+    /// Pass `shared` if the reactor is to be [attached](Self::attach) to other threads.
+    pub fn init(max_throttling: Duration, shared: bool) {
         CURRENT_REACTOR.with(|cur| {
             let mut cur = cur.borrow_mut();
-            if cur.is_none() {
-                *cur = Some(Reactor::new(max_throttling));
+            if cur
+                .as_ref()
+                .is_some_and(|reactor| reactor.shared_state.is_some() == shared)
+            {
+                return;
             }
+
+            LOCAL_STATE.with(|state| {
+                *state.borrow_mut() = (!shared).then(ReactorState::new);
+            });
+            *cur = Some(Arc::new(Reactor::new(max_throttling, shared)));
+        })
+    }
+
+    /// Returns current thread's reactor, if any.
+    ///
+    /// This is used to share the reactor with the other threads of a `Context`.
+    pub fn current() -> Option<Arc<Reactor>> {
+        CURRENT_REACTOR.with(|cur| cur.borrow().clone())
+    }
+
+    /// Attaches current thread to a reactor initialized on another thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the reactor was not initialized as `shared`.
+    pub fn attach(reactor: Arc<Reactor>) {
+        assert!(
+            reactor.shared_state.is_some(),
+            "Attempt to attach a thread local Reactor"
+        );
+
+        CURRENT_REACTOR.with(|cur| {
+            let mut cur = cur.borrow_mut();
+            assert!(cur.is_none(), "A Reactor is already running on this thread");
+            *cur = Some(reactor);
         })
     }
 
     /// Clears the `Reactor`.
     ///
     /// It will be ready for reuse on current thread without reallocating.
+    ///
+    /// If the `Reactor` is shared with other threads, current thread
+    /// is only detached from it.
     pub fn clear() {
         let _ = CURRENT_REACTOR.try_with(|cur_reactor| {
-            cur_reactor.borrow_mut().as_mut().map(|reactor| {
-                reactor.ticker = AtomicUsize::new(0);
-                reactor.wakers.clear();
-                reactor.sources.clear();
-                reactor.events.clear();
-                reactor.timers.clear();
-                reactor.after_timers.clear();
+            let mut cur_reactor = cur_reactor.borrow_mut();
+            if cur_reactor
+                .as_ref()
+                .is_some_and(|reactor| Arc::strong_count(reactor) > 1)
+            {
+                *cur_reactor = None;
+                return;
+            }
+
+            if let Some(reactor) = cur_reactor.as_ref() {
+                reactor.ticker.store(0, Ordering::SeqCst);
                 while !reactor.timer_ops.is_empty() {
                     let _ = reactor.timer_ops.pop();
                 }
-            })
+
+                match reactor.shared_state {
+                    Some(ref state) => state.lock().unwrap().clear(),
+                    None => {
+                        let _ = LOCAL_STATE.try_with(|state| {
+                            if let Some(state) = state.borrow_mut().as_mut() {
+                                state.clear();
+                            }
+                        });
+                    }
+                }
+            }
         });
     }
 
-    /// Executes the function with current thread's reactor as ref.
+    /// Executes the function with current thread's reactor.
     ///
     /// # Panics
     ///
-    /// Panics if:
-    ///
-    /// - The Reactor is not initialized, i.e. if
-    ///   current thread is not a [`Context`] thread.
-    /// - The Reactor is already mutably borrowed.
+    /// Panics if the Reactor is not initialized, i.e. if
+    /// current thread is not a [`Context`] thread.
     ///
     /// Use [`Context::enter`] to register i/o sources
     /// or timers from a different thread.
@@ -189,29 +279,25 @@ impl Reactor {
         })
     }
 
-    /// Executes the function with current thread's reactor as mutable.
+    /// Executes the function with exclusive access to the state of the reactor.
     ///
     /// # Panics
     ///
-    /// Panics if:
-    ///
-    /// - The Reactor is not initialized, i.e. if
-    ///   current thread is not a [`Context`] thread.
-    /// - The Reactor is already mutably borrowed.
-    ///
-    /// Use [`Context::enter`] to register i/o sources
-    /// or timers from a different thread.
-    #[track_caller]
-    pub fn with_mut<F, R>(f: F) -> R
+    /// Panics if the reactor is not shared and current thread is not the one
+    /// which initialized it.
+    fn with_state<F, R>(&self, f: F) -> R
     where
-        F: FnOnce(&mut Reactor) -> R,
+        F: FnOnce(&mut ReactorState) -> R,
     {
-        CURRENT_REACTOR.with(|reactor| {
-            f(reactor
-                .borrow_mut()
-                .as_mut()
-                .expect("Not running in a Context."))
-        })
+        match self.shared_state {
+            Some(ref state) => f(&mut state.lock().unwrap()),
+            None => LOCAL_STATE.with(|state| {
+                f(state
+                    .borrow_mut()
+                    .as_mut()
+                    .expect("Reactor state not initialized on current thread"))
+            }),
+        }
     }
 
     /// Returns the current ticker.
@@ -220,56 +306,64 @@ impl Reactor {
     }
 
     pub fn half_max_throttling(&self) -> Duration {
+        Duration::from_nanos(self.half_max_throttling.load(Ordering::Relaxed))
+    }
+
+    /// Updates the max throttling duration after the `Context` wait changed.
+    pub fn set_max_throttling(&self, max_throttling: Duration) {
         self.half_max_throttling
+            .store((max_throttling / 2).as_nanos() as u64, Ordering::Relaxed);
     }
 
     pub fn timers_check_instant(&self) -> Instant {
-        self.timers_check_instant
+        self.with_state(|state| state.timers_check_instant)
     }
 
     pub fn time_slice_end(&self) -> Instant {
-        self.time_slice_end
+        self.with_state(|state| state.time_slice_end)
     }
 
     /// Registers an I/O source in the reactor.
-    pub fn insert_io(&mut self, raw: Registration) -> io::Result<Arc<Source>> {
-        // Create an I/O source for this file descriptor.
-        let source = {
-            let key = self.sources.vacant_entry().key();
-            let source = Arc::new(Source {
-                registration: raw,
-                key,
-                state: Default::default(),
-            });
-            self.sources.insert(source.clone());
-            source
-        };
-
-        // Register the file descriptor.
-        if let Err(err) = source.registration.add(&self.poller, source.key) {
-            gst::error!(
-                crate::runtime::RUNTIME_CAT,
-                "Failed to register fd {:?}: {}",
-                source.registration,
-                err,
-            );
-            self.sources.remove(source.key);
-            return Err(err);
-        }
+    pub fn insert_io(&self, raw: Registration) -> io::Result<Arc<Source>> {
+        self.with_state(|state| {
+            // Create an I/O source for this file descriptor.
+            let source = {
+                let key = state.sources.vacant_entry().key();
+                let source = Arc::new(Source {
+                    registration: raw,
+                    key,
+                    state: Default::default(),
+                });
+                state.sources.insert(source.clone());
+                source
+            };
 
-        Ok(source)
+            // Register the file descriptor.
+            if let Err(err) = source.registration.add(&self.poller, source.key) {
+                gst::error!(
+                    crate::runtime::RUNTIME_CAT,
+                    "Failed to register fd {:?}: {}",
+                    source.registration,
+                    err,
+                );
+                state.sources.remove(source.key);
+                return Err(err);
+            }
+
+            Ok(source)
+        })
     }
 
     /// Deregisters an I/O source from the reactor.
-    pub fn remove_io(&mut self, source: &Source) -> io::Result<()> {
-        self.sources.remove(source.key);
+    pub fn remove_io(&self, source: &Source) -> io::Result<()> {
+        self.with_state(|state| state.sources.remove(source.key));
         source.registration.delete(&self.poller)
     }
 
     /// Registers a regular timer in the reactor.
     ///
     /// Returns the inserted timer's ID.
-    pub fn insert_regular_timer(&mut self, when: Instant, waker: &Waker) -> RegularTimerId {
+    pub fn insert_regular_timer(&self, when: Instant, waker: &Waker) -> RegularTimerId {
         // Generate a new timer ID.
         static REGULAR_ID_GENERATOR: AtomicUsize = AtomicUsize::new(1);
         let id = RegularTimerId(REGULAR_ID_GENERATOR.fetch_add(1, Ordering::Relaxed));
@@ -282,7 +376,7 @@ impl Reactor {
         {
             // If the queue is full, drain it and try again.
             gst::warning!(RUNTIME_CAT, "react: timer_ops is full");
-            self.process_timer_ops();
+            self.with_state(|state| self.process_timer_ops(state));
         }
 
         id
@@ -291,7 +385,7 @@ impl Reactor {
     /// Registers an after timer in the reactor.
     ///
     /// Returns the inserted timer's ID.
-    pub fn insert_after_timer(&mut self, when: Instant, waker: &Waker) -> AfterTimerId {
+    pub fn insert_after_timer(&self, when: Instant, waker: &Waker) -> AfterTimerId {
         // Generate a new timer ID.
         static AFTER_ID_GENERATOR: AtomicUsize = AtomicUsize::new(1);
         let id = AfterTimerId(AFTER_ID_GENERATOR.fetch_add(1, Ordering::Relaxed));
@@ -304,39 +398,61 @@ impl Reactor {
         {
             // If the queue is full, drain it and try again.
             gst::warning!(RUNTIME_CAT, "react: timer_ops is full");
-            self.process_timer_ops();
+            self.with_state(|state| self.process_timer_ops(state));
+        }
+
+        id
+    }
+
+    /// Registers a precise timer in the reactor.
+    ///
+    /// Returns the inserted timer's ID.
+    pub fn insert_precise_timer(&self, when: Instant, waker: &Waker) -> PreciseTimerId {
+        // Generate a new timer ID.
+        static PRECISE_ID_GENERATOR: AtomicUsize = AtomicUsize::new(1);
+        let id = PreciseTimerId(PRECISE_ID_GENERATOR.fetch_add(1, Ordering::Relaxed));
+
+        // Push an insert operation.
+        while self
+            .timer_ops
+            .push(TimerOp::Insert(when, id.into(), waker.clone()))
+            .is_err()
+        {
+            // If the queue is full, drain it and try again.
+            gst::warning!(RUNTIME_CAT, "react: timer_ops is full");
+            self.with_state(|state| self.process_timer_ops(state));
         }
 
         id
     }
 
     /// Deregisters a timer from the reactor.
-    pub fn remove_timer(&mut self, when: Instant, id: impl Into<TimerId>) {
+    pub fn remove_timer(&self, when: Instant, id: impl Into<TimerId>) {
         // Push a remove operation.
         let id = id.into();
         while self.timer_ops.push(TimerOp::Remove(when, id)).is_err() {
             gst::warning!(RUNTIME_CAT, "react: timer_ops is full");
             // If the queue is full, drain it and try again.
-            self.process_timer_ops();
+            self.with_state(|state| self.process_timer_ops(state));
         }
     }
 
     /// Processes ready timers and extends the list of wakers to wake.
-    fn process_timers(&mut self, now: Instant) {
-        self.process_timer_ops();
+    fn process_timers(&self, state: &mut ReactorState, now: Instant, wakers: &mut Vec<Waker>) {
+        self.process_timer_ops(state);
 
-        self.timers_check_instant = now;
-        self.time_slice_end = now + self.half_max_throttling;
+        state.timers_check_instant = now;
+        state.time_slice_end = now + self.half_max_throttling();
 
         // Split regular timers into ready and pending timers.
         //
         // Careful to split just *after* current time slice end,
         // so that a timer set to fire in current time slice is
         // considered ready.
-        let pending = self
+        let pending = state
             .timers
-            .split_off(&(self.time_slice_end, RegularTimerId::NONE));
-        let ready = mem::replace(&mut self.timers, pending);
+            .split_off(&(state.time_slice_end, RegularTimerId::NONE));
+        let ready = mem::replace(&mut state.timers, pending);
 
         // Add wakers to the list.
         if !ready.is_empty() {
@@ -347,7 +463,7 @@ impl Reactor {
             );
 
             for (_, waker) in ready {
-                self.wakers.push(waker);
+                wakers.push(waker);
             }
         }
 
@@ -355,10 +471,10 @@ impl Reactor {
         //
         // Careful to split just *after* `now`,
         // so that a timer set for exactly `now` is considered ready.
-        let pending = self
+        let pending = state
             .after_timers
-            .split_off(&(self.timers_check_instant, AfterTimerId::NONE));
-        let ready = mem::replace(&mut self.after_timers, pending);
+            .split_off(&(state.timers_check_instant, AfterTimerId::NONE));
+        let ready = mem::replace(&mut state.after_timers, pending);
 
         // Add wakers to the list.
         if !ready.is_empty() {
@@ -369,28 +485,83 @@ impl Reactor {
             );
 
             for (_, waker) in ready {
-                self.wakers.push(waker);
+                wakers.push(waker);
             }
         }
+
+        Self::split_ready_precise_timers(state, now, wakers);
+    }
+
+    /// Moves the wakers of the precise timers which are due at `now` to `wakers`.
+    fn split_ready_precise_timers(state: &mut ReactorState, now: Instant, wakers: &mut Vec<Waker>) {
+        if state.precise_timers.is_empty() {
+            return;
+        }
+
+        // Careful to split just *after* `now`,
+        // so that a timer set for exactly `now` is considered ready.
+        let pending = state.precise_timers.split_off(&(now, PreciseTimerId::NONE));
+        let ready = mem::replace(&mut state.precise_timers, pending);
+
+        if !ready.is_empty() {
+            gst::trace!(
+                RUNTIME_CAT,
+                "process_timers (precise): {} ready wakers",
+                ready.len()
+            );
+
+            for (_, waker) in ready {
+                wakers.push(waker);
+            }
+        }
+    }
+
+    /// Fires the precise timers which are due at `now`, regardless of throttling.
+    ///
+    /// Returns whether timers were fired along with the instant of the next
+    /// pending precise timer if any, so that the caller doesn't park past it.
+    pub fn process_precise_timers(&self, now: Instant) -> (bool, Option<Instant>) {
+        let mut wakers = Vec::new();
+
+        let next = self.with_state(|state| {
+            self.process_timer_ops(state);
+            Self::split_ready_precise_timers(state, now, &mut wakers);
+
+            state.precise_timers.keys().next().map(|(when, _)| *when)
+        });
+
+        let fired = !wakers.is_empty();
+        for waker in wakers.drain(..) {
+            // Don't let a panicking waker blow everything up.
+            panic::catch_unwind(|| waker.wake()).ok();
+        }
+
+        (fired, next)
     }
 
     /// Processes queued timer operations.
-    fn process_timer_ops(&mut self) {
+    fn process_timer_ops(&self, state: &mut ReactorState) {
         // Process only as much as fits into the queue, or else this loop could in theory run
         // forever.
         for _ in 0..self.timer_ops.capacity().unwrap() {
             match self.timer_ops.pop() {
                 Ok(TimerOp::Insert(when, TimerId::Regular(id), waker)) => {
-                    self.timers.insert((when, id), waker);
+                    state.timers.insert((when, id), waker);
                 }
                 Ok(TimerOp::Insert(when, TimerId::After(id), waker)) => {
-                    self.after_timers.insert((when, id), waker);
+                    state.after_timers.insert((when, id), waker);
                 }
                 Ok(TimerOp::Remove(when, TimerId::Regular(id))) => {
-                    self.timers.remove(&(when, id));
+                    state.timers.remove(&(when, id));
                 }
                 Ok(TimerOp::Remove(when, TimerId::After(id))) => {
-                    self.after_timers.remove(&(when, id));
+                    state.after_timers.remove(&(when, id));
+                }
+                Ok(TimerOp::Insert(when, TimerId::Precise(id), waker)) => {
+                    state.precise_timers.insert((when, id), waker);
+                }
+                Ok(TimerOp::Remove(when, TimerId::Precise(id))) => {
+                    state.precise_timers.remove(&(when, id));
                 }
                 Err(_) => break,
             }
@@ -398,69 +569,93 @@ impl Reactor {
     }
 
     /// Processes new events.
-    pub fn react(&mut self, now: Instant) -> io::Result<()> {
-        debug_assert!(self.wakers.is_empty());
-
-        // Process ready timers.
-        self.process_timers(now);
-
-        // Bump the ticker before polling I/O.
-        let tick = self.ticker.fetch_add(1, Ordering::SeqCst).wrapping_add(1);
-
-        self.events.clear();
+    ///
+    /// Returns the instant at which the timers were last checked. When the
+    /// reactor is shared and `min_interval` has not elapsed since another
+    /// thread reacted, this is a no-op.
+    pub fn react(&self, now: Instant, min_interval: Duration) -> io::Result<Instant> {
+        let mut wakers = Vec::new();
+
+        let res = self.with_state(|state| {
+            if !min_interval.is_zero()
+                && now.saturating_duration_since(state.timers_check_instant) < min_interval
+            {
+                return Ok(state.timers_check_instant);
+            }
 
-        // Block on I/O events.
-        let res = match self.poller.wait(&mut self.events, Some(Duration::ZERO)) {
-            // No I/O events occurred.
-            Ok(0) => Ok(()),
-            // At least one I/O event occurred.
-            Ok(_) => {
-                for ev in self.events.iter() {
-                    // Check if there is a source in the table with this key.
-                    if let Some(source) = self.sources.get(ev.key) {
-                        let mut state = source.state.lock().unwrap();
-
-                        // Collect wakers if a writability event was emitted.
-                        for &(dir, emitted) in &[(WRITE, ev.writable), (READ, ev.readable)] {
-                            if emitted {
-                                state[dir].tick = tick;
-                                state[dir].drain_into(&mut self.wakers);
+            // Process ready timers.
+            self.process_timers(state, now, &mut wakers);
+
+            // Bump the ticker before polling I/O.
+            let tick = self.ticker.fetch_add(1, Ordering::SeqCst).wrapping_add(1);
+
+            let ReactorState {
+                ref mut events,
+                ref sources,
+                ..
+            } = *state;
+
+            events.clear();
+
+            // Block on I/O events.
+            let res = match self.poller.wait(events, Some(Duration::ZERO)) {
+                // No I/O events occurred.
+                Ok(0) => Ok(()),
+                // At least one I/O event occurred.
+                Ok(_) => {
+                    let mut res = Ok(());
+                    for ev in events.iter() {
+                        // Check if there is a source in the table with this key.
+                        if let Some(source) = sources.get(ev.key) {
+                            let mut state = source.state.lock().unwrap();
+
+                            // Collect wakers if a writability event was emitted.
+                            for &(dir, emitted) in &[(WRITE, ev.writable), (READ, ev.readable)] {
+                                if emitted {
+                                    state[dir].tick = tick;
+                                    state[dir].drain_into(&mut wakers);
+                                }
                             }
-                        }
 
-                        // Re-register if there are still writers or readers. The can happen if
-                        // e.g. we were previously interested in both readability and writability,
-                        // but only one of them was emitted.
-                        if !state[READ].is_empty() || !state[WRITE].is_empty() {
-                            // Create the event that we are interested in.
-                            let event = {
-                                let mut event = Event::none(source.key);
-                                event.readable = !state[READ].is_empty();
-                                event.writable = !state[WRITE].is_empty();
-                                event
-                            };
-
-                            // Register interest in this event.
-                            source.registration.modify(&self.poller, event)?;
+                            // Re-register if there are still writers or readers. The can happen if
+                            // e.g. we were previously interested in both readability and writability,
+                            // but only one of them was emitted.
+                            if !state[READ].is_empty() || !state[WRITE].is_empty() {
+                                // Create the event that we are interested in.
+                                let event = {
+                                    let mut event = Event::none(source.key);
+                                    event.readable = !state[READ].is_empty();
+                                    event.writable = !state[WRITE].is_empty();
+                                    event
+                                };
+
+                                // Register interest in this event.
+                                if let Err(err) = source.registration.modify(&self.poller, event) {
+                                    res = Err(err);
+                                    break;
+                                }
+                            }
                         }
                     }
+
+                    res
                 }
 
-                Ok(())
-            }
+                // The syscall was interrupted.
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => Ok(()),
 
-            // The syscall was interrupted.
-            Err(err) if err.kind() == io::ErrorKind::Interrupted => Ok(()),
+                // An actual error occureed.
+                Err(err) => Err(err),
+            };
 
-            // An actual error occureed.
-            Err(err) => Err(err),
-        };
+            res.map(|_| now)
+        });
 
         // Wake up ready tasks.
-        if !self.wakers.is_empty() {
-            gst::trace!(RUNTIME_CAT, "react: {} ready wakers", self.wakers.len());
+        if !wakers.is_empty() {
+            gst::trace!(RUNTIME_CAT, "react: {} ready wakers", wakers.len());
 
-            for waker in self.wakers.drain(..) {
+            for waker in wakers.drain(..) {
                 // Don't let a panicking waker blow everything up.
                 panic::catch_unwind(|| waker.wake()).ok();
             }
@@ -485,11 +680,19 @@ impl AfterTimerId {
     const NONE: AfterTimerId = AfterTimerId(0);
 }
 
+/// Timer fires as soon as the expected time is reached, regardless of throttling.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PreciseTimerId(usize);
+impl PreciseTimerId {
+    const NONE: PreciseTimerId = PreciseTimerId(0);
+}
+
 /// Any Timer Ids.
 #[derive(Copy, Clone, Debug)]
 pub(crate) enum TimerId {
     Regular(RegularTimerId),
     After(AfterTimerId),
+    Precise(PreciseTimerId),
 }
 
 impl From<RegularTimerId> for TimerId {
@@ -504,6 +707,12 @@ impl From<AfterTimerId> for TimerId {
     }
 }
 
+impl From<PreciseTimerId> for TimerId {
+    fn from(id: PreciseTimerId) -> Self {
+        TimerId::Precise(id)
+    }
+}
+
 /// A single timer operation.
 enum TimerOp {
     Insert(Instant, TimerId, Waker),
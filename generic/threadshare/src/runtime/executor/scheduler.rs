@@ -3,17 +3,16 @@
 //
 // Take a look at the license at the top of the repository in the LICENSE file.
 
-use futures::future::poll_fn;
+use futures::future::{poll_fn, AbortHandle};
 use futures::pin_mut;
 
 use gio::glib::clone::Downgrade;
 
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::future::Future;
 use std::panic;
-#[cfg(feature = "tuning")]
-use std::sync::atomic::AtomicU64;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc as sync_mpsc;
 use std::sync::{Arc, Condvar, Mutex, Weak};
 use std::task::Poll;
@@ -22,8 +21,9 @@ use std::time::{Duration, Instant};
 
 use waker_fn::waker_fn;
 
-use super::task::{SubTaskOutput, TaskId, TaskQueue};
-use super::{CallOnDrop, JoinHandle, Reactor};
+use super::stats::StatsCounters;
+use super::task::{SubTaskOutput, TaskId, TaskInfo, TaskQueue};
+use super::{CallOnDrop, ContextConfig, ContextStats, JoinHandle, Reactor};
 use crate::runtime::RUNTIME_CAT;
 
 thread_local! {
@@ -33,10 +33,21 @@ thread_local! {
 #[derive(Debug)]
 pub(super) struct Scheduler {
     context_name: Arc<str>,
-    max_throttling: Duration,
+    /// Current max throttling in nanoseconds.
+    max_throttling: AtomicU64,
+    /// Wait durations requested by the users of the `Context` with their count.
+    wait_requests: Mutex<BTreeMap<Duration, usize>>,
+    n_threads: usize,
+    thread_ids: Mutex<Vec<thread::ThreadId>>,
     tasks: TaskQueue,
     must_unpark: Mutex<bool>,
     must_unpark_cvar: Condvar,
+    /// Set when the `Scheduler` is shutting down: new tasks are rejected.
+    closing: AtomicBool,
+    /// Outcome of the shutdown, set once it completed.
+    shutdown_res: Mutex<Option<bool>>,
+    shutdown_res_cvar: Condvar,
+    stats: StatsCounters,
     #[cfg(feature = "tuning")]
     parked_duration: AtomicU64,
 }
@@ -46,25 +57,88 @@ impl Scheduler {
     const MAX_SUCCESSIVE_TASKS: usize = 64;
 
     pub fn start(context_name: &str, max_throttling: Duration) -> Handle {
-        // Name the thread so that it appears in panic messages.
-        let thread = thread::Builder::new().name(context_name.to_string());
+        Self::start_multi(context_name, max_throttling, 1, ContextConfig::default())
+    }
+
+    /// Starts a `Scheduler` running on `n_threads` threads.
+    ///
+    /// All the threads pop runnables from the same task queue and share
+    /// the same [`Reactor`]. `config` is applied to each thread before
+    /// it starts executing tasks.
+    pub fn start_multi(
+        context_name: &str,
+        max_throttling: Duration,
+        n_threads: usize,
+        config: ContextConfig,
+    ) -> Handle {
+        let n_threads = n_threads.max(1);
+        let context_name: Arc<str> = Arc::from(context_name);
+        let config = Arc::new(config);
 
         let (handle_sender, handle_receiver) = sync_mpsc::channel();
-        let context_name = Arc::from(context_name);
         let thread_ctx_name = Arc::clone(&context_name);
-        let join = thread
+        let join = Self::spawn_thread(&context_name, 0, n_threads, &config, move || {
+            let handle = Scheduler::init(thread_ctx_name, max_throttling, n_threads);
+            let this = Arc::clone(&handle.0.scheduler);
+            let must_shutdown = handle.0.must_shutdown.clone();
+            let handle_weak = handle.downgrade();
+            let reactor = Reactor::current().expect("Reactor initialized above");
+            handle_sender.send((handle, reactor)).unwrap();
+
+            (this, must_shutdown, handle_weak)
+        });
+
+        let (handle, reactor) = handle_receiver.recv().expect("Context thread init failed");
+        handle.add_join_handle(join);
+
+        for idx in 1..n_threads {
+            let this = Arc::clone(&handle.0.scheduler);
+            let must_shutdown = handle.0.must_shutdown.clone();
+            let handle_weak = handle.downgrade();
+            let reactor = Arc::clone(&reactor);
+            let join = Self::spawn_thread(&context_name, idx, n_threads, &config, move || {
+                Scheduler::attach(handle_weak.clone(), reactor);
+
+                (this, must_shutdown, handle_weak)
+            });
+            handle.add_join_handle(join);
+        }
+
+        handle
+    }
+
+    fn spawn_thread<F>(
+        context_name: &Arc<str>,
+        idx: usize,
+        n_threads: usize,
+        config: &Arc<ContextConfig>,
+        init: F,
+    ) -> thread::JoinHandle<()>
+    where
+        F: FnOnce() -> (Arc<Scheduler>, Arc<AtomicBool>, HandleWeak) + Send + 'static,
+    {
+        // Name the thread so that it appears in panic messages.
+        let thread = if n_threads > 1 {
+            thread::Builder::new().name(format!("{context_name}-{idx}"))
+        } else {
+            thread::Builder::new().name(context_name.to_string())
+        };
+
+        let thread_ctx_name = Arc::clone(context_name);
+        let config = Arc::clone(config);
+        thread
             .spawn(move || {
                 gst::debug!(
                     RUNTIME_CAT,
-                    "Started Scheduler thread for Context {}",
+                    "Started Scheduler thread {} for Context {}",
+                    idx,
                     thread_ctx_name
                 );
 
-                let handle = Scheduler::init(Arc::clone(&thread_ctx_name), max_throttling);
-                let this = Arc::clone(&handle.0.scheduler);
-                let must_shutdown = handle.0.must_shutdown.clone();
-                let handle_weak = handle.downgrade();
-                handle_sender.send(handle).unwrap();
+                config.apply(&thread_ctx_name);
+
+                let (this, must_shutdown, handle_weak) = init();
+                this.thread_ids.lock().unwrap().push(thread::current().id());
 
                 let shutdown_fut = poll_fn(move |_| {
                     if must_shutdown.load(Ordering::SeqCst) {
@@ -79,14 +153,16 @@ impl Scheduler {
                     Ok(_) => {
                         gst::debug!(
                             RUNTIME_CAT,
-                            "Scheduler thread shut down for Context {}",
+                            "Scheduler thread {} shut down for Context {}",
+                            idx,
                             thread_ctx_name
                         );
                     }
                     Err(e) => {
                         gst::error!(
                             RUNTIME_CAT,
-                            "Scheduler thread shut down due to an error within Context {}",
+                            "Scheduler thread {} shut down due to an error within Context {}",
+                            idx,
                             thread_ctx_name
                         );
 
@@ -98,15 +174,10 @@ impl Scheduler {
                     }
                 }
             })
-            .expect("Failed to spawn Scheduler thread");
-
-        let handle = handle_receiver.recv().expect("Context thread init failed");
-        handle.set_join_handle(join);
-
-        handle
+            .expect("Failed to spawn Scheduler thread")
     }
 
-    fn init(context_name: Arc<str>, max_throttling: Duration) -> Handle {
+    fn init(context_name: Arc<str>, max_throttling: Duration, n_threads: usize) -> Handle {
         let handle = CURRENT_SCHEDULER.with(|cur_scheduler| {
             let mut cur_scheduler = cur_scheduler.borrow_mut();
             if cur_scheduler.is_some() {
@@ -115,10 +186,17 @@ impl Scheduler {
 
             let handle = Handle::new(Arc::new(Scheduler {
                 context_name: context_name.clone(),
-                max_throttling,
+                max_throttling: AtomicU64::new(max_throttling.as_nanos() as u64),
+                wait_requests: Mutex::new(BTreeMap::new()),
+                n_threads,
+                thread_ids: Mutex::new(Vec::new()),
                 tasks: TaskQueue::new(context_name),
                 must_unpark: Mutex::new(false),
                 must_unpark_cvar: Condvar::new(),
+                closing: AtomicBool::new(false),
+                shutdown_res: Mutex::new(None),
+                shutdown_res_cvar: Condvar::new(),
+                stats: StatsCounters::default(),
                 #[cfg(feature = "tuning")]
                 parked_duration: AtomicU64::new(0),
             }));
@@ -128,11 +206,27 @@ impl Scheduler {
             handle
         });
 
-        Reactor::init(handle.max_throttling());
+        Reactor::init(handle.max_throttling(), n_threads > 1);
 
         handle
     }
 
+    /// Attaches current thread to a `Scheduler` initialized on another thread.
+    fn attach(handle_weak: HandleWeak, reactor: Arc<Reactor>) {
+        CURRENT_SCHEDULER.with(|cur_scheduler| {
+            let mut cur_scheduler = cur_scheduler.borrow_mut();
+            if cur_scheduler.is_some() {
+                panic!(
+                    "Attempt to attach a Scheduler on thread where another Scheduler is running."
+                );
+            }
+
+            *cur_scheduler = Some(handle_weak);
+        });
+
+        Reactor::attach(reactor);
+    }
+
     pub fn block_on<F>(future: F) -> F::Output
     where
         F: Future + Send + 'static,
@@ -143,7 +237,7 @@ impl Scheduler {
             "Attempt to block within an existing Scheduler thread."
         );
 
-        let handle = Scheduler::init(Scheduler::DUMMY_NAME.into(), Duration::ZERO);
+        let handle = Scheduler::init(Scheduler::DUMMY_NAME.into(), Duration::ZERO, 1);
         let this = Arc::clone(&handle.0.scheduler);
 
         // Move the (only) handle for this scheduler in the main task.
@@ -200,22 +294,52 @@ impl Scheduler {
         let _guard = CallOnDrop::new(|| Scheduler::close(Arc::clone(&self.context_name)));
 
         let mut now;
+        let mut max_throttling = self.max_throttling();
         // This is to ensure reactor invocation on the first iteration.
-        let mut last_react = Instant::now().checked_sub(self.max_throttling).unwrap();
+        let mut last_react = Instant::now().checked_sub(max_throttling).unwrap();
         let mut tasks_checked;
         'main: loop {
+            // The max throttling changes when users with different waits
+            // join or leave the `Context`.
+            let cur_max_throttling = self.max_throttling();
+            if cur_max_throttling != max_throttling {
+                max_throttling = cur_max_throttling;
+                Reactor::with(|reactor| reactor.set_max_throttling(max_throttling));
+            }
+
             // Only check I/O and timers every `max_throttling`.
             now = Instant::now();
-            if now - last_react >= self.max_throttling {
-                last_react = now;
-                Reactor::with_mut(|reactor| reactor.react(now).ok());
+            if now - last_react >= max_throttling {
+                // Another thread of the same `Scheduler` might have reacted
+                // in the meantime, in which case we will only park until next
+                // time slice.
+                let min_interval = if self.n_threads > 1 {
+                    max_throttling
+                } else {
+                    Duration::ZERO
+                };
+                last_react =
+                    Reactor::with(|reactor| reactor.react(now, min_interval)).unwrap_or(now);
+
+                if last_react == now {
+                    let reacted = Instant::now();
+                    self.stats.add_reactor_run(reacted - now);
+                    self.stats.maybe_log(&self.context_name, reacted);
+                }
             }
 
             if let Poll::Ready(t) = termination_future.as_mut().poll(cx) {
+                if self.closing.load(Ordering::SeqCst) {
+                    self.drop_pending_runnables();
+                }
+
                 return Ok(t);
             }
 
+            super::context::dump_if_requested();
+
             tasks_checked = 0;
+            let exec_start = Instant::now();
             while tasks_checked < Self::MAX_SUCCESSIVE_TASKS {
                 if let Ok(runnable) = self.tasks.pop_runnable() {
                     panic::catch_unwind(|| runnable.run()).inspect_err(|_err| {
@@ -228,6 +352,18 @@ impl Scheduler {
 
                     tasks_checked += 1;
                 } else {
+                    self.stats
+                        .add_task_polls(tasks_checked, exec_start.elapsed());
+
+                    // Precise timers are not subject to throttling.
+                    // Note: this must be called before locking `must_unpark`
+                    // since waking a task could require unparking.
+                    let (fired, next_precise) =
+                        Reactor::with(|reactor| reactor.process_precise_timers(Instant::now()));
+                    if fired {
+                        continue 'main;
+                    }
+
                     let mut must_unpark = self.must_unpark.lock().unwrap();
                     loop {
                         if *must_unpark {
@@ -235,9 +371,17 @@ impl Scheduler {
                             continue 'main;
                         }
 
-                        if let Some(parking_duration) =
-                            self.max_throttling.checked_sub(last_react.elapsed())
-                        {
+                        let mut parking_duration = max_throttling.checked_sub(last_react.elapsed());
+                        if let Some(next_precise) = next_precise {
+                            parking_duration = parking_duration.and_then(|parking_duration| {
+                                next_precise
+                                    .checked_duration_since(Instant::now())
+                                    .filter(|until_precise| !until_precise.is_zero())
+                                    .map(|until_precise| parking_duration.min(until_precise))
+                            });
+                        }
+
+                        if let Some(parking_duration) = parking_duration {
                             #[cfg(feature = "tuning")]
                             self.parked_duration.fetch_add(
                                 parking_duration.subsec_nanos() as u64,
@@ -248,6 +392,7 @@ impl Scheduler {
                                 .must_unpark_cvar
                                 .wait_timeout(must_unpark, parking_duration)
                                 .unwrap();
+                            self.stats.add_wakeup();
 
                             must_unpark = result.0;
                         } else {
@@ -257,7 +402,77 @@ impl Scheduler {
                     }
                 }
             }
+
+            self.stats
+                .add_task_polls(tasks_checked, exec_start.elapsed());
+        }
+    }
+
+    fn max_throttling(&self) -> Duration {
+        Duration::from_nanos(self.max_throttling.load(Ordering::Relaxed))
+    }
+
+    /// Drops the tasks which were scheduled but won't be executed.
+    ///
+    /// This must be called from a `Scheduler` thread since dropping a task
+    /// might require accessing the [`Reactor`], e.g. to remove a timer.
+    fn drop_pending_runnables(&self) {
+        while let Ok(runnable) = self.tasks.pop_runnable() {
+            if panic::catch_unwind(panic::AssertUnwindSafe(|| drop(runnable))).is_err() {
+                gst::warning!(
+                    RUNTIME_CAT,
+                    "A task panicked while being dropped within Context {}",
+                    self.context_name
+                );
+            }
+        }
+    }
+
+    /// Registers a user requesting `wait` and returns the resulting max throttling.
+    fn add_wait_request(&self, wait: Duration) -> Duration {
+        let mut wait_requests = self.wait_requests.lock().unwrap();
+        *wait_requests.entry(wait).or_default() += 1;
+
+        self.update_max_throttling(&wait_requests)
+    }
+
+    /// Unregisters a user requesting `wait` and returns the resulting max throttling.
+    fn remove_wait_request(&self, wait: Duration) -> Duration {
+        let mut wait_requests = self.wait_requests.lock().unwrap();
+        if let Some(count) = wait_requests.get_mut(&wait) {
+            *count -= 1;
+            if *count == 0 {
+                wait_requests.remove(&wait);
+            }
+        }
+
+        self.update_max_throttling(&wait_requests)
+    }
+
+    /// Adopts the smallest wait requested by current users.
+    fn update_max_throttling(&self, wait_requests: &BTreeMap<Duration, usize>) -> Duration {
+        let Some(&wait) = wait_requests.keys().next() else {
+            // Keep current value if no users are left.
+            return self.max_throttling();
+        };
+
+        let prev = self
+            .max_throttling
+            .swap(wait.as_nanos() as u64, Ordering::Relaxed);
+        if prev != wait.as_nanos() as u64 {
+            gst::debug!(
+                RUNTIME_CAT,
+                "Context {} throttling {:?} -> {:?}",
+                self.context_name,
+                Duration::from_nanos(prev),
+                wait,
+            );
+
+            // Make sure the new value is taken into account immediately
+            self.unpark();
         }
+
+        wait
     }
 
     fn unpark(&self) {
@@ -308,7 +523,7 @@ impl Scheduler {
 struct HandleInner {
     scheduler: Arc<Scheduler>,
     must_shutdown: Arc<AtomicBool>,
-    join: Mutex<Option<thread::JoinHandle<()>>>,
+    join: Mutex<Vec<thread::JoinHandle<()>>>,
 }
 
 impl HandleInner {
@@ -319,10 +534,8 @@ impl HandleInner {
             join: Default::default(),
         }
     }
-}
 
-impl Drop for HandleInner {
-    fn drop(&mut self) {
+    fn shutdown_threads(&self) {
         if !self.must_shutdown.fetch_or(true, Ordering::SeqCst) {
             // Was not already shutting down.
             self.scheduler.unpark();
@@ -335,7 +548,8 @@ impl Drop for HandleInner {
 
             // Don't block shutting down itself
             if !self.scheduler.is_current() {
-                if let Some(join_handler) = self.join.lock().unwrap().take() {
+                let join_handlers = std::mem::take(&mut *self.join.lock().unwrap());
+                for join_handler in join_handlers {
                     gst::trace!(
                         RUNTIME_CAT,
                         "Waiting for Scheduler thread to shutdown for Context {}",
@@ -349,6 +563,12 @@ impl Drop for HandleInner {
     }
 }
 
+impl Drop for HandleInner {
+    fn drop(&mut self) {
+        self.shutdown_threads();
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(super) struct HandleWeak(Weak<HandleInner>);
 
@@ -366,13 +586,13 @@ impl Handle {
         Handle(Arc::new(HandleInner::new(scheduler)))
     }
 
-    fn set_join_handle(&self, join: thread::JoinHandle<()>) {
-        *self.0.join.lock().unwrap() = Some(join);
+    fn add_join_handle(&self, join: thread::JoinHandle<()>) {
+        self.0.join.lock().unwrap().push(join);
     }
 
     fn self_shutdown(self) {
         self.0.must_shutdown.store(true, Ordering::SeqCst);
-        *self.0.join.lock().unwrap() = None;
+        self.0.join.lock().unwrap().clear();
     }
 
     pub fn context_name(&self) -> &str {
@@ -380,7 +600,39 @@ impl Handle {
     }
 
     pub fn max_throttling(&self) -> Duration {
-        self.0.scheduler.max_throttling
+        self.0.scheduler.max_throttling()
+    }
+
+    pub fn add_wait_request(&self, wait: Duration) -> Duration {
+        self.0.scheduler.add_wait_request(wait)
+    }
+
+    pub fn remove_wait_request(&self, wait: Duration) -> Duration {
+        self.0.scheduler.remove_wait_request(wait)
+    }
+
+    pub fn has_wait_requests(&self) -> bool {
+        !self.0.scheduler.wait_requests.lock().unwrap().is_empty()
+    }
+
+    pub fn n_threads(&self) -> usize {
+        self.0.scheduler.n_threads
+    }
+
+    pub fn thread_ids(&self) -> Vec<thread::ThreadId> {
+        self.0.scheduler.thread_ids.lock().unwrap().clone()
+    }
+
+    pub fn task_infos(&self) -> Vec<TaskInfo> {
+        self.0.scheduler.tasks.task_infos()
+    }
+
+    pub fn set_task_name(&self, task_id: TaskId, name: Arc<str>) {
+        self.0.scheduler.tasks.set_task_name(task_id, name);
+    }
+
+    pub fn stats(&self) -> ContextStats {
+        self.0.scheduler.stats.snapshot()
     }
 
     #[cfg(feature = "tuning")]
@@ -418,7 +670,7 @@ impl Handle {
         F::Output: Send + 'static,
     {
         let (task_id, task) = self.0.scheduler.tasks.add(future);
-        JoinHandle::new(task_id, task, self)
+        self.join_handle(task_id, task)
     }
 
     pub fn spawn_and_unpark<F>(&self, future: F) -> JoinHandle<F::Output>
@@ -428,20 +680,143 @@ impl Handle {
     {
         let (task_id, task) = self.0.scheduler.tasks.add(future);
         self.0.scheduler.unpark();
+        self.join_handle(task_id, task)
+    }
+
+    fn join_handle<T>(&self, task_id: TaskId, task: async_task::Task<T>) -> JoinHandle<T> {
+        if self.0.scheduler.closing.load(Ordering::SeqCst) {
+            gst::warning!(
+                RUNTIME_CAT,
+                "Context {} is shutting down, cancelling new {:?}",
+                self.0.scheduler.context_name,
+                task_id,
+            );
+
+            // Dropping the `async_task::Task` cancels the task.
+            drop(task);
+            return JoinHandle::cancelled(task_id, self);
+        }
+
         JoinHandle::new(task_id, task, self)
     }
 
+    /// Shuts the `Scheduler` down after draining the pending sub tasks.
+    ///
+    /// New tasks are rejected from now on. The sub tasks which are still
+    /// pending are executed, then the `Scheduler` threads are stopped, even
+    /// if some sub tasks are still running after `timeout`.
+    ///
+    /// Returns `true` if all the pending sub tasks completed.
+    ///
+    /// If the `Scheduler` is already being shut down by another caller,
+    /// this waits for that shutdown to complete and returns its outcome.
+    ///
+    /// # Panic
+    ///
+    /// This will block current thread and would panic if run
+    /// from the [`Scheduler`].
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        assert!(!self.0.scheduler.is_current());
+
+        let scheduler = &self.0.scheduler;
+        if scheduler.closing.swap(true, Ordering::SeqCst) {
+            gst::debug!(
+                RUNTIME_CAT,
+                "Context {} already shutting down, waiting for completion",
+                scheduler.context_name
+            );
+
+            let shutdown_res = scheduler
+                .shutdown_res_cvar
+                .wait_while(scheduler.shutdown_res.lock().unwrap(), |res| res.is_none())
+                .unwrap();
+
+            return shutdown_res.unwrap();
+        }
+
+        let deadline = Instant::now() + timeout;
+
+        // Pending sub tasks are usually drained by their owner task, which
+        // might never happen now, so drain them from dedicated tasks.
+        let (done_tx, done_rx) = sync_mpsc::channel();
+        let mut n_drainers = 0;
+        for (task_id, sub_tasks) in scheduler.tasks.take_sub_tasks() {
+            gst::debug!(
+                RUNTIME_CAT,
+                "Draining {} sub tasks from {:?} before shutting down Context {}",
+                sub_tasks.len(),
+                task_id,
+                scheduler.context_name,
+            );
+
+            let done_tx = done_tx.clone();
+            // Bypassing `spawn` which rejects new tasks at this point.
+            let (_, task) = scheduler.tasks.add(async move {
+                for sub_task in sub_tasks {
+                    if sub_task.await.is_err() {
+                        break;
+                    }
+                }
+
+                // In case the sub tasks added other sub tasks.
+                let _ = super::Context::drain_sub_tasks().await;
+                let _ = done_tx.send(());
+            });
+            task.detach();
+
+            n_drainers += 1;
+        }
+        drop(done_tx);
+        scheduler.unpark();
+
+        let mut drained = true;
+        for _ in 0..n_drainers {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if done_rx.recv_timeout(timeout).is_err() {
+                gst::warning!(
+                    RUNTIME_CAT,
+                    "Timeout draining sub tasks of Context {}",
+                    scheduler.context_name
+                );
+                drained = false;
+                break;
+            }
+        }
+
+        self.0.shutdown_threads();
+
+        *scheduler.shutdown_res.lock().unwrap() = Some(drained);
+        scheduler.shutdown_res_cvar.notify_all();
+
+        drained
+    }
+
     pub(super) fn unpark(&self) {
         self.0.scheduler.unpark();
     }
 
-    pub fn add_sub_task<T>(&self, task_id: TaskId, sub_task: T) -> Result<(), T>
+    pub fn add_sub_task<T>(&self, task_id: TaskId, sub_task: T) -> Result<AbortHandle, T>
     where
         T: Future<Output = SubTaskOutput> + Send + 'static,
     {
         self.0.scheduler.tasks.add_sub_task(task_id, sub_task)
     }
 
+    pub fn abort_sub_tasks(&self, task_id: TaskId) {
+        self.0.scheduler.tasks.abort_sub_tasks(task_id);
+    }
+
+    pub fn n_added_sub_tasks(&self, task_id: TaskId) -> usize {
+        self.0.scheduler.tasks.n_added_sub_tasks(task_id)
+    }
+
+    pub fn added_sub_task_abort_handles(&self, task_id: TaskId, skip: usize) -> Vec<AbortHandle> {
+        self.0
+            .scheduler
+            .tasks
+            .added_sub_task_abort_handles(task_id, skip)
+    }
+
     pub fn downgrade(&self) -> HandleWeak {
         HandleWeak(self.0.downgrade())
     }
@@ -472,8 +847,11 @@ mod tests {
         let (shutdown_sender, shutdown_receiver) = oneshot::channel();
 
         std::thread::spawn(move || {
-            let handle =
-                Scheduler::init("block_on_task_join_handle".into(), Duration::from_millis(2));
+            let handle = Scheduler::init(
+                "block_on_task_join_handle".into(),
+                Duration::from_millis(2),
+                1,
+            );
             let join_handle = handle.spawn(async {
                 timer::delay_for(Duration::from_millis(5)).await;
                 42
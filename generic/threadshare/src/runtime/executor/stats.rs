@@ -0,0 +1,216 @@
+// Take a look at the license at the top of the repository in the LICENSE file.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::runtime::RUNTIME_CAT;
+
+/// Environment variable used to enable periodic logs of the `Context` stats.
+///
+/// The value is the logging period in milliseconds, e.g. `5000`. The stats are
+/// logged at the `debug` level in the `ts-runtime` category.
+pub const STATS_ENV_VAR: &str = "GST_THREADSHARE_CONTEXT_STATS_INTERVAL";
+
+static STATS_LOG_INTERVAL: LazyLock<Option<Duration>> = LazyLock::new(|| {
+    let value = std::env::var(STATS_ENV_VAR).ok()?;
+    match value.trim().parse::<u64>() {
+        Ok(ms) if ms > 0 => Some(Duration::from_millis(ms)),
+        _ => {
+            gst::warning!(
+                RUNTIME_CAT,
+                "Ignoring invalid {} '{}'",
+                STATS_ENV_VAR,
+                value
+            );
+            None
+        }
+    }
+});
+
+/// Utilization statistics of a [`Context`](super::Context).
+///
+/// Values are cumulated since the `Context` was started. Use [`Self::delta`]
+/// to compute the statistics over a period of time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContextStats {
+    /// Duration covered by these statistics.
+    pub elapsed: Duration,
+    /// Number of times the `Context` threads woke up after parking.
+    pub wakeups: u64,
+    /// Number of times the reactor was checked for I/O & timers,
+    /// i.e. the number of throttling periods.
+    pub reactor_runs: u64,
+    /// Time spent polling I/O & timers in the reactor.
+    pub polling_duration: Duration,
+    /// Number of task polls.
+    pub task_polls: u64,
+    /// Time spent executing tasks.
+    pub executing_duration: Duration,
+}
+
+impl ContextStats {
+    /// Returns the statistics over the period since `earlier` was retrieved.
+    pub fn delta(&self, earlier: &ContextStats) -> ContextStats {
+        ContextStats {
+            elapsed: self.elapsed.saturating_sub(earlier.elapsed),
+            wakeups: self.wakeups.saturating_sub(earlier.wakeups),
+            reactor_runs: self.reactor_runs.saturating_sub(earlier.reactor_runs),
+            polling_duration: self
+                .polling_duration
+                .saturating_sub(earlier.polling_duration),
+            task_polls: self.task_polls.saturating_sub(earlier.task_polls),
+            executing_duration: self
+                .executing_duration
+                .saturating_sub(earlier.executing_duration),
+        }
+    }
+
+    pub fn wakeups_per_sec(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+
+        self.wakeups as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Average number of task polls per throttling period.
+    pub fn task_polls_per_period(&self) -> f64 {
+        if self.reactor_runs == 0 {
+            return 0.0;
+        }
+
+        self.task_polls as f64 / self.reactor_runs as f64
+    }
+}
+
+/// Counters updated by the `Scheduler` threads.
+///
+/// Only relaxed atomic operations are used on the hot path.
+#[derive(Debug)]
+pub(super) struct StatsCounters {
+    start: Instant,
+    wakeups: AtomicU64,
+    reactor_runs: AtomicU64,
+    polling_nanos: AtomicU64,
+    task_polls: AtomicU64,
+    executing_nanos: AtomicU64,
+    last_log: Mutex<(Instant, ContextStats)>,
+}
+
+impl Default for StatsCounters {
+    fn default() -> Self {
+        let start = Instant::now();
+        StatsCounters {
+            start,
+            wakeups: AtomicU64::new(0),
+            reactor_runs: AtomicU64::new(0),
+            polling_nanos: AtomicU64::new(0),
+            task_polls: AtomicU64::new(0),
+            executing_nanos: AtomicU64::new(0),
+            last_log: Mutex::new((start, ContextStats::default())),
+        }
+    }
+}
+
+impl StatsCounters {
+    pub fn add_wakeup(&self) {
+        self.wakeups.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_reactor_run(&self, duration: Duration) {
+        self.reactor_runs.fetch_add(1, Ordering::Relaxed);
+        self.polling_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_task_polls(&self, task_polls: usize, duration: Duration) {
+        if task_polls == 0 {
+            return;
+        }
+
+        self.task_polls
+            .fetch_add(task_polls as u64, Ordering::Relaxed);
+        self.executing_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ContextStats {
+        ContextStats {
+            elapsed: self.start.elapsed(),
+            wakeups: self.wakeups.load(Ordering::Relaxed),
+            reactor_runs: self.reactor_runs.load(Ordering::Relaxed),
+            polling_duration: Duration::from_nanos(self.polling_nanos.load(Ordering::Relaxed)),
+            task_polls: self.task_polls.load(Ordering::Relaxed),
+            executing_duration: Duration::from_nanos(self.executing_nanos.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Logs the stats for the last period if periodic logs are enabled.
+    pub fn maybe_log(&self, context_name: &str, now: Instant) {
+        let Some(interval) = *STATS_LOG_INTERVAL else {
+            return;
+        };
+
+        // Another thread of the same `Context` is logging.
+        let Ok(mut last_log) = self.last_log.try_lock() else {
+            return;
+        };
+
+        if now.saturating_duration_since(last_log.0) < interval {
+            return;
+        }
+
+        let stats = self.snapshot();
+        let delta = stats.delta(&last_log.1);
+        *last_log = (now, stats);
+        drop(last_log);
+
+        gst::debug!(
+            RUNTIME_CAT,
+            "Context {} over {:.2?}: {:.1} wakeups/s, polling {:.2?}, executing {:.2?}, {:.1} task polls per period",
+            context_name,
+            delta.elapsed,
+            delta.wakeups_per_sec(),
+            delta.polling_duration,
+            delta.executing_duration,
+            delta.task_polls_per_period(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta() {
+        let earlier = ContextStats {
+            elapsed: Duration::from_secs(1),
+            wakeups: 100,
+            reactor_runs: 50,
+            polling_duration: Duration::from_millis(10),
+            task_polls: 200,
+            executing_duration: Duration::from_millis(100),
+        };
+        let later = ContextStats {
+            elapsed: Duration::from_secs(3),
+            wakeups: 500,
+            reactor_runs: 250,
+            polling_duration: Duration::from_millis(30),
+            task_polls: 1200,
+            executing_duration: Duration::from_millis(300),
+        };
+
+        let delta = later.delta(&earlier);
+        assert_eq!(delta.elapsed, Duration::from_secs(2));
+        assert_eq!(delta.wakeups, 400);
+        assert_eq!(delta.polling_duration, Duration::from_millis(20));
+        assert_eq!(delta.executing_duration, Duration::from_millis(200));
+        assert_eq!(delta.wakeups_per_sec(), 200.0);
+        assert_eq!(delta.task_polls_per_period(), 5.0);
+
+        assert_eq!(ContextStats::default().wakeups_per_sec(), 0.0);
+        assert_eq!(ContextStats::default().task_polls_per_period(), 0.0);
+    }
+}
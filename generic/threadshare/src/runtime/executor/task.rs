@@ -6,7 +6,7 @@
 use async_task::Runnable;
 use concurrent_queue::ConcurrentQueue;
 
-use futures::future::BoxFuture;
+use futures::future::{AbortHandle, Abortable, BoxFuture};
 use futures::prelude::*;
 
 use pin_project_lite::pin_project;
@@ -38,6 +38,14 @@ impl TaskId {
 
 pub type SubTaskOutput = Result<(), gst::FlowError>;
 
+/// Information about a task running on a [`Context`](super::Context).
+#[derive(Clone, Debug)]
+pub struct TaskInfo {
+    pub id: TaskId,
+    pub name: Option<Arc<str>>,
+    pub n_sub_tasks: usize,
+}
+
 pin_project! {
     pub(super) struct TaskFuture<F: Future> {
         id: TaskId,
@@ -74,22 +82,56 @@ impl<F: Future> Future for TaskFuture<F> {
 
 struct Task {
     id: TaskId,
+    name: Option<Arc<str>>,
     sub_tasks: VecDeque<BoxFuture<'static, SubTaskOutput>>,
+    /// Abort handles for the sub tasks which were added since last drained.
+    sub_task_abort_handles: Vec<AbortHandle>,
 }
 
 impl Task {
     fn new(id: TaskId) -> Self {
         Task {
             id,
+            name: None,
             sub_tasks: VecDeque::new(),
+            sub_task_abort_handles: Vec::new(),
+        }
+    }
+
+    fn info(&self) -> TaskInfo {
+        TaskInfo {
+            id: self.id,
+            name: self.name.clone(),
+            n_sub_tasks: self.sub_tasks.len(),
         }
     }
 
-    fn add_sub_task<T>(&mut self, sub_task: T)
+    fn add_sub_task<T>(&mut self, sub_task: T) -> AbortHandle
     where
         T: Future<Output = SubTaskOutput> + Send + 'static,
     {
-        self.sub_tasks.push_back(sub_task.boxed());
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let task_id = self.id;
+        self.sub_tasks.push_back(
+            Abortable::new(sub_task, abort_registration)
+                .map(move |res| {
+                    res.unwrap_or_else(|_| {
+                        gst::trace!(RUNTIME_CAT, "Sub task of {:?} aborted", task_id);
+                        Ok(())
+                    })
+                })
+                .boxed(),
+        );
+        self.sub_task_abort_handles.push(abort_handle.clone());
+
+        abort_handle
+    }
+
+    fn abort_sub_tasks(&mut self) {
+        for abort_handle in self.sub_task_abort_handles.drain(..) {
+            abort_handle.abort();
+        }
+        self.sub_tasks.clear();
     }
 }
 
@@ -97,6 +139,7 @@ impl fmt::Debug for Task {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("Task")
             .field("id", &self.id)
+            .field("name", &self.name)
             .field("sub_tasks len", &self.sub_tasks.len())
             .finish()
     }
@@ -236,7 +279,22 @@ impl TaskQueue {
         self.runnables.pop()
     }
 
-    pub fn add_sub_task<T>(&self, task_id: TaskId, sub_task: T) -> Result<(), T>
+    pub fn set_task_name(&self, task_id: TaskId, name: Arc<str>) {
+        if let Some(task) = self.tasks.lock().unwrap().get_mut(task_id.0) {
+            task.name = Some(name);
+        }
+    }
+
+    pub fn task_infos(&self) -> Vec<TaskInfo> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, task)| task.info())
+            .collect()
+    }
+
+    pub fn add_sub_task<T>(&self, task_id: TaskId, sub_task: T) -> Result<AbortHandle, T>
     where
         T: Future<Output = SubTaskOutput> + Send + 'static,
     {
@@ -249,8 +307,7 @@ impl TaskQueue {
                     task_id,
                     self.context_name
                 );
-                task.add_sub_task(sub_task);
-                Ok(())
+                Ok(task.add_sub_task(sub_task))
             }
             None => {
                 gst::trace!(RUNTIME_CAT, "Task was removed in the meantime");
@@ -259,7 +316,63 @@ impl TaskQueue {
         }
     }
 
+    /// Takes the pending sub tasks of all the tasks.
+    pub fn take_sub_tasks(&self) -> Vec<(TaskId, VecDeque<BoxFuture<'static, SubTaskOutput>>)> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .filter(|(_, task)| !task.sub_tasks.is_empty())
+            .map(|(_, task)| (task.id, std::mem::take(&mut task.sub_tasks)))
+            .collect()
+    }
+
+    /// Returns the number of sub tasks added to the task since it was last drained.
+    pub fn n_added_sub_tasks(&self, task_id: TaskId) -> usize {
+        self.tasks
+            .lock()
+            .unwrap()
+            .get(task_id.0)
+            .map_or(0, |task| task.sub_task_abort_handles.len())
+    }
+
+    /// Returns the abort handles of the sub tasks added to the task since it was last drained,
+    /// skipping the first `skip` ones.
+    pub fn added_sub_task_abort_handles(&self, task_id: TaskId, skip: usize) -> Vec<AbortHandle> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .get(task_id.0)
+            .and_then(|task| task.sub_task_abort_handles.get(skip..))
+            .map(<[AbortHandle]>::to_vec)
+            .unwrap_or_default()
+    }
+
+    /// Aborts the pending sub tasks of the task, including the one being executed if any.
+    pub fn abort_sub_tasks(&self, task_id: TaskId) {
+        if let Some(task) = self.tasks.lock().unwrap().get_mut(task_id.0) {
+            gst::trace!(
+                RUNTIME_CAT,
+                "Aborting {} sub tasks of {:?} on context {}",
+                task.sub_task_abort_handles.len(),
+                task_id,
+                self.context_name
+            );
+            task.abort_sub_tasks();
+        }
+    }
+
     pub async fn drain_sub_tasks(&self, task_id: TaskId) -> SubTaskOutput {
+        let tasks = Arc::clone(&self.tasks);
+        // All the sub tasks taken so far are done or dropped when leaving
+        let _guard = CallOnDrop::new(move || {
+            if let Some(task) = tasks.lock().unwrap().get_mut(task_id.0) {
+                if task.sub_tasks.is_empty() {
+                    task.sub_task_abort_handles.clear();
+                }
+            }
+        });
+
         loop {
             let mut sub_tasks = match self.tasks.lock().unwrap().get_mut(task_id.0) {
                 Some(task) if !task.sub_tasks.is_empty() => std::mem::take(&mut task.sub_tasks),
@@ -14,7 +14,7 @@ use std::pin::Pin;
 use std::task::{Context, Poll, Waker};
 use std::time::{Duration, Instant};
 
-use super::reactor::{AfterTimerId, Reactor, RegularTimerId};
+use super::reactor::{AfterTimerId, PreciseTimerId, Reactor, RegularTimerId};
 
 #[derive(Debug)]
 pub struct IntervalError;
@@ -72,6 +72,8 @@ pub fn at(when: Instant) -> Oneshot {
 /// the nearest time frame, meaning that the delay might elapse
 /// `wait` / 2 ms earlier or later than the expected instant.
 ///
+/// See [`Interval`] for the cadence & the coalescing of short periods.
+///
 /// Use [`interval_at_least`] when it's preferable not to tick
 /// before the expected instants.
 pub fn interval(period: Duration) -> Result<Interval, IntervalError> {
@@ -87,6 +89,8 @@ pub fn interval(period: Duration) -> Result<Interval, IntervalError> {
 /// the nearest time frame, meaning that the delay might elapse
 /// `wait` / 2 ms earlier or later than the expected instant.
 ///
+/// See [`Interval`] for the cadence & the coalescing of short periods.
+///
 /// Use [`interval_delayed_by_at_least`] when it's preferable not to tick
 /// before the expected instants.
 pub fn interval_delayed_by(delay: Duration, period: Duration) -> Result<Interval, IntervalError> {
@@ -100,6 +104,8 @@ pub fn interval_delayed_by(delay: Duration, period: Duration) -> Result<Interval
 /// the nearest time frame, meaning that the delay might elapse
 /// `wait` / 2 ms earlier or later than the expected instant.
 ///
+/// See [`Interval`] for the cadence & the coalescing of short periods.
+///
 /// Use [`interval_after_at_least`] when it's preferable not to tick
 /// before the expected instants.
 pub fn interval_at(start: Instant, period: Duration) -> Result<Interval, IntervalError> {
@@ -137,6 +143,26 @@ pub fn after(when: Instant) -> OneshotAfter {
     OneshotAfter::new(when)
 }
 
+/// Creates a precise timer that emits an event once after the given delay.
+///
+/// As opposed to [`delay_for`] & [`delay_for_at_least`], precise timers
+/// bypass the throttling quantization: the `Context` wakes up as soon as
+/// the delay has elapsed, regardless of its `wait` duration. This costs an
+/// additional wake up of the `Context`, so this should be reserved to the
+/// timers which actually need sub-millisecond accuracy, e.g. deadlines.
+#[track_caller]
+pub fn delay_for_precise(delay: Duration) -> OneshotPrecise {
+    OneshotPrecise::new(Instant::now() + delay)
+}
+
+/// Creates a precise timer that emits an event once at the given time instant.
+///
+/// See [`delay_for_precise`] for details.
+#[track_caller]
+pub fn at_precise(when: Instant) -> OneshotPrecise {
+    OneshotPrecise::new(when)
+}
+
 /// Creates a timer that emits events periodically, starting as soon as possible.
 ///
 /// Returns an error if `period` is zero.
@@ -208,7 +234,7 @@ impl Oneshot {
 impl Drop for Oneshot {
     fn drop(&mut self) {
         if let Some((id, _)) = self.id_and_waker.take() {
-            Reactor::with_mut(|reactor| {
+            Reactor::with(|reactor| {
                 reactor.remove_timer(self.when, id);
             });
         }
@@ -219,7 +245,7 @@ impl Future for Oneshot {
     type Output = ();
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        Reactor::with_mut(|reactor| {
+        Reactor::with(|reactor| {
             if reactor.time_slice_end() >= self.when {
                 if let Some((id, _)) = self.id_and_waker.take() {
                     // Deregister the timer from the reactor.
@@ -278,7 +304,7 @@ impl OneshotAfter {
 impl Drop for OneshotAfter {
     fn drop(&mut self) {
         if let Some((id, _)) = self.id_and_waker.take() {
-            Reactor::with_mut(|reactor| {
+            Reactor::with(|reactor| {
                 reactor.remove_timer(self.when, id);
             });
         }
@@ -289,7 +315,7 @@ impl Future for OneshotAfter {
     type Output = ();
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        Reactor::with_mut(|reactor| {
+        Reactor::with(|reactor| {
             if reactor.timers_check_instant() >= self.when {
                 if let Some((id, _)) = self.id_and_waker.take() {
                     // Deregister the timer from the reactor.
@@ -321,10 +347,101 @@ impl Future for OneshotAfter {
     }
 }
 
+/// A future that emits an event at the given time.
+///
+/// `OneshotPrecise`s are futures that resolve as soon as
+/// the given time is reached, regardless of throttling.
+#[derive(Debug)]
+pub struct OneshotPrecise {
+    /// This timer's ID and last waker that polled it.
+    ///
+    /// When this field is set to `None`, this timer is not registered in the reactor.
+    id_and_waker: Option<(PreciseTimerId, Waker)>,
+
+    /// The instant at which this timer fires.
+    when: Instant,
+}
+
+impl OneshotPrecise {
+    fn new(when: Instant) -> Self {
+        OneshotPrecise {
+            id_and_waker: None,
+            when,
+        }
+    }
+}
+
+impl Drop for OneshotPrecise {
+    fn drop(&mut self) {
+        if let Some((id, _)) = self.id_and_waker.take() {
+            Reactor::with(|reactor| {
+                reactor.remove_timer(self.when, id);
+            });
+        }
+    }
+}
+
+impl Future for OneshotPrecise {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Reactor::with(|reactor| {
+            if Instant::now() >= self.when {
+                if let Some((id, _)) = self.id_and_waker.take() {
+                    // Deregister the timer from the reactor.
+                    reactor.remove_timer(self.when, id);
+                }
+
+                Poll::Ready(())
+            } else {
+                match &self.id_and_waker {
+                    None => {
+                        // Register the timer in the reactor.
+                        let id = reactor.insert_precise_timer(self.when, cx.waker());
+                        self.id_and_waker = Some((id, cx.waker().clone()));
+                    }
+                    Some((id, w)) if !w.will_wake(cx.waker()) => {
+                        // Deregister the timer from the reactor to remove the old waker.
+                        reactor.remove_timer(self.when, *id);
+
+                        // Register the timer in the reactor with the new waker.
+                        let id = reactor.insert_precise_timer(self.when, cx.waker());
+                        self.id_and_waker = Some((id, cx.waker().clone()));
+                    }
+                    Some(_) => {}
+                }
+
+                Poll::Pending
+            }
+        })
+    }
+}
+
 /// A stream that emits timed events.
 ///
 /// `Interval`s are streams that ticks periodically in the closest
-/// time slice.
+/// time slice. The timer is cancelled when the stream is dropped.
+///
+/// # Cadence
+///
+/// Ticks follow an absolute cadence: the n-th tick is expected at
+/// `start + n * period`, regardless of how late the previous ticks were
+/// polled. As opposed to chained [`delay_for`]s, the processing time
+/// between two ticks doesn't accumulate as drift.
+///
+/// # Throttling
+///
+/// When throttling is activated (i.e. when using a non-`0` `wait`
+/// duration in `Context::acquire`), each tick is assigned to the time
+/// frame of the reactor run closest to its expected instant, so it might
+/// fire up to `wait` / 2 earlier or later. The error is bounded and
+/// doesn't depend on the number of elapsed periods.
+///
+/// If `period` is shorter than `wait`, all the ticks expected within
+/// a time frame are coalesced into a single tick: missed ticks are not
+/// replayed and the next tick is the first one of the cadence which is
+/// past the current time frame. Use the elapsed time instead of counting
+/// ticks if the number of periods matters.
 #[derive(Debug)]
 pub struct Interval {
     /// This timer's ID and last waker that polled it.
@@ -352,7 +469,7 @@ impl Interval {
 impl Drop for Interval {
     fn drop(&mut self) {
         if let Some((id, _)) = self.id_and_waker.take() {
-            Reactor::with_mut(|reactor| {
+            Reactor::with(|reactor| {
                 reactor.remove_timer(self.when, id);
             });
         }
@@ -363,7 +480,7 @@ impl Stream for Interval {
     type Item = ();
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Reactor::with_mut(|reactor| {
+        Reactor::with(|reactor| {
             let time_slice_end = reactor.time_slice_end();
             if time_slice_end >= self.when {
                 if let Some((id, _)) = self.id_and_waker.take() {
@@ -416,6 +533,9 @@ impl FusedStream for Interval {
 ///
 /// `IntervalAfter`s are streams that ticks periodically. Ticks are
 /// guaranteed to fire no sooner than the expected instant.
+///
+/// Cadence and coalescing follow the same rules as [`Interval`], except
+/// that ticks might be delayed by up to `wait` but never fire early.
 #[derive(Debug)]
 pub struct IntervalAfter {
     /// This timer's ID and last waker that polled it.
@@ -443,7 +563,7 @@ impl IntervalAfter {
 impl Drop for IntervalAfter {
     fn drop(&mut self) {
         if let Some((id, _)) = self.id_and_waker.take() {
-            Reactor::with_mut(|reactor| {
+            Reactor::with(|reactor| {
                 reactor.remove_timer(self.when, id);
             });
         }
@@ -454,7 +574,7 @@ impl Stream for IntervalAfter {
     type Item = ();
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Reactor::with_mut(|reactor| {
+        Reactor::with(|reactor| {
             let timers_check_instant = reactor.timers_check_instant();
             if timers_check_instant >= self.when {
                 if let Some((id, _)) = self.id_and_waker.take() {
@@ -598,4 +718,97 @@ mod tests {
 
         futures::executor::block_on(join_handle).unwrap();
     }
+
+    #[test]
+    fn interval_no_drift() {
+        use futures::prelude::*;
+
+        const TICKS: u32 = 100;
+        const PROCESSING: Duration = Duration::from_millis(4);
+
+        gst::init().unwrap();
+
+        let handle = Scheduler::start("interval_no_drift", MAX_THROTTLING);
+
+        let join_handle = handle.spawn(async move {
+            let start = Instant::now();
+            let mut interval = super::interval_at(start + PERIOD, PERIOD).unwrap();
+
+            for n in 1..=TICKS {
+                interval.next().await.unwrap();
+
+                // Due to throttling, intervals may tick earlier.
+                let expected = PERIOD * n;
+                assert!(start.elapsed() + MAX_THROTTLING / 2 >= expected);
+
+                // Processing time between ticks must not add up
+                std::thread::sleep(PROCESSING);
+            }
+
+            start.elapsed()
+        });
+
+        let elapsed = futures::executor::block_on(join_handle).unwrap();
+
+        // Chained delays would have drifted by TICKS * PROCESSING
+        let expected = PERIOD * TICKS + PROCESSING;
+        assert!(elapsed + MAX_THROTTLING / 2 >= expected);
+        assert!(
+            elapsed < expected + MAX_THROTTLING * 3,
+            "drifted: {elapsed:?}"
+        );
+    }
+
+    #[test]
+    fn interval_shorter_than_throttling() {
+        use futures::prelude::*;
+
+        const SHORT_PERIOD: Duration = Duration::from_millis(2);
+        const TICKS: u32 = 20;
+
+        gst::init().unwrap();
+
+        let handle = Scheduler::start("interval_shorter_than_throttling", MAX_THROTTLING);
+
+        let join_handle = handle.spawn(async move {
+            let start = Instant::now();
+            let mut interval = super::interval_at(start + SHORT_PERIOD, SHORT_PERIOD).unwrap();
+
+            for _ in 0..TICKS {
+                interval.next().await.unwrap();
+            }
+
+            start.elapsed()
+        });
+
+        let elapsed = futures::executor::block_on(join_handle).unwrap();
+
+        // Ticks expected within a throttling period are coalesced
+        assert!(
+            elapsed >= SHORT_PERIOD * TICKS * 2,
+            "not coalesced: {elapsed:?}"
+        );
+    }
+
+    #[test]
+    fn delay_for_precise() {
+        gst::init().unwrap();
+
+        // Long throttling so that a throttled timer would be noticeably late
+        const LONG_THROTTLING: Duration = Duration::from_millis(50);
+        const PRECISE_DELAY: Duration = Duration::from_millis(7);
+
+        let handle = Scheduler::start("delay_for_precise", LONG_THROTTLING);
+
+        let elapsed = futures::executor::block_on(handle.spawn(async {
+            let start = Instant::now();
+            super::delay_for_precise(PRECISE_DELAY).await;
+            start.elapsed()
+        }))
+        .unwrap();
+
+        // Never returns earlier than PRECISE_DELAY & doesn't wait for the next time slice
+        assert!(elapsed >= PRECISE_DELAY);
+        assert!(elapsed < LONG_THROTTLING / 2, "not precise: {elapsed:?}");
+    }
 }
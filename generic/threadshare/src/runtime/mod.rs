@@ -21,7 +21,9 @@
 //! Current implementation uses a custom executor mostly based on the [`smol`] ecosystem.
 //!
 //! Most `Element`s implementations should use the high-level features provided by [`PadSrc`] &
-//! [`PadSink`].
+//! [`PadSink`]. Sources can build on the helpers from [`src_base`] in order to avoid
+//! duplicating the stream prelude and state change handling. The sockets from [`net`] provide
+//! async network I/O bound to the [`Context`] of the `Element`.
 //!
 //! [talk]: https://gstconf.ubicast.tv/videos/when-adding-more-threads-adds-more-problems-thread-sharing-between-elements-in-gstreamer/
 //! [slides]: https://gstreamer.freedesktop.org/data/events/gstreamer-conference/2018/Sebastian%20Dr%C3%B6ge%20-%20When%20adding%20more%20threads%20adds%20more%20problems:%20Thread-sharing%20between%20elements%20in%20GStreamer.pdf
@@ -29,18 +31,33 @@
 //! [`smol`]: https://github.com/smol-rs/
 //! [`PadSrc`]: pad/struct.PadSrc.html
 //! [`PadSink`]: pad/struct.PadSink.html
+//! [`src_base`]: src_base/index.html
+//! [`net`]: net/index.html
+//! [`Context`]: executor/struct.Context.html
 
 pub mod executor;
-pub use executor::{timer, Async, Context, JoinHandle, SubTaskOutput};
+pub use executor::{
+    timer, Async, Context, ContextConfig, ContextInfo, ContextStats, JoinHandle, SchedulingPolicy,
+    SubTaskJoinHandle, SubTaskOutput, TaskInfo,
+};
+
+pub mod net;
 
 pub mod pad;
 pub use pad::{PadSink, PadSinkRef, PadSinkWeak, PadSrc, PadSrcRef, PadSrcWeak};
 
+pub mod src_base;
+pub use src_base::{SrcItem, SrcTaskAdapter};
+
 pub mod task;
 pub use task::{Task, TaskState};
 
+#[cfg(feature = "tokio-compat")]
+pub mod tokio_compat;
+
 pub mod prelude {
     pub use super::pad::{PadSinkHandler, PadSrcHandler};
+    pub use super::src_base::{SrcTaskImpl, TsSrcImpl};
     pub use super::task::TaskImpl;
 }
 
@@ -53,3 +70,11 @@ static RUNTIME_CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
         Some("Thread-sharing Runtime"),
     )
 });
+
+static CONTEXT_DUMP_CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-context-dump",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing Context dumps"),
+    )
+});
@@ -0,0 +1,1137 @@
+// Take a look at the license at the top of the repository in the LICENSE file.
+
+//! Async networking primitives bound to a [`Context`]: [`UdpSocket`], [`TcpListener`] & [`TcpStream`].
+//!
+//! The sockets defined in this module are registered in the reactor of the
+//! [`Context`] on which they are created. They must be created from a
+//! [`Context`] thread or within [`Context::enter`], they can then be used from
+//! any task spawned on this [`Context`].
+//!
+//! # Example
+//!
+//! ```
+//! # gst::init().unwrap();
+//! use gstthreadshare::runtime::{net::UdpSocket, Context};
+//! use std::time::Duration;
+//!
+//! let ctx = Context::acquire("udp-echo", Duration::from_millis(2)).unwrap();
+//!
+//! let (server, client) = ctx.enter(|| {
+//!     let server = UdpSocket::builder()
+//!         .reuse_address(true)
+//!         .bind(([127, 0, 0, 1], 0))?;
+//!     let client = UdpSocket::bind(([127, 0, 0, 1], 0))?;
+//!     Ok::<_, std::io::Error>((server, client))
+//! })
+//! .unwrap();
+//!
+//! let server_addr = server.local_addr().unwrap();
+//! let echo = ctx.spawn(async move {
+//!     let mut buf = [0; 1500];
+//!     let (len, from) = server.recv_from(&mut buf).await?;
+//!     server.send_to(&buf[..len], from).await
+//! });
+//!
+//! let reply = ctx.spawn(async move {
+//!     client.connect(server_addr).await?;
+//!     client.send(b"ping").await?;
+//!
+//!     let mut buf = [0; 1500];
+//!     let len = client.recv(&mut buf).await?;
+//!     Ok::<_, std::io::Error>(buf[..len].to_vec())
+//! });
+//!
+//! futures::executor::block_on(echo).unwrap().unwrap();
+//! assert_eq!(futures::executor::block_on(reply).unwrap().unwrap(), b"ping");
+//! ```
+//!
+//! [`Context`]: super::Context
+//! [`Context::enter`]: super::Context::enter
+
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::stream::{self, Stream};
+
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr};
+use std::pin::Pin;
+use std::task::Poll;
+
+#[cfg(unix)]
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+
+#[cfg(windows)]
+use std::os::windows::io::{AsRawSocket, AsSocket, BorrowedSocket, RawSocket};
+
+use socket2::{Domain, Protocol, SockRef, Socket, Type};
+
+use super::Async;
+
+/// A UDP socket registered in the reactor of a [`Context`].
+///
+/// Use [`UdpSocket::bind`] for a socket with the default options or
+/// [`UdpSocket::builder`] to configure the socket before it is bound.
+///
+/// See the [module documentation](self) for an example.
+///
+/// [`Context`]: super::Context
+#[derive(Debug)]
+pub struct UdpSocket(Async<std::net::UdpSocket>);
+
+impl UdpSocket {
+    /// Creates a UDP socket bound to the specified address.
+    ///
+    /// Binding with port number 0 will request an available port from the OS.
+    ///
+    /// # Panic
+    ///
+    /// Panics if called out of a [`Context`](super::Context).
+    pub fn bind<A: Into<SocketAddr>>(addr: A) -> io::Result<UdpSocket> {
+        UdpSocketBuilder::default().bind(addr)
+    }
+
+    /// Returns a builder to configure the socket before it is bound.
+    pub fn builder() -> UdpSocketBuilder {
+        UdpSocketBuilder::default()
+    }
+
+    /// Registers an existing `std` UDP socket in the reactor of current [`Context`].
+    ///
+    /// # Panic
+    ///
+    /// Panics if called out of a [`Context`](super::Context).
+    ///
+    /// [`Context`]: super::Context
+    pub fn from_std(socket: std::net::UdpSocket) -> io::Result<UdpSocket> {
+        Async::new(socket).map(UdpSocket)
+    }
+
+    /// Deregisters the socket from the reactor and returns the `std` UDP socket.
+    pub fn into_std(self) -> io::Result<std::net::UdpSocket> {
+        self.0.into_inner()
+    }
+
+    /// Returns a reference to the inner `std` UDP socket.
+    ///
+    /// This can be used to get or set socket options which are not handled by this type.
+    pub fn get_ref(&self) -> &std::net::UdpSocket {
+        self.0.get_ref()
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.get_ref().local_addr()
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.0.get_ref().peer_addr()
+    }
+
+    /// Connects this socket to a remote address.
+    ///
+    /// Only datagrams from this address are received after the call and
+    /// [`Self::send`] & [`Self::recv`] can be used.
+    pub async fn connect<A: Into<SocketAddr>>(&self, addr: A) -> io::Result<()> {
+        self.0.get_ref().connect(addr.into())
+    }
+
+    /// Receives a single datagram message.
+    ///
+    /// Returns the number of bytes read and the address the message came from.
+    ///
+    /// This method must be called with a valid byte slice of sufficient size to hold the message.
+    /// If the message is too long to fit, excess bytes may get discarded.
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.0.recv_from(buf).await
+    }
+
+    /// Receives a single datagram message without removing it from the queue.
+    pub async fn peek_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.0.peek_from(buf).await
+    }
+
+    /// Sends data to the specified address.
+    ///
+    /// Returns the number of bytes written.
+    pub async fn send_to<A: Into<SocketAddr>>(&self, buf: &[u8], addr: A) -> io::Result<usize> {
+        self.0.send_to(buf, addr).await
+    }
+
+    /// Sends the datagrams in `bufs` to the specified address, in order.
+    ///
+    /// Uses a single `sendmmsg(2)` call on Linux.
+    ///
+    /// Returns the number of datagrams sent. If this is lower than the number of datagrams,
+    /// sending the next one failed: calling this again with the remaining datagrams reports
+    /// the error.
+    pub async fn send_batch_to<A: Into<SocketAddr>>(
+        &self,
+        bufs: &[&[u8]],
+        addr: A,
+    ) -> io::Result<usize> {
+        if bufs.is_empty() {
+            return Ok(0);
+        }
+
+        let addr = addr.into();
+
+        #[cfg(target_os = "linux")]
+        {
+            let addr = socket2::SockAddr::from(addr);
+            self.0
+                .write_with(|socket| {
+                    let mut iovecs = bufs
+                        .iter()
+                        .map(|buf| libc::iovec {
+                            iov_base: buf.as_ptr() as *mut _,
+                            iov_len: buf.len(),
+                        })
+                        .collect::<Vec<_>>();
+
+                    let mut msgs = iovecs
+                        .iter_mut()
+                        .map(|iovec| {
+                            // SAFETY: mmsghdr is a plain C struct for which all zeroes is a valid value.
+                            let mut msg: libc::mmsghdr = unsafe { std::mem::zeroed() };
+                            msg.msg_hdr.msg_name = addr.as_ptr() as *mut _;
+                            msg.msg_hdr.msg_namelen = addr.len();
+                            msg.msg_hdr.msg_iov = iovec;
+                            msg.msg_hdr.msg_iovlen = 1;
+
+                            msg
+                        })
+                        .collect::<Vec<_>>();
+
+                    // SAFETY: the messages point to the address & to iovecs which outlive
+                    // the call, the iovecs point to the datagrams. sendmmsg doesn't write
+                    // to the buffers, despite the mutable pointers.
+                    let res = unsafe {
+                        libc::sendmmsg(socket.as_raw_fd(), msgs.as_mut_ptr(), msgs.len() as _, 0)
+                    };
+                    if res < 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+
+                    Ok(res as usize)
+                })
+                .await
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            for (idx, buf) in bufs.iter().enumerate() {
+                if let Err(err) = self.send_to(buf, addr).await {
+                    if idx == 0 {
+                        return Err(err);
+                    }
+
+                    return Ok(idx);
+                }
+            }
+
+            Ok(bufs.len())
+        }
+    }
+
+    /// Receives a single datagram message.
+    ///
+    /// Cheaper than [`Self::recv_from`] when the address of the sender is not needed.
+    /// On a connected socket, only datagrams from the peer are received.
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.recv(buf).await
+    }
+
+    /// Sends data to the connected peer.
+    ///
+    /// This method will fail if the socket is not connected.
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.0.send(buf).await
+    }
+
+    /// Sets the type of service field of outgoing packets, or the traffic class for IPv6 sockets.
+    ///
+    /// See [`UdpSocketBuilder::tos`].
+    pub fn set_tos(&self, tos: u8) -> io::Result<()> {
+        let is_ipv6 = self.local_addr()?.is_ipv6();
+        set_tos(&SockRef::from(self), tos, is_ipv6)
+    }
+
+    /// Joins an IPv4 multicast group on the interface with the provided address.
+    ///
+    /// Use [`Ipv4Addr::UNSPECIFIED`] to let the OS select the interface.
+    pub fn join_multicast_v4(&self, group: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.0.get_ref().join_multicast_v4(group, interface)
+    }
+
+    pub fn leave_multicast_v4(&self, group: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.0.get_ref().leave_multicast_v4(group, interface)
+    }
+
+    /// Joins an IPv6 multicast group on the interface with the provided index.
+    ///
+    /// Use `0` to let the OS select the interface.
+    pub fn join_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.0.get_ref().join_multicast_v6(group, interface)
+    }
+
+    pub fn leave_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.0.get_ref().leave_multicast_v6(group, interface)
+    }
+}
+
+impl AsRef<std::net::UdpSocket> for UdpSocket {
+    fn as_ref(&self) -> &std::net::UdpSocket {
+        self.0.get_ref()
+    }
+}
+
+impl TryFrom<std::net::UdpSocket> for UdpSocket {
+    type Error = io::Error;
+
+    fn try_from(socket: std::net::UdpSocket) -> io::Result<Self> {
+        UdpSocket::from_std(socket)
+    }
+}
+
+impl TryFrom<Socket> for UdpSocket {
+    type Error = io::Error;
+
+    fn try_from(socket: Socket) -> io::Result<Self> {
+        UdpSocket::from_std(socket.into())
+    }
+}
+
+#[cfg(unix)]
+impl AsFd for UdpSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for UdpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+#[cfg(windows)]
+impl AsSocket for UdpSocket {
+    fn as_socket(&self) -> BorrowedSocket<'_> {
+        self.0.as_socket()
+    }
+}
+
+#[cfg(windows)]
+impl AsRawSocket for UdpSocket {
+    fn as_raw_socket(&self) -> RawSocket {
+        self.0.as_raw_socket()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MulticastGroup {
+    V4(Ipv4Addr, Ipv4Addr),
+    V6(Ipv6Addr, u32),
+}
+
+/// Builder for a [`UdpSocket`].
+///
+/// Options are applied in the order of the fields below before the socket is
+/// bound, except for the multicast groups which are joined after binding.
+/// Errors mention the option which couldn't be applied.
+///
+/// ```
+/// # gst::init().unwrap();
+/// # use gstthreadshare::runtime::{net::UdpSocket, Context};
+/// # use std::net::Ipv4Addr;
+/// # use std::time::Duration;
+/// # let ctx = Context::acquire("udp-builder", Duration::from_millis(2)).unwrap();
+/// let socket = ctx
+///     .enter(|| {
+///         UdpSocket::builder()
+///             .reuse_address(true)
+///             .multicast_loop(false)
+///             .multicast_ttl(4)
+///             .tos(46 << 2)
+///             .bind((Ipv4Addr::UNSPECIFIED, 0))
+///     })
+///     .unwrap();
+/// # assert_eq!(socket.get_ref().multicast_ttl_v4().unwrap(), 4);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct UdpSocketBuilder {
+    reuse_address: bool,
+    #[cfg(unix)]
+    reuse_port: bool,
+    only_v6: Option<bool>,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+    ttl: Option<u32>,
+    multicast_ttl: Option<u32>,
+    multicast_loop: Option<bool>,
+    #[cfg(target_os = "linux")]
+    multicast_all: Option<bool>,
+    tos: Option<u8>,
+    multicast_groups: Vec<MulticastGroup>,
+}
+
+impl UdpSocketBuilder {
+    /// Allows binding to an address which is already in use (`SO_REUSEADDR`).
+    pub fn reuse_address(mut self, reuse: bool) -> Self {
+        self.reuse_address = reuse;
+        self
+    }
+
+    /// Allows multiple sockets to bind to the same address & port (`SO_REUSEPORT`).
+    #[cfg(unix)]
+    pub fn reuse_port(mut self, reuse: bool) -> Self {
+        self.reuse_port = reuse;
+        self
+    }
+
+    /// Restricts an IPv6 socket to IPv6 traffic (`IPV6_V6ONLY`).
+    ///
+    /// Set to `false` in order to also receive IPv4 traffic through
+    /// v4-mapped addresses. Ignored for IPv4 sockets.
+    pub fn only_v6(mut self, only_v6: bool) -> Self {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+
+    /// Requests a receive buffer of `size` bytes (`SO_RCVBUF`).
+    ///
+    /// The OS might clamp the value: check the effective size
+    /// after binding if this matters.
+    pub fn recv_buffer_size(mut self, size: usize) -> Self {
+        self.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Requests a send buffer of `size` bytes (`SO_SNDBUF`).
+    pub fn send_buffer_size(mut self, size: usize) -> Self {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the TTL of unicast packets, or the hop limit for IPv6 sockets.
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the TTL of multicast packets, or the hop limit for IPv6 sockets.
+    pub fn multicast_ttl(mut self, ttl: u32) -> Self {
+        self.multicast_ttl = Some(ttl);
+        self
+    }
+
+    /// Whether multicast packets sent by this socket are looped back to local sockets.
+    pub fn multicast_loop(mut self, multicast_loop: bool) -> Self {
+        self.multicast_loop = Some(multicast_loop);
+        self
+    }
+
+    /// Whether the socket receives the datagrams of all the multicast groups joined
+    /// on the system for its port (`IP_MULTICAST_ALL` / `IPV6_MULTICAST_ALL`).
+    ///
+    /// Defaults to `true` on Linux. Set to `false` in order to only receive
+    /// the groups joined on this socket.
+    #[cfg(target_os = "linux")]
+    pub fn multicast_all(mut self, multicast_all: bool) -> Self {
+        self.multicast_all = Some(multicast_all);
+        self
+    }
+
+    /// Sets the type of service field of outgoing packets, or the traffic class for IPv6 sockets.
+    ///
+    /// The DSCP occupies the 6 most significant bits, e.g. `dscp << 2`.
+    /// Not supported on all platforms, in which case this is a no-op.
+    pub fn tos(mut self, tos: u8) -> Self {
+        self.tos = Some(tos);
+        self
+    }
+
+    /// Joins the IPv4 multicast `group` on the interface with the provided address once bound.
+    ///
+    /// Use [`Ipv4Addr::UNSPECIFIED`] to let the OS select the interface.
+    pub fn join_multicast_v4(mut self, group: Ipv4Addr, interface: Ipv4Addr) -> Self {
+        self.multicast_groups
+            .push(MulticastGroup::V4(group, interface));
+        self
+    }
+
+    /// Joins the IPv6 multicast `group` on the interface with the provided index once bound.
+    ///
+    /// Use `0` to let the OS select the interface.
+    pub fn join_multicast_v6(mut self, group: Ipv6Addr, interface: u32) -> Self {
+        self.multicast_groups
+            .push(MulticastGroup::V6(group, interface));
+        self
+    }
+
+    /// Creates the socket, applies the options & binds it to the specified address.
+    ///
+    /// # Panic
+    ///
+    /// Panics if called out of a [`Context`](super::Context).
+    pub fn bind<A: Into<SocketAddr>>(self, addr: A) -> io::Result<UdpSocket> {
+        let addr = addr.into();
+        let is_ipv6 = addr.is_ipv6();
+
+        let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+
+        if is_ipv6 {
+            if let Some(only_v6) = self.only_v6 {
+                socket
+                    .set_only_v6(only_v6)
+                    .map_err(|err| with_option(err, "only_v6"))?;
+            }
+        }
+
+        socket
+            .set_reuse_address(self.reuse_address)
+            .map_err(|err| with_option(err, "reuse_address"))?;
+
+        #[cfg(unix)]
+        socket
+            .set_reuse_port(self.reuse_port)
+            .map_err(|err| with_option(err, "reuse_port"))?;
+
+        if let Some(size) = self.recv_buffer_size {
+            socket
+                .set_recv_buffer_size(size)
+                .map_err(|err| with_option(err, "recv_buffer_size"))?;
+        }
+
+        if let Some(size) = self.send_buffer_size {
+            socket
+                .set_send_buffer_size(size)
+                .map_err(|err| with_option(err, "send_buffer_size"))?;
+        }
+
+        if let Some(ttl) = self.ttl {
+            if is_ipv6 {
+                socket.set_unicast_hops_v6(ttl)
+            } else {
+                socket.set_ttl(ttl)
+            }
+            .map_err(|err| with_option(err, "ttl"))?;
+        }
+
+        if let Some(ttl) = self.multicast_ttl {
+            if is_ipv6 {
+                socket.set_multicast_hops_v6(ttl)
+            } else {
+                socket.set_multicast_ttl_v4(ttl)
+            }
+            .map_err(|err| with_option(err, "multicast_ttl"))?;
+        }
+
+        if let Some(multicast_loop) = self.multicast_loop {
+            if is_ipv6 {
+                socket.set_multicast_loop_v6(multicast_loop)
+            } else {
+                socket.set_multicast_loop_v4(multicast_loop)
+            }
+            .map_err(|err| with_option(err, "multicast_loop"))?;
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(multicast_all) = self.multicast_all {
+            if is_ipv6 {
+                socket.set_multicast_all_v6(multicast_all)
+            } else {
+                socket.set_multicast_all_v4(multicast_all)
+            }
+            .map_err(|err| with_option(err, "multicast_all"))?;
+        }
+
+        if let Some(tos) = self.tos {
+            set_tos(&socket, tos, is_ipv6).map_err(|err| with_option(err, "tos"))?;
+        }
+
+        socket.bind(&addr.into())?;
+
+        for group in self.multicast_groups.iter() {
+            match *group {
+                MulticastGroup::V4(group, interface) => {
+                    socket.join_multicast_v4(&group, &interface)
+                }
+                MulticastGroup::V6(group, interface) => socket.join_multicast_v6(&group, interface),
+            }
+            .map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("failed to join multicast group {group:?}: {err}"),
+                )
+            })?;
+        }
+
+        UdpSocket::try_from(socket)
+    }
+}
+
+/// A TCP listener registered in the reactor of a [`Context`].
+///
+/// ```
+/// # gst::init().unwrap();
+/// use futures::io::{AsyncReadExt, AsyncWriteExt};
+/// use gstthreadshare::runtime::{net::TcpListener, Context};
+/// use std::time::Duration;
+///
+/// let ctx = Context::acquire("tcp-echo", Duration::from_millis(2)).unwrap();
+///
+/// let listener = ctx.enter(|| TcpListener::bind(([127, 0, 0, 1], 0))).unwrap();
+/// # let addr = listener.local_addr().unwrap();
+/// let server = ctx.spawn(async move {
+///     let (stream, _peer) = listener.accept().await?;
+///     let mut buf = Vec::new();
+///     (&stream).read_to_end(&mut buf).await?;
+///     (&stream).write_all(&buf).await
+/// });
+/// # let client = ctx.spawn(async move {
+/// #     let mut stream = gstthreadshare::runtime::net::TcpStream::connect(addr).await?;
+/// #     stream.write_all(b"ping").await?;
+/// #     stream.shutdown(std::net::Shutdown::Write)?;
+/// #     let mut buf = Vec::new();
+/// #     stream.read_to_end(&mut buf).await?;
+/// #     Ok::<_, std::io::Error>(buf)
+/// # });
+/// # futures::executor::block_on(server).unwrap().unwrap();
+/// # assert_eq!(futures::executor::block_on(client).unwrap().unwrap(), b"ping");
+/// ```
+///
+/// [`Context`]: super::Context
+#[derive(Debug)]
+pub struct TcpListener(Async<std::net::TcpListener>);
+
+impl TcpListener {
+    /// Creates a TCP listener bound to the specified address.
+    ///
+    /// Binding with port number 0 will request an available port from the OS.
+    ///
+    /// # Panic
+    ///
+    /// Panics if called out of a [`Context`](super::Context).
+    pub fn bind<A: Into<SocketAddr>>(addr: A) -> io::Result<TcpListener> {
+        Async::<std::net::TcpListener>::bind(addr).map(TcpListener)
+    }
+
+    /// Registers an existing `std` TCP listener in the reactor of current [`Context`].
+    ///
+    /// This can be used with listeners received from another process.
+    ///
+    /// # Panic
+    ///
+    /// Panics if called out of a [`Context`].
+    ///
+    /// [`Context`]: super::Context
+    pub fn from_std(listener: std::net::TcpListener) -> io::Result<TcpListener> {
+        Async::new(listener).map(TcpListener)
+    }
+
+    /// Deregisters the listener from the reactor and returns the `std` TCP listener.
+    pub fn into_std(self) -> io::Result<std::net::TcpListener> {
+        self.0.into_inner()
+    }
+
+    pub fn get_ref(&self) -> &std::net::TcpListener {
+        self.0.get_ref()
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.get_ref().local_addr()
+    }
+
+    /// Accepts a new incoming TCP connection.
+    ///
+    /// The returned stream is registered in the reactor of the [`Context`]
+    /// on which the listener was created.
+    ///
+    /// [`Context`]: super::Context
+    pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let (stream, addr) = self.0.accept().await?;
+        Ok((TcpStream(stream), addr))
+    }
+
+    /// Returns a stream of incoming TCP connections.
+    ///
+    /// The stream is infinite, i.e. it never stops with a [`None`].
+    pub fn incoming(&self) -> impl Stream<Item = io::Result<TcpStream>> + Send + '_ {
+        stream::unfold(self, |listener| async move {
+            let res = listener.accept().await.map(|(stream, _)| stream);
+            Some((res, listener))
+        })
+    }
+}
+
+impl TryFrom<std::net::TcpListener> for TcpListener {
+    type Error = io::Error;
+
+    fn try_from(listener: std::net::TcpListener) -> io::Result<Self> {
+        TcpListener::from_std(listener)
+    }
+}
+
+/// A TCP stream registered in the reactor of a [`Context`].
+///
+/// Besides the inherent methods, [`TcpStream`] & [`&TcpStream`][TcpStream]
+/// implement [`AsyncRead`] & [`AsyncWrite`], so the helpers from
+/// [`futures::io`] can be used. See the caveats about concurrent I/O
+/// in [`Async`].
+///
+/// See [`TcpListener`] for an example.
+///
+/// [`Context`]: super::Context
+#[derive(Debug)]
+pub struct TcpStream(Async<std::net::TcpStream>);
+
+impl TcpStream {
+    /// Creates a TCP connection to the specified address.
+    ///
+    /// The connection attempt is cancelled if the returned future is dropped
+    /// before completion, e.g. when it is aborted or loses a race against a timer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if polled out of a [`Context`](super::Context).
+    pub async fn connect<A: Into<SocketAddr>>(addr: A) -> io::Result<TcpStream> {
+        Async::<std::net::TcpStream>::connect(addr)
+            .await
+            .map(TcpStream)
+    }
+
+    /// Creates a TCP connection to the specified address from the specified local address.
+    ///
+    /// See [`Self::connect`] regarding cancellation.
+    pub async fn connect_from<A: Into<SocketAddr>>(
+        local_addr: SocketAddr,
+        addr: A,
+    ) -> io::Result<TcpStream> {
+        Async::<std::net::TcpStream>::connect_from(local_addr, addr)
+            .await
+            .map(TcpStream)
+    }
+
+    /// Registers an existing `std` TCP stream in the reactor of current [`Context`].
+    ///
+    /// This can be used with connections received from another process.
+    ///
+    /// # Panic
+    ///
+    /// Panics if called out of a [`Context`].
+    ///
+    /// [`Context`]: super::Context
+    pub fn from_std(stream: std::net::TcpStream) -> io::Result<TcpStream> {
+        Async::new(stream).map(TcpStream)
+    }
+
+    /// Deregisters the stream from the reactor and returns the `std` TCP stream.
+    pub fn into_std(self) -> io::Result<std::net::TcpStream> {
+        self.0.into_inner()
+    }
+
+    /// Returns a reference to the inner `std` TCP stream.
+    ///
+    /// This can be used to get or set socket options which are not handled by this type.
+    pub fn get_ref(&self) -> &std::net::TcpStream {
+        self.0.get_ref()
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.get_ref().local_addr()
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.0.get_ref().peer_addr()
+    }
+
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.0.get_ref().set_nodelay(nodelay)
+    }
+
+    /// Reads some bytes from the stream.
+    ///
+    /// Returns the number of bytes read, `0` meaning the peer closed its write side.
+    pub async fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read_with(|mut io| io.read(buf)).await
+    }
+
+    /// Reads data from the stream without removing it from the buffer.
+    pub async fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.peek(buf).await
+    }
+
+    /// Writes some bytes to the stream.
+    ///
+    /// Returns the number of bytes written, which can be less than `buf.len()`.
+    pub async fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write_with(|mut io| io.write(buf)).await
+    }
+
+    /// Writes all the bytes from `buf` to the stream.
+    pub async fn write_all(&self, mut buf: &[u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.write(buf).await? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                n => buf = &buf[n..],
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Shuts down the read, write, or both halves of the connection.
+    ///
+    /// Shutting down the write half notifies the peer that no more data will be sent.
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.0.get_ref().shutdown(how)
+    }
+}
+
+impl AsRef<std::net::TcpStream> for TcpStream {
+    fn as_ref(&self) -> &std::net::TcpStream {
+        self.0.get_ref()
+    }
+}
+
+impl TryFrom<std::net::TcpStream> for TcpStream {
+    type Error = io::Error;
+
+    fn try_from(stream: std::net::TcpStream) -> io::Result<Self> {
+        TcpStream::from_std(stream)
+    }
+}
+
+impl AsyncRead for TcpStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncRead for &TcpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TcpStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_close(cx)
+    }
+}
+
+impl AsyncWrite for &TcpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut &self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut &self.0).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut &self.0).poll_close(cx)
+    }
+}
+
+#[cfg(unix)]
+impl AsFd for TcpListener {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for TcpListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+#[cfg(unix)]
+impl AsFd for TcpStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+#[cfg(windows)]
+impl AsSocket for TcpListener {
+    fn as_socket(&self) -> BorrowedSocket<'_> {
+        self.0.as_socket()
+    }
+}
+
+#[cfg(windows)]
+impl AsRawSocket for TcpListener {
+    fn as_raw_socket(&self) -> RawSocket {
+        self.0.as_raw_socket()
+    }
+}
+
+#[cfg(windows)]
+impl AsSocket for TcpStream {
+    fn as_socket(&self) -> BorrowedSocket<'_> {
+        self.0.as_socket()
+    }
+}
+
+#[cfg(windows)]
+impl AsRawSocket for TcpStream {
+    fn as_raw_socket(&self) -> RawSocket {
+        self.0.as_raw_socket()
+    }
+}
+
+fn with_option(err: io::Error, option: &str) -> io::Error {
+    io::Error::new(err.kind(), format!("failed to set {option}: {err}"))
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "linux",
+    target_os = "android",
+    target_os = "aix",
+    target_os = "fuchsia",
+    target_os = "haiku",
+    target_env = "newlib"
+))]
+fn set_tos(socket: &Socket, tos: u8, is_ipv6: bool) -> io::Result<()> {
+    use rustix::net::sockopt;
+
+    sockopt::set_ip_tos(socket, tos)?;
+
+    if is_ipv6 {
+        sockopt::set_ipv6_tclass(socket, tos as u32)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "linux",
+    target_os = "android",
+    target_os = "aix",
+    target_os = "fuchsia",
+    target_os = "haiku",
+    target_env = "newlib"
+)))]
+fn set_tos(_socket: &Socket, _tos: u8, _is_ipv6: bool) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::runtime::Context;
+
+    #[test]
+    fn udp_send_recv() {
+        gst::init().unwrap();
+
+        let context = Context::acquire("net_udp_send_recv", Duration::from_millis(2)).unwrap();
+
+        let (receiver, sender) = context
+            .enter(|| {
+                let receiver = UdpSocket::builder()
+                    .reuse_address(true)
+                    .recv_buffer_size(64 * 1024)
+                    .bind((Ipv4Addr::LOCALHOST, 0))?;
+                let sender = UdpSocket::builder()
+                    .ttl(12)
+                    .tos(0x2e << 2)
+                    .bind((Ipv4Addr::LOCALHOST, 0))?;
+                Ok::<_, io::Error>((receiver, sender))
+            })
+            .unwrap();
+
+        assert_eq!(sender.get_ref().ttl().unwrap(), 12);
+
+        let receiver_addr = receiver.local_addr().unwrap();
+        let sender_addr = sender.local_addr().unwrap();
+
+        let (len, from) = futures::executor::block_on(context.spawn(async move {
+            sender.send_to(&[1, 2, 3], receiver_addr).await.unwrap();
+
+            let mut buf = [0; 16];
+            let res = receiver.recv_from(&mut buf).await.unwrap();
+            assert_eq!(&buf[..res.0], &[1, 2, 3]);
+
+            // Connected socket only receives from its peer
+            receiver.connect(sender_addr).await.unwrap();
+            assert_eq!(receiver.peer_addr().unwrap(), sender_addr);
+            sender.send_to(&[4, 5], receiver_addr).await.unwrap();
+            let len = receiver.recv(&mut buf).await.unwrap();
+            assert_eq!(&buf[..len], &[4, 5]);
+
+            res
+        }))
+        .unwrap();
+
+        assert_eq!(len, 3);
+        assert_eq!(from, sender_addr);
+    }
+
+    #[test]
+    fn udp_from_std() {
+        gst::init().unwrap();
+
+        let context = Context::acquire("net_udp_from_std", Duration::from_millis(2)).unwrap();
+
+        let std_socket = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let addr = std_socket.local_addr().unwrap();
+
+        let socket = context.enter(|| UdpSocket::try_from(std_socket)).unwrap();
+        assert_eq!(socket.local_addr().unwrap(), addr);
+
+        let std_socket = context.enter(|| socket.into_std()).unwrap();
+        assert_eq!(std_socket.local_addr().unwrap(), addr);
+    }
+
+    #[test]
+    fn tcp_echo() {
+        use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+        gst::init().unwrap();
+
+        let context = Context::acquire("net_tcp_echo", Duration::from_millis(2)).unwrap();
+
+        let listener = context
+            .enter(|| TcpListener::bind((Ipv4Addr::LOCALHOST, 0)))
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Server & client run on the same Context
+        let server = context.spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            assert_eq!(stream.peer_addr().unwrap(), peer);
+
+            let mut buf = [0; 4];
+            let mut received = Vec::new();
+            loop {
+                match stream.read(&mut buf).await.unwrap() {
+                    0 => break,
+                    len => received.extend_from_slice(&buf[..len]),
+                }
+            }
+
+            stream.write_all(&received).await.unwrap();
+            stream.shutdown(Shutdown::Write).unwrap();
+
+            received.len()
+        });
+
+        let client = context.spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.set_nodelay(true).unwrap();
+
+            stream.write_all(b"threadshare echo").await.unwrap();
+            stream.shutdown(Shutdown::Write).unwrap();
+
+            let mut echoed = Vec::new();
+            stream.read_to_end(&mut echoed).await.unwrap();
+
+            echoed
+        });
+
+        assert_eq!(futures::executor::block_on(server).unwrap(), 16);
+        assert_eq!(
+            futures::executor::block_on(client).unwrap(),
+            b"threadshare echo"
+        );
+    }
+
+    #[test]
+    fn tcp_connect_cancelled() {
+        gst::init().unwrap();
+
+        let context =
+            Context::acquire("net_tcp_connect_cancelled", Duration::from_millis(2)).unwrap();
+
+        let std_listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let addr = std_listener.local_addr().unwrap();
+
+        let listener = context
+            .enter(|| TcpListener::try_from(std_listener))
+            .unwrap();
+        assert_eq!(listener.local_addr().unwrap(), addr);
+
+        futures::executor::block_on(context.spawn(async move {
+            // Dropping the pending future cancels the connection attempt
+            let connect = TcpStream::connect(addr);
+            futures::pin_mut!(connect);
+            let _ = futures::poll!(connect.as_mut());
+            drop(connect);
+
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let local_addr = stream.local_addr().unwrap();
+
+            // The cancelled attempt might already be queued by the listener
+            let accepted = loop {
+                let (accepted, peer) = listener.accept().await.unwrap();
+                if peer == local_addr {
+                    break accepted;
+                }
+            };
+
+            stream.write_all(&[1, 2, 3]).await.unwrap();
+            let mut buf = [0; 3];
+            let mut read = 0;
+            while read < buf.len() {
+                read += accepted.read(&mut buf[read..]).await.unwrap();
+            }
+            assert_eq!(buf, [1, 2, 3]);
+        }))
+        .unwrap();
+    }
+}
@@ -69,18 +69,19 @@
 //! [`Context`]: ../executor/struct.Context.html
 
 use futures::future;
-use futures::future::BoxFuture;
+use futures::future::{AbortHandle, BoxFuture};
 use futures::prelude::*;
 
 use gst::prelude::*;
 use gst::subclass::prelude::*;
 use gst::{FlowError, FlowSuccess};
 
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::ops::Deref;
-use std::sync::{Arc, Weak};
+use std::sync::{Arc, Mutex, Weak};
 
-use super::executor::{self, Context};
+use super::executor::{self, Context, SubTaskOutput, TaskId};
 use super::RUNTIME_CAT;
 
 #[inline]
@@ -206,6 +207,8 @@ pub trait PadSrcHandler: Clone + Send + Sync + 'static {
 #[derive(Debug)]
 pub struct PadSrcInner {
     gst_pad: gst::Pad,
+    /// Sub tasks added by downstream while pushing through this pad.
+    sub_task_abort_handles: Mutex<Vec<AbortHandle>>,
 }
 
 impl PadSrcInner {
@@ -214,16 +217,70 @@ impl PadSrcInner {
             panic!("Wrong pad direction for PadSrc");
         }
 
-        PadSrcInner { gst_pad }
+        PadSrcInner {
+            gst_pad,
+            sub_task_abort_handles: Mutex::new(Vec::new()),
+        }
     }
 
     pub fn gst_pad(&self) -> &gst::Pad {
         &self.gst_pad
     }
 
+    /// Returns the current task along with the number of sub tasks it already had
+    /// before pushing through this pad.
+    fn sub_tasks_mark() -> Option<(Context, TaskId, usize)> {
+        Context::current_task().map(|(ctx, task_id)| {
+            let n_added = ctx.n_added_sub_tasks(task_id);
+            (ctx, task_id, n_added)
+        })
+    }
+
+    /// Drains the sub tasks of the current task.
+    ///
+    /// The sub tasks added by downstream since `mark` are aborted
+    /// if the pad is deactivated in the meantime.
+    async fn drain_sub_tasks(&self, mark: Option<(Context, TaskId, usize)>) -> SubTaskOutput {
+        let Some((ctx, task_id, n_added)) = mark else {
+            return Ok(());
+        };
+
+        let abort_handles = ctx.added_sub_task_abort_handles(task_id, n_added);
+        if !abort_handles.is_empty() {
+            self.sub_task_abort_handles
+                .lock()
+                .unwrap()
+                .extend(abort_handles);
+        }
+
+        let res = Context::drain_sub_tasks().await;
+        self.sub_task_abort_handles.lock().unwrap().clear();
+
+        res
+    }
+
+    /// Aborts the sub tasks added by downstream while pushing through this pad.
+    fn abort_sub_tasks(&self) {
+        let abort_handles = std::mem::take(&mut *self.sub_task_abort_handles.lock().unwrap());
+        if abort_handles.is_empty() {
+            return;
+        }
+
+        gst::debug!(
+            RUNTIME_CAT,
+            obj = self.gst_pad,
+            "Aborting {} pending sub tasks",
+            abort_handles.len()
+        );
+        for abort_handle in abort_handles {
+            abort_handle.abort();
+        }
+    }
+
     pub async fn push(&self, buffer: gst::Buffer) -> Result<FlowSuccess, FlowError> {
         gst::log!(RUNTIME_CAT, obj = self.gst_pad, "Pushing {:?}", buffer);
 
+        let mark = Self::sub_tasks_mark();
         let success = self.gst_pad.push(buffer).inspect_err(|&err| {
             gst::error!(
                 RUNTIME_CAT,
@@ -238,7 +295,7 @@ impl PadSrcInner {
             obj = self.gst_pad,
             "Processing any pending sub tasks"
         );
-        Context::drain_sub_tasks().await?;
+        self.drain_sub_tasks(mark).await?;
 
         Ok(success)
     }
@@ -246,6 +303,7 @@ impl PadSrcInner {
     pub async fn push_list(&self, list: gst::BufferList) -> Result<FlowSuccess, FlowError> {
         gst::log!(RUNTIME_CAT, obj = self.gst_pad, "Pushing {:?}", list);
 
+        let mark = Self::sub_tasks_mark();
         let success = self.gst_pad.push_list(list).inspect_err(|&err| {
             gst::error!(
                 RUNTIME_CAT,
@@ -260,7 +318,7 @@ impl PadSrcInner {
             obj = self.gst_pad,
             "Processing any pending sub tasks"
         );
-        Context::drain_sub_tasks().await?;
+        self.drain_sub_tasks(mark).await?;
 
         Ok(success)
     }
@@ -268,6 +326,7 @@ impl PadSrcInner {
     pub async fn push_event(&self, event: gst::Event) -> bool {
         gst::log!(RUNTIME_CAT, obj = self.gst_pad, "Pushing {:?}", event);
 
+        let mark = Self::sub_tasks_mark();
         let was_handled = self.gst_pad.push_event(event);
 
         gst::log!(
@@ -275,7 +334,7 @@ impl PadSrcInner {
             obj = self.gst_pad,
             "Processing any pending sub tasks"
         );
-        if Context::drain_sub_tasks().await.is_err() {
+        if self.drain_sub_tasks(mark).await.is_err() {
             return false;
         }
 
@@ -392,10 +451,12 @@ impl PadSrc {
                 });
 
             let handler_clone = handler.clone();
+            let this_weak = Arc::downgrade(&self.0);
             self.0
                 .gst_pad
                 .set_activatemode_function(move |gst_pad, parent, mode, active| {
                     let handler = handler_clone.clone();
+                    let this_weak = this_weak.clone();
                     H::ElementImpl::catch_panic_pad_function(
                         parent,
                         || {
@@ -426,6 +487,12 @@ impl PadSrc {
                                 ));
                             }
 
+                            if !active {
+                                if let Some(this) = this_weak.upgrade() {
+                                    this.abort_sub_tasks();
+                                }
+                            }
+
                             H::src_activatemode(handler, gst_pad, imp, mode, active)
                         },
                     )
@@ -659,9 +726,17 @@ pub trait PadSinkHandler: Clone + Send + Sync + 'static {
     }
 }
 
+/// Sub tasks added by a [`PadSink`] to the upstream task.
+#[derive(Debug, Default)]
+struct PadSinkSubTasks {
+    next_id: u64,
+    abort_handles: HashMap<u64, AbortHandle>,
+}
+
 #[derive(Debug)]
 pub struct PadSinkInner {
     gst_pad: gst::Pad,
+    sub_tasks: Mutex<PadSinkSubTasks>,
 }
 
 impl PadSinkInner {
@@ -670,12 +745,73 @@ impl PadSinkInner {
             panic!("Wrong pad direction for PadSink");
         }
 
-        PadSinkInner { gst_pad }
+        PadSinkInner {
+            gst_pad,
+            sub_tasks: Mutex::new(PadSinkSubTasks::default()),
+        }
     }
 
     pub fn gst_pad(&self) -> &gst::Pad {
         &self.gst_pad
     }
+
+    /// Adds `sub_task` to the upstream task so that it can be aborted
+    /// when the pad is deactivated.
+    fn add_sub_task<T>(self: &Arc<Self>, ctx: &Context, task_id: TaskId, sub_task: T)
+    where
+        T: Future<Output = SubTaskOutput> + Send + 'static,
+    {
+        struct Untrack(Weak<PadSinkInner>, u64);
+
+        impl Drop for Untrack {
+            fn drop(&mut self) {
+                if let Some(this) = self.0.upgrade() {
+                    this.sub_tasks.lock().unwrap().abort_handles.remove(&self.1);
+                }
+            }
+        }
+
+        let id = {
+            let mut sub_tasks = self.sub_tasks.lock().unwrap();
+            sub_tasks.next_id += 1;
+            sub_tasks.next_id
+        };
+
+        // Stop tracking the sub task as soon as it's done or dropped
+        let untrack = Untrack(Arc::downgrade(self), id);
+        let sub_task = async move {
+            let _untrack = untrack;
+            sub_task.await
+        };
+
+        // The sub task can't be executed before we return
+        // since it's up to the current task to drain it.
+        if let Ok(abort_handle) = ctx.add_sub_task(task_id, sub_task) {
+            self.sub_tasks
+                .lock()
+                .unwrap()
+                .abort_handles
+                .insert(id, abort_handle);
+        }
+    }
+
+    /// Aborts the sub tasks added by this pad which are still pending.
+    fn abort_sub_tasks(&self) {
+        let abort_handles = std::mem::take(&mut self.sub_tasks.lock().unwrap().abort_handles);
+        if abort_handles.is_empty() {
+            return;
+        }
+
+        gst::debug!(
+            RUNTIME_CAT,
+            obj = self.gst_pad,
+            "Aborting {} pending sub tasks",
+            abort_handles.len()
+        );
+        for abort_handle in abort_handles.into_values() {
+            abort_handle.abort();
+        }
+    }
 }
 
 /// A [`PadSink`] which can be moved in `Handler`s functions and `Future`s.
@@ -791,10 +927,12 @@ impl PadSink {
                 });
 
             let handler_clone = handler.clone();
+            let this_weak = Arc::downgrade(&self.0);
             self.0
                 .gst_pad
                 .set_activatemode_function(move |gst_pad, parent, mode, active| {
                     let handler = handler_clone.clone();
+                    let this_weak = this_weak.clone();
                     H::ElementImpl::catch_panic_pad_function(
                         parent,
                         || {
@@ -829,16 +967,24 @@ impl PadSink {
                                 ));
                             }
 
+                            if !active {
+                                if let Some(this) = this_weak.upgrade() {
+                                    this.abort_sub_tasks();
+                                }
+                            }
+
                             H::sink_activatemode(handler, gst_pad, imp, mode, active)
                         },
                     )
                 });
 
             let handler_clone = handler.clone();
+            let this_weak = Arc::downgrade(&self.0);
             self.0
                 .gst_pad
                 .set_chain_function(move |gst_pad, parent, buffer| {
                     let handler = handler_clone.clone();
+                    let this_weak = this_weak.clone();
                     H::ElementImpl::catch_panic_pad_function(
                         parent,
                         || Err(FlowError::Error),
@@ -850,8 +996,13 @@ impl PadSink {
                                 let delayed_fut = async move {
                                     H::sink_chain(handler, gst_pad, elem, buffer).await
                                 };
-                                let _ =
-                                    ctx.add_sub_task(task_id, delayed_fut.map(|res| res.map(drop)));
+                                if let Some(this) = this_weak.upgrade() {
+                                    this.add_sub_task(
+                                        &ctx,
+                                        task_id,
+                                        delayed_fut.map(|res| res.map(drop)),
+                                    );
+                                }
 
                                 Ok(gst::FlowSuccess::Ok)
                             } else {
@@ -863,10 +1014,12 @@ impl PadSink {
                 });
 
             let handler_clone = handler.clone();
+            let this_weak = Arc::downgrade(&self.0);
             self.0
                 .gst_pad
                 .set_chain_list_function(move |gst_pad, parent, list| {
                     let handler = handler_clone.clone();
+                    let this_weak = this_weak.clone();
                     H::ElementImpl::catch_panic_pad_function(
                         parent,
                         || Err(FlowError::Error),
@@ -878,8 +1031,13 @@ impl PadSink {
                                 let delayed_fut = async move {
                                     H::sink_chain_list(handler, gst_pad, elem, list).await
                                 };
-                                let _ =
-                                    ctx.add_sub_task(task_id, delayed_fut.map(|res| res.map(drop)));
+                                if let Some(this) = this_weak.upgrade() {
+                                    this.add_sub_task(
+                                        &ctx,
+                                        task_id,
+                                        delayed_fut.map(|res| res.map(drop)),
+                                    );
+                                }
 
                                 Ok(gst::FlowSuccess::Ok)
                             } else {
@@ -894,10 +1052,12 @@ impl PadSink {
             // No need to `set_event_function` since `set_event_full_function`
             // overrides it and dispatches to `sink_event` when necessary
             let handler_clone = handler.clone();
+            let this_weak = Arc::downgrade(&self.0);
             self.0
                 .gst_pad
                 .set_event_full_function(move |gst_pad, parent, event| {
                     let handler = handler_clone.clone();
+                    let this_weak = this_weak.clone();
                     H::ElementImpl::catch_panic_pad_function(
                         parent,
                         || Err(FlowError::Error),
@@ -911,10 +1071,13 @@ impl PadSink {
                                         H::sink_event_full_serialized(handler, gst_pad, elem, event)
                                             .await
                                     };
-                                    let _ = ctx.add_sub_task(
-                                        task_id,
-                                        delayed_fut.map(|res| res.map(drop)),
-                                    );
+                                    if let Some(this) = this_weak.upgrade() {
+                                        this.add_sub_task(
+                                            &ctx,
+                                            task_id,
+                                            delayed_fut.map(|res| res.map(drop)),
+                                        );
+                                    }
 
                                     Ok(gst::FlowSuccess::Ok)
                                 } else {
@@ -938,13 +1101,15 @@ impl PadSink {
                         parent,
                         || false,
                         move |imp| {
-                            if !query.is_serialized() {
+                            // Serialized queries can only be handled synchronously
+                            // if this doesn't block a Context thread
+                            if !query.is_serialized() || Context::current_task().is_none() {
                                 H::sink_query(handler, gst_pad, imp, query)
                             } else {
                                 gst::fixme!(
                                     RUNTIME_CAT,
                                     obj = gst_pad,
-                                    "Serialized Query not supported"
+                                    "Serialized Query not supported from a Context thread"
                                 );
                                 false
                             }
@@ -0,0 +1,395 @@
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+//! Helpers to implement thread-sharing sources.
+//!
+//! Most `ts` sources share the same boilerplate: push the stream-start, caps & segment
+//! events before the first item, push a new segment after a flush, push EOS or post an
+//! error when the [`Task`] loop or a transition fails and map the `Element` state changes
+//! to [`Task`] transitions.
+//!
+//! [`SrcTaskAdapter`] wraps a [`SrcTaskImpl`], which only needs to produce the items
+//! to push and optionally the caps, and takes care of the stream prelude and loop errors.
+//! [`TsSrcImpl`] provides the state change handling for the `Element`.
+//!
+//! See `examples/ts_countersrc.rs` for a minimal source based on these helpers.
+
+use futures::future::{self, BoxFuture};
+use futures::prelude::*;
+
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+
+use super::task::{self, TaskImpl};
+use super::{PadSrcRef, Task, TaskState, RUNTIME_CAT};
+
+/// An item produced by a [`SrcTaskImpl`], to be pushed downstream.
+#[derive(Debug)]
+pub enum SrcItem {
+    Buffer(gst::Buffer),
+    BufferList(gst::BufferList),
+    Event(gst::Event),
+}
+
+/// The `Element` specific part of a thread-sharing source `Task`.
+///
+/// Wrap it in a [`SrcTaskAdapter`] to get a [`TaskImpl`].
+///
+/// The transition functions are called by the [`SrcTaskAdapter`] after it updated
+/// its own state. Their default implementation does nothing.
+pub trait SrcTaskImpl: Send + 'static {
+    /// Returns the `Element` this `Task` belongs to.
+    fn element(&self) -> &gst::Element;
+
+    /// Returns the `PadSrc` the items are pushed to.
+    fn src_pad(&self) -> PadSrcRef<'_>;
+
+    /// Returns the caps to push after the stream-start event, if any.
+    fn caps(&mut self) -> Option<gst::Caps> {
+        None
+    }
+
+    /// Called after the caps returned by [`Self::caps`] were pushed.
+    fn caps_pushed(&mut self, _caps: &gst::Caps) {}
+
+    /// Called when downstream requested a reconfiguration after the caps were pushed.
+    fn reconfigure(&mut self) {}
+
+    /// Returns the segment to push after the caps and after each flush.
+    fn segment(&mut self) -> gst::Segment {
+        gst::FormattedSegment::<gst::format::Time>::new().upcast()
+    }
+
+    /// Retrieves the next item to push.
+    ///
+    /// This can be cancelled at `await` points, see [`TaskImpl::try_next`].
+    ///
+    /// Return `Err(gst::FlowError::Eos)` in order to push EOS and stop the `Task`.
+    fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>>;
+
+    fn prepare(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        future::ok(()).boxed()
+    }
+
+    fn unprepare(&mut self) -> BoxFuture<'_, ()> {
+        future::ready(()).boxed()
+    }
+
+    fn start(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        future::ok(()).boxed()
+    }
+
+    fn pause(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        future::ok(()).boxed()
+    }
+
+    fn flush_start(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        future::ok(()).boxed()
+    }
+
+    fn flush_stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        future::ok(()).boxed()
+    }
+
+    fn stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        future::ok(()).boxed()
+    }
+}
+
+/// A [`TaskImpl`] pushing the items produced by a [`SrcTaskImpl`].
+///
+/// The stream-start, caps & segment events are pushed before the first item.
+/// A new segment is pushed after each flush and the whole prelude is pushed
+/// again after the `Task` was stopped. Transition errors, e.g. a socket which
+/// can't be bound in [`SrcTaskImpl::prepare`], are posted on the `Element`.
+#[derive(Debug)]
+pub struct SrcTaskAdapter<T> {
+    inner: T,
+    need_initial_events: bool,
+    need_segment: bool,
+}
+
+impl<T: SrcTaskImpl> SrcTaskAdapter<T> {
+    pub fn new(inner: T) -> Self {
+        SrcTaskAdapter {
+            inner,
+            need_initial_events: true,
+            need_segment: true,
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    async fn push_prelude(&mut self) {
+        if self.need_initial_events {
+            gst::debug!(
+                RUNTIME_CAT,
+                obj = self.inner.element(),
+                "Pushing initial events"
+            );
+
+            let stream_id = format!("{:08x}{:08x}", rand::random::<u32>(), rand::random::<u32>());
+            let stream_start_evt = gst::event::StreamStart::builder(&stream_id)
+                .group_id(gst::GroupId::next())
+                .build();
+            self.inner.src_pad().push_event(stream_start_evt).await;
+
+            if let Some(caps) = self.inner.caps() {
+                self.inner
+                    .src_pad()
+                    .push_event(gst::event::Caps::new(&caps))
+                    .await;
+                self.inner.caps_pushed(&caps);
+            }
+
+            self.need_initial_events = false;
+        } else if self.inner.src_pad().gst_pad().check_reconfigure() {
+            gst::debug!(RUNTIME_CAT, obj = self.inner.element(), "Reconfiguring");
+            self.inner.reconfigure();
+        }
+
+        if self.need_segment {
+            let segment_evt = gst::event::Segment::new(&self.inner.segment());
+            self.inner.src_pad().push_event(segment_evt).await;
+
+            self.need_segment = false;
+        }
+    }
+}
+
+impl<T: SrcTaskImpl> TaskImpl for SrcTaskAdapter<T> {
+    type Item = SrcItem;
+
+    fn prepare(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        self.inner.prepare()
+    }
+
+    fn unprepare(&mut self) -> BoxFuture<'_, ()> {
+        self.inner.unprepare()
+    }
+
+    fn start(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        self.inner.start()
+    }
+
+    fn try_next(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
+        self.inner.iterate()
+    }
+
+    fn handle_item(&mut self, item: SrcItem) -> BoxFuture<'_, Result<(), gst::FlowError>> {
+        async move {
+            self.push_prelude().await;
+
+            gst::log!(RUNTIME_CAT, obj = self.inner.element(), "Pushing {item:?}");
+            let pad = self.inner.src_pad();
+            match item {
+                SrcItem::Buffer(buffer) => {
+                    pad.push(buffer).await?;
+                }
+                SrcItem::BufferList(list) => {
+                    pad.push_list(list).await?;
+                }
+                SrcItem::Event(event) => {
+                    pad.push_event(event).await;
+                }
+            }
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn pause(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        self.inner.pause()
+    }
+
+    fn flush_start(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        self.need_segment = true;
+        self.inner.flush_start()
+    }
+
+    fn flush_stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        self.inner.flush_stop()
+    }
+
+    fn stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        self.need_initial_events = true;
+        self.need_segment = true;
+        self.inner.stop()
+    }
+
+    fn handle_action_error(
+        &mut self,
+        trigger: task::Trigger,
+        state: TaskState,
+        err: gst::ErrorMessage,
+    ) -> BoxFuture<'_, task::Trigger> {
+        async move {
+            gst::error!(
+                RUNTIME_CAT,
+                obj = self.inner.element(),
+                "{trigger:?} from {state:?} failed: {err:?}"
+            );
+            self.inner.element().post_error_message(err);
+
+            task::Trigger::Error
+        }
+        .boxed()
+    }
+
+    fn handle_loop_error(&mut self, err: gst::FlowError) -> BoxFuture<'_, task::Trigger> {
+        async move {
+            match err {
+                gst::FlowError::Flushing => {
+                    gst::debug!(RUNTIME_CAT, obj = self.inner.element(), "Flushing");
+
+                    task::Trigger::FlushStart
+                }
+                gst::FlowError::Eos => {
+                    gst::debug!(RUNTIME_CAT, obj = self.inner.element(), "EOS");
+
+                    self.push_prelude().await;
+                    self.inner
+                        .src_pad()
+                        .push_event(gst::event::Eos::new())
+                        .await;
+
+                    task::Trigger::Stop
+                }
+                err => {
+                    gst::error!(RUNTIME_CAT, obj = self.inner.element(), "Got error {err}");
+                    gst::element_error!(
+                        self.inner.element(),
+                        gst::StreamError::Failed,
+                        ("Internal data stream error"),
+                        ["streaming stopped, reason {}", err]
+                    );
+
+                    task::Trigger::Error
+                }
+            }
+        }
+        .boxed()
+    }
+
+    fn handle_loop_panic(&mut self, panic_msg: String) -> BoxFuture<'_, task::Trigger> {
+        async move {
+            gst::error!(
+                RUNTIME_CAT,
+                obj = self.inner.element(),
+                "Task loop panicked: {panic_msg}"
+            );
+            gst::element_error!(
+                self.inner.element(),
+                gst::CoreError::Failed,
+                ("Internal data stream error"),
+                ["streaming task panicked: {}", panic_msg]
+            );
+
+            task::Trigger::Error
+        }
+        .boxed()
+    }
+}
+
+/// State changes handling for thread-sharing sources.
+///
+/// Call [`Self::src_change_state`] from `ElementImpl::change_state`.
+///
+/// The [`Task`] is prepared in `NullToReady`, started in `PausedToPlaying`
+/// and paused in `PlayingToPaused`. As live sources, `ReadyToPaused` and
+/// `PlayingToPaused` return `NoPreroll`.
+///
+/// The start & stop transitions don't block when the state change is
+/// requested from a `Context` thread, e.g. by an element sharing the `Context`.
+pub trait TsSrcImpl: ElementImpl {
+    /// Returns the [`Task`] driving this source.
+    fn task(&self) -> &Task;
+
+    /// Acquires the `Context` and prepares the [`Task`], e.g. with a [`SrcTaskAdapter`].
+    fn prepare(&self) -> Result<(), gst::ErrorMessage>;
+
+    /// Unprepares the [`Task`] and releases the resources.
+    fn unprepare(&self) {
+        self.task().unprepare().block_on().unwrap();
+    }
+
+    fn src_change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        gst::trace!(RUNTIME_CAT, imp = self, "Changing state {transition:?}");
+
+        match transition {
+            gst::StateChange::NullToReady => {
+                gst::debug!(RUNTIME_CAT, imp = self, "Preparing");
+                self.prepare().map_err(|err| {
+                    self.post_error_message(err);
+                    gst::StateChangeError
+                })?;
+                gst::debug!(RUNTIME_CAT, imp = self, "Prepared");
+            }
+            gst::StateChange::PlayingToPaused => {
+                gst::debug!(RUNTIME_CAT, imp = self, "Pausing");
+                self.task()
+                    .pause()
+                    .block_on()
+                    .map_err(|_| gst::StateChangeError)?;
+                gst::debug!(RUNTIME_CAT, imp = self, "Paused");
+            }
+            gst::StateChange::ReadyToNull => {
+                gst::debug!(RUNTIME_CAT, imp = self, "Unpreparing");
+                self.unprepare();
+                gst::debug!(RUNTIME_CAT, imp = self, "Unprepared");
+            }
+            _ => (),
+        }
+
+        let mut success = self.parent_change_state(transition)?;
+
+        match transition {
+            gst::StateChange::ReadyToPaused | gst::StateChange::PlayingToPaused => {
+                success = gst::StateChangeSuccess::NoPreroll;
+            }
+            gst::StateChange::PausedToPlaying => {
+                gst::debug!(RUNTIME_CAT, imp = self, "Starting");
+                self.task()
+                    .start()
+                    .await_maybe_on_context()
+                    .map_err(|_| gst::StateChangeError)?;
+                gst::debug!(RUNTIME_CAT, imp = self, "Started");
+            }
+            gst::StateChange::PausedToReady => {
+                gst::debug!(RUNTIME_CAT, imp = self, "Stopping");
+                self.task()
+                    .stop()
+                    .await_maybe_on_context()
+                    .map_err(|_| gst::StateChangeError)?;
+                gst::debug!(RUNTIME_CAT, imp = self, "Stopped");
+            }
+            _ => (),
+        }
+
+        Ok(success)
+    }
+}
@@ -25,8 +25,10 @@ use futures::channel::oneshot;
 use futures::future::{self, BoxFuture};
 use futures::prelude::*;
 
+use std::any::Any;
 use std::fmt;
 use std::ops::Deref;
+use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::task::Poll;
@@ -410,6 +412,25 @@ pub trait TaskImpl: Send + 'static {
         .boxed()
     }
 
+    /// Handles a panic occurring during the execution of the `Task` loop.
+    ///
+    /// This includes panics in [`Self::try_next`] & [`Self::handle_item`].
+    /// The panic is caught so that it doesn't take down the [`Context`] thread,
+    /// along with the other `Task`s sharing the `Context`.
+    ///
+    /// Implementations might use `gst::Element::post_error_message`
+    /// and return `Trigger::Error`.
+    ///
+    /// Default is to `gst::error` log and to hand a `FlowError::Error`
+    /// over to [`Self::handle_loop_error`].
+    fn handle_loop_panic(&mut self, panic_msg: String) -> BoxFuture<'_, Trigger> {
+        async move {
+            gst::error!(RUNTIME_CAT, "Task loop panicked: {}", panic_msg);
+            self.handle_loop_error(gst::FlowError::Error).await
+        }
+        .boxed()
+    }
+
     /// Handles an error occurring during the execution of a transition action.
     ///
     /// This handler also catches errors returned by subtasks spawned by the transition action.
@@ -441,6 +462,33 @@ pub trait TaskImpl: Send + 'static {
     }
 }
 
+/// An error which interrupted the `Task` loop.
+#[derive(Debug)]
+enum LoopError {
+    Flow(gst::FlowError),
+    Panic(String),
+}
+
+impl From<gst::FlowError> for LoopError {
+    fn from(err: gst::FlowError) -> Self {
+        LoopError::Flow(err)
+    }
+}
+
+impl LoopError {
+    fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        let panic_msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+            msg.to_string()
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+
+        LoopError::Panic(panic_msg)
+    }
+}
+
 type AckSender = oneshot::Sender<Result<TransitionOk, TransitionError>>;
 type AckReceiver = oneshot::Receiver<Result<TransitionOk, TransitionError>>;
 
@@ -514,6 +562,7 @@ impl StateMachineHandle {
 
 #[derive(Debug)]
 struct TaskInner {
+    name: Option<Arc<str>>,
     state: TaskState,
     state_machine_handle: Option<StateMachineHandle>,
 }
@@ -521,6 +570,7 @@ struct TaskInner {
 impl Default for TaskInner {
     fn default() -> Self {
         TaskInner {
+            name: None,
             state: TaskState::Unprepared,
             state_machine_handle: None,
         }
@@ -622,6 +672,28 @@ impl Default for Task {
 }
 
 impl Task {
+    /// Sets the name of the `Task`, typically the name of the owning element.
+    ///
+    /// The name identifies the `Task` in [`Context`] introspection.
+    ///
+    /// [`Context`]: ../executor/struct.Context.html
+    pub fn set_name(&self, name: impl Into<Arc<str>>) {
+        let mut inner = self.0.lock().unwrap();
+        let name = name.into();
+
+        if let Some(state_machine) = inner.state_machine_handle.as_ref() {
+            state_machine
+                .context
+                .set_task_name(state_machine.join_handle.task_id(), Arc::clone(&name));
+        }
+
+        inner.name = Some(name);
+    }
+
+    pub fn name(&self) -> Option<Arc<str>> {
+        self.0.lock().unwrap().name.clone()
+    }
+
     pub fn state(&self) -> TaskState {
         self.0.lock().unwrap().state
     }
@@ -663,11 +735,14 @@ impl Task {
         inner.state = TaskState::Preparing;
 
         gst::log!(RUNTIME_CAT, "Spawning task state machine");
-        inner.state_machine_handle = Some(StateMachine::spawn(
-            self.0.clone(),
-            Box::new(task_impl),
-            context,
-        ));
+        let state_machine_handle =
+            StateMachine::spawn(self.0.clone(), Box::new(task_impl), context);
+        if let Some(name) = inner.name.as_ref() {
+            state_machine_handle
+                .context
+                .set_task_name(state_machine_handle.join_handle.task_id(), Arc::clone(name));
+        }
+        inner.state_machine_handle = Some(state_machine_handle);
 
         let ack_rx = match inner.trigger(Trigger::Prepare) {
             Ok(ack_rx) => ack_rx,
@@ -914,9 +989,10 @@ impl<Item: Send + 'static> StateMachine<Item> {
 
             match triggering_evt.trigger {
                 Trigger::Error => {
-                    let mut task_inner = task_inner.lock().unwrap();
-                    task_inner.switch_to_err(triggering_evt);
+                    task_inner.lock().unwrap().switch_to_err(triggering_evt);
                     gst::trace!(RUNTIME_CAT, "Switched to Error");
+
+                    Context::dump_all();
                 }
                 Trigger::Start => {
                     let origin = {
@@ -1105,17 +1181,17 @@ impl<Item: Send + 'static> StateMachine<Item> {
             }
         }
 
-        match self.run_loop().await {
-            Ok(()) => (),
-            Err(err) => {
-                let next_trigger = self.task_impl.handle_loop_error(err).await;
-                let (triggering_evt, _) = TriggeringEvent::new(next_trigger);
-                self.pending_triggering_evt = Some(triggering_evt);
-            }
-        }
+        let next_trigger = match self.run_loop().await {
+            Ok(()) => return,
+            Err(LoopError::Flow(err)) => self.task_impl.handle_loop_error(err).await,
+            Err(LoopError::Panic(panic_msg)) => self.task_impl.handle_loop_panic(panic_msg).await,
+        };
+
+        let (triggering_evt, _) = TriggeringEvent::new(next_trigger);
+        self.pending_triggering_evt = Some(triggering_evt);
     }
 
-    async fn run_loop(&mut self) -> Result<(), gst::FlowError> {
+    async fn run_loop(&mut self) -> Result<(), LoopError> {
         gst::trace!(RUNTIME_CAT, "Task loop started");
 
         let mut item;
@@ -1127,7 +1203,9 @@ impl<Item: Send + 'static> StateMachine<Item> {
                 // `try_next`. Since we need to get a new `BoxFuture` at
                 // each iteration, we can guarantee that the future is
                 // always valid for use in `select_biased`.
-                let mut try_next_fut = self.task_impl.try_next().fuse();
+                let mut try_next_fut = AssertUnwindSafe(self.task_impl.try_next())
+                    .catch_unwind()
+                    .fuse();
                 futures::select_biased! {
                     triggering_evt = self.triggering_evt_rx.next() => {
                         let triggering_evt = triggering_evt.expect("broken state machine channel");
@@ -1139,15 +1217,21 @@ impl<Item: Send + 'static> StateMachine<Item> {
                         self.pending_triggering_evt = Some(triggering_evt);
                         return Ok(());
                     }
-                    try_next_res = try_next_fut => try_next_res.inspect_err(|&err| {
-                        gst::debug!(RUNTIME_CAT, "TaskImpl::try_next returned {:?}", err);
-                    })?,
+                    try_next_res = try_next_fut => try_next_res
+                        .map_err(LoopError::from_panic)?
+                        .inspect_err(|&err| {
+                            gst::debug!(RUNTIME_CAT, "TaskImpl::try_next returned {:?}", err);
+                        })?,
                 }
             };
 
-            self.task_impl.handle_item(item).await.inspect_err(|&err| {
-                gst::debug!(RUNTIME_CAT, "TaskImpl::handle_item returned {:?}", err);
-            })?;
+            AssertUnwindSafe(self.task_impl.handle_item(item))
+                .catch_unwind()
+                .await
+                .map_err(LoopError::from_panic)?
+                .inspect_err(|&err| {
+                    gst::debug!(RUNTIME_CAT, "TaskImpl::handle_item returned {:?}", err);
+                })?;
         }
     }
 }
@@ -1933,6 +2017,104 @@ mod tests {
         );
     }
 
+    #[test]
+    fn item_panic() {
+        // Purpose: make sure a panic in the loop of a Task
+        // doesn't affect the other Tasks sharing the same Context.
+        gst::init().unwrap();
+
+        struct TaskTest {
+            try_next_receiver: mpsc::Receiver<bool>,
+            handle_item_sender: mpsc::Sender<()>,
+        }
+
+        impl TaskImpl for TaskTest {
+            type Item = bool;
+
+            fn try_next(&mut self) -> BoxFuture<'_, Result<bool, gst::FlowError>> {
+                async move {
+                    gst::debug!(RUNTIME_CAT, "item_panic: awaiting try_next");
+                    Ok(self.try_next_receiver.next().await.unwrap())
+                }
+                .boxed()
+            }
+
+            fn handle_item(
+                &mut self,
+                must_panic: bool,
+            ) -> BoxFuture<'_, Result<(), gst::FlowError>> {
+                async move {
+                    if must_panic {
+                        panic!("item_panic: panicking as requested");
+                    }
+
+                    gst::debug!(RUNTIME_CAT, "item_panic: handle_item");
+                    self.handle_item_sender.send(()).await.unwrap();
+                    Ok(())
+                }
+                .boxed()
+            }
+        }
+
+        let context = Context::acquire("item_panic", Duration::from_millis(2)).unwrap();
+
+        let panicking_task = Task::default();
+        let (mut panicking_sender, try_next_receiver) = mpsc::channel(1);
+        let (handle_item_sender, mut panicking_item_receiver) = mpsc::channel(1);
+        panicking_task
+            .prepare(
+                TaskTest {
+                    try_next_receiver,
+                    handle_item_sender,
+                },
+                context.clone(),
+            )
+            .block_on()
+            .unwrap();
+
+        let sibling_task = Task::default();
+        let (mut sibling_sender, try_next_receiver) = mpsc::channel(1);
+        let (handle_item_sender, mut sibling_item_receiver) = mpsc::channel(1);
+        sibling_task
+            .prepare(
+                TaskTest {
+                    try_next_receiver,
+                    handle_item_sender,
+                },
+                context,
+            )
+            .block_on()
+            .unwrap();
+
+        panicking_task.start().block_on().unwrap();
+        sibling_task.start().block_on().unwrap();
+
+        block_on(panicking_sender.send(false)).unwrap();
+        block_on(panicking_item_receiver.next()).unwrap();
+
+        gst::debug!(RUNTIME_CAT, "item_panic: req. handle_item to panic");
+        block_on(panicking_sender.send(true)).unwrap();
+        // Wait for state machine to reach Error
+        while TaskState::Error != panicking_task.state() {
+            std::thread::sleep(Duration::from_millis(2));
+        }
+
+        gst::debug!(RUNTIME_CAT, "item_panic: checking sibling task");
+        block_on(sibling_sender.send(false)).unwrap();
+        block_on(sibling_item_receiver.next()).unwrap();
+        assert_eq!(sibling_task.state(), Started);
+
+        assert_eq!(
+            panicking_task.unprepare().block_on().unwrap(),
+            Complete {
+                origin: TaskState::Error,
+                target: Unprepared,
+            },
+        );
+
+        stop_then_unprepare(sibling_task);
+    }
+
     #[test]
     fn flush_regular_sync() {
         gst::init().unwrap();
@@ -0,0 +1,138 @@
+// Take a look at the license at the top of the repository in the LICENSE file.
+
+//! Compatibility layer for futures which require a [`tokio`] runtime.
+//!
+//! Some crates, e.g. HTTP or database clients, can only be awaited from within a `tokio`
+//! runtime. [`spawn_tokio`] executes such futures on a small `tokio` runtime owned by this
+//! module and delivers their output back to the awaiting [`Context`] task.
+//!
+//! This is intended for occasional control-plane operations, such as fetching a configuration
+//! or establishing a session when an `Element` is prepared. Don't use it on per-buffer paths:
+//! each call involves a round trip between the `tokio` and the `Context` threads.
+//!
+//! Requires the `tokio-compat` feature.
+//!
+//! # Example
+//!
+//! ```
+//! # gst::init().unwrap();
+//! use gstthreadshare::runtime::{tokio_compat, Context};
+//! use std::time::Duration;
+//!
+//! let ctx = Context::acquire("tokio-compat-example", Duration::from_millis(2)).unwrap();
+//! let res = futures::executor::block_on(ctx.spawn(async {
+//!     tokio_compat::spawn_tokio(async {
+//!         tokio::time::sleep(Duration::from_millis(10)).await;
+//!         42
+//!     })
+//!     .await
+//! }));
+//! assert_eq!(res.unwrap().unwrap(), 42);
+//! ```
+//!
+//! [`Context`]: super::Context
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::LazyLock;
+use std::task::{self, Poll};
+
+pub use tokio::task::JoinError;
+
+static TOKIO_RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("ts-tokio-compat")
+        .enable_all()
+        .build()
+        .expect("Failed to build the tokio compat runtime")
+});
+
+/// Spawns `future` on the `tokio` compat runtime.
+///
+/// The returned [`TokioJoinHandle`] can be awaited from a [`Context`] task, or from any other
+/// executor. It resolves to the output of `future`, or to a [`JoinError`] if `future` panicked.
+///
+/// [`Context`]: super::Context
+pub fn spawn_tokio<Fut>(future: Fut) -> TokioJoinHandle<Fut::Output>
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    TokioJoinHandle(TOKIO_RUNTIME.spawn(future))
+}
+
+/// A handle to a future spawned using [`spawn_tokio`].
+///
+/// Dropping the handle aborts the future.
+#[derive(Debug)]
+#[must_use = "dropping the handle aborts the future"]
+pub struct TokioJoinHandle<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Future for TokioJoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T> Drop for TokioJoinHandle<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::runtime::Context;
+
+    #[test]
+    fn tokio_sleep() {
+        gst::init().unwrap();
+
+        const DELAY: Duration = Duration::from_millis(20);
+
+        let context = Context::acquire("tokio_sleep", Duration::from_millis(2)).unwrap();
+
+        let elapsed = futures::executor::block_on(context.spawn(async {
+            let start = Instant::now();
+            super::spawn_tokio(tokio::time::sleep(DELAY)).await.unwrap();
+
+            // Back on the Context
+            assert_eq!(Context::current().unwrap().name(), "tokio_sleep");
+
+            start.elapsed()
+        }))
+        .unwrap();
+
+        assert!(elapsed >= DELAY);
+    }
+
+    #[test]
+    fn abort_on_drop() {
+        gst::init().unwrap();
+
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (dropped_tx, dropped_rx) = std::sync::mpsc::channel::<()>();
+
+        struct NotifyOnDrop(std::sync::mpsc::Sender<()>);
+        impl Drop for NotifyOnDrop {
+            fn drop(&mut self) {
+                let _ = self.0.send(());
+            }
+        }
+
+        let handle = super::spawn_tokio(async move {
+            let _notify = NotifyOnDrop(dropped_tx);
+            started_tx.send(()).unwrap();
+            futures::future::pending::<()>().await;
+        });
+        started_rx.recv().unwrap();
+
+        drop(handle);
+        dropped_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    }
+}
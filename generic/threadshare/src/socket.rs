@@ -18,7 +18,7 @@
 //
 // SPDX-License-Identifier: LGPL-2.1-or-later
 
-use futures::future::BoxFuture;
+use futures::future::{BoxFuture, FutureExt};
 
 use gst::glib;
 use gst::prelude::*;
@@ -28,15 +28,11 @@ use std::sync::LazyLock;
 use std::error;
 use std::fmt;
 use std::io;
-use std::net::UdpSocket;
 
-use crate::runtime::Async;
+use crate::runtime::net::UdpSocket;
 
 #[cfg(unix)]
-use std::os::{
-    fd::BorrowedFd,
-    unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
-};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 
 #[cfg(windows)]
 use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket};
@@ -56,6 +52,30 @@ pub trait SocketRead: Send + Unpin {
         &'buf mut self,
         buffer: &'buf mut [u8],
     ) -> BoxFuture<'buf, io::Result<(usize, Option<std::net::SocketAddr>)>>;
+
+    /// Reads the datagrams which are already available into `buffers`, without waiting.
+    ///
+    /// Returns the size & sender address of each datagram read, in the order of `buffers`.
+    /// The default implementation reads one datagram at a time.
+    fn try_read_batch(
+        &mut self,
+        buffers: &mut [&mut [u8]],
+    ) -> io::Result<Vec<(usize, Option<std::net::SocketAddr>)>> {
+        let mut reads = Vec::with_capacity(buffers.len());
+        for buffer in buffers.iter_mut() {
+            match self.read(buffer).now_or_never() {
+                Some(Ok(read)) => reads.push(read),
+                Some(Err(err)) if reads.is_empty() => return Err(err),
+                Some(Err(err)) => {
+                    gst::debug!(SOCKET_CAT, "Ending batch: {}", err);
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        Ok(reads)
+    }
 }
 
 pub struct Socket<T: SocketRead> {
@@ -154,24 +174,7 @@ impl<T: SocketRead> Socket<T> {
             .await
         {
             Ok((len, saddr)) => {
-                let dts = if T::DO_TIMESTAMP {
-                    let time = self.clock.as_ref().unwrap().time();
-                    let running_time = time.opt_checked_sub(self.base_time).ok().flatten();
-                    // FIXME maybe we should check if running_time.is_none
-                    // so as to display another message
-                    gst::debug!(
-                        SOCKET_CAT,
-                        obj = self.element,
-                        "Read {} bytes at {} (clock {})",
-                        len,
-                        running_time.display(),
-                        time.display(),
-                    );
-                    running_time
-                } else {
-                    gst::debug!(SOCKET_CAT, obj = self.element, "Read {} bytes", len);
-                    gst::ClockTime::NONE
-                };
+                let dts = self.dts(len);
 
                 let mut buffer = self.mapped_buffer.take().unwrap().into_buffer();
                 {
@@ -193,6 +196,94 @@ impl<T: SocketRead> Socket<T> {
     }
 }
 
+impl<T: SocketRead> Socket<T> {
+    /// Reads up to `max` datagrams which are already available, without waiting.
+    pub fn try_next_batch(
+        &mut self,
+        max: usize,
+    ) -> Result<Vec<(gst::Buffer, Option<std::net::SocketAddr>)>, SocketError> {
+        let mut mapped_buffers = Vec::with_capacity(max);
+        mapped_buffers.extend(self.mapped_buffer.take());
+        while mapped_buffers.len() < max {
+            match self.buffer_pool.acquire_buffer(None) {
+                Ok(buffer) => {
+                    mapped_buffers.push(buffer.into_mapped_buffer_writable().unwrap());
+                }
+                Err(err) if mapped_buffers.is_empty() => {
+                    gst::debug!(
+                        SOCKET_CAT,
+                        obj = self.element,
+                        "Failed to acquire buffer {:?}",
+                        err
+                    );
+                    return Err(SocketError::Gst(err));
+                }
+                Err(_) => break,
+            }
+        }
+
+        let res = {
+            let mut slices = mapped_buffers
+                .iter_mut()
+                .map(|buffer| buffer.as_mut_slice())
+                .collect::<Vec<_>>();
+            self.reader.try_read_batch(&mut slices)
+        };
+
+        let mut mapped_buffers = mapped_buffers.into_iter();
+        let reads = match res {
+            Ok(reads) => reads,
+            Err(err) => {
+                gst::debug!(SOCKET_CAT, obj = self.element, "Read error {:?}", err);
+                self.mapped_buffer = mapped_buffers.next();
+
+                return Err(SocketError::Io(err));
+            }
+        };
+
+        let mut packets = Vec::with_capacity(reads.len());
+        for (len, saddr) in reads {
+            let dts = self.dts(len);
+            let mut buffer = mapped_buffers.next().unwrap().into_buffer();
+            {
+                let buffer = buffer.get_mut().unwrap();
+                if len < buffer.size() {
+                    buffer.set_size(len);
+                }
+                buffer.set_dts(dts);
+            }
+
+            packets.push((buffer, saddr));
+        }
+
+        // Keep one of the unused buffers for the next read, the others return to the pool
+        self.mapped_buffer = mapped_buffers.next();
+
+        Ok(packets)
+    }
+
+    fn dts(&self, len: usize) -> Option<gst::ClockTime> {
+        if T::DO_TIMESTAMP {
+            let time = self.clock.as_ref().unwrap().time();
+            let running_time = time.opt_checked_sub(self.base_time).ok().flatten();
+            // FIXME maybe we should check if running_time.is_none
+            // so as to display another message
+            gst::debug!(
+                SOCKET_CAT,
+                obj = self.element,
+                "Read {} bytes at {} (clock {})",
+                len,
+                running_time.display(),
+                time.display(),
+            );
+            running_time
+        } else {
+            gst::debug!(SOCKET_CAT, obj = self.element, "Read {} bytes", len);
+            gst::ClockTime::NONE
+        }
+    }
+}
+
 impl<T: SocketRead> Drop for Socket<T> {
     fn drop(&mut self) {
         if let Err(err) = self.buffer_pool.set_active(false) {
@@ -237,61 +328,6 @@ impl GioSocketWrapper {
         }
     }
 
-    #[cfg(any(
-        target_os = "macos",
-        target_os = "ios",
-        target_os = "freebsd",
-        target_os = "dragonfly",
-        target_os = "openbsd",
-        target_os = "netbsd",
-        target_os = "linux",
-        target_os = "android",
-        target_os = "aix",
-        target_os = "fuchsia",
-        target_os = "haiku",
-        target_env = "newlib"
-    ))]
-    pub fn set_tos(&self, qos_dscp: i32) -> rustix::io::Result<()> {
-        use gio::prelude::*;
-        use rustix::net::sockopt;
-
-        let tos = (qos_dscp & 0x3f) << 2;
-
-        let socket = self.as_socket();
-
-        sockopt::set_ip_tos(
-            unsafe { BorrowedFd::borrow_raw(socket.as_raw_fd()) },
-            tos as u8,
-        )?;
-
-        if socket.family() == gio::SocketFamily::Ipv6 {
-            sockopt::set_ipv6_tclass(
-                unsafe { BorrowedFd::borrow_raw(socket.as_raw_fd()) },
-                tos as u32,
-            )?;
-        }
-
-        Ok(())
-    }
-
-    #[cfg(not(any(
-        target_os = "macos",
-        target_os = "ios",
-        target_os = "freebsd",
-        target_os = "dragonfly",
-        target_os = "openbsd",
-        target_os = "netbsd",
-        target_os = "linux",
-        target_os = "android",
-        target_os = "aix",
-        target_os = "fuchsia",
-        target_os = "haiku",
-        target_env = "newlib"
-    )))]
-    pub fn set_tos(&self, _qos_dscp: i32) -> rustix::io::Result<()> {
-        Ok(())
-    }
-
     #[cfg(not(windows))]
     pub fn get<T: FromRawFd>(&self) -> T {
         unsafe {
@@ -364,7 +400,7 @@ unsafe fn dup_socket(socket: usize) -> usize {
     socket
 }
 
-pub fn wrap_socket(socket: &Async<UdpSocket>) -> Result<GioSocketWrapper, gst::ErrorMessage> {
+pub fn wrap_socket(socket: &UdpSocket) -> Result<GioSocketWrapper, gst::ErrorMessage> {
     #[cfg(unix)]
     unsafe {
         let dupped = rustix::io::dup(socket).unwrap();
@@ -0,0 +1,726 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use futures::future::{abortable, AbortHandle, BoxFuture};
+use futures::prelude::*;
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gst::EventView;
+use gst::{element_error, element_warning, error_msg};
+
+use std::sync::LazyLock;
+
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::net;
+use crate::runtime::executor::{block_on_or_add_sub_task, debug_assert_not_context_thread};
+use crate::runtime::prelude::*;
+use crate::runtime::{timer, Async, Context, JoinHandle, PadSink};
+
+use crate::udpsink::OnSendError;
+
+const DEFAULT_HOST: Option<&str> = Some("127.0.0.1");
+const DEFAULT_PORT: i32 = 4953;
+const DEFAULT_CONTEXT: &str = "";
+const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+const DEFAULT_RECONNECT: bool = false;
+const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_RECONNECT_ATTEMPTS: u32 = 0;
+const DEFAULT_ON_ERROR: OnSendError = OnSendError::Error;
+
+#[derive(Debug, Clone)]
+struct Settings {
+    host: Option<String>,
+    port: i32,
+    context: String,
+    context_wait: Duration,
+    reconnect: bool,
+    reconnect_delay: Duration,
+    max_reconnect_attempts: u32,
+    on_error: OnSendError,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            host: DEFAULT_HOST.map(Into::into),
+            port: DEFAULT_PORT,
+            context: DEFAULT_CONTEXT.into(),
+            context_wait: DEFAULT_CONTEXT_WAIT,
+            reconnect: DEFAULT_RECONNECT,
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+            max_reconnect_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
+            on_error: DEFAULT_ON_ERROR,
+        }
+    }
+}
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-tcpclientsink",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing TCP client sink"),
+    )
+});
+
+#[derive(Clone, Debug, Default)]
+struct TcpClientSinkPadHandler {
+    inner: Arc<futures::lock::Mutex<TcpClientSinkPadHandlerInner>>,
+    // Kept out of `inner` which is locked while connecting or writing
+    abort_handle: Arc<Mutex<Option<AbortHandle>>>,
+}
+
+impl TcpClientSinkPadHandler {
+    fn prepare(&self, ts_ctx: Context, host: String, port: u16, settings: &Settings) {
+        debug_assert_not_context_thread("TcpClientSink prepare");
+
+        futures::executor::block_on(async move {
+            let mut inner = self.inner.lock().await;
+
+            inner.ts_ctx = Some(ts_ctx);
+            inner.server = format!("{host}:{port}");
+            inner.host = host;
+            inner.port = port;
+            inner.reconnect = settings.reconnect;
+            inner.reconnect_delay = settings.reconnect_delay;
+            inner.max_reconnect_attempts = settings.max_reconnect_attempts;
+            inner.on_error = settings.on_error;
+        })
+    }
+
+    fn unprepare(&self) {
+        futures::executor::block_on(async move {
+            let mut inner = self.inner.lock().await;
+            inner.disconnect();
+            inner.ts_ctx = None;
+        })
+    }
+
+    fn start(&self) {
+        futures::executor::block_on(async move {
+            let mut inner = self.inner.lock().await;
+            inner.is_flushing = false;
+            inner.error_posted = false;
+            // Connect in the background so that the server is hopefully
+            // reachable by the time the first buffer arrives
+            inner.spawn_connect(None);
+        })
+    }
+
+    fn stop(&self) {
+        self.abort();
+        futures::executor::block_on(async move {
+            let mut inner = self.inner.lock().await;
+            inner.is_flushing = true;
+            inner.disconnect();
+        })
+    }
+
+    fn abort(&self) {
+        if let Some(abort_handle) = self.abort_handle.lock().unwrap().take() {
+            abort_handle.abort();
+        }
+    }
+
+    fn set_on_error(&self, on_error: OnSendError) {
+        futures::executor::block_on(async move {
+            self.inner.lock().await.on_error = on_error;
+        })
+    }
+
+    /// Runs `fut`, returning `Err(Flushing)` if it was aborted
+    /// by a flush or a state change.
+    async fn run_abortable(
+        &self,
+        fut: impl Future<Output = Result<gst::FlowSuccess, gst::FlowError>>,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let (fut, abort_handle) = abortable(fut);
+        *self.abort_handle.lock().unwrap() = Some(abort_handle);
+        let res = fut.await;
+        self.abort_handle.lock().unwrap().take();
+
+        res.unwrap_or(Err(gst::FlowError::Flushing))
+    }
+}
+
+impl PadSinkHandler for TcpClientSinkPadHandler {
+    type ElementImpl = TcpClientSink;
+
+    fn sink_chain(
+        self,
+        _pad: gst::Pad,
+        elem: super::TcpClientSink,
+        buffer: gst::Buffer,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            self.run_abortable(async {
+                self.inner.lock().await.handle_buffer(&elem, &buffer).await
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn sink_chain_list(
+        self,
+        _pad: gst::Pad,
+        elem: super::TcpClientSink,
+        list: gst::BufferList,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            self.run_abortable(async {
+                let mut inner = self.inner.lock().await;
+                for buffer in list.iter() {
+                    inner.handle_buffer(&elem, buffer).await?;
+                }
+
+                Ok(gst::FlowSuccess::Ok)
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn sink_event_serialized(
+        self,
+        _pad: gst::Pad,
+        elem: super::TcpClientSink,
+        event: gst::Event,
+    ) -> BoxFuture<'static, bool> {
+        async move {
+            gst::debug!(CAT, obj = elem, "Handling {event:?}");
+
+            match event.view() {
+                EventView::Eos(_) => {
+                    let _ = elem.post_message(gst::message::Eos::builder().src(&elem).build());
+                }
+                EventView::FlushStop(_) => {
+                    self.inner.lock().await.is_flushing = false;
+                }
+                EventView::SinkMessage(e) => {
+                    let _ = elem.post_message(e.message());
+                }
+                _ => (),
+            }
+
+            true
+        }
+        .boxed()
+    }
+
+    fn sink_event(self, _pad: &gst::Pad, imp: &TcpClientSink, event: gst::Event) -> bool {
+        gst::debug!(CAT, imp = imp, "Handling {event:?}");
+
+        if let EventView::FlushStart(..) = event.view() {
+            self.abort();
+            block_on_or_add_sub_task(async move {
+                self.inner.lock().await.is_flushing = true;
+            });
+        }
+
+        true
+    }
+}
+
+#[derive(Debug)]
+struct TcpClientSinkPadHandlerInner {
+    is_flushing: bool,
+    ts_ctx: Option<Context>,
+    /// Server as configured, for messages.
+    server: String,
+    host: String,
+    port: u16,
+    socket: Option<Async<TcpStream>>,
+    pending_connect: Option<JoinHandle<io::Result<Async<TcpStream>>>>,
+    reconnect: bool,
+    reconnect_delay: Duration,
+    max_reconnect_attempts: u32,
+    backoff: net::ReconnectBackoff,
+    on_error: OnSendError,
+    error_posted: bool,
+}
+
+impl Default for TcpClientSinkPadHandlerInner {
+    fn default() -> Self {
+        Self {
+            is_flushing: true,
+            ts_ctx: None,
+            server: String::new(),
+            host: String::new(),
+            port: 0,
+            socket: None,
+            pending_connect: None,
+            reconnect: DEFAULT_RECONNECT,
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+            max_reconnect_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
+            backoff: Default::default(),
+            on_error: DEFAULT_ON_ERROR,
+            error_posted: false,
+        }
+    }
+}
+
+impl TcpClientSinkPadHandlerInner {
+    /// Starts connecting to the server on the `Context`, after `delay` if set.
+    fn spawn_connect(&mut self, delay: Option<Duration>) {
+        let Some(ts_ctx) = self.ts_ctx.as_ref() else {
+            return;
+        };
+
+        let host = self.host.clone();
+        let port = self.port;
+        self.pending_connect = Some(ts_ctx.spawn(async move {
+            if let Some(delay) = delay {
+                timer::delay_for(delay).await;
+            }
+
+            // Resolved on each attempt as the addresses might have changed
+            let addrs = match host.parse::<IpAddr>() {
+                Ok(ip) => vec![SocketAddr::new(ip, port)],
+                Err(_) => net::resolve(&host, port).await.map_err(|err| {
+                    io::Error::new(err.kind(), format!("Failed to resolve {host}: {err}"))
+                })?,
+            };
+
+            let mut last_err = None;
+            for addr in addrs {
+                gst::debug!(CAT, "Connecting to {addr}");
+                match Async::<TcpStream>::connect(addr).await {
+                    Ok(socket) => return Ok(socket),
+                    Err(err) => last_err = Some(err),
+                }
+            }
+
+            Err(last_err.unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No address")))
+        }));
+    }
+
+    fn disconnect(&mut self) {
+        self.socket = None;
+        if let Some(pending_connect) = self.pending_connect.take() {
+            pending_connect.cancel();
+        }
+        self.backoff.reset();
+    }
+
+    /// Schedules a new connection attempt if allowed, returns `err` otherwise.
+    fn schedule_reconnect(
+        &mut self,
+        elem: &super::TcpClientSink,
+        err: String,
+    ) -> Result<(), String> {
+        self.socket = None;
+
+        if !self.reconnect {
+            return Err(err);
+        }
+
+        let Some(delay) = self
+            .backoff
+            .next_delay(self.reconnect_delay, self.max_reconnect_attempts)
+        else {
+            gst::error!(
+                CAT,
+                obj = elem,
+                "Giving up after {} reconnection attempts",
+                self.backoff.attempts(),
+            );
+            return Err(err);
+        };
+
+        gst::debug!(CAT, obj = elem, "Connection failed: {err}");
+        element_warning!(
+            elem,
+            gst::ResourceError::OpenWrite,
+            ("Connection to {} failed, reconnecting", self.server),
+            [
+                "Reconnection attempt {} in {:?}",
+                self.backoff.attempts(),
+                delay
+            ]
+        );
+
+        self.spawn_connect(Some(delay));
+
+        Ok(())
+    }
+
+    /// Waits for the pending connection, reconnecting if allowed.
+    async fn ensure_connected(&mut self, elem: &super::TcpClientSink) -> Result<(), String> {
+        while self.socket.is_none() {
+            let Some(pending_connect) = self.pending_connect.take() else {
+                return Err(format!("Not connected to {}", self.server));
+            };
+
+            match pending_connect.await {
+                Ok(Ok(socket)) => {
+                    gst::info!(CAT, obj = elem, "Connected to {}", self.server);
+                    self.socket = Some(socket);
+                    self.backoff.reset();
+                }
+                Ok(Err(err)) => {
+                    let err = format!("Failed to connect to {}: {err}", self.server);
+                    self.schedule_reconnect(elem, err)?;
+                }
+                Err(err) => return Err(format!("Connection to {}: {err}", self.server)),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn write_buffer(
+        &mut self,
+        elem: &super::TcpClientSink,
+        buffer: &gst::BufferRef,
+    ) -> Result<(), String> {
+        self.ensure_connected(elem).await?;
+
+        let map = buffer
+            .map_readable()
+            .map_err(|_| "Failed to map buffer readable".to_string())?;
+
+        // Waits for the socket to be writable if the kernel send buffer is full
+        let res = self.socket.as_mut().unwrap().write_all(&map).await;
+        if let Err(err) = res {
+            let err = format!("Failed to write to {}: {err}", self.server);
+            self.schedule_reconnect(elem, err)?;
+            gst::debug!(CAT, obj = elem, "Dropping buffer, reconnecting");
+        }
+
+        Ok(())
+    }
+
+    async fn handle_buffer(
+        &mut self,
+        elem: &super::TcpClientSink,
+        buffer: &gst::BufferRef,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        if self.is_flushing {
+            gst::debug!(CAT, obj = elem, "Flushing");
+            return Err(gst::FlowError::Flushing);
+        }
+
+        gst::log!(CAT, obj = elem, "Handling {buffer:?}");
+        let res = self.write_buffer(elem, buffer).await;
+        self.handle_error(elem, res.err())?;
+
+        Ok(gst::FlowSuccess::Ok)
+    }
+
+    /// Applies the `on-error` policy.
+    fn handle_error(
+        &mut self,
+        elem: &super::TcpClientSink,
+        err: Option<String>,
+    ) -> Result<(), gst::FlowError> {
+        let Some(err) = err else {
+            self.error_posted = false;
+            return Ok(());
+        };
+
+        match self.on_error {
+            OnSendError::Error => {
+                element_error!(
+                    elem,
+                    gst::ResourceError::Write,
+                    ("Failed to send data to {}", self.server),
+                    ["streaming stopped, {}", err]
+                );
+                return Err(gst::FlowError::Error);
+            }
+            OnSendError::Warn => {
+                // Don't flood the bus, post again after a successful send
+                if !self.error_posted {
+                    element_warning!(
+                        elem,
+                        gst::ResourceError::Write,
+                        ("Failed to send data to {}", self.server),
+                        ["{}", err]
+                    );
+                    self.error_posted = true;
+                }
+            }
+            OnSendError::Ignore => {
+                gst::debug!(CAT, obj = elem, "Ignoring error: {err}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct TcpClientSink {
+    sink_pad: PadSink,
+    sink_pad_handler: TcpClientSinkPadHandler,
+    settings: Mutex<Settings>,
+}
+
+impl TcpClientSink {
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
+        gst::debug!(CAT, imp = self, "Preparing");
+
+        let settings = self.settings.lock().unwrap().clone();
+
+        let ts_ctx = Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
+            error_msg!(
+                gst::ResourceError::OpenWrite,
+                ["Failed to acquire Context: {}", err]
+            )
+        })?;
+
+        let host = settings
+            .host
+            .clone()
+            .ok_or_else(|| error_msg!(gst::ResourceError::Settings, ["No host set"]))?;
+        let port = settings.port as u16;
+
+        // Resolved asynchronously when connecting
+        self.sink_pad_handler.prepare(ts_ctx, host, port, &settings);
+
+        gst::debug!(CAT, imp = self, "Prepared");
+
+        Ok(())
+    }
+
+    fn unprepare(&self) {
+        gst::debug!(CAT, imp = self, "Unpreparing");
+        self.sink_pad_handler.unprepare();
+        gst::debug!(CAT, imp = self, "Unprepared");
+    }
+
+    fn stop(&self) {
+        gst::debug!(CAT, imp = self, "Stopping");
+        self.sink_pad_handler.stop();
+        gst::debug!(CAT, imp = self, "Stopped");
+    }
+
+    fn start(&self) {
+        gst::debug!(CAT, imp = self, "Starting");
+        self.sink_pad_handler.start();
+        gst::debug!(CAT, imp = self, "Started");
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for TcpClientSink {
+    const NAME: &'static str = "GstTsTcpClientSink";
+    type Type = super::TcpClientSink;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        let sink_pad_handler = TcpClientSinkPadHandler::default();
+        Self {
+            sink_pad: PadSink::new(
+                gst::Pad::from_template(&klass.pad_template("sink").unwrap()),
+                sink_pad_handler.clone(),
+            ),
+            sink_pad_handler,
+            settings: Default::default(),
+        }
+    }
+}
+
+impl ObjectImpl for TcpClientSink {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("context")
+                    .nick("Context")
+                    .blurb("Context name to share threads with")
+                    .default_value(Some(DEFAULT_CONTEXT))
+                    .build(),
+                glib::ParamSpecUInt::builder("context-wait")
+                    .nick("Context Wait")
+                    .blurb("Throttle poll loop to run at most once every this many ms")
+                    .maximum(1000)
+                    .default_value(DEFAULT_CONTEXT_WAIT.as_millis() as u32)
+                    .build(),
+                glib::ParamSpecString::builder("host")
+                    .nick("Host")
+                    .blurb("The host name or IP address to send the data to")
+                    .default_value(DEFAULT_HOST)
+                    .build(),
+                glib::ParamSpecInt::builder("port")
+                    .nick("Port")
+                    .blurb("Port to send the data to")
+                    .minimum(0)
+                    .maximum(u16::MAX as i32)
+                    .default_value(DEFAULT_PORT)
+                    .build(),
+                glib::ParamSpecBoolean::builder("reconnect")
+                    .nick("Reconnect")
+                    .blurb("Reconnect to the server when the connection fails or is lost")
+                    .default_value(DEFAULT_RECONNECT)
+                    .build(),
+                glib::ParamSpecUInt::builder("reconnect-delay")
+                    .nick("Reconnect Delay")
+                    .blurb(
+                        "Initial delay in ms before reconnecting, doubled on each failed attempt",
+                    )
+                    .minimum(1)
+                    .default_value(DEFAULT_RECONNECT_DELAY.as_millis() as u32)
+                    .build(),
+                glib::ParamSpecUInt::builder("max-reconnect-attempts")
+                    .nick("Max Reconnect Attempts")
+                    .blurb("Maximum number of consecutive reconnection attempts (0 = unlimited)")
+                    .default_value(DEFAULT_MAX_RECONNECT_ATTEMPTS)
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("on-error", DEFAULT_ON_ERROR)
+                    .nick("On Error")
+                    .blurb("How to handle errors sending to the server")
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "context" => {
+                settings.context = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| DEFAULT_CONTEXT.into());
+            }
+            "context-wait" => {
+                settings.context_wait = Duration::from_millis(
+                    value.get::<u32>().expect("type checked upstream").into(),
+                );
+            }
+            "host" => {
+                settings.host = value.get().expect("type checked upstream");
+            }
+            "port" => {
+                settings.port = value.get().expect("type checked upstream");
+            }
+            "reconnect" => {
+                settings.reconnect = value.get().expect("type checked upstream");
+            }
+            "reconnect-delay" => {
+                settings.reconnect_delay = Duration::from_millis(
+                    value.get::<u32>().expect("type checked upstream").into(),
+                );
+            }
+            "max-reconnect-attempts" => {
+                settings.max_reconnect_attempts = value.get().expect("type checked upstream");
+            }
+            "on-error" => {
+                let on_error = value.get().expect("type checked upstream");
+                settings.on_error = on_error;
+                self.sink_pad_handler.set_on_error(on_error);
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "context" => settings.context.to_value(),
+            "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
+            "host" => settings.host.to_value(),
+            "port" => settings.port.to_value(),
+            "reconnect" => settings.reconnect.to_value(),
+            "reconnect-delay" => (settings.reconnect_delay.as_millis() as u32).to_value(),
+            "max-reconnect-attempts" => settings.max_reconnect_attempts.to_value(),
+            "on-error" => settings.on_error.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let obj = self.obj();
+        obj.add_pad(self.sink_pad.gst_pad()).unwrap();
+        obj.set_element_flags(gst::ElementFlags::SINK);
+    }
+}
+
+impl GstObjectImpl for TcpClientSink {}
+
+impl ElementImpl for TcpClientSink {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Thread-sharing TCP client sink",
+                "Sink/Network",
+                "Sends data to a TCP server",
+                "agent <agent@local>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+            let caps = gst::Caps::new_any();
+
+            let sink_pad_template = gst::PadTemplate::new(
+                "sink",
+                gst::PadDirection::Sink,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![sink_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        gst::trace!(CAT, imp = self, "Changing state {:?}", transition);
+
+        match transition {
+            gst::StateChange::NullToReady => {
+                self.prepare().map_err(|err| {
+                    self.post_error_message(err);
+                    gst::StateChangeError
+                })?;
+            }
+            gst::StateChange::ReadyToPaused => {
+                self.start();
+            }
+            gst::StateChange::PausedToReady => {
+                self.stop();
+            }
+            gst::StateChange::ReadyToNull => {
+                self.unprepare();
+            }
+            _ => (),
+        }
+
+        self.parent_change_state(transition)
+    }
+}
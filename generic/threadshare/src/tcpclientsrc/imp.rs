@@ -18,7 +18,7 @@
 //
 // SPDX-License-Identifier: LGPL-2.1-or-later
 
-use futures::future::BoxFuture;
+use futures::future::{self, BoxFuture};
 use futures::prelude::*;
 
 use gst::glib;
@@ -28,25 +28,44 @@ use gst::subclass::prelude::*;
 use std::sync::LazyLock;
 
 use std::io;
-use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+#[cfg(feature = "tls")]
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Mutex;
 use std::time::Duration;
 
+use crate::net;
 use crate::runtime::prelude::*;
-use crate::runtime::task;
-use crate::runtime::{Context, PadSrc, Task, TaskState};
+use crate::runtime::{timer, Context, PadSrc, PadSrcRef, SrcItem, SrcTaskAdapter, Task, TaskState};
 
 use crate::runtime::Async;
 use crate::socket::{Socket, SocketError, SocketRead};
 use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::pin_mut;
 
+#[cfg(feature = "tls")]
+use super::tls;
+
 const DEFAULT_HOST: Option<&str> = Some("127.0.0.1");
 const DEFAULT_PORT: i32 = 4953;
 const DEFAULT_CAPS: Option<gst::Caps> = None;
 const DEFAULT_BLOCKSIZE: u32 = 4096;
 const DEFAULT_CONTEXT: &str = "";
 const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+const DEFAULT_RECONNECT: bool = false;
+const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_RECONNECT_ATTEMPTS: u32 = 0;
+const DEFAULT_LOCAL_ADDRESS: Option<&str> = None;
+const DEFAULT_LOCAL_PORT: i32 = 0;
+const DEFAULT_CONNECT_TIMEOUT: u64 = 0;
+const DEFAULT_READ_TIMEOUT: u64 = 0;
+const DEFAULT_TCP_NODELAY: bool = true;
+const DEFAULT_KEEPALIVE: bool = false;
+const DEFAULT_KEEPALIVE_TIME: u64 = 0;
+const DEFAULT_TLS: bool = false;
+const DEFAULT_TLS_VALIDATION_FLAGS: gio::TlsCertificateFlags =
+    gio::TlsCertificateFlags::VALIDATE_ALL;
 
 #[derive(Debug, Default)]
 struct State {
@@ -61,6 +80,21 @@ struct Settings {
     blocksize: u32,
     context: String,
     context_wait: Duration,
+    reconnect: bool,
+    reconnect_delay: Duration,
+    max_reconnect_attempts: u32,
+    local_address: Option<String>,
+    local_port: i32,
+    connect_timeout: u64,
+    read_timeout: u64,
+    tcp_nodelay: bool,
+    keepalive: bool,
+    keepalive_time: u64,
+    tls: bool,
+    tls_validation_flags: gio::TlsCertificateFlags,
+    tls_ca_file: Option<String>,
+    tls_certificate_file: Option<String>,
+    tls_private_key_file: Option<String>,
 }
 
 impl Default for Settings {
@@ -72,15 +106,46 @@ impl Default for Settings {
             blocksize: DEFAULT_BLOCKSIZE,
             context: DEFAULT_CONTEXT.into(),
             context_wait: DEFAULT_CONTEXT_WAIT,
+            reconnect: DEFAULT_RECONNECT,
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+            max_reconnect_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
+            local_address: DEFAULT_LOCAL_ADDRESS.map(Into::into),
+            local_port: DEFAULT_LOCAL_PORT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            tcp_nodelay: DEFAULT_TCP_NODELAY,
+            keepalive: DEFAULT_KEEPALIVE,
+            keepalive_time: DEFAULT_KEEPALIVE_TIME,
+            tls: DEFAULT_TLS,
+            tls_validation_flags: DEFAULT_TLS_VALIDATION_FLAGS,
+            tls_ca_file: None,
+            tls_certificate_file: None,
+            tls_private_key_file: None,
         }
     }
 }
 
-struct TcpClientReader(Async<TcpStream>);
+enum TcpClientStream {
+    Tcp(Async<TcpStream>),
+    #[cfg(feature = "tls")]
+    Tls(Box<tls::TlsStream<Async<TcpStream>>>),
+}
+
+impl TcpClientStream {
+    fn tcp_stream(&self) -> &TcpStream {
+        match self {
+            TcpClientStream::Tcp(socket) => socket.get_ref(),
+            #[cfg(feature = "tls")]
+            TcpClientStream::Tls(stream) => stream.get_ref().0.get_ref(),
+        }
+    }
+}
+
+struct TcpClientReader(TcpClientStream);
 
 impl TcpClientReader {
-    pub fn new(socket: Async<TcpStream>) -> Self {
-        TcpClientReader(socket)
+    pub fn new(stream: TcpClientStream) -> Self {
+        TcpClientReader(stream)
     }
 }
 
@@ -91,7 +156,16 @@ impl SocketRead for TcpClientReader {
         &'buf mut self,
         buffer: &'buf mut [u8],
     ) -> BoxFuture<'buf, io::Result<(usize, Option<std::net::SocketAddr>)>> {
-        async move { self.0.read(buffer).await.map(|read_size| (read_size, None)) }.boxed()
+        async move {
+            let res = match self.0 {
+                TcpClientStream::Tcp(ref mut socket) => socket.read(buffer).await,
+                #[cfg(feature = "tls")]
+                TcpClientStream::Tls(ref mut stream) => stream.read(buffer).await,
+            };
+
+            res.map(|read_size| (read_size, None))
+        }
+        .boxed()
     }
 }
 
@@ -166,141 +240,425 @@ impl PadSrcHandler for TcpClientSrcPadHandler {
 
 struct TcpClientSrcTask {
     element: super::TcpClientSrc,
-    saddr: SocketAddr,
-    buffer_pool: Option<gst::BufferPool>,
+    host: String,
+    port: u16,
+    local_ip: Option<IpAddr>,
+    local_port: u16,
+    #[cfg(feature = "tls")]
+    tls: Option<(tls::TlsConnector, String)>,
+    buffer_pool: gst::BufferPool,
     socket: Option<Socket<TcpClientReader>>,
-    need_initial_events: bool,
-    need_segment: bool,
+    backoff: net::ReconnectBackoff,
+    reconnect_delay: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    need_discont: bool,
     event_receiver: Receiver<gst::Event>,
 }
 
 impl TcpClientSrcTask {
     fn new(
         element: super::TcpClientSrc,
-        saddr: SocketAddr,
+        host: String,
+        port: u16,
+        local_ip: Option<IpAddr>,
+        local_port: u16,
+        #[cfg(feature = "tls")] tls: Option<(tls::TlsConnector, String)>,
         buffer_pool: gst::BufferPool,
         event_receiver: Receiver<gst::Event>,
     ) -> Self {
         TcpClientSrcTask {
             element,
-            saddr,
-            buffer_pool: Some(buffer_pool),
+            host,
+            port,
+            local_ip,
+            local_port,
+            #[cfg(feature = "tls")]
+            tls,
+            buffer_pool,
             socket: None,
-            need_initial_events: true,
-            need_segment: true,
+            backoff: Default::default(),
+            reconnect_delay: None,
+            connect_timeout: None,
+            read_timeout: None,
+            need_discont: false,
             event_receiver,
         }
     }
 
-    async fn push_buffer(
-        &mut self,
-        buffer: gst::Buffer,
-    ) -> Result<gst::FlowSuccess, gst::FlowError> {
-        gst::log!(CAT, obj = self.element, "Handling {:?}", buffer);
+    async fn open_stream(
+        element: &super::TcpClientSrc,
+        saddr: SocketAddr,
+        local_addr: Option<SocketAddr>,
+        #[cfg(feature = "tls")] tls_connector: Option<&(tls::TlsConnector, String)>,
+    ) -> Result<TcpClientStream, gst::ErrorMessage> {
+        let socket = match local_addr {
+            Some(local_addr) => Async::<TcpStream>::connect_from(local_addr, saddr)
+                .await
+                .map_err(|err| {
+                    gst::error_msg!(
+                        gst::ResourceError::OpenRead,
+                        [
+                            "Failed to connect to {:?} from {:?}: {:?}",
+                            saddr,
+                            local_addr,
+                            err
+                        ]
+                    )
+                })?,
+            None => Async::<TcpStream>::connect(saddr).await.map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to connect to {:?}: {:?}", saddr, err]
+                )
+            })?,
+        };
 
-        let tcpclientsrc = self.element.imp();
+        gst::debug!(CAT, obj = element, "Connected to {saddr:?}");
 
-        if self.need_initial_events {
-            gst::debug!(CAT, obj = self.element, "Pushing initial events");
+        #[cfg(feature = "tls")]
+        let stream = match tls_connector {
+            Some((connector, host)) => {
+                gst::debug!(CAT, obj = element, "Performing TLS handshake with {host}");
+                TcpClientStream::Tls(Box::new(tls::handshake(connector, host, socket).await?))
+            }
+            None => TcpClientStream::Tcp(socket),
+        };
+        #[cfg(not(feature = "tls"))]
+        let stream = TcpClientStream::Tcp(socket);
 
-            let stream_id = format!("{:08x}{:08x}", rand::random::<u32>(), rand::random::<u32>());
-            let stream_start_evt = gst::event::StreamStart::builder(&stream_id)
-                .group_id(gst::GroupId::next())
-                .build();
-            tcpclientsrc.src_pad.push_event(stream_start_evt).await;
+        Ok(stream)
+    }
 
-            let caps = tcpclientsrc.settings.lock().unwrap().caps.clone();
-            if let Some(caps) = caps {
-                tcpclientsrc
-                    .src_pad
-                    .push_event(gst::event::Caps::new(&caps))
-                    .await;
-                *tcpclientsrc.configured_caps.lock().unwrap() = Some(caps);
+    /// Returns the local address to bind to for connecting to `saddr`, if any.
+    fn local_addr_for(&self, saddr: SocketAddr) -> Option<SocketAddr> {
+        match self.local_ip {
+            Some(local_ip) => Some(SocketAddr::new(local_ip, self.local_port)),
+            None if self.local_port != 0 => {
+                let local_ip: IpAddr = if saddr.is_ipv4() {
+                    Ipv4Addr::UNSPECIFIED.into()
+                } else {
+                    Ipv6Addr::UNSPECIFIED.into()
+                };
+                Some(SocketAddr::new(local_ip, self.local_port))
             }
-
-            self.need_initial_events = false;
+            None => None,
         }
+    }
+
+    async fn connect(&mut self) -> Result<(), gst::ErrorMessage> {
+        // Release the previous socket first: dropping it deactivates
+        // the buffer pool which is shared with the new socket.
+        self.set_disconnected();
+
+        let saddrs = resolve(&self.element, &self.host, self.port, self.local_ip).await?;
+
+        // Try each resolved address in turn until one of them can be connected to
+        let mut res = Err(None);
+        for saddr in saddrs {
+            gst::debug!(CAT, obj = self.element, "Trying to connect to {saddr:?}");
+
+            let stream_fut = Self::open_stream(
+                &self.element,
+                saddr,
+                self.local_addr_for(saddr),
+                #[cfg(feature = "tls")]
+                self.tls.as_ref(),
+            );
 
-        if self.need_segment {
-            let segment_evt =
-                gst::event::Segment::new(&gst::FormattedSegment::<gst::format::Time>::new());
-            tcpclientsrc.src_pad.push_event(segment_evt).await;
+            let stream_res = match self.connect_timeout {
+                Some(timeout) => {
+                    let stream_fut = stream_fut.fuse();
+                    let timeout_fut = timer::delay_for_at_least(timeout).fuse();
 
-            self.need_segment = false;
+                    pin_mut!(stream_fut);
+                    pin_mut!(timeout_fut);
+
+                    futures::select! {
+                        res = stream_fut => res,
+                        _ = timeout_fut => Err(gst::error_msg!(
+                            gst::ResourceError::OpenRead,
+                            ["Connection to {:?} timed out after {:?}", saddr, timeout]
+                        )),
+                    }
+                }
+                None => stream_fut.await,
+            };
+
+            match stream_res {
+                Ok(stream) => {
+                    res = Ok((saddr, stream));
+                    break;
+                }
+                Err(err) => {
+                    gst::debug!(CAT, obj = self.element, "{saddr:?}: {err:?}");
+                    res = Err(Some(err));
+                }
+            }
         }
 
-        if buffer.size() == 0 {
-            tcpclientsrc
-                .src_pad
-                .push_event(gst::event::Eos::new())
-                .await;
-            return Ok(gst::FlowSuccess::Ok);
+        // `resolve` doesn't return an empty list of addresses
+        let (saddr, stream) = res.map_err(Option::unwrap)?;
+
+        self.apply_socket_options(stream.tcp_stream());
+        let peer_addr = stream.tcp_stream().peer_addr().unwrap_or(saddr);
+
+        self.socket = Some(
+            Socket::try_new(
+                self.element.clone().upcast(),
+                self.buffer_pool.clone(),
+                TcpClientReader::new(stream),
+            )
+            .map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to prepare socket {:?}", err]
+                )
+            })?,
+        );
+
+        self.set_connected(peer_addr);
+
+        Ok(())
+    }
+
+    fn apply_socket_options(&self, socket: &TcpStream) {
+        let settings = self.element.imp().settings.lock().unwrap();
+        let socket = socket2::SockRef::from(socket);
+
+        if let Err(err) = socket.set_nodelay(settings.tcp_nodelay) {
+            gst::warning!(CAT, obj = self.element, "Failed to set TCP_NODELAY: {err}");
         }
 
-        let res = tcpclientsrc.src_pad.push(buffer).await;
-        match res {
-            Ok(_) => {
-                gst::log!(CAT, obj = self.element, "Successfully pushed buffer");
-            }
-            Err(gst::FlowError::Flushing) => {
-                gst::debug!(CAT, obj = self.element, "Flushing");
-            }
-            Err(gst::FlowError::Eos) => {
-                gst::debug!(CAT, obj = self.element, "EOS");
-                tcpclientsrc
-                    .src_pad
-                    .push_event(gst::event::Eos::new())
-                    .await;
-            }
-            Err(err) => {
-                gst::error!(CAT, obj = self.element, "Got error {}", err);
-                gst::element_error!(
-                    self.element,
-                    gst::StreamError::Failed,
-                    ("Internal data stream error"),
-                    ["streaming stopped, reason {}", err]
-                );
+        let res = if settings.keepalive {
+            let mut keepalive = socket2::TcpKeepalive::new();
+            if settings.keepalive_time > 0 {
+                keepalive = keepalive.with_time(Duration::from_nanos(settings.keepalive_time));
             }
+            socket.set_tcp_keepalive(&keepalive)
+        } else {
+            socket.set_keepalive(false)
+        };
+        if let Err(err) = res {
+            gst::warning!(
+                CAT,
+                obj = self.element,
+                "Failed to configure keepalive: {err}"
+            );
+        }
+    }
+
+    fn set_connected(&self, peer_addr: SocketAddr) {
+        let imp = self.element.imp();
+        imp.connected.store(true, Ordering::SeqCst);
+
+        gst::info!(CAT, obj = self.element, "Connected to {peer_addr:?}");
+        let _ = self.element.post_message(
+            gst::message::Element::builder(
+                gst::Structure::builder("GstTcpClientSrcConnected")
+                    .field("address", peer_addr.ip().to_string())
+                    .field("port", peer_addr.port() as i32)
+                    .field("bytes-received", imp.bytes_received.load(Ordering::Relaxed))
+                    .build(),
+            )
+            .src(&self.element)
+            .build(),
+        );
+
+        self.element.notify("connected");
+    }
+
+    fn set_disconnected(&mut self) {
+        self.socket = None;
+
+        let imp = self.element.imp();
+        if !imp.connected.swap(false, Ordering::SeqCst) {
+            return;
         }
 
-        res
+        gst::info!(
+            CAT,
+            obj = self.element,
+            "Disconnected from {}:{}",
+            self.host,
+            self.port
+        );
+        let _ = self.element.post_message(
+            gst::message::Element::builder(
+                gst::Structure::builder("GstTcpClientSrcDisconnected")
+                    .field("bytes-received", imp.bytes_received.load(Ordering::Relaxed))
+                    .build(),
+            )
+            .src(&self.element)
+            .build(),
+        );
+
+        self.element.notify("connected");
+    }
+
+    /// Schedules a new connection attempt after `err`.
+    ///
+    /// Returns `err` back if reconnection is disabled or if
+    /// the maximum number of attempts has been reached.
+    fn schedule_reconnect(&mut self, err: gst::ErrorMessage) -> Result<(), gst::ErrorMessage> {
+        self.set_disconnected();
+
+        let settings = self.element.imp().settings.lock().unwrap();
+        if !settings.reconnect {
+            return Err(err);
+        }
+
+        let Some(delay) = self
+            .backoff
+            .next_delay(settings.reconnect_delay, settings.max_reconnect_attempts)
+        else {
+            gst::error!(
+                CAT,
+                obj = self.element,
+                "Giving up after {} reconnection attempts",
+                self.backoff.attempts(),
+            );
+            return Err(err);
+        };
+        drop(settings);
+
+        gst::debug!(CAT, obj = self.element, "Connection failed: {:?}", err);
+        gst::element_warning!(
+            self.element,
+            gst::ResourceError::OpenRead,
+            (
+                "Connection to {}:{} failed, reconnecting",
+                self.host,
+                self.port
+            ),
+            [
+                "Reconnection attempt {} in {:?}",
+                self.backoff.attempts(),
+                delay
+            ]
+        );
+
+        self.reconnect_delay = Some(delay);
+        self.need_discont = true;
+
+        Ok(())
     }
+
+    fn reconnect_enabled(&self) -> bool {
+        self.element.imp().settings.lock().unwrap().reconnect
+    }
+}
+
+/// Resolves `host` without blocking the `Context`.
+///
+/// Only the addresses of the same family as `local_ip`, if any, are returned.
+async fn resolve(
+    element: &super::TcpClientSrc,
+    host: &str,
+    port: u16,
+    local_ip: Option<IpAddr>,
+) -> Result<Vec<SocketAddr>, gst::ErrorMessage> {
+    let saddrs = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => {
+            gst::debug!(CAT, obj = element, "Resolving {host}");
+
+            net::resolve(host, port).await.map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::NotFound,
+                    ["Failed to resolve host '{}': {}", host, err]
+                )
+            })?
+        }
+    };
+
+    let saddrs = saddrs
+        .into_iter()
+        .filter(|saddr: &SocketAddr| match local_ip {
+            Some(local_ip) => local_ip.is_ipv4() == saddr.is_ipv4(),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+
+    if saddrs.is_empty() {
+        return Err(gst::error_msg!(
+            gst::ResourceError::NotFound,
+            ["No usable address found for host '{}'", host]
+        ));
+    }
+
+    gst::debug!(CAT, obj = element, "{host} resolved to {saddrs:?}");
+
+    Ok(saddrs)
 }
 
-impl TaskImpl for TcpClientSrcTask {
-    type Item = gst::Buffer;
+fn handle_element_event(
+    element: &super::TcpClientSrc,
+    event_res: Option<gst::Event>,
+) -> Result<SrcItem, gst::FlowError> {
+    match event_res {
+        Some(event) => {
+            gst::debug!(CAT, obj = element, "Handling element level event {event:?}");
+
+            match event.view() {
+                gst::EventView::Eos(_) => Err(gst::FlowError::Eos),
+                ev => {
+                    gst::error!(CAT, obj = element, "Unexpected event {ev:?} on channel");
+                    Err(gst::FlowError::Error)
+                }
+            }
+        }
+        None => {
+            gst::error!(CAT, obj = element, "Unexpected return on event channel");
+            Err(gst::FlowError::Error)
+        }
+    }
+}
+
+impl SrcTaskImpl for TcpClientSrcTask {
+    fn element(&self) -> &gst::Element {
+        self.element.upcast_ref()
+    }
+
+    fn src_pad(&self) -> PadSrcRef<'_> {
+        self.element.imp().src_pad.as_ref()
+    }
+
+    fn caps(&mut self) -> Option<gst::Caps> {
+        self.element.imp().settings.lock().unwrap().caps.clone()
+    }
+
+    fn caps_pushed(&mut self, caps: &gst::Caps) {
+        *self.element.imp().configured_caps.lock().unwrap() = Some(caps.clone());
+    }
 
     fn prepare(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
         async move {
             gst::log!(
                 CAT,
                 obj = self.element,
-                "Preparing task connecting to {:?}",
-                self.saddr
+                "Preparing task connecting to {}:{}",
+                self.host,
+                self.port
             );
 
-            let socket = Async::<TcpStream>::connect(self.saddr)
-                .await
-                .map_err(|err| {
-                    gst::error_msg!(
-                        gst::ResourceError::OpenRead,
-                        ["Failed to connect to {:?}: {:?}", self.saddr, err]
-                    )
-                })?;
+            {
+                let settings = self.element.imp().settings.lock().unwrap();
+                self.connect_timeout = (settings.connect_timeout > 0)
+                    .then(|| Duration::from_nanos(settings.connect_timeout));
+                self.read_timeout = (settings.read_timeout > 0)
+                    .then(|| Duration::from_nanos(settings.read_timeout));
+            }
 
-            self.socket = Some(
-                Socket::try_new(
-                    self.element.clone().upcast(),
-                    self.buffer_pool.take().unwrap(),
-                    TcpClientReader::new(socket),
-                )
-                .map_err(|err| {
-                    gst::error_msg!(
-                        gst::ResourceError::OpenRead,
-                        ["Failed to prepare socket {:?}", err]
-                    )
-                })?,
-            );
+            self.element
+                .imp()
+                .bytes_received
+                .store(0, Ordering::Relaxed);
+            self.backoff.reset();
+            self.reconnect_delay = None;
+            if let Err(err) = self.connect().await {
+                self.schedule_reconnect(err)?;
+            }
 
             gst::log!(CAT, obj = self.element, "Task prepared");
             Ok(())
@@ -308,56 +666,134 @@ impl TaskImpl for TcpClientSrcTask {
         .boxed()
     }
 
-    fn handle_action_error(
-        &mut self,
-        trigger: task::Trigger,
-        state: TaskState,
-        err: gst::ErrorMessage,
-    ) -> BoxFuture<'_, task::Trigger> {
+    fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
         async move {
-            match trigger {
-                task::Trigger::Prepare => {
-                    gst::error!(CAT, "Task preparation failed: {:?}", err);
-                    self.element.post_error_message(err);
+            loop {
+                if let Some(delay) = self.reconnect_delay.take() {
+                    let event_fut = self.event_receiver.next().fuse();
+                    let delay_fut = timer::delay_for(delay).fuse();
 
-                    task::Trigger::Error
+                    pin_mut!(event_fut);
+                    pin_mut!(delay_fut);
+
+                    futures::select! {
+                        event_res = event_fut => return handle_element_event(&self.element, event_res),
+                        _ = delay_fut => (),
+                    }
+
+                    gst::debug!(
+                        CAT,
+                        obj = self.element,
+                        "Reconnecting to {}:{} (attempt {})",
+                        self.host,
+                        self.port,
+                        self.backoff.attempts(),
+                    );
+
+                    if let Err(err) = self.connect().await {
+                        if let Err(err) = self.schedule_reconnect(err) {
+                            self.element.post_error_message(err);
+                            return Err(gst::FlowError::Error);
+                        }
+                        continue;
+                    }
+
+                    gst::info!(CAT, obj = self.element, "Reconnected to {}:{}", self.host, self.port);
                 }
-                other => unreachable!("Action error for {:?} in state {:?}", other, state),
-            }
-        }
-        .boxed()
-    }
 
-    fn try_next(&mut self) -> BoxFuture<'_, Result<gst::Buffer, gst::FlowError>> {
-        async move {
-            let event_fut = self.event_receiver.next().fuse();
-            let socket_fut = self.socket.as_mut().unwrap().try_next().fuse();
-
-            pin_mut!(event_fut);
-            pin_mut!(socket_fut);
-
-            futures::select! {
-                event_res = event_fut => match event_res {
-                    Some(event) => {
-                        gst::debug!(CAT, obj = self.element, "Handling element level event {event:?}");
-
-                        match event.view() {
-                            gst::EventView::Eos(_) => Err(gst::FlowError::Eos),
-                            ev => {
-                                gst::error!(CAT, obj = self.element, "Unexpected event {ev:?} on channel");
-                                Err(gst::FlowError::Error)
-                            }
+                let socket_res = {
+                    let event_fut = self.event_receiver.next().fuse();
+                    let socket_fut = self.socket.as_mut().unwrap().try_next().fuse();
+                    // The timeout is re-armed for each read
+                    let timeout_fut = match self.read_timeout {
+                        Some(timeout) => timer::delay_for_at_least(timeout).left_future(),
+                        None => future::pending::<()>().right_future(),
+                    }
+                    .fuse();
+
+                    pin_mut!(event_fut);
+                    pin_mut!(socket_fut);
+                    pin_mut!(timeout_fut);
+
+                    futures::select! {
+                        event_res = event_fut => return handle_element_event(&self.element, event_res),
+                        socket_res = socket_fut => Some(socket_res),
+                        _ = timeout_fut => None,
+                    }
+                };
+
+                let Some(socket_res) = socket_res else {
+                    let timeout = self.read_timeout.unwrap();
+                    gst::debug!(CAT, obj = self.element, "No data received for {timeout:?}");
+
+                    let _ = self.element.post_message(
+                        gst::message::Element::builder(
+                            gst::Structure::builder("GstTcpClientSrcReadTimeout")
+                                .field("timeout", timeout.as_nanos() as u64)
+                                .build(),
+                        )
+                        .src(&self.element)
+                        .build(),
+                    );
+
+                    if self.reconnect_enabled() {
+                        let err = gst::error_msg!(
+                            gst::ResourceError::Read,
+                            ["No data received from {}:{} for {:?}", self.host, self.port, timeout]
+                        );
+                        if let Err(err) = self.schedule_reconnect(err) {
+                            self.element.post_error_message(err);
+                            return Err(gst::FlowError::Error);
+                        }
+                    }
+
+                    continue;
+                };
+
+                match socket_res {
+                    Ok((buffer, _saddr)) if buffer.size() == 0 && self.reconnect_enabled() => {
+                        let err = gst::error_msg!(
+                            gst::ResourceError::Read,
+                            ["Connection to {}:{} closed by peer", self.host, self.port]
+                        );
+                        if let Err(err) = self.schedule_reconnect(err) {
+                            self.element.post_error_message(err);
+                            return Err(gst::FlowError::Error);
                         }
                     }
-                    None => {
-                        gst::error!(CAT, obj = self.element, "Unexpected return on event channel");
-                        Err(gst::FlowError::Error)
+                    Ok((buffer, _saddr)) if buffer.size() == 0 => {
+                        gst::debug!(CAT, obj = self.element, "Connection closed by peer");
+                        self.set_disconnected();
+
+                        return Err(gst::FlowError::Eos);
+                    }
+                    Ok((mut buffer, _saddr)) => {
+                        self.backoff.reset();
+                        self.element
+                            .imp()
+                            .bytes_received
+                            .fetch_add(buffer.size() as u64, Ordering::Relaxed);
+
+                        if self.need_discont {
+                            buffer.make_mut().set_flags(gst::BufferFlags::DISCONT);
+                            self.need_discont = false;
+                        }
+
+                        return Ok(SrcItem::Buffer(buffer));
+                    }
+                    Err(SocketError::Io(err)) if self.reconnect_enabled() => {
+                        let err = gst::error_msg!(
+                            gst::ResourceError::Read,
+                            ["Connection to {}:{} lost: {}", self.host, self.port, err]
+                        );
+                        if let Err(err) = self.schedule_reconnect(err) {
+                            self.element.post_error_message(err);
+                            return Err(gst::FlowError::Error);
+                        }
                     }
-                },
-                socket_res = socket_fut => match socket_res {
-                    Ok((buffer, _saddr)) => Ok(buffer),
                     Err(err) => {
                         gst::error!(CAT, obj = self.element, "Got error {err:#}");
+                        self.set_disconnected();
 
                         match err {
                             SocketError::Gst(err) => {
@@ -378,68 +814,19 @@ impl TaskImpl for TcpClientSrcTask {
                             }
                         }
 
-                        Err(gst::FlowError::Error)
+                        return Err(gst::FlowError::Error);
                     }
-                },
+                }
             }
         }
         .boxed()
     }
 
-    fn handle_item(&mut self, buffer: gst::Buffer) -> BoxFuture<'_, Result<(), gst::FlowError>> {
-        self.push_buffer(buffer).map_ok(drop).boxed()
-    }
-
-    fn stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+    fn unprepare(&mut self) -> BoxFuture<'_, ()> {
         async move {
-            gst::log!(CAT, obj = self.element, "Stopping task");
-            self.need_initial_events = true;
-            gst::log!(CAT, obj = self.element, "Task stopped");
-            Ok(())
-        }
-        .boxed()
-    }
-
-    fn flush_stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
-        async move {
-            gst::log!(CAT, obj = self.element, "Stopping task flush");
-            self.need_initial_events = true;
-            gst::log!(CAT, obj = self.element, "Task flush stopped");
-            Ok(())
-        }
-        .boxed()
-    }
-
-    fn handle_loop_error(&mut self, err: gst::FlowError) -> BoxFuture<'_, task::Trigger> {
-        async move {
-            match err {
-                gst::FlowError::Flushing => {
-                    gst::debug!(CAT, obj = self.element, "Flushing");
-
-                    task::Trigger::FlushStart
-                }
-                gst::FlowError::Eos => {
-                    gst::debug!(CAT, obj = self.element, "EOS");
-                    self.element
-                        .imp()
-                        .src_pad
-                        .push_event(gst::event::Eos::new())
-                        .await;
-
-                    task::Trigger::Stop
-                }
-                err => {
-                    gst::error!(CAT, obj = self.element, "Got error {err}");
-                    gst::element_error!(
-                        &self.element,
-                        gst::StreamError::Failed,
-                        ("Internal data stream error"),
-                        ["streaming stopped, reason {}", err]
-                    );
-
-                    task::Trigger::Error
-                }
-            }
+            gst::log!(CAT, obj = self.element, "Unpreparing task");
+            self.set_disconnected();
+            gst::log!(CAT, obj = self.element, "Task unprepared");
         }
         .boxed()
     }
@@ -451,9 +838,11 @@ pub struct TcpClientSrc {
     configured_caps: Mutex<Option<gst::Caps>>,
     settings: Mutex<Settings>,
     state: Mutex<State>,
+    connected: AtomicBool,
+    bytes_received: AtomicU64,
 }
 
-static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+pub(super) static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
     gst::DebugCategory::new(
         "ts-tcpclientsrc",
         gst::DebugColorFlags::empty(),
@@ -462,8 +851,17 @@ static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
 });
 
 impl TcpClientSrc {
+    fn state(&self) -> TaskState {
+        self.task.state()
+    }
+}
+
+impl TsSrcImpl for TcpClientSrc {
+    fn task(&self) -> &Task {
+        &self.task
+    }
+
     fn prepare(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Preparing");
         let settings = self.settings.lock().unwrap().clone();
 
         let context =
@@ -476,23 +874,17 @@ impl TcpClientSrc {
 
         *self.configured_caps.lock().unwrap() = None;
 
-        let host: IpAddr = match settings.host {
+        // The host is resolved by the task, before each connection attempt
+        let host = match settings.host {
             None => {
                 return Err(gst::error_msg!(
                     gst::ResourceError::Settings,
                     ["No host set"]
                 ));
             }
-            Some(ref host) => match host.parse() {
-                Err(err) => {
-                    return Err(gst::error_msg!(
-                        gst::ResourceError::Settings,
-                        ["Invalid host '{}' set: {}", host, err]
-                    ));
-                }
-                Ok(host) => host,
-            },
+            Some(ref host) => host.clone(),
         };
+        let host_ip = host.parse::<IpAddr>().ok();
         let port = settings.port;
 
         let buffer_pool = gst::BufferPool::new();
@@ -505,17 +897,107 @@ impl TcpClientSrc {
             )
         })?;
 
-        let saddr = SocketAddr::new(host, port as u16);
+        let local_ip = match settings.local_address {
+            Some(ref local_address) => {
+                let local_ip: IpAddr = local_address.parse().map_err(|err| {
+                    gst::error_msg!(
+                        gst::ResourceError::Settings,
+                        ["Invalid local-address '{}' set: {}", local_address, err]
+                    )
+                })?;
+
+                if let Some(host_ip) = host_ip {
+                    if local_ip.is_ipv4() != host_ip.is_ipv4() {
+                        return Err(gst::error_msg!(
+                            gst::ResourceError::Settings,
+                            [
+                                "Local address {} and host {} are not of the same family",
+                                local_ip,
+                                host_ip
+                            ]
+                        ));
+                    }
+                }
+
+                Some(local_ip)
+            }
+            None => None,
+        };
+        let local_port = settings.local_port as u16;
+
+        if local_ip.is_some() || local_port != 0 {
+            let local_addr = SocketAddr::new(
+                local_ip.unwrap_or_else(|| match host_ip {
+                    Some(IpAddr::V6(_)) => Ipv6Addr::UNSPECIFIED.into(),
+                    _ => Ipv4Addr::UNSPECIFIED.into(),
+                }),
+                local_port,
+            );
+
+            // Check early that the local address can be bound to,
+            // the socket is bound again for each connection attempt.
+            let socket = socket2::Socket::new(
+                socket2::Domain::for_address(local_addr),
+                socket2::Type::STREAM,
+                Some(socket2::Protocol::TCP),
+            )
+            .and_then(|socket| {
+                socket.set_reuse_address(true)?;
+                socket.bind(&local_addr.into())?;
+                Ok(socket)
+            })
+            .map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to bind to {}: {}", local_addr, err]
+                )
+            })?;
+            drop(socket);
+        }
+
+        #[cfg(feature = "tls")]
+        let tls = if settings.tls {
+            let connector = tls::connector(
+                settings.tls_validation_flags,
+                settings.tls_ca_file.as_deref().map(Path::new),
+                settings.tls_certificate_file.as_deref().map(Path::new),
+                settings.tls_private_key_file.as_deref().map(Path::new),
+            )?;
+
+            // The server identity is verified against the configured host
+            Some((connector, host.clone()))
+        } else {
+            None
+        };
+        #[cfg(not(feature = "tls"))]
+        if settings.tls {
+            return Err(gst::error_msg!(
+                gst::ResourceError::Settings,
+                ["TLS support not enabled in this build"]
+            ));
+        }
 
         let (sender, receiver) = channel(1);
 
+        self.task.set_name(self.obj().name().as_str());
+
         // Don't block on `prepare` as the socket connection takes time.
         // This will be performed in the background and we'll block on
         // `start` which will also ensure `prepare` completed successfully.
         let fut = self
             .task
             .prepare(
-                TcpClientSrcTask::new(self.obj().clone(), saddr, buffer_pool, receiver),
+                SrcTaskAdapter::new(TcpClientSrcTask::new(
+                    self.obj().clone(),
+                    host,
+                    port as u16,
+                    local_ip,
+                    local_port,
+                    #[cfg(feature = "tls")]
+                    tls,
+                    buffer_pool,
+                    receiver,
+                )),
                 context,
             )
             .check()?;
@@ -529,37 +1011,6 @@ impl TcpClientSrc {
 
         Ok(())
     }
-
-    fn unprepare(&self) {
-        gst::debug!(CAT, imp = self, "Unpreparing");
-        self.task.unprepare().block_on().unwrap();
-        gst::debug!(CAT, imp = self, "Unprepared");
-    }
-
-    fn stop(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Stopping");
-        self.task.stop().block_on()?;
-        gst::debug!(CAT, imp = self, "Stopped");
-        Ok(())
-    }
-
-    fn start(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Starting");
-        self.task.start().block_on()?;
-        gst::debug!(CAT, imp = self, "Started");
-        Ok(())
-    }
-
-    fn pause(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Pausing");
-        self.task.pause().block_on()?;
-        gst::debug!(CAT, imp = self, "Paused");
-        Ok(())
-    }
-
-    fn state(&self) -> TaskState {
-        self.task.state()
-    }
 }
 
 #[glib::object_subclass]
@@ -578,6 +1029,8 @@ impl ObjectSubclass for TcpClientSrc {
             configured_caps: Default::default(),
             settings: Default::default(),
             state: Default::default(),
+            connected: AtomicBool::new(false),
+            bytes_received: AtomicU64::new(0),
         }
     }
 }
@@ -599,7 +1052,7 @@ impl ObjectImpl for TcpClientSrc {
                     .build(),
                 glib::ParamSpecString::builder("host")
                     .nick("Host")
-                    .blurb("The host IP address to receive packets from")
+                    .blurb("The host name or IP address to receive packets from")
                     .default_value(DEFAULT_HOST)
                     .build(),
                 glib::ParamSpecInt::builder("port")
@@ -618,6 +1071,88 @@ impl ObjectImpl for TcpClientSrc {
                     .blurb("Size in bytes to read per buffer (-1 = default)")
                     .default_value(DEFAULT_BLOCKSIZE)
                     .build(),
+                glib::ParamSpecBoolean::builder("reconnect")
+                    .nick("Reconnect")
+                    .blurb("Reconnect to the server when the connection fails or is lost")
+                    .default_value(DEFAULT_RECONNECT)
+                    .build(),
+                glib::ParamSpecUInt::builder("reconnect-delay")
+                    .nick("Reconnect Delay")
+                    .blurb(
+                        "Initial delay in ms before reconnecting, doubled on each failed attempt",
+                    )
+                    .minimum(1)
+                    .default_value(DEFAULT_RECONNECT_DELAY.as_millis() as u32)
+                    .build(),
+                glib::ParamSpecUInt::builder("max-reconnect-attempts")
+                    .nick("Max Reconnect Attempts")
+                    .blurb("Maximum number of consecutive reconnection attempts (0 = unlimited)")
+                    .default_value(DEFAULT_MAX_RECONNECT_ATTEMPTS)
+                    .build(),
+                glib::ParamSpecString::builder("local-address")
+                    .nick("Local Address")
+                    .blurb("Local IP address to bind the connection to (NULL = any)")
+                    .default_value(DEFAULT_LOCAL_ADDRESS)
+                    .build(),
+                glib::ParamSpecInt::builder("local-port")
+                    .nick("Local Port")
+                    .blurb("Local port to bind the connection to (0 = ephemeral)")
+                    .minimum(0)
+                    .maximum(u16::MAX as i32)
+                    .default_value(DEFAULT_LOCAL_PORT)
+                    .build(),
+                glib::ParamSpecUInt64::builder("connect-timeout")
+                    .nick("Connect Timeout")
+                    .blurb("Fail if the connection is not established after this many nanoseconds (0 = disabled)")
+                    .default_value(DEFAULT_CONNECT_TIMEOUT)
+                    .build(),
+                glib::ParamSpecUInt64::builder("read-timeout")
+                    .nick("Read Timeout")
+                    .blurb("Post a message after this many nanoseconds without receiving data, reconnecting if enabled (0 = disabled)")
+                    .default_value(DEFAULT_READ_TIMEOUT)
+                    .build(),
+                glib::ParamSpecBoolean::builder("tcp-nodelay")
+                    .nick("TCP No Delay")
+                    .blurb("Disable Nagle's algorithm (TCP_NODELAY)")
+                    .default_value(DEFAULT_TCP_NODELAY)
+                    .build(),
+                glib::ParamSpecBoolean::builder("keepalive")
+                    .nick("Keepalive")
+                    .blurb("Enable TCP keepalive probes to detect dead peers")
+                    .default_value(DEFAULT_KEEPALIVE)
+                    .build(),
+                glib::ParamSpecUInt64::builder("keepalive-time")
+                    .nick("Keepalive Time")
+                    .blurb("Idle time in nanoseconds before sending keepalive probes (0 = system default)")
+                    .default_value(DEFAULT_KEEPALIVE_TIME)
+                    .build(),
+                glib::ParamSpecBoolean::builder("connected")
+                    .nick("Connected")
+                    .blurb("Whether the connection to the server is established")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecBoolean::builder("tls")
+                    .nick("TLS")
+                    .blurb("Use TLS on top of the TCP connection")
+                    .default_value(DEFAULT_TLS)
+                    .build(),
+                glib::ParamSpecFlags::builder::<gio::TlsCertificateFlags>("tls-validation-flags")
+                    .nick("TLS Validation Flags")
+                    .blurb("TLS certificate validation flags used to validate the server certificate")
+                    .default_value(DEFAULT_TLS_VALIDATION_FLAGS)
+                    .build(),
+                glib::ParamSpecString::builder("tls-ca-file")
+                    .nick("TLS CA File")
+                    .blurb("PEM file with the CA certificates to trust (default: system certificates)")
+                    .build(),
+                glib::ParamSpecString::builder("tls-certificate-file")
+                    .nick("TLS Certificate File")
+                    .blurb("PEM file with the client certificate chain, if client authentication is required")
+                    .build(),
+                glib::ParamSpecString::builder("tls-private-key-file")
+                    .nick("TLS Private Key File")
+                    .blurb("PEM file with the private key of the client certificate")
+                    .build(),
             ]
         });
 
@@ -650,6 +1185,53 @@ impl ObjectImpl for TcpClientSrc {
                     value.get::<u32>().expect("type checked upstream").into(),
                 );
             }
+            "reconnect" => {
+                settings.reconnect = value.get().expect("type checked upstream");
+            }
+            "reconnect-delay" => {
+                settings.reconnect_delay = Duration::from_millis(
+                    value.get::<u32>().expect("type checked upstream").into(),
+                );
+            }
+            "max-reconnect-attempts" => {
+                settings.max_reconnect_attempts = value.get().expect("type checked upstream");
+            }
+            "local-address" => {
+                settings.local_address = value.get().expect("type checked upstream");
+            }
+            "local-port" => {
+                settings.local_port = value.get().expect("type checked upstream");
+            }
+            "connect-timeout" => {
+                settings.connect_timeout = value.get().expect("type checked upstream");
+            }
+            "read-timeout" => {
+                settings.read_timeout = value.get().expect("type checked upstream");
+            }
+            "tcp-nodelay" => {
+                settings.tcp_nodelay = value.get().expect("type checked upstream");
+            }
+            "keepalive" => {
+                settings.keepalive = value.get().expect("type checked upstream");
+            }
+            "keepalive-time" => {
+                settings.keepalive_time = value.get().expect("type checked upstream");
+            }
+            "tls" => {
+                settings.tls = value.get().expect("type checked upstream");
+            }
+            "tls-validation-flags" => {
+                settings.tls_validation_flags = value.get().expect("type checked upstream");
+            }
+            "tls-ca-file" => {
+                settings.tls_ca_file = value.get().expect("type checked upstream");
+            }
+            "tls-certificate-file" => {
+                settings.tls_certificate_file = value.get().expect("type checked upstream");
+            }
+            "tls-private-key-file" => {
+                settings.tls_private_key_file = value.get().expect("type checked upstream");
+            }
             _ => unimplemented!(),
         }
     }
@@ -663,6 +1245,22 @@ impl ObjectImpl for TcpClientSrc {
             "blocksize" => settings.blocksize.to_value(),
             "context" => settings.context.to_value(),
             "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
+            "reconnect" => settings.reconnect.to_value(),
+            "reconnect-delay" => (settings.reconnect_delay.as_millis() as u32).to_value(),
+            "max-reconnect-attempts" => settings.max_reconnect_attempts.to_value(),
+            "local-address" => settings.local_address.to_value(),
+            "local-port" => settings.local_port.to_value(),
+            "connect-timeout" => settings.connect_timeout.to_value(),
+            "read-timeout" => settings.read_timeout.to_value(),
+            "tcp-nodelay" => settings.tcp_nodelay.to_value(),
+            "keepalive" => settings.keepalive.to_value(),
+            "keepalive-time" => settings.keepalive_time.to_value(),
+            "connected" => self.connected.load(Ordering::SeqCst).to_value(),
+            "tls" => settings.tls.to_value(),
+            "tls-validation-flags" => settings.tls_validation_flags.to_value(),
+            "tls-ca-file" => settings.tls_ca_file.to_value(),
+            "tls-certificate-file" => settings.tls_certificate_file.to_value(),
+            "tls-private-key-file" => settings.tls_private_key_file.to_value(),
             _ => unimplemented!(),
         }
     }
@@ -713,43 +1311,7 @@ impl ElementImpl for TcpClientSrc {
         &self,
         transition: gst::StateChange,
     ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
-        gst::trace!(CAT, imp = self, "Changing state {:?}", transition);
-
-        match transition {
-            gst::StateChange::NullToReady => {
-                self.prepare().map_err(|err| {
-                    self.post_error_message(err);
-                    gst::StateChangeError
-                })?;
-            }
-            gst::StateChange::PlayingToPaused => {
-                self.pause().map_err(|_| gst::StateChangeError)?;
-            }
-            gst::StateChange::ReadyToNull => {
-                self.unprepare();
-            }
-            _ => (),
-        }
-
-        let mut success = self.parent_change_state(transition)?;
-
-        match transition {
-            gst::StateChange::ReadyToPaused => {
-                success = gst::StateChangeSuccess::NoPreroll;
-            }
-            gst::StateChange::PausedToPlaying => {
-                self.start().map_err(|_| gst::StateChangeError)?;
-            }
-            gst::StateChange::PlayingToPaused => {
-                success = gst::StateChangeSuccess::NoPreroll;
-            }
-            gst::StateChange::PausedToReady => {
-                self.stop().map_err(|_| gst::StateChangeError)?;
-            }
-            _ => (),
-        }
-
-        Ok(success)
+        self.src_change_state(transition)
     }
 
     fn send_event(&self, event: gst::Event) -> bool {
@@ -760,7 +1322,7 @@ impl ElementImpl for TcpClientSrc {
         match event.view() {
             EventView::Eos(_) => {
                 if self.state() != TaskState::Started {
-                    if let Err(err) = self.start() {
+                    if let Err(err) = self.task.start().block_on() {
                         gst::error!(CAT, imp = self, "Failed to start task thread {err:?}");
                     }
                 }
@@ -22,6 +22,8 @@ use gst::glib;
 use gst::prelude::*;
 
 mod imp;
+#[cfg(feature = "tls")]
+mod tls;
 
 glib::wrapper! {
     pub struct TcpClientSrc(ObjectSubclass<imp::TcpClientSrc>) @extends gst::Element, gst::Object;
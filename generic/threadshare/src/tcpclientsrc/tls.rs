@@ -0,0 +1,466 @@
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::{verify_server_cert_signed_by_trust_anchor, verify_server_name};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::ParsedCertificate;
+use rustls::{CertificateError, DigitallySignedStruct, RootCertStore, SignatureScheme};
+
+use std::fs::File;
+use std::io::BufReader;
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::runtime::Async;
+
+pub use futures_rustls::client::TlsStream;
+pub use futures_rustls::TlsConnector;
+
+/// Returns the `gio::TlsCertificateFlags` matching a certificate verification error.
+fn certificate_flag(err: &CertificateError) -> gio::TlsCertificateFlags {
+    match err {
+        CertificateError::UnknownIssuer => gio::TlsCertificateFlags::UNKNOWN_CA,
+        CertificateError::NotValidForName => gio::TlsCertificateFlags::BAD_IDENTITY,
+        CertificateError::NotValidYet => gio::TlsCertificateFlags::NOT_ACTIVATED,
+        CertificateError::Expired => gio::TlsCertificateFlags::EXPIRED,
+        CertificateError::Revoked => gio::TlsCertificateFlags::REVOKED,
+        _ => gio::TlsCertificateFlags::GENERIC_ERROR,
+    }
+}
+
+/// Server certificate verifier only enforcing the checks selected in `validation_flags`.
+///
+/// The chain and the server name are always both checked so that ignoring
+/// a category doesn't skip the checks of the other categories.
+#[derive(Debug)]
+struct Verifier {
+    roots: Arc<RootCertStore>,
+    provider: Arc<CryptoProvider>,
+    validation_flags: gio::TlsCertificateFlags,
+}
+
+impl Verifier {
+    /// Ignores the certificate errors not selected in `validation_flags`.
+    fn waive(&self, res: Result<(), rustls::Error>) -> Result<(), rustls::Error> {
+        match res {
+            Err(rustls::Error::InvalidCertificate(err))
+                if !self.validation_flags.contains(certificate_flag(&err)) =>
+            {
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    fn verify_chain(
+        &self,
+        cert: &ParsedCertificate<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<(), rustls::Error> {
+        verify_server_cert_signed_by_trust_anchor(
+            cert,
+            &self.roots,
+            intermediates,
+            now,
+            self.provider.signature_verification_algorithms.all,
+        )
+    }
+
+    /// Looks for a time at which the chain is within its validity period.
+    ///
+    /// `expired` tells whether the chain expired at `now` or is not valid yet.
+    fn valid_time(
+        &self,
+        cert: &ParsedCertificate<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+        expired: bool,
+    ) -> Option<UnixTime> {
+        const CENTURY_SECS: u64 = 100 * 365 * 24 * 3600;
+
+        // Not valid yet at `before` & expired at `after`
+        let (mut before, mut after) = if expired {
+            (0, now.as_secs())
+        } else {
+            (now.as_secs(), now.as_secs() + CENTURY_SECS)
+        };
+
+        while after - before > 1 {
+            let time = before + (after - before) / 2;
+            let time = UnixTime::since_unix_epoch(Duration::from_secs(time));
+            match self.verify_chain(cert, intermediates, time) {
+                Err(rustls::Error::InvalidCertificate(CertificateError::Expired)) => {
+                    after = time.as_secs()
+                }
+                Err(rustls::Error::InvalidCertificate(CertificateError::NotValidYet)) => {
+                    before = time.as_secs()
+                }
+                _ => return Some(time),
+            }
+        }
+
+        None
+    }
+}
+
+impl ServerCertVerifier for Verifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let cert = ParsedCertificate::try_from(end_entity)?;
+
+        let mut res = self.verify_chain(&cert, intermediates, now);
+        if let Err(rustls::Error::InvalidCertificate(
+            err @ (CertificateError::Expired | CertificateError::NotValidYet),
+        )) = &res
+        {
+            if !self.validation_flags.contains(certificate_flag(err)) {
+                // The validity period is ignored, but the chain must still be
+                // checked, which can only be done while it is valid.
+                let expired = matches!(err, CertificateError::Expired);
+                if let Some(time) = self.valid_time(&cert, intermediates, now, expired) {
+                    res = self.verify_chain(&cert, intermediates, time);
+                }
+            }
+        }
+        self.waive(res)?;
+
+        self.waive(verify_server_name(&cert, server_name))?;
+
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, gst::ErrorMessage> {
+    let file = File::open(path).map_err(|err| {
+        gst::error_msg!(
+            gst::ResourceError::OpenRead,
+            [
+                "Failed to open certificate file {}: {}",
+                path.display(),
+                err
+            ]
+        )
+    })?;
+
+    rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::Settings,
+                [
+                    "Failed to parse certificate file {}: {}",
+                    path.display(),
+                    err
+                ]
+            )
+        })
+}
+
+fn read_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, gst::ErrorMessage> {
+    let file = File::open(path).map_err(|err| {
+        gst::error_msg!(
+            gst::ResourceError::OpenRead,
+            [
+                "Failed to open private key file {}: {}",
+                path.display(),
+                err
+            ]
+        )
+    })?;
+
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::Settings,
+                [
+                    "Failed to parse private key file {}: {}",
+                    path.display(),
+                    err
+                ]
+            )
+        })?
+        .ok_or_else(|| {
+            gst::error_msg!(
+                gst::ResourceError::Settings,
+                ["No private key found in {}", path.display()]
+            )
+        })
+}
+
+/// Builds a `TlsConnector` from the element's TLS settings.
+///
+/// Server certificates are checked against `ca_file` if provided
+/// or against the system certificate store otherwise.
+pub fn connector(
+    validation_flags: gio::TlsCertificateFlags,
+    ca_file: Option<&Path>,
+    certificate_file: Option<&Path>,
+    private_key_file: Option<&Path>,
+) -> Result<TlsConnector, gst::ErrorMessage> {
+    let mut root_store = RootCertStore::empty();
+    if let Some(ca_file) = ca_file {
+        root_store.add_parsable_certificates(read_certs(ca_file)?);
+    } else {
+        let native_certs = rustls_native_certs::load_native_certs();
+        for err in native_certs.errors {
+            gst::warning!(super::imp::CAT, "Failed to load native certificate: {err}");
+        }
+        root_store.add_parsable_certificates(native_certs.certs);
+    }
+
+    if root_store.is_empty() && validation_flags.contains(gio::TlsCertificateFlags::UNKNOWN_CA) {
+        return Err(gst::error_msg!(
+            gst::ResourceError::Settings,
+            ["No trusted CA certificates available"]
+        ));
+    }
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::Settings,
+                ["Failed to configure TLS: {}", err]
+            )
+        })?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(Verifier {
+            roots: Arc::new(root_store),
+            provider,
+            validation_flags,
+        }));
+
+    let config = match (certificate_file, private_key_file) {
+        (Some(certificate_file), Some(private_key_file)) => builder
+            .with_client_auth_cert(
+                read_certs(certificate_file)?,
+                read_private_key(private_key_file)?,
+            )
+            .map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::Settings,
+                    ["Invalid client certificate: {}", err]
+                )
+            })?,
+        (None, None) => builder.with_no_client_auth(),
+        _ => {
+            return Err(gst::error_msg!(
+                gst::ResourceError::Settings,
+                ["Both tls-certificate-file and tls-private-key-file must be set"]
+            ));
+        }
+    };
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// Performs the TLS handshake on `socket`, verifying the server identity against `host`.
+pub async fn handshake(
+    connector: &TlsConnector,
+    host: &str,
+    socket: Async<TcpStream>,
+) -> Result<TlsStream<Async<TcpStream>>, gst::ErrorMessage> {
+    let server_name = ServerName::try_from(host.to_owned()).map_err(|err| {
+        gst::error_msg!(
+            gst::ResourceError::Settings,
+            ["Invalid TLS server name '{}': {}", host, err]
+        )
+    })?;
+
+    connector.connect(server_name, socket).await.map_err(|err| {
+        gst::error_msg!(
+            gst::ResourceError::OpenRead,
+            ["TLS handshake with {} failed: {}", host, err]
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Valid from 2025 to 2125, the leaf is issued by the CA for localhost
+    const CA: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBgjCCASegAwIBAgIUUw0W0tINfmJlP9R8rc6HX4k/wqIwCgYIKoZIzj0EAwIw
+FTETMBEGA1UEAwwKdHMtdGVzdC1jYTAgFw0yNTAxMDEwMDAwMDBaGA8yMTI1MDEw
+MTAwMDAwMFowFTETMBEGA1UEAwwKdHMtdGVzdC1jYTBZMBMGByqGSM49AgEGCCqG
+SM49AwEHA0IABOfVyHGk8QnnKcmeK5HfRsG85PbXBYtkKv8s16J/z595ZDk3vyJG
+ebnN+ySkhiEevLE/oa0MkPSmbotYlabD6iWjUzBRMB0GA1UdDgQWBBRSMIrEg81S
+Ok+qVAMOof3fykvyczAfBgNVHSMEGDAWgBRSMIrEg81SOk+qVAMOof3fykvyczAP
+BgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0kAMEYCIQDBST9T8fQ47JSvvTl9
+JNejWH5MJvt/xqsuI3Ez5JmqdAIhAJ5dt9iegCbqhF0CtPeyw38dv4Uz1BKpRpUS
+XnqfT25n
+-----END CERTIFICATE-----
+";
+    const LEAF: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBpTCCAUugAwIBAgIUPe/ryxoNC6S/rxogAkDKDHulxTAwCgYIKoZIzj0EAwIw
+FTETMBEGA1UEAwwKdHMtdGVzdC1jYTAgFw0yNTAxMDEwMDAwMDBaGA8yMTI1MDEw
+MTAwMDAwMFowFDESMBAGA1UEAwwJbG9jYWxob3N0MFkwEwYHKoZIzj0CAQYIKoZI
+zj0DAQcDQgAEx7fH4RBNAYdmxAhVU7EXd6QhBDW2c0FvinpJK595dtm2/wwJNeVI
+nyg5lMz25EkjbzzPK0whYqSsK2R38Wr8GKN4MHYwCQYDVR0TBAIwADAUBgNVHREE
+DTALgglsb2NhbGhvc3QwEwYDVR0lBAwwCgYIKwYBBQUHAwEwHQYDVR0OBBYEFEPM
+3oXLSFBkV+fej8UhYY+XSdhNMB8GA1UdIwQYMBaAFFIwisSDzVI6T6pUAw6h/d/K
+S/JzMAoGCCqGSM49BAMCA0gAMEUCIHhvZDAjEpC9Ec2kLuk4pqM7nSVrUfS3pDts
+p+CpTE+5AiEAqShRPWq9q5OYHMOibzY19BAe6TJg4Eo0idzG/7KQRMo=
+-----END CERTIFICATE-----
+";
+
+    // 2026-01-01
+    const NOW: u64 = 1_767_225_600;
+    // 2000-01-01
+    const BEFORE: u64 = 946_684_800;
+    // 2200-01-01
+    const AFTER: u64 = 7_258_118_400;
+
+    fn cert(pem: &str) -> CertificateDer<'static> {
+        rustls_pemfile::certs(&mut pem.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap()
+    }
+
+    fn verifier(trusted: bool, ignored: gio::TlsCertificateFlags) -> Verifier {
+        let mut roots = RootCertStore::empty();
+        if trusted {
+            roots.add(cert(CA)).unwrap();
+        }
+
+        Verifier {
+            roots: Arc::new(roots),
+            provider: Arc::new(rustls::crypto::ring::default_provider()),
+            validation_flags: gio::TlsCertificateFlags::VALIDATE_ALL.difference(ignored),
+        }
+    }
+
+    fn verify(verifier: &Verifier, host: &str, now: u64) -> Result<(), CertificateError> {
+        verifier
+            .verify_server_cert(
+                &cert(LEAF),
+                &[],
+                &ServerName::try_from(host.to_owned()).unwrap(),
+                &[],
+                UnixTime::since_unix_epoch(Duration::from_secs(now)),
+            )
+            .map(|_| ())
+            .map_err(|err| match err {
+                rustls::Error::InvalidCertificate(err) => err,
+                other => panic!("Unexpected error {other:?}"),
+            })
+    }
+
+    #[test]
+    fn default_flags() {
+        let trusted = verifier(true, gio::TlsCertificateFlags::empty());
+        verify(&trusted, "localhost", NOW).unwrap();
+
+        let untrusted = verifier(false, gio::TlsCertificateFlags::empty());
+        assert_eq!(
+            verify(&untrusted, "localhost", NOW),
+            Err(CertificateError::UnknownIssuer)
+        );
+    }
+
+    #[test]
+    fn unknown_ca_ignored() {
+        let verifier = verifier(false, gio::TlsCertificateFlags::UNKNOWN_CA);
+        verify(&verifier, "localhost", NOW).unwrap();
+
+        // The server name is still checked
+        assert_eq!(
+            verify(&verifier, "example.com", NOW),
+            Err(CertificateError::NotValidForName)
+        );
+        // And so is the validity period
+        assert_eq!(
+            verify(&verifier, "localhost", AFTER),
+            Err(CertificateError::Expired)
+        );
+    }
+
+    #[test]
+    fn validity_ignored() {
+        let ignored = gio::TlsCertificateFlags::EXPIRED | gio::TlsCertificateFlags::NOT_ACTIVATED;
+
+        let trusted = verifier(true, ignored);
+        verify(&trusted, "localhost", AFTER).unwrap();
+        verify(&trusted, "localhost", BEFORE).unwrap();
+        assert_eq!(
+            verify(&trusted, "example.com", AFTER),
+            Err(CertificateError::NotValidForName)
+        );
+
+        // The chain is still checked
+        let untrusted = verifier(false, ignored);
+        assert_eq!(
+            verify(&untrusted, "localhost", AFTER),
+            Err(CertificateError::UnknownIssuer)
+        );
+        assert_eq!(
+            verify(&untrusted, "localhost", BEFORE),
+            Err(CertificateError::UnknownIssuer)
+        );
+    }
+}
@@ -0,0 +1,715 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use futures::future::{BoxFuture, Either};
+use futures::prelude::*;
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+
+use std::sync::LazyLock;
+
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::runtime::prelude::*;
+use crate::runtime::{Context, PadSrc, PadSrcRef, SrcItem, SrcTaskAdapter, Task, TaskState};
+
+use crate::runtime::Async;
+use crate::socket::{Socket, SocketError, SocketRead};
+use futures::channel::mpsc::{channel, Receiver, Sender};
+use futures::pin_mut;
+
+const DEFAULT_HOST: Option<&str> = Some("127.0.0.1");
+const DEFAULT_PORT: i32 = 4953;
+const DEFAULT_CAPS: Option<gst::Caps> = None;
+const DEFAULT_BLOCKSIZE: u32 = 4096;
+const DEFAULT_CONTEXT: &str = "";
+const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+const DEFAULT_EOS_ON_DISCONNECT: bool = true;
+
+#[derive(Debug, Default)]
+struct State {
+    event_sender: Option<Sender<gst::Event>>,
+}
+
+#[derive(Debug, Clone)]
+struct Settings {
+    host: Option<String>,
+    port: i32,
+    current_port: i32,
+    caps: Option<gst::Caps>,
+    blocksize: u32,
+    context: String,
+    context_wait: Duration,
+    eos_on_disconnect: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            host: DEFAULT_HOST.map(Into::into),
+            port: DEFAULT_PORT,
+            current_port: 0,
+            caps: DEFAULT_CAPS,
+            blocksize: DEFAULT_BLOCKSIZE,
+            context: DEFAULT_CONTEXT.into(),
+            context_wait: DEFAULT_CONTEXT_WAIT,
+            eos_on_disconnect: DEFAULT_EOS_ON_DISCONNECT,
+        }
+    }
+}
+
+struct TcpServerReader(Async<TcpStream>);
+
+impl SocketRead for TcpServerReader {
+    const DO_TIMESTAMP: bool = false;
+
+    fn read<'buf>(
+        &'buf mut self,
+        buffer: &'buf mut [u8],
+    ) -> BoxFuture<'buf, io::Result<(usize, Option<std::net::SocketAddr>)>> {
+        async move { self.0.read(buffer).await.map(|read_size| (read_size, None)) }.boxed()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TcpServerSrcPadHandler;
+
+impl PadSrcHandler for TcpServerSrcPadHandler {
+    type ElementImpl = TcpServerSrc;
+
+    fn src_event(self, pad: &gst::Pad, imp: &TcpServerSrc, event: gst::Event) -> bool {
+        gst::log!(CAT, obj = pad, "Handling {:?}", event);
+
+        use gst::EventView;
+        let ret = match event.view() {
+            EventView::FlushStart(..) => imp.task.flush_start().await_maybe_on_context().is_ok(),
+            EventView::FlushStop(..) => imp.task.flush_stop().await_maybe_on_context().is_ok(),
+            EventView::Reconfigure(..) => true,
+            EventView::Latency(..) => true,
+            _ => false,
+        };
+
+        if ret {
+            gst::log!(CAT, obj = pad, "Handled {:?}", event);
+        } else {
+            gst::log!(CAT, obj = pad, "Didn't handle {:?}", event);
+        }
+
+        ret
+    }
+
+    fn src_query(self, pad: &gst::Pad, imp: &TcpServerSrc, query: &mut gst::QueryRef) -> bool {
+        gst::log!(CAT, obj = pad, "Handling {:?}", query);
+
+        use gst::QueryViewMut;
+        let ret = match query.view_mut() {
+            QueryViewMut::Latency(q) => {
+                q.set(false, gst::ClockTime::ZERO, gst::ClockTime::NONE);
+                true
+            }
+            QueryViewMut::Scheduling(q) => {
+                q.set(gst::SchedulingFlags::SEQUENTIAL, 1, -1, 0);
+                q.add_scheduling_modes(&[gst::PadMode::Push]);
+                true
+            }
+            QueryViewMut::Caps(q) => {
+                let caps = if let Some(caps) = imp.configured_caps.lock().unwrap().as_ref() {
+                    q.filter()
+                        .map(|f| f.intersect_with_mode(caps, gst::CapsIntersectMode::First))
+                        .unwrap_or_else(|| caps.clone())
+                } else {
+                    q.filter()
+                        .map(|f| f.to_owned())
+                        .unwrap_or_else(gst::Caps::new_any)
+                };
+
+                q.set_result(&caps);
+
+                true
+            }
+            _ => false,
+        };
+
+        if ret {
+            gst::log!(CAT, obj = pad, "Handled {:?}", query);
+        } else {
+            gst::log!(CAT, obj = pad, "Didn't handle {:?}", query);
+        }
+
+        ret
+    }
+}
+
+/// Converts an event received on the element's event channel
+/// into the `FlowError` ending the current iteration.
+fn handle_element_event(
+    element: &super::TcpServerSrc,
+    event: Option<gst::Event>,
+) -> gst::FlowError {
+    match event {
+        Some(event) => {
+            gst::debug!(CAT, obj = element, "Handling element level event {event:?}");
+
+            match event.view() {
+                gst::EventView::Eos(_) => gst::FlowError::Eos,
+                ev => {
+                    gst::error!(CAT, obj = element, "Unexpected event {ev:?} on channel");
+                    gst::FlowError::Error
+                }
+            }
+        }
+        None => {
+            gst::error!(CAT, obj = element, "Unexpected return on event channel");
+            gst::FlowError::Error
+        }
+    }
+}
+
+struct TcpServerSrcTask {
+    element: super::TcpServerSrc,
+    saddr: SocketAddr,
+    buffer_pool: gst::BufferPool,
+    listener: Option<Async<TcpListener>>,
+    socket: Option<Socket<TcpServerReader>>,
+    need_discont: bool,
+    event_receiver: Receiver<gst::Event>,
+}
+
+impl TcpServerSrcTask {
+    fn new(
+        element: super::TcpServerSrc,
+        saddr: SocketAddr,
+        buffer_pool: gst::BufferPool,
+        event_receiver: Receiver<gst::Event>,
+    ) -> Self {
+        TcpServerSrcTask {
+            element,
+            saddr,
+            buffer_pool,
+            listener: None,
+            socket: None,
+            need_discont: false,
+            event_receiver,
+        }
+    }
+
+    /// Waits for the next client to connect.
+    async fn accept(&mut self) -> Result<(), gst::FlowError> {
+        let accept_res = {
+            let event_fut = self.event_receiver.next().fuse();
+            let accept_fut = self.listener.as_ref().unwrap().accept().fuse();
+
+            pin_mut!(event_fut);
+            pin_mut!(accept_fut);
+
+            futures::select! {
+                event_res = event_fut => return Err(handle_element_event(&self.element, event_res)),
+                accept_res = accept_fut => accept_res,
+            }
+        };
+
+        let (stream, peer_addr) = accept_res.map_err(|err| {
+            gst::element_error!(
+                self.element,
+                gst::ResourceError::OpenRead,
+                ("Failed to accept client"),
+                ["{err}"]
+            );
+            gst::FlowError::Error
+        })?;
+
+        gst::info!(CAT, obj = self.element, "Accepted client {peer_addr}");
+
+        self.socket = Some(
+            Socket::try_new(
+                self.element.clone().upcast(),
+                self.buffer_pool.clone(),
+                TcpServerReader(stream),
+            )
+            .map_err(|err| {
+                gst::element_error!(
+                    self.element,
+                    gst::ResourceError::OpenRead,
+                    ("Failed to prepare socket"),
+                    ["{err}"]
+                );
+                gst::FlowError::Error
+            })?,
+        );
+
+        Ok(())
+    }
+
+    /// Handles the current client going away.
+    ///
+    /// Returns `Err(Eos)` if `eos-on-disconnect` is set,
+    /// otherwise the next client will be accepted.
+    fn handle_disconnect(&mut self) -> Result<(), gst::FlowError> {
+        gst::info!(CAT, obj = self.element, "Client disconnected");
+        // Drop the socket first so the buffer pool is deactivated before reuse
+        self.socket = None;
+
+        if self
+            .element
+            .imp()
+            .settings
+            .lock()
+            .unwrap()
+            .eos_on_disconnect
+        {
+            return Err(gst::FlowError::Eos);
+        }
+
+        self.need_discont = true;
+
+        Ok(())
+    }
+}
+
+impl SrcTaskImpl for TcpServerSrcTask {
+    fn element(&self) -> &gst::Element {
+        self.element.upcast_ref()
+    }
+
+    fn src_pad(&self) -> PadSrcRef<'_> {
+        self.element.imp().src_pad.as_ref()
+    }
+
+    fn caps(&mut self) -> Option<gst::Caps> {
+        self.element.imp().settings.lock().unwrap().caps.clone()
+    }
+
+    fn caps_pushed(&mut self, caps: &gst::Caps) {
+        *self.element.imp().configured_caps.lock().unwrap() = Some(caps.clone());
+    }
+
+    fn prepare(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        async move {
+            gst::log!(
+                CAT,
+                obj = self.element,
+                "Preparing task listening on {:?}",
+                self.saddr
+            );
+
+            let listener = Async::<TcpListener>::bind(self.saddr).map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to listen on {:?}: {}", self.saddr, err]
+                )
+            })?;
+
+            let port = listener.get_ref().local_addr().map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to retrieve listening address: {}", err]
+                )
+            })?;
+            self.element.imp().settings.lock().unwrap().current_port = port.port() as i32;
+            self.element.notify("current-port");
+
+            self.listener = Some(listener);
+
+            gst::log!(CAT, obj = self.element, "Task prepared");
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn unprepare(&mut self) -> BoxFuture<'_, ()> {
+        async move {
+            gst::log!(CAT, obj = self.element, "Unpreparing task");
+            self.socket = None;
+            self.listener = None;
+            self.element.imp().settings.lock().unwrap().current_port = 0;
+            self.element.notify("current-port");
+            gst::log!(CAT, obj = self.element, "Task unprepared");
+        }
+        .boxed()
+    }
+
+    fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
+        async move {
+            loop {
+                if self.socket.is_none() {
+                    self.accept().await?;
+                }
+
+                let res = {
+                    let event_fut = self.event_receiver.next().fuse();
+                    let socket_fut = self.socket.as_mut().unwrap().try_next().fuse();
+
+                    pin_mut!(event_fut);
+                    pin_mut!(socket_fut);
+
+                    futures::select! {
+                        event_res = event_fut => Either::Left(event_res),
+                        socket_res = socket_fut => Either::Right(socket_res),
+                    }
+                };
+
+                match res {
+                    Either::Left(event_res) => {
+                        return Err(handle_element_event(&self.element, event_res));
+                    }
+                    Either::Right(Ok((buffer, _saddr))) if buffer.size() == 0 => {
+                        self.handle_disconnect()?;
+                    }
+                    Either::Right(Ok((mut buffer, _saddr))) => {
+                        if self.need_discont {
+                            buffer.make_mut().set_flags(gst::BufferFlags::DISCONT);
+                            self.need_discont = false;
+                        }
+
+                        return Ok(SrcItem::Buffer(buffer));
+                    }
+                    Either::Right(Err(SocketError::Io(err))) => {
+                        gst::warning!(CAT, obj = self.element, "Read error {err}");
+                        self.handle_disconnect()?;
+                    }
+                    Either::Right(Err(SocketError::Gst(err))) => {
+                        gst::error!(CAT, obj = self.element, "Got error {err:#}");
+                        gst::element_error!(
+                            self.element,
+                            gst::StreamError::Failed,
+                            ("Internal data stream error"),
+                            ["streaming stopped, reason {err}"]
+                        );
+
+                        return Err(gst::FlowError::Error);
+                    }
+                }
+            }
+        }
+        .boxed()
+    }
+
+    fn stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        async move {
+            gst::log!(CAT, obj = self.element, "Stopping task");
+            self.socket = None;
+            self.need_discont = false;
+            gst::log!(CAT, obj = self.element, "Task stopped");
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+pub struct TcpServerSrc {
+    src_pad: PadSrc,
+    task: Task,
+    configured_caps: Mutex<Option<gst::Caps>>,
+    settings: Mutex<Settings>,
+    state: Mutex<State>,
+}
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-tcpserversrc",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing TCP Server source"),
+    )
+});
+
+impl TcpServerSrc {
+    fn state(&self) -> TaskState {
+        self.task.state()
+    }
+}
+
+impl TsSrcImpl for TcpServerSrc {
+    fn task(&self) -> &Task {
+        &self.task
+    }
+
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
+        let settings = self.settings.lock().unwrap().clone();
+
+        let context =
+            Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to acquire Context: {}", err]
+                )
+            })?;
+
+        *self.configured_caps.lock().unwrap() = None;
+
+        let host: IpAddr = match settings.host {
+            None => {
+                return Err(gst::error_msg!(
+                    gst::ResourceError::Settings,
+                    ["No host set"]
+                ));
+            }
+            Some(ref host) => match host.parse() {
+                Err(err) => {
+                    return Err(gst::error_msg!(
+                        gst::ResourceError::Settings,
+                        ["Invalid host '{}' set: {}", host, err]
+                    ));
+                }
+                Ok(host) => host,
+            },
+        };
+        let port = settings.port;
+
+        let buffer_pool = gst::BufferPool::new();
+        let mut config = buffer_pool.config();
+        config.set_params(None, settings.blocksize, 0, 0);
+        buffer_pool.set_config(config).map_err(|_| {
+            gst::error_msg!(
+                gst::ResourceError::Settings,
+                ["Failed to configure buffer pool"]
+            )
+        })?;
+
+        let saddr = SocketAddr::new(host, port as u16);
+
+        let (sender, receiver) = channel(1);
+
+        self.task.set_name(self.obj().name().as_str());
+
+        // Binding is quick, wait for it so that `current-port`
+        // is available as soon as we are in Ready.
+        self.task
+            .prepare(
+                SrcTaskAdapter::new(TcpServerSrcTask::new(
+                    self.obj().clone(),
+                    saddr,
+                    buffer_pool,
+                    receiver,
+                )),
+                context,
+            )
+            .block_on()?;
+
+        let mut state = self.state.lock().unwrap();
+        state.event_sender = Some(sender);
+        drop(state);
+
+        Ok(())
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for TcpServerSrc {
+    const NAME: &'static str = "GstTsTcpServerSrc";
+    type Type = super::TcpServerSrc;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        Self {
+            src_pad: PadSrc::new(
+                gst::Pad::from_template(&klass.pad_template("src").unwrap()),
+                TcpServerSrcPadHandler,
+            ),
+            task: Task::default(),
+            configured_caps: Default::default(),
+            settings: Default::default(),
+            state: Default::default(),
+        }
+    }
+}
+
+impl ObjectImpl for TcpServerSrc {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("context")
+                    .nick("Context")
+                    .blurb("Context name to share threads with")
+                    .default_value(Some(DEFAULT_CONTEXT))
+                    .build(),
+                glib::ParamSpecUInt::builder("context-wait")
+                    .nick("Context Wait")
+                    .blurb("Throttle poll loop to run at most once every this many ms")
+                    .maximum(1000)
+                    .default_value(DEFAULT_CONTEXT_WAIT.as_millis() as u32)
+                    .build(),
+                glib::ParamSpecString::builder("host")
+                    .nick("Host")
+                    .blurb("The IP address to listen on")
+                    .default_value(DEFAULT_HOST)
+                    .build(),
+                glib::ParamSpecInt::builder("port")
+                    .nick("Port")
+                    .blurb("Port to listen on (0 = allocate a random port)")
+                    .minimum(0)
+                    .maximum(u16::MAX as i32)
+                    .default_value(DEFAULT_PORT)
+                    .build(),
+                glib::ParamSpecInt::builder("current-port")
+                    .nick("Current Port")
+                    .blurb("The port number the socket is currently bound to")
+                    .minimum(0)
+                    .maximum(u16::MAX as i32)
+                    .read_only()
+                    .build(),
+                glib::ParamSpecBoxed::builder::<gst::Caps>("caps")
+                    .nick("Caps")
+                    .blurb("Caps to use")
+                    .build(),
+                glib::ParamSpecUInt::builder("blocksize")
+                    .nick("Blocksize")
+                    .blurb("Size in bytes to read per buffer (-1 = default)")
+                    .default_value(DEFAULT_BLOCKSIZE)
+                    .build(),
+                glib::ParamSpecBoolean::builder("eos-on-disconnect")
+                    .nick("EOS on Disconnect")
+                    .blurb(
+                        "Send EOS when the client disconnects instead of waiting for the next one",
+                    )
+                    .default_value(DEFAULT_EOS_ON_DISCONNECT)
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "host" => {
+                settings.host = value.get().expect("type checked upstream");
+            }
+            "port" => {
+                settings.port = value.get().expect("type checked upstream");
+            }
+            "caps" => {
+                settings.caps = value.get().expect("type checked upstream");
+            }
+            "blocksize" => {
+                settings.blocksize = value.get().expect("type checked upstream");
+            }
+            "context" => {
+                settings.context = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| DEFAULT_CONTEXT.into());
+            }
+            "context-wait" => {
+                settings.context_wait = Duration::from_millis(
+                    value.get::<u32>().expect("type checked upstream").into(),
+                );
+            }
+            "eos-on-disconnect" => {
+                settings.eos_on_disconnect = value.get().expect("type checked upstream");
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "host" => settings.host.to_value(),
+            "port" => settings.port.to_value(),
+            "current-port" => settings.current_port.to_value(),
+            "caps" => settings.caps.to_value(),
+            "blocksize" => settings.blocksize.to_value(),
+            "context" => settings.context.to_value(),
+            "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
+            "eos-on-disconnect" => settings.eos_on_disconnect.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let obj = self.obj();
+        obj.add_pad(self.src_pad.gst_pad()).unwrap();
+        obj.set_element_flags(gst::ElementFlags::SOURCE);
+    }
+}
+
+impl GstObjectImpl for TcpServerSrc {}
+
+impl ElementImpl for TcpServerSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Thread-sharing TCP server source",
+                "Source/Network",
+                "Receives data over the network via TCP from a connecting client",
+                "agent <agent@local>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+            let caps = gst::Caps::new_any();
+            let src_pad_template = gst::PadTemplate::new(
+                "src",
+                gst::PadDirection::Src,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![src_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        self.src_change_state(transition)
+    }
+
+    fn send_event(&self, event: gst::Event) -> bool {
+        use gst::EventView;
+
+        gst::debug!(CAT, imp = self, "Handling element level event {event:?}");
+
+        match event.view() {
+            EventView::Eos(_) => {
+                if self.state() != TaskState::Started {
+                    if let Err(err) = self.task.start().block_on() {
+                        gst::error!(CAT, imp = self, "Failed to start task thread {err:?}");
+                    }
+                }
+
+                if self.state() == TaskState::Started {
+                    let mut state = self.state.lock().unwrap();
+
+                    if let Some(event_tx) = state.event_sender.as_mut() {
+                        return event_tx.try_send(event.clone()).is_ok();
+                    }
+                }
+
+                false
+            }
+            _ => self.parent_send_event(event),
+        }
+    }
+}
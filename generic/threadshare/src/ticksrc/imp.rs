@@ -0,0 +1,443 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use futures::future::BoxFuture;
+use futures::prelude::*;
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::runtime::prelude::*;
+use crate::runtime::{self, timer, PadSrc, PadSrcRef, SrcItem, SrcTaskAdapter, Task};
+
+use super::EventMode;
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-ticksrc",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing tick source"),
+    )
+});
+
+const DEFAULT_CONTEXT: &str = "";
+const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_EVENT_MODE: EventMode = EventMode::Buffer;
+
+const TICK_EVENT_NAME: &str = "ts-tick";
+
+static TICK_CAPS: LazyLock<gst::Caps> =
+    LazyLock::new(|| gst::Caps::builder("application/x-ts-tick").build());
+
+#[derive(Debug, Clone)]
+struct Settings {
+    context: String,
+    context_wait: Duration,
+    interval: Duration,
+    payload: Option<glib::Bytes>,
+    event_mode: EventMode,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            context: DEFAULT_CONTEXT.into(),
+            context_wait: DEFAULT_CONTEXT_WAIT,
+            interval: DEFAULT_INTERVAL,
+            payload: None,
+            event_mode: DEFAULT_EVENT_MODE,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TickSrcPadHandler;
+impl PadSrcHandler for TickSrcPadHandler {
+    type ElementImpl = TickSrc;
+
+    fn src_event(self, pad: &gst::Pad, imp: &Self::ElementImpl, event: gst::Event) -> bool {
+        gst::log!(CAT, obj = pad, "Handling {event:?}");
+
+        use gst::EventView;
+        let ret = match event.view() {
+            EventView::FlushStart(..) => imp.task.flush_start().await_maybe_on_context().is_ok(),
+            EventView::FlushStop(..) => imp.task.flush_stop().await_maybe_on_context().is_ok(),
+            EventView::Reconfigure(..) => true,
+            EventView::Latency(..) => true,
+            _ => false,
+        };
+
+        if ret {
+            gst::log!(CAT, obj = pad, "Handled {event:?}");
+        } else {
+            gst::log!(CAT, obj = pad, "Didn't handle {event:?}");
+        }
+
+        ret
+    }
+
+    fn src_query(self, pad: &gst::Pad, imp: &Self::ElementImpl, query: &mut gst::QueryRef) -> bool {
+        gst::log!(CAT, obj = pad, "Handling {query:?}");
+
+        use gst::QueryViewMut;
+        let ret = match query.view_mut() {
+            QueryViewMut::Latency(q) => {
+                // Ticks are timestamped when they are emitted,
+                // but they might be delayed by the Context throttling.
+                q.set(
+                    true,
+                    gst::ClockTime::ZERO,
+                    runtime::Context::current().map_or(gst::ClockTime::ZERO, |ctx| {
+                        gst::ClockTime::try_from(ctx.wait_duration()).unwrap()
+                    }),
+                );
+                true
+            }
+            QueryViewMut::Scheduling(q) => {
+                q.set(gst::SchedulingFlags::SEQUENTIAL, 1, -1, 0);
+                q.add_scheduling_modes(&[gst::PadMode::Push]);
+                true
+            }
+            QueryViewMut::Caps(q) => {
+                let caps = q
+                    .filter()
+                    .map(|f| f.intersect_with_mode(&TICK_CAPS, gst::CapsIntersectMode::First))
+                    .unwrap_or_else(|| TICK_CAPS.clone());
+                q.set_result(&caps);
+                true
+            }
+            _ => false,
+        };
+
+        if ret {
+            gst::log!(CAT, obj = pad, "Handled {query:?}");
+        } else {
+            gst::log!(CAT, obj = pad, "Didn't handle {query:?}");
+        }
+
+        ret
+    }
+}
+
+#[derive(Debug)]
+struct TickSrcTask {
+    elem: super::TickSrc,
+    interval: Duration,
+    payload: Option<glib::Bytes>,
+    event_mode: EventMode,
+    timer: Option<timer::Interval>,
+    tick_count: u64,
+}
+
+impl TickSrcTask {
+    fn new(elem: super::TickSrc) -> Self {
+        TickSrcTask {
+            elem,
+            interval: DEFAULT_INTERVAL,
+            payload: None,
+            event_mode: DEFAULT_EVENT_MODE,
+            timer: None,
+            tick_count: 0,
+        }
+    }
+}
+
+impl SrcTaskImpl for TickSrcTask {
+    fn element(&self) -> &gst::Element {
+        self.elem.upcast_ref()
+    }
+
+    fn src_pad(&self) -> PadSrcRef<'_> {
+        self.elem.imp().src_pad.as_ref()
+    }
+
+    fn caps(&mut self) -> Option<gst::Caps> {
+        Some(TICK_CAPS.clone())
+    }
+
+    fn prepare(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        gst::log!(CAT, obj = self.elem, "Preparing Task");
+
+        let settings = self.elem.imp().settings.lock().unwrap();
+        self.interval = settings.interval;
+        self.payload = settings.payload.clone();
+        self.event_mode = settings.event_mode;
+
+        future::ok(()).boxed()
+    }
+
+    fn start(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        async move {
+            gst::log!(CAT, obj = self.elem, "Starting Task");
+
+            // (Re)started here so that no ticks are accumulated while paused.
+            self.timer = Some(timer::interval(self.interval).map_err(|_| {
+                gst::error_msg!(
+                    gst::CoreError::Failed,
+                    ["Invalid interval {:?}", self.interval]
+                )
+            })?);
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn pause(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        gst::log!(CAT, obj = self.elem, "Pausing Task");
+        self.timer = None;
+
+        future::ok(()).boxed()
+    }
+
+    fn stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        gst::log!(CAT, obj = self.elem, "Stopping Task");
+
+        self.timer = None;
+        self.tick_count = 0;
+
+        future::ok(()).boxed()
+    }
+
+    fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
+        async move {
+            self.timer.as_mut().unwrap().next().await;
+            gst::log!(CAT, obj = self.elem, "Timer ticked");
+
+            // Without a clock, fall back to timestamps computed from the tick count
+            let duration = gst::ClockTime::try_from(self.interval).unwrap();
+            let ts = self
+                .elem
+                .current_running_time()
+                .unwrap_or_else(|| duration * self.tick_count);
+
+            let item = match self.event_mode {
+                EventMode::Buffer => {
+                    let mut buffer = match self.payload {
+                        Some(ref payload) => gst::Buffer::from_slice(payload.clone()),
+                        None => gst::Buffer::new(),
+                    };
+                    {
+                        let buffer = buffer.get_mut().unwrap();
+                        buffer.set_pts(ts);
+                        buffer.set_duration(duration);
+                        buffer.set_offset(self.tick_count);
+                    }
+
+                    SrcItem::Buffer(buffer)
+                }
+                EventMode::Gap => {
+                    SrcItem::Event(gst::event::Gap::builder(ts).duration(duration).build())
+                }
+                EventMode::Custom => SrcItem::Event(gst::event::CustomDownstream::new(
+                    gst::Structure::builder(TICK_EVENT_NAME)
+                        .field("running-time", ts)
+                        .field("count", self.tick_count)
+                        .build(),
+                )),
+            };
+
+            self.tick_count += 1;
+
+            Ok(item)
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug)]
+pub struct TickSrc {
+    src_pad: PadSrc,
+    task: Task,
+    settings: Mutex<Settings>,
+}
+
+impl TsSrcImpl for TickSrc {
+    fn task(&self) -> &Task {
+        &self.task
+    }
+
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
+        let settings = self.settings.lock().unwrap();
+        let context =
+            runtime::Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to acquire Context: {}", err]
+                )
+            })?;
+        drop(settings);
+
+        self.task.set_name(self.obj().name().as_str());
+
+        self.task
+            .prepare(
+                SrcTaskAdapter::new(TickSrcTask::new(self.obj().clone())),
+                context,
+            )
+            .block_on()?;
+
+        Ok(())
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for TickSrc {
+    const NAME: &'static str = "GstTsTickSrc";
+    type Type = super::TickSrc;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        Self {
+            src_pad: PadSrc::new(
+                gst::Pad::from_template(&klass.pad_template("src").unwrap()),
+                TickSrcPadHandler,
+            ),
+            task: Task::default(),
+            settings: Default::default(),
+        }
+    }
+}
+
+impl ObjectImpl for TickSrc {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("context")
+                    .nick("Context")
+                    .blurb("Context name to share threads with")
+                    .default_value(Some(DEFAULT_CONTEXT))
+                    .build(),
+                glib::ParamSpecUInt::builder("context-wait")
+                    .nick("Context Wait")
+                    .blurb("Throttle poll loop to run at most once every this many ms")
+                    .maximum(1000)
+                    .default_value(DEFAULT_CONTEXT_WAIT.as_millis() as u32)
+                    .build(),
+                glib::ParamSpecUInt::builder("interval")
+                    .nick("Interval")
+                    .blurb("Interval between two ticks in ms")
+                    .minimum(1)
+                    .default_value(DEFAULT_INTERVAL.as_millis() as u32)
+                    .build(),
+                glib::ParamSpecBoxed::builder::<glib::Bytes>("payload")
+                    .nick("Payload")
+                    .blurb("Content of the buffers pushed in buffer mode (empty buffers if unset)")
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("event-mode", DEFAULT_EVENT_MODE)
+                    .nick("Event mode")
+                    .blurb("What to push downstream on each tick")
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "context" => {
+                settings.context = value
+                    .get::<Option<String>>()
+                    .unwrap()
+                    .unwrap_or_else(|| DEFAULT_CONTEXT.into());
+            }
+            "context-wait" => {
+                settings.context_wait = Duration::from_millis(value.get::<u32>().unwrap().into());
+            }
+            "interval" => {
+                settings.interval = Duration::from_millis(value.get::<u32>().unwrap().into());
+            }
+            "payload" => {
+                settings.payload = value.get::<Option<glib::Bytes>>().unwrap();
+            }
+            "event-mode" => {
+                settings.event_mode = value.get::<EventMode>().unwrap();
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "context" => settings.context.to_value(),
+            "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
+            "interval" => (settings.interval.as_millis() as u32).to_value(),
+            "payload" => settings.payload.to_value(),
+            "event-mode" => settings.event_mode.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let obj = self.obj();
+        obj.add_pad(self.src_pad.gst_pad()).unwrap();
+        obj.set_element_flags(gst::ElementFlags::SOURCE);
+    }
+}
+
+impl GstObjectImpl for TickSrc {}
+
+impl ElementImpl for TickSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Thread-sharing tick source",
+                "Source/Generic",
+                "Periodically emits a buffer or an event on a shared Context",
+                "agent <agent@local>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+            let src_pad_template = gst::PadTemplate::new(
+                "src",
+                gst::PadDirection::Src,
+                gst::PadPresence::Always,
+                &TICK_CAPS,
+            )
+            .unwrap();
+
+            vec![src_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        self.src_change_state(transition)
+    }
+}
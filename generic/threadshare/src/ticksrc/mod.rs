@@ -0,0 +1,54 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use gst::glib;
+use gst::prelude::*;
+
+mod imp;
+
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstTsTickSrcEventMode")]
+pub enum EventMode {
+    #[default]
+    #[enum_value(name = "Buffer: push a buffer holding the payload", nick = "buffer")]
+    Buffer,
+    #[enum_value(name = "Gap: push a GAP event", nick = "gap")]
+    Gap,
+    #[enum_value(
+        name = "Custom: push a custom downstream event named ts-tick",
+        nick = "custom"
+    )]
+    Custom,
+}
+
+glib::wrapper! {
+    pub struct TickSrc(ObjectSubclass<imp::TickSrc>) @extends gst::Element, gst::Object;
+}
+
+pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    EventMode::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+
+    gst::Element::register(
+        Some(plugin),
+        "ts-ticksrc",
+        gst::Rank::NONE,
+        TickSrc::static_type(),
+    )
+}
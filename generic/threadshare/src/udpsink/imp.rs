@@ -17,31 +17,42 @@
 //
 // SPDX-License-Identifier: LGPL-2.1-or-later
 
-use futures::future::BoxFuture;
+use futures::future::{abortable, AbortHandle, BoxFuture};
 use futures::prelude::*;
 
+use gio::prelude::*;
+
+use gst::error_msg;
 use gst::glib;
 use gst::prelude::*;
 use gst::subclass::prelude::*;
 use gst::EventView;
-use gst::{element_error, error_msg};
 
 use std::sync::LazyLock;
 
 use crate::net;
-use crate::runtime::executor::block_on_or_add_sub_task;
+use crate::runtime::executor::{block_on_or_add_sub_task, debug_assert_not_context_thread};
+use crate::runtime::net::UdpSocket;
 use crate::runtime::prelude::*;
-use crate::runtime::{self, Async, Context, PadSink};
+use crate::runtime::{self, Context, PadSink};
 use crate::socket::{wrap_socket, GioSocketWrapper};
 
-use std::collections::BTreeSet;
-use std::net::{IpAddr, SocketAddr, UdpSocket};
+use super::OnSendError;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 const DEFAULT_HOST: Option<&str> = Some("127.0.0.1");
 const DEFAULT_PORT: i32 = 5004;
 const DEFAULT_SYNC: bool = true;
+const DEFAULT_TS_OFFSET: i64 = 0;
+const DEFAULT_RENDER_DELAY: gst::ClockTime = gst::ClockTime::ZERO;
+const DEFAULT_MAX_BITRATE: u64 = 0;
+const DEFAULT_ON_SEND_ERROR: OnSendError = OnSendError::Error;
+const MAX_BITRATE_BURST: Duration = Duration::from_millis(10);
 const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0";
 const DEFAULT_BIND_PORT: i32 = 0;
 const DEFAULT_BIND_ADDRESS_V6: &str = "::";
@@ -50,6 +61,7 @@ const DEFAULT_SOCKET: Option<GioSocketWrapper> = None;
 const DEFAULT_USED_SOCKET: Option<GioSocketWrapper> = None;
 const DEFAULT_SOCKET_V6: Option<GioSocketWrapper> = None;
 const DEFAULT_USED_SOCKET_V6: Option<GioSocketWrapper> = None;
+const DEFAULT_CLOSE_SOCKET: bool = true;
 const DEFAULT_AUTO_MULTICAST: bool = true;
 const DEFAULT_LOOP: bool = true;
 const DEFAULT_TTL: u32 = 64;
@@ -82,14 +94,21 @@ impl Default for SocketConf {
 #[derive(Debug, Clone)]
 struct Settings {
     sync: bool,
+    ts_offset: i64,
+    render_delay: gst::ClockTime,
+    max_bitrate: u64,
+    on_send_error: OnSendError,
     bind_address: String,
     bind_port: i32,
     bind_address_v6: String,
     bind_port_v6: i32,
+    used_bind_port: i32,
+    used_bind_port_v6: i32,
     socket: Option<GioSocketWrapper>,
     used_socket: Option<GioSocketWrapper>,
     socket_v6: Option<GioSocketWrapper>,
     used_socket_v6: Option<GioSocketWrapper>,
+    close_socket: bool,
     socket_conf: SocketConf,
     qos_dscp: i32,
     context: String,
@@ -101,14 +120,21 @@ impl Default for Settings {
     fn default() -> Self {
         Settings {
             sync: DEFAULT_SYNC,
+            ts_offset: DEFAULT_TS_OFFSET,
+            render_delay: DEFAULT_RENDER_DELAY,
+            max_bitrate: DEFAULT_MAX_BITRATE,
+            on_send_error: DEFAULT_ON_SEND_ERROR,
             bind_address: DEFAULT_BIND_ADDRESS.into(),
             bind_port: DEFAULT_BIND_PORT,
             bind_address_v6: DEFAULT_BIND_ADDRESS_V6.into(),
             bind_port_v6: DEFAULT_BIND_PORT_V6,
+            used_bind_port: 0,
+            used_bind_port_v6: 0,
             socket: DEFAULT_SOCKET,
             used_socket: DEFAULT_USED_SOCKET,
             socket_v6: DEFAULT_SOCKET_V6,
             used_socket_v6: DEFAULT_USED_SOCKET_V6,
+            close_socket: DEFAULT_CLOSE_SOCKET,
             socket_conf: SocketConf::default(),
             qos_dscp: DEFAULT_QOS_DSCP,
             context: DEFAULT_CONTEXT.into(),
@@ -127,90 +153,171 @@ static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
 });
 
 #[derive(Clone, Debug, Default)]
-struct UdpSinkPadHandler(Arc<futures::lock::Mutex<UdpSinkPadHandlerInner>>);
+struct UdpSinkPadHandler {
+    inner: Arc<futures::lock::Mutex<UdpSinkPadHandlerInner>>,
+    // Kept out of `inner` which is locked while waiting for the clock
+    sync_handle: Arc<Mutex<Option<AbortHandle>>>,
+}
 
 impl UdpSinkPadHandler {
     fn prepare(
         &self,
         imp: &UdpSink,
-        socket: Option<Async<UdpSocket>>,
-        socket_v6: Option<Async<UdpSocket>>,
+        socket: Option<UdpSocket>,
+        socket_v6: Option<UdpSocket>,
         settings: &Settings,
     ) -> Result<(), gst::ErrorMessage> {
+        debug_assert_not_context_thread("UdpSink prepare");
+
         futures::executor::block_on(async move {
-            let mut inner = self.0.lock().await;
+            let mut inner = self.inner.lock().await;
 
             inner.sync = settings.sync;
+            inner.ts_offset = settings.ts_offset;
+            inner.render_delay = settings.render_delay;
+            inner.on_send_error = settings.on_send_error;
+            inner.send_error_posted = false;
+            inner.token_bucket =
+                (settings.max_bitrate > 0).then(|| TokenBucket::new(settings.max_bitrate));
             inner.socket_conf = settings.socket_conf;
             inner.socket = socket;
             inner.socket_v6 = socket_v6;
+            inner.keep_memberships = !settings.close_socket
+                && (settings.socket.is_some() || settings.socket_v6.is_some());
 
             if let Some(multicast_iface) = &settings.multicast_iface {
+                inner.multicast_ifaces = Self::find_multicast_ifaces(imp, multicast_iface)?;
+                inner.apply_multicast_if()?;
+            }
+
+            inner.apply_ttl()?;
+            inner.apply_multicast_loop()?;
+
+            for addr in inner.clients.iter() {
+                inner.configure_client(addr)?;
+            }
+            inner.update_client_stats(&imp.stats);
+
+            Ok(())
+        })
+    }
+
+    fn find_multicast_ifaces(
+        imp: &UdpSink,
+        multicast_iface: &str,
+    ) -> Result<Vec<getifaddrs::Interface>, gst::ErrorMessage> {
+        gst::debug!(
+            CAT,
+            imp = imp,
+            "searching for interface: {}",
+            multicast_iface
+        );
+
+        // The 'InterfaceFilter::name' only checks for the 'name' field , it does not check
+        // whether the given name with the interface 'description' (Friendly Name) on Windows
+
+        // So we first get all the interfaces and then apply filter
+        // for name and description (Friendly Name) of each interface.
+
+        let ifaces = getifaddrs::getifaddrs().map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::OpenRead,
+                ["Failed to find interface {}: {}", multicast_iface, err]
+            )
+        })?;
+
+        let iface_filter = ifaces.filter(|i| {
+            let ip_ver = if i.address.is_ipv4() { "IPv4" } else { "IPv6" };
+
+            if i.name == multicast_iface {
                 gst::debug!(
                     CAT,
                     imp = imp,
-                    "searching for interface: {}",
-                    multicast_iface
+                    "Found interface: {}, version: {ip_ver}",
+                    i.name,
                 );
+                true
+            } else {
+                #[cfg(windows)]
+                if i.description == multicast_iface {
+                    gst::debug!(
+                        CAT,
+                        imp = imp,
+                        "Found interface: {}, version: {ip_ver}",
+                        i.description,
+                    );
+                    return true;
+                }
 
-                // The 'InterfaceFilter::name' only checks for the 'name' field , it does not check
-                // whether the given name with the interface 'description' (Friendly Name) on Windows
+                gst::trace!(CAT, imp = imp, "skipping interface {}", i.name);
+                false
+            }
+        });
 
-                // So we first get all the interfaces and then apply filter
-                // for name and description (Friendly Name) of each interface.
+        let ifaces = iface_filter.collect::<Vec<_>>();
+        if ifaces.is_empty() {
+            return Err(gst::error_msg!(
+                gst::ResourceError::NotFound,
+                ["Interface {} not found", multicast_iface]
+            ));
+        }
 
-                let ifaces = getifaddrs::getifaddrs().map_err(|err| {
-                    gst::error_msg!(
-                        gst::ResourceError::OpenRead,
-                        ["Failed to find interface {}: {}", multicast_iface, err]
-                    )
-                })?;
+        Ok(ifaces)
+    }
 
-                let iface_filter = ifaces.filter(|i| {
-                    let ip_ver = if i.address.is_ipv4() { "IPv4" } else { "IPv6" };
+    fn set_multicast_iface(&self, imp: &UdpSink, multicast_iface: Option<&str>) {
+        futures::executor::block_on(async move {
+            let mut inner = self.inner.lock().await;
+            if inner.socket.is_none() && inner.socket_v6.is_none() {
+                // Will be applied when preparing
+                return;
+            }
 
-                    if &i.name == multicast_iface {
-                        gst::debug!(
-                            CAT,
-                            imp = imp,
-                            "Found interface: {}, version: {ip_ver}",
-                            i.name,
+            let ifaces = match multicast_iface {
+                Some(multicast_iface) => match Self::find_multicast_ifaces(imp, multicast_iface) {
+                    Ok(ifaces) => ifaces,
+                    Err(err) => {
+                        gst::element_imp_warning!(
+                            imp,
+                            gst::ResourceError::NotFound,
+                            ("Keeping previous multicast interface"),
+                            ["{}", err]
                         );
-                        true
-                    } else {
-                        #[cfg(windows)]
-                        if &i.description == multicast_iface {
-                            gst::debug!(
-                                CAT,
-                                imp = imp,
-                                "Found interface: {}, version: {ip_ver}",
-                                i.description,
-                            );
-                            return true;
-                        }
-
-                        gst::trace!(CAT, imp = imp, "skipping interface {}", i.name);
-                        false
+                        return;
                     }
-                });
+                },
+                None => Vec::new(),
+            };
 
-                inner.multicast_ifaces = iface_filter.collect();
+            // Memberships are bound to the interfaces, but those of a shared
+            // socket may still be used by its owner
+            if !inner.keep_memberships {
+                for addr in inner.clients.iter() {
+                    let _ = inner.unconfigure_client(addr);
+                }
             }
 
-            for addr in inner.clients.iter() {
-                inner.configure_client(addr)?;
+            inner.multicast_ifaces = ifaces;
+            if let Err(err) = inner.apply_multicast_if() {
+                gst::warning!(CAT, imp = imp, "{err}");
             }
 
-            Ok(())
+            for addr in inner.clients.iter() {
+                if let Err(err) = inner.configure_client(addr) {
+                    gst::warning!(CAT, imp = imp, "{err}");
+                }
+            }
         })
     }
 
     fn unprepare(&self) {
         futures::executor::block_on(async move {
-            let mut inner = self.0.lock().await;
+            let mut inner = self.inner.lock().await;
 
-            for addr in inner.clients.iter() {
-                let _ = inner.unconfigure_client(addr);
+            if !inner.keep_memberships {
+                for addr in inner.clients.iter() {
+                    let _ = inner.unconfigure_client(addr);
+                }
             }
 
             inner.socket = None;
@@ -220,41 +327,86 @@ impl UdpSinkPadHandler {
 
     fn start(&self) {
         futures::executor::block_on(async move {
-            self.0.lock().await.is_flushing = false;
+            let mut inner = self.inner.lock().await;
+            inner.is_flushing = false;
+            if let Some(token_bucket) = inner.token_bucket.as_mut() {
+                // Start with a full bucket
+                token_bucket.last_refill = None;
+            }
         })
     }
 
     fn stop(&self) {
+        self.abort_sync();
         futures::executor::block_on(async move {
-            self.0.lock().await.is_flushing = true;
+            self.inner.lock().await.is_flushing = true;
         })
     }
 
+    fn abort_sync(&self) {
+        if let Some(sync_handle) = self.sync_handle.lock().unwrap().take() {
+            sync_handle.abort();
+        }
+    }
+
     fn set_sync(&self, sync: bool) {
         futures::executor::block_on(async move {
-            self.0.lock().await.sync = sync;
+            self.inner.lock().await.sync = sync;
+        })
+    }
+
+    fn set_ts_offset(&self, ts_offset: i64) {
+        futures::executor::block_on(async move {
+            self.inner.lock().await.ts_offset = ts_offset;
+        })
+    }
+
+    fn set_render_delay(&self, render_delay: gst::ClockTime) {
+        futures::executor::block_on(async move {
+            self.inner.lock().await.render_delay = render_delay;
+        })
+    }
+
+    fn set_max_bitrate(&self, max_bitrate: u64) {
+        futures::executor::block_on(async move {
+            self.inner.lock().await.token_bucket =
+                (max_bitrate > 0).then(|| TokenBucket::new(max_bitrate));
+        })
+    }
+
+    fn set_on_send_error(&self, on_send_error: OnSendError) {
+        futures::executor::block_on(async move {
+            self.inner.lock().await.on_send_error = on_send_error;
         })
     }
 
     fn set_latency(&self, latency: Option<gst::ClockTime>) {
         futures::executor::block_on(async move {
-            self.0.lock().await.latency = latency;
+            self.inner.lock().await.latency = latency;
         })
     }
 
-    fn set_socket_conf(&self, socket_conf: SocketConf) {
+    fn set_socket_conf(&self, imp: &UdpSink, socket_conf: SocketConf) {
         futures::executor::block_on(async move {
-            self.0.lock().await.socket_conf = socket_conf;
+            let mut inner = self.inner.lock().await;
+            inner.socket_conf = socket_conf;
+
+            if let Err(err) = inner.apply_ttl() {
+                gst::warning!(CAT, imp = imp, "{err}");
+            }
+            if let Err(err) = inner.apply_multicast_loop() {
+                gst::warning!(CAT, imp = imp, "{err}");
+            }
         })
     }
 
     fn clients(&self) -> BTreeSet<SocketAddr> {
-        futures::executor::block_on(async move { self.0.lock().await.clients.clone() })
+        futures::executor::block_on(async move { self.inner.lock().await.clients.clone() })
     }
 
     fn add_client(&self, imp: &UdpSink, addr: SocketAddr) {
         futures::executor::block_on(async move {
-            let mut inner = self.0.lock().await;
+            let mut inner = self.inner.lock().await;
             if inner.clients.contains(&addr) {
                 gst::warning!(CAT, imp = imp, "Not adding client {addr:?} again");
                 return;
@@ -264,6 +416,7 @@ impl UdpSinkPadHandler {
                 Ok(()) => {
                     gst::info!(CAT, imp = imp, "Added client {addr:?}");
                     inner.clients.insert(addr);
+                    inner.update_client_stats(&imp.stats);
                 }
                 Err(err) => {
                     gst::error!(CAT, imp = imp, "Failed to add client {addr:?}: {err}");
@@ -275,11 +428,12 @@ impl UdpSinkPadHandler {
 
     fn remove_client(&self, imp: &UdpSink, addr: SocketAddr) {
         futures::executor::block_on(async move {
-            let mut inner = self.0.lock().await;
+            let mut inner = self.inner.lock().await;
             if inner.clients.take(&addr).is_none() {
                 gst::warning!(CAT, imp = imp, "Not removing unknown client {addr:?}");
                 return;
             }
+            inner.update_client_stats(&imp.stats);
 
             match inner.unconfigure_client(&addr) {
                 Ok(()) => {
@@ -295,7 +449,7 @@ impl UdpSinkPadHandler {
 
     fn replace_clients(&self, imp: &UdpSink, mut new_clients: BTreeSet<SocketAddr>) {
         futures::executor::block_on(async move {
-            let mut inner = self.0.lock().await;
+            let mut inner = self.inner.lock().await;
             if new_clients.is_empty() {
                 gst::info!(CAT, imp = imp, "Clearing clients");
             } else {
@@ -327,6 +481,7 @@ impl UdpSinkPadHandler {
                     inner.clients.insert(addr);
                 }
             }
+            inner.update_client_stats(&imp.stats);
 
             // FIXME: which error handling:
             // - If at least one client could be configured, should we keep going? (current)
@@ -348,7 +503,14 @@ impl PadSinkHandler for UdpSinkPadHandler {
         elem: super::UdpSink,
         buffer: gst::Buffer,
     ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
-        async move { self.0.lock().await.handle_buffer(&elem, buffer).await }.boxed()
+        async move {
+            self.inner
+                .lock()
+                .await
+                .handle_buffer(&elem, buffer, &self.sync_handle)
+                .await
+        }
+        .boxed()
     }
 
     fn sink_chain_list(
@@ -358,12 +520,11 @@ impl PadSinkHandler for UdpSinkPadHandler {
         list: gst::BufferList,
     ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
         async move {
-            let mut inner = self.0.lock().await;
-            for buffer in list.iter_owned() {
-                inner.handle_buffer(&elem, buffer).await?;
-            }
-
-            Ok(gst::FlowSuccess::Ok)
+            self.inner
+                .lock()
+                .await
+                .handle_list(&elem, list, &self.sync_handle)
+                .await
         }
         .boxed()
     }
@@ -382,10 +543,10 @@ impl PadSinkHandler for UdpSinkPadHandler {
                     let _ = elem.post_message(gst::message::Eos::builder().src(&elem).build());
                 }
                 EventView::Segment(e) => {
-                    self.0.lock().await.segment = Some(e.segment().clone());
+                    self.inner.lock().await.segment = Some(e.segment().clone());
                 }
                 EventView::FlushStop(_) => {
-                    self.0.lock().await.is_flushing = false;
+                    self.inner.lock().await.is_flushing = false;
                 }
                 EventView::SinkMessage(e) => {
                     let _ = elem.post_message(e.message());
@@ -402,8 +563,9 @@ impl PadSinkHandler for UdpSinkPadHandler {
         gst::debug!(CAT, imp = imp, "Handling {event:?}");
 
         if let EventView::FlushStart(..) = event.view() {
+            self.abort_sync();
             block_on_or_add_sub_task(async move {
-                self.0.lock().await.is_flushing = true;
+                self.inner.lock().await.is_flushing = true;
             });
         }
 
@@ -411,17 +573,73 @@ impl PadSinkHandler for UdpSinkPadHandler {
     }
 }
 
+/// Token bucket limiting the output bitrate.
+///
+/// Sending is allowed to run into debt, which is paid back
+/// by waiting before the next send.
+#[derive(Debug)]
+struct TokenBucket {
+    /// Bits per second.
+    rate: u64,
+    /// Available bits, negative when in debt.
+    tokens: f64,
+    last_refill: Option<Instant>,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        TokenBucket {
+            rate,
+            tokens: 0.0,
+            last_refill: None,
+        }
+    }
+
+    /// Reserves `bits`, returning how long to wait before they can be sent.
+    fn reserve(&mut self, bits: u64) -> Duration {
+        let now = Instant::now();
+        let rate = self.rate as f64;
+        // Allow bursts up to `MAX_BITRATE_BURST` worth of data
+        let capacity = rate * MAX_BITRATE_BURST.as_secs_f64();
+
+        self.tokens = match self.last_refill {
+            Some(last_refill) => {
+                let elapsed = now.duration_since(last_refill).as_secs_f64();
+                (self.tokens + elapsed * rate).min(capacity)
+            }
+            None => capacity,
+        };
+        self.last_refill = Some(now);
+
+        self.tokens -= bits as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
 #[derive(Debug)]
 struct UdpSinkPadHandlerInner {
     is_flushing: bool,
     sync: bool,
+    ts_offset: i64,
+    render_delay: gst::ClockTime,
+    token_bucket: Option<TokenBucket>,
+    on_send_error: OnSendError,
+    send_error_posted: bool,
     latency: Option<gst::ClockTime>,
-    socket: Option<Async<UdpSocket>>,
-    socket_v6: Option<Async<UdpSocket>>,
+    socket: Option<UdpSocket>,
+    socket_v6: Option<UdpSocket>,
     clients: BTreeSet<SocketAddr>,
+    client_stats: BTreeMap<SocketAddr, Arc<ClientStats>>,
     socket_conf: SocketConf,
     segment: Option<gst::Segment>,
     multicast_ifaces: Vec<getifaddrs::Interface>,
+    /// Whether to keep the multicast memberships when unpreparing,
+    /// i.e. when the provided socket is still used by someone else.
+    keep_memberships: bool,
 }
 
 impl Default for UdpSinkPadHandlerInner {
@@ -429,247 +647,312 @@ impl Default for UdpSinkPadHandlerInner {
         Self {
             is_flushing: true,
             sync: DEFAULT_SYNC,
+            ts_offset: DEFAULT_TS_OFFSET,
+            render_delay: DEFAULT_RENDER_DELAY,
+            token_bucket: None,
+            on_send_error: DEFAULT_ON_SEND_ERROR,
+            send_error_posted: false,
             latency: None,
             socket: None,
             socket_v6: None,
+            client_stats: BTreeMap::new(),
             clients: BTreeSet::from([SocketAddr::new(
                 DEFAULT_HOST.unwrap().parse().unwrap(),
                 DEFAULT_PORT as u16,
             )]),
             socket_conf: Default::default(),
             segment: None,
-            multicast_ifaces: Vec::<getifaddrs::Interface>::new(),
+            multicast_ifaces: Vec::new(),
+            keep_memberships: false,
         }
     }
 }
 
 /// Socket configuration.
 impl UdpSinkPadHandlerInner {
-    fn configure_client(&self, client: &SocketAddr) -> Result<(), gst::ErrorMessage> {
-        if client.ip().is_multicast() {
-            match client.ip() {
-                IpAddr::V4(addr) => {
-                    let Some(socket) = self.socket.as_ref() else {
-                        return Ok(());
-                    };
-
-                    if self.socket_conf.auto_multicast {
-                        for iface in &self.multicast_ifaces {
-                            if !iface.address.is_ipv4() {
-                                gst::debug!(
-                                    CAT,
-                                    "Skipping the IPv6 version of the interface {}",
-                                    iface.name
-                                );
-                                continue;
-                            }
+    /// Joins or leaves the multicast group of `client` on the configured
+    /// interfaces, or on the default interface if no multicast-iface was configured.
+    fn multicast_membership(
+        &self,
+        socket: &UdpSocket,
+        client: &SocketAddr,
+        join: bool,
+    ) -> Result<(), gst::ErrorMessage> {
+        let socket = socket.as_ref();
+        let action = if join { "join" } else { "leave" };
 
-                            gst::debug!(CAT, "interface {} joining the multicast", iface.name);
-                            net::imp::join_multicast_v4(socket.as_ref(), &addr, iface).map_err(
-                                |err| {
-                                    error_msg!(
-                                        gst::ResourceError::OpenWrite,
-                                        [
-                                            "Failed to join multicast group on iface {} for {:?}: {}",
-                                            iface.name,
-                                            client,
-                                            err
-                                        ]
-                                    )
-                                },
-                            )?;
-                        }
+        let ifaces = if self.multicast_ifaces.is_empty() {
+            vec![None]
+        } else {
+            self.multicast_ifaces
+                .iter()
+                .filter(|iface| {
+                    if iface.address.is_ipv4() != client.is_ipv4() {
+                        gst::debug!(
+                            CAT,
+                            "Skipping the other IP version of the interface {}",
+                            iface.name
+                        );
+                        return false;
                     }
+                    true
+                })
+                .map(Some)
+                .collect()
+        };
 
-                    if self.socket_conf.multicast_loop {
-                        socket.as_ref().set_multicast_loop_v4(true).map_err(|err| {
-                            error_msg!(
-                                gst::ResourceError::OpenWrite,
-                                ["Failed to set multicast loop for {:?}: {}", client, err]
-                            )
-                        })?;
-                    }
+        for iface in ifaces {
+            let iface_name = iface.map_or("default", |iface| iface.name.as_str());
+            gst::debug!(
+                CAT,
+                "interface {iface_name}: {action} the multicast group {client}"
+            );
 
-                    socket
-                        .as_ref()
-                        .set_multicast_ttl_v4(self.socket_conf.ttl_mc)
-                        .map_err(|err| {
-                            error_msg!(
-                                gst::ResourceError::OpenWrite,
-                                ["Failed to set multicast ttl for {:?}: {}", client, err]
-                            )
-                        })?;
+            let res = match (client.ip(), iface) {
+                (IpAddr::V4(group), Some(iface)) if join => {
+                    net::imp::join_multicast_v4(socket, &group, iface)
                 }
-                IpAddr::V6(addr) => {
-                    let Some(socket) = self.socket.as_ref() else {
-                        return Err(error_msg!(
-                            gst::ResourceError::OpenWrite,
-                            ["Socket not available"]
-                        ));
-                    };
-
-                    if self.socket_conf.auto_multicast {
-                        for iface in &self.multicast_ifaces {
-                            if !iface.address.is_ipv6() {
-                                gst::debug!(
-                                    CAT,
-                                    "Skipping the IPv4 version of the interface {}",
-                                    iface.name
-                                );
-                                continue;
-                            }
-
-                            gst::debug!(CAT, "interface {} joining the multicast", iface.name);
-                            socket
-                                .as_ref()
-                                .join_multicast_v6(&addr, iface.index.unwrap_or(0))
-                                .map_err(|err| {
-                                    error_msg!(
-                                        gst::ResourceError::OpenWrite,
-                                        [
-                                            "Failed to join multicast group on iface {} for {:?}: {}",
-                                            iface.name,
-                                            client,
-                                            err
-                                        ]
-                                    )
-                                })?;
-                        }
-                    }
-
-                    if self.socket_conf.multicast_loop {
-                        socket.as_ref().set_multicast_loop_v6(true).map_err(|err| {
-                            error_msg!(
-                                gst::ResourceError::OpenWrite,
-                                ["Failed to set multicast loop for {:?}: {}", client, err]
-                            )
-                        })?;
-                    }
-                    /* FIXME no API for set_multicast_ttl_v6 ? */
+                (IpAddr::V4(group), Some(iface)) => {
+                    net::imp::leave_multicast_v4(socket, &group, iface)
                 }
-            }
-        } else {
-            match client.ip() {
-                IpAddr::V4(_) => {
-                    if let Some(socket) = self.socket.as_ref() {
-                        socket
-                            .as_ref()
-                            .set_ttl(self.socket_conf.ttl)
-                            .map_err(|err| {
-                                error_msg!(
-                                    gst::ResourceError::OpenWrite,
-                                    ["Failed to set unicast ttl for {:?}: {}", client, err]
-                                )
-                            })?;
-                    }
+                (IpAddr::V4(group), None) if join => {
+                    socket.join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED)
+                }
+                (IpAddr::V4(group), None) => {
+                    socket.leave_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED)
                 }
-                IpAddr::V6(_) => {
-                    if let Some(socket) = self.socket_v6.as_ref() {
-                        socket
-                            .as_ref()
-                            .set_ttl(self.socket_conf.ttl)
-                            .map_err(|err| {
-                                error_msg!(
-                                    gst::ResourceError::OpenWrite,
-                                    ["Failed to set unicast ttl for {:?}: {}", client, err]
-                                )
-                            })?;
+                (IpAddr::V6(group), iface) => {
+                    let index = iface.and_then(|iface| iface.index).unwrap_or(0);
+                    if join {
+                        socket.join_multicast_v6(&group, index)
+                    } else {
+                        socket.leave_multicast_v6(&group, index)
                     }
                 }
+            };
+
+            match res {
+                Ok(()) => (),
+                // e.g. a provided socket which already joined the group
+                Err(err) if join && err.kind() == std::io::ErrorKind::AddrInUse => {
+                    gst::debug!(
+                        CAT,
+                        "interface {iface_name} already joined the multicast group {client}"
+                    );
+                }
+                Err(err) => {
+                    return Err(error_msg!(
+                        gst::ResourceError::OpenWrite,
+                        [
+                            "Failed to {} multicast group on iface {} for {:?}: {}",
+                            action,
+                            iface_name,
+                            client,
+                            err
+                        ]
+                    ));
+                }
             }
         }
 
         Ok(())
     }
 
-    fn unconfigure_client(&self, client: &SocketAddr) -> Result<(), gst::ErrorMessage> {
-        if client.ip().is_multicast() {
-            match client.ip() {
-                IpAddr::V4(addr) => {
-                    let Some(socket) = self.socket.as_ref() else {
-                        return Err(error_msg!(
-                            gst::ResourceError::OpenWrite,
-                            ["Socket not available"]
-                        ));
-                    };
-
-                    if self.socket_conf.auto_multicast {
-                        for iface in &self.multicast_ifaces {
-                            if !iface.address.is_ipv4() {
-                                gst::debug!(
-                                    CAT,
-                                    "Skipping the IPv6 version of the interface {}",
-                                    iface.name
-                                );
-                                continue;
-                            }
+    /// Keeps the per-client statistics in sync with the clients.
+    fn update_client_stats(&mut self, stats: &Stats) {
+        let clients = &self.clients;
+        self.client_stats.retain(|addr, _| clients.contains(addr));
+        for addr in clients {
+            self.client_stats.entry(*addr).or_default();
+        }
 
-                            gst::debug!(CAT, "interface {} leaving the multicast", iface.name);
-                            net::imp::leave_multicast_v4(socket.as_ref(), &addr, iface).map_err(
-                                |err| {
-                                    error_msg!(
-                                        gst::ResourceError::OpenWrite,
-                                        [
-                                            "Failed to leave multicast group for {:?}: {}",
-                                            client,
-                                            err
-                                        ]
-                                    )
-                                },
-                            )?;
-                        }
-                    }
-                }
-                IpAddr::V6(addr) => {
-                    let Some(socket) = self.socket.as_ref() else {
-                        return Err(error_msg!(
-                            gst::ResourceError::OpenWrite,
-                            ["Socket not available"]
-                        ));
-                    };
-
-                    if self.socket_conf.auto_multicast {
-                        for iface in &self.multicast_ifaces {
-                            if !iface.address.is_ipv6() {
-                                gst::debug!(
-                                    CAT,
-                                    "Skipping the IPv4 version of the interface {}",
-                                    iface.name
-                                );
-                                continue;
-                            }
+        *stats.clients.lock().unwrap() = self.client_stats.clone();
+    }
 
-                            gst::debug!(CAT, "interface {} leaving the multicast", iface.name);
-                            socket
-                                .as_ref()
-                                .leave_multicast_v6(&addr, iface.index.unwrap_or(0))
-                                .map_err(|err| {
-                                    error_msg!(
-                                        gst::ResourceError::OpenWrite,
-                                        [
-                                            "Failed to leave multicast group for {:?}: {}",
-                                            client,
-                                            err
-                                        ]
-                                    )
-                                })?;
-                        }
-                    }
-                }
+    fn configure_client(&self, client: &SocketAddr) -> Result<(), gst::ErrorMessage> {
+        if !client.ip().is_multicast() || !self.socket_conf.auto_multicast {
+            return Ok(());
+        }
+
+        let socket = match client.ip() {
+            IpAddr::V4(_) => {
+                let Some(socket) = self.socket.as_ref() else {
+                    return Ok(());
+                };
+                socket
             }
+            IpAddr::V6(_) => {
+                let Some(socket) = self.socket_v6.as_ref() else {
+                    return Err(error_msg!(
+                        gst::ResourceError::OpenWrite,
+                        ["Socket not available"]
+                    ));
+                };
+                socket
+            }
+        };
+
+        self.multicast_membership(socket, client, true)
+    }
+
+    /// Sets the interface used to send multicast packets.
+    ///
+    /// Falls back to the system default if no interfaces are configured.
+    fn apply_multicast_if(&self) -> Result<(), gst::ErrorMessage> {
+        if let Some(socket) = self.socket.as_ref() {
+            let addr = self
+                .multicast_ifaces
+                .iter()
+                .find_map(|iface| match iface.address {
+                    IpAddr::V4(addr) => Some(addr),
+                    IpAddr::V6(_) => None,
+                })
+                .unwrap_or(Ipv4Addr::UNSPECIFIED);
+
+            gst::debug!(CAT, "Sending IPv4 multicast from {addr}");
+            socket2::SockRef::from(socket.as_ref())
+                .set_multicast_if_v4(&addr)
+                .map_err(|err| {
+                    error_msg!(
+                        gst::ResourceError::OpenWrite,
+                        ["Failed to set multicast interface {}: {}", addr, err]
+                    )
+                })?;
+        }
+
+        if let Some(socket) = self.socket_v6.as_ref() {
+            let index = self
+                .multicast_ifaces
+                .iter()
+                .filter(|iface| iface.address.is_ipv6())
+                .find_map(|iface| iface.index)
+                .unwrap_or(0);
+
+            gst::debug!(CAT, "Sending IPv6 multicast from interface index {index}");
+            socket2::SockRef::from(socket.as_ref())
+                .set_multicast_if_v6(index)
+                .map_err(|err| {
+                    error_msg!(
+                        gst::ResourceError::OpenWrite,
+                        ["Failed to set multicast interface index {}: {}", index, err]
+                    )
+                })?;
         }
 
         Ok(())
     }
+
+    /// Applies the multicast loop parameter to the sockets.
+    fn apply_multicast_loop(&self) -> Result<(), gst::ErrorMessage> {
+        let multicast_loop = self.socket_conf.multicast_loop;
+
+        if let Some(socket) = self.socket.as_ref() {
+            socket
+                .as_ref()
+                .set_multicast_loop_v4(multicast_loop)
+                .map_err(|err| {
+                    error_msg!(
+                        gst::ResourceError::OpenWrite,
+                        [
+                            "Failed to set multicast loop to {}: {}",
+                            multicast_loop,
+                            err
+                        ]
+                    )
+                })?;
+        }
+
+        if let Some(socket) = self.socket_v6.as_ref() {
+            socket
+                .as_ref()
+                .set_multicast_loop_v6(multicast_loop)
+                .map_err(|err| {
+                    error_msg!(
+                        gst::ResourceError::OpenWrite,
+                        [
+                            "Failed to set multicast loop to {}: {}",
+                            multicast_loop,
+                            err
+                        ]
+                    )
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies the unicast & multicast TTLs to the sockets.
+    ///
+    /// IPv6 sockets use the hop limit equivalents.
+    fn apply_ttl(&self) -> Result<(), gst::ErrorMessage> {
+        let SocketConf { ttl, ttl_mc, .. } = self.socket_conf;
+
+        if let Some(socket) = self.socket.as_ref() {
+            let socket = socket.as_ref();
+            socket.set_ttl(ttl).map_err(|err| {
+                error_msg!(
+                    gst::ResourceError::OpenWrite,
+                    ["Failed to set unicast ttl {}: {}", ttl, err]
+                )
+            })?;
+            socket.set_multicast_ttl_v4(ttl_mc).map_err(|err| {
+                error_msg!(
+                    gst::ResourceError::OpenWrite,
+                    ["Failed to set multicast ttl {}: {}", ttl_mc, err]
+                )
+            })?;
+        }
+
+        if let Some(socket) = self.socket_v6.as_ref() {
+            let socket = socket2::SockRef::from(socket.as_ref());
+            socket.set_unicast_hops_v6(ttl).map_err(|err| {
+                error_msg!(
+                    gst::ResourceError::OpenWrite,
+                    ["Failed to set unicast hops {}: {}", ttl, err]
+                )
+            })?;
+            socket.set_multicast_hops_v6(ttl_mc).map_err(|err| {
+                error_msg!(
+                    gst::ResourceError::OpenWrite,
+                    ["Failed to set multicast hops {}: {}", ttl_mc, err]
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn unconfigure_client(&self, client: &SocketAddr) -> Result<(), gst::ErrorMessage> {
+        if !client.ip().is_multicast() || !self.socket_conf.auto_multicast {
+            return Ok(());
+        }
+
+        let socket = match client.ip() {
+            IpAddr::V4(_) => self.socket.as_ref(),
+            IpAddr::V6(_) => self.socket_v6.as_ref(),
+        };
+        let Some(socket) = socket else {
+            return Err(error_msg!(
+                gst::ResourceError::OpenWrite,
+                ["Socket not available"]
+            ));
+        };
+
+        self.multicast_membership(socket, client, false)
+    }
 }
 
 /// Buffer handling.
 impl UdpSinkPadHandlerInner {
-    async fn render(
+    /// Sends `buffer` to all clients.
+    ///
+    /// Returns the number of clients the buffer was sent to
+    /// and the last error if it couldn't be sent to some of them.
+    async fn send(
         &mut self,
         elem: &super::UdpSink,
-        buffer: gst::Buffer,
-    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        buffer: &gst::BufferRef,
+    ) -> Result<(usize, Option<String>), gst::FlowError> {
         let data = buffer.map_readable().map_err(|_| {
             gst::element_error!(
                 elem,
@@ -679,53 +962,355 @@ impl UdpSinkPadHandlerInner {
             gst::FlowError::Error
         })?;
 
+        let now = elem.current_running_time();
+
+        // A failing client must not prevent sending to the others
+        let mut last_err = None;
+        let mut sent = 0;
+        for client in self.clients.iter() {
+            let socket = match client.ip() {
+                IpAddr::V4(_) => &mut self.socket,
+                IpAddr::V6(_) => &mut self.socket_v6,
+            };
+
+            let Some(socket) = socket.as_mut() else {
+                gst::warning!(
+                    CAT,
+                    obj = elem,
+                    "No socket available for sending to {client}"
+                );
+                last_err = Some(format!("No socket available for sending to {client}"));
+                continue;
+            };
+
+            let client_stats = self.client_stats.get(client);
+
+            gst::log!(CAT, obj = elem, "Sending to {client:?}");
+            match socket.send_to(&data, *client).await {
+                Ok(len) => {
+                    sent += 1;
+                    if let Some(client_stats) = client_stats {
+                        client_stats.packets_sent.fetch_add(1, Ordering::Relaxed);
+                        client_stats
+                            .bytes_sent
+                            .fetch_add(len as u64, Ordering::Relaxed);
+                        if let Some(now) = now {
+                            client_stats
+                                .last_send_time
+                                .store(now.nseconds(), Ordering::Relaxed);
+                        }
+                    }
+                }
+                Err(err) => {
+                    if is_fatal_send_error(&err) {
+                        gst::element_error!(
+                            elem,
+                            gst::ResourceError::Write,
+                            ("Socket error"),
+                            ["Failed to send to {}: {}", client, err]
+                        );
+                        return Err(gst::FlowError::Error);
+                    }
+
+                    gst::warning!(CAT, obj = elem, "Failed to send to {client}: {err}");
+                    elem.imp().stats.send_errors.fetch_add(1, Ordering::Relaxed);
+                    if let Some(client_stats) = client_stats {
+                        client_stats.send_errors.fetch_add(1, Ordering::Relaxed);
+                    }
+                    last_err = Some(format!("I/O error sending to {client}: {err}"));
+                }
+            }
+        }
+
+        Ok((sent, last_err))
+    }
+
+    async fn render(
+        &mut self,
+        elem: &super::UdpSink,
+        buffer: gst::Buffer,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let (sent, last_err) = self.send(elem, &buffer).await?;
+        self.handle_send_error(elem, sent, last_err)?;
+
+        gst::log!(CAT, obj = elem, "Sent buffer {buffer:?} to {sent} clients");
+
+        Ok(gst::FlowSuccess::Ok)
+    }
+
+    /// Sends the buffers of `list` to all clients, in order.
+    ///
+    /// Returns the number of datagrams sent, the number of datagrams
+    /// which couldn't be sent and the last error if any.
+    async fn send_list(
+        &mut self,
+        elem: &super::UdpSink,
+        list: &gst::BufferListRef,
+    ) -> Result<(usize, usize, Option<String>), gst::FlowError> {
+        let maps = list
+            .iter()
+            .map(|buffer| buffer.map_readable())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| {
+                gst::element_error!(
+                    elem,
+                    gst::StreamError::Format,
+                    ["Failed to map buffer readable"]
+                );
+                gst::FlowError::Error
+            })?;
+        let datas = maps.iter().map(|map| map.as_slice()).collect::<Vec<_>>();
+
+        let now = elem.current_running_time();
+
+        // A failing datagram or client must not prevent sending the others
+        let mut sent = 0;
+        let mut failed = 0;
+        let mut last_err = None;
         for client in self.clients.iter() {
             let socket = match client.ip() {
                 IpAddr::V4(_) => &mut self.socket,
                 IpAddr::V6(_) => &mut self.socket_v6,
             };
 
-            if let Some(socket) = socket.as_mut() {
-                gst::log!(CAT, obj = elem, "Sending to {client:?}");
-                socket.send_to(&data, *client).await.map_err(|err| {
+            let Some(socket) = socket.as_mut() else {
+                gst::warning!(
+                    CAT,
+                    obj = elem,
+                    "No socket available for sending to {client}"
+                );
+                failed += datas.len();
+                last_err = Some(format!("No socket available for sending to {client}"));
+                continue;
+            };
+
+            let client_stats = self.client_stats.get(client);
+
+            gst::log!(
+                CAT,
+                obj = elem,
+                "Sending {} datagrams to {client:?}",
+                datas.len()
+            );
+            let mut remaining = &datas[..];
+            while !remaining.is_empty() {
+                match socket.send_batch_to(remaining, *client).await {
+                    Ok(0) => break,
+                    Ok(count) => {
+                        sent += count;
+                        if let Some(client_stats) = client_stats {
+                            let len = remaining[..count]
+                                .iter()
+                                .map(|data| data.len())
+                                .sum::<usize>();
+                            client_stats
+                                .packets_sent
+                                .fetch_add(count as u64, Ordering::Relaxed);
+                            client_stats
+                                .bytes_sent
+                                .fetch_add(len as u64, Ordering::Relaxed);
+                            if let Some(now) = now {
+                                client_stats
+                                    .last_send_time
+                                    .store(now.nseconds(), Ordering::Relaxed);
+                            }
+                        }
+
+                        remaining = &remaining[count..];
+                    }
+                    Err(err) => {
+                        if is_fatal_send_error(&err) {
+                            gst::element_error!(
+                                elem,
+                                gst::ResourceError::Write,
+                                ("Socket error"),
+                                ["Failed to send to {}: {}", client, err]
+                            );
+                            return Err(gst::FlowError::Error);
+                        }
+
+                        gst::debug!(CAT, obj = elem, "Failed to send to {client}: {err}");
+                        elem.imp().stats.send_errors.fetch_add(1, Ordering::Relaxed);
+                        if let Some(client_stats) = client_stats {
+                            client_stats.send_errors.fetch_add(1, Ordering::Relaxed);
+                        }
+                        failed += 1;
+                        last_err = Some(format!("I/O error sending to {client}: {err}"));
+
+                        // Skip the failing datagram
+                        remaining = &remaining[1..];
+                    }
+                }
+            }
+        }
+
+        Ok((sent, failed, last_err))
+    }
+
+    async fn render_list(
+        &mut self,
+        elem: &super::UdpSink,
+        list: gst::BufferList,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let (sent, failed, last_err) = self.send_list(elem, &list).await?;
+
+        if failed > 0 {
+            gst::warning!(
+                CAT,
+                obj = elem,
+                "Failed to send {failed} / {} datagrams",
+                failed + sent,
+            );
+        }
+        self.handle_send_error(elem, sent, last_err)?;
+
+        gst::log!(
+            CAT,
+            obj = elem,
+            "Sent list of {} buffers ({sent} datagrams)",
+            list.len(),
+        );
+
+        Ok(gst::FlowSuccess::Ok)
+    }
+
+    /// Applies the `on-send-error` policy.
+    ///
+    /// `sent` is the number of datagrams which could be sent
+    /// and `err` the last error, if any.
+    fn handle_send_error(
+        &mut self,
+        elem: &super::UdpSink,
+        sent: usize,
+        err: Option<String>,
+    ) -> Result<(), gst::FlowError> {
+        let Some(err) = err else {
+            self.send_error_posted = false;
+            return Ok(());
+        };
+
+        match self.on_send_error {
+            OnSendError::Error => {
+                // Only give up if nothing could be sent
+                if sent == 0 {
                     gst::element_error!(
                         elem,
                         gst::StreamError::Failed,
                         ("I/O error"),
-                        ["streaming stopped, I/O error {}", err]
+                        ["streaming stopped, {}", err]
                     );
-                    gst::FlowError::Error
-                })?;
-            } else {
-                gst::element_error!(
-                    elem,
-                    gst::StreamError::Failed,
-                    ("I/O error"),
-                    ["No socket available for sending to {}", client]
-                );
-                return Err(gst::FlowError::Error);
+                    return Err(gst::FlowError::Error);
+                }
+            }
+            OnSendError::Warn => {
+                // Don't flood the bus, post again after a successful send
+                if !self.send_error_posted {
+                    gst::element_warning!(
+                        elem,
+                        gst::ResourceError::Write,
+                        ("Failed to send data"),
+                        ["{}", err]
+                    );
+                    self.send_error_posted = true;
+                }
+            }
+            OnSendError::Ignore => {
+                gst::debug!(CAT, obj = elem, "Ignoring send error: {err}");
             }
         }
 
-        gst::log!(CAT, obj = elem, "Sent buffer {buffer:?} to all clients");
+        Ok(())
+    }
 
-        Ok(gst::FlowSuccess::Ok)
+    /// Computes the running time at which `buffer` is to be sent.
+    fn render_time(&self, buffer: &gst::BufferRef) -> Option<gst::ClockTime> {
+        let rtime = self
+            .segment
+            .as_ref()?
+            .downcast_ref::<gst::format::Time>()?
+            .to_running_time(buffer.pts())?
+            .opt_add(self.latency)?
+            + self.render_delay;
+
+        if self.ts_offset >= 0 {
+            rtime.checked_add(gst::ClockTime::from_nseconds(self.ts_offset as u64))
+        } else {
+            rtime.checked_sub(gst::ClockTime::from_nseconds(self.ts_offset.unsigned_abs()))
+        }
     }
 
     /// Waits until specified time.
-    async fn sync(&self, elem: &super::UdpSink, running_time: gst::ClockTime) {
+    ///
+    /// Returns `Err(Flushing)` if the wait was aborted.
+    async fn sync(
+        &self,
+        elem: &super::UdpSink,
+        running_time: gst::ClockTime,
+        sync_handle: &Mutex<Option<AbortHandle>>,
+    ) -> Result<(), gst::FlowError> {
         let now = elem.current_running_time();
 
+        if now.is_some_and(|now| running_time < now) {
+            gst::debug!(
+                CAT,
+                obj = elem,
+                "Late buffer: running time {running_time} < now {}",
+                now.display(),
+            );
+            elem.imp()
+                .stats
+                .late_buffers
+                .fetch_add(1, Ordering::Relaxed);
+
+            return Ok(());
+        }
+
         if let Ok(Some(delay)) = running_time.opt_checked_sub(now) {
             gst::trace!(CAT, obj = elem, "sync: waiting {delay}");
-            runtime::timer::delay_for(delay.into()).await;
+            Self::wait(delay.into(), sync_handle).await?;
         }
+
+        Ok(())
+    }
+
+    /// Waits until `bytes` can be sent without exceeding the max bitrate.
+    async fn throttle(
+        &mut self,
+        elem: &super::UdpSink,
+        bytes: usize,
+        sync_handle: &Mutex<Option<AbortHandle>>,
+    ) -> Result<(), gst::FlowError> {
+        let Some(bucket) = self.token_bucket.as_mut() else {
+            return Ok(());
+        };
+
+        let delay = bucket.reserve(8 * bytes as u64);
+        if !delay.is_zero() {
+            gst::trace!(CAT, obj = elem, "throttle: waiting {delay:?}");
+            Self::wait(delay, sync_handle).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits for `delay`, returns `Err(Flushing)` if the wait was aborted.
+    async fn wait(
+        delay: Duration,
+        sync_handle: &Mutex<Option<AbortHandle>>,
+    ) -> Result<(), gst::FlowError> {
+        let (delay_fut, abort_handle) = abortable(runtime::timer::delay_for(delay));
+        *sync_handle.lock().unwrap() = Some(abort_handle);
+        let res = delay_fut.await;
+        sync_handle.lock().unwrap().take();
+
+        res.map_err(|_| gst::FlowError::Flushing)
     }
 
     async fn handle_buffer(
         &mut self,
         elem: &super::UdpSink,
         buffer: gst::Buffer,
+        sync_handle: &Mutex<Option<AbortHandle>>,
     ) -> Result<gst::FlowSuccess, gst::FlowError> {
         if self.is_flushing {
             gst::info!(CAT, obj = elem, "Discarding {buffer:?} (flushing)");
@@ -734,16 +1319,8 @@ impl UdpSinkPadHandlerInner {
         }
 
         if self.sync {
-            let rtime = self.segment.as_ref().and_then(|segment| {
-                segment
-                    .downcast_ref::<gst::format::Time>()
-                    .and_then(|segment| segment.to_running_time(buffer.pts()).opt_add(self.latency))
-            });
-
-            if let Some(rtime) = rtime {
-                self.sync(elem, rtime).await;
-
-                if self.is_flushing {
+            if let Some(rtime) = self.render_time(&buffer) {
+                if self.sync(elem, rtime, sync_handle).await.is_err() || self.is_flushing {
                     gst::info!(CAT, obj = elem, "Discarding {buffer:?} (flushing)");
 
                     return Err(gst::FlowError::Flushing);
@@ -751,16 +1328,153 @@ impl UdpSinkPadHandlerInner {
             }
         }
 
+        if self
+            .throttle(elem, buffer.size(), sync_handle)
+            .await
+            .is_err()
+            || self.is_flushing
+        {
+            gst::info!(CAT, obj = elem, "Discarding {buffer:?} (flushing)");
+
+            return Err(gst::FlowError::Flushing);
+        }
+
         gst::debug!(CAT, obj = elem, "Handling {buffer:?}");
 
-        self.render(elem, buffer).await.map_err(|err| {
-            element_error!(
-                elem,
-                gst::StreamError::Failed,
-                ["Failed to render item, stopping task: {}", err]
-            );
-            gst::FlowError::Error
-        })
+        // Errors are posted where they occur
+        self.render(elem, buffer).await
+    }
+
+    async fn handle_list(
+        &mut self,
+        elem: &super::UdpSink,
+        list: gst::BufferList,
+        sync_handle: &Mutex<Option<AbortHandle>>,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        if self.is_flushing {
+            gst::info!(CAT, obj = elem, "Discarding {list:?} (flushing)");
+
+            return Err(gst::FlowError::Flushing);
+        }
+
+        // The whole list is sent in one go, sync on its first buffer
+        if self.sync {
+            if let Some(rtime) = list.get(0).and_then(|buffer| self.render_time(buffer)) {
+                if self.sync(elem, rtime, sync_handle).await.is_err() || self.is_flushing {
+                    gst::info!(CAT, obj = elem, "Discarding {list:?} (flushing)");
+
+                    return Err(gst::FlowError::Flushing);
+                }
+            }
+        }
+
+        if self
+            .throttle(elem, list.calculate_size(), sync_handle)
+            .await
+            .is_err()
+            || self.is_flushing
+        {
+            gst::info!(CAT, obj = elem, "Discarding {list:?} (flushing)");
+
+            return Err(gst::FlowError::Flushing);
+        }
+
+        gst::debug!(CAT, obj = elem, "Handling {list:?}");
+
+        // Errors are posted where they occur
+        self.render_list(elem, list).await
+    }
+}
+
+/// Errors which can't be caused by a transient network condition.
+fn is_fatal_send_error(err: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        matches!(err.raw_os_error(), Some(libc::EBADF | libc::ENOTSOCK))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = err;
+        false
+    }
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    late_buffers: AtomicU64,
+    send_errors: AtomicU64,
+    /// Shared with the pad handler which updates them without locking.
+    clients: Mutex<BTreeMap<SocketAddr, Arc<ClientStats>>>,
+}
+
+impl Stats {
+    fn reset(&self) {
+        self.late_buffers.store(0, Ordering::Relaxed);
+        self.send_errors.store(0, Ordering::Relaxed);
+        for client_stats in self.clients.lock().unwrap().values() {
+            client_stats.reset();
+        }
+    }
+
+    fn to_structure(&self) -> gst::Structure {
+        let clients = self
+            .clients
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(addr, client_stats)| client_stats.to_structure(addr).to_send_value())
+            .collect::<Vec<_>>();
+
+        gst::Structure::builder("application/x-ts-udpsink-stats")
+            .field("late-buffers", self.late_buffers.load(Ordering::Relaxed))
+            .field("send-errors", self.send_errors.load(Ordering::Relaxed))
+            .field("clients", gst::Array::from(clients))
+            .build()
+    }
+}
+
+#[derive(Debug)]
+struct ClientStats {
+    bytes_sent: AtomicU64,
+    packets_sent: AtomicU64,
+    send_errors: AtomicU64,
+    /// Running time of the last send in nanoseconds, `u64::MAX` if none.
+    last_send_time: AtomicU64,
+}
+
+impl Default for ClientStats {
+    fn default() -> Self {
+        ClientStats {
+            bytes_sent: AtomicU64::new(0),
+            packets_sent: AtomicU64::new(0),
+            send_errors: AtomicU64::new(0),
+            last_send_time: AtomicU64::new(u64::MAX),
+        }
+    }
+}
+
+impl ClientStats {
+    fn reset(&self) {
+        self.bytes_sent.store(0, Ordering::Relaxed);
+        self.packets_sent.store(0, Ordering::Relaxed);
+        self.send_errors.store(0, Ordering::Relaxed);
+        self.last_send_time.store(u64::MAX, Ordering::Relaxed);
+    }
+
+    fn to_structure(&self, addr: &SocketAddr) -> gst::Structure {
+        let last_send_time = match self.last_send_time.load(Ordering::Relaxed) {
+            u64::MAX => gst::ClockTime::NONE,
+            time => Some(gst::ClockTime::from_nseconds(time)),
+        };
+
+        gst::Structure::builder("application/x-ts-udpsink-client-stats")
+            .field("host", addr.ip().to_string())
+            .field("port", addr.port() as i32)
+            .field("bytes-sent", self.bytes_sent.load(Ordering::Relaxed))
+            .field("packets-sent", self.packets_sent.load(Ordering::Relaxed))
+            .field("send-errors", self.send_errors.load(Ordering::Relaxed))
+            .field("last-send-time", last_send_time)
+            .build()
     }
 }
 
@@ -776,6 +1490,7 @@ pub struct UdpSink {
     sink_pad_handler: UdpSinkPadHandler,
     settings: Mutex<Settings>,
     ts_ctx: Mutex<Option<Context>>,
+    stats: Stats,
 }
 
 impl UdpSink {
@@ -784,16 +1499,16 @@ impl UdpSink {
         ts_ctx: &Context,
         settings: &mut Settings,
         family: SocketFamily,
-    ) -> Result<Option<Async<UdpSocket>>, gst::ErrorMessage> {
+    ) -> Result<Option<UdpSocket>, gst::ErrorMessage> {
         let wrapped_socket = match family {
             SocketFamily::Ipv4 => &settings.socket,
             SocketFamily::Ipv6 => &settings.socket_v6,
         };
 
         if let Some(ref wrapped_socket) = wrapped_socket {
-            let socket: UdpSocket = wrapped_socket.get();
+            let socket: std::net::UdpSocket = wrapped_socket.get();
             let socket = ts_ctx.enter(|| {
-                Async::<UdpSocket>::try_from(socket).map_err(|err| {
+                UdpSocket::from_std(socket).map_err(|err| {
                     error_msg!(
                         gst::ResourceError::OpenWrite,
                         ["Failed to setup Async socket: {}", err]
@@ -809,6 +1524,7 @@ impl UdpSink {
                     settings.used_socket_v6 = Some(wrapped_socket.clone());
                 }
             }
+            self.set_used_bind_port(settings, &family, &socket);
 
             Ok(Some(socket))
         } else {
@@ -865,12 +1581,12 @@ impl UdpSink {
             socket.bind(&saddr.into()).map_err(|err| {
                 error_msg!(
                     gst::ResourceError::OpenWrite,
-                    ["Failed to bind socket: {}", err]
+                    ["Failed to bind socket to {}: {}", saddr, err]
                 )
             })?;
 
             let socket = ts_ctx.enter(|| {
-                Async::<UdpSocket>::try_from(socket).map_err(|err| {
+                UdpSocket::try_from(socket).map_err(|err| {
                     error_msg!(
                         gst::ResourceError::OpenWrite,
                         ["Failed to setup Async socket: {}", err]
@@ -878,17 +1594,19 @@ impl UdpSink {
                 })
             })?;
 
-            let wrapper = wrap_socket(&socket)?;
-
             if settings.qos_dscp != -1 {
-                wrapper.set_tos(settings.qos_dscp).map_err(|err| {
-                    error_msg!(
-                        gst::ResourceError::OpenWrite,
-                        ["Failed to set QoS DSCP: {}", err]
-                    )
-                })?;
+                socket
+                    .set_tos(((settings.qos_dscp & 0x3f) << 2) as u8)
+                    .map_err(|err| {
+                        error_msg!(
+                            gst::ResourceError::OpenWrite,
+                            ["Failed to set QoS DSCP: {}", err]
+                        )
+                    })?;
             }
 
+            let wrapper = wrap_socket(&socket)?;
+
             match family {
                 SocketFamily::Ipv4 => {
                     settings.used_socket = Some(wrapper);
@@ -897,11 +1615,35 @@ impl UdpSink {
                     settings.used_socket_v6 = Some(wrapper);
                 }
             }
+            self.set_used_bind_port(settings, &family, &socket);
 
             Ok(Some(socket))
         }
     }
 
+    fn set_used_bind_port(
+        &self,
+        settings: &mut Settings,
+        family: &SocketFamily,
+        socket: &UdpSocket,
+    ) {
+        let port = match socket.as_ref().local_addr() {
+            Ok(saddr) => {
+                gst::debug!(CAT, imp = self, "Socket bound to {saddr}");
+                saddr.port() as i32
+            }
+            Err(err) => {
+                gst::warning!(CAT, imp = self, "Failed to retrieve local address: {err}");
+                0
+            }
+        };
+
+        match family {
+            SocketFamily::Ipv4 => settings.used_bind_port = port,
+            SocketFamily::Ipv6 => settings.used_bind_port_v6 = port,
+        }
+    }
+
     fn prepare(&self) -> Result<(), gst::ErrorMessage> {
         gst::debug!(CAT, imp = self, "Preparing");
 
@@ -930,6 +1672,24 @@ impl UdpSink {
         gst::debug!(CAT, imp = self, "Unpreparing");
         self.sink_pad_handler.unprepare();
         *self.ts_ctx.lock().unwrap() = None;
+        self.stats.reset();
+
+        let mut settings = self.settings.lock().unwrap();
+        if settings.close_socket {
+            for wrapped_socket in [&settings.socket, &settings.socket_v6]
+                .into_iter()
+                .flatten()
+            {
+                gst::debug!(CAT, imp = self, "Closing provided socket");
+                if let Err(err) = wrapped_socket.as_socket().close() {
+                    gst::warning!(CAT, imp = self, "Failed to close provided socket: {}", err);
+                }
+            }
+        }
+        settings.used_socket = None;
+        settings.used_socket_v6 = None;
+        settings.used_bind_port = 0;
+        settings.used_bind_port_v6 = 0;
         gst::debug!(CAT, imp = self, "Unprepared");
     }
 
@@ -984,6 +1744,7 @@ impl ObjectSubclass for UdpSink {
             sink_pad_handler,
             settings: Default::default(),
             ts_ctx: Default::default(),
+            stats: Default::default(),
         }
     }
 }
@@ -1008,6 +1769,26 @@ impl ObjectImpl for UdpSink {
                     .blurb("Sync on the clock")
                     .default_value(DEFAULT_SYNC)
                     .build(),
+                glib::ParamSpecInt64::builder("ts-offset")
+                    .nick("TS Offset")
+                    .blurb("Timestamp offset in nanoseconds applied when syncing on the clock")
+                    .default_value(DEFAULT_TS_OFFSET)
+                    .build(),
+                glib::ParamSpecUInt64::builder("render-delay")
+                    .nick("Render Delay")
+                    .blurb("Additional delay in nanoseconds applied when syncing on the clock")
+                    .maximum(u64::MAX - 1)
+                    .default_value(DEFAULT_RENDER_DELAY.nseconds())
+                    .build(),
+                glib::ParamSpecUInt64::builder("max-bitrate")
+                    .nick("Max Bitrate")
+                    .blurb("Maximum output bitrate in bits per second (0 = unlimited)")
+                    .default_value(DEFAULT_MAX_BITRATE)
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("on-send-error", DEFAULT_ON_SEND_ERROR)
+                    .nick("On Send Error")
+                    .blurb("How to handle errors sending to the clients")
+                    .build(),
                 glib::ParamSpecString::builder("bind-address")
                     .nick("Bind Address")
                     .blurb("Address to bind the socket to")
@@ -1032,6 +1813,20 @@ impl ObjectImpl for UdpSink {
                     .maximum(u16::MAX as i32)
                     .default_value(DEFAULT_BIND_PORT_V6)
                     .build(),
+                glib::ParamSpecInt::builder("used-bind-port")
+                    .nick("Used Bind Port")
+                    .blurb("Port the socket is actually bound to (0 = not bound)")
+                    .minimum(0)
+                    .maximum(u16::MAX as i32)
+                    .read_only()
+                    .build(),
+                glib::ParamSpecInt::builder("used-bind-port-v6")
+                    .nick("Used Bind Port V6")
+                    .blurb("Port the V6 socket is actually bound to (0 = not bound)")
+                    .minimum(0)
+                    .maximum(u16::MAX as i32)
+                    .read_only()
+                    .build(),
                 glib::ParamSpecObject::builder::<gio::Socket>("socket")
                     .nick("Socket")
                     .blurb("Socket to use for UDP transmission. (None == allocate)")
@@ -1050,9 +1845,14 @@ impl ObjectImpl for UdpSink {
                     .blurb("V6 Socket currently in use for UDP transmission. (None = no socket)")
                     .read_only()
                     .build(),
+                glib::ParamSpecBoolean::builder("close-socket")
+                    .nick("Close Socket")
+                    .blurb("Close the provided sockets when the element shuts down")
+                    .default_value(DEFAULT_CLOSE_SOCKET)
+                    .build(),
                 glib::ParamSpecBoolean::builder("auto-multicast")
                     .nick("Auto multicast")
-                    .blurb("Automatically join/leave the multicast groups, FALSE means user has to do it himself")
+                    .blurb("Automatically join/leave the multicast groups, FALSE means user has to do it himself. Groups are not left if close-socket is FALSE for a provided socket")
                     .default_value(DEFAULT_AUTO_MULTICAST)
                     .build(),
                 glib::ParamSpecBoolean::builder("loop")
@@ -1086,9 +1886,14 @@ impl ObjectImpl for UdpSink {
                     .build(),
                 glib::ParamSpecString::builder("multicast-iface")
                     .nick("Multicast Interface")
-                    .blurb("The network interface on which to join the multicast group. (Supports only single interface)")
+                    .blurb("The network interface on which to join the multicast group and to send multicast packets. (Supports only single interface)")
                     .default_value(DEFAULT_MULTICAST_IFACE)
                     .build(),
+                glib::ParamSpecBoxed::builder::<gst::Structure>("stats")
+                    .nick("Statistics")
+                    .blurb("Send statistics")
+                    .read_only()
+                    .build(),
             ]
         });
 
@@ -1155,6 +1960,27 @@ impl ObjectImpl for UdpSink {
                 settings.sync = sync;
                 self.sink_pad_handler.set_sync(sync);
             }
+            "ts-offset" => {
+                let ts_offset = value.get().expect("type checked upstream");
+                settings.ts_offset = ts_offset;
+                self.sink_pad_handler.set_ts_offset(ts_offset);
+            }
+            "render-delay" => {
+                let render_delay =
+                    gst::ClockTime::from_nseconds(value.get().expect("type checked upstream"));
+                settings.render_delay = render_delay;
+                self.sink_pad_handler.set_render_delay(render_delay);
+            }
+            "on-send-error" => {
+                let on_send_error = value.get().expect("type checked upstream");
+                settings.on_send_error = on_send_error;
+                self.sink_pad_handler.set_on_send_error(on_send_error);
+            }
+            "max-bitrate" => {
+                let max_bitrate = value.get().expect("type checked upstream");
+                settings.max_bitrate = max_bitrate;
+                self.sink_pad_handler.set_max_bitrate(max_bitrate);
+            }
             "bind-address" => {
                 settings.bind_address = value
                     .get::<Option<String>>()
@@ -1191,21 +2017,28 @@ impl ObjectImpl for UdpSink {
             "used-socket-v6" => {
                 unreachable!();
             }
+            "close-socket" => {
+                settings.close_socket = value.get().expect("type checked upstream");
+            }
             "auto-multicast" => {
                 settings.socket_conf.auto_multicast = value.get().expect("type checked upstream");
-                self.sink_pad_handler.set_socket_conf(settings.socket_conf);
+                self.sink_pad_handler
+                    .set_socket_conf(self, settings.socket_conf);
             }
             "loop" => {
                 settings.socket_conf.multicast_loop = value.get().expect("type checked upstream");
-                self.sink_pad_handler.set_socket_conf(settings.socket_conf);
+                self.sink_pad_handler
+                    .set_socket_conf(self, settings.socket_conf);
             }
             "ttl" => {
                 settings.socket_conf.ttl = value.get().expect("type checked upstream");
-                self.sink_pad_handler.set_socket_conf(settings.socket_conf);
+                self.sink_pad_handler
+                    .set_socket_conf(self, settings.socket_conf);
             }
             "ttl-mc" => {
                 settings.socket_conf.ttl_mc = value.get().expect("type checked upstream");
-                self.sink_pad_handler.set_socket_conf(settings.socket_conf);
+                self.sink_pad_handler
+                    .set_socket_conf(self, settings.socket_conf);
             }
             "qos-dscp" => {
                 settings.qos_dscp = value.get().expect("type checked upstream");
@@ -1258,6 +2091,8 @@ impl ObjectImpl for UdpSink {
             }
             "multicast-iface" => {
                 settings.multicast_iface = value.get().expect("type checked upstream");
+                self.sink_pad_handler
+                    .set_multicast_iface(self, settings.multicast_iface.as_deref());
             }
             _ => unimplemented!(),
         }
@@ -1267,10 +2102,17 @@ impl ObjectImpl for UdpSink {
         let settings = self.settings.lock().unwrap();
         match pspec.name() {
             "sync" => settings.sync.to_value(),
+            "ts-offset" => settings.ts_offset.to_value(),
+            "render-delay" => settings.render_delay.nseconds().to_value(),
+            "max-bitrate" => settings.max_bitrate.to_value(),
+            "on-send-error" => settings.on_send_error.to_value(),
+            "stats" => self.stats.to_structure().to_value(),
             "bind-address" => settings.bind_address.to_value(),
             "bind-port" => settings.bind_port.to_value(),
             "bind-address-v6" => settings.bind_address_v6.to_value(),
             "bind-port-v6" => settings.bind_port_v6.to_value(),
+            "used-bind-port" => settings.used_bind_port.to_value(),
+            "used-bind-port-v6" => settings.used_bind_port_v6.to_value(),
             "socket" => settings
                 .socket
                 .as_ref()
@@ -1291,6 +2133,7 @@ impl ObjectImpl for UdpSink {
                 .as_ref()
                 .map(GioSocketWrapper::as_socket)
                 .to_value(),
+            "close-socket" => settings.close_socket.to_value(),
             "auto-multicast" => settings.socket_conf.auto_multicast.to_value(),
             "loop" => settings.socket_conf.multicast_loop.to_value(),
             "ttl" => settings.socket_conf.ttl.to_value(),
@@ -22,11 +22,29 @@ use gst::prelude::*;
 
 mod imp;
 
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstTsUdpSinkOnSendError")]
+pub enum OnSendError {
+    #[default]
+    #[enum_value(
+        name = "Error: post an error if the data couldn't be sent to any client",
+        nick = "error"
+    )]
+    Error,
+    #[enum_value(name = "Warn: post a warning and keep going", nick = "warn")]
+    Warn,
+    #[enum_value(name = "Ignore: silently drop the data", nick = "ignore")]
+    Ignore,
+}
+
 glib::wrapper! {
     pub struct UdpSink(ObjectSubclass<imp::UdpSink>) @extends gst::Element, gst::Object;
 }
 
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    OnSendError::static_type().mark_as_plugin_api(gst::PluginAPIFlags::empty());
+
     gst::Element::register(
         Some(plugin),
         "ts-udpsink",
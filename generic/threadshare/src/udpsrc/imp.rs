@@ -17,8 +17,11 @@
 //
 // SPDX-License-Identifier: LGPL-2.1-or-later
 
-use futures::future::BoxFuture;
+use futures::future::{self, BoxFuture};
 use futures::prelude::*;
+use futures::stream;
+
+use gio::prelude::*;
 
 use gst::glib;
 use gst::prelude::*;
@@ -28,12 +31,14 @@ use gst_net::*;
 use std::sync::LazyLock;
 
 use std::io;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
-use std::sync::Mutex;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use crate::runtime::net::UdpSocket;
 use crate::runtime::prelude::*;
-use crate::runtime::{task, Async, Context, PadSrc, Task, TaskState};
+use crate::runtime::{timer, Context, PadSrc, PadSrcRef, SrcItem, SrcTaskAdapter, Task, TaskState};
 
 use crate::net;
 use crate::socket::{wrap_socket, GioSocketWrapper, Socket, SocketError, SocketRead};
@@ -42,38 +47,109 @@ use futures::pin_mut;
 
 const DEFAULT_ADDRESS: Option<&str> = Some("0.0.0.0");
 const DEFAULT_PORT: i32 = 5004;
+const DEFAULT_ADDRESSES: Option<&str> = None;
+const DEFAULT_IGNORE_BIND_ERRORS: bool = false;
 const DEFAULT_REUSE: bool = true;
+const DEFAULT_REUSE_PORT: bool = true;
 const DEFAULT_CAPS: Option<gst::Caps> = None;
 const DEFAULT_MTU: u32 = 1492;
 const DEFAULT_SOCKET: Option<GioSocketWrapper> = None;
 const DEFAULT_USED_SOCKET: Option<GioSocketWrapper> = None;
+const DEFAULT_CLOSE_SOCKET: bool = true;
 const DEFAULT_CONTEXT: &str = "";
 const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
 const DEFAULT_RETRIEVE_SENDER_ADDRESS: bool = true;
+const DEFAULT_RETRIEVE_DESTINATION_ADDRESS: bool = false;
 const DEFAULT_MULTICAST_LOOP: bool = true;
 const DEFAULT_BUFFER_SIZE: u32 = 0;
 const DEFAULT_MULTICAST_IFACE: Option<&str> = None;
+const DEFAULT_MULTICAST_SOURCE: Option<&str> = None;
+const DEFAULT_MULTICAST_SSM_FALLBACK: bool = false;
+const DEFAULT_TIMEOUT: u64 = 0;
+const DEFAULT_BATCH_SIZE: u32 = 1;
 
 #[derive(Debug, Default)]
 struct State {
     event_sender: Option<Sender<gst::Event>>,
 }
 
+/// Receive statistics, updated from the task without locking.
+#[derive(Debug)]
+struct Stats {
+    packets_received: AtomicU64,
+    bytes_received: AtomicU64,
+    packets_truncated: AtomicU64,
+    /// Running time of the last packet in nanoseconds, `u64::MAX` if none.
+    last_packet_time: AtomicU64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            packets_received: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            packets_truncated: AtomicU64::new(0),
+            last_packet_time: AtomicU64::new(u64::MAX),
+        }
+    }
+}
+
+impl Stats {
+    fn reset(&self) {
+        self.packets_received.store(0, Ordering::Relaxed);
+        self.bytes_received.store(0, Ordering::Relaxed);
+        self.packets_truncated.store(0, Ordering::Relaxed);
+        self.last_packet_time.store(u64::MAX, Ordering::Relaxed);
+    }
+
+    fn to_structure(&self) -> gst::Structure {
+        let last_packet_time = match self.last_packet_time.load(Ordering::Relaxed) {
+            u64::MAX => gst::ClockTime::NONE,
+            time => Some(gst::ClockTime::from_nseconds(time)),
+        };
+
+        gst::Structure::builder("application/x-ts-udpsrc-stats")
+            .field(
+                "packets-received",
+                self.packets_received.load(Ordering::Relaxed),
+            )
+            .field(
+                "bytes-received",
+                self.bytes_received.load(Ordering::Relaxed),
+            )
+            .field(
+                "packets-truncated",
+                self.packets_truncated.load(Ordering::Relaxed),
+            )
+            .field("last-packet-time", last_packet_time)
+            .build()
+    }
+}
+
 #[derive(Debug, Clone)]
 struct Settings {
     address: Option<String>,
     port: i32, // for conformity with C based udpsrc
+    addresses: Option<String>,
+    ignore_bind_errors: bool,
     reuse: bool,
+    reuse_port: bool,
     caps: Option<gst::Caps>,
     mtu: u32,
     socket: Option<GioSocketWrapper>,
     used_socket: Option<GioSocketWrapper>,
+    close_socket: bool,
     context: String,
     context_wait: Duration,
     retrieve_sender_address: bool,
+    retrieve_destination_address: bool,
     multicast_loop: bool,
     buffer_size: u32,
     multicast_iface: Option<String>,
+    multicast_source: Option<String>,
+    multicast_ssm_fallback: bool,
+    timeout: u64,
+    batch_size: u32,
 }
 
 impl Default for Settings {
@@ -81,30 +157,344 @@ impl Default for Settings {
         Settings {
             address: DEFAULT_ADDRESS.map(Into::into),
             port: DEFAULT_PORT,
+            addresses: DEFAULT_ADDRESSES.map(Into::into),
+            ignore_bind_errors: DEFAULT_IGNORE_BIND_ERRORS,
             reuse: DEFAULT_REUSE,
+            reuse_port: DEFAULT_REUSE_PORT,
             caps: DEFAULT_CAPS,
             mtu: DEFAULT_MTU,
             socket: DEFAULT_SOCKET,
             used_socket: DEFAULT_USED_SOCKET,
+            close_socket: DEFAULT_CLOSE_SOCKET,
             context: DEFAULT_CONTEXT.into(),
             context_wait: DEFAULT_CONTEXT_WAIT,
             retrieve_sender_address: DEFAULT_RETRIEVE_SENDER_ADDRESS,
+            retrieve_destination_address: DEFAULT_RETRIEVE_DESTINATION_ADDRESS,
             multicast_loop: DEFAULT_MULTICAST_LOOP,
             buffer_size: DEFAULT_BUFFER_SIZE,
             multicast_iface: DEFAULT_MULTICAST_IFACE.map(Into::into),
+            multicast_source: DEFAULT_MULTICAST_SOURCE.map(Into::into),
+            multicast_ssm_fallback: DEFAULT_MULTICAST_SSM_FALLBACK,
+            timeout: DEFAULT_TIMEOUT,
+            batch_size: DEFAULT_BATCH_SIZE,
         }
     }
 }
 
 #[derive(Debug)]
-struct UdpReader(Async<UdpSocket>);
+struct MulticastMembership {
+    group: IpAddr,
+    ifaces: Vec<getifaddrs::Interface>,
+    /// Sources for source-specific multicast. Empty for any-source multicast.
+    sources: Vec<IpAddr>,
+}
+
+impl MulticastMembership {
+    fn join(
+        &mut self,
+        element: &super::UdpSrc,
+        socket: &UdpSocket,
+        ssm_fallback: bool,
+    ) -> Result<(), gst::ErrorMessage> {
+        if !self.sources.is_empty() {
+            match self.join_ssm(socket) {
+                Ok(()) => return Ok(()),
+                Err(err) if ssm_fallback => {
+                    gst::warning!(
+                        CAT,
+                        obj = element,
+                        "Failed to join source-specific multicast group: {}. Falling back to any-source multicast",
+                        err
+                    );
+                    self.sources.clear();
+                }
+                Err(err) => {
+                    return Err(gst::error_msg!(
+                        gst::ResourceError::OpenRead,
+                        ["Failed to join source-specific multicast group: {}", err]
+                    ));
+                }
+            }
+        }
+
+        match self.group {
+            IpAddr::V4(group) => {
+                for iface in &self.ifaces {
+                    if !iface.address.is_ipv4() {
+                        gst::debug!(
+                            CAT,
+                            "Skipping the IPv6 version of the interface {}",
+                            iface.name
+                        );
+                        continue;
+                    }
+
+                    gst::debug!(CAT, "interface {} joining the multicast", iface.name);
+                    // use the custom written API to be able to pass the interface index
+                    // for all types of target OS
+                    net::imp::join_multicast_v4(socket.as_ref(), &group, iface).map_err(|err| {
+                        gst::error_msg!(
+                            gst::ResourceError::OpenRead,
+                            ["Failed to join multicast group: {}", err]
+                        )
+                    })?;
+                }
+            }
+            IpAddr::V6(group) => {
+                for iface in &self.ifaces {
+                    if !iface.address.is_ipv6() {
+                        gst::debug!(
+                            CAT,
+                            "Skipping the IPv4 version of the interface {}",
+                            iface.name
+                        );
+                        continue;
+                    }
+
+                    gst::debug!(CAT, "interface {} joining the multicast", iface.name);
+                    socket
+                        .join_multicast_v6(&group, iface.index.unwrap_or(0))
+                        .map_err(|err| {
+                            gst::error_msg!(
+                                gst::ResourceError::OpenRead,
+                                ["Failed to join multicast group: {}", err]
+                            )
+                        })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn leave(&self, element: &super::UdpSrc, socket: &UdpSocket) {
+        if !self.sources.is_empty() {
+            self.leave_ssm(element, socket);
+            return;
+        }
+
+        match self.group {
+            IpAddr::V4(group) => {
+                for iface in &self.ifaces {
+                    if !iface.address.is_ipv4() {
+                        gst::debug!(
+                            CAT,
+                            "Skipping the IPv6 version of the interface {}",
+                            iface.name
+                        );
+                        continue;
+                    }
+
+                    gst::debug!(CAT, "interface {} leaving the multicast", iface.name);
+                    if let Err(err) = net::imp::leave_multicast_v4(socket.as_ref(), &group, iface) {
+                        gst::warning!(
+                            CAT,
+                            obj = element,
+                            "Failed to leave multicast group {} on interface {}: {}",
+                            group,
+                            iface.name,
+                            err
+                        );
+                    }
+                }
+            }
+            IpAddr::V6(group) => {
+                for iface in &self.ifaces {
+                    if !iface.address.is_ipv6() {
+                        gst::debug!(
+                            CAT,
+                            "Skipping the IPv4 version of the interface {}",
+                            iface.name
+                        );
+                        continue;
+                    }
+
+                    gst::debug!(CAT, "interface {} leaving the multicast", iface.name);
+                    if let Err(err) = socket.leave_multicast_v6(&group, iface.index.unwrap_or(0)) {
+                        gst::warning!(
+                            CAT,
+                            obj = element,
+                            "Failed to leave multicast group {} on interface {}: {}",
+                            group,
+                            iface.name,
+                            err
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Joins the (S,G) channels for all the sources on all the interfaces of the group family.
+    ///
+    /// On failure, the channels joined so far are left.
+    fn join_ssm(&self, socket: &UdpSocket) -> io::Result<()> {
+        let mut joined = Vec::new();
+        for iface in &self.ifaces {
+            if iface.address.is_ipv4() != self.group.is_ipv4() {
+                continue;
+            }
+
+            for source in &self.sources {
+                gst::debug!(
+                    CAT,
+                    "interface {} joining the multicast group {} for source {}",
+                    iface.name,
+                    self.group,
+                    source
+                );
+                if let Err(err) = self.ssm_operation(socket, source, iface, true) {
+                    for (source, iface) in joined {
+                        let _ = self.ssm_operation(socket, source, iface, false);
+                    }
+
+                    return Err(err);
+                }
+
+                joined.push((source, iface));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn leave_ssm(&self, element: &super::UdpSrc, socket: &UdpSocket) {
+        for iface in &self.ifaces {
+            if iface.address.is_ipv4() != self.group.is_ipv4() {
+                continue;
+            }
+
+            for source in &self.sources {
+                gst::debug!(
+                    CAT,
+                    "interface {} leaving the multicast group {} for source {}",
+                    iface.name,
+                    self.group,
+                    source
+                );
+                if let Err(err) = self.ssm_operation(socket, source, iface, false) {
+                    gst::warning!(
+                        CAT,
+                        obj = element,
+                        "Failed to leave multicast group {} for source {}: {}",
+                        self.group,
+                        source,
+                        err
+                    );
+                }
+            }
+        }
+    }
+
+    fn ssm_operation(
+        &self,
+        socket: &UdpSocket,
+        source: &IpAddr,
+        iface: &getifaddrs::Interface,
+        join: bool,
+    ) -> io::Result<()> {
+        match (self.group, source, iface.address) {
+            (IpAddr::V4(group), IpAddr::V4(source), IpAddr::V4(iface_addr)) => {
+                let sock_ref = socket2::SockRef::from(socket);
+                if join {
+                    sock_ref.join_ssm_v4(source, &group, &iface_addr)
+                } else {
+                    sock_ref.leave_ssm_v4(source, &group, &iface_addr)
+                }
+            }
+            (IpAddr::V6(group), IpAddr::V6(source), IpAddr::V6(_)) => {
+                let iface_index = iface.index.unwrap_or(0);
+                if join {
+                    net::imp::join_ssm_v6(socket.as_ref(), source, &group, iface_index)
+                } else {
+                    net::imp::leave_ssm_v6(socket.as_ref(), source, &group, iface_index)
+                }
+            }
+            _ => unreachable!("sources & interfaces are of the group family"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct UdpReader {
+    socket: UdpSocket,
+    retrieve_sender_address: bool,
+    multicast: Option<MulticastMembership>,
+    /// Local address the datagrams are destined to, if it must be added as meta.
+    destination: Option<SocketAddr>,
+}
 
 impl UdpReader {
-    fn new(socket: Async<UdpSocket>) -> Self {
-        UdpReader(socket)
+    fn new(
+        socket: UdpSocket,
+        retrieve_sender_address: bool,
+        multicast: Option<MulticastMembership>,
+        destination: Option<SocketAddr>,
+    ) -> Self {
+        UdpReader {
+            socket,
+            retrieve_sender_address,
+            multicast,
+            destination,
+        }
     }
 }
 
+type UdpPacket = (gst::Buffer, Option<SocketAddr>, Option<SocketAddr>);
+type UdpSocketItem = Result<Vec<UdpPacket>, SocketError>;
+
+/// Reads datagrams from `socket`, along with their sender & destination addresses.
+///
+/// Once a datagram is received, the datagrams which are already pending are read
+/// in the same go, up to `batch_room` datagrams.
+fn socket_stream(
+    socket: Arc<futures::lock::Mutex<Socket<UdpReader>>>,
+    batch_room: Arc<AtomicUsize>,
+) -> stream::BoxStream<'static, UdpSocketItem> {
+    stream::unfold(
+        (socket, None::<SocketError>),
+        move |(socket, pending_error)| {
+            let batch_room = batch_room.clone();
+            async move {
+                if let Some(err) = pending_error {
+                    return Some((Err(err), (socket, None)));
+                }
+
+                let mut pending_error = None;
+                let res = {
+                    let mut socket = socket.lock().await;
+                    let destination = socket.get().destination;
+
+                    match socket.try_next().await {
+                        Ok((buffer, saddr)) => {
+                            let mut packets = vec![(buffer, saddr, destination)];
+
+                            let room = batch_room.load(Ordering::Relaxed);
+                            if room > 1 {
+                                match socket.try_next_batch(room - 1) {
+                                    Ok(batch) => packets.extend(
+                                        batch
+                                            .into_iter()
+                                            .map(|(buffer, saddr)| (buffer, saddr, destination)),
+                                    ),
+                                    // Reported on next iteration
+                                    Err(err) => pending_error = Some(err),
+                                }
+                            }
+
+                            Ok(packets)
+                        }
+                        Err(err) => Err(err),
+                    }
+                };
+
+                Some((res, (socket, pending_error)))
+            }
+        },
+    )
+    .boxed()
+}
+
 impl SocketRead for UdpReader {
     const DO_TIMESTAMP: bool = true;
 
@@ -113,13 +503,94 @@ impl SocketRead for UdpReader {
         buffer: &'buf mut [u8],
     ) -> BoxFuture<'buf, io::Result<(usize, Option<std::net::SocketAddr>)>> {
         async move {
-            self.0
-                .recv_from(buffer)
-                .await
-                .map(|(read_size, saddr)| (read_size, Some(saddr)))
+            if self.retrieve_sender_address {
+                self.socket
+                    .recv_from(buffer)
+                    .await
+                    .map(|(read_size, saddr)| (read_size, Some(saddr)))
+            } else {
+                // Cheaper path: recv(2) on a bound, unconnected socket
+                // doesn't need to retrieve the peer address.
+                self.socket
+                    .recv(buffer)
+                    .await
+                    .map(|read_size| (read_size, None))
+            }
         }
         .boxed()
     }
+
+    #[cfg(target_os = "linux")]
+    fn try_read_batch(
+        &mut self,
+        buffers: &mut [&mut [u8]],
+    ) -> io::Result<Vec<(usize, Option<SocketAddr>)>> {
+        use std::mem;
+        use std::os::unix::io::AsRawFd;
+
+        let mut iovecs = buffers
+            .iter_mut()
+            .map(|buffer| libc::iovec {
+                iov_base: buffer.as_mut_ptr() as *mut _,
+                iov_len: buffer.len(),
+            })
+            .collect::<Vec<_>>();
+        // SAFETY: sockaddr_storage is a plain C struct for which all zeroes is a valid value.
+        let mut saddrs = vec![unsafe { mem::zeroed::<libc::sockaddr_storage>() }; buffers.len()];
+
+        let mut msgs = iovecs
+            .iter_mut()
+            .zip(saddrs.iter_mut())
+            .map(|(iovec, saddr)| {
+                // SAFETY: mmsghdr is a plain C struct for which all zeroes is a valid value.
+                let mut msg: libc::mmsghdr = unsafe { mem::zeroed() };
+                msg.msg_hdr.msg_iov = iovec;
+                msg.msg_hdr.msg_iovlen = 1;
+                if self.retrieve_sender_address {
+                    msg.msg_hdr.msg_name = saddr as *mut _ as *mut _;
+                    msg.msg_hdr.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as _;
+                }
+
+                msg
+            })
+            .collect::<Vec<_>>();
+
+        // SAFETY: the messages point to iovecs & addresses which outlive the call,
+        // the iovecs point to buffers of the provided sizes.
+        let res = unsafe {
+            libc::recvmmsg(
+                self.socket.as_ref().as_raw_fd(),
+                msgs.as_mut_ptr(),
+                msgs.len() as _,
+                libc::MSG_DONTWAIT as _,
+                std::ptr::null_mut(),
+            )
+        };
+
+        if res < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::WouldBlock {
+                return Ok(Vec::new());
+            }
+
+            return Err(err);
+        }
+
+        Ok(msgs[..res as usize]
+            .iter()
+            .zip(saddrs)
+            .map(|(msg, saddr)| {
+                let saddr = if self.retrieve_sender_address {
+                    // SAFETY: the kernel initialized the address & set its length.
+                    unsafe { socket2::SockAddr::new(saddr, msg.msg_hdr.msg_namelen) }.as_socket()
+                } else {
+                    None
+                };
+
+                (msg.msg_len as usize, saddr)
+            })
+            .collect())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -193,344 +664,572 @@ impl PadSrcHandler for UdpSrcPadHandler {
 
 struct UdpSrcTask {
     element: super::UdpSrc,
-    socket: Option<Socket<UdpReader>>,
+    sockets: Vec<Arc<futures::lock::Mutex<Socket<UdpReader>>>>,
+    /// The datagrams of all the sockets, built on start.
+    socket_stream: Option<stream::SelectAll<stream::BoxStream<'static, UdpSocketItem>>>,
+    /// Maximum number of datagrams the next socket read can add to the current batch.
+    batch_room: Arc<AtomicUsize>,
+    pending_error: Option<SocketError>,
     retrieve_sender_address: bool,
-    need_initial_events: bool,
-    need_segment: bool,
     event_receiver: Receiver<gst::Event>,
-    multicast_ifaces: Vec<getifaddrs::Interface>,
-    multicast_addr: Option<IpAddr>,
+    timeout: Option<Duration>,
+    mtu: usize,
+    batch_size: usize,
 }
 
 impl UdpSrcTask {
     fn new(element: super::UdpSrc, event_receiver: Receiver<gst::Event>) -> Self {
         UdpSrcTask {
             element,
-            socket: None,
+            sockets: Vec::new(),
+            socket_stream: None,
+            batch_room: Arc::new(AtomicUsize::new(DEFAULT_BATCH_SIZE as usize)),
+            pending_error: None,
             retrieve_sender_address: DEFAULT_RETRIEVE_SENDER_ADDRESS,
-            need_initial_events: true,
-            need_segment: true,
             event_receiver,
-            multicast_ifaces: Vec::<getifaddrs::Interface>::new(),
-            multicast_addr: None,
+            timeout: None,
+            mtu: DEFAULT_MTU as usize,
+            batch_size: DEFAULT_BATCH_SIZE as usize,
         }
     }
-}
 
-impl TaskImpl for UdpSrcTask {
-    type Item = gst::Buffer;
+    fn handle_packet(
+        element: &super::UdpSrc,
+        mtu: usize,
+        mut buffer: gst::Buffer,
+        saddr: Option<SocketAddr>,
+        daddr: Option<SocketAddr>,
+    ) -> gst::Buffer {
+        if buffer.size() > mtu {
+            Self::handle_truncated(element, mtu, buffer.get_mut().unwrap());
+        }
 
-    fn prepare(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
-        async move {
-            let udpsrc = self.element.imp();
-            let mut settings = udpsrc.settings.lock().unwrap();
+        let stats = &element.imp().stats;
+        stats.packets_received.fetch_add(1, Ordering::Relaxed);
+        stats
+            .bytes_received
+            .fetch_add(buffer.size() as u64, Ordering::Relaxed);
+        if let Some(dts) = buffer.dts() {
+            stats
+                .last_packet_time
+                .store(dts.nseconds(), Ordering::Relaxed);
+        }
 
-            gst::debug!(CAT, obj = self.element, "Preparing Task");
+        if let Some(saddr) = saddr {
+            NetAddressMeta::add(
+                buffer.get_mut().unwrap(),
+                &gio::InetSocketAddress::from(saddr),
+            );
+        }
 
-            self.retrieve_sender_address = settings.retrieve_sender_address;
+        // Added after the sender address meta, if any
+        if let Some(daddr) = daddr {
+            NetAddressMeta::add(
+                buffer.get_mut().unwrap(),
+                &gio::InetSocketAddress::from(daddr),
+            );
+        }
 
-            let socket = if let Some(ref wrapped_socket) = settings.socket {
-                let socket: UdpSocket;
+        buffer
+    }
 
-                #[cfg(unix)]
-                {
-                    socket = wrapped_socket.get()
+    /// Collects the packets which are already pending on the sockets
+    /// without waiting, until the list holds `batch_size` buffers.
+    fn fill_batch(&mut self, list: &mut gst::BufferListRef) {
+        let socket_stream = self.socket_stream.as_mut().unwrap();
+        while list.len() < self.batch_size {
+            self.batch_room
+                .store(self.batch_size - list.len(), Ordering::Relaxed);
+
+            match socket_stream.next().now_or_never() {
+                Some(Some(Ok(packets))) => {
+                    for (buffer, saddr, daddr) in packets {
+                        list.add(Self::handle_packet(
+                            &self.element,
+                            self.mtu,
+                            buffer,
+                            saddr,
+                            daddr,
+                        ));
+                    }
                 }
-                #[cfg(windows)]
-                {
-                    socket = wrapped_socket.get()
+                Some(Some(Err(err))) => {
+                    // Let the next regular read report the error
+                    gst::debug!(CAT, obj = self.element, "Ending batch: {err:#}");
+                    self.pending_error = Some(err);
+                    break;
                 }
+                Some(None) | None => break,
+            }
+        }
+    }
 
-                let socket = Async::<UdpSocket>::try_from(socket).map_err(|err| {
-                    gst::error_msg!(
-                        gst::ResourceError::OpenRead,
-                        ["Failed to setup Async socket: {}", err]
-                    )
-                })?;
+    fn handle_socket_error(element: &super::UdpSrc, err: SocketError) -> gst::FlowError {
+        gst::error!(CAT, obj = element, "Got error {err:#}");
 
-                settings.used_socket = Some(wrapped_socket.clone());
+        match err {
+            SocketError::Gst(err) => {
+                gst::element_error!(
+                    element,
+                    gst::StreamError::Failed,
+                    ("Internal data stream error"),
+                    ["streaming stopped, reason {err}"]
+                );
+            }
+            SocketError::Io(err) => {
+                gst::element_error!(
+                    element,
+                    gst::StreamError::Failed,
+                    ("I/O error"),
+                    ["streaming stopped, I/O error {err}"]
+                );
+            }
+        }
 
-                socket
-            } else {
-                let addr: IpAddr = match settings.address {
-                    None => {
-                        return Err(gst::error_msg!(
-                            gst::ResourceError::Settings,
-                            ["No address set"]
-                        ));
-                    }
-                    Some(ref addr) => match addr.parse() {
-                        Err(err) => {
-                            return Err(gst::error_msg!(
-                                gst::ResourceError::Settings,
-                                ["Invalid address '{}' set: {}", addr, err]
-                            ));
-                        }
-                        Ok(addr) => {
-                            self.multicast_addr = Some(addr);
-                            addr
-                        }
-                    },
+        gst::FlowError::Error
+    }
+
+    fn handle_truncated(element: &super::UdpSrc, mtu: usize, buffer: &mut gst::BufferRef) {
+        buffer.set_size(mtu);
+
+        let truncated_packets = element
+            .imp()
+            .stats
+            .packets_truncated
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+
+        if truncated_packets == 1 {
+            gst::element_warning!(
+                element,
+                gst::ResourceError::Read,
+                ("Received packet larger than the mtu"),
+                [
+                    "Packet truncated to {} bytes, consider increasing the mtu",
+                    mtu
+                ]
+            );
+        } else {
+            gst::warning!(
+                CAT,
+                obj = element,
+                "Packet truncated to {} bytes ({} truncated packets so far)",
+                mtu,
+                truncated_packets,
+            );
+        }
+    }
+
+    fn multicast_ifaces(
+        &self,
+        settings: &Settings,
+        group: IpAddr,
+        scope_id: u32,
+    ) -> Result<Vec<getifaddrs::Interface>, gst::ErrorMessage> {
+        let mut multicast_ifaces = Vec::new();
+
+        if let Some(multicast_iface) = &settings.multicast_iface {
+            let multi_ifaces: Vec<String> =
+                multicast_iface.split(',').map(|s| s.to_string()).collect();
+
+            let iter = getifaddrs::getifaddrs().map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to get interfaces: {}", err]
+                )
+            })?;
+
+            iter.for_each(|iface| {
+                let ip_ver = if iface.address.is_ipv4() {
+                    "IPv4"
+                } else {
+                    "IPv6"
                 };
-                let port = settings.port;
 
-                // TODO: TTL etc
-                let saddr = if addr.is_multicast() {
-                    let bind_addr = if addr.is_ipv4() {
-                        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+                for m in &multi_ifaces {
+                    if &iface.name == m {
+                        multicast_ifaces.push(iface.clone());
+                        gst::debug!(
+                            CAT,
+                            obj = self.element,
+                            "Interface {m} available, version: {ip_ver}"
+                        );
                     } else {
-                        IpAddr::V6(Ipv6Addr::UNSPECIFIED)
-                    };
+                        // check if name matches the interface description (Friendly name) on Windows
+                        #[cfg(windows)]
+                        if &iface.description == m {
+                            multicast_ifaces.push(iface.clone());
+                            gst::debug!(
+                                CAT,
+                                obj = self.element,
+                                "Interface {m} available, version: {ip_ver}"
+                            );
+                        }
+                    }
+                }
+            });
+        }
 
-                    let saddr = SocketAddr::new(bind_addr, port as u16);
-                    gst::debug!(
-                        CAT,
-                        obj = self.element,
-                        "Binding to {:?} for multicast group {:?}",
-                        saddr,
-                        addr
-                    );
+        if multicast_ifaces.is_empty() {
+            gst::warning!(
+                CAT,
+                obj = self.element,
+                "No suitable network interfaces found, adding default iface"
+            );
 
-                    saddr
+            // For IPv6, use the scope from the address if any
+            multicast_ifaces.push(getifaddrs::Interface {
+                name: "default".to_owned(),
+                #[cfg(windows)]
+                description: "default".to_owned(),
+                address: if group.is_ipv4() {
+                    IpAddr::V4(Ipv4Addr::UNSPECIFIED)
                 } else {
-                    let saddr = SocketAddr::new(addr, port as u16);
-                    gst::debug!(CAT, obj = self.element, "Binding to {:?}", saddr);
+                    IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+                },
+                #[cfg(not(windows))]
+                associated_address: None,
+                netmask: None,
+                flags: getifaddrs::InterfaceFlags::UP,
+                index: Some(scope_id),
+            });
+        }
 
-                    saddr
-                };
+        Ok(multicast_ifaces)
+    }
 
-                let socket = if addr.is_ipv4() {
-                    socket2::Socket::new(
-                        socket2::Domain::IPV4,
-                        socket2::Type::DGRAM,
-                        Some(socket2::Protocol::UDP),
-                    )
-                } else {
-                    socket2::Socket::new(
-                        socket2::Domain::IPV6,
-                        socket2::Type::DGRAM,
-                        Some(socket2::Protocol::UDP),
-                    )
+    fn multicast_sources(
+        &self,
+        settings: &Settings,
+        group: IpAddr,
+    ) -> Result<Vec<IpAddr>, gst::ErrorMessage> {
+        let mut multicast_sources = Vec::new();
+
+        let Some(ref sources) = settings.multicast_source else {
+            return Ok(multicast_sources);
+        };
+
+        for source in sources.split(',').map(str::trim) {
+            match source.parse::<IpAddr>().map(|source| source.to_canonical()) {
+                Ok(source) if source.is_ipv4() == group.is_ipv4() => multicast_sources.push(source),
+                Ok(_) => {
+                    return Err(gst::error_msg!(
+                        gst::ResourceError::Settings,
+                        [
+                            "Multicast source {} is not of the same family as the group {}",
+                            source,
+                            group
+                        ]
+                    ));
                 }
-                .map_err(|err| {
-                    gst::error_msg!(
-                        gst::ResourceError::OpenRead,
-                        ["Failed to create socket: {}", err]
-                    )
-                })?;
+                Err(err) => {
+                    return Err(gst::error_msg!(
+                        gst::ResourceError::Settings,
+                        ["Invalid multicast source '{}' set: {}", source, err]
+                    ));
+                }
+            }
+        }
 
-                socket.set_reuse_address(settings.reuse).map_err(|err| {
-                    gst::error_msg!(
-                        gst::ResourceError::OpenRead,
-                        ["Failed to set reuse_address: {}", err]
-                    )
-                })?;
+        Ok(multicast_sources)
+    }
+
+    /// Creates a socket bound to `address` & `port`, joining the multicast group if needed.
+    fn bind(
+        &self,
+        settings: &Settings,
+        address: &str,
+        port: i32,
+    ) -> Result<(UdpSocket, Option<MulticastMembership>), gst::ErrorMessage> {
+        let (addr, scope_id) = net::parse_address(address).map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::Settings,
+                ["Invalid address '{}' set: {}", address, err]
+            )
+        })?;
+
+        // TODO: TTL etc
+        let saddr = if addr.is_multicast() {
+            let bind_addr = if addr.is_ipv4() {
+                IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+            } else {
+                IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+            };
+
+            let saddr = SocketAddr::new(bind_addr, port as u16);
+            gst::debug!(
+                CAT,
+                obj = self.element,
+                "Binding to {:?} for multicast group {:?}",
+                saddr,
+                addr
+            );
+
+            saddr
+        } else {
+            let saddr = match addr {
+                IpAddr::V4(_) => SocketAddr::new(addr, port as u16),
+                IpAddr::V6(addr) => SocketAddrV6::new(addr, port as u16, 0, scope_id).into(),
+            };
+            gst::debug!(CAT, obj = self.element, "Binding to {:?}", saddr);
+
+            saddr
+        };
+
+        let mut builder = UdpSocket::builder().reuse_address(settings.reuse);
+
+        #[cfg(unix)]
+        {
+            builder = builder.reuse_port(settings.reuse_port);
+        }
+
+        if addr == IpAddr::V6(Ipv6Addr::UNSPECIFIED) {
+            // Also accept IPv4 traffic through v4-mapped addresses
+            builder = builder.only_v6(false);
+        }
+
+        if settings.buffer_size != 0 {
+            gst::debug!(
+                CAT,
+                obj = self.element,
+                "changing the socket recv buffer size to {}",
+                settings.buffer_size
+            );
+            builder = builder.recv_buffer_size(settings.buffer_size as usize);
+        }
+
+        if addr.is_multicast() {
+            builder = builder.multicast_loop(settings.multicast_loop);
+
+            // Only deliver the datagrams of the groups joined on this socket,
+            // not those joined by other sockets bound to the same port.
+            #[cfg(target_os = "linux")]
+            {
+                builder = builder.multicast_all(false);
+            }
+        }
+
+        let socket = builder.bind(saddr).map_err(|err| {
+            #[cfg(unix)]
+            let reuse_port = settings.reuse_port;
+            #[cfg(not(unix))]
+            let reuse_port = false;
+
+            gst::error_msg!(
+                gst::ResourceError::OpenRead,
+                [
+                    "Failed to bind socket to {} (reuse: {}, reuse-port: {}): {}",
+                    saddr,
+                    settings.reuse,
+                    reuse_port,
+                    err
+                ]
+            )
+        })?;
 
+        match socket2::SockRef::from(&socket).recv_buffer_size() {
+            Ok(effective) => {
                 gst::debug!(
                     CAT,
                     obj = self.element,
-                    "socket recv buffer size is {:?}",
-                    socket.recv_buffer_size()
+                    "effective socket recv buffer size is {}",
+                    effective
                 );
-                if settings.buffer_size != 0 {
-                    gst::debug!(
+                if settings.buffer_size != 0
+                    && net::buffer_size_clamped(settings.buffer_size as usize, effective)
+                {
+                    gst::warning!(
                         CAT,
                         obj = self.element,
-                        "changing the socket recv buffer size to {}",
-                        settings.buffer_size
+                        "Requested buffer-size {} clamped to {} by the OS",
+                        settings.buffer_size,
+                        effective
                     );
-                    socket
-                        .set_recv_buffer_size(settings.buffer_size as usize)
-                        .map_err(|err| {
-                            gst::error_msg!(
-                                gst::ResourceError::OpenRead,
-                                ["Failed to set buffer_size: {}", err]
-                            )
-                        })?;
                 }
+            }
+            Err(err) => {
+                gst::warning!(
+                    CAT,
+                    obj = self.element,
+                    "Failed to retrieve effective socket recv buffer size: {}",
+                    err
+                );
+            }
+        }
 
-                #[cfg(unix)]
-                {
-                    socket.set_reuse_port(settings.reuse).map_err(|err| {
+        let multicast = if addr.is_multicast() {
+            let mut membership = MulticastMembership {
+                group: addr,
+                ifaces: self.multicast_ifaces(settings, addr, scope_id)?,
+                sources: self.multicast_sources(settings, addr)?,
+            };
+            membership.join(&self.element, &socket, settings.multicast_ssm_fallback)?;
+
+            Some(membership)
+        } else {
+            None
+        };
+
+        Ok((socket, multicast))
+    }
+
+    /// Returns the `(address, port)` entries to bind.
+    fn addresses(&self, settings: &Settings) -> Result<Vec<(String, i32)>, gst::ErrorMessage> {
+        if let Some(ref addresses) = settings.addresses {
+            let entries = addresses
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| {
+                    parse_address_port(entry, settings.port).map_err(|err| {
                         gst::error_msg!(
-                            gst::ResourceError::OpenRead,
-                            ["Failed to set reuse_port: {}", err]
+                            gst::ResourceError::Settings,
+                            ["Invalid addresses entry '{}': {}", entry, err]
                         )
-                    })?;
-                }
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
 
-                socket.bind(&saddr.into()).map_err(|err| {
-                    gst::error_msg!(
-                        gst::ResourceError::OpenRead,
-                        ["Failed to bind socket: {}", err]
-                    )
-                })?;
+            if entries.is_empty() {
+                return Err(gst::error_msg!(
+                    gst::ResourceError::Settings,
+                    ["No address in addresses '{}'", addresses]
+                ));
+            }
 
-                let socket = Async::<UdpSocket>::try_from(socket).map_err(|err| {
-                    gst::error_msg!(
-                        gst::ResourceError::OpenRead,
-                        ["Failed to setup Async socket: {}", err]
-                    )
-                })?;
+            return Ok(entries);
+        }
 
-                if addr.is_multicast() {
-                    if let Some(multicast_iface) = &settings.multicast_iface {
-                        let multi_ifaces: Vec<String> =
-                            multicast_iface.split(',').map(|s| s.to_string()).collect();
+        match settings.address {
+            Some(ref address) => Ok(vec![(address.clone(), settings.port)]),
+            None => Err(gst::error_msg!(
+                gst::ResourceError::Settings,
+                ["No address set"]
+            )),
+        }
+    }
+}
 
-                        let iter = getifaddrs::getifaddrs().map_err(|err| {
-                            gst::error_msg!(
-                                gst::ResourceError::OpenRead,
-                                ["Failed to get interfaces: {}", err]
-                            )
-                        })?;
+/// Parses an `address:port` entry, the port being optional.
+///
+/// IPv6 addresses with a port must be bracketed, e.g. `"[ff02::1%eth0]:5004"`.
+fn parse_address_port(entry: &str, default_port: i32) -> Result<(String, i32), String> {
+    let (address, port) = if let Some((address, port)) = entry.rsplit_once("]:") {
+        (format!("{address}]"), Some(port))
+    } else if entry.starts_with('[') || entry.matches(':').count() != 1 {
+        (entry.to_string(), None)
+    } else {
+        let (address, port) = entry.split_once(':').unwrap();
+        (address.to_string(), Some(port))
+    };
+
+    let port = match port {
+        Some(port) => port
+            .parse::<u16>()
+            .map_err(|err| format!("invalid port '{port}': {err}"))?
+            .into(),
+        None => default_port,
+    };
+
+    Ok((address, port))
+}
 
-                        iter.for_each(|iface| {
-                            let ip_ver = if iface.address.is_ipv4() {
-                                "IPv4"
-                            } else {
-                                "IPv6"
-                            };
-
-                            for m in &multi_ifaces {
-                                if &iface.name == m {
-                                    self.multicast_ifaces.push(iface.clone());
-                                    gst::debug!(
-                                        CAT,
-                                        obj = self.element,
-                                        "Interface {m} available, version: {ip_ver}"
-                                    );
-                                } else {
-                                    // check if name matches the interface description (Friendly name) on Windows
-                                    #[cfg(windows)]
-                                    if &iface.description == m {
-                                        self.multicast_ifaces.push(iface.clone());
-                                        gst::debug!(
-                                            CAT,
-                                            obj = self.element,
-                                            "Interface {m} available, version: {ip_ver}"
-                                        );
-                                    }
-                                }
-                            }
-                        });
-                    }
+impl SrcTaskImpl for UdpSrcTask {
+    fn element(&self) -> &gst::Element {
+        self.element.upcast_ref()
+    }
 
-                    if self.multicast_ifaces.is_empty() {
-                        gst::warning!(
-                            CAT,
-                            obj = self.element,
-                            "No suitable network interfaces found, adding default iface"
-                        );
+    fn src_pad(&self) -> PadSrcRef<'_> {
+        self.element.imp().src_pad.as_ref()
+    }
 
-                        self.multicast_ifaces.push(getifaddrs::Interface {
-                            name: "default".to_owned(),
-                            #[cfg(windows)]
-                            description: "default".to_owned(),
-                            address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
-                            #[cfg(not(windows))]
-                            associated_address: None,
-                            netmask: None,
-                            flags: getifaddrs::InterfaceFlags::UP,
-                            index: Some(0),
-                        });
-                    }
+    fn caps(&mut self) -> Option<gst::Caps> {
+        self.element.imp().settings.lock().unwrap().caps.clone()
+    }
 
-                    match addr {
-                        IpAddr::V4(addr) => {
-                            for iface in &self.multicast_ifaces {
-                                if !iface.address.is_ipv4() {
-                                    gst::debug!(
-                                        CAT,
-                                        "Skipping the IPv6 version of the interface {}",
-                                        iface.name
-                                    );
-                                    continue;
-                                }
+    fn caps_pushed(&mut self, caps: &gst::Caps) {
+        *self.element.imp().configured_caps.lock().unwrap() = Some(caps.clone());
+    }
 
-                                gst::debug!(CAT, "interface {} joining the multicast", iface.name);
-                                // use the custom written API to be able to pass the interface index
-                                // for all types of target OS
-                                net::imp::join_multicast_v4(socket.as_ref(), &addr, iface)
-                                    .map_err(|err| {
-                                        gst::error_msg!(
-                                            gst::ResourceError::OpenRead,
-                                            ["Failed to join multicast group: {}", err]
-                                        )
-                                    })?;
-                            }
+    fn prepare(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        async move {
+            let udpsrc = self.element.imp();
+            let mut settings = udpsrc.settings.lock().unwrap();
 
-                            socket
-                                .as_ref()
-                                .set_multicast_loop_v4(settings.multicast_loop)
-                                .map_err(|err| {
-                                    gst::error_msg!(
-                                        gst::ResourceError::OpenWrite,
-                                        [
-                                            "Failed to set multicast loop to {}: {}",
-                                            settings.multicast_loop,
-                                            err
-                                        ]
-                                    )
-                                })?;
-                        }
-                        IpAddr::V6(addr) => {
-                            for iface in &self.multicast_ifaces {
-                                if !iface.address.is_ipv6() {
-                                    gst::debug!(
-                                        CAT,
-                                        "Skipping the IPv4 version of the interface {}",
-                                        iface.name
-                                    );
-                                    continue;
-                                }
+            gst::debug!(CAT, obj = self.element, "Preparing Task");
 
-                                gst::debug!(CAT, "interface {} joining the multicast", iface.name);
-                                socket
-                                    .as_ref()
-                                    .join_multicast_v6(&addr, iface.index.unwrap_or(0))
-                                    .map_err(|err| {
-                                        gst::error_msg!(
-                                            gst::ResourceError::OpenRead,
-                                            ["Failed to join multicast group: {}", err]
-                                        )
-                                    })?;
-                            }
+            self.retrieve_sender_address = settings.retrieve_sender_address;
+
+            let mut sockets = Vec::new();
+            if let Some(ref wrapped_socket) = settings.socket {
+                if settings.address.as_deref() != DEFAULT_ADDRESS
+                    || settings.port != DEFAULT_PORT
+                    || settings.addresses.is_some()
+                    || settings.multicast_iface.is_some()
+                {
+                    gst::warning!(
+                        CAT,
+                        obj = self.element,
+                        "Using provided socket, ignoring address, addresses, port & multicast-iface settings"
+                    );
+                }
+
+                let socket = UdpSocket::from_std(wrapped_socket.get()).map_err(|err| {
+                    gst::error_msg!(
+                        gst::ResourceError::OpenRead,
+                        ["Failed to setup Async socket: {}", err]
+                    )
+                })?;
+
+                settings.used_socket = Some(wrapped_socket.clone());
 
-                            socket
-                                .as_ref()
-                                .set_multicast_loop_v6(settings.multicast_loop)
-                                .map_err(|err| {
-                                    gst::error_msg!(
-                                        gst::ResourceError::OpenWrite,
-                                        [
-                                            "Failed to set multicast loop to {}: {}",
-                                            settings.multicast_loop,
-                                            err
-                                        ]
-                                    )
-                                })?;
+                sockets.push((socket, None));
+            } else {
+                for (address, port) in self.addresses(&settings)? {
+                    match self.bind(&settings, &address, port) {
+                        Ok(socket) => sockets.push(socket),
+                        Err(err) if settings.ignore_bind_errors => {
+                            gst::warning!(
+                                CAT,
+                                obj = self.element,
+                                "Skipping {}:{}: {}",
+                                address,
+                                port,
+                                err
+                            );
                         }
+                        Err(err) => return Err(err),
                     }
                 }
 
-                settings.used_socket = Some(wrap_socket(&socket)?);
+                if sockets.is_empty() {
+                    return Err(gst::error_msg!(
+                        gst::ResourceError::OpenRead,
+                        ["Failed to bind any of the addresses"]
+                    ));
+                }
+
+                settings.used_socket = Some(wrap_socket(&sockets[0].0)?);
+            }
 
-                socket
-            };
+            if sockets.len() == 1 {
+                let port: i32 = sockets[0].0.local_addr().unwrap().port().into();
+                if settings.port != port {
+                    settings.port = port;
+                    drop(settings);
+                    self.element.notify("port");
 
-            let port: i32 = socket.as_ref().local_addr().unwrap().port().into();
-            if settings.port != port {
-                settings.port = port;
-                drop(settings);
-                self.element.notify("port");
+                    settings = udpsrc.settings.lock().unwrap();
+                };
+            }
 
-                settings = udpsrc.settings.lock().unwrap();
-            };
+            self.mtu = settings.mtu as usize;
+            let retrieve_destination_address = settings.retrieve_destination_address;
 
+            // Allocate one extra byte so we can detect packets larger than the mtu
             let buffer_pool = gst::BufferPool::new();
             let mut config = buffer_pool.config();
-            config.set_params(None, settings.mtu, 0, 0);
+            config.set_params(None, settings.mtu.saturating_add(1), 0, 0);
             buffer_pool.set_config(config).map_err(|err| {
                 gst::error_msg!(
                     gst::ResourceError::Settings,
@@ -540,19 +1239,38 @@ impl TaskImpl for UdpSrcTask {
 
             drop(settings);
 
-            self.socket = Some(
-                Socket::try_new(
+            for (socket, multicast) in sockets {
+                let destination = if retrieve_destination_address {
+                    let saddr = socket.local_addr().map_err(|err| {
+                        gst::error_msg!(
+                            gst::ResourceError::OpenRead,
+                            ["Failed to retrieve local address: {}", err]
+                        )
+                    })?;
+
+                    match multicast {
+                        Some(ref multicast) => Some(SocketAddr::new(multicast.group, saddr.port())),
+                        None => Some(saddr),
+                    }
+                } else {
+                    None
+                };
+
+                let socket = Socket::try_new(
                     self.element.clone().upcast(),
-                    buffer_pool,
-                    UdpReader::new(socket),
+                    buffer_pool.clone(),
+                    UdpReader::new(socket, self.retrieve_sender_address, multicast, destination),
                 )
                 .map_err(|err| {
                     gst::error_msg!(
                         gst::ResourceError::OpenRead,
                         ["Failed to prepare socket {:?}", err]
                     )
-                })?,
-            );
+                })?;
+
+                self.sockets
+                    .push(Arc::new(futures::lock::Mutex::new(socket)));
+            }
 
             self.element.notify("used-socket");
 
@@ -565,48 +1283,34 @@ impl TaskImpl for UdpSrcTask {
         async move {
             gst::debug!(CAT, obj = self.element, "Unpreparing Task");
             let udpsrc = self.element.imp();
-            if let Some(reader) = &self.socket {
-                let socket = &reader.get().0;
-                if let Some(addr) = self.multicast_addr {
-                    match addr {
-                        IpAddr::V4(addr) => {
-                            for iface in &self.multicast_ifaces {
-                                if !iface.address.is_ipv4() {
-                                    gst::debug!(
-                                        CAT,
-                                        "Skipping the IPv6 version of the interface {}",
-                                        iface.name
-                                    );
-                                    continue;
-                                }
-
-                                gst::debug!(CAT, "interface {} leaving the multicast", iface.name);
-                                net::imp::leave_multicast_v4(socket.as_ref(), &addr, iface)
-                                    .unwrap();
-                            }
-                        }
-                        IpAddr::V6(addr) => {
-                            for iface in &self.multicast_ifaces {
-                                if !iface.address.is_ipv6() {
-                                    gst::debug!(
-                                        CAT,
-                                        "Skipping the IPv4 version of the interface {}",
-                                        iface.name
-                                    );
-                                    continue;
-                                }
+            self.socket_stream = None;
+            for socket in &self.sockets {
+                let socket = socket.lock().await;
+                let reader = socket.get();
+                if let Some(ref multicast) = reader.multicast {
+                    multicast.leave(&self.element, &reader.socket);
+                }
+            }
+            // Release our dup of the socket before possibly closing the provided one
+            self.sockets.clear();
 
-                                gst::debug!(CAT, "interface {} leaving the multicast", iface.name);
-                                socket
-                                    .as_ref()
-                                    .leave_multicast_v6(&addr, iface.index.unwrap_or(0))
-                                    .unwrap();
-                            }
-                        }
+            let mut settings = udpsrc.settings.lock().unwrap();
+            if settings.close_socket {
+                if let Some(ref wrapped_socket) = settings.socket {
+                    gst::debug!(CAT, obj = self.element, "Closing provided socket");
+                    if let Err(err) = wrapped_socket.as_socket().close() {
+                        gst::warning!(
+                            CAT,
+                            obj = self.element,
+                            "Failed to close provided socket: {}",
+                            err
+                        );
                     }
                 }
             }
-            udpsrc.settings.lock().unwrap().used_socket = None;
+            settings.used_socket = None;
+            drop(settings);
+
             self.element.notify("used-socket");
         }
         .boxed()
@@ -615,195 +1319,119 @@ impl TaskImpl for UdpSrcTask {
     fn start(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
         async move {
             gst::log!(CAT, obj = self.element, "Starting task");
-            self.socket
-                .as_mut()
-                .unwrap()
-                .set_clock(self.element.clock(), self.element.base_time());
+            let settings = self.element.imp().settings.lock().unwrap();
+            self.timeout = (settings.timeout > 0).then(|| Duration::from_nanos(settings.timeout));
+            self.batch_size = settings.batch_size as usize;
+            drop(settings);
+
+            // Release the sockets held by the pending reads
+            self.socket_stream = None;
+            self.pending_error = None;
+            for socket in &self.sockets {
+                socket
+                    .lock()
+                    .await
+                    .set_clock(self.element.clock(), self.element.base_time());
+            }
+            self.socket_stream =
+                Some(stream::select_all(self.sockets.iter().map(|socket| {
+                    socket_stream(socket.clone(), self.batch_room.clone())
+                })));
+
             gst::log!(CAT, obj = self.element, "Task started");
             Ok(())
         }
         .boxed()
     }
 
-    fn try_next(&mut self) -> BoxFuture<'_, Result<gst::Buffer, gst::FlowError>> {
+    fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
         async move {
-            let event_fut = self.event_receiver.next().fuse();
-            let socket_fut = self.socket.as_mut().unwrap().try_next().fuse();
-
-            pin_mut!(event_fut);
-            pin_mut!(socket_fut);
-
-            futures::select! {
-                event_res = event_fut => match event_res {
-                    Some(event) => {
-                        gst::debug!(CAT, obj = self.element, "Handling element level event {event:?}");
-
-                        match event.view() {
-                            gst::EventView::Eos(_) => Err(gst::FlowError::Eos),
-                            ev => {
-                                gst::error!(CAT, obj = self.element, "Unexpected event {ev:?} on channel");
-                                Err(gst::FlowError::Error)
+            if let Some(err) = self.pending_error.take() {
+                return Err(Self::handle_socket_error(&self.element, err));
+            }
+
+            let packets = loop {
+                let event_fut = self.event_receiver.next().fuse();
+                self.batch_room.store(self.batch_size, Ordering::Relaxed);
+                let socket_fut = self.socket_stream.as_mut().unwrap().next();
+                // The timeout is re-armed for each packet
+                let timeout_fut = match self.timeout {
+                    Some(timeout) => timer::delay_for_at_least(timeout).left_future(),
+                    None => future::pending::<()>().right_future(),
+                }
+                .fuse();
+
+                pin_mut!(event_fut);
+                pin_mut!(socket_fut);
+                pin_mut!(timeout_fut);
+
+                let res = futures::select! {
+                    event_res = event_fut => match event_res {
+                        Some(event) => {
+                            gst::debug!(CAT, obj = self.element, "Handling element level event {event:?}");
+
+                            match event.view() {
+                                gst::EventView::Eos(_) => Err(gst::FlowError::Eos),
+                                ev => {
+                                    gst::error!(CAT, obj = self.element, "Unexpected event {ev:?} on channel");
+                                    Err(gst::FlowError::Error)
+                                }
                             }
                         }
-                    }
-                    None => {
-                        gst::error!(CAT, obj = self.element, "Unexpected return on event channel");
-                        Err(gst::FlowError::Error)
-                    }
-                },
-                socket_res = socket_fut => match socket_res {
-                    Ok((mut buffer, saddr)) => {
-                        if let Some(saddr) = saddr {
-                            if self.retrieve_sender_address {
-                                NetAddressMeta::add(
-                                    buffer.get_mut().unwrap(),
-                                    &gio::InetSocketAddress::from(saddr),
-                                );
-                            }
+                        None => {
+                            gst::error!(CAT, obj = self.element, "Unexpected return on event channel");
+                            Err(gst::FlowError::Error)
                         }
-
-                        Ok(buffer)
                     },
-                    Err(err) => {
-                        gst::error!(CAT, obj = self.element, "Got error {err:#}");
-
-                        match err {
-                            SocketError::Gst(err) => {
-                                gst::element_error!(
-                                    self.element,
-                                    gst::StreamError::Failed,
-                                    ("Internal data stream error"),
-                                    ["streaming stopped, reason {err}"]
-                                );
-                            }
-                            SocketError::Io(err) => {
-                                gst::element_error!(
-                                    self.element,
-                                    gst::StreamError::Failed,
-                                    ("I/O error"),
-                                    ["streaming stopped, I/O error {err}"]
-                                );
-                            }
-                        }
-
-                        Err(gst::FlowError::Error)
-                    }
-                },
-            }
-        }
-        .boxed()
-    }
-
-    fn handle_item(&mut self, buffer: gst::Buffer) -> BoxFuture<'_, Result<(), gst::FlowError>> {
-        async {
-            gst::log!(CAT, obj = self.element, "Handling {:?}", buffer);
-            let udpsrc = self.element.imp();
+                    socket_res = socket_fut => match socket_res {
+                        Some(Ok(packets)) => Ok(packets),
+                        Some(Err(err)) => Err(Self::handle_socket_error(&self.element, err)),
+                        None => unreachable!("socket streams never end"),
+                    },
+                    _ = timeout_fut => {
+                        let timeout = self.timeout.unwrap();
+                        gst::debug!(CAT, obj = self.element, "No data received for {timeout:?}");
+
+                        let _ = self.element.post_message(
+                            gst::message::Element::builder(
+                                gst::Structure::builder("GstUDPSrcTimeout")
+                                    .field("timeout", timeout.as_nanos() as u64)
+                                    .build(),
+                            )
+                            .src(&self.element)
+                            .build(),
+                        );
 
-            if self.need_initial_events {
-                gst::debug!(CAT, obj = self.element, "Pushing initial events");
-
-                let stream_id =
-                    format!("{:08x}{:08x}", rand::random::<u32>(), rand::random::<u32>());
-                let stream_start_evt = gst::event::StreamStart::builder(&stream_id)
-                    .group_id(gst::GroupId::next())
-                    .build();
-                udpsrc.src_pad.push_event(stream_start_evt).await;
-
-                let caps = udpsrc.settings.lock().unwrap().caps.clone();
-                if let Some(caps) = caps {
-                    udpsrc
-                        .src_pad
-                        .push_event(gst::event::Caps::new(&caps))
-                        .await;
-                    *udpsrc.configured_caps.lock().unwrap() = Some(caps);
-                }
+                        continue;
+                    },
+                };
 
-                self.need_initial_events = false;
-            }
+                break res;
+            }?;
 
-            if self.need_segment {
-                let segment_evt =
-                    gst::event::Segment::new(&gst::FormattedSegment::<gst::format::Time>::new());
-                udpsrc.src_pad.push_event(segment_evt).await;
+            let mut packets = packets.into_iter().map(|(buffer, saddr, daddr)| {
+                Self::handle_packet(&self.element, self.mtu, buffer, saddr, daddr)
+            });
 
-                self.need_segment = false;
+            if self.batch_size <= 1 {
+                return Ok(SrcItem::Buffer(packets.next().unwrap()));
             }
 
-            let res = udpsrc.src_pad.push(buffer).await.map(drop);
-            match res {
-                Ok(_) => gst::log!(CAT, obj = self.element, "Successfully pushed buffer"),
-                Err(gst::FlowError::Flushing) => gst::debug!(CAT, obj = self.element, "Flushing"),
-                Err(gst::FlowError::Eos) => {
-                    gst::debug!(CAT, obj = self.element, "EOS");
-                    udpsrc.src_pad.push_event(gst::event::Eos::new()).await;
-                }
-                Err(err) => {
-                    gst::error!(CAT, obj = self.element, "Got error {}", err);
-                    gst::element_error!(
-                        self.element,
-                        gst::StreamError::Failed,
-                        ("Internal data stream error"),
-                        ["streaming stopped, reason {}", err]
-                    );
+            let mut list = gst::BufferList::new_sized(self.batch_size);
+            {
+                let list = list.get_mut().unwrap();
+                for buffer in packets {
+                    list.add(buffer);
                 }
+                self.fill_batch(list);
             }
 
-            res
-        }
-        .boxed()
-    }
-
-    fn stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
-        async move {
-            gst::log!(CAT, obj = self.element, "Stopping task");
-            self.need_initial_events = true;
-            self.need_segment = true;
-            gst::log!(CAT, obj = self.element, "Task stopped");
-            Ok(())
-        }
-        .boxed()
-    }
-
-    fn flush_stop(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
-        async move {
-            gst::log!(CAT, obj = self.element, "Stopping task flush");
-            self.need_segment = true;
-            gst::log!(CAT, obj = self.element, "Stopped task flush");
-            Ok(())
-        }
-        .boxed()
-    }
-
-    fn handle_loop_error(&mut self, err: gst::FlowError) -> BoxFuture<'_, task::Trigger> {
-        async move {
-            match err {
-                gst::FlowError::Flushing => {
-                    gst::debug!(CAT, obj = self.element, "Flushing");
-
-                    task::Trigger::FlushStart
-                }
-                gst::FlowError::Eos => {
-                    gst::debug!(CAT, obj = self.element, "EOS");
-                    self.element
-                        .imp()
-                        .src_pad
-                        .push_event(gst::event::Eos::new())
-                        .await;
-
-                    task::Trigger::Stop
-                }
-                err => {
-                    gst::error!(CAT, obj = self.element, "Got error {err}");
-                    gst::element_error!(
-                        &self.element,
-                        gst::StreamError::Failed,
-                        ("Internal data stream error"),
-                        ["streaming stopped, reason {}", err]
-                    );
-
-                    task::Trigger::Error
-                }
+            if list.len() == 1 {
+                let buffer = list.get_owned(0).unwrap();
+                return Ok(SrcItem::Buffer(buffer));
             }
+
+            Ok(SrcItem::BufferList(list))
         }
         .boxed()
     }
@@ -815,6 +1443,7 @@ pub struct UdpSrc {
     configured_caps: Mutex<Option<gst::Caps>>,
     settings: Mutex<Settings>,
     state: Mutex<State>,
+    stats: Stats,
 }
 
 static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
@@ -826,9 +1455,17 @@ static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
 });
 
 impl UdpSrc {
-    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Preparing");
+    fn state(&self) -> TaskState {
+        self.task.state()
+    }
+}
+
+impl TsSrcImpl for UdpSrc {
+    fn task(&self) -> &Task {
+        &self.task
+    }
 
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
         let settings = self.settings.lock().unwrap();
         let context =
             Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
@@ -842,48 +1479,22 @@ impl UdpSrc {
         let (sender, receiver) = channel(1);
 
         *self.configured_caps.lock().unwrap() = None;
+        self.task.set_name(self.obj().name().as_str());
         self.task
-            .prepare(UdpSrcTask::new(self.obj().clone(), receiver), context)
+            .prepare(
+                SrcTaskAdapter::new(UdpSrcTask::new(self.obj().clone(), receiver)),
+                context,
+            )
             .block_on()?;
 
-        let mut state = self.state.lock().unwrap();
-        state.event_sender = Some(sender);
-        drop(state);
-
-        gst::debug!(CAT, imp = self, "Prepared");
+        self.state.lock().unwrap().event_sender = Some(sender);
 
         Ok(())
     }
 
     fn unprepare(&self) {
-        gst::debug!(CAT, imp = self, "Unpreparing");
         self.task.unprepare().block_on().unwrap();
-        gst::debug!(CAT, imp = self, "Unprepared");
-    }
-
-    fn stop(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Stopping");
-        self.task.stop().block_on()?;
-        gst::debug!(CAT, imp = self, "Stopped");
-        Ok(())
-    }
-
-    fn start(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Starting");
-        self.task.start().block_on()?;
-        gst::debug!(CAT, imp = self, "Started");
-        Ok(())
-    }
-
-    fn pause(&self) -> Result<(), gst::ErrorMessage> {
-        gst::debug!(CAT, imp = self, "Pausing");
-        self.task.pause().block_on()?;
-        gst::debug!(CAT, imp = self, "Paused");
-        Ok(())
-    }
-
-    fn state(&self) -> TaskState {
-        self.task.state()
+        self.stats.reset();
     }
 }
 
@@ -903,6 +1514,7 @@ impl ObjectSubclass for UdpSrc {
             configured_caps: Default::default(),
             settings: Default::default(),
             state: Default::default(),
+            stats: Default::default(),
         }
     }
 }
@@ -924,7 +1536,7 @@ impl ObjectImpl for UdpSrc {
                     .build(),
                 glib::ParamSpecString::builder("address")
                     .nick("Address")
-                    .blurb("Address/multicast group to listen on")
+                    .blurb("Address/multicast group to listen on. IPv6 addresses can be bracketed and scoped, e.g. \"[ff02::1%eth0]\"")
                     .default_value(DEFAULT_ADDRESS)
                     .build(),
                 glib::ParamSpecInt::builder("port")
@@ -934,9 +1546,19 @@ impl ObjectImpl for UdpSrc {
                     .maximum(u16::MAX as i32)
                     .default_value(DEFAULT_PORT)
                     .build(),
+                glib::ParamSpecString::builder("addresses")
+                    .nick("Addresses")
+                    .blurb("Comma separated list of address:port to listen on, overriding address & port (\"239.0.0.1:5004,[ff02::1]:5006\")")
+                    .default_value(DEFAULT_ADDRESSES)
+                    .build(),
+                glib::ParamSpecBoolean::builder("ignore-bind-errors")
+                    .nick("Ignore Bind Errors")
+                    .blurb("Skip the addresses which can't be bound as long as one of them succeeds")
+                    .default_value(DEFAULT_IGNORE_BIND_ERRORS)
+                    .build(),
                 glib::ParamSpecBoolean::builder("reuse")
                     .nick("Reuse")
-                    .blurb("Allow reuse of the port")
+                    .blurb("Allow reuse of the address (SO_REUSEADDR)")
                     .default_value(DEFAULT_REUSE)
                     .build(),
                 glib::ParamSpecBoxed::builder::<gst::Caps>("caps")
@@ -949,11 +1571,26 @@ impl ObjectImpl for UdpSrc {
                     .maximum(i32::MAX as u32)
                     .default_value(DEFAULT_MTU)
                     .build(),
+                glib::ParamSpecUInt64::builder("truncated-packets")
+                    .nick("Truncated Packets")
+                    .blurb("Number of received packets truncated because they were larger than the mtu")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecBoxed::builder::<gst::Structure>("stats")
+                    .nick("Statistics")
+                    .blurb("Receive statistics")
+                    .read_only()
+                    .build(),
                 glib::ParamSpecBoolean::builder("retrieve-sender-address")
                     .nick("Retrieve sender address")
                     .blurb("Whether to retrieve the sender address and add it to buffers as meta. Disabling this might result in minor performance improvements in certain scenarios")
                     .default_value(DEFAULT_RETRIEVE_SENDER_ADDRESS)
                     .build(),
+                glib::ParamSpecBoolean::builder("retrieve-destination-address")
+                    .nick("Retrieve destination address")
+                    .blurb("Whether to add the local address (e.g. the multicast group) the packets were received on to buffers as meta, after the sender address meta if any")
+                    .default_value(DEFAULT_RETRIEVE_DESTINATION_ADDRESS)
+                    .build(),
                 glib::ParamSpecBoolean::builder("loop")
                     .nick("Loop")
                     .blurb("Set the multicast loop parameter")
@@ -971,9 +1608,41 @@ impl ObjectImpl for UdpSrc {
                         separated by comma. (\"eth0,eth1\")")
                     .default_value(DEFAULT_MULTICAST_IFACE)
                     .build(),
+                glib::ParamSpecString::builder("multicast-source")
+                    .nick("Multicast Source")
+                    .blurb("Comma separated list of source addresses to join the multicast group for (source-specific multicast)")
+                    .default_value(DEFAULT_MULTICAST_SOURCE)
+                    .build(),
+                glib::ParamSpecBoolean::builder("multicast-ssm-fallback")
+                    .nick("Multicast SSM Fallback")
+                    .blurb("Join the multicast group for any source if joining for the multicast-source fails")
+                    .default_value(DEFAULT_MULTICAST_SSM_FALLBACK)
+                    .build(),
+                glib::ParamSpecUInt64::builder("timeout")
+                    .nick("Timeout")
+                    .blurb("Post a message after timeout nanoseconds without receiving data (0 = disabled)")
+                    .default_value(DEFAULT_TIMEOUT)
+                    .build(),
+                glib::ParamSpecUInt::builder("batch-size")
+                    .nick("Batch Size")
+                    .blurb("Maximum number of packets read per wakeup and pushed as a buffer list (1 = push each buffer)")
+                    .minimum(1)
+                    .default_value(DEFAULT_BATCH_SIZE)
+                    .build(),
 
             ];
 
+            #[cfg(unix)]
+            {
+                properties.push(
+                    glib::ParamSpecBoolean::builder("reuse-port")
+                        .nick("Reuse Port")
+                        .blurb("Allow multiple sockets to bind the same port and load-balance reception (SO_REUSEPORT)")
+                        .default_value(DEFAULT_REUSE_PORT)
+                        .build(),
+                );
+            }
+
             #[cfg(not(windows))]
             {
                 properties.push(
@@ -982,6 +1651,13 @@ impl ObjectImpl for UdpSrc {
                         .blurb("Socket to use for UDP reception. (None == allocate)")
                         .build(),
                 );
+                properties.push(
+                    glib::ParamSpecBoolean::builder("close-socket")
+                        .nick("Close Socket")
+                        .blurb("Close the provided socket when the element shuts down")
+                        .default_value(DEFAULT_CLOSE_SOCKET)
+                        .build(),
+                );
                 properties.push(
                     glib::ParamSpecObject::builder::<gio::Socket>("used-socket")
                         .nick("Used Socket")
@@ -1006,9 +1682,18 @@ impl ObjectImpl for UdpSrc {
             "port" => {
                 settings.port = value.get().expect("type checked upstream");
             }
+            "addresses" => {
+                settings.addresses = value.get().expect("type checked upstream");
+            }
+            "ignore-bind-errors" => {
+                settings.ignore_bind_errors = value.get().expect("type checked upstream");
+            }
             "reuse" => {
                 settings.reuse = value.get().expect("type checked upstream");
             }
+            "reuse-port" => {
+                settings.reuse_port = value.get().expect("type checked upstream");
+            }
             "caps" => {
                 settings.caps = value.get().expect("type checked upstream");
             }
@@ -1024,6 +1709,9 @@ impl ObjectImpl for UdpSrc {
             "used-socket" => {
                 unreachable!();
             }
+            "close-socket" => {
+                settings.close_socket = value.get().expect("type checked upstream");
+            }
             "context" => {
                 settings.context = value
                     .get::<Option<String>>()
@@ -1038,6 +1726,9 @@ impl ObjectImpl for UdpSrc {
             "retrieve-sender-address" => {
                 settings.retrieve_sender_address = value.get().expect("type checked upstream");
             }
+            "retrieve-destination-address" => {
+                settings.retrieve_destination_address = value.get().expect("type checked upstream");
+            }
             "loop" => {
                 settings.multicast_loop = value.get().expect("type checked upstream");
             }
@@ -1047,16 +1738,43 @@ impl ObjectImpl for UdpSrc {
             "multicast-iface" => {
                 settings.multicast_iface = value.get().expect("type checked upstream");
             }
+            "multicast-source" => {
+                settings.multicast_source = value.get().expect("type checked upstream");
+            }
+            "multicast-ssm-fallback" => {
+                settings.multicast_ssm_fallback = value.get().expect("type checked upstream");
+            }
+            "timeout" => {
+                settings.timeout = value.get().expect("type checked upstream");
+            }
+            "batch-size" => {
+                settings.batch_size = value.get().expect("type checked upstream");
+            }
             _ => unimplemented!(),
         }
     }
 
     fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "truncated-packets" => {
+                return self
+                    .stats
+                    .packets_truncated
+                    .load(Ordering::Relaxed)
+                    .to_value();
+            }
+            "stats" => return self.stats.to_structure().to_value(),
+            _ => (),
+        }
+
         let settings = self.settings.lock().unwrap();
         match pspec.name() {
             "address" => settings.address.to_value(),
             "port" => settings.port.to_value(),
+            "addresses" => settings.addresses.to_value(),
+            "ignore-bind-errors" => settings.ignore_bind_errors.to_value(),
             "reuse" => settings.reuse.to_value(),
+            "reuse-port" => settings.reuse_port.to_value(),
             "caps" => settings.caps.to_value(),
             "mtu" => settings.mtu.to_value(),
             "socket" => settings
@@ -1069,12 +1787,18 @@ impl ObjectImpl for UdpSrc {
                 .as_ref()
                 .map(GioSocketWrapper::as_socket)
                 .to_value(),
+            "close-socket" => settings.close_socket.to_value(),
             "context" => settings.context.to_value(),
             "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
             "retrieve-sender-address" => settings.retrieve_sender_address.to_value(),
+            "retrieve-destination-address" => settings.retrieve_destination_address.to_value(),
             "loop" => settings.multicast_loop.to_value(),
             "buffer-size" => settings.buffer_size.to_value(),
             "multicast-iface" => settings.multicast_iface.to_value(),
+            "multicast-source" => settings.multicast_source.to_value(),
+            "multicast-ssm-fallback" => settings.multicast_ssm_fallback.to_value(),
+            "timeout" => settings.timeout.to_value(),
+            "batch-size" => settings.batch_size.to_value(),
             _ => unimplemented!(),
         }
     }
@@ -1125,43 +1849,7 @@ impl ElementImpl for UdpSrc {
         &self,
         transition: gst::StateChange,
     ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
-        gst::trace!(CAT, imp = self, "Changing state {:?}", transition);
-
-        match transition {
-            gst::StateChange::NullToReady => {
-                self.prepare().map_err(|err| {
-                    self.post_error_message(err);
-                    gst::StateChangeError
-                })?;
-            }
-            gst::StateChange::PlayingToPaused => {
-                self.pause().map_err(|_| gst::StateChangeError)?;
-            }
-            gst::StateChange::ReadyToNull => {
-                self.unprepare();
-            }
-            _ => (),
-        }
-
-        let mut success = self.parent_change_state(transition)?;
-
-        match transition {
-            gst::StateChange::ReadyToPaused => {
-                success = gst::StateChangeSuccess::NoPreroll;
-            }
-            gst::StateChange::PausedToPlaying => {
-                self.start().map_err(|_| gst::StateChangeError)?;
-            }
-            gst::StateChange::PlayingToPaused => {
-                success = gst::StateChangeSuccess::NoPreroll;
-            }
-            gst::StateChange::PausedToReady => {
-                self.stop().map_err(|_| gst::StateChangeError)?;
-            }
-            _ => (),
-        }
-
-        Ok(success)
+        self.src_change_state(transition)
     }
 
     fn send_event(&self, event: gst::Event) -> bool {
@@ -1172,7 +1860,7 @@ impl ElementImpl for UdpSrc {
         match event.view() {
             EventView::Eos(_) => {
                 if self.state() != TaskState::Started {
-                    if let Err(err) = self.start() {
+                    if let Err(err) = self.task.start().block_on() {
                         gst::error!(CAT, imp = self, "Failed to start task thread {err:?}");
                     }
                 }
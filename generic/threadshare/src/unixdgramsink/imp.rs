@@ -0,0 +1,510 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use futures::future::{abortable, AbortHandle, BoxFuture};
+use futures::prelude::*;
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gst::EventView;
+use gst::{element_error, error_msg};
+
+use std::sync::LazyLock;
+
+use std::io;
+use std::os::unix::net::{SocketAddr as UnixSocketAddr, UnixDatagram};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::net::unix_socket_addr;
+use crate::runtime::executor::{block_on_or_add_sub_task, debug_assert_not_context_thread};
+use crate::runtime::prelude::*;
+use crate::runtime::{Async, Context, PadSink};
+
+const DEFAULT_PATH: Option<&str> = None;
+const DEFAULT_ABSTRACT: bool = false;
+const DEFAULT_CONTEXT: &str = "";
+const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+
+#[derive(Debug, Clone)]
+struct Settings {
+    path: Option<String>,
+    is_abstract: bool,
+    context: String,
+    context_wait: Duration,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            path: DEFAULT_PATH.map(Into::into),
+            is_abstract: DEFAULT_ABSTRACT,
+            context: DEFAULT_CONTEXT.into(),
+            context_wait: DEFAULT_CONTEXT_WAIT,
+        }
+    }
+}
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-unixdgramsink",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing unix datagram sink"),
+    )
+});
+
+fn is_fatal_send_error(err: &io::Error) -> bool {
+    matches!(err.raw_os_error(), Some(libc::EBADF | libc::ENOTSOCK))
+}
+
+#[derive(Clone, Debug, Default)]
+struct UnixDgramSinkPadHandler {
+    inner: Arc<futures::lock::Mutex<UnixDgramSinkPadHandlerInner>>,
+    // Kept out of `inner` which is locked while waiting for the socket
+    abort_handle: Arc<Mutex<Option<AbortHandle>>>,
+}
+
+impl UnixDgramSinkPadHandler {
+    fn prepare(&self, socket: Async<UnixDatagram>, path: String, addr: UnixSocketAddr) {
+        debug_assert_not_context_thread("UnixDgramSink prepare");
+
+        futures::executor::block_on(async move {
+            let mut inner = self.inner.lock().await;
+            inner.socket = Some(socket);
+            inner.path = path;
+            inner.addr = Some(addr);
+        })
+    }
+
+    fn unprepare(&self) {
+        futures::executor::block_on(async move {
+            let mut inner = self.inner.lock().await;
+            inner.socket = None;
+            inner.addr = None;
+        })
+    }
+
+    fn start(&self) {
+        futures::executor::block_on(async move {
+            self.inner.lock().await.is_flushing = false;
+        })
+    }
+
+    fn stop(&self) {
+        self.abort();
+        futures::executor::block_on(async move {
+            self.inner.lock().await.is_flushing = true;
+        })
+    }
+
+    fn abort(&self) {
+        if let Some(abort_handle) = self.abort_handle.lock().unwrap().take() {
+            abort_handle.abort();
+        }
+    }
+
+    /// Runs `fut`, returning `Err(Flushing)` if it was aborted
+    /// by a flush or a state change.
+    async fn run_abortable(
+        &self,
+        fut: impl Future<Output = Result<gst::FlowSuccess, gst::FlowError>>,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let (fut, abort_handle) = abortable(fut);
+        *self.abort_handle.lock().unwrap() = Some(abort_handle);
+        let res = fut.await;
+        self.abort_handle.lock().unwrap().take();
+
+        res.unwrap_or(Err(gst::FlowError::Flushing))
+    }
+}
+
+impl PadSinkHandler for UnixDgramSinkPadHandler {
+    type ElementImpl = UnixDgramSink;
+
+    fn sink_chain(
+        self,
+        _pad: gst::Pad,
+        elem: super::UnixDgramSink,
+        buffer: gst::Buffer,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            self.run_abortable(async {
+                self.inner.lock().await.handle_buffer(&elem, &buffer).await
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn sink_chain_list(
+        self,
+        _pad: gst::Pad,
+        elem: super::UnixDgramSink,
+        list: gst::BufferList,
+    ) -> BoxFuture<'static, Result<gst::FlowSuccess, gst::FlowError>> {
+        async move {
+            self.run_abortable(async {
+                let inner = self.inner.lock().await;
+                for buffer in list.iter() {
+                    inner.handle_buffer(&elem, buffer).await?;
+                }
+
+                Ok(gst::FlowSuccess::Ok)
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn sink_event_serialized(
+        self,
+        _pad: gst::Pad,
+        elem: super::UnixDgramSink,
+        event: gst::Event,
+    ) -> BoxFuture<'static, bool> {
+        async move {
+            gst::debug!(CAT, obj = elem, "Handling {event:?}");
+
+            match event.view() {
+                EventView::Eos(_) => {
+                    let _ = elem.post_message(gst::message::Eos::builder().src(&elem).build());
+                }
+                EventView::FlushStop(_) => {
+                    self.inner.lock().await.is_flushing = false;
+                }
+                EventView::SinkMessage(e) => {
+                    let _ = elem.post_message(e.message());
+                }
+                _ => (),
+            }
+
+            true
+        }
+        .boxed()
+    }
+
+    fn sink_event(self, _pad: &gst::Pad, imp: &UnixDgramSink, event: gst::Event) -> bool {
+        gst::debug!(CAT, imp = imp, "Handling {event:?}");
+
+        if let EventView::FlushStart(..) = event.view() {
+            self.abort();
+            block_on_or_add_sub_task(async move {
+                self.inner.lock().await.is_flushing = true;
+            });
+        }
+
+        true
+    }
+}
+
+#[derive(Debug)]
+struct UnixDgramSinkPadHandlerInner {
+    is_flushing: bool,
+    socket: Option<Async<UnixDatagram>>,
+    /// Path as configured, for messages.
+    path: String,
+    addr: Option<UnixSocketAddr>,
+}
+
+impl Default for UnixDgramSinkPadHandlerInner {
+    fn default() -> Self {
+        Self {
+            is_flushing: true,
+            socket: None,
+            path: String::new(),
+            addr: None,
+        }
+    }
+}
+
+impl UnixDgramSinkPadHandlerInner {
+    async fn handle_buffer(
+        &self,
+        elem: &super::UnixDgramSink,
+        buffer: &gst::BufferRef,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        if self.is_flushing {
+            gst::debug!(CAT, obj = elem, "Flushing");
+            return Err(gst::FlowError::Flushing);
+        }
+
+        let (Some(socket), Some(addr)) = (self.socket.as_ref(), self.addr.as_ref()) else {
+            gst::debug!(CAT, obj = elem, "Not prepared");
+            return Err(gst::FlowError::Flushing);
+        };
+
+        gst::log!(CAT, obj = elem, "Handling {buffer:?}");
+
+        let map = buffer.map_readable().map_err(|_| {
+            element_error!(
+                elem,
+                gst::StreamError::Format,
+                ["Failed to map buffer readable"]
+            );
+            gst::FlowError::Error
+        })?;
+
+        match socket.write_with(|io| io.send_to_addr(&map, addr)).await {
+            Ok(_) => (),
+            Err(err) if is_fatal_send_error(&err) => {
+                element_error!(
+                    elem,
+                    gst::ResourceError::Write,
+                    ("Socket error"),
+                    ["Failed to send to {}: {}", self.path, err]
+                );
+                return Err(gst::FlowError::Error);
+            }
+            Err(err) => {
+                // Most likely no one is bound to the path (yet)
+                gst::warning!(CAT, obj = elem, "Failed to send to {}: {err}", self.path);
+            }
+        }
+
+        Ok(gst::FlowSuccess::Ok)
+    }
+}
+
+#[derive(Debug)]
+pub struct UnixDgramSink {
+    sink_pad: PadSink,
+    sink_pad_handler: UnixDgramSinkPadHandler,
+    settings: Mutex<Settings>,
+    ts_ctx: Mutex<Option<Context>>,
+}
+
+impl UnixDgramSink {
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
+        gst::debug!(CAT, imp = self, "Preparing");
+
+        let settings = self.settings.lock().unwrap().clone();
+
+        let ts_ctx = Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
+            error_msg!(
+                gst::ResourceError::OpenWrite,
+                ["Failed to acquire Context: {}", err]
+            )
+        })?;
+
+        let path = settings
+            .path
+            .ok_or_else(|| error_msg!(gst::ResourceError::Settings, ["No path set"]))?;
+
+        let addr = unix_socket_addr(&path, settings.is_abstract).map_err(|err| {
+            error_msg!(
+                gst::ResourceError::Settings,
+                ["Invalid path {}: {}", path, err]
+            )
+        })?;
+
+        let socket = ts_ctx
+            .enter(Async::<UnixDatagram>::unbound)
+            .map_err(|err| {
+                error_msg!(
+                    gst::ResourceError::OpenWrite,
+                    ["Failed to create socket: {}", err]
+                )
+            })?;
+
+        self.sink_pad_handler.prepare(socket, path, addr);
+        *self.ts_ctx.lock().unwrap() = Some(ts_ctx);
+
+        gst::debug!(CAT, imp = self, "Prepared");
+
+        Ok(())
+    }
+
+    fn unprepare(&self) {
+        gst::debug!(CAT, imp = self, "Unpreparing");
+        self.sink_pad_handler.unprepare();
+        *self.ts_ctx.lock().unwrap() = None;
+        gst::debug!(CAT, imp = self, "Unprepared");
+    }
+
+    fn stop(&self) {
+        gst::debug!(CAT, imp = self, "Stopping");
+        self.sink_pad_handler.stop();
+        gst::debug!(CAT, imp = self, "Stopped");
+    }
+
+    fn start(&self) {
+        gst::debug!(CAT, imp = self, "Starting");
+        self.sink_pad_handler.start();
+        gst::debug!(CAT, imp = self, "Started");
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for UnixDgramSink {
+    const NAME: &'static str = "GstTsUnixDgramSink";
+    type Type = super::UnixDgramSink;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        let sink_pad_handler = UnixDgramSinkPadHandler::default();
+        Self {
+            sink_pad: PadSink::new(
+                gst::Pad::from_template(&klass.pad_template("sink").unwrap()),
+                sink_pad_handler.clone(),
+            ),
+            sink_pad_handler,
+            settings: Default::default(),
+            ts_ctx: Default::default(),
+        }
+    }
+}
+
+impl ObjectImpl for UnixDgramSink {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("context")
+                    .nick("Context")
+                    .blurb("Context name to share threads with")
+                    .default_value(Some(DEFAULT_CONTEXT))
+                    .build(),
+                glib::ParamSpecUInt::builder("context-wait")
+                    .nick("Context Wait")
+                    .blurb("Throttle poll loop to run at most once every this many ms")
+                    .maximum(1000)
+                    .default_value(DEFAULT_CONTEXT_WAIT.as_millis() as u32)
+                    .build(),
+                glib::ParamSpecString::builder("path")
+                    .nick("Path")
+                    .blurb("Path of the socket to send to")
+                    .default_value(DEFAULT_PATH)
+                    .build(),
+                glib::ParamSpecBoolean::builder("abstract")
+                    .nick("Abstract")
+                    .blurb("Whether the path is a name in the Linux abstract namespace")
+                    .default_value(DEFAULT_ABSTRACT)
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "path" => {
+                settings.path = value.get().expect("type checked upstream");
+            }
+            "abstract" => {
+                settings.is_abstract = value.get().expect("type checked upstream");
+            }
+            "context" => {
+                settings.context = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| DEFAULT_CONTEXT.into());
+            }
+            "context-wait" => {
+                settings.context_wait = Duration::from_millis(
+                    value.get::<u32>().expect("type checked upstream").into(),
+                );
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "path" => settings.path.to_value(),
+            "abstract" => settings.is_abstract.to_value(),
+            "context" => settings.context.to_value(),
+            "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let obj = self.obj();
+        obj.add_pad(self.sink_pad.gst_pad()).unwrap();
+        obj.set_element_flags(gst::ElementFlags::SINK);
+    }
+}
+
+impl GstObjectImpl for UnixDgramSink {}
+
+impl ElementImpl for UnixDgramSink {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Thread-sharing unix datagram sink",
+                "Sink/Network",
+                "Sends data to a unix datagram socket",
+                "agent <agent@local>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+            let caps = gst::Caps::new_any();
+
+            let sink_pad_template = gst::PadTemplate::new(
+                "sink",
+                gst::PadDirection::Sink,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![sink_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        gst::trace!(CAT, imp = self, "Changing state {:?}", transition);
+
+        match transition {
+            gst::StateChange::NullToReady => {
+                self.prepare().map_err(|err| {
+                    self.post_error_message(err);
+                    gst::StateChangeError
+                })?;
+            }
+            gst::StateChange::ReadyToPaused => {
+                self.start();
+            }
+            gst::StateChange::PausedToReady => {
+                self.stop();
+            }
+            gst::StateChange::ReadyToNull => {
+                self.unprepare();
+            }
+            _ => (),
+        }
+
+        self.parent_change_state(transition)
+    }
+}
@@ -0,0 +1,530 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use futures::future::BoxFuture;
+use futures::prelude::*;
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+
+use std::sync::LazyLock;
+
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::net::unix_socket_addr;
+use crate::runtime::prelude::*;
+use crate::runtime::{Async, Context, PadSrc, PadSrcRef, SrcItem, SrcTaskAdapter, Task};
+use crate::socket::{Socket, SocketError, SocketRead};
+
+const DEFAULT_PATH: Option<&str> = None;
+const DEFAULT_ABSTRACT: bool = false;
+const DEFAULT_CAPS: Option<gst::Caps> = None;
+const DEFAULT_MTU: u32 = 1492;
+const DEFAULT_CONTEXT: &str = "";
+const DEFAULT_CONTEXT_WAIT: Duration = Duration::ZERO;
+
+#[derive(Debug, Clone)]
+struct Settings {
+    path: Option<String>,
+    is_abstract: bool,
+    caps: Option<gst::Caps>,
+    mtu: u32,
+    context: String,
+    context_wait: Duration,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            path: DEFAULT_PATH.map(Into::into),
+            is_abstract: DEFAULT_ABSTRACT,
+            caps: DEFAULT_CAPS,
+            mtu: DEFAULT_MTU,
+            context: DEFAULT_CONTEXT.into(),
+            context_wait: DEFAULT_CONTEXT_WAIT,
+        }
+    }
+}
+
+struct UnixDgramReader(Async<UnixDatagram>);
+
+impl SocketRead for UnixDgramReader {
+    const DO_TIMESTAMP: bool = true;
+
+    fn read<'buf>(
+        &'buf mut self,
+        buffer: &'buf mut [u8],
+    ) -> BoxFuture<'buf, io::Result<(usize, Option<std::net::SocketAddr>)>> {
+        async move { self.0.recv(buffer).await.map(|read_size| (read_size, None)) }.boxed()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct UnixDgramSrcPadHandler;
+
+impl PadSrcHandler for UnixDgramSrcPadHandler {
+    type ElementImpl = UnixDgramSrc;
+
+    fn src_event(self, pad: &gst::Pad, imp: &UnixDgramSrc, event: gst::Event) -> bool {
+        gst::log!(CAT, obj = pad, "Handling {:?}", event);
+
+        use gst::EventView;
+        let ret = match event.view() {
+            EventView::FlushStart(..) => imp.task.flush_start().await_maybe_on_context().is_ok(),
+            EventView::FlushStop(..) => imp.task.flush_stop().await_maybe_on_context().is_ok(),
+            EventView::Reconfigure(..) => true,
+            EventView::Latency(..) => true,
+            _ => false,
+        };
+
+        if ret {
+            gst::log!(CAT, obj = pad, "Handled {:?}", event);
+        } else {
+            gst::log!(CAT, obj = pad, "Didn't handle {:?}", event);
+        }
+
+        ret
+    }
+
+    fn src_query(self, pad: &gst::Pad, imp: &UnixDgramSrc, query: &mut gst::QueryRef) -> bool {
+        gst::log!(CAT, obj = pad, "Handling {:?}", query);
+
+        use gst::QueryViewMut;
+        let ret = match query.view_mut() {
+            QueryViewMut::Latency(q) => {
+                q.set(true, gst::ClockTime::ZERO, gst::ClockTime::NONE);
+                true
+            }
+            QueryViewMut::Scheduling(q) => {
+                q.set(gst::SchedulingFlags::SEQUENTIAL, 1, -1, 0);
+                q.add_scheduling_modes(&[gst::PadMode::Push]);
+                true
+            }
+            QueryViewMut::Caps(q) => {
+                let caps = if let Some(caps) = imp.configured_caps.lock().unwrap().as_ref() {
+                    q.filter()
+                        .map(|f| f.intersect_with_mode(caps, gst::CapsIntersectMode::First))
+                        .unwrap_or_else(|| caps.clone())
+                } else {
+                    q.filter()
+                        .map(|f| f.to_owned())
+                        .unwrap_or_else(gst::Caps::new_any)
+                };
+
+                q.set_result(&caps);
+
+                true
+            }
+            _ => false,
+        };
+
+        if ret {
+            gst::log!(CAT, obj = pad, "Handled {:?}", query);
+        } else {
+            gst::log!(CAT, obj = pad, "Didn't handle {:?}", query);
+        }
+
+        ret
+    }
+}
+
+struct UnixDgramSrcTask {
+    element: super::UnixDgramSrc,
+    path: String,
+    is_abstract: bool,
+    buffer_pool: gst::BufferPool,
+    socket: Option<Socket<UnixDgramReader>>,
+}
+
+impl UnixDgramSrcTask {
+    fn new(
+        element: super::UnixDgramSrc,
+        path: String,
+        is_abstract: bool,
+        buffer_pool: gst::BufferPool,
+    ) -> Self {
+        UnixDgramSrcTask {
+            element,
+            path,
+            is_abstract,
+            buffer_pool,
+            socket: None,
+        }
+    }
+}
+
+impl SrcTaskImpl for UnixDgramSrcTask {
+    fn element(&self) -> &gst::Element {
+        self.element.upcast_ref()
+    }
+
+    fn src_pad(&self) -> PadSrcRef<'_> {
+        self.element.imp().src_pad.as_ref()
+    }
+
+    fn caps(&mut self) -> Option<gst::Caps> {
+        self.element.imp().settings.lock().unwrap().caps.clone()
+    }
+
+    fn caps_pushed(&mut self, caps: &gst::Caps) {
+        *self.element.imp().configured_caps.lock().unwrap() = Some(caps.clone());
+    }
+
+    fn prepare(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        async move {
+            gst::log!(CAT, obj = self.element, "Preparing task on {}", self.path);
+
+            let socket = unix_socket_addr(&self.path, self.is_abstract)
+                .and_then(|addr| UnixDatagram::bind_addr(&addr))
+                .and_then(Async::<UnixDatagram>::new)
+                .map_err(|err| {
+                    gst::error_msg!(
+                        gst::ResourceError::OpenRead,
+                        ["Failed to bind {}: {}", self.path, err]
+                    )
+                })?;
+
+            self.socket = Some(
+                Socket::try_new(
+                    self.element.clone().upcast(),
+                    self.buffer_pool.clone(),
+                    UnixDgramReader(socket),
+                )
+                .map_err(|err| {
+                    gst::error_msg!(
+                        gst::ResourceError::OpenRead,
+                        ["Failed to prepare socket {:?}", err]
+                    )
+                })?,
+            );
+
+            gst::log!(CAT, obj = self.element, "Task prepared");
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn unprepare(&mut self) -> BoxFuture<'_, ()> {
+        async move {
+            gst::log!(CAT, obj = self.element, "Unpreparing task");
+
+            if self.socket.take().is_some() && !self.is_abstract {
+                // Closing the socket doesn't remove the file it was bound to
+                if let Err(err) = std::fs::remove_file(&self.path) {
+                    gst::warning!(
+                        CAT,
+                        obj = self.element,
+                        "Failed to remove {}: {err}",
+                        self.path
+                    );
+                }
+            }
+
+            gst::log!(CAT, obj = self.element, "Task unprepared");
+        }
+        .boxed()
+    }
+
+    fn unprepare(&mut self) -> BoxFuture<'_, ()> {
+        async move {
+            gst::log!(CAT, obj = self.element, "Unpreparing task");
+
+            if self.socket.take().is_some() && !self.is_abstract {
+                // Closing the socket doesn't remove the file it was bound to
+                if let Err(err) = std::fs::remove_file(&self.path) {
+                    gst::warning!(
+                        CAT,
+                        obj = self.element,
+                        "Failed to remove {}: {err}",
+                        self.path
+                    );
+                }
+            }
+
+            gst::log!(CAT, obj = self.element, "Task unprepared");
+        }
+        .boxed()
+    }
+
+    fn start(&mut self) -> BoxFuture<'_, Result<(), gst::ErrorMessage>> {
+        async move {
+            gst::log!(CAT, obj = self.element, "Starting task");
+            self.socket
+                .as_mut()
+                .unwrap()
+                .set_clock(self.element.clock(), self.element.base_time());
+            gst::log!(CAT, obj = self.element, "Task started");
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn iterate(&mut self) -> BoxFuture<'_, Result<SrcItem, gst::FlowError>> {
+        async move {
+            match self.socket.as_mut().unwrap().try_next().await {
+                Ok((buffer, _saddr)) => Ok(SrcItem::Buffer(buffer)),
+                Err(SocketError::Gst(err)) => {
+                    gst::error!(CAT, obj = self.element, "Got error {err:#}");
+                    gst::element_error!(
+                        self.element,
+                        gst::StreamError::Failed,
+                        ("Internal data stream error"),
+                        ["streaming stopped, reason {err}"]
+                    );
+
+                    Err(gst::FlowError::Error)
+                }
+                Err(SocketError::Io(err)) => {
+                    gst::error!(CAT, obj = self.element, "Got error {err:#}");
+                    gst::element_error!(
+                        self.element,
+                        gst::ResourceError::Read,
+                        ("I/O error"),
+                        ["streaming stopped, I/O error {err}"]
+                    );
+
+                    Err(gst::FlowError::Error)
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+pub struct UnixDgramSrc {
+    src_pad: PadSrc,
+    task: Task,
+    configured_caps: Mutex<Option<gst::Caps>>,
+    settings: Mutex<Settings>,
+}
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "ts-unixdgramsrc",
+        gst::DebugColorFlags::empty(),
+        Some("Thread-sharing unix datagram source"),
+    )
+});
+
+impl TsSrcImpl for UnixDgramSrc {
+    fn task(&self) -> &Task {
+        &self.task
+    }
+
+    fn prepare(&self) -> Result<(), gst::ErrorMessage> {
+        let settings = self.settings.lock().unwrap().clone();
+
+        let context =
+            Context::acquire(&settings.context, settings.context_wait).map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to acquire Context: {}", err]
+                )
+            })?;
+
+        *self.configured_caps.lock().unwrap() = None;
+
+        let path = settings
+            .path
+            .ok_or_else(|| gst::error_msg!(gst::ResourceError::Settings, ["No path set"]))?;
+
+        let buffer_pool = gst::BufferPool::new();
+        let mut config = buffer_pool.config();
+        config.set_params(None, settings.mtu, 0, 0);
+        buffer_pool.set_config(config).map_err(|_| {
+            gst::error_msg!(
+                gst::ResourceError::Settings,
+                ["Failed to configure buffer pool"]
+            )
+        })?;
+
+        self.task.set_name(self.obj().name().as_str());
+
+        self.task
+            .prepare(
+                SrcTaskAdapter::new(UnixDgramSrcTask::new(
+                    self.obj().clone(),
+                    path,
+                    settings.is_abstract,
+                    buffer_pool,
+                )),
+                context,
+            )
+            .block_on()?;
+
+        Ok(())
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for UnixDgramSrc {
+    const NAME: &'static str = "GstTsUnixDgramSrc";
+    type Type = super::UnixDgramSrc;
+    type ParentType = gst::Element;
+
+    fn with_class(klass: &Self::Class) -> Self {
+        Self {
+            src_pad: PadSrc::new(
+                gst::Pad::from_template(&klass.pad_template("src").unwrap()),
+                UnixDgramSrcPadHandler,
+            ),
+            task: Task::default(),
+            configured_caps: Default::default(),
+            settings: Default::default(),
+        }
+    }
+}
+
+impl ObjectImpl for UnixDgramSrc {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("context")
+                    .nick("Context")
+                    .blurb("Context name to share threads with")
+                    .default_value(Some(DEFAULT_CONTEXT))
+                    .build(),
+                glib::ParamSpecUInt::builder("context-wait")
+                    .nick("Context Wait")
+                    .blurb("Throttle poll loop to run at most once every this many ms")
+                    .maximum(1000)
+                    .default_value(DEFAULT_CONTEXT_WAIT.as_millis() as u32)
+                    .build(),
+                glib::ParamSpecString::builder("path")
+                    .nick("Path")
+                    .blurb("Path of the socket to bind, removed when the element is closed")
+                    .default_value(DEFAULT_PATH)
+                    .build(),
+                glib::ParamSpecBoolean::builder("abstract")
+                    .nick("Abstract")
+                    .blurb("Whether the path is a name in the Linux abstract namespace")
+                    .default_value(DEFAULT_ABSTRACT)
+                    .build(),
+                glib::ParamSpecBoxed::builder::<gst::Caps>("caps")
+                    .nick("Caps")
+                    .blurb("Caps to use")
+                    .build(),
+                glib::ParamSpecUInt::builder("mtu")
+                    .nick("MTU")
+                    .blurb("Maximum expected packet size. This directly defines the allocation size of the receive buffer pool")
+                    .maximum(i32::MAX as u32)
+                    .default_value(DEFAULT_MTU)
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let mut settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "path" => {
+                settings.path = value.get().expect("type checked upstream");
+            }
+            "abstract" => {
+                settings.is_abstract = value.get().expect("type checked upstream");
+            }
+            "caps" => {
+                settings.caps = value.get().expect("type checked upstream");
+            }
+            "mtu" => {
+                settings.mtu = value.get().expect("type checked upstream");
+            }
+            "context" => {
+                settings.context = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream")
+                    .unwrap_or_else(|| DEFAULT_CONTEXT.into());
+            }
+            "context-wait" => {
+                settings.context_wait = Duration::from_millis(
+                    value.get::<u32>().expect("type checked upstream").into(),
+                );
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "path" => settings.path.to_value(),
+            "abstract" => settings.is_abstract.to_value(),
+            "caps" => settings.caps.to_value(),
+            "mtu" => settings.mtu.to_value(),
+            "context" => settings.context.to_value(),
+            "context-wait" => (settings.context_wait.as_millis() as u32).to_value(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let obj = self.obj();
+        obj.add_pad(self.src_pad.gst_pad()).unwrap();
+        obj.set_element_flags(gst::ElementFlags::SOURCE);
+    }
+}
+
+impl GstObjectImpl for UnixDgramSrc {}
+
+impl ElementImpl for UnixDgramSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Thread-sharing unix datagram source",
+                "Source/Network",
+                "Receives data from a unix datagram socket",
+                "agent <agent@local>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+            let caps = gst::Caps::new_any();
+            let src_pad_template = gst::PadTemplate::new(
+                "src",
+                gst::PadDirection::Src,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![src_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        self.src_change_state(transition)
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use gst::prelude::*;
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstthreadshare::plugin_register_static().expect("gstthreadshare appsink test");
+    });
+}
+
+#[test]
+fn loopback() {
+    init();
+
+    let pipeline = gst::Pipeline::default();
+
+    let caps = gst::Caps::builder("foo/bar").build();
+    let appsrc = gst::ElementFactory::make("ts-appsrc")
+        .property("context", "appsink-loopback")
+        .property("caps", &caps)
+        .build()
+        .unwrap();
+    let appsink = gst::ElementFactory::make("ts-appsink").build().unwrap();
+
+    pipeline.add_many([&appsrc, &appsink]).unwrap();
+    appsrc.link(&appsink).unwrap();
+
+    let new_samples = Arc::new(AtomicU32::new(0));
+    appsink.connect("new-sample", false, {
+        let new_samples = new_samples.clone();
+        move |_| {
+            new_samples.fetch_add(1, Ordering::SeqCst);
+            None
+        }
+    });
+
+    let got_eos = Arc::new(AtomicBool::new(false));
+    appsink.connect("eos", false, {
+        let got_eos = got_eos.clone();
+        move |_| {
+            got_eos.store(true, Ordering::SeqCst);
+            None
+        }
+    });
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+
+    for idx in 0..3u8 {
+        let buffer = gst::Buffer::from_slice(vec![idx; 4]);
+        assert!(appsrc.emit_by_name::<bool>("push-buffer", &[&buffer]));
+    }
+    assert!(appsrc.emit_by_name::<bool>("end-of-stream", &[]));
+
+    for idx in 0..3u8 {
+        let sample = appsink
+            .emit_by_name::<Option<gst::Sample>>("pull-sample", &[])
+            .unwrap();
+        assert_eq!(sample.caps(), Some(caps.as_ref()));
+        assert!(sample.segment().is_some());
+        let map = sample.buffer().unwrap().map_readable().unwrap();
+        assert_eq!(map.as_slice(), &[idx; 4]);
+    }
+
+    assert!(appsink
+        .emit_by_name::<Option<gst::Sample>>("pull-sample", &[])
+        .is_none());
+
+    let bus = pipeline.bus().unwrap();
+    let msg = bus
+        .timed_pop_filtered(gst::ClockTime::from_seconds(5), &[gst::MessageType::Eos])
+        .unwrap();
+    assert_eq!(msg.src(), Some(pipeline.upcast_ref::<gst::Object>()));
+
+    assert_eq!(new_samples.load(Ordering::SeqCst), 3);
+    assert!(got_eos.load(Ordering::SeqCst));
+
+    pipeline.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+fn drop_old_samples() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-appsink");
+    {
+        let appsink = h.element().unwrap();
+        appsink.set_property("max-buffers", 1u32);
+        appsink.set_property("drop", true);
+    }
+
+    h.play();
+    h.set_src_caps(gst::Caps::builder("foo/bar").build());
+
+    for idx in 0..3u8 {
+        h.push(gst::Buffer::from_slice(vec![idx; 4])).unwrap();
+    }
+
+    let appsink = h.element().unwrap();
+    let sample = appsink
+        .emit_by_name::<Option<gst::Sample>>("try-pull-sample", &[&0u64])
+        .unwrap();
+    let map = sample.buffer().unwrap().map_readable().unwrap();
+    assert_eq!(map.as_slice(), &[2u8; 4]);
+
+    assert!(appsink
+        .emit_by_name::<Option<gst::Sample>>("try-pull-sample", &[&10_000_000u64])
+        .is_none());
+}
@@ -236,3 +236,244 @@ fn pause_flush() {
     let _ = h.pull().unwrap();
     assert!(h.try_pull().is_none());
 }
+
+#[test]
+fn handle_event() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-appsrc");
+
+    let caps = gst::Caps::builder("foo/bar").build();
+    {
+        let appsrc = h.element().unwrap();
+        appsrc.set_property("caps", &caps);
+        appsrc.set_property("context", "appsrc-handle_event");
+    }
+
+    h.play();
+
+    let appsrc = h.element().unwrap();
+
+    // No handler connected: upstream event is not handled
+    let s = gst::Structure::new_empty("ts-appsrc-test");
+    assert!(!h.push_upstream_event(gst::event::CustomUpstream::new(s.clone())));
+
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    appsrc.connect("handle-event", false, move |args| {
+        let event = args[1].get::<gst::Event>().unwrap();
+        event_tx.send(event).unwrap();
+        Some(true.to_value())
+    });
+
+    assert!(h.push_upstream_event(gst::event::CustomUpstream::new(s)));
+    let event = event_rx.try_recv().unwrap();
+    assert_eq!(event.type_(), gst::EventType::CustomUpstream);
+    assert_eq!(event.structure().unwrap().name(), "ts-appsrc-test");
+
+    // Flush events are handled internally
+    assert!(h.push_upstream_event(gst::event::FlushStart::new()));
+    assert!(h.push_upstream_event(gst::event::FlushStop::new(true)));
+    assert!(event_rx.try_recv().is_err());
+}
+
+#[test]
+fn position() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-appsrc");
+
+    let caps = gst::Caps::builder("foo/bar").build();
+    {
+        let appsrc = h.element().unwrap();
+        appsrc.set_property("caps", &caps);
+        appsrc.set_property("context", "appsrc-position");
+    }
+
+    h.play();
+
+    let appsrc = h.element().unwrap();
+    let sinkpad = h.sinkpad().unwrap();
+
+    for i in 0..3u64 {
+        let mut buffer = gst::Buffer::new();
+        {
+            let buffer = buffer.get_mut().unwrap();
+            buffer.set_pts(gst::ClockTime::from_mseconds(20 * i));
+            buffer.set_duration(gst::ClockTime::from_mseconds(20));
+        }
+        assert!(appsrc.emit_by_name::<bool>("push-buffer", &[&buffer]));
+        let _ = h.pull().unwrap();
+
+        assert_eq!(
+            sinkpad.peer_query_position::<gst::ClockTime>(),
+            Some(gst::ClockTime::from_mseconds(20 * (i + 1))),
+        );
+    }
+
+    // Position is reset on flush
+    assert!(h.push_upstream_event(gst::event::FlushStart::new()));
+    assert!(h.push_upstream_event(gst::event::FlushStop::new(true)));
+    assert_eq!(sinkpad.peer_query_position::<gst::ClockTime>(), None);
+}
+
+#[test]
+fn negotiated_pool() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-appsrc");
+
+    let caps = gst::Caps::builder("foo/bar").build();
+    {
+        let appsrc = h.element().unwrap();
+        appsrc.set_property("caps", &caps);
+        appsrc.set_property("context", "appsrc-negotiated_pool");
+    }
+
+    // Downstream proposes a pool in the Allocation query
+    let proposed_pool = gst::BufferPool::new();
+    h.sinkpad()
+        .unwrap()
+        .add_probe(gst::PadProbeType::QUERY_DOWNSTREAM, {
+            let proposed_pool = proposed_pool.clone();
+            move |_, info| {
+                let Some(query) = info.query_mut() else {
+                    return gst::PadProbeReturn::Ok;
+                };
+
+                match query.view_mut() {
+                    gst::QueryViewMut::Allocation(q) => {
+                        q.add_allocation_pool(Some(&proposed_pool), 1024, 0, 0);
+                        gst::PadProbeReturn::Handled
+                    }
+                    _ => gst::PadProbeReturn::Ok,
+                }
+            }
+        });
+
+    h.play();
+
+    let appsrc = h.element().unwrap();
+    assert!(appsrc
+        .property::<Option<gst::BufferPool>>("negotiated-pool")
+        .is_none());
+
+    // The pool is negotiated when the caps are pushed, before the first buffer
+    assert!(appsrc.emit_by_name::<bool>("push-buffer", &[&gst::Buffer::new()]));
+    let _ = h.pull().unwrap();
+
+    let pool = appsrc
+        .property::<Option<gst::BufferPool>>("negotiated-pool")
+        .expect("no negotiated pool");
+    assert_eq!(pool, proposed_pool);
+    assert!(pool.is_active());
+    let (pool_caps, size, _, _) = pool.config().params().unwrap();
+    assert_eq!(pool_caps.as_ref(), Some(&caps));
+    assert_eq!(size, 1024);
+
+    // Buffers acquired from the pool flow downstream
+    let buffer = pool.acquire_buffer(None).unwrap();
+    assert!(appsrc.emit_by_name::<bool>("push-buffer", &[&buffer]));
+    let buffer = h.pull().unwrap();
+    assert_eq!(buffer.size(), 1024);
+
+    // The pool is released when stopping
+    appsrc.set_state(gst::State::Null).unwrap();
+    assert!(appsrc
+        .property::<Option<gst::BufferPool>>("negotiated-pool")
+        .is_none());
+    assert!(!proposed_pool.is_active());
+}
+
+#[test]
+fn wait_for_clock() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-appsrc");
+
+    let caps = gst::Caps::builder("foo/bar").build();
+    {
+        let appsrc = h.element().unwrap();
+        appsrc.set_property("caps", &caps);
+        appsrc.set_property("do-timestamp", true);
+        appsrc.set_property("wait-for-clock", true);
+        appsrc.set_property("context", "appsrc-wait_for_clock");
+    }
+
+    h.play();
+
+    let appsrc = h.element().unwrap();
+    appsrc.set_clock(None::<&gst::Clock>).unwrap();
+
+    // No clock yet: the buffer is queued
+    assert!(appsrc.emit_by_name::<bool>("push-buffer", &[&gst::Buffer::new()]));
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    assert!(h.try_pull().is_none());
+
+    // Providing a clock releases the timestamped buffer
+    let clock = gst::SystemClock::obtain();
+    appsrc.set_clock(Some(&clock)).unwrap();
+    appsrc.set_base_time(clock.time());
+
+    let buffer = h.pull().unwrap();
+    assert!(buffer.dts().is_some());
+    assert!(buffer.pts().is_none());
+}
+
+#[test]
+fn block() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-appsrc");
+
+    let caps = gst::Caps::builder("foo/bar").build();
+    {
+        let appsrc = h.element().unwrap();
+        appsrc.set_property("caps", &caps);
+        appsrc.set_property("max-buffers", 1u32);
+        appsrc.set_property("block", true);
+        appsrc.set_property("context", "appsrc-block");
+    }
+
+    h.play();
+
+    let appsrc = h.element().unwrap();
+
+    // Pushing from a regular thread waits for room in the queue
+    let pusher = std::thread::spawn({
+        let appsrc = appsrc.clone();
+        move || (0..5).all(|_| appsrc.emit_by_name::<bool>("push-buffer", &[&gst::Buffer::new()]))
+    });
+    for _ in 0..5 {
+        let _ = h.pull().unwrap();
+    }
+    assert!(pusher.join().unwrap());
+
+    // A blocked push is released when the element is stopped
+    h.element().unwrap().set_state(gst::State::Paused).unwrap();
+    // Fill the queue without blocking, the task doesn't dequeue while Paused
+    appsrc.set_property("block", false);
+    while appsrc.emit_by_name::<bool>("push-buffer", &[&gst::Buffer::new()]) {}
+    appsrc.set_property("block", true);
+    let pusher = std::thread::spawn({
+        let appsrc = appsrc.clone();
+        move || appsrc.emit_by_name::<bool>("push-buffer", &[&gst::Buffer::new()])
+    });
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    h.element().unwrap().set_state(gst::State::Ready).unwrap();
+    assert!(!pusher.join().unwrap());
+
+    // Blocking from a Context thread is refused
+    #[cfg(not(feature = "context-assertions"))]
+    {
+        let context = gstthreadshare::runtime::Context::acquire(
+            "appsrc-block-application",
+            std::time::Duration::ZERO,
+        )
+        .unwrap();
+        let pushed = futures::executor::block_on(context.spawn(async move {
+            appsrc.emit_by_name::<bool>("push-buffer", &[&gst::Buffer::new()])
+        }))
+        .unwrap();
+        assert!(!pushed);
+    }
+}
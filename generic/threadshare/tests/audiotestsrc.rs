@@ -0,0 +1,61 @@
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use gst::prelude::*;
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstthreadshare::plugin_register_static().expect("gstthreadshare audiotestsrc test");
+    });
+}
+
+#[test]
+fn test_f32_silence() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-audiotestsrc");
+    let audiotestsrc = h.element().unwrap();
+    audiotestsrc.set_property("context", "audiotestsrc::test");
+    audiotestsrc.set_property("samples-per-buffer", 100u32);
+    audiotestsrc.set_property_from_str("wave", "silence");
+    audiotestsrc.set_property("num-buffers", 2i32);
+
+    h.set_sink_caps(
+        gst_audio::AudioCapsBuilder::new_interleaved()
+            .format(gst_audio::AUDIO_FORMAT_F32)
+            .rate(8_000)
+            .channels(2)
+            .build(),
+    );
+    h.play();
+
+    for offset in [0, 100] {
+        let buffer = h.pull().unwrap();
+        assert_eq!(buffer.size(), 100 * 2 * std::mem::size_of::<f32>());
+        assert_eq!(buffer.offset(), offset);
+        assert_eq!(buffer.offset_end(), offset + 100);
+        assert!(buffer.map_readable().unwrap().iter().all(|byte| *byte == 0));
+    }
+
+    let caps = h.sinkpad().unwrap().current_caps().unwrap();
+    let info = gst_audio::AudioInfo::from_caps(&caps).unwrap();
+    assert_eq!(info.format(), gst_audio::AUDIO_FORMAT_F32);
+}
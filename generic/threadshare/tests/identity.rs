@@ -0,0 +1,203 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use gst::prelude::*;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstthreadshare::plugin_register_static().expect("gstthreadshare identity test");
+    });
+}
+
+fn count_signal(element: &gst::Element, signal: &str) -> Arc<AtomicU32> {
+    let count = Arc::new(AtomicU32::new(0));
+    element.connect(signal, false, {
+        let count = count.clone();
+        move |_| {
+            count.fetch_add(1, Ordering::SeqCst);
+            None
+        }
+    });
+
+    count
+}
+
+#[test]
+fn test_latency() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-identity");
+    let identity = h.element().unwrap();
+    identity.set_property("latency", 50 * gst::ClockTime::MSECOND.nseconds());
+    let handoffs = count_signal(&identity, "handoff");
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+    buffer
+        .get_mut()
+        .unwrap()
+        .set_pts(gst::ClockTime::from_seconds(1));
+
+    let start = Instant::now();
+    assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+    assert!(start.elapsed() >= Duration::from_millis(40));
+
+    // Timestamps are untouched
+    let buffer = h.pull().unwrap();
+    assert_eq!(buffer.pts(), Some(gst::ClockTime::from_seconds(1)));
+    assert_eq!(handoffs.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_drop_probability() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-identity");
+    let identity = h.element().unwrap();
+    identity.set_property("drop-probability", 1.0f32);
+    let handoffs = count_signal(&identity, "handoff");
+    let drops = count_signal(&identity, "drop");
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    for _ in 0..3 {
+        assert_eq!(
+            h.push(gst::Buffer::from_slice([0u8; 4])),
+            Ok(gst::FlowSuccess::Ok)
+        );
+    }
+
+    assert!(h.try_pull().is_none());
+    assert_eq!(drops.load(Ordering::SeqCst), 3);
+    assert_eq!(handoffs.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn test_error_after() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-identity");
+    h.element().unwrap().set_property("error-after", 2i32);
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([0u8; 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([0u8; 4])),
+        Err(gst::FlowError::Error)
+    );
+}
+
+#[test]
+fn test_drop_list() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-identity");
+    let identity = h.element().unwrap();
+    identity.set_property("drop-probability", 1.0f32);
+    let drops = count_signal(&identity, "drop");
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    let mut list = gst::BufferList::new();
+    {
+        let list = list.get_mut().unwrap();
+        for _ in 0..3 {
+            list.add(gst::Buffer::from_slice([0u8; 4]));
+        }
+    }
+    assert_eq!(
+        h.srcpad().unwrap().push_list(list),
+        Ok(gst::FlowSuccess::Ok)
+    );
+
+    assert!(h.try_pull().is_none());
+    // One signal per dropped buffer
+    assert_eq!(drops.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_latency_overlap() {
+    init();
+
+    let pipeline = gst::Pipeline::default();
+    let appsrc = gst::ElementFactory::make("ts-appsrc")
+        .property("context", "test-identity-latency-overlap")
+        .property("caps", gst::Caps::builder("foo/bar").build())
+        .build()
+        .unwrap();
+    let identity = gst::ElementFactory::make("ts-identity")
+        .property("latency", 100 * gst::ClockTime::MSECOND.nseconds())
+        .build()
+        .unwrap();
+    let appsink = gst::ElementFactory::make("ts-appsink").build().unwrap();
+
+    pipeline.add_many([&appsrc, &identity, &appsink]).unwrap();
+    gst::Element::link_many([&appsrc, &identity, &appsink]).unwrap();
+
+    let handoffs = count_signal(&identity, "handoff");
+    let new_samples = count_signal(&appsink, "new-sample");
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+
+    let start = Instant::now();
+    for idx in 0..5u8 {
+        let buffer = gst::Buffer::from_slice(vec![idx; 4]);
+        assert!(appsrc.emit_by_name::<bool>("push-buffer", &[&buffer]));
+    }
+    assert!(appsrc.emit_by_name::<bool>("end-of-stream", &[]));
+
+    // The EOS is delivered after the delayed buffers
+    let bus = pipeline.bus().unwrap();
+    bus.timed_pop_filtered(gst::ClockTime::from_seconds(5), &[gst::MessageType::Eos])
+        .unwrap();
+    assert_eq!(new_samples.load(Ordering::SeqCst), 5);
+    assert_eq!(handoffs.load(Ordering::SeqCst), 5);
+
+    // Each buffer is delayed from its own arrival, not after the previous one
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(90), "{elapsed:?}");
+    assert!(elapsed < Duration::from_millis(400), "{elapsed:?}");
+
+    for idx in 0..5u8 {
+        let sample = appsink
+            .emit_by_name::<Option<gst::Sample>>("pull-sample", &[])
+            .unwrap();
+        let map = sample.buffer().unwrap().map_readable().unwrap();
+        assert_eq!(map.as_slice(), &[idx; 4]);
+    }
+
+    pipeline.set_state(gst::State::Null).unwrap();
+}
@@ -93,3 +93,516 @@ fn test_active_pad() {
 
     let _ = is.set_state(gst::State::Null);
 }
+
+#[test]
+fn test_switch() {
+    init();
+
+    let is = gst::ElementFactory::make("ts-input-selector")
+        .build()
+        .unwrap();
+
+    let mut h1 = gst_check::Harness::with_element(&is, Some("sink_%u"), Some("src"));
+    let mut h2 = gst_check::Harness::with_element(&is, Some("sink_%u"), None);
+
+    h1.set_src_caps_str("foo/bar");
+    h2.set_src_caps_str("foo/bar");
+
+    h1.play();
+
+    let push = |h: &mut gst_check::Harness, pts_ms: u64| {
+        let mut buf = gst::Buffer::new();
+        buf.get_mut()
+            .unwrap()
+            .set_pts(gst::ClockTime::from_mseconds(pts_ms));
+        assert_eq!(h.push(buf), Ok(gst::FlowSuccess::Ok));
+    };
+
+    push(&mut h1, 0);
+    push(&mut h1, 10);
+    assert_eq!(h1.buffers_received(), 2);
+
+    /* Switch to the second pad once the first one reaches 30ms, starting at 40ms */
+    let pad2 = h2.srcpad().unwrap().peer().unwrap();
+    is.emit_by_name::<()>(
+        "switch",
+        &[
+            &pad2,
+            &gst::ClockTime::from_mseconds(30).nseconds(),
+            &gst::ClockTime::from_mseconds(40).nseconds(),
+        ],
+    );
+    assert_ne!(
+        is.property::<Option<gst::Pad>>("active-pad"),
+        Some(pad2.clone())
+    );
+
+    push(&mut h1, 20);
+    assert_eq!(h1.buffers_received(), 3);
+
+    push(&mut h1, 30);
+    assert_eq!(h1.buffers_received(), 3);
+    assert_eq!(is.property::<Option<gst::Pad>>("active-pad"), Some(pad2));
+
+    /* Buffers of the new pad before the start running time are dropped */
+    push(&mut h2, 30);
+    assert_eq!(h1.buffers_received(), 3);
+    push(&mut h2, 40);
+    assert_eq!(h1.buffers_received(), 4);
+    assert_eq!(
+        h1.pull().unwrap().pts(),
+        Some(gst::ClockTime::from_mseconds(0))
+    );
+
+    /* The forwarded segment starts at the switch */
+    let mut segment = None;
+    while let Some(event) = h1.try_pull_event() {
+        if let gst::EventView::Segment(e) = event.view() {
+            segment = Some(e.segment().clone());
+        }
+    }
+    let segment = segment.unwrap().downcast::<gst::format::Time>().unwrap();
+    assert_eq!(segment.start(), Some(gst::ClockTime::from_mseconds(40)));
+
+    /* Switch back right away, running time must not go backwards */
+    let pad1 = h1.srcpad().unwrap().peer().unwrap();
+    is.emit_by_name::<()>("switch", &[&pad1, &u64::MAX, &u64::MAX]);
+    assert_eq!(is.property::<Option<gst::Pad>>("active-pad"), Some(pad1));
+
+    push(&mut h1, 35);
+    assert_eq!(h1.buffers_received(), 4);
+    push(&mut h1, 50);
+    assert_eq!(h1.buffers_received(), 5);
+
+    let _ = is.set_state(gst::State::Null);
+}
+
+#[test]
+fn test_release_pad() {
+    init();
+
+    let is = gst::ElementFactory::make("ts-input-selector")
+        .build()
+        .unwrap();
+
+    let mut h = gst_check::Harness::with_element(&is, None, Some("src"));
+    let pad1 = is.request_pad_simple("sink_%u").unwrap();
+    let mut h2 = gst_check::Harness::with_element(&is, Some("sink_%u"), None);
+    let pad3 = is.request_pad_simple("sink_%u").unwrap();
+    let pad2 = h2.srcpad().unwrap().peer().unwrap();
+
+    h2.set_src_caps_str("foo/bar");
+
+    h.play();
+
+    assert!(pad1.send_event(gst::event::StreamStart::new("test")));
+    assert!(pad1.send_event(gst::event::Caps::new(
+        &gst::Caps::builder("foo/bar").build()
+    )));
+    let segment = gst::FormattedSegment::<gst::format::Time>::new();
+    assert!(pad1.send_event(gst::event::Segment::new(&segment)));
+
+    let buffer = |pts_ms: u64| {
+        let mut buf = gst::Buffer::new();
+        buf.get_mut()
+            .unwrap()
+            .set_pts(gst::ClockTime::from_mseconds(pts_ms));
+        buf
+    };
+
+    assert_eq!(pad1.chain(buffer(0)), Ok(gst::FlowSuccess::Ok));
+    assert_eq!(h.buffers_received(), 1);
+
+    /* Releasing the target of a pending switch cancels it */
+    is.emit_by_name::<()>(
+        "switch",
+        &[
+            &pad3,
+            &gst::ClockTime::from_mseconds(10).nseconds(),
+            &u64::MAX,
+        ],
+    );
+    is.release_request_pad(&pad3);
+
+    assert_eq!(pad1.chain(buffer(10)), Ok(gst::FlowSuccess::Ok));
+    assert_eq!(h.buffers_received(), 2);
+    assert_eq!(
+        is.property::<Option<gst::Pad>>("active-pad"),
+        Some(pad1.clone())
+    );
+
+    /* Releasing the active pad leaves no pad active */
+    is.emit_by_name::<()>(
+        "switch",
+        &[
+            &pad2,
+            &gst::ClockTime::from_mseconds(30).nseconds(),
+            &u64::MAX,
+        ],
+    );
+    is.release_request_pad(&pad1);
+    assert_eq!(is.property::<Option<gst::Pad>>("active-pad"), None);
+
+    assert_eq!(h2.push(buffer(30)), Ok(gst::FlowSuccess::Ok));
+    assert_eq!(h.buffers_received(), 2);
+
+    let _ = is.set_state(gst::State::Null);
+}
+
+fn check_sync_streams(sync_streams: bool, sync_mode: &str) {
+    init();
+
+    let is = gst::ElementFactory::make("ts-input-selector")
+        .property("sync-streams", sync_streams)
+        .property_from_str("sync-mode", sync_mode)
+        .build()
+        .unwrap();
+
+    let mut h1 = gst_check::Harness::with_element(&is, Some("sink_%u"), Some("src"));
+    let mut h2 = gst_check::Harness::with_element(&is, Some("sink_%u"), None);
+
+    h1.set_src_caps_str("foo/bar");
+    h2.set_src_caps_str("foo/bar");
+
+    h1.play();
+
+    for pts_ms in [0, 10, 20] {
+        for h in [&mut h1, &mut h2] {
+            let mut buf = gst::Buffer::new();
+            buf.get_mut()
+                .unwrap()
+                .set_pts(gst::ClockTime::from_mseconds(pts_ms));
+            assert_eq!(h.push(buf), Ok(gst::FlowSuccess::Ok));
+        }
+    }
+
+    /* The inactive pad kept consuming buffers but they were discarded */
+    assert_eq!(h1.buffers_received(), 3);
+
+    let _ = is.set_state(gst::State::Null);
+}
+
+#[test]
+fn test_sync_streams_active_segment() {
+    check_sync_streams(true, "active-segment");
+}
+
+#[test]
+fn test_sync_streams_clock() {
+    check_sync_streams(true, "clock");
+}
+
+#[test]
+fn test_no_sync_streams() {
+    check_sync_streams(false, "active-segment");
+}
+
+#[test]
+fn test_always_ok() {
+    init();
+
+    let is = gst::ElementFactory::make("ts-input-selector")
+        .build()
+        .unwrap();
+
+    let mut h1 = gst_check::Harness::with_element(&is, Some("sink_%u"), Some("src"));
+    let mut h2 = gst_check::Harness::with_element(&is, Some("sink_%u"), None);
+
+    h1.set_src_caps_str("foo/bar");
+    h2.set_src_caps_str("foo/bar");
+
+    h1.play();
+
+    let pad2 = h2.srcpad().unwrap().peer().unwrap();
+    assert!(pad2.property::<bool>("always-ok"));
+
+    /* Inactive pads return OK by default */
+    assert_eq!(h2.push(gst::Buffer::new()), Ok(gst::FlowSuccess::Ok));
+
+    pad2.set_property("always-ok", false);
+    assert_eq!(h2.push(gst::Buffer::new()), Err(gst::FlowError::NotLinked));
+
+    /* The active pad returns the downstream flow return */
+    let pad1 = h1.srcpad().unwrap().peer().unwrap();
+    pad1.set_property("always-ok", false);
+    assert_eq!(h1.push(gst::Buffer::new()), Ok(gst::FlowSuccess::Ok));
+    assert_eq!(h1.buffers_received(), 1);
+
+    let _ = is.set_state(gst::State::Null);
+}
+
+#[test]
+fn test_cache_buffers() {
+    init();
+
+    let is = gst::ElementFactory::make("ts-input-selector")
+        .property("cache-buffers", true)
+        .property("sync-streams", false)
+        .build()
+        .unwrap();
+
+    let mut h1 = gst_check::Harness::with_element(&is, Some("sink_%u"), Some("src"));
+    let mut h2 = gst_check::Harness::with_element(&is, Some("sink_%u"), None);
+
+    h1.set_src_caps_str("video/x-h264");
+    h2.set_src_caps_str("video/x-h264");
+
+    h1.play();
+
+    let push = |h: &mut gst_check::Harness, pts_ms: u64, keyframe: bool| {
+        let mut buf = gst::Buffer::new();
+        {
+            let buf = buf.get_mut().unwrap();
+            buf.set_pts(gst::ClockTime::from_mseconds(pts_ms));
+            if !keyframe {
+                buf.set_flags(gst::BufferFlags::DELTA_UNIT);
+            }
+        }
+        assert_eq!(h.push(buf), Ok(gst::FlowSuccess::Ok));
+    };
+
+    /* Only the GOP of the last keyframe is cached on the inactive pad */
+    push(&mut h2, 0, false);
+    push(&mut h2, 10, true);
+    push(&mut h2, 20, false);
+    push(&mut h2, 30, false);
+    assert_eq!(h1.buffers_received(), 0);
+
+    /* Switch mid-GOP: the cached buffers are forwarded first */
+    is.set_property("active-pad", h2.srcpad().unwrap().peer());
+    push(&mut h2, 40, false);
+    assert_eq!(h1.buffers_received(), 4);
+
+    let buf = h1.pull().unwrap();
+    assert_eq!(buf.pts(), Some(gst::ClockTime::from_mseconds(10)));
+    assert!(!buf.flags().contains(gst::BufferFlags::DELTA_UNIT));
+    assert!(buf.flags().contains(gst::BufferFlags::DISCONT));
+    for pts_ms in [20, 30, 40] {
+        let buf = h1.pull().unwrap();
+        assert_eq!(buf.pts(), Some(gst::ClockTime::from_mseconds(pts_ms)));
+        assert!(!buf.flags().contains(gst::BufferFlags::DISCONT));
+    }
+
+    /* A GOP exceeding the maximum duration is not cached */
+    is.set_property("cache-buffers-max-duration", 15_000_000u64);
+    push(&mut h1, 50, true);
+    push(&mut h1, 60, false);
+    push(&mut h1, 70, false);
+    is.set_property("active-pad", h1.srcpad().unwrap().peer());
+    push(&mut h1, 80, false);
+    assert_eq!(h1.buffers_received(), 5);
+    assert_eq!(
+        h1.pull().unwrap().pts(),
+        Some(gst::ClockTime::from_mseconds(80))
+    );
+
+    let _ = is.set_state(gst::State::Null);
+}
+
+#[test]
+fn test_pad_switched() {
+    use std::sync::{Arc, Mutex};
+
+    init();
+
+    let is = gst::ElementFactory::make("ts-input-selector")
+        .build()
+        .unwrap();
+    let bus = gst::Bus::new();
+    is.set_bus(Some(&bus));
+
+    let switches = Arc::new(Mutex::new(Vec::new()));
+    is.connect("pad-switched", false, {
+        let switches = switches.clone();
+        move |args| {
+            let old_pad = args[1].get::<Option<gst::Pad>>().unwrap();
+            let new_pad = args[2].get::<gst::Pad>().unwrap();
+            let running_time = args[3].get::<Option<gst::ClockTime>>().unwrap();
+            switches
+                .lock()
+                .unwrap()
+                .push((old_pad, new_pad, running_time));
+            None
+        }
+    });
+
+    let mut h1 = gst_check::Harness::with_element(&is, Some("sink_%u"), Some("src"));
+    let mut h2 = gst_check::Harness::with_element(&is, Some("sink_%u"), None);
+
+    h1.set_src_caps_str("foo/bar");
+    h2.set_src_caps_str("foo/bar");
+
+    h1.play();
+
+    let push = |h: &mut gst_check::Harness, pts_ms: u64| {
+        let mut buf = gst::Buffer::new();
+        buf.get_mut()
+            .unwrap()
+            .set_pts(gst::ClockTime::from_mseconds(pts_ms));
+        assert_eq!(h.push(buf), Ok(gst::FlowSuccess::Ok));
+    };
+
+    push(&mut h1, 0);
+
+    /* Nothing is notified until a buffer of the new pad is forwarded */
+    let pad1 = h1.srcpad().unwrap().peer().unwrap();
+    let pad2 = h2.srcpad().unwrap().peer().unwrap();
+    is.set_property("active-pad", &pad2);
+    assert!(switches.lock().unwrap().is_empty());
+
+    push(&mut h2, 10);
+    assert_eq!(
+        *switches.lock().unwrap(),
+        [(
+            Some(pad1.clone()),
+            pad2.clone(),
+            Some(gst::ClockTime::from_mseconds(10))
+        )]
+    );
+
+    let msg = bus
+        .iter_filtered(&[gst::MessageType::Element])
+        .find(|msg| msg.structure().unwrap().name() == "ts-input-selector-pad-switched")
+        .unwrap();
+    let s = msg.structure().unwrap();
+    assert_eq!(s.get::<Option<gst::Pad>>("old-pad").unwrap(), Some(pad1));
+    assert_eq!(s.get::<gst::Pad>("new-pad").unwrap(), pad2);
+    assert_eq!(
+        s.get::<Option<gst::ClockTime>>("running-time").unwrap(),
+        Some(gst::ClockTime::from_mseconds(10))
+    );
+
+    let _ = is.set_state(gst::State::Null);
+}
+
+#[test]
+fn test_switch_caps() {
+    init();
+
+    let is = gst::ElementFactory::make("ts-input-selector")
+        .build()
+        .unwrap();
+
+    let mut h1 = gst_check::Harness::with_element(&is, Some("sink_%u"), Some("src"));
+    let mut h2 = gst_check::Harness::with_element(&is, Some("sink_%u"), None);
+
+    h1.set_src_caps_str("video/x-raw,format=I420,width=320,height=240");
+    h2.set_src_caps_str("video/x-raw,format=I420,width=640,height=480");
+
+    h1.play();
+
+    let current_width = |h: &gst_check::Harness| {
+        h.sinkpad()
+            .unwrap()
+            .current_caps()
+            .unwrap()
+            .structure(0)
+            .unwrap()
+            .get::<i32>("width")
+            .unwrap()
+    };
+
+    assert_eq!(h1.push(gst::Buffer::new()), Ok(gst::FlowSuccess::Ok));
+    assert_eq!(current_width(&h1), 320);
+
+    /* The inactive branch doesn't affect the downstream caps */
+    assert_eq!(h2.push(gst::Buffer::new()), Ok(gst::FlowSuccess::Ok));
+    assert_eq!(current_width(&h1), 320);
+
+    /* Caps only change with the first buffer of the new active pad */
+    is.set_property("active-pad", h2.srcpad().unwrap().peer());
+    assert_eq!(current_width(&h1), 320);
+    assert_eq!(h2.push(gst::Buffer::new()), Ok(gst::FlowSuccess::Ok));
+    assert_eq!(current_width(&h1), 640);
+
+    let mut events = vec![];
+    while let Some(event) = h1.try_pull_event() {
+        events.push(event.type_());
+    }
+    assert_eq!(
+        events,
+        [
+            gst::EventType::StreamStart,
+            gst::EventType::Caps,
+            gst::EventType::Segment,
+            gst::EventType::StreamStart,
+            gst::EventType::Caps,
+            gst::EventType::Segment,
+        ]
+    );
+
+    /* Switching back restores the caps of the first branch */
+    is.set_property("active-pad", h1.srcpad().unwrap().peer());
+    assert_eq!(h1.push(gst::Buffer::new()), Ok(gst::FlowSuccess::Ok));
+    assert_eq!(current_width(&h1), 320);
+
+    let _ = is.set_state(gst::State::Null);
+}
+
+#[test]
+fn test_running_time_offset() {
+    init();
+
+    let is = gst::ElementFactory::make("ts-input-selector")
+        .property("sync-streams", false)
+        .build()
+        .unwrap();
+
+    let mut h1 = gst_check::Harness::with_element(&is, Some("sink_%u"), Some("src"));
+    let mut h2 = gst_check::Harness::with_element(&is, Some("sink_%u"), None);
+
+    h1.set_src_caps_str("foo/bar");
+    h2.set_src_caps_str("foo/bar");
+
+    h1.play();
+
+    let push = |h: &mut gst_check::Harness, pts_ms: u64| {
+        let mut buf = gst::Buffer::new();
+        {
+            let buf = buf.get_mut().unwrap();
+            buf.set_pts(gst::ClockTime::from_mseconds(pts_ms));
+            buf.set_duration(gst::ClockTime::from_mseconds(10));
+        }
+        assert_eq!(h.push(buf), Ok(gst::FlowSuccess::Ok));
+    };
+
+    /* The second branch has a segment starting later */
+    let mut segment = gst::FormattedSegment::<gst::ClockTime>::new();
+    segment.set_start(gst::ClockTime::from_mseconds(100));
+    assert!(h2.push_event(gst::event::Segment::new(&segment)));
+
+    for pts_ms in [0, 10, 20] {
+        push(&mut h1, pts_ms);
+    }
+    assert_eq!(is.property::<u64>("running-time-offset"), 0);
+
+    /* The new branch is shifted after the last pushed buffer */
+    is.set_property("active-pad", h2.srcpad().unwrap().peer());
+    push(&mut h2, 100);
+    assert_eq!(h1.buffers_received(), 4);
+    assert_eq!(
+        is.property::<u64>("running-time-offset"),
+        gst::ClockTime::from_mseconds(30).nseconds()
+    );
+
+    let mut segment = None;
+    while let Some(event) = h1.try_pull_event() {
+        if let gst::EventView::Segment(e) = event.view() {
+            segment = Some(e.segment().clone());
+        }
+    }
+    let segment = segment.unwrap().downcast::<gst::format::Time>().unwrap();
+    assert_eq!(segment.base(), Some(gst::ClockTime::from_mseconds(30)));
+    assert_eq!(
+        segment.to_running_time(gst::ClockTime::from_mseconds(100)),
+        Some(gst::ClockTime::from_mseconds(30))
+    );
+
+    /* A branch ahead of the output doesn't need any offset */
+    push(&mut h1, 50);
+    is.set_property("active-pad", h1.srcpad().unwrap().peer());
+    push(&mut h1, 60);
+    assert_eq!(is.property::<u64>("running-time-offset"), 0);
+
+    let _ = is.set_state(gst::State::Null);
+}
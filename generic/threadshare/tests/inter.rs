@@ -0,0 +1,121 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use gst::prelude::*;
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstthreadshare::plugin_register_static().expect("gstthreadshare inter test");
+    });
+}
+
+fn producer(channel: &str, policy: &str) -> gst_check::Harness {
+    let mut h = gst_check::Harness::new("ts-intersink");
+    let intersink = h.element().unwrap();
+    intersink.set_property("channel", channel);
+    intersink.set_property_from_str("no-consumer-policy", policy);
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    h
+}
+
+fn consumer(channel: &str) -> gst_check::Harness {
+    let mut h = gst_check::Harness::new("ts-intersrc");
+    let intersrc = h.element().unwrap();
+    intersrc.set_property("channel", channel);
+    intersrc.set_property("context", "inter::test");
+
+    h.play();
+
+    h
+}
+
+fn push_buffer(h: &mut gst_check::Harness, secs: u64) {
+    let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+    buffer
+        .get_mut()
+        .unwrap()
+        .set_pts(gst::ClockTime::from_seconds(secs));
+
+    assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+}
+
+fn assert_initial_events(h: &mut gst_check::Harness) {
+    let event = h.pull_event().unwrap();
+    assert_eq!(event.type_(), gst::EventType::StreamStart);
+
+    let event = h.pull_event().unwrap();
+    match event.view() {
+        gst::EventView::Caps(ev) => {
+            assert_eq!(ev.caps(), &gst::Caps::builder("foo/bar").build());
+        }
+        _ => panic!("Expected caps, got {event:?}"),
+    }
+
+    let event = h.pull_event().unwrap();
+    assert_eq!(event.type_(), gst::EventType::Segment);
+}
+
+#[test]
+fn test_keep_before_consumer() {
+    init();
+
+    let mut producer = producer("inter::test_keep", "keep");
+    for secs in 0..3 {
+        push_buffer(&mut producer, secs);
+    }
+
+    let mut consumer = consumer("inter::test_keep");
+    assert_initial_events(&mut consumer);
+
+    for secs in 0..3 {
+        let buffer = consumer.pull().unwrap();
+        assert_eq!(buffer.pts(), Some(gst::ClockTime::from_seconds(secs)));
+    }
+}
+
+#[test]
+fn test_restart_consumer() {
+    init();
+
+    let mut producer = producer("inter::test_restart", "drop");
+
+    let mut consumer_1 = consumer("inter::test_restart");
+    push_buffer(&mut producer, 0);
+    assert_initial_events(&mut consumer_1);
+    let buffer = consumer_1.pull().unwrap();
+    assert_eq!(buffer.pts(), Some(gst::ClockTime::ZERO));
+    drop(consumer_1);
+
+    // No consumer: dropped
+    push_buffer(&mut producer, 1);
+
+    // Caps were sent before this consumer attached, they must be re-sent
+    let mut consumer_2 = consumer("inter::test_restart");
+    push_buffer(&mut producer, 2);
+    assert_initial_events(&mut consumer_2);
+    let buffer = consumer_2.pull().unwrap();
+    assert_eq!(buffer.pts(), Some(gst::ClockTime::from_seconds(2)));
+}
@@ -20,6 +20,7 @@
 use gst::prelude::*;
 
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
 
 use std::sync::LazyLock;
 
@@ -220,3 +221,785 @@ fn jb_ts_pipeline() {
 
     pipeline.set_state(gst::State::Null).unwrap();
 }
+
+const PACKET_DURATION: gst::ClockTime = gst::ClockTime::from_mseconds(20);
+
+fn rtp_buffer(seq: u16) -> gst::Buffer {
+    rtp_buffer_with_pt(seq, 0, 160 * seq as u32)
+}
+
+fn rtp_buffer_with_pt(seq: u16, pt: u8, rtptime: u32) -> gst::Buffer {
+    let mut buffer = gst::Buffer::new_rtp_with_sizes(160, 0, 0).unwrap();
+    {
+        let buffer = buffer.get_mut().unwrap();
+        buffer.set_pts(PACKET_DURATION * seq as u64);
+
+        let mut rtp_buffer = gst_rtp::RTPBuffer::from_buffer_writable(buffer).unwrap();
+        rtp_buffer.set_seq(seq);
+        rtp_buffer.set_timestamp(rtptime);
+        rtp_buffer.set_payload_type(pt);
+    }
+
+    buffer
+}
+
+#[test]
+fn jb_retransmission_request() {
+    init();
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_retransmission_request")
+        .property("latency", 1000u32)
+        .property("do-retransmission", true)
+        .property("rtx-delay", 10i32)
+        .build()
+        .unwrap();
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    // #2 is missing
+    for seq in [0, 1, 3, 4] {
+        h.push(rtp_buffer(seq)).unwrap();
+    }
+
+    let request = loop {
+        let event = h.pull_upstream_event().unwrap();
+        if let Some(s) = event.structure() {
+            if s.name() == "GstRTPRetransmissionRequest" {
+                break s.to_owned();
+            }
+        }
+    };
+
+    assert_eq!(request.get::<u32>("seqnum").unwrap(), 2);
+    assert_eq!(request.get::<u32>("retry").unwrap(), 0);
+    assert_eq!(request.get::<u32>("deadline").unwrap(), 1000);
+
+    // The retransmitted packet is recovered, the duplicate is dropped
+    h.push(rtp_buffer(2)).unwrap();
+    h.push(rtp_buffer(2)).unwrap();
+
+    let stats = jb.property::<gst::Structure>("stats");
+    assert!(stats.get::<u64>("rtx-count").unwrap() >= 1);
+    assert_eq!(stats.get::<u64>("rtx-success-count").unwrap(), 1);
+    assert_eq!(stats.get::<u64>("num-duplicates").unwrap(), 1);
+
+    for seq in 0..5 {
+        let buffer = h.pull().unwrap();
+        let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+        assert_eq!(rtp_buffer.seq(), seq);
+    }
+}
+
+#[test]
+fn jb_retransmission_large_gap() {
+    init();
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_retransmission_large_gap")
+        .property("latency", 200u32)
+        .property("do-retransmission", true)
+        .property("rtx-delay", 10i32)
+        .build()
+        .unwrap();
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    // #11..=#109 are missing
+    for seq in (0..=10).chain([110]) {
+        h.push(rtp_buffer(seq)).unwrap();
+    }
+
+    // At 50 packets/s, only the 30 packets preceding #110 can be received
+    // within the latency and are requested.
+    let mut requested = std::collections::BTreeSet::new();
+    while !requested.contains(&109) {
+        let event = h.pull_upstream_event().unwrap();
+        if let Some(s) = event.structure() {
+            if s.name() == "GstRTPRetransmissionRequest" {
+                requested.insert(s.get::<u32>("seqnum").unwrap());
+            }
+        }
+    }
+
+    assert_eq!(requested, (80..110).collect());
+}
+
+#[test]
+fn jb_drop_on_latency() {
+    init();
+
+    const PACKETS: u16 = 20;
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_drop_on_latency")
+        .property("latency", 100u32)
+        .property("drop-on-latency", true)
+        .build()
+        .unwrap();
+
+    let bus = gst::Bus::new();
+    jb.set_bus(Some(&bus));
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    for seq in 0..PACKETS {
+        h.push(rtp_buffer(seq)).unwrap();
+    }
+
+    let stats = jb.property::<gst::Structure>("stats");
+    let num_dropped = stats.get::<u64>("num-dropped").unwrap();
+    assert!(num_dropped > 0);
+
+    let msg = bus
+        .timed_pop_filtered(
+            gst::ClockTime::from_seconds(5),
+            &[gst::MessageType::Warning],
+        )
+        .unwrap();
+    assert_eq!(msg.src(), Some(jb.upcast_ref()));
+
+    // The oldest packets were dropped, the remaining ones are pushed in order
+    let buffer = h.pull().unwrap();
+    assert!(buffer.flags().contains(gst::BufferFlags::DISCONT));
+    let first_seq = gst_rtp::RTPBuffer::from_buffer_readable(&buffer)
+        .unwrap()
+        .seq();
+    assert!(first_seq > 0);
+
+    for seq in first_seq + 1..PACKETS {
+        let buffer = h.pull().unwrap();
+        let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+        assert_eq!(rtp_buffer.seq(), seq);
+    }
+
+    jb.set_bus(None);
+}
+
+#[test]
+fn jb_stats() {
+    init();
+
+    const PACKETS: u16 = 5;
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_stats")
+        .property("latency", 20u32)
+        .build()
+        .unwrap();
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    for seq in 0..PACKETS {
+        h.push(rtp_buffer(seq)).unwrap();
+    }
+
+    for _ in 0..PACKETS {
+        let _ = h.pull().unwrap();
+    }
+
+    // Same structure as rtpjitterbuffer's, readable from any thread
+    let stats = std::thread::spawn(move || jb.property::<gst::Structure>("stats"))
+        .join()
+        .unwrap();
+
+    assert_eq!(stats.name(), "application/x-rtp-jitterbuffer-stats");
+    assert_eq!(stats.get::<u64>("num-pushed").unwrap(), PACKETS as u64);
+    assert_eq!(stats.get::<u64>("num-lost").unwrap(), 0);
+    assert_eq!(stats.get::<u64>("num-late").unwrap(), 0);
+    assert_eq!(stats.get::<u64>("num-duplicates").unwrap(), 0);
+    stats.get::<u64>("avg-jitter").unwrap();
+    assert_eq!(stats.get::<u64>("rtx-count").unwrap(), 0);
+    assert_eq!(stats.get::<u64>("rtx-success-count").unwrap(), 0);
+    assert_eq!(stats.get::<f64>("rtx-per-packet").unwrap(), 0.0);
+    assert_eq!(stats.get::<u64>("rtx-rtt").unwrap(), 0);
+}
+
+#[test]
+fn jb_lost_events() {
+    init();
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_lost_events")
+        .property("latency", 100u32)
+        .build()
+        .unwrap();
+
+    assert!(jb.property::<bool>("do-lost"));
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    // #2, #3 and #4 are missing
+    for seq in [0, 1, 5] {
+        h.push(rtp_buffer(seq)).unwrap();
+    }
+
+    for seq in [0, 1, 5] {
+        let buffer = h.pull().unwrap();
+        let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+        assert_eq!(rtp_buffer.seq(), seq);
+        if seq == 5 {
+            assert!(buffer.flags().contains(gst::BufferFlags::DISCONT));
+        }
+    }
+
+    let mut lost = vec![];
+    while let Some(event) = h.try_pull_event() {
+        let Some(s) = event.structure() else {
+            continue;
+        };
+        if s.name() != "GstRTPPacketLost" {
+            continue;
+        }
+
+        let seqnum = s.get::<u32>("seqnum").unwrap() as u16;
+        let duration = s.get::<u64>("duration").unwrap();
+        assert!(duration > 0);
+        lost.push(seqnum);
+    }
+
+    assert_eq!(lost.first(), Some(&2));
+    assert!(lost.windows(2).all(|w| w[0] < w[1]));
+
+    let stats = jb.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("num-lost").unwrap(), 3);
+}
+
+#[test]
+fn jb_request_pt_map() {
+    init();
+
+    const PACKETS: u16 = 12;
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_request_pt_map")
+        .property("latency", 20u32)
+        .build()
+        .unwrap();
+
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    jb.connect("request-pt-map", false, {
+        let requests = requests.clone();
+        move |args| {
+            let pt = args[1].get::<u32>().unwrap();
+            requests.lock().unwrap().push(pt);
+
+            let clock_rate = match pt {
+                0 => 8000,
+                96 => 16000,
+                _ => unreachable!(),
+            };
+
+            let caps = gst::Caps::builder("application/x-rtp")
+                .field("media", "audio")
+                .field("payload", pt as i32)
+                .field("clock-rate", clock_rate)
+                .build();
+
+            Some(caps.to_value())
+        }
+    });
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    // No clock-rate in the caps
+    h.set_src_caps_str("application/x-rtp,media=audio");
+    h.play();
+
+    // Switch from PT 0 to 96 and back, each with its own clock rate
+    for seq in 0..PACKETS {
+        let buffer = match seq / 4 {
+            1 => rtp_buffer_with_pt(seq, 96, 320 * seq as u32),
+            _ => rtp_buffer_with_pt(seq, 0, 160 * seq as u32),
+        };
+        h.push(buffer).unwrap();
+    }
+
+    let mut prev_pts = None;
+    for seq in 0..PACKETS {
+        let buffer = h.pull().unwrap();
+        let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+        assert_eq!(rtp_buffer.seq(), seq);
+
+        let pts = buffer.pts().unwrap();
+        if let Some(prev_pts) = prev_pts {
+            let spacing = pts.nseconds() as i64 - prev_pts.nseconds() as i64;
+            assert!(
+                (spacing - PACKET_DURATION.nseconds() as i64).abs() < 5_000_000,
+                "unexpected spacing {spacing} before #{seq}",
+            );
+        }
+        prev_pts = Some(pts);
+    }
+
+    // The clock rates are cached per PT
+    assert_eq!(*requests.lock().unwrap(), [0, 96]);
+
+    // Clearing the PT map triggers a new request
+    jb.emit_by_name::<()>("clear-pt-map", &[]);
+    h.push(rtp_buffer(PACKETS)).unwrap();
+    assert_eq!(*requests.lock().unwrap(), [0, 96, 0]);
+}
+
+#[test]
+fn jb_resync() {
+    init();
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_resync")
+        .property("latency", 20u32)
+        .build()
+        .unwrap();
+
+    let bus = gst::Bus::new();
+    jb.set_bus(Some(&bus));
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    // Seqnums jump forward after #4, then backward after #30005
+    let seqnums = (0..5).chain(30_000..30_006).chain(100..106);
+    for (idx, seq) in seqnums.enumerate() {
+        let mut buffer = rtp_buffer_with_pt(seq, 0, 160 * idx as u32);
+        buffer
+            .get_mut()
+            .unwrap()
+            .set_pts(PACKET_DURATION * idx as u64);
+        h.push(buffer).unwrap();
+    }
+
+    for (reason, last_seqnum, seqnum) in [("dropout", 4, 30_000), ("misorder", 30_005, 100)] {
+        let msg = bus
+            .timed_pop_filtered(
+                gst::ClockTime::from_seconds(5),
+                &[gst::MessageType::Element],
+            )
+            .unwrap();
+        let s = msg.structure().unwrap();
+        assert_eq!(s.name(), "ts-jitterbuffer-resync");
+        assert_eq!(s.get::<&str>("reason").unwrap(), reason);
+        assert_eq!(s.get::<u32>("last-seqnum").unwrap(), last_seqnum);
+        assert_eq!(s.get::<u32>("seqnum").unwrap(), seqnum);
+    }
+
+    // Nothing gets stuck after the resyncs
+    let mut seqnums = vec![];
+    while seqnums.last() != Some(&105) {
+        let buffer = h.pull().unwrap();
+        let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+        seqnums.push(rtp_buffer.seq());
+    }
+    assert!(seqnums.ends_with(&[100, 101, 102, 103, 104, 105]));
+
+    jb.set_bus(None);
+}
+
+fn rtcp_sr_buffer(ssrc: u32, ntptime: u64, rtptime: u32) -> gst::Buffer {
+    let mut data = vec![0x80, 200, 0x00, 0x06];
+    data.extend_from_slice(&ssrc.to_be_bytes());
+    data.extend_from_slice(&ntptime.to_be_bytes());
+    data.extend_from_slice(&rtptime.to_be_bytes());
+    // Packet & octet counts
+    data.extend_from_slice(&[0; 8]);
+
+    gst::Buffer::from_mut_slice(data)
+}
+
+#[test]
+fn jb_rtcp_sync() {
+    init();
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_rtcp_sync")
+        .property("latency", 20u32)
+        .build()
+        .unwrap();
+
+    let syncs = Arc::new(Mutex::new(Vec::new()));
+    jb.connect("handle-sync", false, {
+        let syncs = syncs.clone();
+        move |args| {
+            let s = args[1].get::<gst::Structure>().unwrap();
+            syncs.lock().unwrap().push(s);
+            None
+        }
+    });
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+
+    let mut h_rtcp = gst_check::Harness::with_element(&jb, Some("sink_rtcp"), None);
+    h_rtcp.set_src_caps_str("application/x-rtcp");
+
+    h.play();
+
+    // No RTP packets yet, the sender report is kept for later
+    h_rtcp.push(rtcp_sr_buffer(0, 0, 0)).unwrap();
+    assert!(syncs.lock().unwrap().is_empty());
+
+    for seq in 0..5 {
+        h.push(rtp_buffer(seq)).unwrap();
+    }
+
+    // Close to the last RTP time
+    h_rtcp.push(rtcp_sr_buffer(0, 0, 160 * 4)).unwrap();
+    // 2s ahead of the last RTP time
+    h_rtcp.push(rtcp_sr_buffer(0, 0, 160 * 4 + 16_000)).unwrap();
+    // Other SSRC
+    h_rtcp.push(rtcp_sr_buffer(1, 0, 160 * 4)).unwrap();
+
+    let syncs = syncs.lock().unwrap();
+    assert_eq!(syncs.len(), 3);
+
+    for s in syncs.iter() {
+        assert_eq!(s.name(), "application/x-rtp-sync");
+        assert_eq!(s.get::<u32>("clock-rate").unwrap(), 8000);
+        assert_eq!(s.get::<u64>("base-rtptime").unwrap() & 0xffff_ffff, 0);
+        assert!(s.get::<gst::Buffer>("sr-buffer").is_ok());
+    }
+
+    assert_eq!(
+        syncs[0].get::<u64>("sr-ext-rtptime").unwrap() & 0xffff_ffff,
+        0
+    );
+    assert_eq!(
+        syncs[1].get::<u64>("sr-ext-rtptime").unwrap() & 0xffff_ffff,
+        160 * 4
+    );
+    assert_eq!(syncs[2].get::<u64>("sr-ext-rtptime").unwrap(), u64::MAX);
+}
+
+#[test]
+fn jb_faststart() {
+    init();
+
+    let latency = gst::ClockTime::from_seconds(1);
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_faststart")
+        .property("latency", latency.mseconds() as u32)
+        .property("faststart-min-packets", 3u32)
+        .build()
+        .unwrap();
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    let start = std::time::Instant::now();
+
+    // Not enough consecutive packets yet
+    h.push(rtp_buffer(0)).unwrap();
+    h.push(rtp_buffer(2)).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(h.try_pull().is_none());
+
+    h.push(rtp_buffer(1)).unwrap();
+    for expected_seq in 0..3 {
+        let buffer = h.pull().unwrap();
+        let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+        assert_eq!(rtp_buffer.seq(), expected_seq);
+    }
+    assert!(start.elapsed() < std::time::Duration::from(latency / 2));
+
+    // Back to waiting for the latency
+    h.push(rtp_buffer(3)).unwrap();
+    let buffer = h.pull().unwrap();
+    let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+    assert_eq!(rtp_buffer.seq(), 3);
+    assert!(start.elapsed() >= std::time::Duration::from(latency / 2));
+}
+
+fn jb_mode_check(mode: &str, expect_wait: bool) {
+    init();
+
+    let latency = gst::ClockTime::from_mseconds(400);
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", format!("jb_mode_{mode}"))
+        .property("latency", latency.mseconds() as u32)
+        .property_from_str("mode", mode)
+        .build()
+        .unwrap();
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    // The first packet always waits for the latency
+    h.push(rtp_buffer(0)).unwrap();
+    let buffer = h.pull().unwrap();
+    let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+    assert_eq!(rtp_buffer.seq(), 0);
+
+    // Consistent RTP time and PTS, as if sent in real time
+    let now = jb.current_running_time().unwrap();
+    let mut buffer = rtp_buffer_with_pt(1, 0, (now.mseconds() * 8) as u32);
+    buffer.get_mut().unwrap().set_pts(now);
+
+    let start = std::time::Instant::now();
+    h.push(buffer).unwrap();
+    let buffer = h.pull().unwrap();
+    let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+    assert_eq!(rtp_buffer.seq(), 1);
+
+    let waited = start.elapsed() >= std::time::Duration::from(latency / 2);
+    assert_eq!(waited, expect_wait);
+}
+
+#[test]
+fn jb_mode_slave() {
+    jb_mode_check("slave", true);
+}
+
+#[test]
+fn jb_mode_none() {
+    jb_mode_check("none", false);
+}
+
+#[test]
+fn jb_reference_timestamp_meta() {
+    init();
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_reference_timestamp_meta")
+        .property("latency", 20u32)
+        .property("add-reference-timestamp-meta", true)
+        .build()
+        .unwrap();
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+
+    let mut h_rtcp = gst_check::Harness::with_element(&jb, Some("sink_rtcp"), None);
+    h_rtcp.set_src_caps_str("application/x-rtcp");
+
+    h.play();
+
+    // NTP time 1000s at RTP time 0
+    let sr_ntptime = gst::ClockTime::from_seconds(1000);
+    h_rtcp
+        .push(rtcp_sr_buffer(0, sr_ntptime.seconds() << 32, 0))
+        .unwrap();
+
+    for seq in 0..5 {
+        h.push(rtp_buffer(seq)).unwrap();
+    }
+
+    for seq in 0..5 {
+        let buffer = h.pull().unwrap();
+        let meta = buffer
+            .meta::<gst::ReferenceTimestampMeta>()
+            .expect("no reference timestamp meta");
+        assert_eq!(
+            meta.reference().structure(0).unwrap().name(),
+            "timestamp/x-ntp"
+        );
+        assert_eq!(meta.timestamp(), sr_ntptime + PACKET_DURATION * seq as u64);
+    }
+}
+
+#[test]
+fn jb_seqnum_wraparound() {
+    init();
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_seqnum_wraparound")
+        .property("latency", 20u32)
+        .build()
+        .unwrap();
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    // #65534 to #1 are lost around the wraparound
+    let seqnums = (0xfff0..=0xffffu16).chain(0..16);
+    let mut expected = vec![];
+    for (idx, seq) in seqnums.enumerate() {
+        if [0xfffe, 0xffff, 0, 1].contains(&seq) {
+            continue;
+        }
+
+        let mut buffer = rtp_buffer_with_pt(seq, 0, 160 * idx as u32);
+        buffer
+            .get_mut()
+            .unwrap()
+            .set_pts(PACKET_DURATION * idx as u64);
+        h.push(buffer).unwrap();
+        expected.push(seq);
+    }
+
+    let mut seqnums = vec![];
+    for _ in 0..expected.len() {
+        let buffer = h.pull().unwrap();
+        let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+        seqnums.push(rtp_buffer.seq());
+    }
+    assert_eq!(seqnums, expected);
+
+    let mut lost = vec![];
+    while let Some(event) = h.try_pull_event() {
+        let Some(s) = event.structure() else {
+            continue;
+        };
+        if s.name() == "GstRTPPacketLost" {
+            lost.push(s.get::<u32>("seqnum").unwrap() as u16);
+        }
+    }
+    assert_eq!(lost.first(), Some(&0xfffe));
+
+    let stats = jb.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("num-lost").unwrap(), 4);
+    assert_eq!(stats.get::<u64>("num-late").unwrap(), 0);
+}
+
+#[test]
+fn jb_sender_pause() {
+    init();
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_sender_pause")
+        .property("latency", 200u32)
+        .build()
+        .unwrap();
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    for seq in 0..5 {
+        h.push(rtp_buffer(seq)).unwrap();
+    }
+    for seq in 0..5 {
+        let buffer = h.pull().unwrap();
+        let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+        assert_eq!(rtp_buffer.seq(), seq);
+    }
+
+    // The sender pauses, then resumes with the next seqnums
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    let resume = jb.current_running_time().unwrap();
+
+    // #7 arrives after #8
+    for seq in [5, 6, 8, 7, 9, 10, 11, 12, 13, 14] {
+        let pts = resume + PACKET_DURATION * (seq - 5) as u64;
+        let mut buffer = rtp_buffer_with_pt(seq, 0, (pts.mseconds() * 8) as u32);
+        buffer.get_mut().unwrap().set_pts(pts);
+        h.push(buffer).unwrap();
+    }
+
+    for seq in 5..15 {
+        let buffer = h.pull().unwrap();
+        let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+        assert_eq!(rtp_buffer.seq(), seq);
+    }
+
+    let stats = jb.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("num-late").unwrap(), 0);
+    assert_eq!(stats.get::<u64>("num-lost").unwrap(), 0);
+}
+
+#[test]
+fn jb_loss_messages() {
+    init();
+
+    let jb = gst::ElementFactory::make("ts-jitterbuffer")
+        .name("ts-jitterbuffer")
+        .property("context", "jb_loss_messages")
+        .property("latency", 50u32)
+        .build()
+        .unwrap();
+
+    let bus = gst::Bus::new();
+    jb.set_bus(Some(&bus));
+
+    let mut h = gst_check::Harness::with_element(&jb, Some("sink"), Some("src"));
+    h.use_systemclock();
+    h.set_src_caps_str("application/x-rtp,media=audio,payload=0,clock-rate=8000");
+    h.play();
+
+    let mut push_and_pull = |seqnums: &[u16]| {
+        for &seq in seqnums {
+            h.push(rtp_buffer(seq)).unwrap();
+        }
+        for &seq in seqnums {
+            let buffer = h.pull().unwrap();
+            let rtp_buffer = gst_rtp::RTPBuffer::from_buffer_readable(&buffer).unwrap();
+            assert_eq!(rtp_buffer.seq(), seq);
+        }
+    };
+
+    let next_loss = || {
+        let msg = bus
+            .timed_pop_filtered(
+                gst::ClockTime::from_seconds(5),
+                &[gst::MessageType::Element],
+            )
+            .unwrap();
+        let s = msg.structure().unwrap();
+        assert_eq!(s.name(), "ts-jitterbuffer-loss");
+        assert!(s
+            .get::<Option<gst::ClockTime>>("running-time")
+            .unwrap()
+            .is_some());
+        (
+            s.get::<u32>("first-seqnum").unwrap(),
+            s.get::<u64>("count").unwrap(),
+            s.get::<u32>("num-bursts").unwrap(),
+        )
+    };
+
+    // #2, #3 and #4 are lost
+    push_and_pull(&[0, 1, 5, 6]);
+    assert_eq!(next_loss(), (2, 3, 1));
+
+    // Rate limited: #7 & #8, then #10 are reported together later on
+    push_and_pull(&[9, 11]);
+    assert!(bus.pop_filtered(&[gst::MessageType::Element]).is_none());
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    push_and_pull(&[12]);
+    assert_eq!(next_loss(), (7, 3, 2));
+
+    let stats = jb.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("num-lost").unwrap(), 6);
+
+    jb.set_bus(None);
+}
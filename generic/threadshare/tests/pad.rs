@@ -1237,3 +1237,92 @@ fn start_flush() {
 
     pipeline.set_state(gst::State::Null).unwrap();
 }
+
+#[test]
+fn src_deactivation_aborts_sub_tasks() {
+    use std::sync::mpsc::{self as sync_mpsc, RecvTimeoutError};
+
+    init();
+
+    let scenario_name = "src_deactivation_aborts_sub_tasks";
+
+    let (pipeline, src_element, sink_element, mut receiver) = setup(scenario_name, None, None);
+
+    // Downstream adds a sub task which never completes to the src task
+    let (started_tx, started_rx) = sync_mpsc::channel();
+    let (dropped_tx, dropped_rx) = sync_mpsc::channel::<()>();
+    let sub_task_senders = Mutex::new(Some((started_tx, dropped_tx)));
+    sink_element
+        .static_pad("sink")
+        .unwrap()
+        .add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+            if let Some((started_tx, dropped_tx)) = sub_task_senders.lock().unwrap().take() {
+                let (ctx, task_id) = Context::current_task().unwrap();
+                let res = ctx.add_sub_task(task_id, async move {
+                    let _dropped_tx = dropped_tx;
+                    started_tx.send(()).unwrap();
+                    // Sleep forever
+                    futures::future::pending::<()>().await;
+                    Ok(())
+                });
+                assert!(res.is_ok());
+            }
+
+            gst::PadProbeReturn::Ok
+        })
+        .unwrap();
+
+    let elem_src_test = src_element.imp();
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+
+    // Initial events
+    elem_src_test
+        .try_push(Item::Event(
+            gst::event::StreamStart::builder(scenario_name)
+                .group_id(gst::GroupId::next())
+                .build(),
+        ))
+        .unwrap();
+
+    match futures::executor::block_on(receiver.next()).unwrap() {
+        Item::Event(event) => match event.view() {
+            EventView::StreamStart(_) => (),
+            other => panic!("Unexpected event {other:?}"),
+        },
+        other => panic!("Unexpected item {other:?}"),
+    }
+
+    elem_src_test
+        .try_push(Item::Event(gst::event::Segment::new(
+            &gst::FormattedSegment::<gst::format::Time>::new(),
+        )))
+        .unwrap();
+
+    match futures::executor::block_on(receiver.next()).unwrap() {
+        Item::Event(event) => match event.view() {
+            EventView::Segment(_) => (),
+            other => panic!("Unexpected event {other:?}"),
+        },
+        other => panic!("Unexpected item {other:?}"),
+    }
+
+    // Buffer: the src task is now stuck draining the sleeping sub task
+    elem_src_test
+        .try_push(Item::Buffer(gst::Buffer::from_slice(vec![1, 2, 3, 4])))
+        .unwrap();
+    started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    // Deactivating the src pad aborts the sub tasks added while pushing through it
+    src_element
+        .static_pad("src")
+        .unwrap()
+        .set_active(false)
+        .unwrap();
+    assert_eq!(
+        dropped_rx.recv_timeout(Duration::from_secs(5)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+
+    pipeline.set_state(gst::State::Null).unwrap();
+}
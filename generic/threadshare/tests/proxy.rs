@@ -181,3 +181,611 @@ fn test_from_pipeline_to_pipeline_and_back() {
     pipe_1.set_state(gst::State::Null).unwrap();
     pipe_2.set_state(gst::State::Null).unwrap();
 }
+
+#[test]
+fn test_fan_out() {
+    init();
+
+    let mut sink_h = gst_check::Harness::new("ts-proxysink");
+    sink_h
+        .element()
+        .unwrap()
+        .set_property("proxy-context", "proxy::test_fan_out");
+
+    let mut src_hs = (0..2)
+        .map(|_| {
+            let mut h = gst_check::Harness::new("ts-proxysrc");
+            let proxysrc = h.element().unwrap();
+            proxysrc.set_property("proxy-context", "proxy::test_fan_out");
+            proxysrc.set_property("context", "proxy::test_fan_out");
+            h.play();
+            h
+        })
+        .collect::<Vec<_>>();
+
+    sink_h.play();
+    sink_h.set_src_caps_str("foo/bar");
+
+    let push = |h: &mut gst_check::Harness, offset: u64| {
+        let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+        buffer.get_mut().unwrap().set_offset(offset);
+        assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+    };
+
+    for offset in 0..5 {
+        push(&mut sink_h, offset);
+    }
+
+    for h in src_hs.iter_mut() {
+        let offsets: Vec<u64> = (0..5).map(|_| h.pull().unwrap().offset()).collect();
+        assert_eq!(offsets, [0, 1, 2, 3, 4]);
+    }
+
+    // Removing a consumer doesn't disturb the others
+    let removed = src_hs.pop().unwrap();
+    removed
+        .element()
+        .unwrap()
+        .set_state(gst::State::Null)
+        .unwrap();
+    drop(removed);
+
+    for offset in 5..8 {
+        push(&mut sink_h, offset);
+    }
+
+    let offsets: Vec<u64> = (0..3).map(|_| src_hs[0].pull().unwrap().offset()).collect();
+    assert_eq!(offsets, [5, 6, 7]);
+}
+
+#[test]
+fn test_unconnected_drop() {
+    init();
+
+    let mut sink_h = gst_check::Harness::new("ts-proxysink");
+    let proxysink = sink_h.element().unwrap();
+    proxysink.set_property("proxy-context", "proxy::test_unconnected_drop");
+    proxysink.set_property_from_str("unconnected-mode", "drop");
+
+    sink_h.play();
+    sink_h.set_src_caps_str("foo/bar");
+
+    // No proxysrc attached yet: buffers are dropped without blocking
+    let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+    buffer.get_mut().unwrap().set_offset(0);
+    assert_eq!(sink_h.push(buffer), Ok(gst::FlowSuccess::Ok));
+
+    let bus = gst::Bus::new();
+    proxysink.set_bus(Some(&bus));
+
+    let mut src_h = gst_check::Harness::new("ts-proxysrc");
+    let proxysrc = src_h.element().unwrap();
+    proxysrc.set_property("proxy-context", "proxy::test_unconnected_drop");
+    proxysrc.set_property("context", "proxy::test_unconnected_drop");
+    src_h.play();
+
+    let msg = bus
+        .timed_pop_filtered(
+            gst::ClockTime::from_seconds(5),
+            &[gst::MessageType::Element],
+        )
+        .expect("proxysrc attached message");
+    let s = msg.structure().unwrap();
+    assert_eq!(s.name(), "GstTsProxySrcAttached");
+    assert_eq!(s.get::<u32>("n-proxysrcs").unwrap(), 1);
+
+    let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+    buffer.get_mut().unwrap().set_offset(1);
+    assert_eq!(sink_h.push(buffer), Ok(gst::FlowSuccess::Ok));
+
+    // The sticky events are replayed before the first buffer
+    assert_eq!(src_h.pull().unwrap().offset(), 1);
+
+    let mut event_types = Vec::new();
+    while let Some(event) = src_h.try_pull_event() {
+        event_types.push(event.type_());
+    }
+
+    assert_eq!(
+        event_types,
+        [
+            gst::EventType::StreamStart,
+            gst::EventType::Caps,
+            gst::EventType::Segment,
+        ]
+    );
+
+    proxysrc.set_state(gst::State::Null).unwrap();
+
+    let msg = bus
+        .timed_pop_filtered(
+            gst::ClockTime::from_seconds(5),
+            &[gst::MessageType::Element],
+        )
+        .expect("proxysrc detached message");
+    let s = msg.structure().unwrap();
+    assert_eq!(s.name(), "GstTsProxySrcDetached");
+    assert_eq!(s.get::<u32>("n-proxysrcs").unwrap(), 0);
+}
+
+#[test]
+fn test_unconnected_error() {
+    init();
+
+    let mut sink_h = gst_check::Harness::new("ts-proxysink");
+    let proxysink = sink_h.element().unwrap();
+    proxysink.set_property("proxy-context", "proxy::test_unconnected_error");
+    proxysink.set_property_from_str("unconnected-mode", "error");
+    proxysink.set_property("unconnected-timeout", 50u32);
+
+    sink_h.play();
+
+    assert_eq!(
+        sink_h.push(gst::Buffer::from_slice([0u8; 4])),
+        Err(gst::FlowError::Error)
+    );
+}
+
+#[test]
+fn test_current_level() {
+    init();
+
+    let mut sink_h = gst_check::Harness::new("ts-proxysink");
+    let proxysink = sink_h.element().unwrap();
+    proxysink.set_property("proxy-context", "proxy::test_current_level");
+
+    let mut src_h = gst_check::Harness::new("ts-proxysrc");
+    let proxysrc = src_h.element().unwrap();
+    proxysrc.set_property("proxy-context", "proxy::test_current_level");
+    proxysrc.set_property("context", "proxy::test_current_level");
+    proxysrc.set_property("max-size-buffers", 2u32);
+    proxysrc.set_property("max-size-bytes", 0u32);
+    proxysrc.set_property("max-size-time", 0u64);
+
+    let full_notifications = Arc::new(Mutex::new(Vec::new()));
+    for element in [&proxysink, &proxysrc] {
+        let full_notifications = full_notifications.clone();
+        element.connect_notify(Some("full"), move |element, _| {
+            full_notifications
+                .lock()
+                .unwrap()
+                .push((element.name(), element.property::<bool>("full")));
+        });
+    }
+
+    src_h.play();
+    sink_h.play();
+    sink_h.set_src_caps_str("foo/bar");
+
+    assert_eq!(
+        sink_h.push(gst::Buffer::from_slice([0u8; 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    let _ = src_h.pull().unwrap();
+
+    // Keep the items in the queue
+    proxysrc.set_state(gst::State::Paused).unwrap();
+
+    for _ in 0..2 {
+        assert_eq!(
+            sink_h.push(gst::Buffer::from_slice([0u8; 4])),
+            Ok(gst::FlowSuccess::Ok)
+        );
+    }
+
+    for element in [&proxysink, &proxysrc] {
+        assert_eq!(element.property::<u32>("current-level-buffers"), 2);
+        assert_eq!(element.property::<u32>("current-level-bytes"), 8);
+        assert!(element.property::<bool>("full"));
+        assert!(!element.property::<bool>("empty"));
+    }
+
+    {
+        let mut full_notifications = full_notifications.lock().unwrap();
+        full_notifications.sort();
+        assert_eq!(
+            *full_notifications,
+            [(proxysink.name(), true), (proxysrc.name(), true)]
+        );
+        full_notifications.clear();
+    }
+
+    proxysrc.set_state(gst::State::Playing).unwrap();
+    for _ in 0..2 {
+        let _ = src_h.pull().unwrap();
+    }
+
+    for element in [&proxysink, &proxysrc] {
+        assert_eq!(element.property::<u32>("current-level-buffers"), 0);
+        assert!(!element.property::<bool>("full"));
+        assert!(element.property::<bool>("empty"));
+    }
+}
+
+#[test]
+fn test_upstream_events() {
+    init();
+
+    let mut sink_h = gst_check::Harness::new("ts-proxysink");
+    sink_h
+        .element()
+        .unwrap()
+        .set_property("proxy-context", "proxy::test_upstream_events");
+
+    let mut src_h = gst_check::Harness::new("ts-proxysrc");
+    let proxysrc = src_h.element().unwrap();
+    proxysrc.set_property("proxy-context", "proxy::test_upstream_events");
+    proxysrc.set_property("context", "proxy::test_upstream_events");
+
+    src_h.play();
+    sink_h.play();
+
+    let event = gst::event::CustomUpstream::new(gst::Structure::new_empty("test-upstream"));
+    let seqnum = event.seqnum();
+    assert!(src_h.push_upstream_event(event));
+
+    let event = sink_h.pull_upstream_event().unwrap();
+    assert_eq!(event.type_(), gst::EventType::CustomUpstream);
+    assert_eq!(event.structure().unwrap().name(), "test-upstream");
+    assert_eq!(event.seqnum(), seqnum);
+
+    // Flushing stays on the proxysrc side
+    assert!(src_h.push_upstream_event(gst::event::FlushStart::new()));
+    assert!(src_h.push_upstream_event(gst::event::FlushStop::new(true)));
+    assert!(sink_h.try_pull_upstream_event().is_none());
+}
+
+#[test]
+fn test_rebind_proxy_context() {
+    init();
+
+    let mut sink_hs = ["proxy::test_rebind_1", "proxy::test_rebind_2"]
+        .into_iter()
+        .map(|proxy_context| {
+            let mut h = gst_check::Harness::new("ts-proxysink");
+            let proxysink = h.element().unwrap();
+            proxysink.set_property("proxy-context", proxy_context);
+            proxysink.set_property_from_str("unconnected-mode", "drop");
+            h.play();
+            h
+        })
+        .collect::<Vec<_>>();
+
+    sink_hs[1].set_src_caps_str("foo/bar");
+    assert_eq!(
+        sink_hs[1].push(gst::Buffer::from_slice([0u8; 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+
+    let mut src_h = gst_check::Harness::new("ts-proxysrc");
+    let proxysrc = src_h.element().unwrap();
+    proxysrc.set_property("proxy-context", "proxy::test_rebind_1");
+    proxysrc.set_property("context", "proxy::test_rebind");
+    proxysrc.set_state(gst::State::Ready).unwrap();
+
+    proxysrc.set_property("proxy-context", "proxy::test_rebind_2");
+    src_h.play();
+
+    let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+    buffer.get_mut().unwrap().set_offset(1);
+    assert_eq!(sink_hs[1].push(buffer), Ok(gst::FlowSuccess::Ok));
+
+    // The sticky events of the new proxy context are replayed
+    assert_eq!(src_h.pull().unwrap().offset(), 1);
+
+    let mut event_types = Vec::new();
+    while let Some(event) = src_h.try_pull_event() {
+        event_types.push(event.type_());
+    }
+
+    assert_eq!(
+        event_types,
+        [
+            gst::EventType::StreamStart,
+            gst::EventType::Caps,
+            gst::EventType::Segment,
+        ]
+    );
+
+    // Can't be changed while playing
+    proxysrc.set_property("proxy-context", "proxy::test_rebind_1");
+    assert_eq!(
+        proxysrc.property::<String>("proxy-context"),
+        "proxy::test_rebind_2"
+    );
+}
+
+#[test]
+fn test_latency() {
+    init();
+
+    let mut sink_h = gst_check::Harness::new("ts-proxysink");
+    let proxysink = sink_h.element().unwrap();
+    proxysink.set_property("proxy-context", "proxy::test_latency");
+
+    let mut src_h = gst_check::Harness::new("ts-proxysrc");
+    let proxysrc = src_h.element().unwrap();
+    proxysrc.set_property("proxy-context", "proxy::test_latency");
+    proxysrc.set_property("context", "proxy::test_latency");
+
+    let bus = gst::Bus::new();
+    proxysrc.set_bus(Some(&bus));
+
+    sink_h.set_upstream_latency(20.mseconds());
+    sink_h.play();
+    src_h.play();
+
+    assert_eq!(src_h.query_latency(), Some(20.mseconds()));
+
+    // The producer's pipeline queries its latency again after a change
+    sink_h.set_upstream_latency(40.mseconds());
+    let mut query = gst::query::Latency::new();
+    assert!(proxysink.query(&mut query));
+    assert_eq!(query.result().1, 40.mseconds());
+
+    assert!(bus.pop_filtered(&[gst::MessageType::Latency]).is_some());
+    assert_eq!(src_h.query_latency(), Some(40.mseconds()));
+}
+
+#[test]
+fn test_sink_max_size_time() {
+    init();
+
+    let mut sink_h = gst_check::Harness::new("ts-proxysink");
+    let proxysink = sink_h.element().unwrap();
+    proxysink.set_property("proxy-context", "proxy::test_sink_max_size_time");
+    proxysink.set_property("max-size-time", 40.mseconds().nseconds());
+
+    let mut src_h = gst_check::Harness::new("ts-proxysrc");
+    let proxysrc = src_h.element().unwrap();
+    proxysrc.set_property("proxy-context", "proxy::test_sink_max_size_time");
+    proxysrc.set_property("context", "proxy::test_sink_max_size_time");
+    proxysrc.set_property("max-size-buffers", 0u32);
+    proxysrc.set_property("max-size-bytes", 0u32);
+
+    src_h.play();
+    sink_h.play();
+    sink_h.set_src_caps_str("foo/bar");
+
+    // The proxysink's max-size-time applies to the proxysrc
+    assert_eq!(
+        proxysrc.property::<u64>("max-size-time"),
+        40.mseconds().nseconds()
+    );
+
+    let push = |h: &mut gst_check::Harness, pts: gst::ClockTime| {
+        let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+        {
+            let buffer = buffer.get_mut().unwrap();
+            buffer.set_pts(pts);
+            buffer.set_duration(20.mseconds());
+        }
+        assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+    };
+
+    push(&mut sink_h, gst::ClockTime::ZERO);
+    let _ = src_h.pull().unwrap();
+
+    // Keep the items in the queue
+    proxysrc.set_state(gst::State::Paused).unwrap();
+
+    push(&mut sink_h, 20.mseconds());
+    push(&mut sink_h, 40.mseconds());
+
+    assert_eq!(
+        proxysrc.property::<u64>("current-level-time"),
+        40.mseconds().nseconds()
+    );
+    assert!(proxysrc.property::<bool>("full"));
+    assert!(proxysink.property::<bool>("full"));
+
+    // Growing the limit while playing makes room for more
+    proxysink.set_property("max-size-time", 60.mseconds().nseconds());
+    push(&mut sink_h, 60.mseconds());
+
+    assert_eq!(
+        proxysrc.property::<u64>("current-level-time"),
+        60.mseconds().nseconds()
+    );
+}
+
+#[test]
+fn test_no_forward_eos() {
+    init();
+
+    let new_producer = || {
+        let mut h = gst_check::Harness::new("ts-proxysink");
+        h.element()
+            .unwrap()
+            .set_property("proxy-context", "proxy::test_no_forward_eos");
+        h.play();
+        h.set_src_caps_str("foo/bar");
+        h
+    };
+
+    let mut src_h = gst_check::Harness::new("ts-proxysrc");
+    let proxysrc = src_h.element().unwrap();
+    proxysrc.set_property("proxy-context", "proxy::test_no_forward_eos");
+    proxysrc.set_property("context", "proxy::test_no_forward_eos");
+    proxysrc.set_property("forward-eos", false);
+
+    let bus = gst::Bus::new();
+    proxysrc.set_bus(Some(&bus));
+
+    src_h.play();
+
+    let mut sink_h = new_producer();
+    assert_eq!(
+        sink_h.push(gst::Buffer::from_slice([0u8; 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    let _ = src_h.pull().unwrap();
+    while src_h.try_pull_event().is_some() {}
+
+    assert!(sink_h.push_event(gst::event::Eos::new()));
+
+    let msg = bus
+        .timed_pop_filtered(
+            gst::ClockTime::from_seconds(5),
+            &[gst::MessageType::Element],
+        )
+        .expect("proxysrc EOS message");
+    assert_eq!(msg.structure().unwrap().name(), "GstTsProxySrcEos");
+    assert!(src_h.try_pull_event().is_none());
+
+    // The producer comes back with a new stream
+    drop(sink_h);
+    let mut sink_h = new_producer();
+
+    let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+    buffer.get_mut().unwrap().set_offset(1);
+    assert_eq!(sink_h.push(buffer), Ok(gst::FlowSuccess::Ok));
+
+    assert_eq!(src_h.pull().unwrap().offset(), 1);
+    assert_eq!(
+        src_h.pull_event().unwrap().type_(),
+        gst::EventType::StreamStart
+    );
+}
+
+#[test]
+fn test_stats() {
+    init();
+
+    let mut sink_h = gst_check::Harness::new("ts-proxysink");
+    let proxysink = sink_h.element().unwrap();
+    proxysink.set_property("proxy-context", "proxy::test_stats");
+    proxysink.set_property_from_str("unconnected-mode", "drop");
+
+    sink_h.play();
+    sink_h.set_src_caps_str("foo/bar");
+
+    // Dropped: no proxysrc yet
+    assert_eq!(
+        sink_h.push(gst::Buffer::from_slice([0u8; 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+
+    let mut src_h = gst_check::Harness::new("ts-proxysrc");
+    let proxysrc = src_h.element().unwrap();
+    proxysrc.set_property("proxy-context", "proxy::test_stats");
+    proxysrc.set_property("context", "proxy::test_stats");
+    src_h.play();
+
+    for _ in 0..2 {
+        assert_eq!(
+            sink_h.push(gst::Buffer::from_slice([0u8; 4])),
+            Ok(gst::FlowSuccess::Ok)
+        );
+        let _ = src_h.pull().unwrap();
+    }
+
+    let stats = proxysink.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("buffers-transferred").unwrap(), 2);
+    assert_eq!(stats.get::<u64>("bytes-transferred").unwrap(), 8);
+    assert_eq!(stats.get::<u64>("dropped").unwrap(), 1);
+    assert!(stats.has_field("last-transfer-time"));
+
+    let stats = proxysrc.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("buffers-transferred").unwrap(), 2);
+    assert_eq!(stats.get::<u64>("bytes-transferred").unwrap(), 8);
+    // The replayed stream-start, caps & segment
+    assert_eq!(stats.get::<u64>("events-transferred").unwrap(), 3);
+    assert_eq!(stats.get::<u64>("dropped").unwrap(), 0);
+}
+
+#[test]
+fn test_serialized_events_order() {
+    init();
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Item {
+        Buffer(u64),
+        Event(u64),
+        Caps(u64),
+    }
+
+    const ITERATIONS: u64 = 200;
+
+    let mut sink_h = gst_check::Harness::new("ts-proxysink");
+    sink_h
+        .element()
+        .unwrap()
+        .set_property("proxy-context", "proxy::test_serialized_events_order");
+
+    let mut src_h = gst_check::Harness::new("ts-proxysrc");
+    let proxysrc = src_h.element().unwrap();
+    proxysrc.set_property("proxy-context", "proxy::test_serialized_events_order");
+    proxysrc.set_property("context", "proxy::test_serialized_events_order");
+    // Keep the queue small so that items go through the pending queue
+    proxysrc.set_property("max-size-buffers", 4u32);
+
+    let items = Arc::new(Mutex::new(Vec::new()));
+    let items_clone = items.clone();
+    src_h.sinkpad().unwrap().add_probe(
+        gst::PadProbeType::BUFFER | gst::PadProbeType::EVENT_DOWNSTREAM,
+        move |_pad, info| {
+            let item = match info.data {
+                Some(gst::PadProbeData::Buffer(ref buffer)) => Some(Item::Buffer(buffer.offset())),
+                Some(gst::PadProbeData::Event(ref event)) => match event.view() {
+                    gst::EventView::CustomDownstream(ev) => ev
+                        .structure()
+                        .and_then(|s| s.get::<u64>("seq").ok())
+                        .map(Item::Event),
+                    gst::EventView::Caps(ev) => ev
+                        .caps()
+                        .structure(0)
+                        .and_then(|s| s.get::<u64>("seq").ok())
+                        .map(Item::Caps),
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            if let Some(item) = item {
+                items_clone.lock().unwrap().push(item);
+            }
+
+            gst::PadProbeReturn::Ok
+        },
+    );
+
+    src_h.play();
+    sink_h.play();
+    sink_h.set_src_caps_str("foo/bar");
+
+    let producer = std::thread::spawn(move || {
+        for seq in 0..ITERATIONS {
+            if seq % 10 == 0 {
+                let caps = gst::Caps::builder("foo/bar").field("seq", seq).build();
+                assert!(sink_h.push_event(gst::event::Caps::new(&caps)));
+            }
+
+            assert!(sink_h.push_event(gst::event::CustomDownstream::new(
+                gst::Structure::builder("test").field("seq", seq).build()
+            )));
+
+            let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+            buffer.get_mut().unwrap().set_offset(seq);
+            assert_eq!(sink_h.push(buffer), Ok(gst::FlowSuccess::Ok));
+        }
+
+        sink_h
+    });
+
+    for _ in 0..ITERATIONS {
+        let _ = src_h.pull().unwrap();
+    }
+    let _sink_h = producer.join().unwrap();
+
+    let mut expected = Vec::new();
+    for seq in 0..ITERATIONS {
+        if seq % 10 == 0 {
+            expected.push(Item::Caps(seq));
+        }
+        expected.push(Item::Event(seq));
+        expected.push(Item::Buffer(seq));
+    }
+
+    assert_eq!(*items.lock().unwrap(), expected);
+}
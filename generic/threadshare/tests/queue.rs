@@ -19,7 +19,8 @@
 
 use gst::prelude::*;
 
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 
 fn init() {
     use std::sync::Once;
@@ -90,3 +91,576 @@ fn test_push() {
 
     pipeline.set_state(gst::State::Null).unwrap();
 }
+
+// Blocks the queue's streaming thread on the first buffer until `release` is sent.
+fn block_first_buffer(h: &gst_check::Harness) -> (mpsc::Receiver<()>, mpsc::Sender<()>) {
+    let (entered_tx, entered_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let release_rx = Mutex::new(Some(release_rx));
+
+    let src_pad = h.element().unwrap().static_pad("src").unwrap();
+    src_pad.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+        if let Some(release_rx) = release_rx.lock().unwrap().take() {
+            entered_tx.send(()).unwrap();
+            let _ = release_rx.recv();
+        }
+
+        gst::PadProbeReturn::Ok
+    });
+
+    (entered_rx, release_tx)
+}
+
+fn push_while_blocked(leaky: &str) -> (Vec<u64>, u64) {
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", format!("queue::test_leaky_{leaky}"));
+    queue.set_property("max-size-buffers", 2u32);
+    queue.set_property_from_str("leaky", leaky);
+
+    let (entered_rx, release_tx) = block_first_buffer(&h);
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    for offset in 0..6u64 {
+        let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+        buffer.get_mut().unwrap().set_offset(offset);
+        assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+
+        if offset == 0 {
+            // Make sure the first buffer left the queue
+            entered_rx.recv().unwrap();
+        }
+    }
+
+    release_tx.send(()).unwrap();
+
+    let offsets = (0..3).map(|_| h.pull().unwrap().offset()).collect();
+    let dropped = queue
+        .property::<gst::Structure>("stats")
+        .get::<u64>("dropped")
+        .unwrap();
+
+    (offsets, dropped)
+}
+
+#[test]
+fn test_leaky_downstream() {
+    init();
+
+    let (offsets, dropped) = push_while_blocked("downstream");
+    assert_eq!(offsets, [0, 4, 5]);
+    assert_eq!(dropped, 3);
+}
+
+#[test]
+fn test_leaky_upstream() {
+    init();
+
+    let (offsets, dropped) = push_while_blocked("upstream");
+    assert_eq!(offsets, [0, 1, 2]);
+    assert_eq!(dropped, 3);
+}
+
+#[test]
+fn test_levels_and_overrun() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", "queue::test_levels_and_overrun");
+    queue.set_property("max-size-buffers", 2u32);
+    queue.set_property_from_str("leaky", "upstream");
+
+    let overruns = Arc::new(AtomicU32::new(0));
+    queue.connect("overrun", false, {
+        let overruns = overruns.clone();
+        move |_| {
+            overruns.fetch_add(1, Ordering::SeqCst);
+            None
+        }
+    });
+
+    let (entered_rx, release_tx) = block_first_buffer(&h);
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    for idx in 0..6u64 {
+        let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+        {
+            let buffer = buffer.get_mut().unwrap();
+            buffer.set_pts(10.mseconds() * idx);
+            buffer.set_duration(10.mseconds());
+        }
+        assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+
+        if idx == 0 {
+            entered_rx.recv().unwrap();
+        }
+    }
+
+    assert_eq!(overruns.load(Ordering::SeqCst), 1);
+    assert_eq!(queue.property::<u32>("current-level-buffers"), 2);
+    assert_eq!(queue.property::<u32>("current-level-bytes"), 8);
+    assert_eq!(
+        queue.property::<u64>("current-level-time"),
+        20.mseconds().nseconds()
+    );
+
+    release_tx.send(()).unwrap();
+    for _ in 0..3 {
+        let _ = h.pull().unwrap();
+    }
+
+    assert_eq!(queue.property::<u32>("current-level-buffers"), 0);
+    assert_eq!(queue.property::<u32>("current-level-bytes"), 0);
+    assert_eq!(queue.property::<u64>("current-level-time"), 0);
+
+    let stats = queue.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("full-count").unwrap(), 1);
+    assert!(stats.get::<u64>("full-time").unwrap() > 0);
+    assert!(stats.get::<u64>("empty-count").unwrap() >= 1);
+    assert_eq!(stats.get::<u32>("max-level-buffers").unwrap(), 2);
+    assert_eq!(stats.get::<u32>("max-level-bytes").unwrap(), 8);
+    assert_eq!(
+        stats.get::<u64>("max-level-time").unwrap(),
+        20.mseconds().nseconds()
+    );
+
+    h.element().unwrap().set_state(gst::State::Null).unwrap();
+    let stats = queue.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("full-count").unwrap(), 0);
+    assert_eq!(stats.get::<u32>("max-level-buffers").unwrap(), 0);
+}
+
+fn check_max_size_time(context: &str, with_duration: bool) {
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", context);
+    queue.set_property("max-size-buffers", 0u32);
+    queue.set_property("max-size-bytes", 0u32);
+    queue.set_property("max-size-time", 50.mseconds().nseconds());
+    queue.set_property_from_str("leaky", "downstream");
+
+    let (entered_rx, release_tx) = block_first_buffer(&h);
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    for idx in 0..20u64 {
+        let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+        {
+            let buffer = buffer.get_mut().unwrap();
+            buffer.set_pts(10.mseconds() * idx);
+            if with_duration {
+                buffer.set_duration(10.mseconds());
+            }
+        }
+        assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+
+        if idx == 0 {
+            entered_rx.recv().unwrap();
+        }
+
+        assert!(queue.property::<u64>("current-level-time") <= 50.mseconds().nseconds());
+    }
+
+    assert!(queue.property::<u64>("current-level-time") > 0);
+
+    release_tx.send(()).unwrap();
+    let _ = h.pull().unwrap();
+}
+
+#[test]
+fn test_max_size_time() {
+    init();
+
+    check_max_size_time("queue::test_max_size_time", true);
+}
+
+#[test]
+fn test_max_size_time_pts_delta() {
+    init();
+
+    check_max_size_time("queue::test_max_size_time_pts_delta", false);
+}
+
+#[test]
+fn test_buffer_list_passthrough() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", "queue::test_buffer_list_passthrough");
+
+    let items = Arc::new(Mutex::new(Vec::new()));
+    let src_pad = queue.static_pad("src").unwrap();
+    src_pad.add_probe(
+        gst::PadProbeType::BUFFER | gst::PadProbeType::BUFFER_LIST,
+        {
+            let items = items.clone();
+            move |_, info| {
+                match info.data {
+                    Some(gst::PadProbeData::Buffer(_)) => items.lock().unwrap().push(1),
+                    Some(gst::PadProbeData::BufferList(ref list)) => {
+                        items.lock().unwrap().push(list.len())
+                    }
+                    _ => unreachable!(),
+                }
+
+                gst::PadProbeReturn::Ok
+            }
+        },
+    );
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    let mut offset = 0u64;
+    let mut new_buffer = || {
+        let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+        buffer.get_mut().unwrap().set_offset(offset);
+        offset += 1;
+        buffer
+    };
+
+    assert_eq!(h.push(new_buffer()), Ok(gst::FlowSuccess::Ok));
+
+    let mut list = gst::BufferList::new();
+    for _ in 0..3 {
+        list.get_mut().unwrap().add(new_buffer());
+    }
+    assert_eq!(
+        h.srcpad().unwrap().push_list(list),
+        Ok(gst::FlowSuccess::Ok)
+    );
+
+    assert_eq!(h.push(new_buffer()), Ok(gst::FlowSuccess::Ok));
+
+    let mut list = gst::BufferList::new();
+    for _ in 0..2 {
+        list.get_mut().unwrap().add(new_buffer());
+    }
+    assert_eq!(
+        h.srcpad().unwrap().push_list(list),
+        Ok(gst::FlowSuccess::Ok)
+    );
+
+    let offsets: Vec<u64> = (0..7).map(|_| h.pull().unwrap().offset()).collect();
+    assert_eq!(offsets, [0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(*items.lock().unwrap(), [1, 3, 1, 2]);
+}
+
+// Answers Allocation queries with a pool and records the buffers and queries
+// reaching the harness sink pad
+fn propose_pool(h: &gst_check::Harness) -> Arc<Mutex<Vec<&'static str>>> {
+    let items = Arc::new(Mutex::new(Vec::new()));
+    h.sinkpad().unwrap().add_probe(
+        gst::PadProbeType::BUFFER | gst::PadProbeType::QUERY_DOWNSTREAM,
+        {
+            let items = items.clone();
+            move |_, info| {
+                if let Some(gst::PadProbeData::Buffer(_)) = info.data {
+                    items.lock().unwrap().push("buffer");
+                    return gst::PadProbeReturn::Ok;
+                }
+
+                let Some(query) = info.query_mut() else {
+                    return gst::PadProbeReturn::Ok;
+                };
+
+                match query.view_mut() {
+                    gst::QueryViewMut::Allocation(q) => {
+                        items.lock().unwrap().push("allocation");
+                        let pool = gst::BufferPool::new();
+                        q.add_allocation_pool(Some(&pool), 4, 0, 0);
+                        gst::PadProbeReturn::Handled
+                    }
+                    gst::QueryViewMut::Drain(_) => {
+                        items.lock().unwrap().push("drain");
+                        gst::PadProbeReturn::Handled
+                    }
+                    _ => gst::PadProbeReturn::Ok,
+                }
+            }
+        },
+    );
+
+    items
+}
+
+#[test]
+fn test_allocation_query() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", "queue::test_allocation_query");
+
+    let items = propose_pool(&h);
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    let caps = gst::Caps::builder("foo/bar").build();
+    let mut query = gst::query::Allocation::new(Some(&caps), true);
+    assert!(h.srcpad().unwrap().peer_query(&mut query));
+    assert_eq!(query.allocation_pools().len(), 1);
+    assert_eq!(*items.lock().unwrap(), ["allocation"]);
+}
+
+#[test]
+fn test_drain_query() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", "queue::test_drain_query");
+
+    let items = propose_pool(&h);
+    let (entered_rx, release_tx) = block_first_buffer(&h);
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    for idx in 0..3 {
+        assert_eq!(
+            h.push(gst::Buffer::from_slice([0u8; 4])),
+            Ok(gst::FlowSuccess::Ok)
+        );
+
+        if idx == 0 {
+            entered_rx.recv().unwrap();
+        }
+    }
+
+    let (res_tx, res_rx) = mpsc::channel();
+    let srcpad = h.srcpad().unwrap().clone();
+    let handle = std::thread::spawn(move || {
+        let mut query = gst::query::Drain::new();
+        res_tx.send(srcpad.peer_query(&mut query)).unwrap();
+    });
+
+    // The query must wait for the queued buffers to be pushed downstream
+    assert_eq!(
+        res_rx.recv_timeout(std::time::Duration::from_millis(100)),
+        Err(mpsc::RecvTimeoutError::Timeout)
+    );
+
+    release_tx.send(()).unwrap();
+    assert!(res_rx.recv().unwrap());
+    handle.join().unwrap();
+
+    assert_eq!(
+        *items.lock().unwrap(),
+        ["buffer", "buffer", "buffer", "drain"]
+    );
+}
+
+#[test]
+fn test_sticky_events_after_flush() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", "queue::test_sticky_events_after_flush");
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([0u8; 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    let _ = h.pull().unwrap();
+    while h.try_pull_event().is_some() {}
+
+    assert!(h.push_event(gst::event::FlushStart::new()));
+    assert!(h.push_event(gst::event::FlushStop::new(true)));
+
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([0u8; 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    let _ = h.pull().unwrap();
+
+    let mut event_types = Vec::new();
+    while let Some(event) = h.try_pull_event() {
+        event_types.push(event.type_());
+    }
+
+    assert_eq!(
+        event_types,
+        [
+            gst::EventType::FlushStart,
+            gst::EventType::FlushStop,
+            gst::EventType::StreamStart,
+            gst::EventType::Caps,
+            gst::EventType::Segment,
+        ]
+    );
+}
+
+#[test]
+fn test_latency() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", "queue::test_latency");
+    queue.set_property("max-size-time", 50.mseconds().nseconds());
+
+    let bus = gst::Bus::new();
+    queue.set_bus(Some(&bus));
+
+    h.set_upstream_latency(10.mseconds());
+    h.play();
+
+    assert_eq!(h.query_latency(), Some(60.mseconds()));
+
+    // The latency property only applies when max-size-time is unlimited
+    queue.set_property("latency", 20.mseconds().nseconds());
+    assert!(bus.pop_filtered(&[gst::MessageType::Latency]).is_none());
+    assert_eq!(h.query_latency(), Some(60.mseconds()));
+
+    queue.set_property("max-size-time", 0u64);
+    assert!(bus.pop_filtered(&[gst::MessageType::Latency]).is_some());
+    assert_eq!(h.query_latency(), Some(30.mseconds()));
+}
+
+#[test]
+fn test_min_threshold() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", "queue::test_min_threshold");
+    queue.set_property("min-threshold-buffers", 3u32);
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    let mut offset = 0u64;
+    let mut push = |h: &mut gst_check::Harness| {
+        let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+        buffer.get_mut().unwrap().set_offset(offset);
+        offset += 1;
+        assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+    };
+
+    push(&mut h);
+    push(&mut h);
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    assert!(h.try_pull().is_none());
+
+    push(&mut h);
+    for expected in 0..3 {
+        assert_eq!(h.pull().unwrap().offset(), expected);
+    }
+
+    // Holding again after the underrun
+    push(&mut h);
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    assert!(h.try_pull().is_none());
+
+    // EOS overrides the threshold
+    assert!(h.push_event(gst::event::Eos::new()));
+    assert_eq!(h.pull().unwrap().offset(), 3);
+
+    let mut eos = false;
+    while let Ok(event) = h.pull_event() {
+        if event.type_() == gst::EventType::Eos {
+            eos = true;
+            break;
+        }
+    }
+    assert!(eos);
+}
+
+#[test]
+fn test_grow_max_size_while_playing() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-queue");
+    let queue = h.element().unwrap();
+    queue.set_property("context", "queue::test_grow_max_size_while_playing");
+    queue.set_property("max-size-buffers", 2u32);
+
+    let (entered_rx, release_tx) = block_first_buffer(&h);
+
+    h.play();
+    h.set_src_caps_str("foo/bar");
+
+    let new_buffer = |offset: u64| {
+        let mut buffer = gst::Buffer::from_slice([0u8; 4]);
+        buffer.get_mut().unwrap().set_offset(offset);
+        buffer
+    };
+
+    assert_eq!(h.push(new_buffer(0)), Ok(gst::FlowSuccess::Ok));
+    entered_rx.recv().unwrap();
+    assert_eq!(h.push(new_buffer(1)), Ok(gst::FlowSuccess::Ok));
+    assert_eq!(h.push(new_buffer(2)), Ok(gst::FlowSuccess::Ok));
+
+    // The queue is full: the next push blocks upstream
+    let (res_tx, res_rx) = mpsc::channel();
+    let srcpad = h.srcpad().unwrap().clone();
+    let handle = std::thread::spawn(move || {
+        res_tx.send(srcpad.push(new_buffer(3))).unwrap();
+    });
+    assert_eq!(
+        res_rx.recv_timeout(std::time::Duration::from_millis(100)),
+        Err(mpsc::RecvTimeoutError::Timeout)
+    );
+
+    // Growing the limit unblocks upstream
+    queue.set_property("max-size-buffers", 5u32);
+    assert_eq!(res_rx.recv().unwrap(), Ok(gst::FlowSuccess::Ok));
+    handle.join().unwrap();
+    assert_eq!(queue.property::<u32>("current-level-buffers"), 3);
+
+    // Shrinking doesn't drop the queued buffers
+    queue.set_property("max-size-buffers", 1u32);
+    assert_eq!(queue.property::<u32>("current-level-buffers"), 3);
+
+    release_tx.send(()).unwrap();
+    let offsets: Vec<u64> = (0..4).map(|_| h.pull().unwrap().offset()).collect();
+    assert_eq!(offsets, [0, 1, 2, 3]);
+}
+
+#[test]
+fn test_context_wait_override() {
+    use gstthreadshare::runtime::Context;
+    use std::time::Duration;
+
+    init();
+
+    const CONTEXT: &str = "queue-test-context-wait-override";
+
+    let queue_20 = gst::ElementFactory::make("ts-queue")
+        .property("context", CONTEXT)
+        .property("context-wait", 20u32)
+        .build()
+        .unwrap();
+    let queue_5 = gst::ElementFactory::make("ts-queue")
+        .property("context", CONTEXT)
+        .property("context-wait", 5u32)
+        .build()
+        .unwrap();
+
+    queue_20.set_state(gst::State::Ready).unwrap();
+    queue_5.set_state(gst::State::Ready).unwrap();
+
+    // The largest wait doesn't influence the Context throttling
+    let context = Context::acquire(CONTEXT, Duration::from_millis(50)).unwrap();
+    assert_eq!(context.wait_duration(), Duration::from_millis(5));
+
+    // Throttling is re-adjusted when the elements release the Context
+    queue_5.set_state(gst::State::Null).unwrap();
+    assert_eq!(context.wait_duration(), Duration::from_millis(20));
+
+    queue_20.set_state(gst::State::Null).unwrap();
+    assert_eq!(context.wait_duration(), Duration::from_millis(50));
+}
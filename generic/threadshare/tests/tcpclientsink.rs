@@ -0,0 +1,160 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use gst::prelude::*;
+
+use std::io::Read;
+use std::net::TcpListener;
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstthreadshare::plugin_register_static().expect("gstthreadshare tcpclientsink test");
+    });
+}
+
+#[test]
+fn test_send() {
+    init();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let mut h = gst_check::Harness::new("ts-tcpclientsink");
+    h.set_src_caps_str("foo/bar");
+    {
+        let tcpclientsink = h.element().unwrap();
+        tcpclientsink.set_property("port", port as i32);
+        tcpclientsink.set_property("context", "test-tcpclientsink-send");
+    }
+    h.play();
+
+    let (mut stream, _) = listener.accept().unwrap();
+
+    for idx in 0..3u8 {
+        assert_eq!(
+            h.push(gst::Buffer::from_slice([idx; 4])),
+            Ok(gst::FlowSuccess::Ok)
+        );
+    }
+
+    let mut data = [0u8; 12];
+    stream.read_exact(&mut data).unwrap();
+    assert_eq!(data, [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2]);
+
+    h.element().unwrap().set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+fn test_on_error() {
+    init();
+
+    // Bind then drop a listener to get a port nobody listens on
+    let port = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+
+    for on_error in ["error", "warn", "ignore"] {
+        let mut h = gst_check::Harness::new("ts-tcpclientsink");
+        h.set_src_caps_str("foo/bar");
+        let tcpclientsink = h.element().unwrap();
+        let bus = gst::Bus::new();
+        tcpclientsink.set_bus(Some(&bus));
+        tcpclientsink.set_property("port", port as i32);
+        tcpclientsink.set_property_from_str("on-error", on_error);
+        tcpclientsink.set_property("context", "test-tcpclientsink-on-error");
+        h.play();
+
+        for _ in 0..2 {
+            let res = h.push(gst::Buffer::from_slice([0; 4]));
+            if on_error == "error" {
+                assert_eq!(res, Err(gst::FlowError::Error));
+            } else {
+                assert_eq!(res, Ok(gst::FlowSuccess::Ok));
+            }
+        }
+
+        let msg_types = [gst::MessageType::Error, gst::MessageType::Warning];
+        let msgs = std::iter::from_fn(|| bus.pop_filtered(&msg_types)).collect::<Vec<_>>();
+        match on_error {
+            "error" => assert_eq!(msgs[0].type_(), gst::MessageType::Error),
+            // Only one warning until a send succeeds again
+            "warn" => {
+                assert_eq!(msgs.len(), 1);
+                assert_eq!(msgs[0].type_(), gst::MessageType::Warning);
+            }
+            _ => assert!(msgs.is_empty()),
+        }
+
+        tcpclientsink.set_state(gst::State::Null).unwrap();
+    }
+}
+
+#[test]
+fn test_reconnect() {
+    init();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let mut h = gst_check::Harness::new("ts-tcpclientsink");
+    h.set_src_caps_str("foo/bar");
+    let tcpclientsink = h.element().unwrap();
+    let bus = gst::Bus::new();
+    tcpclientsink.set_bus(Some(&bus));
+    tcpclientsink.set_property("port", port as i32);
+    tcpclientsink.set_property("reconnect", true);
+    tcpclientsink.set_property("reconnect-delay", 10u32);
+    tcpclientsink.set_property("context", "test-tcpclientsink-reconnect");
+    h.play();
+
+    let (mut stream, _) = listener.accept().unwrap();
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([0u8; 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    let mut data = [0u8; 4];
+    stream.read_exact(&mut data).unwrap();
+
+    // Drop the connection, writes eventually fail and trigger a reconnection
+    drop(stream);
+    let mut stream = loop {
+        assert_eq!(
+            h.push(gst::Buffer::from_slice([1u8; 4])),
+            Ok(gst::FlowSuccess::Ok)
+        );
+        if bus.pop_filtered(&[gst::MessageType::Warning]).is_some() {
+            break listener.accept().unwrap().0;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([2u8; 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    stream.read_exact(&mut data).unwrap();
+    assert_eq!(data, [2u8; 4]);
+
+    tcpclientsink.set_state(gst::State::Null).unwrap();
+}
@@ -120,3 +120,569 @@ fn test_push() {
 
     handler.join().unwrap();
 }
+
+#[test]
+fn test_reconnect() {
+    init();
+
+    let (listening_tx, listening_rx) = mpsc::channel();
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let handler = thread::spawn(move || {
+        use std::net;
+
+        let buffer = [0; 160];
+
+        // First connection, closed right away by the server
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        listening_tx.send(addr.port()).unwrap();
+        let (mut socket, _) = listener.accept().unwrap();
+        socket.write_all(&buffer).unwrap();
+        drop(socket);
+        drop(listener);
+
+        // Let the client fail a few connection attempts
+        thread::sleep(time::Duration::from_millis(200));
+
+        // Server restarted on the same port
+        let listener = net::TcpListener::bind(addr).unwrap();
+        let (mut socket, _) = listener.accept().unwrap();
+        socket.write_all(&buffer).unwrap();
+
+        // Keep the connection open until the test is done
+        let _ = done_rx.recv();
+    });
+
+    // Wait for the server to listen
+    let port = listening_rx.recv().unwrap();
+
+    let pipeline = gst::Pipeline::default();
+
+    let tcpclientsrc = gst::ElementFactory::make("ts-tcpclientsrc")
+        .property("port", port as i32)
+        .property("context", "test-reconnect")
+        .property("reconnect", true)
+        .property("reconnect-delay", 20u32)
+        .build()
+        .unwrap();
+    let appsink = gst_app::AppSink::builder()
+        .sync(false)
+        .async_(false)
+        .build();
+
+    pipeline
+        .add_many([&tcpclientsrc, appsink.upcast_ref()])
+        .unwrap();
+    tcpclientsrc.link(&appsink).unwrap();
+
+    let received = Arc::new(Mutex::new(0));
+
+    let received_clone = received.clone();
+    appsink.set_callbacks(
+        gst_app::AppSinkCallbacks::builder()
+            .new_sample(move |appsink| {
+                let sample = appsink.pull_sample().unwrap();
+                *received_clone.lock().unwrap() += sample.buffer().unwrap().size();
+                Ok(gst::FlowSuccess::Ok)
+            })
+            .build(),
+    );
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+
+    let mut warnings = 0;
+    let bus = pipeline.bus().unwrap();
+    let deadline = time::Instant::now() + time::Duration::from_secs(5);
+    while *received.lock().unwrap() < 2 * 160 {
+        assert!(time::Instant::now() < deadline, "Timed out");
+
+        if let Some(msg) = bus.timed_pop(20.mseconds()) {
+            use gst::MessageView;
+            match msg.view() {
+                MessageView::Warning(..) => warnings += 1,
+                MessageView::Eos(..) => panic!("Unexpected EOS while reconnecting"),
+                MessageView::Error(err) => panic!("{err:?}"),
+                _ => (),
+            }
+        }
+    }
+
+    assert!(warnings > 0);
+    assert_eq!(*received.lock().unwrap(), 2 * 160);
+
+    pipeline.set_state(gst::State::Null).unwrap();
+
+    done_tx.send(()).unwrap();
+    handler.join().unwrap();
+}
+
+#[test]
+fn test_reconnect_attempts_exhausted() {
+    init();
+
+    // Reserve a port with no listener
+    let port = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+
+    let pipeline = gst::Pipeline::default();
+
+    let tcpclientsrc = gst::ElementFactory::make("ts-tcpclientsrc")
+        .property("port", port as i32)
+        .property("context", "test-reconnect-attempts-exhausted")
+        .property("reconnect", true)
+        .property("reconnect-delay", 10u32)
+        .property("max-reconnect-attempts", 2u32)
+        .build()
+        .unwrap();
+    let appsink = gst_app::AppSink::builder()
+        .sync(false)
+        .async_(false)
+        .build();
+
+    pipeline
+        .add_many([&tcpclientsrc, appsink.upcast_ref()])
+        .unwrap();
+    tcpclientsrc.link(&appsink).unwrap();
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+
+    let mut warnings = 0;
+    let mut error = false;
+    let bus = pipeline.bus().unwrap();
+    while let Some(msg) = bus.timed_pop(5.seconds()) {
+        use gst::MessageView;
+        match msg.view() {
+            MessageView::Warning(..) => warnings += 1,
+            MessageView::Eos(..) => panic!("Unexpected EOS"),
+            MessageView::Error(..) => {
+                error = true;
+                break;
+            }
+            _ => (),
+        }
+    }
+
+    assert!(error);
+    assert_eq!(warnings, 2);
+
+    pipeline.set_state(gst::State::Null).unwrap();
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_tls_handshake_failure() {
+    init();
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handler = thread::spawn(move || {
+        // Not a TLS server
+        let (mut socket, _) = listener.accept().unwrap();
+        let _ = socket.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+    });
+
+    let pipeline = gst::Pipeline::default();
+
+    let tcpclientsrc = gst::ElementFactory::make("ts-tcpclientsrc")
+        .property("port", port as i32)
+        .property("context", "test-tls-handshake-failure")
+        .property("tls", true)
+        .build()
+        .unwrap();
+    let appsink = gst_app::AppSink::builder()
+        .sync(false)
+        .async_(false)
+        .build();
+
+    pipeline
+        .add_many([&tcpclientsrc, appsink.upcast_ref()])
+        .unwrap();
+    tcpclientsrc.link(&appsink).unwrap();
+
+    let _ = pipeline.set_state(gst::State::Playing);
+
+    let bus = pipeline.bus().unwrap();
+    let msg = bus
+        .timed_pop_filtered(5.seconds(), &[gst::MessageType::Error])
+        .expect("Expected an error");
+    match msg.view() {
+        gst::MessageView::Error(err) => {
+            assert!(err.error().matches(gst::ResourceError::OpenRead));
+            assert!(err.debug().unwrap().contains("TLS handshake"));
+        }
+        _ => unreachable!(),
+    }
+
+    pipeline.set_state(gst::State::Null).unwrap();
+
+    handler.join().unwrap();
+}
+
+#[test]
+fn test_read_timeout() {
+    init();
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let handler = thread::spawn(move || {
+        // Accept the connection but never send anything
+        let (_socket, _) = listener.accept().unwrap();
+        let _ = done_rx.recv();
+    });
+
+    let mut h = gst_check::Harness::new("ts-tcpclientsrc");
+    {
+        let tcpclientsrc = h.element().unwrap();
+        tcpclientsrc.set_property("port", port as i32);
+        tcpclientsrc.set_property("read-timeout", 20.mseconds().nseconds());
+        tcpclientsrc.set_property("context", "test-read-timeout");
+    }
+
+    let bus = gst::Bus::new();
+    h.element().unwrap().set_bus(Some(&bus));
+
+    h.play();
+
+    // Timeout messages are posted repeatedly while no data is received
+    for _ in 0..2 {
+        let msg = bus
+            .timed_pop_filtered(
+                5.seconds(),
+                &[gst::MessageType::Element, gst::MessageType::Error],
+            )
+            .unwrap();
+        let s = msg.structure().unwrap();
+        assert_eq!(s.name(), "GstTcpClientSrcReadTimeout");
+        assert_eq!(s.get::<u64>("timeout").unwrap(), 20.mseconds().nseconds());
+    }
+
+    drop(h);
+
+    done_tx.send(()).unwrap();
+    handler.join().unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_connect_timeout() {
+    use socket2::{Domain, Socket, Type};
+
+    init();
+
+    // A listener with a full accept queue silently drops new connection requests
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener
+        .bind(
+            &"127.0.0.1:0"
+                .parse::<std::net::SocketAddr>()
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+    listener.listen(0).unwrap();
+    let saddr = listener.local_addr().unwrap().as_socket().unwrap();
+
+    let mut fillers = Vec::new();
+    for _ in 0..2 {
+        if let Ok(socket) =
+            std::net::TcpStream::connect_timeout(&saddr, time::Duration::from_millis(100))
+        {
+            fillers.push(socket);
+        }
+    }
+
+    let pipeline = gst::Pipeline::default();
+
+    let tcpclientsrc = gst::ElementFactory::make("ts-tcpclientsrc")
+        .property("port", saddr.port() as i32)
+        .property("connect-timeout", 50.mseconds().nseconds())
+        .property("context", "test-connect-timeout")
+        .build()
+        .unwrap();
+    let appsink = gst_app::AppSink::builder()
+        .sync(false)
+        .async_(false)
+        .build();
+
+    pipeline
+        .add_many([&tcpclientsrc, appsink.upcast_ref()])
+        .unwrap();
+    tcpclientsrc.link(&appsink).unwrap();
+
+    let _ = pipeline.set_state(gst::State::Playing);
+
+    let bus = pipeline.bus().unwrap();
+    let msg = bus
+        .timed_pop_filtered(5.seconds(), &[gst::MessageType::Error])
+        .expect("Expected an error");
+    match msg.view() {
+        gst::MessageView::Error(err) => {
+            assert!(err.error().matches(gst::ResourceError::OpenRead));
+            assert!(err.debug().unwrap().contains("timed out"));
+        }
+        _ => unreachable!(),
+    }
+
+    pipeline.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+fn test_connection_state() {
+    init();
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (start_tx, start_rx) = mpsc::channel::<()>();
+    let handler = thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        let _ = start_rx.recv();
+        socket.write_all(&[0; 160]).unwrap();
+    });
+
+    let pipeline = gst::Pipeline::default();
+
+    let tcpclientsrc = gst::ElementFactory::make("ts-tcpclientsrc")
+        .property("port", port as i32)
+        .property("context", "test-connection-state")
+        .build()
+        .unwrap();
+    let appsink = gst_app::AppSink::builder()
+        .sync(false)
+        .async_(false)
+        .build();
+
+    pipeline
+        .add_many([&tcpclientsrc, appsink.upcast_ref()])
+        .unwrap();
+    tcpclientsrc.link(&appsink).unwrap();
+
+    assert!(!tcpclientsrc.property::<bool>("connected"));
+
+    let notifications = Arc::new(Mutex::new(Vec::new()));
+    let notifications_clone = notifications.clone();
+    tcpclientsrc.connect_notify(Some("connected"), move |elem, _| {
+        notifications_clone
+            .lock()
+            .unwrap()
+            .push(elem.property::<bool>("connected"));
+    });
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+
+    let bus = pipeline.bus().unwrap();
+    let mut connected = false;
+    let mut disconnected = false;
+    while let Some(msg) = bus.timed_pop(5.seconds()) {
+        use gst::MessageView;
+        match msg.view() {
+            MessageView::Element(..) => {
+                let s = msg.structure().unwrap();
+                match s.name().as_str() {
+                    "GstTcpClientSrcConnected" => {
+                        assert_eq!(s.get::<&str>("address").unwrap(), "127.0.0.1");
+                        assert_eq!(s.get::<i32>("port").unwrap(), port as i32);
+                        assert_eq!(s.get::<u64>("bytes-received").unwrap(), 0);
+                        assert!(tcpclientsrc.property::<bool>("connected"));
+                        connected = true;
+
+                        // Let the server send data and close the connection
+                        start_tx.send(()).unwrap();
+                    }
+                    "GstTcpClientSrcDisconnected" => {
+                        assert!(connected);
+                        assert_eq!(s.get::<u64>("bytes-received").unwrap(), 160);
+                        disconnected = true;
+                    }
+                    _ => (),
+                }
+            }
+            MessageView::Eos(..) => break,
+            MessageView::Error(err) => panic!("{err:?}"),
+            _ => (),
+        }
+    }
+
+    assert!(connected);
+    assert!(disconnected);
+    assert!(!tcpclientsrc.property::<bool>("connected"));
+    assert_eq!(*notifications.lock().unwrap(), [true, false]);
+
+    pipeline.set_state(gst::State::Null).unwrap();
+
+    handler.join().unwrap();
+}
+
+#[test]
+fn test_local_address() {
+    init();
+
+    // Reserve a local port for the client
+    let local_port = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handler = thread::spawn(move || {
+        let (mut socket, peer_addr) = listener.accept().unwrap();
+        socket.write_all(&[0; 160]).unwrap();
+        peer_addr
+    });
+
+    let mut h = gst_check::Harness::new("ts-tcpclientsrc");
+    {
+        let tcpclientsrc = h.element().unwrap();
+        tcpclientsrc.set_property("port", port as i32);
+        tcpclientsrc.set_property("local-address", "127.0.0.1");
+        tcpclientsrc.set_property("local-port", local_port as i32);
+        tcpclientsrc.set_property("context", "test-local-address");
+    }
+
+    h.play();
+
+    let buffer = h.pull().unwrap();
+    assert_eq!(buffer.size(), 160);
+
+    let peer_addr = handler.join().unwrap();
+    assert_eq!(
+        peer_addr,
+        std::net::SocketAddr::from(([127, 0, 0, 1], local_port))
+    );
+}
+
+#[test]
+fn test_local_address_bind_failure() {
+    init();
+
+    let tcpclientsrc = gst::ElementFactory::make("ts-tcpclientsrc")
+        .property("local-address", "192.0.2.1")
+        .property("context", "test-local-address-bind-failure")
+        .build()
+        .unwrap();
+
+    let bus = gst::Bus::new();
+    tcpclientsrc.set_bus(Some(&bus));
+
+    assert!(tcpclientsrc.set_state(gst::State::Ready).is_err());
+
+    let msg = bus
+        .pop_filtered(&[gst::MessageType::Error])
+        .expect("Expected an error");
+    match msg.view() {
+        gst::MessageView::Error(err) => {
+            assert!(err.debug().unwrap().contains("192.0.2.1"));
+        }
+        _ => unreachable!(),
+    }
+
+    tcpclientsrc.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_socket_options() {
+    init();
+
+    let tcpclientsrc = gst::ElementFactory::make("ts-tcpclientsrc")
+        .build()
+        .unwrap();
+    assert!(tcpclientsrc.property::<bool>("tcp-nodelay"));
+    assert!(!tcpclientsrc.property::<bool>("keepalive"));
+    assert_eq!(tcpclientsrc.property::<u64>("keepalive-time"), 0);
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (client_addr_tx, client_addr_rx) = mpsc::channel();
+    let handler = thread::spawn(move || {
+        let (mut socket, client_addr) = listener.accept().unwrap();
+        client_addr_tx.send(client_addr).unwrap();
+        socket.write_all(&[0; 160]).unwrap();
+    });
+
+    let mut h = gst_check::Harness::new("ts-tcpclientsrc");
+    {
+        let tcpclientsrc = h.element().unwrap();
+        tcpclientsrc.set_property("port", port as i32);
+        tcpclientsrc.set_property("keepalive", true);
+        tcpclientsrc.set_property("keepalive-time", 10.seconds().nseconds());
+        tcpclientsrc.set_property("context", "test-socket-options");
+    }
+
+    h.play();
+
+    let buffer = h.pull().unwrap();
+    assert_eq!(buffer.size(), 160);
+
+    let client_addr = client_addr_rx.recv().unwrap();
+    handler.join().unwrap();
+
+    // Look up the element's socket among the process file descriptors
+    let fds = std::fs::read_dir("/proc/self/fd")
+        .unwrap()
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .map(|fd| {
+            // SAFETY: the element's socket stays open while it is playing
+            // and the other file descriptors are only queried for failure.
+            unsafe { std::os::fd::BorrowedFd::borrow_raw(fd) }
+        })
+        .collect::<Vec<_>>();
+    let socket = fds
+        .iter()
+        .map(socket2::SockRef::from)
+        .find(|socket| {
+            socket.local_addr().ok().and_then(|addr| addr.as_socket()) == Some(client_addr)
+        })
+        .expect("client socket not found");
+
+    assert!(socket.nodelay().unwrap());
+    assert!(socket.keepalive().unwrap());
+    assert_eq!(
+        socket.keepalive_time().unwrap(),
+        std::time::Duration::from_secs(10)
+    );
+
+    h.element().unwrap().set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+fn test_host_name() {
+    init();
+
+    // "localhost" might also resolve to "::1" on which nothing is listening
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handler = thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        socket.write_all(&[0; 160]).unwrap();
+    });
+
+    let mut h = gst_check::Harness::new("ts-tcpclientsrc");
+    {
+        let tcpclientsrc = h.element().unwrap();
+        tcpclientsrc.set_property("host", "localhost");
+        tcpclientsrc.set_property("port", port as i32);
+        tcpclientsrc.set_property("context", "test-host-name");
+    }
+
+    let bus = gst::Bus::new();
+    h.element().unwrap().set_bus(Some(&bus));
+
+    h.play();
+
+    let buffer = h.pull().unwrap();
+    assert_eq!(buffer.size(), 160);
+
+    let msg = bus
+        .pop_filtered(&[gst::MessageType::Element])
+        .expect("Expected a connection message");
+    let s = msg.structure().unwrap();
+    assert_eq!(s.name(), "GstTcpClientSrcConnected");
+    assert_eq!(s.get::<&str>("address").unwrap(), "127.0.0.1");
+    assert_eq!(s.get::<i32>("port").unwrap(), port as i32);
+
+    handler.join().unwrap();
+}
@@ -0,0 +1,140 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use gst::prelude::*;
+
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstthreadshare::plugin_register_static().expect("gstthreadshare tcpserversrc test");
+    });
+}
+
+fn build_pipeline(
+    context: &str,
+    eos_on_disconnect: bool,
+) -> (gst::Pipeline, gst::Element, Arc<Mutex<Vec<u8>>>) {
+    let pipeline = gst::Pipeline::default();
+
+    let tcpserversrc = gst::ElementFactory::make("ts-tcpserversrc")
+        .property("port", 0i32)
+        .property("context", context)
+        .property("eos-on-disconnect", eos_on_disconnect)
+        .build()
+        .unwrap();
+    let appsink = gst_app::AppSink::builder()
+        .sync(false)
+        .async_(false)
+        .build();
+
+    pipeline
+        .add_many([&tcpserversrc, appsink.upcast_ref()])
+        .unwrap();
+    tcpserversrc.link(&appsink).unwrap();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let received_clone = received.clone();
+    appsink.set_callbacks(
+        gst_app::AppSinkCallbacks::builder()
+            .new_sample(move |appsink| {
+                let sample = appsink.pull_sample().unwrap();
+                let map = sample.buffer().unwrap().map_readable().unwrap();
+                received_clone.lock().unwrap().extend_from_slice(&map);
+                Ok(gst::FlowSuccess::Ok)
+            })
+            .build(),
+    );
+
+    (pipeline, tcpserversrc, received)
+}
+
+#[test]
+fn test_push() {
+    init();
+
+    let (pipeline, tcpserversrc, received) = build_pipeline("test-tcpserversrc-push", true);
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+    let port = tcpserversrc.property::<i32>("current-port");
+    assert_ne!(port, 0);
+
+    let mut client = TcpStream::connect(("127.0.0.1", port as u16)).unwrap();
+    client.write_all(&[1u8; 160]).unwrap();
+    drop(client);
+
+    let bus = pipeline.bus().unwrap();
+    let msg = bus
+        .timed_pop_filtered(
+            gst::ClockTime::from_seconds(5),
+            &[gst::MessageType::Eos, gst::MessageType::Error],
+        )
+        .unwrap();
+    assert_eq!(msg.type_(), gst::MessageType::Eos);
+
+    assert_eq!(*received.lock().unwrap(), vec![1u8; 160]);
+
+    pipeline.set_state(gst::State::Null).unwrap();
+    assert_eq!(tcpserversrc.property::<i32>("current-port"), 0);
+}
+
+#[test]
+fn test_next_client() {
+    init();
+
+    let (pipeline, tcpserversrc, received) = build_pipeline("test-tcpserversrc-next-client", false);
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+    let port = tcpserversrc.property::<i32>("current-port");
+
+    for val in 1..=2u8 {
+        let mut client = TcpStream::connect(("127.0.0.1", port as u16)).unwrap();
+        client.write_all(&[val; 100]).unwrap();
+        drop(client);
+
+        // Wait for the data before connecting the next client
+        let expected = 100 * val as usize;
+        for _ in 0..500 {
+            if received.lock().unwrap().len() >= expected {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(received.lock().unwrap().len(), expected);
+    }
+
+    // No EOS when the clients disconnect
+    let bus = pipeline.bus().unwrap();
+    assert!(bus
+        .pop_filtered(&[gst::MessageType::Eos, gst::MessageType::Error])
+        .is_none());
+
+    let received = received.lock().unwrap();
+    assert_eq!(received[..100], [1u8; 100]);
+    assert_eq!(received[100..], [2u8; 100]);
+    drop(received);
+
+    pipeline.set_state(gst::State::Null).unwrap();
+}
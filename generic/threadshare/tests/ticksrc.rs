@@ -0,0 +1,114 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+use gst::glib;
+use gst::prelude::*;
+
+use std::time::Duration;
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstthreadshare::plugin_register_static().expect("gstthreadshare ticksrc test");
+    });
+}
+
+fn pull_event(h: &mut gst_check::Harness, event_type: gst::EventType) -> gst::Event {
+    loop {
+        let event = h.pull_event().unwrap();
+        if event.type_() == event_type {
+            return event;
+        }
+    }
+}
+
+#[test]
+fn test_buffer_payload() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-ticksrc");
+    let ticksrc = h.element().unwrap();
+    ticksrc.set_property("context", "ticksrc::test_buffer_payload");
+    ticksrc.set_property("interval", 10u32);
+    ticksrc.set_property("payload", glib::Bytes::from_static(b"tick"));
+
+    h.play();
+
+    let mut last_pts = None;
+    for offset in 0..3 {
+        let buffer = h.pull().unwrap();
+        assert_eq!(buffer.map_readable().unwrap().as_slice(), b"tick");
+        assert_eq!(buffer.offset(), offset);
+        assert_eq!(buffer.duration(), Some(gst::ClockTime::from_mseconds(10)));
+        assert!(buffer.pts().is_some());
+        assert!(buffer.pts() >= last_pts);
+        last_pts = buffer.pts();
+    }
+}
+
+#[test]
+fn test_gap() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-ticksrc");
+    let ticksrc = h.element().unwrap();
+    ticksrc.set_property("context", "ticksrc::test_gap");
+    ticksrc.set_property("interval", 10u32);
+    ticksrc.set_property_from_str("event-mode", "gap");
+
+    h.play();
+
+    let event = pull_event(&mut h, gst::EventType::Gap);
+    let gst::EventView::Gap(gap) = event.view() else {
+        unreachable!();
+    };
+    assert_eq!(gap.get().1, Some(gst::ClockTime::from_mseconds(10)));
+
+    assert!(h.try_pull().is_none());
+}
+
+#[test]
+fn test_custom_event_and_pause() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-ticksrc");
+    let ticksrc = h.element().unwrap();
+    ticksrc.set_property("context", "ticksrc::test_custom_event_and_pause");
+    ticksrc.set_property("interval", 10u32);
+    ticksrc.set_property_from_str("event-mode", "custom");
+
+    h.play();
+
+    for count in 0..2u64 {
+        let event = pull_event(&mut h, gst::EventType::CustomDownstream);
+        let s = event.structure().unwrap();
+        assert_eq!(s.name(), "ts-tick");
+        assert_eq!(s.get::<u64>("count").unwrap(), count);
+    }
+
+    ticksrc.set_state(gst::State::Paused).unwrap();
+    // Drain ticks which might have been emitted before pausing
+    while h.try_pull_event().is_some() {}
+
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(h.try_pull_event().is_none());
+}
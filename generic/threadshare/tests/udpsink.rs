@@ -105,3 +105,541 @@ fn test_chain() {
     let buf = gst::Buffer::from_slice([42, 43, 44, 45]);
     assert!(h.push(buf) == Ok(gst::FlowSuccess::Ok));
 }
+
+#[test]
+fn test_multiple_clients() {
+    init();
+
+    let receivers = (0..3)
+        .map(|_| {
+            let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket
+                .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+                .unwrap();
+            socket
+        })
+        .collect::<Vec<_>>();
+    let ports = receivers
+        .iter()
+        .map(|socket| socket.local_addr().unwrap().port())
+        .collect::<Vec<_>>();
+
+    let mut h = gst_check::Harness::new("ts-udpsink");
+    h.set_src_caps_str("foo/bar");
+    {
+        let udpsink = h.element().unwrap();
+        udpsink.set_property("sync", false);
+        udpsink.set_property(
+            "clients",
+            format!("127.0.0.1:{},127.0.0.1:{}", ports[0], ports[1]),
+        );
+        udpsink.set_property("context", "test-multiple-clients");
+    }
+    h.play();
+
+    let recv = |socket: &std::net::UdpSocket| {
+        let mut buf = [0; 8];
+        let len = socket.recv(&mut buf).unwrap();
+        buf[..len].to_vec()
+    };
+
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([1, 2, 3, 4])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    assert_eq!(recv(&receivers[0]), [1, 2, 3, 4]);
+    assert_eq!(recv(&receivers[1]), [1, 2, 3, 4]);
+
+    // Clients can be changed while playing
+    let udpsink = h.element().unwrap();
+    udpsink.emit_by_name::<()>("add", &[&"127.0.0.1", &(ports[2] as i32)]);
+    udpsink.emit_by_name::<()>("remove", &[&"127.0.0.1", &(ports[0] as i32)]);
+
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([5, 6, 7])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    assert_eq!(recv(&receivers[1]), [5, 6, 7]);
+    assert_eq!(recv(&receivers[2]), [5, 6, 7]);
+
+    receivers[0].set_nonblocking(true).unwrap();
+    assert!(receivers[0].recv(&mut [0; 8]).is_err());
+}
+
+#[test]
+fn test_sync() {
+    init();
+
+    let receiver = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver
+        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .unwrap();
+    let port = receiver.local_addr().unwrap().port();
+
+    let mut h = gst_check::Harness::new("ts-udpsink");
+    h.use_systemclock();
+    h.set_src_caps_str("foo/bar");
+    let udpsink = h.element().unwrap();
+    udpsink.set_property("clients", format!("127.0.0.1:{port}"));
+    udpsink.set_property("context", "test-sync");
+    h.play();
+    udpsink.send_event(gst::event::Latency::new(gst::ClockTime::ZERO));
+
+    // Late buffers are sent immediately and counted
+    let mut buffer = gst::Buffer::from_slice([1]);
+    buffer.get_mut().unwrap().set_pts(gst::ClockTime::ZERO);
+    assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+    receiver.recv(&mut [0; 8]).unwrap();
+    let stats = udpsink.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("late-buffers").unwrap(), 1);
+
+    // Delay rendering using the ts-offset
+    let now = udpsink.current_running_time().unwrap();
+    let offset = gst::ClockTime::from_mseconds(200);
+    udpsink.set_property("ts-offset", (now + offset).nseconds() as i64);
+
+    let start = std::time::Instant::now();
+    let mut buffer = gst::Buffer::from_slice([2]);
+    buffer.get_mut().unwrap().set_pts(gst::ClockTime::ZERO);
+    assert_eq!(h.push(buffer), Ok(gst::FlowSuccess::Ok));
+    receiver.recv(&mut [0; 8]).unwrap();
+    assert!(start.elapsed() >= std::time::Duration::from_millis(150));
+
+    let stats = udpsink.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("late-buffers").unwrap(), 1);
+}
+
+#[test]
+fn test_ttl() {
+    use gio::prelude::*;
+
+    init();
+
+    let h = gst_check::Harness::new("ts-udpsink");
+    let udpsink = h.element().unwrap();
+    udpsink.set_property("ttl", 10u32);
+    udpsink.set_property("ttl-mc", 3u32);
+    udpsink.set_property("context", "test-ttl");
+    udpsink.set_state(gst::State::Ready).unwrap();
+
+    let sockets = ["used-socket", "used-socket-v6"]
+        .into_iter()
+        .filter_map(|name| udpsink.property::<Option<gio::Socket>>(name))
+        .collect::<Vec<_>>();
+    assert!(!sockets.is_empty());
+
+    for socket in &sockets {
+        assert_eq!(socket.ttl(), 10);
+        assert_eq!(socket.multicast_ttl(), 3);
+    }
+
+    // Changes are applied to the prepared sockets
+    udpsink.set_property("ttl", 20u32);
+    udpsink.set_property("ttl-mc", 1u32);
+    for socket in &sockets {
+        assert_eq!(socket.ttl(), 20);
+        assert_eq!(socket.multicast_ttl(), 1);
+    }
+
+    udpsink.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+fn test_bind() {
+    init();
+
+    let receiver = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver
+        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .unwrap();
+    let port = receiver.local_addr().unwrap().port();
+
+    let mut h = gst_check::Harness::new("ts-udpsink");
+    h.set_src_caps_str("foo/bar");
+    let udpsink = h.element().unwrap();
+    udpsink.set_property("sync", false);
+    udpsink.set_property("bind-address", "127.0.0.1");
+    udpsink.set_property("bind-port", 0i32);
+    udpsink.set_property("clients", format!("127.0.0.1:{port}"));
+    udpsink.set_property("context", "test-bind");
+    assert_eq!(udpsink.property::<i32>("used-bind-port"), 0);
+    h.play();
+
+    let used_bind_port = udpsink.property::<i32>("used-bind-port");
+    assert_ne!(used_bind_port, 0);
+    // The requested port is left untouched
+    assert_eq!(udpsink.property::<i32>("bind-port"), 0);
+
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([1, 2])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    let (_, saddr) = receiver.recv_from(&mut [0; 8]).unwrap();
+    assert_eq!(
+        saddr,
+        format!("127.0.0.1:{used_bind_port}").parse().unwrap()
+    );
+
+    udpsink.set_state(gst::State::Null).unwrap();
+    assert_eq!(udpsink.property::<i32>("used-bind-port"), 0);
+
+    // Binding to a port already in use fails the transition to READY
+    udpsink.set_property("bind-port", port as i32);
+    assert!(udpsink.set_state(gst::State::Ready).is_err());
+    udpsink.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+fn test_buffer_list() {
+    init();
+
+    let receiver = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver
+        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .unwrap();
+    let port = receiver.local_addr().unwrap().port();
+
+    let mut h = gst_check::Harness::new("ts-udpsink");
+    h.set_src_caps_str("foo/bar");
+    {
+        let udpsink = h.element().unwrap();
+        udpsink.set_property("sync", false);
+        udpsink.set_property("clients", format!("127.0.0.1:{port}"));
+        udpsink.set_property("context", "test-buffer-list");
+    }
+    h.play();
+
+    let mut list = gst::BufferList::new();
+    {
+        let list = list.get_mut().unwrap();
+        for i in 0..3u8 {
+            list.add(gst::Buffer::from_slice([i; 4]));
+        }
+    }
+    assert_eq!(
+        h.srcpad().unwrap().push_list(list),
+        Ok(gst::FlowSuccess::Ok)
+    );
+
+    for i in 0..3u8 {
+        let mut buf = [0; 8];
+        let len = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], &[i; 4]);
+    }
+
+    let stats = h.element().unwrap().property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("send-errors").unwrap(), 0);
+}
+
+#[test]
+fn test_max_bitrate() {
+    init();
+
+    let receiver = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = receiver.local_addr().unwrap().port();
+
+    let mut h = gst_check::Harness::new("ts-udpsink");
+    h.set_src_caps_str("foo/bar");
+    {
+        let udpsink = h.element().unwrap();
+        udpsink.set_property("sync", false);
+        // 10 kB/s
+        udpsink.set_property("max-bitrate", 80_000u64);
+        udpsink.set_property("clients", format!("127.0.0.1:{port}"));
+        udpsink.set_property("context", "test-max-bitrate");
+    }
+    h.play();
+
+    let start = std::time::Instant::now();
+    for _ in 0..10 {
+        assert_eq!(
+            h.push(gst::Buffer::from_slice([0; 500])),
+            Ok(gst::FlowSuccess::Ok)
+        );
+    }
+
+    // 5 kB need about 500ms
+    assert!(start.elapsed() >= std::time::Duration::from_millis(400));
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_socket_from_udpsrc() {
+    init();
+
+    let udpsrc = gst::ElementFactory::make("ts-udpsrc")
+        .property("address", "127.0.0.1")
+        .property("port", 0i32)
+        .property("context", "test-socket-from-udpsrc")
+        .build()
+        .unwrap();
+    udpsrc.set_state(gst::State::Ready).unwrap();
+    let src_port = udpsrc.property::<i32>("port") as u16;
+    let socket = udpsrc.property::<gio::Socket>("used-socket");
+
+    let receiver = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver
+        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .unwrap();
+    let port = receiver.local_addr().unwrap().port();
+
+    let mut h = gst_check::Harness::new("ts-udpsink");
+    h.set_src_caps_str("foo/bar");
+    let udpsink = h.element().unwrap();
+    udpsink.set_property("sync", false);
+    udpsink.set_property("socket", &socket);
+    // The socket is still used by the udpsrc
+    udpsink.set_property("close-socket", false);
+    udpsink.set_property("clients", format!("127.0.0.1:{port}"));
+    udpsink.set_property("context", "test-socket-from-udpsrc");
+    h.play();
+
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([1, 2, 3])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+
+    // Packets leave from the udpsrc port
+    let (_, saddr) = receiver.recv_from(&mut [0; 8]).unwrap();
+    assert_eq!(saddr.port(), src_port);
+
+    udpsink.set_state(gst::State::Null).unwrap();
+    assert!(udpsink
+        .property::<Option<gio::Socket>>("used-socket")
+        .is_none());
+    {
+        use gio::prelude::*;
+        assert!(!socket.is_closed());
+    }
+
+    udpsrc.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_on_send_error() {
+    init();
+
+    for on_send_error in ["error", "warn", "ignore"] {
+        let mut h = gst_check::Harness::new("ts-udpsink");
+        h.set_src_caps_str("foo/bar");
+        let udpsink = h.element().unwrap();
+        let bus = gst::Bus::new();
+        udpsink.set_bus(Some(&bus));
+        udpsink.set_property("sync", false);
+        udpsink.set_property_from_str("on-send-error", on_send_error);
+        // Sending to broadcast fails without SO_BROADCAST
+        udpsink.set_property("clients", "255.255.255.255:5004");
+        udpsink.set_property("context", "test-on-send-error");
+        h.play();
+
+        for _ in 0..2 {
+            let res = h.push(gst::Buffer::from_slice([0; 4]));
+            if on_send_error == "error" {
+                assert_eq!(res, Err(gst::FlowError::Error));
+            } else {
+                assert_eq!(res, Ok(gst::FlowSuccess::Ok));
+            }
+        }
+
+        let stats = udpsink.property::<gst::Structure>("stats");
+        assert_eq!(stats.get::<u64>("send-errors").unwrap(), 2);
+
+        let msg_types = [gst::MessageType::Error, gst::MessageType::Warning];
+        let mut msgs = std::iter::from_fn(|| bus.pop_filtered(&msg_types)).collect::<Vec<_>>();
+        match on_send_error {
+            "error" => assert_eq!(msgs.remove(0).type_(), gst::MessageType::Error),
+            // Only one warning until a send succeeds again
+            "warn" => {
+                assert_eq!(msgs.len(), 1);
+                assert_eq!(msgs[0].type_(), gst::MessageType::Warning);
+            }
+            _ => assert!(msgs.is_empty()),
+        }
+
+        udpsink.set_state(gst::State::Null).unwrap();
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_multicast_iface() {
+    use gio::prelude::*;
+
+    init();
+
+    let h = gst_check::Harness::new("ts-udpsink");
+    let udpsink = h.element().unwrap();
+    let bus = gst::Bus::new();
+    udpsink.set_bus(Some(&bus));
+    udpsink.set_property("multicast-iface", "lo");
+    udpsink.set_property("context", "test-multicast-iface");
+    udpsink.set_state(gst::State::Ready).unwrap();
+
+    let socket = udpsink.property::<gio::Socket>("used-socket");
+    // IP_MULTICAST_IF
+    let iface = socket
+        .option(libc::IPPROTO_IP, libc::IP_MULTICAST_IF)
+        .unwrap();
+    assert_eq!(iface, i32::from_ne_bytes([127, 0, 0, 1]));
+
+    // Unknown interfaces keep the previous setting
+    udpsink.set_property("multicast-iface", "doesnotexist0");
+    let msg = bus.pop_filtered(&[gst::MessageType::Warning]).unwrap();
+    assert_eq!(msg.type_(), gst::MessageType::Warning);
+    let iface = socket
+        .option(libc::IPPROTO_IP, libc::IP_MULTICAST_IF)
+        .unwrap();
+    assert_eq!(iface, i32::from_ne_bytes([127, 0, 0, 1]));
+
+    udpsink.set_state(gst::State::Null).unwrap();
+
+    // Unknown interfaces fail the preparation
+    assert!(udpsink.set_state(gst::State::Ready).is_err());
+    udpsink.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_multicast_shared_socket() {
+    init();
+
+    let group = std::net::Ipv4Addr::new(224, 3, 3, 3);
+
+    // The udpsrc owns the socket and its membership
+    let mut src_h = gst_check::Harness::new("ts-udpsrc");
+    let udpsrc = src_h.element().unwrap();
+    udpsrc.set_property("address", group.to_string());
+    udpsrc.set_property("port", 0i32);
+    udpsrc.set_property("multicast-iface", "lo");
+    udpsrc.set_property("context", "test-multicast-shared-socket");
+    src_h.play();
+    let port = udpsrc.property::<i32>("port") as u16;
+    let socket = udpsrc.property::<gio::Socket>("used-socket");
+
+    let mut h = gst_check::Harness::new("ts-udpsink");
+    h.set_src_caps_str("foo/bar");
+    let udpsink = h.element().unwrap();
+    udpsink.set_property("sync", false);
+    udpsink.set_property("socket", &socket);
+    udpsink.set_property("close-socket", false);
+    udpsink.set_property("multicast-iface", "lo");
+    udpsink.set_property("clients", format!("{group}:{port}"));
+    udpsink.set_property("context", "test-multicast-shared-socket");
+    // The group was already joined by the udpsrc
+    h.play();
+
+    assert_eq!(
+        h.push(gst::Buffer::from_slice([1, 2, 3])),
+        Ok(gst::FlowSuccess::Ok)
+    );
+    let buffer = src_h.pull().unwrap();
+    assert_eq!(buffer.map_readable().unwrap().as_slice(), [1, 2, 3]);
+
+    // Neither changing the interface nor stopping the udpsink
+    // drops the membership on lo
+    udpsink.set_property("multicast-iface", None::<&str>);
+    udpsink.set_state(gst::State::Null).unwrap();
+
+    let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket2::SockRef::from(&sender)
+        .set_multicast_if_v4(&std::net::Ipv4Addr::LOCALHOST)
+        .unwrap();
+    sender.send_to(&[4, 5, 6], (group, port)).unwrap();
+    let buffer = src_h.pull().unwrap();
+    assert_eq!(buffer.map_readable().unwrap().as_slice(), [4, 5, 6]);
+
+    udpsrc.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+fn test_client_stats() {
+    init();
+
+    let receivers = (0..2)
+        .map(|_| std::net::UdpSocket::bind("127.0.0.1:0").unwrap())
+        .collect::<Vec<_>>();
+    let ports = receivers
+        .iter()
+        .map(|socket| socket.local_addr().unwrap().port())
+        .collect::<Vec<_>>();
+
+    let mut h = gst_check::Harness::new("ts-udpsink");
+    h.set_src_caps_str("foo/bar");
+    let udpsink = h.element().unwrap();
+    udpsink.set_property("sync", false);
+    udpsink.set_property(
+        "clients",
+        format!("127.0.0.1:{},127.0.0.1:{}", ports[0], ports[1]),
+    );
+    udpsink.set_property("context", "test-client-stats");
+    h.play();
+
+    for size in [10, 20] {
+        assert_eq!(
+            h.push(gst::Buffer::from_mut_slice(vec![0; size])),
+            Ok(gst::FlowSuccess::Ok)
+        );
+    }
+
+    let client_stats = |udpsink: &gst::Element| {
+        udpsink
+            .property::<gst::Structure>("stats")
+            .get::<gst::Array>("clients")
+            .unwrap()
+            .iter()
+            .map(|value| value.get::<gst::Structure>().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let stats = client_stats(&udpsink);
+    assert_eq!(stats.len(), 2);
+    for (client, port) in stats.iter().zip(&ports) {
+        assert_eq!(client.get::<String>("host").unwrap(), "127.0.0.1");
+        assert_eq!(client.get::<i32>("port").unwrap(), *port as i32);
+        assert_eq!(client.get::<u64>("packets-sent").unwrap(), 2);
+        assert_eq!(client.get::<u64>("bytes-sent").unwrap(), 30);
+        assert_eq!(client.get::<u64>("send-errors").unwrap(), 0);
+        assert!(client
+            .get::<Option<gst::ClockTime>>("last-send-time")
+            .unwrap()
+            .is_some());
+    }
+
+    udpsink.emit_by_name::<()>("remove", &[&"127.0.0.1", &(ports[0] as i32)]);
+    assert_eq!(client_stats(&udpsink).len(), 1);
+
+    udpsink.set_state(gst::State::Null).unwrap();
+    let stats = client_stats(&udpsink);
+    assert_eq!(stats[0].get::<u64>("packets-sent").unwrap(), 0);
+    assert_eq!(stats[0].get::<u64>("bytes-sent").unwrap(), 0);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_multicast_loop() {
+    use gio::prelude::*;
+
+    init();
+
+    let h = gst_check::Harness::new("ts-udpsink");
+    let udpsink = h.element().unwrap();
+    udpsink.set_property("loop", false);
+    udpsink.set_property("context", "test-multicast-loop");
+    udpsink.set_state(gst::State::Ready).unwrap();
+
+    let socket = udpsink.property::<gio::Socket>("used-socket");
+    assert!(!socket.is_multicast_loopback());
+    let socket_v6 = udpsink.property::<Option<gio::Socket>>("used-socket-v6");
+    if let Some(ref socket_v6) = socket_v6 {
+        assert!(!socket_v6.is_multicast_loopback());
+    }
+
+    udpsink.set_property("loop", true);
+    assert!(socket.is_multicast_loopback());
+    if let Some(ref socket_v6) = socket_v6 {
+        assert!(socket_v6.is_multicast_loopback());
+    }
+
+    udpsink.set_state(gst::State::Null).unwrap();
+}
@@ -159,3 +159,580 @@ fn test_socket_reuse() {
         assert_eq!(buffer.size(), 160);
     }
 }
+
+#[test]
+#[cfg(not(windows))]
+fn test_close_socket() {
+    init();
+
+    for close_socket in [false, true] {
+        let socket = gio::Socket::new(
+            gio::SocketFamily::Ipv4,
+            gio::SocketType::Datagram,
+            gio::SocketProtocol::Udp,
+        )
+        .unwrap();
+        let addr = gio::InetSocketAddress::from_string("127.0.0.1", 0).unwrap();
+        socket.bind(&addr, true).unwrap();
+
+        let mut h = gst_check::Harness::new("ts-udpsrc");
+        {
+            let udpsrc = h.element().unwrap();
+            udpsrc.set_property("socket", &socket);
+            udpsrc.set_property("close-socket", close_socket);
+            udpsrc.set_property("context", "test-close-socket");
+        }
+        h.play();
+
+        let udpsrc = h.element().unwrap();
+        assert!(udpsrc
+            .property::<Option<gio::Socket>>("used-socket")
+            .is_some());
+
+        udpsrc.set_state(gst::State::Null).unwrap();
+        assert!(udpsrc
+            .property::<Option<gio::Socket>>("used-socket")
+            .is_none());
+
+        assert_eq!(socket.is_closed(), close_socket);
+    }
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_used_socket_any_port() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-udpsrc");
+
+    let (notify_tx, notify_rx) = std::sync::mpsc::channel();
+    {
+        let udpsrc = h.element().unwrap();
+        udpsrc.set_property("address", "127.0.0.1");
+        udpsrc.set_property("port", 0i32);
+        udpsrc.set_property("context", "test-used-socket-any-port");
+
+        udpsrc.connect_notify(None, move |_, pspec| {
+            notify_tx.send(pspec.name().to_string()).unwrap();
+        });
+    }
+
+    assert!(h
+        .element()
+        .unwrap()
+        .property::<Option<gio::Socket>>("used-socket")
+        .is_none());
+
+    h.play();
+
+    let notified = notify_rx.try_iter().collect::<Vec<_>>();
+    assert!(notified.iter().any(|name| name == "port"));
+    assert!(notified.iter().any(|name| name == "used-socket"));
+
+    let udpsrc = h.element().unwrap();
+    let port = udpsrc.property::<i32>("port");
+    assert_ne!(port, 0);
+
+    let socket = udpsrc.property::<gio::Socket>("used-socket");
+    let local_addr = socket
+        .local_address()
+        .unwrap()
+        .downcast::<gio::InetSocketAddress>()
+        .unwrap();
+    assert_eq!(local_addr.port() as i32, port);
+
+    // Make sure data is received on the assigned port
+    let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender
+        .send_to(&[0; 160], ("127.0.0.1", port as u16))
+        .unwrap();
+
+    let buffer = h.pull().unwrap();
+    assert_eq!(buffer.size(), 160);
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_retrieve_sender_address() {
+    use gst_net::NetAddressMeta;
+
+    init();
+
+    for retrieve_sender_address in [true, false] {
+        let mut h = gst_check::Harness::new("ts-udpsrc");
+        {
+            let udpsrc = h.element().unwrap();
+            udpsrc.set_property("address", "127.0.0.1");
+            udpsrc.set_property("port", 0i32);
+            udpsrc.set_property("retrieve-sender-address", retrieve_sender_address);
+            udpsrc.set_property("context", "test-retrieve-sender-address");
+        }
+        h.play();
+
+        let port = h.element().unwrap().property::<i32>("port") as u16;
+
+        let sender1 = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let sender2 = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        sender1.send_to(&[1; 16], ("127.0.0.1", port)).unwrap();
+        let buffer1 = h.pull().unwrap();
+        sender2.send_to(&[2; 16], ("127.0.0.1", port)).unwrap();
+        let buffer2 = h.pull().unwrap();
+
+        if !retrieve_sender_address {
+            assert!(buffer1.meta::<NetAddressMeta>().is_none());
+            assert!(buffer2.meta::<NetAddressMeta>().is_none());
+            continue;
+        }
+
+        let sender_port = |buffer: &gst::Buffer| {
+            buffer
+                .meta::<NetAddressMeta>()
+                .unwrap()
+                .addr()
+                .downcast::<gio::InetSocketAddress>()
+                .unwrap()
+                .port()
+        };
+
+        assert_eq!(sender_port(&buffer1), sender1.local_addr().unwrap().port());
+        assert_eq!(sender_port(&buffer2), sender2.local_addr().unwrap().port());
+        assert_ne!(sender_port(&buffer1), sender_port(&buffer2));
+    }
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_ipv6() {
+    init();
+
+    // Skip if IPv6 is not available on this host
+    let Ok(sender) = std::net::UdpSocket::bind("[::1]:0") else {
+        return;
+    };
+
+    for address in ["::1", "[::1]"] {
+        let mut h = gst_check::Harness::new("ts-udpsrc");
+        {
+            let udpsrc = h.element().unwrap();
+            udpsrc.set_property("address", address);
+            udpsrc.set_property("port", 0i32);
+            udpsrc.set_property("context", "test-ipv6");
+        }
+        h.play();
+
+        let port = h.element().unwrap().property::<i32>("port") as u16;
+
+        sender.send_to(&[0; 160], ("::1", port)).unwrap();
+
+        let buffer = h.pull().unwrap();
+        assert_eq!(buffer.size(), 160);
+    }
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_timeout() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-udpsrc");
+    {
+        let udpsrc = h.element().unwrap();
+        udpsrc.set_property("address", "127.0.0.1");
+        udpsrc.set_property("port", 0i32);
+        udpsrc.set_property("timeout", gst::ClockTime::from_mseconds(20).nseconds());
+        udpsrc.set_property("context", "test-timeout");
+    }
+
+    let bus = gst::Bus::new();
+    h.element().unwrap().set_bus(Some(&bus));
+
+    h.play();
+
+    // Timeout messages are posted repeatedly while no data is received
+    for _ in 0..2 {
+        let msg = bus
+            .timed_pop_filtered(
+                gst::ClockTime::from_seconds(5),
+                &[gst::MessageType::Element],
+            )
+            .unwrap();
+        let s = msg.structure().unwrap();
+        assert_eq!(s.name(), "GstUDPSrcTimeout");
+        assert_eq!(
+            s.get::<u64>("timeout").unwrap(),
+            gst::ClockTime::from_mseconds(20).nseconds()
+        );
+    }
+
+    // Data is still received after a timeout
+    let port = h.element().unwrap().property::<i32>("port") as u16;
+    let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender.send_to(&[0; 160], ("127.0.0.1", port)).unwrap();
+
+    let buffer = h.pull().unwrap();
+    assert_eq!(buffer.size(), 160);
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_mtu_truncation() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-udpsrc");
+    {
+        let udpsrc = h.element().unwrap();
+        udpsrc.set_property("address", "127.0.0.1");
+        udpsrc.set_property("port", 0i32);
+        udpsrc.set_property("mtu", 100u32);
+        udpsrc.set_property("context", "test-mtu-truncation");
+    }
+    h.play();
+
+    let udpsrc = h.element().unwrap();
+    let port = udpsrc.property::<i32>("port") as u16;
+    let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    // Packet fitting the mtu
+    sender.send_to(&[0; 100], ("127.0.0.1", port)).unwrap();
+    let buffer = h.pull().unwrap();
+    assert_eq!(buffer.size(), 100);
+    assert_eq!(udpsrc.property::<u64>("truncated-packets"), 0);
+
+    // Packets larger than the mtu
+    for i in 1..=2 {
+        sender.send_to(&[0; 160], ("127.0.0.1", port)).unwrap();
+        let buffer = h.pull().unwrap();
+        assert_eq!(buffer.size(), 100);
+        assert_eq!(udpsrc.property::<u64>("truncated-packets"), i);
+    }
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_stats() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-udpsrc");
+    {
+        let udpsrc = h.element().unwrap();
+        udpsrc.set_property("address", "127.0.0.1");
+        udpsrc.set_property("port", 0i32);
+        udpsrc.set_property("mtu", 100u32);
+        udpsrc.set_property("context", "test-stats");
+    }
+    h.play();
+
+    let udpsrc = h.element().unwrap();
+    let stats = udpsrc.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("packets-received").unwrap(), 0);
+    assert!(stats
+        .get::<Option<gst::ClockTime>>("last-packet-time")
+        .unwrap()
+        .is_none());
+
+    let port = udpsrc.property::<i32>("port") as u16;
+    let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender.send_to(&[0; 40], ("127.0.0.1", port)).unwrap();
+    let _ = h.pull().unwrap();
+    sender.send_to(&[0; 160], ("127.0.0.1", port)).unwrap();
+    let buffer = h.pull().unwrap();
+
+    let stats = udpsrc.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("packets-received").unwrap(), 2);
+    assert_eq!(stats.get::<u64>("bytes-received").unwrap(), 140);
+    assert_eq!(stats.get::<u64>("packets-truncated").unwrap(), 1);
+    assert_eq!(
+        stats
+            .get::<Option<gst::ClockTime>>("last-packet-time")
+            .unwrap(),
+        buffer.dts()
+    );
+
+    udpsrc.set_state(gst::State::Null).unwrap();
+    let stats = udpsrc.property::<gst::Structure>("stats");
+    assert_eq!(stats.get::<u64>("packets-received").unwrap(), 0);
+    assert_eq!(stats.get::<u64>("bytes-received").unwrap(), 0);
+    assert_eq!(stats.get::<u64>("packets-truncated").unwrap(), 0);
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_batch_size() {
+    init();
+
+    let mut h = gst_check::Harness::new("ts-udpsrc");
+    let udpsrc = h.element().unwrap();
+    udpsrc.set_property("address", "127.0.0.1");
+    udpsrc.set_property("port", 0i32);
+    udpsrc.set_property("batch-size", 4u32);
+    udpsrc.set_property("context", "test-batch-size");
+
+    let list_lens = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    udpsrc
+        .static_pad("src")
+        .unwrap()
+        .add_probe(gst::PadProbeType::BUFFER_LIST, {
+            let list_lens = list_lens.clone();
+            move |_, info| {
+                if let Some(gst::PadProbeData::BufferList(list)) = &info.data {
+                    list_lens.lock().unwrap().push(list.len());
+                }
+                gst::PadProbeReturn::Ok
+            }
+        });
+
+    // The socket is bound in READY: queue the packets before the task starts
+    udpsrc.set_state(gst::State::Ready).unwrap();
+    let port = udpsrc.property::<i32>("port") as u16;
+    let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    for i in 0..6u8 {
+        sender.send_to(&[i; 10], ("127.0.0.1", port)).unwrap();
+    }
+
+    h.play();
+
+    for i in 0..6u8 {
+        let buffer = h.pull().unwrap();
+        assert_eq!(buffer.map_readable().unwrap().as_slice(), &[i; 10]);
+        let meta = buffer.meta::<gst_net::NetAddressMeta>().unwrap();
+        assert_eq!(
+            meta.addr()
+                .downcast::<gio::InetSocketAddress>()
+                .unwrap()
+                .port(),
+            sender.local_addr().unwrap().port()
+        );
+    }
+
+    // The burst is pushed as a full list followed by the remainder
+    assert_eq!(*list_lens.lock().unwrap(), [4, 2]);
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_multiple_addresses() {
+    init();
+
+    // Find two free ports
+    let ports = (0..2)
+        .map(|_| {
+            std::net::UdpSocket::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port()
+        })
+        .collect::<Vec<_>>();
+
+    let mut h = gst_check::Harness::new("ts-udpsrc");
+    {
+        let udpsrc = h.element().unwrap();
+        udpsrc.set_property(
+            "addresses",
+            format!("127.0.0.1:{},127.0.0.1:{}", ports[0], ports[1]),
+        );
+        udpsrc.set_property("context", "test-multiple-addresses");
+    }
+    h.play();
+
+    let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender.send_to(&[0; 100], ("127.0.0.1", ports[0])).unwrap();
+    sender.send_to(&[0; 160], ("127.0.0.1", ports[1])).unwrap();
+
+    let mut sizes = (0..2).map(|_| h.pull().unwrap().size()).collect::<Vec<_>>();
+    sizes.sort();
+    assert_eq!(sizes, [100, 160]);
+}
+
+#[test]
+fn test_retrieve_destination_address() {
+    use gio::prelude::*;
+    use gst_net::NetAddressMeta;
+
+    init();
+
+    // Find two free ports
+    let ports = (0..2)
+        .map(|_| {
+            std::net::UdpSocket::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port()
+        })
+        .collect::<Vec<_>>();
+
+    let mut h = gst_check::Harness::new("ts-udpsrc");
+    {
+        let udpsrc = h.element().unwrap();
+        udpsrc.set_property(
+            "addresses",
+            format!("127.0.0.1:{},127.0.0.1:{}", ports[0], ports[1]),
+        );
+        udpsrc.set_property("retrieve-sender-address", false);
+        udpsrc.set_property("retrieve-destination-address", true);
+        udpsrc.set_property("context", "test-retrieve-destination-address");
+    }
+    h.play();
+
+    let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    for (size, port) in [(100, ports[0]), (160, ports[1])] {
+        sender.send_to(&vec![0; size], ("127.0.0.1", port)).unwrap();
+
+        let buffer = h.pull().unwrap();
+        assert_eq!(buffer.size(), size);
+
+        let metas = buffer.iter_meta::<NetAddressMeta>().collect::<Vec<_>>();
+        assert_eq!(metas.len(), 1);
+        let daddr = metas[0]
+            .addr()
+            .downcast::<gio::InetSocketAddress>()
+            .unwrap();
+        assert_eq!(daddr.port(), port);
+        assert_eq!(daddr.address().to_str(), "127.0.0.1");
+    }
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_multiple_addresses_bind_errors() {
+    init();
+
+    let busy = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let busy_port = busy.local_addr().unwrap().port();
+
+    for ignore_bind_errors in [false, true] {
+        let h = gst_check::Harness::new("ts-udpsrc");
+        let udpsrc = h.element().unwrap();
+        // `busy` doesn't set SO_REUSEADDR nor SO_REUSEPORT so binding its port fails
+        udpsrc.set_property("addresses", format!("127.0.0.1:{busy_port},127.0.0.1:0"));
+        udpsrc.set_property("ignore-bind-errors", ignore_bind_errors);
+        udpsrc.set_property("context", "test-multiple-addresses-bind-errors");
+
+        assert_eq!(
+            udpsrc.set_state(gst::State::Ready).is_ok(),
+            ignore_bind_errors
+        );
+        udpsrc.set_state(gst::State::Null).unwrap();
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn test_reuse_port() {
+    init();
+
+    for reuse_port in [true, false] {
+        let h1 = gst_check::Harness::new("ts-udpsrc");
+        let udpsrc1 = h1.element().unwrap();
+        udpsrc1.set_property("address", "127.0.0.1");
+        udpsrc1.set_property("port", 0i32);
+        udpsrc1.set_property("reuse", false);
+        udpsrc1.set_property("reuse-port", reuse_port);
+        udpsrc1.set_property("context", "test-reuse-port");
+        udpsrc1.set_state(gst::State::Ready).unwrap();
+
+        let port = udpsrc1.property::<i32>("port");
+
+        let h2 = gst_check::Harness::new("ts-udpsrc");
+        let udpsrc2 = h2.element().unwrap();
+        udpsrc2.set_property("address", "127.0.0.1");
+        udpsrc2.set_property("port", port);
+        udpsrc2.set_property("reuse", false);
+        udpsrc2.set_property("reuse-port", reuse_port);
+        udpsrc2.set_property("context", "test-reuse-port");
+
+        assert_eq!(udpsrc2.set_state(gst::State::Ready).is_ok(), reuse_port);
+
+        udpsrc2.set_state(gst::State::Null).unwrap();
+        udpsrc1.set_state(gst::State::Null).unwrap();
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_buffer_size() {
+    use gio::prelude::*;
+
+    init();
+
+    let h = gst_check::Harness::new("ts-udpsrc");
+    let udpsrc = h.element().unwrap();
+    udpsrc.set_property("address", "127.0.0.1");
+    udpsrc.set_property("port", 0i32);
+    udpsrc.set_property("buffer-size", 32_768u32);
+    udpsrc.set_property("context", "test-buffer-size");
+    udpsrc.set_state(gst::State::Ready).unwrap();
+
+    let socket = udpsrc.property::<gio::Socket>("used-socket");
+    // Linux doubles the requested value
+    let rcvbuf = socket.option(libc::SOL_SOCKET, libc::SO_RCVBUF).unwrap();
+    assert_eq!(rcvbuf, 2 * 32_768);
+
+    udpsrc.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_ssm() {
+    use std::net::{IpAddr, SocketAddr};
+
+    init();
+
+    let cases: [(&str, &str, &[&str]); 2] = [
+        ("232.1.1.1", "127.0.0.1", &["127.0.0.2"]),
+        ("ff3e::8000:1", "::1", &[]),
+    ];
+
+    for (group, source, filtered_sources) in cases {
+        let group_addr = group.parse::<IpAddr>().unwrap();
+        let source_addr = source.parse::<IpAddr>().unwrap();
+
+        let Ok(sender) = std::net::UdpSocket::bind(SocketAddr::new(source_addr, 0)) else {
+            println!("Skipping SSM test for {group}: {source} unavailable");
+            continue;
+        };
+
+        let mut h = gst_check::Harness::new("ts-udpsrc");
+        let udpsrc = h.element().unwrap();
+        udpsrc.set_property("address", group);
+        udpsrc.set_property("port", 0i32);
+        udpsrc.set_property("multicast-iface", "lo");
+        udpsrc.set_property("multicast-source", source);
+        udpsrc.set_property("context", "test-ssm");
+
+        if udpsrc.set_state(gst::State::Ready).is_err() {
+            println!("Skipping SSM test for {group}: joining failed");
+            continue;
+        }
+        h.play();
+
+        let dest = SocketAddr::new(group_addr, udpsrc.property::<i32>("port") as u16);
+
+        // Packets from other sources are filtered out
+        for filtered_source in filtered_sources {
+            let filtered_source = filtered_source.parse::<IpAddr>().unwrap();
+            let other = std::net::UdpSocket::bind(SocketAddr::new(filtered_source, 0)).unwrap();
+            socket2::SockRef::from(&other)
+                .set_multicast_if_v4(&"127.0.0.1".parse().unwrap())
+                .unwrap();
+            other.send_to(&[0; 80], dest).unwrap();
+        }
+
+        let sock_ref = socket2::SockRef::from(&sender);
+        match group_addr {
+            IpAddr::V4(_) => sock_ref
+                .set_multicast_if_v4(&"127.0.0.1".parse().unwrap())
+                .unwrap(),
+            IpAddr::V6(_) => sock_ref.set_multicast_if_v6(1).unwrap(),
+        }
+        if let Err(err) = sender.send_to(&[0; 160], dest) {
+            println!("Skipping SSM test for {group}: {err}");
+            continue;
+        }
+
+        let buffer = h.pull().unwrap();
+        assert_eq!(buffer.size(), 160);
+    }
+}
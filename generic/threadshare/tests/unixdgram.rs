@@ -0,0 +1,85 @@
+// Copyright (C) 2026 agent <agent@local>
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Library General Public
+// License as published by the Free Software Foundation; either
+// version 2 of the License, or (at your option) any later version.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Library General Public License for more details.
+//
+// You should have received a copy of the GNU Library General Public
+// License along with this library; if not, write to the
+// Free Software Foundation, Inc., 51 Franklin Street, Suite 500,
+// Boston, MA 02110-1335, USA.
+//
+// SPDX-License-Identifier: LGPL-2.1-or-later
+
+#![cfg(unix)]
+
+use gst::prelude::*;
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstthreadshare::plugin_register_static().expect("gstthreadshare unixdgram test");
+    });
+}
+
+fn check_roundtrip(path: &str, is_abstract: bool) {
+    let mut src_h = gst_check::Harness::new("ts-unixdgramsrc");
+    let unixdgramsrc = src_h.element().unwrap();
+    unixdgramsrc.set_property("context", "unixdgram::test");
+    unixdgramsrc.set_property("path", path);
+    unixdgramsrc.set_property("abstract", is_abstract);
+    unixdgramsrc.set_property("caps", gst::Caps::builder("foo/bar").build());
+    src_h.play();
+
+    let mut sink_h = gst_check::Harness::new("ts-unixdgramsink");
+    let unixdgramsink = sink_h.element().unwrap();
+    unixdgramsink.set_property("context", "unixdgram::test");
+    unixdgramsink.set_property("path", path);
+    unixdgramsink.set_property("abstract", is_abstract);
+    sink_h.play();
+    sink_h.set_src_caps_str("foo/bar");
+
+    for i in 0..3u8 {
+        assert_eq!(
+            sink_h.push(gst::Buffer::from_slice([i; 160])),
+            Ok(gst::FlowSuccess::Ok)
+        );
+
+        let buffer = src_h.pull().unwrap();
+        assert_eq!(buffer.map_readable().unwrap().as_slice(), &[i; 160]);
+        assert!(buffer.dts().is_some());
+    }
+}
+
+#[test]
+fn test_path() {
+    init();
+
+    let path = std::env::temp_dir().join(format!("ts-unixdgram-{}.sock", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    check_roundtrip(path, false);
+
+    // The harnesses are gone, the socket file must have been cleaned up
+    assert!(!std::path::Path::new(path).exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_abstract() {
+    init();
+
+    check_roundtrip(
+        &format!("ts-unixdgram-abstract-{}", std::process::id()),
+        true,
+    );
+}